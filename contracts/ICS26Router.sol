@@ -291,6 +291,19 @@ contract ICS26Router is
         emit TimeoutPacket(msg_.packet.sourceClient, msg_.packet.sequence, msg_.packet);
     }
 
+    /// @inheritdoc IICS26Router
+    function commitUpgradedClient(
+        uint64 slot,
+        bytes32 upgradedClientStateHash,
+        bytes32 upgradedConsensusStateHash
+    )
+        external
+        onlyAdmin
+    {
+        commitUpgradedStates(slot, upgradedClientStateHash, upgradedConsensusStateHash);
+        emit UpgradedClientCommitted(slot, upgradedClientStateHash, upgradedConsensusStateHash);
+    }
+
     /// @dev See {AccessControlUpgradeable-grantRole}. Reverts for `DEFAULT_ADMIN_ROLE`.
     /// @inheritdoc AccessControlUpgradeable
     function grantRole(bytes32 role, address account) public override(AccessControlUpgradeable) {
@@ -25,6 +25,13 @@ interface IICS24HostErrors {
     /// @notice No acknowledgements to process
     error NoAcknowledgements();
 
+    /// @notice Upgraded state already committed
+    /// @param path commitment path
+    error IBCUpgradedStateAlreadyCommitted(bytes path);
+
+    /// @notice Upgraded state commitment is empty
+    error IBCEmptyUpgradedStateCommitment();
+
     /// @notice IBC packet receipt mismatch
     /// @param expected stored packet receipt
     /// @param actual actual packet receipt
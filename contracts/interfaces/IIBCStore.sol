@@ -8,4 +8,9 @@ interface IIBCStore {
     /// @param hashedPath The hashed path to get the commitment for.
     /// @return The commitment for the given path.
     function getCommitment(bytes32 hashedPath) external view returns (bytes32);
+
+    /// @notice Gets the storage slot of the commitments mapping.
+    /// @dev The light clients of counterparty chains prove the commitments against this slot.
+    /// @return The storage slot of the commitments mapping.
+    function getCommitmentStorageSlot() external pure returns (bytes32);
 }
@@ -51,6 +51,18 @@ interface IICS26Router {
     /// @param msg The message for timing out packets
     function timeoutPacket(IICS26RouterMsgs.MsgTimeoutPacket calldata msg) external;
 
+    /// @notice Commits the states the counterparty light clients of this chain are upgraded to
+    /// @dev Only callable by the admins. The states are committed once per slot, under the upgrade paths of the slot.
+    /// @param slot The slot the light clients are upgraded to
+    /// @param upgradedClientStateHash The hash of the upgraded client state
+    /// @param upgradedConsensusStateHash The hash of the upgraded consensus state
+    function commitUpgradedClient(
+        uint64 slot,
+        bytes32 upgradedClientStateHash,
+        bytes32 upgradedConsensusStateHash
+    )
+        external;
+
     /// @notice Initializes the contract instead of a constructor
     /// @dev Meant to be called only once from the proxy
     /// @param timelockedAdmin The address of the timelocked admin for IBCUUPSUpgradeable
@@ -93,4 +105,11 @@ interface IICS26Router {
     );
     /// @notice Emitted when a redundant relay occurs
     event Noop();
+    /// @notice Emitted when the states the counterparty light clients are upgraded to are committed
+    /// @param slot The slot the light clients are upgraded to
+    /// @param upgradedClientStateHash The hash of the upgraded client state
+    /// @param upgradedConsensusStateHash The hash of the upgraded consensus state
+    event UpgradedClientCommitted(
+        uint64 indexed slot, bytes32 upgradedClientStateHash, bytes32 upgradedConsensusStateHash
+    );
 }
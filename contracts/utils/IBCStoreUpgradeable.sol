@@ -32,6 +32,12 @@ abstract contract IBCStoreUpgradeable is IIBCStore, IICS24HostErrors, Initializa
         return _getIBCStoreStorage().commitments[hashedPath];
     }
 
+    /// @inheritdoc IIBCStore
+    function getCommitmentStorageSlot() public pure returns (bytes32) {
+        // The commitments mapping is the first field of the storage struct
+        return IBCSTORE_STORAGE_SLOT;
+    }
+
     /// @dev Returns the next sequence send for the given client
     /// @param clientId The client ID
     /// @return The next sequence send for the given client
@@ -114,6 +120,38 @@ abstract contract IBCStoreUpgradeable is IIBCStore, IICS24HostErrors, Initializa
         $.commitments[path] = commitment;
     }
 
+    /// @notice Commits the hashes of the states the counterparty light clients of this chain are upgraded to
+    /// @dev The light clients prove the commitments before replacing their state with the upgraded one
+    /// @param slot The slot the light clients are upgraded to
+    /// @param upgradedClientStateHash The hash of the upgraded client state
+    /// @param upgradedConsensusStateHash The hash of the upgraded consensus state
+    function commitUpgradedStates(
+        uint64 slot,
+        bytes32 upgradedClientStateHash,
+        bytes32 upgradedConsensusStateHash
+    )
+        internal
+    {
+        require(
+            upgradedClientStateHash != 0 && upgradedConsensusStateHash != 0, IBCEmptyUpgradedStateCommitment()
+        );
+        IBCStoreStorage storage $ = _getIBCStoreStorage();
+
+        bytes32 clientStateKey = ICS24Host.upgradedClientStateKey(slot);
+        bytes32 consensusStateKey = ICS24Host.upgradedConsensusStateKey(slot);
+        require(
+            $.commitments[clientStateKey] == 0,
+            IBCUpgradedStateAlreadyCommitted(ICS24Host.upgradedClientStatePath(slot))
+        );
+        require(
+            $.commitments[consensusStateKey] == 0,
+            IBCUpgradedStateAlreadyCommitted(ICS24Host.upgradedConsensusStatePath(slot))
+        );
+
+        $.commitments[clientStateKey] = upgradedClientStateHash;
+        $.commitments[consensusStateKey] = upgradedConsensusStateHash;
+    }
+
     /// @notice Returns the storage of the IBCStore contract
     function _getIBCStoreStorage() private pure returns (IBCStoreStorage storage $) {
         // solhint-disable-next-line no-inline-assembly
@@ -3,6 +3,7 @@ pragma solidity ^0.8.28;
 
 import { IICS26RouterMsgs } from "../msgs/IICS26RouterMsgs.sol";
 import { IICS24HostErrors } from "../errors/IICS24HostErrors.sol";
+import { Strings } from "@openzeppelin-contracts/utils/Strings.sol";
 
 // @title ICS24 Host Path Generators
 // @notice ICS24Host is a library that provides commitment path generators for ICS24 host requirements.
@@ -63,6 +64,22 @@ library ICS24Host {
         return abi.encodePacked(clientId, uint8(2), uint64ToBigEndian(sequence));
     }
 
+    /// @notice Generator for the path of an upgraded client state commitment
+    /// @dev Mirrors the upgrade paths of ibc-go, keyed by the slot the counterparty light clients are upgraded to
+    /// @param slot The slot the light clients are upgraded to
+    /// @return The full path of the upgraded client state commitment
+    function upgradedClientStatePath(uint64 slot) internal pure returns (bytes memory) {
+        return abi.encodePacked("upgradedIBCState/", Strings.toString(slot), "/upgradedClient");
+    }
+
+    /// @notice Generator for the path of an upgraded consensus state commitment
+    /// @dev Mirrors the upgrade paths of ibc-go, keyed by the slot the counterparty light clients are upgraded to
+    /// @param slot The slot the light clients are upgraded to
+    /// @return The full path of the upgraded consensus state commitment
+    function upgradedConsensusStatePath(uint64 slot) internal pure returns (bytes memory) {
+        return abi.encodePacked("upgradedIBCState/", Strings.toString(slot), "/upgradedConsState");
+    }
+
     // Key generators for Commitment mapping
 
     /// @notice Generator for the key of a packet commitment
@@ -103,6 +120,20 @@ library ICS24Host {
         return keccak256(packetReceiptCommitmentPathCalldata(clientId, sequence));
     }
 
+    /// @notice Generator for the key of an upgraded client state commitment
+    /// @param slot The slot the light clients are upgraded to
+    /// @return The keccak256 hash of the upgraded client state commitment path
+    function upgradedClientStateKey(uint64 slot) internal pure returns (bytes32) {
+        return keccak256(upgradedClientStatePath(slot));
+    }
+
+    /// @notice Generator for the key of an upgraded consensus state commitment
+    /// @param slot The slot the light clients are upgraded to
+    /// @return The keccak256 hash of the upgraded consensus state commitment path
+    function upgradedConsensusStateKey(uint64 slot) internal pure returns (bytes32) {
+        return keccak256(upgradedConsensusStatePath(slot));
+    }
+
     /// @notice Get the packet commitment bytes.
     /// @dev CommitPacket returns the V2 packet commitment bytes. The commitment consists of:
     /// @dev sha256_hash(0x02 + sha256_hash(destinationClient) + sha256_hash(timeout) + sha256_hash(payload)) for a
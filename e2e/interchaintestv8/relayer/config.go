@@ -119,4 +119,13 @@ type ethToCosmosConfig struct {
 	EthBeaconApiUrl string `json:"eth_beacon_api_url"`
 	SignerAddress   string `json:"signer_address"`
 	Mock            bool   `json:"mock"`
+
+	// ConfirmationPolicy is left to the relayer default (finalized) if nil
+	ConfirmationPolicy *EthConfirmationPolicy `json:"confirmation_policy,omitempty"`
+}
+
+// EthConfirmationPolicy represents the confirmation policy for events sourced from Ethereum
+type EthConfirmationPolicy struct {
+	Type          string `json:"type"`
+	Confirmations uint64 `json:"confirmations"`
 }
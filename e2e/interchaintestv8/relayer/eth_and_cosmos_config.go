@@ -25,6 +25,12 @@ type EthCosmosConfigInfo struct {
 func CreateEthCosmosModules(
 	configInfo EthCosmosConfigInfo,
 ) []ModuleConfig {
+	var ethConfirmationPolicy *EthConfirmationPolicy
+	if configInfo.MockWasmClient {
+		// The mock client does not wait for finality, so events are relayed as soon as they are included
+		ethConfirmationPolicy = &EthConfirmationPolicy{Type: "confirmations", Confirmations: 0}
+	}
+
 	return []ModuleConfig{
 		{
 			Name:     ModuleEthToCosmos,
@@ -37,6 +43,8 @@ func CreateEthCosmosModules(
 				EthBeaconApiUrl: configInfo.BeaconAPI,
 				SignerAddress:   configInfo.SignerAddress,
 				Mock:            configInfo.MockWasmClient,
+
+				ConfirmationPolicy: ethConfirmationPolicy,
 			},
 		},
 		{
@@ -86,6 +86,8 @@ const (
 	SP1ICS07FixturesDir = "test/sp1-ics07/fixtures"
 	// WasmFixturesDir is the directory where the Rust fixtures are stored.
 	WasmFixturesDir = "packages/ethereum/light-client/src/test_utils/fixtures"
+	// WasmFixturesVersion is the schema version of the Rust fixtures, it must match `STEPS_FIXTURE_VERSION`.
+	WasmFixturesVersion = 1
 	// RelayerConfigFilePath is the path to generate the relayer config file.
 	RelayerConfigFilePath = "programs/relayer/config.json"
 	// E2EDeployScriptPath is the path to the E2E deploy script.
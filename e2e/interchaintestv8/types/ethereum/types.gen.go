@@ -17,6 +17,13 @@ type GeneratedTypes struct {
 type ClientState struct {
 	// The chain ID
 	ChainID uint64 `json:"chain_id"`
+	// Whether the client accepts zlib compressed client messages
+	CompressedClientMessages *bool `json:"compressed_client_messages,omitempty"`
+	// The number of slots below the latest slot the consensus states are kept for, if the older
+	// consensus states are pruned
+	ConsensusStateRetention *uint64 `json:"consensus_state_retention,omitempty"`
+	// The consensus states persisted when a header batch is applied
+	ConsensusStateWrites *ConsensusStateWrites `json:"consensus_state_writes,omitempty"`
 	// The number of epochs per sync committee period
 	EpochsPerSyncCommitteePeriod uint64 `json:"epochs_per_sync_committee_period"`
 	// The fork parameters
@@ -37,12 +44,20 @@ type ClientState struct {
 	LatestExecutionBlockNumber uint64 `json:"latest_execution_block_number"`
 	// The latest slot of this client
 	LatestSlot uint64 `json:"latest_slot"`
+	// The minimum number of slots an update must advance the client by within a sync committee
+	// period, if any
+	MinSlotAdvance *uint64 `json:"min_slot_advance,omitempty"`
 	// The minimum number of participants in the sync committee
 	MinSyncCommitteeParticipants uint64 `json:"min_sync_committee_participants"`
+	// The revision number of the heights of this client
+	RevisionNumber *uint64 `json:"revision_number,omitempty"`
 	// The slot duration in seconds
 	SecondsPerSlot uint64 `json:"seconds_per_slot"`
 	// The number of slots per epoch
 	SlotsPerEpoch uint64 `json:"slots_per_epoch"`
+	// The number of seconds after the timestamp of the latest consensus state the client expires
+	// at, if it expires
+	TrustingPeriod *uint64 `json:"trusting_period,omitempty"`
 }
 
 // The fork parameters
@@ -285,4 +300,14 @@ type Step struct {
 type StepsFixture struct {
 	// steps is a list of light client operations
 	Steps []Step `json:"steps"`
+	// version is the schema version of the fixture, fixtures without a version are version 0
+	Version *uint64 `json:"version,omitempty"`
 }
+
+// The consensus states persisted when a header batch is applied
+type ConsensusStateWrites string
+
+const (
+	All              ConsensusStateWrites = "all"
+	PeriodBoundaries ConsensusStateWrites = "period_boundaries"
+)
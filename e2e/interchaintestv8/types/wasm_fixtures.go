@@ -115,6 +115,8 @@ func (g *WasmFixtureGenerator) writeFixtures(fixtureName string) error {
 	filePath := fmt.Sprintf("%s/%s.json", testvalues.WasmFixturesDir, fixtureName)
 
 	fmt.Printf("Writing %d fixtures to %s\n", len(g.fixture.Steps), filePath)
+	version := uint64(testvalues.WasmFixturesVersion)
+	g.fixture.Version = &version
 	fixturesBz, err := json.MarshalIndent(g.fixture, "", " ")
 	if err != nil {
 		return err
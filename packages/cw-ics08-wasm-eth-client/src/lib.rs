@@ -0,0 +1,14 @@
+#![doc = include_str!("../README.md")]
+#![deny(
+    clippy::nursery,
+    clippy::pedantic,
+    warnings,
+    missing_docs,
+    unused_crate_dependencies
+)]
+
+pub mod msg;
+pub mod querier;
+
+pub use msg::Height;
+pub use querier::EthLightClient;
@@ -0,0 +1,136 @@
+//! The queries of the Ethereum light client for other contracts
+//!
+//! The messages and results share their JSON encoding with the messages of the light client
+//! contract, but only carry the fields other contracts need.
+#![allow(clippy::module_name_repetitions)]
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Binary;
+
+/// The status of an active client
+pub const STATUS_ACTIVE: &str = "Active";
+
+/// The query messages of the light client for other contracts
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// The message to get the status
+    #[returns[StatusResult]]
+    Status(StatusMsg),
+
+    /// The message to get the latest height
+    #[returns[LatestHeightResult]]
+    LatestHeight(LatestHeightMsg),
+
+    /// The message to verify membership against a stored consensus state
+    #[returns[()]]
+    VerifyMembership(VerifyMembershipMsg),
+
+    /// The message to verify non-membership against a stored consensus state
+    #[returns[()]]
+    VerifyNonMembership(VerifyNonMembershipMsg),
+
+    /// The message to verify membership against a provided consensus state
+    #[returns[()]]
+    VerifyMembershipAt(VerifyMembershipAtMsg),
+}
+
+/// The status query message
+#[cw_serde]
+pub struct StatusMsg {}
+
+/// The latest height query message
+#[cw_serde]
+pub struct LatestHeightMsg {}
+
+/// Verify membership message
+#[cw_serde]
+pub struct VerifyMembershipMsg {
+    /// The proof height
+    pub height: Height,
+    /// The proof bytes
+    pub proof: Binary,
+    /// The path to the value
+    pub merkle_path: MerklePath,
+    /// The value to verify
+    pub value: Binary,
+}
+
+/// Verify non-membership message
+#[cw_serde]
+pub struct VerifyNonMembershipMsg {
+    /// The proof height
+    pub height: Height,
+    /// The proof bytes
+    pub proof: Binary,
+    /// The path to the empty value
+    pub merkle_path: MerklePath,
+}
+
+/// The message to verify membership against a provided consensus state
+#[cw_serde]
+pub struct VerifyMembershipAtMsg {
+    /// The consensus state to verify against, encoded like the consensus state of the client
+    pub consensus_state: Binary,
+    /// The proof bytes
+    pub proof: Binary,
+    /// The path to the value
+    pub merkle_path: MerklePath,
+    /// The value to verify
+    pub value: Binary,
+}
+
+/// Height of the ethereum chain
+#[cw_serde]
+#[derive(Copy, Default, Eq, PartialOrd, Ord)]
+pub struct Height {
+    /// The revision that the client is currently on
+    #[serde(default)]
+    pub revision_number: u64,
+    /// The execution height of ethereum chain
+    #[serde(default)]
+    pub revision_height: u64,
+}
+
+/// The merkle path
+#[cw_serde]
+pub struct MerklePath {
+    /// The key path
+    pub key_path: Vec<Binary>,
+}
+
+/// The response to the status query
+#[cw_serde]
+pub struct StatusResult {
+    /// The status of the client
+    pub status: String,
+    /// The latest slot of the client
+    pub latest_slot: u64,
+    /// The timestamp of the latest consensus state (in nanoseconds)
+    pub latest_timestamp: u64,
+    /// The latest slot of the client when it was frozen, if frozen
+    pub frozen_at: Option<u64>,
+    /// The timestamp the client expired at (in nanoseconds), if expired
+    pub expired_at: Option<u64>,
+    /// The height of the earliest stored consensus state, the lowest height packets can be proven
+    /// at
+    #[serde(default)]
+    pub earliest_height: Option<Height>,
+}
+
+impl StatusResult {
+    /// Returns whether the client is active
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.status == STATUS_ACTIVE
+    }
+}
+
+/// The response to the latest height query
+#[cw_serde]
+pub struct LatestHeightResult {
+    /// The latest height of the client
+    pub height: Height,
+    /// The timestamp of the latest consensus state (in nanoseconds)
+    pub timestamp: u64,
+}
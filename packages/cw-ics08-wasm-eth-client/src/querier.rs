@@ -0,0 +1,103 @@
+//! This module defines the [`EthLightClient`] wrapper to query the light client from a contract
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, CustomQuery, QuerierWrapper, StdResult};
+
+use crate::msg::{
+    Height, LatestHeightMsg, LatestHeightResult, MerklePath, QueryMsg, StatusMsg, StatusResult,
+    VerifyMembershipAtMsg, VerifyMembershipMsg, VerifyNonMembershipMsg,
+};
+
+/// The address of an Ethereum light client contract, with helpers to query it
+#[cw_serde]
+pub struct EthLightClient(pub Addr);
+
+impl EthLightClient {
+    /// Returns the address of the light client
+    #[must_use]
+    pub const fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    /// Gets the status of the light client
+    /// # Errors
+    /// Returns an error if the query fails
+    pub fn status<C: CustomQuery>(&self, querier: &QuerierWrapper<C>) -> StdResult<StatusResult> {
+        querier.query_wasm_smart(&self.0, &QueryMsg::Status(StatusMsg {}))
+    }
+
+    /// Gets the latest height of the light client and the timestamp of its consensus state
+    /// # Errors
+    /// Returns an error if the query fails
+    pub fn latest_height<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+    ) -> StdResult<LatestHeightResult> {
+        querier.query_wasm_smart(&self.0, &QueryMsg::LatestHeight(LatestHeightMsg {}))
+    }
+
+    /// Verifies the membership of a value at a height of the light client
+    /// # Errors
+    /// Returns an error if the proof is invalid, or if there is no consensus state at the height
+    pub fn verify_membership<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        height: Height,
+        proof: Binary,
+        key_path: Vec<Binary>,
+        value: Binary,
+    ) -> StdResult<()> {
+        querier.query_wasm_smart(
+            &self.0,
+            &QueryMsg::VerifyMembership(VerifyMembershipMsg {
+                height,
+                proof,
+                merkle_path: MerklePath { key_path },
+                value,
+            }),
+        )
+    }
+
+    /// Verifies the non-membership of a path at a height of the light client
+    /// # Errors
+    /// Returns an error if the proof is invalid, or if there is no consensus state at the height
+    pub fn verify_non_membership<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        height: Height,
+        proof: Binary,
+        key_path: Vec<Binary>,
+    ) -> StdResult<()> {
+        querier.query_wasm_smart(
+            &self.0,
+            &QueryMsg::VerifyNonMembership(VerifyNonMembershipMsg {
+                height,
+                proof,
+                merkle_path: MerklePath { key_path },
+            }),
+        )
+    }
+
+    /// Verifies the membership of a value against a consensus state trusted by the caller, which
+    /// is not stored by the light client
+    /// # Errors
+    /// Returns an error if the consensus state cannot be decoded, or if the proof is invalid
+    pub fn verify_membership_at<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        consensus_state: Binary,
+        proof: Binary,
+        key_path: Vec<Binary>,
+        value: Binary,
+    ) -> StdResult<()> {
+        querier.query_wasm_smart(
+            &self.0,
+            &QueryMsg::VerifyMembershipAt(VerifyMembershipAtMsg {
+                consensus_state,
+                proof,
+                merkle_path: MerklePath { key_path },
+                value,
+            }),
+        )
+    }
+}
@@ -7,7 +7,7 @@ use ethereum_types::consensus::{
     light_client_header::{LightClientFinalityUpdate, LightClientUpdate},
     spec::Spec,
 };
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, Response as HttpResponse, StatusCode};
 use serde::de::DeserializeOwned;
 use tracing::debug;
 
@@ -15,6 +15,9 @@ use super::{
     error::{BeaconApiClientError, InternalServerError, NotFoundError},
     response::{BeaconBlockRoot, Response, Version},
 };
+use crate::circuit_breaker::{
+    endpoint_label, CircuitBreaker, CircuitBreakerConfig, TransitionHook,
+};
 
 const SPEC_PATH: &str = "/eth/v1/config/spec";
 const GENESIS_PATH: &str = "/eth/v1/beacon/genesis";
@@ -24,11 +27,26 @@ const LIGHT_CLIENT_BOOTSTRAP_PATH: &str = "/eth/v1/beacon/light_client/bootstrap
 const LIGHT_CLIENT_FINALITY_UPDATE_PATH: &str = "/eth/v1/beacon/light_client/finality_update";
 const LIGHT_CLIENT_UPDATES_PATH: &str = "/eth/v1/beacon/light_client/updates";
 
+/// The default maximum size of a Beacon API response body in bytes
+pub const DEFAULT_MAX_RESPONSE_SIZE: usize = 64 * 1024 * 1024;
+
 /// The api client for interacting with the Beacon API
+///
+/// Requests go to the primary endpoint, and to the fallback endpoints in order when the
+/// previous ones fail. With a circuit breaker, an endpoint that keeps failing is skipped until
+/// its circuit lets a probe through.
 #[allow(clippy::module_name_repetitions)]
 pub struct BeaconApiClient {
+    endpoints: Vec<Endpoint>,
+    max_response_size: usize,
+    circuit_breaker: Option<(CircuitBreakerConfig, Option<TransitionHook>)>,
+}
+
+/// A Beacon API endpoint
+struct Endpoint {
     client: Client,
     base_url: String,
+    breaker: Option<CircuitBreaker>,
 }
 
 impl BeaconApiClient {
@@ -36,9 +54,57 @@ impl BeaconApiClient {
     #[must_use]
     pub fn new(base_url: String) -> Self {
         Self {
-            client: Client::new(),
+            endpoints: vec![Endpoint {
+                client: Client::new(),
+                base_url,
+                breaker: None,
+            }],
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            circuit_breaker: None,
+        }
+    }
+
+    /// Sets the HTTP client used for the requests to the primary endpoint, e.g. to configure a
+    /// proxy or authentication
+    #[must_use]
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.endpoints[0].client = client;
+        self
+    }
+
+    /// Adds a fallback endpoint, requested with its own HTTP client when the previous endpoints
+    /// fail
+    #[must_use]
+    pub fn with_fallback(mut self, base_url: String, client: Client) -> Self {
+        let breaker = new_breaker(self.circuit_breaker, &base_url);
+        self.endpoints.push(Endpoint {
+            client,
             base_url,
+            breaker,
+        });
+        self
+    }
+
+    /// Guards every endpoint with a circuit breaker, whose state changes are reported to the
+    /// optional hook
+    #[must_use]
+    pub fn with_circuit_breaker(
+        mut self,
+        config: CircuitBreakerConfig,
+        on_transition: Option<TransitionHook>,
+    ) -> Self {
+        self.circuit_breaker = Some((config, on_transition));
+        for endpoint in &mut self.endpoints {
+            endpoint.breaker = new_breaker(self.circuit_breaker, &endpoint.base_url);
         }
+        self
+    }
+
+    /// Sets the maximum size of a response body in bytes, larger responses are rejected
+    #[must_use]
+    pub const fn with_max_response_size(mut self, max_response_size: usize) -> Self {
+        self.max_response_size = max_response_size;
+        self
     }
 
     /// Fetches the Beacon spec
@@ -113,32 +179,114 @@ impl BeaconApiClient {
     }
 
     // Helper functions
+    /// Requests the endpoints in order until one of them answers, skipping the endpoints whose
+    /// circuit is open
     #[tracing::instrument(skip_all)]
     async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, BeaconApiClientError> {
-        let url = format!("{}{}", self.base_url, path);
+        let mut last_err = None;
+        for endpoint in &self.endpoints {
+            if endpoint
+                .breaker
+                .as_ref()
+                .is_some_and(|breaker| !breaker.try_acquire())
+            {
+                continue;
+            }
+
+            match self.get_json_from(endpoint, path).await {
+                Err(err) if err.is_endpoint_failure() => {
+                    if let Some(breaker) = &endpoint.breaker {
+                        breaker.record_failure();
+                    }
+                    tracing::warn!(
+                        endpoint = %endpoint_label(&endpoint.base_url),
+                        %err,
+                        "Beacon API endpoint failed"
+                    );
+                    last_err = Some(err);
+                }
+                res => {
+                    if let Some(breaker) = &endpoint.breaker {
+                        breaker.record_success();
+                    }
+                    return res;
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or(BeaconApiClientError::NoAvailableEndpoint))
+    }
+
+    async fn get_json_from<T: DeserializeOwned>(
+        &self,
+        endpoint: &Endpoint,
+        path: &str,
+    ) -> Result<T, BeaconApiClientError> {
+        let url = format!("{}{}", endpoint.base_url, path);
 
         debug!(%url, "get_json");
 
-        let res = self.client.get(url).send().await?;
+        let res = endpoint.client.get(url).send().await?;
 
         match res.status() {
             StatusCode::OK => {
-                let bytes = res.bytes().await?;
+                let body = self.read_body(res).await?;
 
-                debug!(response = %String::from_utf8_lossy(&bytes), "get_json");
+                debug!(response_size = body.len(), "get_json");
 
-                Ok(serde_json::from_slice(&bytes).map_err(BeaconApiClientError::Json)?)
+                Ok(serde_json::from_slice(&body).map_err(BeaconApiClientError::Json)?)
             }
-            StatusCode::NOT_FOUND => Err(BeaconApiClientError::NotFound(
-                res.json::<NotFoundError>().await?,
-            )),
+            StatusCode::NOT_FOUND => Err(BeaconApiClientError::NotFound(serde_json::from_slice(
+                &self.read_body(res).await?,
+            )?)),
             StatusCode::INTERNAL_SERVER_ERROR => Err(BeaconApiClientError::Internal(
-                res.json::<InternalServerError>().await?,
+                serde_json::from_slice(&self.read_body(res).await?)?,
             )),
             code => Err(BeaconApiClientError::Other {
                 code,
-                text: res.text().await?,
+                text: String::from_utf8_lossy(&self.read_body(res).await?).into_owned(),
             }),
         }
     }
+
+    /// Reads the response body chunk by chunk, rejecting it as soon as it exceeds the maximum
+    /// response size so that misbehaving endpoints cannot exhaust the memory
+    async fn read_body(&self, mut res: HttpResponse) -> Result<Vec<u8>, BeaconApiClientError> {
+        let too_large = || BeaconApiClientError::ResponseTooLarge {
+            max_size: self.max_response_size,
+        };
+
+        let content_length = res
+            .content_length()
+            .map(usize::try_from)
+            .transpose()
+            .map_err(|_| too_large())?;
+        if content_length.is_some_and(|len| len > self.max_response_size) {
+            return Err(too_large());
+        }
+
+        let mut body = Vec::with_capacity(content_length.unwrap_or_default());
+        while let Some(chunk) = res.chunk().await? {
+            if body.len() + chunk.len() > self.max_response_size {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+}
+
+/// Creates the circuit breaker of an endpoint, if circuit breaking is enabled
+fn new_breaker(
+    circuit_breaker: Option<(CircuitBreakerConfig, Option<TransitionHook>)>,
+    base_url: &str,
+) -> Option<CircuitBreaker> {
+    circuit_breaker.map(|(config, on_transition)| {
+        let breaker = CircuitBreaker::new(endpoint_label(base_url), config);
+        match on_transition {
+            Some(on_transition) => breaker.with_transition_hook(on_transition),
+            None => breaker,
+        }
+    })
 }
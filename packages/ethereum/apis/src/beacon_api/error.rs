@@ -19,8 +19,43 @@ pub enum BeaconApiClientError {
     #[error("internal error: {0}")]
     Internal(#[from] InternalServerError),
 
+    #[error("response body exceeds the maximum size of {max_size} bytes")]
+    ResponseTooLarge { max_size: usize },
+
+    #[error("no beacon block found for execution block {0}")]
+    ExecutionBlockNotFound(u64),
+
     #[error("unknown error ({code}): {text}")]
     Other { code: StatusCode, text: String },
+
+    #[error("no beacon api endpoint available, all circuits are open")]
+    NoAvailableEndpoint,
+}
+
+impl BeaconApiClientError {
+    /// Returns true if the beacon node rejected the request because of rate limiting.
+    #[must_use]
+    pub fn is_rate_limited(&self) -> bool {
+        match self {
+            Self::Http(err) => err.status() == Some(StatusCode::TOO_MANY_REQUESTS),
+            Self::Other { code, .. } => *code == StatusCode::TOO_MANY_REQUESTS,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the error is a failure of the endpoint rather than an answer to the
+    /// request, i.e. a transport error, a timeout, a server error or rate limiting, after which
+    /// the request is retried on the next endpoint.
+    #[must_use]
+    pub fn is_endpoint_failure(&self) -> bool {
+        match self {
+            Self::Http(_) | Self::Internal(_) => true,
+            Self::Other { code, .. } => {
+                code.is_server_error() || *code == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
 }
 
 /// The not found error structure returned by the Beacon API.
@@ -3,3 +3,4 @@
 pub mod client;
 pub mod error;
 pub mod response;
+pub mod slot_resolver;
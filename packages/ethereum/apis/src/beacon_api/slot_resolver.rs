@@ -0,0 +1,144 @@
+//! This module defines [`SlotResolver`] which translates execution block numbers to beacon slots.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use tracing::debug;
+
+use super::{client::BeaconApiClient, error::BeaconApiClientError};
+
+/// Resolves the beacon slot of an execution block by binary searching the beacon blocks.
+///
+/// Every beacon block since the merge contains at most one execution payload, and the execution
+/// block numbers increase with the slots, so the distance between two slots is at least the
+/// distance between their execution blocks. Resolved and visited blocks are cached and used to
+/// narrow down later searches.
+#[allow(clippy::module_name_repetitions)]
+pub struct SlotResolver {
+    beacon_api_client: BeaconApiClient,
+    /// Cached execution block number to slot mappings
+    cache: Mutex<BTreeMap<u64, u64>>,
+}
+
+impl SlotResolver {
+    /// Create a new `SlotResolver`
+    #[must_use]
+    pub const fn new(beacon_api_client: BeaconApiClient) -> Self {
+        Self {
+            beacon_api_client,
+            cache: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the underlying beacon api client
+    #[must_use]
+    pub const fn beacon_api_client(&self) -> &BeaconApiClient {
+        &self.beacon_api_client
+    }
+
+    /// Returns the beacon slot whose execution payload has the given `block_number`
+    /// # Errors
+    /// Returns an error if a request fails, or if no beacon block contains the execution block
+    /// # Panics
+    /// Panics if the cache lock is poisoned
+    pub async fn slot_at_execution_block(
+        &self,
+        block_number: u64,
+    ) -> Result<u64, BeaconApiClientError> {
+        let (mut lower_bound, mut upper_bound) = {
+            let cache = self.cache.lock().unwrap();
+            if let Some(slot) = cache.get(&block_number) {
+                return Ok(*slot);
+            }
+
+            // A block `n` blocks after a known block is at least `n` slots after it, and vice versa
+            let lower_bound = cache
+                .range(..block_number)
+                .next_back()
+                .map(|(known_block, known_slot)| known_slot + (block_number - known_block));
+            let upper_bound =
+                cache
+                    .range(block_number..)
+                    .next()
+                    .map(|(known_block, known_slot)| {
+                        known_slot.saturating_sub(known_block - block_number)
+                    });
+            (lower_bound.unwrap_or_default(), upper_bound)
+        };
+
+        if upper_bound.is_none() {
+            let head = self.beacon_api_client.beacon_block("head").await?.message;
+            let head_block_number = head.body.execution_payload.block_number;
+            self.cache_slot(head_block_number, head.slot);
+            if head_block_number < block_number {
+                return Err(BeaconApiClientError::ExecutionBlockNotFound(block_number));
+            }
+            upper_bound = Some(head.slot.saturating_sub(head_block_number - block_number));
+        }
+        let mut upper_bound = upper_bound.unwrap_or_default();
+
+        debug!(
+            block_number,
+            lower_bound, upper_bound, "resolving execution block slot"
+        );
+
+        while lower_bound <= upper_bound {
+            let mid = lower_bound + (upper_bound - lower_bound) / 2;
+            let Some((slot, found_block_number)) =
+                self.first_block_in_range(mid, upper_bound).await?
+            else {
+                // All the slots from `mid` are empty
+                upper_bound = match mid.checked_sub(1) {
+                    Some(slot) => slot,
+                    None => break,
+                };
+                continue;
+            };
+
+            match found_block_number.cmp(&block_number) {
+                std::cmp::Ordering::Equal => return Ok(slot),
+                std::cmp::Ordering::Less => {
+                    lower_bound = slot + (block_number - found_block_number);
+                }
+                std::cmp::Ordering::Greater => {
+                    // The slots between `mid` and `slot` are empty, so the block is before `mid`
+                    if mid == 0 {
+                        break;
+                    }
+                    upper_bound = slot
+                        .saturating_sub(found_block_number - block_number)
+                        .min(mid - 1);
+                }
+            }
+        }
+
+        Err(BeaconApiClientError::ExecutionBlockNotFound(block_number))
+    }
+
+    /// Returns the slot and execution block number of the first non-empty slot in
+    /// `[start_slot, end_slot]`, if any
+    async fn first_block_in_range(
+        &self,
+        start_slot: u64,
+        end_slot: u64,
+    ) -> Result<Option<(u64, u64)>, BeaconApiClientError> {
+        for slot in start_slot..=end_slot {
+            match self.beacon_api_client.beacon_block(&slot.to_string()).await {
+                Ok(block) => {
+                    let block_number = block.message.body.execution_payload.block_number;
+                    self.cache_slot(block_number, slot);
+                    return Ok(Some((slot, block_number)));
+                }
+                // Missed slots do not have a beacon block
+                Err(BeaconApiClientError::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Caches the slot of an execution block
+    fn cache_slot(&self, block_number: u64, slot: u64) {
+        self.cache.lock().unwrap().insert(block_number, slot);
+    }
+}
@@ -3,14 +3,42 @@
 use std::str::FromStr;
 
 use alloy::{
-    primitives::{Address, StorageKey},
-    providers::Provider,
-    rpc::types::{Block, EIP1186AccountProofResponse},
+    eips::BlockId,
+    primitives::{Address, BlockHash, StorageKey, TxHash, U64},
+    providers::{Provider, RootProvider},
+    rpc::{
+        client::{BatchRequest, NoParams},
+        types::{Block, EIP1186AccountProofResponse, TransactionReceipt},
+    },
 };
 
-use super::error::EthClientError;
+use super::{error::EthClientError, pool::EndpointPool};
 
 const RPC_METHOD_GET_PROOF: &str = "eth_getProof";
+const RPC_METHOD_BLOCK_NUMBER: &str = "eth_blockNumber";
+const RPC_METHOD_GET_TRANSACTION_RECEIPT: &str = "eth_getTransactionReceipt";
+
+/// A single `eth_getProof` request to be sent as part of a batch.
+#[derive(Clone, Debug)]
+pub struct ProofRequest {
+    /// The address of the account.
+    pub address: String,
+    /// The storage keys to prove under the account.
+    pub storage_keys: Vec<String>,
+    /// The block to prove against, preferably pinned with [`pinned_block`].
+    pub block: BlockId,
+}
+
+/// The response of a batched request. Each item succeeds or fails independently.
+#[derive(Debug)]
+pub struct BatchResponse {
+    /// The current block number.
+    pub block_number: Result<u64, EthClientError>,
+    /// The proofs, in the same order as the requests.
+    pub proofs: Vec<Result<EIP1186AccountProofResponse, EthClientError>>,
+    /// The transaction receipts, in the same order as the requested hashes.
+    pub receipts: Vec<Result<Option<TransactionReceipt>, EthClientError>>,
+}
 
 /// The api client for interacting with the Beacon API
 #[allow(clippy::module_name_repetitions)]
@@ -25,30 +53,84 @@ impl<P: Provider + Clone> EthApiClient<P> {
     }
 
     /// Fetches proof for an account and optionally storage keys under the given account at the block.
+    ///
+    /// The block should be pinned by hash with [`pinned_block`], so that nodes behind a load
+    /// balancer cannot serve the proof from a different block or fork than intended.
     /// # Errors
     /// Returns an error if the input fails to serialize, the request fails or the response is not successful deserialized
     pub async fn get_proof(
         &self,
         address: &str,
         storage_keys: Vec<String>,
-        block_hex: String,
+        block: BlockId,
     ) -> Result<EIP1186AccountProofResponse, EthClientError> {
-        let address: Address = Address::from_str(address)
-            .map_err(|e| EthClientError::ParseError(address.to_string(), e.to_string()))?;
-        let storage_keys: Vec<StorageKey> = storage_keys
-            .into_iter()
-            .map(|key| {
-                StorageKey::from_str(&key)
-                    .map_err(|e| EthClientError::ParseError(key, e.to_string()))
-            })
-            .collect::<Result<_, _>>()?;
+        let (address, storage_keys) = parse_proof_params(address, storage_keys)?;
         Ok(self
             .provider
             .client()
-            .request(RPC_METHOD_GET_PROOF, (address, storage_keys, block_hex))
+            .request(RPC_METHOD_GET_PROOF, (address, storage_keys, block))
             .await?)
     }
 
+    /// Fetches the current block number, multiple proofs and transaction receipts in a single
+    /// JSON-RPC batch request.
+    ///
+    /// Errors of individual items are returned in the [`BatchResponse`] so that a single failing
+    /// item does not fail the whole batch.
+    /// # Errors
+    /// Returns an error if a request fails to serialize or the batch request itself fails
+    pub async fn batch(
+        &self,
+        proof_requests: Vec<ProofRequest>,
+        tx_hashes: Vec<TxHash>,
+    ) -> Result<BatchResponse, EthClientError> {
+        let mut batch = BatchRequest::new(self.provider.client());
+
+        let block_number_waiter = batch.add_call::<NoParams, U64>(RPC_METHOD_BLOCK_NUMBER, &[])?;
+        let proof_waiters = proof_requests
+            .into_iter()
+            .map(|req| {
+                let (address, storage_keys) = parse_proof_params(&req.address, req.storage_keys)?;
+                Ok(batch.add_call::<_, EIP1186AccountProofResponse>(
+                    RPC_METHOD_GET_PROOF,
+                    &(address, storage_keys, req.block),
+                )?)
+            })
+            .collect::<Result<Vec<_>, EthClientError>>()?;
+        let receipt_waiters = tx_hashes
+            .into_iter()
+            .map(|tx_hash| {
+                batch.add_call::<_, Option<TransactionReceipt>>(
+                    RPC_METHOD_GET_TRANSACTION_RECEIPT,
+                    &(tx_hash,),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        batch.send().await?;
+
+        let block_number = block_number_waiter
+            .await
+            .map(|n| n.to::<u64>())
+            .map_err(EthClientError::from);
+
+        let mut proofs = Vec::with_capacity(proof_waiters.len());
+        for waiter in proof_waiters {
+            proofs.push(waiter.await.map_err(EthClientError::from));
+        }
+
+        let mut receipts = Vec::with_capacity(receipt_waiters.len());
+        for waiter in receipt_waiters {
+            receipts.push(waiter.await.map_err(EthClientError::from));
+        }
+
+        Ok(BatchResponse {
+            block_number,
+            proofs,
+            receipts,
+        })
+    }
+
     /// Fetches the current block number.
     /// # Errors
     /// Returns an error if the request fails
@@ -66,3 +148,201 @@ impl<P: Provider + Clone> EthApiClient<P> {
             .ok_or_else(|| EthClientError::BlockNotFound(block_number))
     }
 }
+
+impl EthApiClient<RootProvider> {
+    /// Create new `EthApiClient` sending its requests through an [`EndpointPool`], which fails
+    /// over to the next endpoint when one fails
+    #[must_use]
+    pub fn from_pool(pool: EndpointPool) -> Self {
+        Self::new(pool.into_provider())
+    }
+}
+
+/// Returns the EIP-1898 block parameter pinning a query to the canonical block with the given hash.
+///
+/// Nodes that do not have the block, or for which it is not canonical, reject the query instead of
+/// answering it at another block.
+#[must_use]
+pub const fn pinned_block(block_hash: BlockHash) -> BlockId {
+    BlockId::hash_canonical(block_hash)
+}
+
+/// Parses the address and storage keys of an `eth_getProof` request.
+fn parse_proof_params(
+    address: &str,
+    storage_keys: Vec<String>,
+) -> Result<(Address, Vec<StorageKey>), EthClientError> {
+    let address: Address = Address::from_str(address)
+        .map_err(|e| EthClientError::ParseError(address.to_string(), e.to_string()))?;
+    let storage_keys: Vec<StorageKey> = storage_keys
+        .into_iter()
+        .map(|key| {
+            StorageKey::from_str(&key).map_err(|e| EthClientError::ParseError(key, e.to_string()))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((address, storage_keys))
+}
+
+#[cfg(test)]
+mod test {
+    use std::task::{Context, Poll};
+
+    use alloy::{
+        rpc::{
+            client::RpcClient,
+            json_rpc::{RequestPacket, ResponsePacket},
+        },
+        transports::{TransportError, TransportFut},
+    };
+    use serde_json::{json, Value};
+    use tower::Service;
+
+    use super::*;
+
+    /// The byte repeated in the addresses and transaction hashes the mock node fails to answer.
+    const FAILING_BYTE: u8 = 0xee;
+
+    /// A node answering batches in the reverse order of their requests.
+    #[derive(Clone)]
+    struct ReversingNode;
+
+    impl ReversingNode {
+        fn payload(method: &str, params: &str) -> Value {
+            let failure = json!({ "error": { "code": -32000, "message": "missing trie node" } });
+            match method {
+                RPC_METHOD_BLOCK_NUMBER => json!({ "result": "0x2a" }),
+                RPC_METHOD_GET_PROOF => {
+                    let (address, _, _): (Address, Vec<StorageKey>, BlockId) =
+                        serde_json::from_str(params).unwrap();
+                    if address == Address::repeat_byte(FAILING_BYTE) {
+                        return failure;
+                    }
+                    let proof = EIP1186AccountProofResponse {
+                        address,
+                        ..Default::default()
+                    };
+                    json!({ "result": proof })
+                }
+                RPC_METHOD_GET_TRANSACTION_RECEIPT => {
+                    let (tx_hash,): (TxHash,) = serde_json::from_str(params).unwrap();
+                    if tx_hash == TxHash::repeat_byte(FAILING_BYTE) {
+                        return failure;
+                    }
+                    json!({ "result": null })
+                }
+                method => panic!("unexpected method {method}"),
+            }
+        }
+    }
+
+    impl Service<RequestPacket> for ReversingNode {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: RequestPacket) -> Self::Future {
+            let RequestPacket::Batch(requests) = request else {
+                panic!("expected a batch request");
+            };
+            let responses = requests
+                .iter()
+                .rev()
+                .map(|request| {
+                    let params = request.params().map_or("[]", |params| params.get());
+                    let mut response = Self::payload(request.method(), params);
+                    response["jsonrpc"] = json!("2.0");
+                    response["id"] = json!(request.id());
+                    response
+                })
+                .collect::<Vec<_>>();
+            let response = serde_json::from_str(&Value::from(responses).to_string()).unwrap();
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    fn client() -> EthApiClient<RootProvider> {
+        EthApiClient::new(RootProvider::new(RpcClient::new(ReversingNode, true)))
+    }
+
+    fn proof_request(byte: u8) -> ProofRequest {
+        ProofRequest {
+            address: Address::repeat_byte(byte).to_string(),
+            storage_keys: vec![StorageKey::repeat_byte(byte).to_string()],
+            block: pinned_block(BlockHash::repeat_byte(0x01)),
+        }
+    }
+
+    #[test]
+    fn test_batch_matches_out_of_order_responses_by_id() {
+        let response = futures::executor::block_on(client().batch(
+            vec![
+                proof_request(0x01),
+                proof_request(0x02),
+                proof_request(0x03),
+            ],
+            vec![TxHash::repeat_byte(0x01)],
+        ))
+        .unwrap();
+
+        assert_eq!(response.block_number.unwrap(), 42);
+        let addresses = response
+            .proofs
+            .into_iter()
+            .map(|proof| proof.unwrap().address)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            addresses,
+            vec![
+                Address::repeat_byte(0x01),
+                Address::repeat_byte(0x02),
+                Address::repeat_byte(0x03)
+            ]
+        );
+        assert!(matches!(response.receipts.as_slice(), [Ok(None)]));
+    }
+
+    #[test]
+    fn test_batch_with_mixed_success_and_error_responses() {
+        let response = futures::executor::block_on(client().batch(
+            vec![
+                proof_request(0x01),
+                proof_request(FAILING_BYTE),
+                proof_request(0x03),
+            ],
+            vec![TxHash::repeat_byte(FAILING_BYTE), TxHash::repeat_byte(0x02)],
+        ))
+        .unwrap();
+
+        // The failing items do not fail the others
+        assert_eq!(response.block_number.unwrap(), 42);
+        assert_eq!(
+            response.proofs[0].as_ref().unwrap().address,
+            Address::repeat_byte(0x01)
+        );
+        assert!(matches!(
+            response.proofs[1],
+            Err(EthClientError::ProviderError(_))
+        ));
+        assert_eq!(
+            response.proofs[2].as_ref().unwrap().address,
+            Address::repeat_byte(0x03)
+        );
+        assert!(matches!(
+            response.receipts[0],
+            Err(EthClientError::ProviderError(_))
+        ));
+        assert!(matches!(response.receipts[1], Ok(None)));
+
+        // An invalid request fails the whole batch before it is sent
+        let mut invalid = proof_request(0x01);
+        invalid.address = "not an address".to_string();
+        assert!(matches!(
+            futures::executor::block_on(client().batch(vec![invalid], vec![])),
+            Err(EthClientError::ParseError(..))
+        ));
+    }
+}
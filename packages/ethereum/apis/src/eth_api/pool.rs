@@ -0,0 +1,332 @@
+//! This module implements the [`EndpointPool`] of execution RPC endpoints.
+//!
+//! The pool is a transport, so that every request of a provider built on it is routed: requests
+//! go to the healthiest endpoint first, and to the next ones when an endpoint fails. Endpoints are
+//! ranked by whether they failed recently, then by the moving average of their latency. Endpoints
+//! whose latency is not measured yet are ranked first, so that every endpoint gets measured.
+//!
+//! Requests pinned to a block hash with [`super::client::pinned_block`] stick to the endpoint that
+//! first answered a request pinned to the same block, so that all the proofs at a pinned block are
+//! served by the same node. A node that does not have the pinned block yet answers with an error,
+//! in which case the request is retried on the next endpoint.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    primitives::BlockHash,
+    providers::RootProvider,
+    rpc::{
+        client::RpcClient,
+        json_rpc::{RequestPacket, ResponsePacket, ResponsePayload},
+    },
+    transports::{BoxTransport, Transport, TransportError, TransportErrorKind, TransportFut},
+};
+use tower::Service;
+
+/// The default time after which an endpoint that failed is ranked by its latency again, in
+/// seconds.
+pub const DEFAULT_RECOVERY_SECS: u64 = 30;
+
+/// The number of pinned blocks whose endpoint is remembered.
+const STICKY_BLOCKS: usize = 256;
+
+/// The health of an endpoint of the pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct EndpointHealth {
+    /// The moving average of the latency of the endpoint in microseconds, if measured.
+    latency_micros: Option<u64>,
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+}
+
+impl EndpointHealth {
+    fn record_success(&mut self, latency: Duration) {
+        let sample = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.latency_micros = Some(self.latency_micros.map_or(sample, |average| {
+            average.saturating_mul(3).saturating_add(sample) / 4
+        }));
+        self.consecutive_failures = 0;
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.last_failure = Some(now);
+    }
+
+    /// Returns whether the endpoint failed within the recovery period.
+    fn is_degraded(&self, now: Instant, recovery: Duration) -> bool {
+        self.consecutive_failures > 0
+            && self
+                .last_failure
+                .is_some_and(|last_failure| now.saturating_duration_since(last_failure) < recovery)
+    }
+}
+
+/// An endpoint of the pool.
+#[derive(Debug)]
+struct PoolEndpoint {
+    label: String,
+    transport: BoxTransport,
+    health: Mutex<EndpointHealth>,
+}
+
+/// A pool of execution RPC endpoints with health based routing and failover. Clones share the
+/// health of the endpoints.
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EndpointPool {
+    endpoints: Vec<Arc<PoolEndpoint>>,
+    /// The endpoint that answered each of the last pinned blocks.
+    sticky_blocks: Arc<Mutex<VecDeque<(BlockHash, usize)>>>,
+    recovery: Duration,
+}
+
+impl EndpointPool {
+    /// Create a new `EndpointPool` with the primary endpoint, which is preferred until the latency
+    /// of the other endpoints is measured
+    #[must_use]
+    pub fn new<T: Transport + Clone>(label: String, transport: T) -> Self {
+        Self {
+            endpoints: vec![Arc::new(PoolEndpoint {
+                label,
+                transport: BoxTransport::new(transport),
+                health: Mutex::default(),
+            })],
+            sticky_blocks: Arc::default(),
+            recovery: Duration::from_secs(DEFAULT_RECOVERY_SECS),
+        }
+    }
+
+    /// Adds an endpoint to the pool
+    #[must_use]
+    pub fn with_endpoint<T: Transport + Clone>(mut self, label: String, transport: T) -> Self {
+        self.endpoints.push(Arc::new(PoolEndpoint {
+            label,
+            transport: BoxTransport::new(transport),
+            health: Mutex::default(),
+        }));
+        self
+    }
+
+    /// Sets the time after which an endpoint that failed is ranked by its latency again
+    #[must_use]
+    pub const fn with_recovery(mut self, recovery: Duration) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// Returns the labels of the endpoints of the pool
+    #[must_use]
+    pub fn endpoints(&self) -> Vec<&str> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.label.as_str())
+            .collect()
+    }
+
+    /// Returns a provider sending its requests through the pool
+    #[must_use]
+    pub fn into_provider(self) -> RootProvider {
+        RootProvider::new(RpcClient::new(self, false))
+    }
+
+    /// Returns the indices of the endpoints in the order they are requested
+    /// # Panics
+    /// Panics if a lock is poisoned
+    fn ranking(&self, pinned_block: Option<BlockHash>, now: Instant) -> Vec<usize> {
+        let health = self
+            .endpoints
+            .iter()
+            .map(|endpoint| *endpoint.health.lock().unwrap())
+            .collect::<Vec<_>>();
+        let sticky = pinned_block.and_then(|block_hash| {
+            self.sticky_blocks
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(hash, _)| *hash == block_hash)
+                .map(|(_, index)| *index)
+        });
+        rank(&health, sticky, now, self.recovery)
+    }
+
+    /// Remembers the endpoint that answered a request pinned to a block
+    fn stick(&self, block_hash: BlockHash, index: usize) {
+        let mut sticky_blocks = self.sticky_blocks.lock().unwrap();
+        sticky_blocks.retain(|(hash, _)| *hash != block_hash);
+        if sticky_blocks.len() == STICKY_BLOCKS {
+            sticky_blocks.pop_front();
+        }
+        sticky_blocks.push_back((block_hash, index));
+    }
+
+    async fn send(&self, request: &RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let pinned_block = pinned_block_hash(request);
+        let mut last_error = None;
+        for index in self.ranking(pinned_block, Instant::now()) {
+            let endpoint = &self.endpoints[index];
+            let started_at = Instant::now();
+            let response = endpoint.transport.clone().call(request.clone()).await;
+            match response {
+                // Error responses are successful round trips, but a node may reject a pinned block
+                // it does not have yet, which another node can serve
+                Ok(response) if pinned_block.is_some() && is_error(&response) => {
+                    endpoint
+                        .health
+                        .lock()
+                        .unwrap()
+                        .record_success(started_at.elapsed());
+                    tracing::debug!(endpoint = %endpoint.label, "Pinned block rejected, trying the next endpoint");
+                    last_error = Some(Ok(response));
+                }
+                Ok(response) => {
+                    endpoint
+                        .health
+                        .lock()
+                        .unwrap()
+                        .record_success(started_at.elapsed());
+                    if let Some(block_hash) = pinned_block {
+                        self.stick(block_hash, index);
+                    }
+                    return Ok(response);
+                }
+                Err(e) => {
+                    endpoint
+                        .health
+                        .lock()
+                        .unwrap()
+                        .record_failure(Instant::now());
+                    tracing::warn!(endpoint = %endpoint.label, "Execution RPC request failed, trying the next endpoint: {e}");
+                    last_error = Some(Err(e));
+                }
+            }
+        }
+
+        last_error.unwrap_or_else(|| {
+            Err(TransportErrorKind::custom_str(
+                "the endpoint pool has no endpoints",
+            ))
+        })
+    }
+}
+
+impl Service<RequestPacket> for EndpointPool {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let pool = self.clone();
+        Box::pin(async move { pool.send(&request).await })
+    }
+}
+
+/// Returns the indices of the endpoints in the order they are requested: the endpoint a pinned
+/// block sticks to, the endpoints that did not fail recently, and the others, each by latency
+fn rank(
+    health: &[EndpointHealth],
+    sticky: Option<usize>,
+    now: Instant,
+    recovery: Duration,
+) -> Vec<usize> {
+    let mut ranking = (0..health.len()).collect::<Vec<_>>();
+    // The sort is stable, so that endpoints of equal health keep the configured order
+    ranking.sort_by_key(|&index| {
+        (
+            Some(index) != sticky,
+            health[index].is_degraded(now, recovery),
+            health[index].latency_micros.unwrap_or_default(),
+        )
+    });
+    ranking
+}
+
+/// Returns the block hash a request is pinned to, if any
+fn pinned_block_hash(request: &RequestPacket) -> Option<BlockHash> {
+    let requests = match request {
+        RequestPacket::Single(request) => std::slice::from_ref(request),
+        RequestPacket::Batch(requests) => requests.as_slice(),
+    };
+    requests
+        .iter()
+        .filter_map(|request| request.params())
+        .find_map(|params| pinned_block_hash_of_params(params.get()))
+}
+
+/// Returns the block hash of the first EIP-1898 block parameter of the JSON-RPC params, if any
+fn pinned_block_hash_of_params(params: &str) -> Option<BlockHash> {
+    let serde_json::Value::Array(params) = serde_json::from_str(params).ok()? else {
+        return None;
+    };
+    params.iter().find_map(|param| {
+        param
+            .get("blockHash")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|block_hash| block_hash.parse().ok())
+    })
+}
+
+/// Returns whether a response is, or contains, an error response
+fn is_error(response: &ResponsePacket) -> bool {
+    match response {
+        ResponsePacket::Single(response) => {
+            matches!(response.payload, ResponsePayload::Failure(_))
+        }
+        ResponsePacket::Batch(responses) => responses
+            .iter()
+            .any(|response| matches!(response.payload, ResponsePayload::Failure(_))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eth_api::client::pinned_block;
+
+    #[test]
+    fn test_rank() {
+        let now = Instant::now();
+        let recovery = Duration::from_secs(DEFAULT_RECOVERY_SECS);
+        let mut health = [EndpointHealth::default(); 3];
+
+        // The configured order is kept until the endpoints are measured
+        assert_eq!(rank(&health, None, now, recovery), vec![0, 1, 2]);
+
+        health[0].record_success(Duration::from_millis(300));
+        health[1].record_success(Duration::from_millis(20));
+        health[2].record_success(Duration::from_millis(50));
+        assert_eq!(rank(&health, None, now, recovery), vec![1, 2, 0]);
+
+        // A failing endpoint is ranked last until it recovers
+        health[1].record_failure(now);
+        assert_eq!(rank(&health, None, now, recovery), vec![2, 0, 1]);
+        assert_eq!(rank(&health, None, now + recovery, recovery), vec![1, 2, 0]);
+
+        // A pinned block sticks to its endpoint, even if it is degraded
+        assert_eq!(rank(&health, Some(1), now, recovery), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_pinned_block_hash_of_params() {
+        let block_hash = BlockHash::repeat_byte(0xab);
+        let params = serde_json::to_string(&(
+            "0x0000000000000000000000000000000000000001",
+            Vec::<String>::new(),
+            pinned_block(block_hash),
+        ))
+        .unwrap();
+        assert_eq!(pinned_block_hash_of_params(&params), Some(block_hash));
+
+        assert_eq!(pinned_block_hash_of_params(r#"["0x1", false]"#), None);
+        assert_eq!(pinned_block_hash_of_params("[]"), None);
+    }
+}
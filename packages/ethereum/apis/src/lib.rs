@@ -8,4 +8,5 @@
 )]
 
 pub mod beacon_api;
+pub mod circuit_breaker;
 pub mod eth_api;
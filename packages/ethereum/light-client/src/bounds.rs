@@ -0,0 +1,198 @@
+//! This module defines the maximum lengths of the variable length fields of untrusted inputs.
+//!
+//! Client messages and proofs are submitted by untrusted relayers, so every variable length field
+//! is checked against an explicit bound right after decoding, before any verification work
+//! proportional to its length is done.
+
+use ethereum_types::{
+    consensus::{
+        light_client_header::{LightClientHeader, LightClientUpdate},
+        sync_committee::{SyncAggregate, SyncCommittee},
+    },
+    execution::storage_proof::StorageProof,
+};
+
+use crate::{error::EthereumIBCError, header::Header};
+
+/// The maximum number of public keys in a sync committee, the mainnet `SYNC_COMMITTEE_SIZE`.
+pub const MAX_SYNC_COMMITTEE_SIZE: usize = 512;
+/// The maximum length of the sync committee participation bitfield in bytes.
+pub const MAX_SYNC_COMMITTEE_BITS_LEN: usize = MAX_SYNC_COMMITTEE_SIZE / 8;
+/// The maximum depth of a merkle branch of the beacon state.
+pub const MAX_MERKLE_BRANCH_DEPTH: usize = 16;
+/// The maximum number of nodes in a Merkle-Patricia trie proof.
+pub const MAX_TRIE_PROOF_NODES: usize = 64;
+/// The maximum size of a Merkle-Patricia trie proof node in bytes.
+pub const MAX_TRIE_PROOF_NODE_SIZE: usize = 1024;
+/// The maximum size of an encoded storage proof in bytes.
+pub const MAX_STORAGE_PROOF_SIZE: usize = 256 * 1024;
+/// The maximum length of an IBC key path in bytes.
+pub const MAX_KEY_PATH_LENGTH: usize = 1024;
+/// The maximum length of the extra data of an execution payload, the `MAX_EXTRA_DATA_BYTES`.
+pub const MAX_EXTRA_DATA_BYTES: usize = 32;
+
+/// Returns an error if `found` exceeds `max`.
+const fn check_length(
+    field: &'static str,
+    found: usize,
+    max: usize,
+) -> Result<(), EthereumIBCError> {
+    if found > max {
+        return Err(EthereumIBCError::ExceedsMaxLength { field, max, found });
+    }
+    Ok(())
+}
+
+/// Checks the lengths of a header.
+/// # Errors
+/// Returns [`EthereumIBCError::ExceedsMaxLength`] if a field is too long.
+pub fn check_header_bounds(header: &Header) -> Result<(), EthereumIBCError> {
+    check_sync_committee_bounds(header.active_sync_committee.sync_committee())?;
+    check_light_client_update_bounds(&header.consensus_update)?;
+    check_trie_proof_bounds("account proof", &header.account_update.account_proof.proof)
+}
+
+/// Checks the lengths of a light client update.
+/// # Errors
+/// Returns [`EthereumIBCError::ExceedsMaxLength`] if a field is too long.
+pub fn check_light_client_update_bounds(
+    update: &LightClientUpdate,
+) -> Result<(), EthereumIBCError> {
+    check_light_client_header_bounds(&update.attested_header)?;
+    check_light_client_header_bounds(&update.finalized_header)?;
+    if let Some(next_sync_committee) = &update.next_sync_committee {
+        check_sync_committee_bounds(next_sync_committee)?;
+    }
+    if let Some(next_sync_committee_branch) = &update.next_sync_committee_branch {
+        check_length(
+            "next sync committee branch",
+            next_sync_committee_branch.len(),
+            MAX_MERKLE_BRANCH_DEPTH,
+        )?;
+    }
+    check_length(
+        "finality branch",
+        update.finality_branch.len(),
+        MAX_MERKLE_BRANCH_DEPTH,
+    )?;
+    check_sync_aggregate_bounds(&update.sync_aggregate)
+}
+
+/// Checks the lengths of a sync committee.
+/// # Errors
+/// Returns [`EthereumIBCError::ExceedsMaxLength`] if a field is too long.
+pub const fn check_sync_committee_bounds(
+    sync_committee: &SyncCommittee,
+) -> Result<(), EthereumIBCError> {
+    check_length(
+        "sync committee",
+        sync_committee.pubkeys.len(),
+        MAX_SYNC_COMMITTEE_SIZE,
+    )
+}
+
+/// Checks the lengths of a storage proof.
+/// # Errors
+/// Returns [`EthereumIBCError::ExceedsMaxLength`] if a field is too long.
+pub fn check_storage_proof_bounds(storage_proof: &StorageProof) -> Result<(), EthereumIBCError> {
+    check_trie_proof_bounds("storage proof", &storage_proof.proof)
+}
+
+/// Checks the lengths of the key path of a membership proof.
+/// # Errors
+/// Returns [`EthereumIBCError::ExceedsMaxLength`] if a key is too long.
+pub fn check_key_path_bounds(path: &[Vec<u8>]) -> Result<(), EthereumIBCError> {
+    path.iter()
+        .try_for_each(|key| check_length("key path", key.len(), MAX_KEY_PATH_LENGTH))
+}
+
+/// Checks the size of an encoded storage proof before it is decoded.
+/// # Errors
+/// Returns [`EthereumIBCError::ExceedsMaxLength`] if the proof is too large.
+pub const fn check_encoded_storage_proof_bounds(proof: &[u8]) -> Result<(), EthereumIBCError> {
+    check_length("encoded storage proof", proof.len(), MAX_STORAGE_PROOF_SIZE)
+}
+
+fn check_light_client_header_bounds(header: &LightClientHeader) -> Result<(), EthereumIBCError> {
+    check_length(
+        "execution payload extra data",
+        header.execution.extra_data.len(),
+        MAX_EXTRA_DATA_BYTES,
+    )
+}
+
+fn check_sync_aggregate_bounds(sync_aggregate: &SyncAggregate) -> Result<(), EthereumIBCError> {
+    check_length(
+        "sync committee bits",
+        sync_aggregate.sync_committee_bits.len(),
+        MAX_SYNC_COMMITTEE_BITS_LEN,
+    )
+}
+
+fn check_trie_proof_bounds<T: AsRef<[u8]>>(
+    field: &'static str,
+    proof: &[T],
+) -> Result<(), EthereumIBCError> {
+    check_length(field, proof.len(), MAX_TRIE_PROOF_NODES)?;
+    proof
+        .iter()
+        .try_for_each(|node| check_length(field, node.as_ref().len(), MAX_TRIE_PROOF_NODE_SIZE))
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::{Bytes, B256};
+
+    use super::*;
+
+    #[test]
+    fn test_check_light_client_update_bounds() {
+        let mut update = LightClientUpdate::default();
+        assert_eq!(check_light_client_update_bounds(&update), Ok(()));
+
+        update.finality_branch = vec![B256::ZERO; MAX_MERKLE_BRANCH_DEPTH + 1];
+        assert_eq!(
+            check_light_client_update_bounds(&update),
+            Err(EthereumIBCError::ExceedsMaxLength {
+                field: "finality branch",
+                max: MAX_MERKLE_BRANCH_DEPTH,
+                found: MAX_MERKLE_BRANCH_DEPTH + 1,
+            })
+        );
+
+        update.finality_branch = vec![];
+        update.sync_aggregate.sync_committee_bits =
+            Bytes::from(vec![0xff; MAX_SYNC_COMMITTEE_BITS_LEN + 1]);
+        assert!(matches!(
+            check_light_client_update_bounds(&update),
+            Err(EthereumIBCError::ExceedsMaxLength {
+                field: "sync committee bits",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_check_storage_proof_bounds() {
+        let mut storage_proof = StorageProof {
+            proof: vec![Bytes::from(vec![0; MAX_TRIE_PROOF_NODE_SIZE]); MAX_TRIE_PROOF_NODES],
+            ..Default::default()
+        };
+        assert_eq!(check_storage_proof_bounds(&storage_proof), Ok(()));
+
+        storage_proof.proof[0] = Bytes::from(vec![0; MAX_TRIE_PROOF_NODE_SIZE + 1]);
+        assert!(check_storage_proof_bounds(&storage_proof).is_err());
+
+        storage_proof.proof = vec![Bytes::new(); MAX_TRIE_PROOF_NODES + 1];
+        assert!(check_storage_proof_bounds(&storage_proof).is_err());
+    }
+
+    #[test]
+    fn test_check_key_path_bounds() {
+        assert_eq!(
+            check_key_path_bounds(&[vec![0; MAX_KEY_PATH_LENGTH]]),
+            Ok(())
+        );
+        assert!(check_key_path_bounds(&[vec![0; MAX_KEY_PATH_LENGTH + 1]]).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! This module provides the canonical JSON encoding of the client and consensus states.
+//!
+//! The states are stored as the JSON produced by serde, whose field order follows the declaration
+//! order of the structs, so the same state encoded by another implementation, or by another
+//! version of this one, may differ byte for byte. The canonical encoding is the subset of
+//! [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) needed by the states: object keys are sorted
+//! by their UTF-8 bytes, which matches the UTF-16 order of RFC 8785 for ASCII keys, there is no
+//! insignificant whitespace, and only integer numbers are accepted. Hashes of the states compared
+//! across implementations must be computed over this encoding.
+
+use alloy_primitives::B256;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{client_state::ClientState, consensus_state::ConsensusState, error::EthereumIBCError};
+
+/// Returns the canonical JSON encoding of `value`.
+/// # Errors
+/// Returns an error if `value` cannot be serialized to JSON, or if it contains a non-integer
+/// number.
+pub fn canonical_json(value: &impl Serialize) -> Result<Vec<u8>, EthereumIBCError> {
+    let value = serde_json::to_value(value)
+        .map_err(|e| EthereumIBCError::CanonicalJsonFailed(e.to_string()))?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out.into_bytes())
+}
+
+/// Returns the sha256 hash of the canonical JSON encoding of `value`.
+/// # Errors
+/// Returns an error if `value` has no canonical JSON encoding, see [`canonical_json`].
+pub fn canonical_hash(value: &impl Serialize) -> Result<B256, EthereumIBCError> {
+    Ok(B256::from_slice(&Sha256::digest(canonical_json(value)?)))
+}
+
+fn write_canonical(value: &Value, out: &mut String) -> Result<(), EthereumIBCError> {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => out.push_str(&value.to_string()),
+        Value::Number(number) => {
+            ensure!(
+                number.is_u64() || number.is_i64(),
+                EthereumIBCError::CanonicalJsonFailed(format!("non-integer number {number}"))
+            );
+            out.push_str(&number.to_string());
+        }
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(value, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(fields) => {
+            let mut fields = fields.iter().collect::<Vec<_>>();
+            fields.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+            out.push('{');
+            for (i, (key, value)) in fields.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+impl ClientState {
+    /// Returns the sha256 hash of the canonical JSON encoding of the client state, which is
+    /// independent of the field order of the implementation.
+    /// # Errors
+    /// Returns an error if the client state cannot be serialized.
+    pub fn canonical_hash(&self) -> Result<B256, EthereumIBCError> {
+        canonical_hash(self)
+    }
+}
+
+impl ConsensusState {
+    /// Returns the sha256 hash of the canonical JSON encoding of the consensus state, which is
+    /// independent of the field order of the implementation.
+    /// # Errors
+    /// Returns an error if the consensus state cannot be serialized.
+    pub fn canonical_hash(&self) -> Result<B256, EthereumIBCError> {
+        canonical_hash(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_canonical_json() {
+        let value = json!({
+            "b": [3, {"z": null, "a": true}],
+            "a": "\u{1f}é",
+            "c": -1,
+        });
+        assert_eq!(
+            String::from_utf8(canonical_json(&value).unwrap()).unwrap(),
+            r#"{"a":"\u001fé","b":[3,{"a":true,"z":null}],"c":-1}"#
+        );
+
+        let err = canonical_json(&json!({ "a": 1.5 })).unwrap_err();
+        assert!(matches!(err, EthereumIBCError::CanonicalJsonFailed(_)));
+    }
+
+    #[test]
+    fn test_canonical_hash_ignores_field_order() {
+        let client_state = ClientState {
+            chain_id: 11_155_111,
+            latest_slot: 42,
+            ..Default::default()
+        };
+
+        // The same state with its fields in reverse order, as another implementation may encode
+        let Value::Object(fields) = serde_json::to_value(&client_state).unwrap() else {
+            panic!("client state is not an object");
+        };
+        let reversed = format!(
+            "{{{}}}",
+            fields
+                .into_iter()
+                .rev()
+                .map(|(key, value)| format!("{}:{value}", Value::String(key)))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        assert_ne!(serde_json::to_string(&client_state).unwrap(), reversed);
+
+        let decoded: ClientState = serde_json::from_str(&reversed).unwrap();
+        assert_eq!(
+            decoded.canonical_hash().unwrap(),
+            client_state.canonical_hash().unwrap()
+        );
+        assert_eq!(
+            canonical_hash(&serde_json::from_str::<Value>(&reversed).unwrap()).unwrap(),
+            client_state.canonical_hash().unwrap()
+        );
+        assert_ne!(
+            ClientState::default().canonical_hash().unwrap(),
+            client_state.canonical_hash().unwrap()
+        );
+    }
+}
@@ -33,6 +33,9 @@ pub struct ClientState {
     pub latest_slot: u64,
     /// The latest execution block number, used for relayer convenience only
     pub latest_execution_block_number: u64,
+    /// The revision number of the heights of this client
+    #[serde(default)]
+    pub revision_number: u64,
     /// Whether the client is frozen
     pub is_frozen: bool,
     /// The address of the IBC contract being tracked on Ethereum
@@ -41,6 +44,36 @@ pub struct ClientState {
     /// The storage slot of the IBC commitment in the Ethereum contract
     #[schemars(with = "String")]
     pub ibc_commitment_slot: U256,
+    /// Whether the client accepts zlib compressed client messages
+    #[serde(default)]
+    pub compressed_client_messages: bool,
+    /// The consensus states persisted when a header batch is applied
+    #[serde(default)]
+    pub consensus_state_writes: ConsensusStateWrites,
+    /// The minimum number of slots an update must advance the client by within a sync committee
+    /// period, if any
+    #[serde(default)]
+    pub min_slot_advance: Option<u64>,
+    /// The number of slots below the latest slot the consensus states are kept for, if the older
+    /// consensus states are pruned
+    #[serde(default)]
+    pub consensus_state_retention: Option<u64>,
+    /// The number of seconds after the timestamp of the latest consensus state the client expires
+    /// at, if it expires
+    #[serde(default)]
+    pub trusting_period: Option<u64>,
+}
+
+/// The policy selecting the consensus states persisted when a header batch is applied
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsensusStateWrites {
+    /// The consensus state of every header is persisted
+    #[default]
+    All,
+    /// Only the last consensus state of each sync committee period and the newest one are
+    /// persisted, for chains that only need recent proof heights
+    PeriodBoundaries,
 }
 
 impl ClientState {
@@ -55,6 +88,46 @@ impl ClientState {
         Ok(())
     }
 
+    /// Verifies that an update to `update_finalized_slot` advances the client from `store_slot` by
+    /// at least `min_slot_advance` slots. Updates to a later sync committee period are always
+    /// accepted, so that the client can follow the rotations of the sync committee, and historical
+    /// updates are left to [`EthereumIBCError::HistoricalUpdateNotAllowed`].
+    /// # Errors
+    /// Returns an error if the update advances the client by too few slots.
+    pub const fn verify_slot_advance(
+        &self,
+        store_slot: u64,
+        update_finalized_slot: u64,
+    ) -> Result<(), EthereumIBCError> {
+        let Some(min_slot_advance) = self.min_slot_advance else {
+            return Ok(());
+        };
+        if update_finalized_slot <= store_slot
+            || self.compute_sync_committee_period_at_slot(update_finalized_slot)
+                > self.compute_sync_committee_period_at_slot(store_slot)
+        {
+            return Ok(());
+        }
+
+        ensure!(
+            update_finalized_slot - store_slot >= min_slot_advance,
+            EthereumIBCError::InsufficientSlotAdvance {
+                consensus_state_slot: store_slot,
+                update_finalized_slot,
+                min_slot_advance,
+            }
+        );
+        Ok(())
+    }
+
+    /// Returns the timestamp (unix seconds) the client expires at, given the timestamp of its latest
+    /// consensus state, if it has a trusting period.
+    #[must_use]
+    pub fn expires_at(&self, latest_timestamp: u64) -> Option<u64> {
+        self.trusting_period
+            .map(|trusting_period| latest_timestamp.saturating_add(trusting_period))
+    }
+
     /// Returns the computed slot at a given `timestamp_seconds`.
     #[must_use]
     pub fn compute_slot_at_timestamp(&self, timestamp_seconds: u64) -> Option<u64> {
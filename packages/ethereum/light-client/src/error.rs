@@ -9,6 +9,13 @@ pub enum EthereumIBCError {
     #[error("invalid path length, expected {expected} but found {found}")]
     InvalidPathLength { expected: usize, found: usize },
 
+    #[error("{field} length ({found}) exceeds the maximum of {max}")]
+    ExceedsMaxLength {
+        field: &'static str,
+        max: usize,
+        found: usize,
+    },
+
     #[error("unable to decode storage proof")]
     StorageProofDecode,
 
@@ -190,6 +197,21 @@ pub enum EthereumIBCError {
         update_finalized_slot: u64,
     },
 
+    #[error("canonical json serialization failed: {0}")]
+    CanonicalJsonFailed(String),
+
+    #[error(
+        "update advances the client by too few slots: \
+        stored consensus state slot: {consensus_state_slot}, \
+        update finalized header slot: {update_finalized_slot}, \
+        minimum slot advance: {min_slot_advance}"
+    )]
+    InsufficientSlotAdvance {
+        consensus_state_slot: u64,
+        update_finalized_slot: u64,
+        min_slot_advance: u64,
+    },
+
     #[error(
         "client and consensus slot mismatch: \
         client state slot: {client_state_slot}, \
@@ -199,6 +221,35 @@ pub enum EthereumIBCError {
         client_state_slot: u64,
         consensus_state_slot: u64,
     },
+
+    #[error(
+        "upgrade must advance the client: \
+        current slot: {current_slot}, \
+        upgraded slot: {upgraded_slot}"
+    )]
+    UpgradeSlotNotIncreasing {
+        current_slot: u64,
+        upgraded_slot: u64,
+    },
+
+    #[error("upgraded client state must not be frozen")]
+    UpgradedClientFrozen,
+
+    #[error("substitute client state must track the same chain as the subject client state")]
+    SubstituteClientMismatch,
+
+    #[error(
+        "substitute client must be fresher than the subject client: \
+        subject slot: {subject_slot}, \
+        substitute slot: {substitute_slot}"
+    )]
+    SubstituteSlotNotIncreasing {
+        subject_slot: u64,
+        substitute_slot: u64,
+    },
+
+    #[error("substitute client state must not be frozen")]
+    SubstituteClientFrozen,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
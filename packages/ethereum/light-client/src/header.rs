@@ -1,7 +1,9 @@
 //! This module defines types related to the Header we use for the Ethereum light client
 
+use alloy_primitives::B256;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tree_hash::TreeHash;
 
 use ethereum_types::{
     consensus::{light_client_header::LightClientUpdate, sync_committee::SyncCommittee},
@@ -34,3 +36,19 @@ pub enum ActiveSyncCommittee {
     /// The next sync committee
     Next(SyncCommittee),
 }
+
+impl ActiveSyncCommittee {
+    /// Returns the sync committee regardless of whether it is the current or next one
+    #[must_use]
+    pub const fn sync_committee(&self) -> &SyncCommittee {
+        match self {
+            Self::Current(committee) | Self::Next(committee) => committee,
+        }
+    }
+
+    /// Returns the hash tree root of the sync committee
+    #[must_use]
+    pub fn tree_hash_root(&self) -> B256 {
+        self.sync_committee().tree_hash_root()
+    }
+}
@@ -17,15 +17,19 @@ macro_rules! ensure {
     };
 }
 
+pub mod bounds;
+pub mod canonical;
 pub mod client_state;
 pub mod consensus_state;
 pub mod error;
 pub mod header;
 pub mod membership;
 pub mod misbehaviour;
+pub mod recovery;
 pub mod sync_protocol_helpers;
 pub mod trie;
 pub mod update;
+pub mod upgrade;
 pub mod verify;
 
 #[cfg(any(test, feature = "test-utils"))]
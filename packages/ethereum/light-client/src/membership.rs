@@ -1,11 +1,21 @@
 //! This module provides [`verify_membership`] function to verify the membership of a key in the
 //! storage trie.
 
-use alloy_primitives::{keccak256, Keccak256, U256};
-use ethereum_trie_db::trie_db::{verify_storage_exclusion_proof, verify_storage_inclusion_proof};
+use alloy_primitives::U256;
+use ethereum_trie_db::{
+    trie_db::{verify_storage_exclusion_proof, verify_storage_inclusion_proof},
+    types::keccak_256,
+};
 use ethereum_types::execution::storage_proof::StorageProof;
 
-use crate::{client_state::ClientState, consensus_state::ConsensusState, error::EthereumIBCError};
+use crate::{
+    bounds::{
+        check_encoded_storage_proof_bounds, check_key_path_bounds, check_storage_proof_bounds,
+    },
+    client_state::ClientState,
+    consensus_state::ConsensusState,
+    error::EthereumIBCError,
+};
 
 /// Verifies the membership of a key in the storage trie.
 /// # Errors
@@ -18,8 +28,11 @@ pub fn verify_membership(
     path: Vec<Vec<u8>>,
     raw_value: Vec<u8>,
 ) -> Result<(), EthereumIBCError> {
+    check_encoded_storage_proof_bounds(&proof)?;
+    check_key_path_bounds(&path)?;
     let storage_proof: StorageProof = serde_json::from_slice(proof.as_slice())
         .map_err(|_| EthereumIBCError::StorageProofDecode)?;
+    check_storage_proof_bounds(&storage_proof)?;
 
     check_commitment_path(
         &path,
@@ -55,8 +68,11 @@ pub fn verify_non_membership(
     proof: Vec<u8>,
     path: Vec<Vec<u8>>,
 ) -> Result<(), EthereumIBCError> {
+    check_encoded_storage_proof_bounds(&proof)?;
+    check_key_path_bounds(&path)?;
     let storage_proof: StorageProof = serde_json::from_slice(proof.as_slice())
         .map_err(|_| EthereumIBCError::StorageProofDecode)?;
+    check_storage_proof_bounds(&storage_proof)?;
 
     check_commitment_path(
         &path,
@@ -109,13 +125,10 @@ fn check_commitment_path(
 /// Computes the commitment key for a given path and slot.
 #[must_use = "calculating the commitment path has no effect"]
 pub fn evm_ics26_commitment_path(ibc_path: &[u8], slot: U256) -> U256 {
-    let path_hash = keccak256(ibc_path);
+    let path_hash = keccak_256(ibc_path);
+    let preimage = [path_hash, slot.to_be_bytes::<32>()].concat();
 
-    let mut hasher = Keccak256::new();
-    hasher.update(path_hash);
-    hasher.update(slot.to_be_bytes_vec());
-
-    hasher.finalize().into()
+    U256::from_be_bytes(keccak_256(&preimage))
 }
 
 #[cfg(test)]
@@ -132,6 +145,13 @@ mod test {
         hex::{self, FromHex},
         Bytes, FixedBytes, B256, U256,
     };
+    #[cfg(feature = "tiny-keccak")]
+    use ethereum_trie_db::hasher::TinyKeccak;
+    use ethereum_trie_db::{
+        hasher::{CosmWasmKeccak, Keccak256Backend, Sha3Keccak},
+        trie_db::{verify_account_storage_root_with, verify_storage_inclusion_proof_with},
+        TrieDBError,
+    };
     use ethereum_types::execution::storage_proof::StorageProof;
     use ibc_proto_eureka::ibc::lightclients::wasm::v1::ClientMessage;
 
@@ -165,7 +185,7 @@ mod test {
         let mut latest_client_state = initial_state.client_state;
         for header in headers {
             let (_, updated_consensus_state, updated_client_state) =
-                update_consensus_state(latest_consensus_state, latest_client_state, header.clone())
+                update_consensus_state(latest_consensus_state, latest_client_state, &header)
                     .unwrap();
 
             latest_consensus_state = updated_consensus_state;
@@ -189,6 +209,94 @@ mod test {
         .unwrap();
     }
 
+    #[test]
+    fn test_keccak_backends_with_fixture() {
+        fn verify_with<K: Keccak256Backend>(
+            client_state: &ClientState,
+            headers: &[Header],
+            storage_proofs: &[StorageProof],
+        ) -> Result<(), TrieDBError> {
+            for header in headers {
+                verify_account_storage_root_with::<K>(
+                    header
+                        .consensus_update
+                        .finalized_header
+                        .execution
+                        .state_root,
+                    client_state.ibc_contract_address,
+                    &header.account_update.account_proof.proof,
+                    header.account_update.account_proof.storage_root,
+                )?;
+            }
+            // The packets are proven at the slot of the last header
+            let storage_root = headers
+                .last()
+                .unwrap()
+                .account_update
+                .account_proof
+                .storage_root;
+            for storage_proof in storage_proofs {
+                verify_storage_inclusion_proof_with::<K>(
+                    &storage_root,
+                    &storage_proof.key,
+                    &alloy_rlp::encode_fixed_size(&storage_proof.value),
+                    &storage_proof.proof,
+                )?;
+            }
+            Ok(())
+        }
+
+        let fixture: fixtures::StepsFixture =
+            fixtures::load("TestICS20TransferERC20TokenfromEthereumToCosmosAndBack_Groth16");
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, recv_msgs, _) = relayer_messages.get_sdk_msgs();
+
+        // The account and storage proofs returned by `eth_getProof` in the e2e test
+        let headers = update_client_msgs
+            .iter()
+            .map(|msg| {
+                let client_msg =
+                    ClientMessage::decode(msg.client_message.clone().unwrap().value.as_slice())
+                        .unwrap();
+                serde_json::from_slice(client_msg.data.as_slice()).unwrap()
+            })
+            .collect::<Vec<Header>>();
+        let storage_proofs = recv_msgs
+            .iter()
+            .map(|msg| serde_json::from_slice(&msg.proof_commitment).unwrap())
+            .collect::<Vec<StorageProof>>();
+        assert!(!headers.is_empty());
+        assert!(!storage_proofs.is_empty());
+        let client_state = initial_state.client_state;
+
+        verify_with::<Sha3Keccak>(&client_state, &headers, &storage_proofs).unwrap();
+        #[cfg(feature = "tiny-keccak")]
+        verify_with::<TinyKeccak>(&client_state, &headers, &storage_proofs).unwrap();
+
+        // The CosmWasm backend hashes with sha3 until the contract registers the function of its
+        // host, which is used from then on
+        verify_with::<CosmWasmKeccak>(&client_state, &headers, &storage_proofs).unwrap();
+        CosmWasmKeccak::register_host(|_| [0; 32]);
+        verify_with::<CosmWasmKeccak>(&client_state, &headers, &storage_proofs).unwrap_err();
+        CosmWasmKeccak::register_host(Sha3Keccak::keccak_256);
+        verify_with::<CosmWasmKeccak>(&client_state, &headers, &storage_proofs).unwrap();
+
+        // The backends hash every node of the proofs alike
+        let nodes = headers
+            .iter()
+            .flat_map(|header| &header.account_update.account_proof.proof)
+            .chain(storage_proofs.iter().flat_map(|proof| &proof.proof));
+        for node in nodes {
+            assert_eq!(
+                Sha3Keccak::keccak_256(node),
+                CosmWasmKeccak::keccak_256(node)
+            );
+            #[cfg(feature = "tiny-keccak")]
+            assert_eq!(Sha3Keccak::keccak_256(node), TinyKeccak::keccak_256(node));
+        }
+    }
+
     #[test]
     fn test_verify_membership() {
         let client_state: ClientState = ClientState {
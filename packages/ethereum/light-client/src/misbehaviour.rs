@@ -3,6 +3,7 @@
 use ethereum_types::consensus::light_client_header::LightClientUpdate;
 
 use crate::{
+    bounds::{check_light_client_update_bounds, check_sync_committee_bounds},
     client_state::ClientState,
     consensus_state::{ConsensusState, TrustedConsensusState},
     error::EthereumIBCError,
@@ -32,6 +33,10 @@ pub fn verify_misbehaviour<V: BlsVerify>(
     current_timestamp: u64,
     bls_verifier: V,
 ) -> Result<(), EthereumIBCError> {
+    check_sync_committee_bounds(full_sync_committee.sync_committee())?;
+    check_light_client_update_bounds(update_1)?;
+    check_light_client_update_bounds(update_2)?;
+
     let trusted_consensus_state = TrustedConsensusState::new(
         consensus_state.clone(),
         full_sync_committee.clone(),
@@ -0,0 +1,186 @@
+//! This module provides [`check_substitute`] function to check the substitute client a frozen or
+//! expired client is recovered with.
+//!
+//! Governance recovers a client by replacing its state with the latest state of a substitute
+//! client, created for the same chain after the subject client stopped being updated. The
+//! substitute must track the same chain and IBC contract as the subject, and be fresher than it.
+
+use crate::{
+    client_state::{ClientState, ConsensusStateWrites},
+    consensus_state::ConsensusState,
+    error::EthereumIBCError,
+};
+
+/// Checks the substitute client the subject client is recovered with, and returns the client
+/// state the subject client is recovered to.
+///
+/// * `subject_client_state`: The client state of the frozen or expired client.
+/// * `substitute_client_state`: The client state of the substitute client.
+/// * `substitute_consensus_state`: The consensus state at the latest slot of the substitute client.
+///
+/// The recovered client state keeps the parameters chosen by the subject client, like an upgraded
+/// one. The fork parameters are taken from the substitute, so that a client that stopped at a fork
+/// it did not know can be recovered with a substitute that knows it.
+///
+/// # Errors
+/// Returns an error if the substitute does not track the same chain, or is not fresher than the
+/// subject client.
+pub fn check_substitute(
+    subject_client_state: &ClientState,
+    substitute_client_state: &ClientState,
+    substitute_consensus_state: &ConsensusState,
+) -> Result<ClientState, EthereumIBCError> {
+    ensure!(
+        !substitute_client_state.is_frozen,
+        EthereumIBCError::SubstituteClientFrozen
+    );
+    ensure!(
+        substitute_client_state.latest_slot == substitute_consensus_state.slot,
+        EthereumIBCError::ClientAndConsensusSlotMismatch {
+            client_state_slot: substitute_client_state.latest_slot,
+            consensus_state_slot: substitute_consensus_state.slot,
+        }
+    );
+    ensure!(
+        substitute_client_state.latest_slot > subject_client_state.latest_slot,
+        EthereumIBCError::SubstituteSlotNotIncreasing {
+            subject_slot: subject_client_state.latest_slot,
+            substitute_slot: substitute_client_state.latest_slot,
+        }
+    );
+
+    let recovered_client_state = ClientState {
+        latest_slot: substitute_client_state.latest_slot,
+        latest_execution_block_number: substitute_client_state.latest_execution_block_number,
+        fork_parameters: substitute_client_state.fork_parameters.clone(),
+        is_frozen: false,
+        ..subject_client_state.clone()
+    };
+    // Besides the fields taken from the substitute, only the parameters chosen by the clients may
+    // differ
+    let chain_parameters = |client_state: &ClientState| ClientState {
+        min_sync_committee_participants: 0,
+        compressed_client_messages: false,
+        consensus_state_writes: ConsensusStateWrites::default(),
+        min_slot_advance: None,
+        consensus_state_retention: None,
+        trusting_period: None,
+        ..client_state.clone()
+    };
+    ensure!(
+        chain_parameters(&recovered_client_state) == chain_parameters(substitute_client_state),
+        EthereumIBCError::SubstituteClientMismatch
+    );
+
+    Ok(recovered_client_state)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::Address;
+
+    use crate::{
+        client_state::ConsensusStateWrites, error::EthereumIBCError,
+        test_utils::update_generator::UpdateGenerator,
+    };
+
+    use super::check_substitute;
+
+    #[test]
+    fn test_check_substitute() {
+        let generator = UpdateGenerator::new(16);
+        let (mut subject_client_state, _) =
+            generator.initial_states(generator.period_start_slot(1));
+        subject_client_state.is_frozen = true;
+        subject_client_state.min_slot_advance = Some(8);
+        subject_client_state.trusting_period = Some(86_400);
+        let (mut substitute_client_state, substitute_consensus_state) =
+            generator.initial_states(generator.period_start_slot(3));
+        substitute_client_state.consensus_state_writes = ConsensusStateWrites::PeriodBoundaries;
+        substitute_client_state.fork_parameters.electra.epoch += 1;
+
+        let recovered_client_state = check_substitute(
+            &subject_client_state,
+            &substitute_client_state,
+            &substitute_consensus_state,
+        )
+        .unwrap();
+        assert!(!recovered_client_state.is_frozen);
+        assert_eq!(
+            recovered_client_state.latest_slot,
+            substitute_client_state.latest_slot
+        );
+        assert_eq!(
+            recovered_client_state.fork_parameters,
+            substitute_client_state.fork_parameters
+        );
+        // The parameters chosen by the subject client are kept
+        assert_eq!(recovered_client_state.min_slot_advance, Some(8));
+        assert_eq!(recovered_client_state.trusting_period, Some(86_400));
+        assert_eq!(
+            recovered_client_state.consensus_state_writes,
+            ConsensusStateWrites::All
+        );
+    }
+
+    #[test]
+    fn test_check_substitute_rejects_invalid_substitutes() {
+        let generator = UpdateGenerator::new(16);
+        let (subject_client_state, _) = generator.initial_states(generator.period_start_slot(2));
+        let (substitute_client_state, substitute_consensus_state) =
+            generator.initial_states(generator.period_start_slot(3));
+        let check = |substitute_client_state| {
+            check_substitute(
+                &subject_client_state,
+                &substitute_client_state,
+                &substitute_consensus_state,
+            )
+            .unwrap_err()
+        };
+
+        // A substitute of another chain
+        let mut other_chain = substitute_client_state.clone();
+        other_chain.chain_id += 1;
+        assert_eq!(
+            check(other_chain),
+            EthereumIBCError::SubstituteClientMismatch
+        );
+
+        // A substitute of another IBC contract
+        let mut other_contract = substitute_client_state.clone();
+        other_contract.ibc_contract_address = Address::repeat_byte(0x42);
+        assert_eq!(
+            check(other_contract),
+            EthereumIBCError::SubstituteClientMismatch
+        );
+
+        // A substitute that is not fresher than the subject client
+        let (stale_client_state, stale_consensus_state) =
+            generator.initial_states(generator.period_start_slot(1));
+        assert_eq!(
+            check_substitute(
+                &subject_client_state,
+                &stale_client_state,
+                &stale_consensus_state,
+            )
+            .unwrap_err(),
+            EthereumIBCError::SubstituteSlotNotIncreasing {
+                subject_slot: subject_client_state.latest_slot,
+                substitute_slot: stale_client_state.latest_slot,
+            }
+        );
+
+        // A frozen substitute
+        let mut frozen = substitute_client_state.clone();
+        frozen.is_frozen = true;
+        assert_eq!(check(frozen), EthereumIBCError::SubstituteClientFrozen);
+
+        // A consensus state that is not the latest one of the substitute
+        let mut ahead = substitute_client_state;
+        ahead.latest_slot += 1;
+        assert!(matches!(
+            check(ahead),
+            EthereumIBCError::ClientAndConsensusSlotMismatch { .. }
+        ));
+    }
+}
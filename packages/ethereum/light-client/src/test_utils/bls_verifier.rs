@@ -3,10 +3,29 @@
 use ethereum_types::consensus::bls::{BlsPublicKey, BlsSignature};
 use thiserror::Error;
 
+use crate::verify::BlsVerify;
+
 /// Test implementation of the BLS verifier
 #[allow(clippy::module_name_repetitions)]
 pub struct TestBlsVerifier;
 
+impl BlsVerify for TestBlsVerifier {
+    type Error = BlsError;
+
+    fn fast_aggregate_verify(
+        &self,
+        public_keys: &[BlsPublicKey],
+        msg: alloy_primitives::B256,
+        signature: BlsSignature,
+    ) -> Result<(), BlsError> {
+        fast_aggregate_verify(public_keys, msg, signature)
+    }
+
+    fn aggregate(&self, public_keys: &[BlsPublicKey]) -> Result<BlsPublicKey, BlsError> {
+        aggreagate(public_keys)
+    }
+}
+
 #[derive(Error, Debug)]
 #[allow(missing_docs)]
 pub enum BlsError {
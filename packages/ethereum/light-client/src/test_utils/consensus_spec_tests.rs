@@ -0,0 +1,693 @@
+//! This module runs the light client `sync` test vectors from the upstream
+//! [consensus-spec-tests](https://github.com/ethereum/consensus-spec-tests) against the
+//! verification and update functions of the light client.
+//!
+//! The vectors are not vendored in this repository. Download them with
+//! `just download-consensus-spec-tests` and point `CONSENSUS_SPEC_TESTS_DIR` at the extracted
+//! `tests` directory, the conformance test is skipped when the variable is not set.
+//!
+//! Only the forks supported by the light client (Deneb and later) are run. Test cases that rely on
+//! `force_update` are reported as skipped, since the light client never applies updates without a
+//! supermajority of the sync committee.
+
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::{aliases::B32, Address, Bloom, Bytes, FixedBytes, B256, U256};
+use ethereum_types::consensus::{
+    bootstrap::LightClientBootstrap,
+    fork::{Fork, ForkParameters},
+    light_client_header::{
+        BeaconBlockHeader, ExecutionPayloadHeader, LightClientHeader, LightClientUpdate,
+    },
+    sync_committee::{SyncAggregate, SyncCommittee},
+};
+use serde::Deserialize;
+use tree_hash::TreeHash;
+
+use crate::{
+    client_state::ClientState,
+    consensus_state::{ConsensusState, TrustedConsensusState},
+    error::EthereumIBCError,
+    header::{AccountUpdate, ActiveSyncCommittee, Header},
+    sync_protocol_helpers::{
+        current_sync_committee_gindex_at_slot, is_valid_light_client_header,
+        normalize_merkle_branch,
+    },
+    test_utils::bls_verifier::TestBlsVerifier,
+    trie::is_valid_normalized_merkle_branch,
+    update::update_consensus_state,
+    verify::validate_light_client_update,
+};
+
+/// The environment variable pointing at the extracted `tests` directory of the consensus-spec-tests
+const CONSENSUS_SPEC_TESTS_DIR_ENV: &str = "CONSENSUS_SPEC_TESTS_DIR";
+
+/// The forks of the consensus-spec-tests that the light client supports
+const SUPPORTED_FORKS: [&str; 2] = ["deneb", "electra"];
+
+/// The size of the fixed part of an SSZ encoded Deneb `ExecutionPayloadHeader`
+const EXECUTION_PAYLOAD_HEADER_FIXED_SIZE: usize = 584;
+/// The size of an SSZ offset
+const OFFSET_SIZE: usize = 4;
+
+/// The presets of the consensus-spec-tests
+#[derive(Clone, Copy, Debug)]
+enum Preset {
+    Minimal,
+    Mainnet,
+}
+
+impl Preset {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Minimal => "minimal",
+            Self::Mainnet => "mainnet",
+        }
+    }
+
+    const fn sync_committee_size(self) -> usize {
+        match self {
+            Self::Minimal => 32,
+            Self::Mainnet => 512,
+        }
+    }
+
+    const fn slots_per_epoch(self) -> u64 {
+        match self {
+            Self::Minimal => 8,
+            Self::Mainnet => 32,
+        }
+    }
+
+    const fn epochs_per_sync_committee_period(self) -> u64 {
+        match self {
+            Self::Minimal => 8,
+            Self::Mainnet => 256,
+        }
+    }
+}
+
+/// The `meta.yaml` of a `sync` test case
+#[derive(Deserialize)]
+struct SyncTestMeta {
+    genesis_validators_root: B256,
+    trusted_block_root: B256,
+}
+
+/// A step in the `steps.yaml` of a `sync` test case
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SyncTestStep {
+    ProcessUpdate {
+        update: String,
+        current_slot: u64,
+        checks: SyncTestChecks,
+    },
+    ForceUpdate {},
+    UpgradeStore {
+        checks: SyncTestChecks,
+    },
+}
+
+/// The expected light client store after a step
+#[derive(Deserialize)]
+struct SyncTestChecks {
+    finalized_header: HeaderCheck,
+}
+
+/// The expected header in the light client store
+#[derive(Deserialize)]
+struct HeaderCheck {
+    slot: u64,
+    beacon_root: B256,
+}
+
+/// The outcome of running all the test cases of a test suite
+#[derive(Default)]
+struct SpecTestSummary {
+    passed: Vec<String>,
+    skipped: Vec<String>,
+    failed: Vec<(String, String)>,
+}
+
+/// A light client store driven by the light client's verification and update functions
+struct SpecTestStore {
+    client_state: ClientState,
+    consensus_state: ConsensusState,
+    current_sync_committee: SyncCommittee,
+    next_sync_committee: Option<SyncCommittee>,
+    finalized_header: LightClientHeader,
+}
+
+impl SpecTestStore {
+    /// Initializes the store from a bootstrap, as in `initialize_light_client_store`
+    fn new(
+        client_state: ClientState,
+        trusted_block_root: B256,
+        bootstrap: LightClientBootstrap,
+    ) -> Result<Self, String> {
+        is_valid_light_client_header(&client_state, &bootstrap.header)
+            .map_err(|e| format!("invalid bootstrap header: {e}"))?;
+        if bootstrap.header.beacon.tree_hash_root() != trusted_block_root {
+            return Err("bootstrap header does not match the trusted block root".to_string());
+        }
+
+        let gindex =
+            current_sync_committee_gindex_at_slot(&client_state, bootstrap.header.beacon.slot)
+                .map_err(|e| e.to_string())?;
+        is_valid_normalized_merkle_branch(
+            bootstrap.current_sync_committee.tree_hash_root(),
+            &normalize_merkle_branch(&bootstrap.current_sync_committee_branch, gindex),
+            gindex,
+            bootstrap.header.beacon.state_root,
+        )
+        .map_err(|e| format!("invalid bootstrap sync committee branch: {e}"))?;
+
+        let consensus_state = ConsensusState {
+            slot: bootstrap.header.beacon.slot,
+            state_root: bootstrap.header.execution.state_root,
+            storage_root: B256::default(),
+            timestamp: bootstrap.header.execution.timestamp,
+            current_sync_committee: bootstrap.current_sync_committee.aggregate_pubkey,
+            next_sync_committee: None,
+        };
+        let client_state = ClientState {
+            latest_slot: bootstrap.header.beacon.slot,
+            latest_execution_block_number: bootstrap.header.execution.block_number,
+            ..client_state
+        };
+
+        Ok(Self {
+            client_state,
+            consensus_state,
+            current_sync_committee: bootstrap.current_sync_committee,
+            next_sync_committee: None,
+            finalized_header: bootstrap.header,
+        })
+    }
+
+    /// Verifies and applies an update, as in `process_light_client_update`
+    fn process_update(
+        &mut self,
+        update: LightClientUpdate,
+        current_slot: u64,
+    ) -> Result<(), EthereumIBCError> {
+        let store_period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(self.consensus_state.slot);
+        let signature_period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(update.signature_slot);
+        let active_sync_committee = if signature_period == store_period {
+            ActiveSyncCommittee::Current(self.current_sync_committee.clone())
+        } else {
+            ActiveSyncCommittee::Next(
+                self.next_sync_committee
+                    .clone()
+                    .ok_or(EthereumIBCError::NextSyncCommitteeUnknown)?,
+            )
+        };
+
+        let trusted_consensus_state = TrustedConsensusState::new(
+            self.consensus_state.clone(),
+            active_sync_committee.clone(),
+            &TestBlsVerifier,
+        )?;
+        validate_light_client_update(
+            &self.client_state,
+            &trusted_consensus_state,
+            &update,
+            current_slot,
+            &TestBlsVerifier,
+        )?;
+        ensure!(
+            update.sync_aggregate.validate_signature_supermajority(),
+            EthereumIBCError::NotEnoughSignatures
+        );
+
+        let (_, consensus_state, client_state) = update_consensus_state(
+            self.consensus_state.clone(),
+            self.client_state.clone(),
+            &Header {
+                active_sync_committee,
+                consensus_update: update.clone(),
+                account_update: AccountUpdate::default(),
+            },
+        )?;
+
+        // The consensus state only stores the aggregate keys, so the full committees are tracked
+        // alongside it to build the active sync committee of the next update.
+        if consensus_state.current_sync_committee != self.consensus_state.current_sync_committee {
+            self.current_sync_committee = self
+                .next_sync_committee
+                .take()
+                .ok_or(EthereumIBCError::NextSyncCommitteeUnknown)?;
+            self.next_sync_committee = update.next_sync_committee;
+        } else if self.next_sync_committee.is_none() {
+            self.next_sync_committee = update.next_sync_committee;
+        }
+
+        self.consensus_state = consensus_state;
+        if let Some(client_state) = client_state {
+            self.client_state = client_state;
+        }
+        self.finalized_header = update.finalized_header;
+
+        Ok(())
+    }
+
+    /// Compares the store against the expected checks of a step
+    fn check(&self, checks: &SyncTestChecks) -> Result<(), String> {
+        let expected = &checks.finalized_header;
+        let found_root = self.finalized_header.beacon.tree_hash_root();
+        if self.finalized_header.beacon.slot != expected.slot || found_root != expected.beacon_root
+        {
+            return Err(format!(
+                "finalized header mismatch: expected slot {} ({}), found slot {} ({found_root})",
+                expected.slot, expected.beacon_root, self.finalized_header.beacon.slot,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the consensus-spec-tests directory if it is configured
+fn consensus_spec_tests_dir() -> Option<PathBuf> {
+    std::env::var_os(CONSENSUS_SPEC_TESTS_DIR_ENV).map(PathBuf::from)
+}
+
+/// Runs all the `light_client/sync` test cases of a fork and preset
+fn run_sync_tests(root: &Path, preset: Preset, fork: &str) -> SpecTestSummary {
+    let mut summary = SpecTestSummary::default();
+    let suite_dir = root
+        .join(preset.name())
+        .join(fork)
+        .join("light_client/sync/pyspec_tests");
+    let Ok(entries) = std::fs::read_dir(&suite_dir) else {
+        return summary;
+    };
+
+    let mut case_dirs = entries
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    case_dirs.sort();
+
+    for case_dir in case_dirs {
+        let case_name = format!(
+            "{}/{fork}/{}",
+            preset.name(),
+            case_dir.file_name().unwrap().to_string_lossy()
+        );
+        match run_sync_test_case(&case_dir, preset) {
+            Ok(true) => summary.passed.push(case_name),
+            Ok(false) => summary.skipped.push(case_name),
+            Err(err) => summary.failed.push((case_name, err)),
+        }
+    }
+
+    summary
+}
+
+/// Runs a single `sync` test case
+/// Returns `false` if the test case was skipped because it uses unsupported steps
+fn run_sync_test_case(case_dir: &Path, preset: Preset) -> Result<bool, String> {
+    let meta: SyncTestMeta = read_yaml(&case_dir.join("meta.yaml"))?;
+    let config: serde_yaml::Value = read_yaml(&case_dir.join("config.yaml"))?;
+    let steps: Vec<SyncTestStep> = read_yaml(&case_dir.join("steps.yaml"))?;
+
+    if steps
+        .iter()
+        .any(|step| matches!(step, SyncTestStep::ForceUpdate {}))
+    {
+        return Ok(false);
+    }
+
+    let client_state = client_state_from_config(&config, preset, meta.genesis_validators_root)?;
+    let bootstrap = decode_bootstrap(
+        &read_ssz_snappy(&case_dir.join("bootstrap.ssz_snappy"))?,
+        preset.sync_committee_size(),
+    )?;
+    let mut store = SpecTestStore::new(client_state, meta.trusted_block_root, bootstrap)?;
+
+    for (i, step) in steps.into_iter().enumerate() {
+        let checks = match step {
+            SyncTestStep::ProcessUpdate {
+                update,
+                current_slot,
+                checks,
+            } => {
+                let update = decode_update(
+                    &read_ssz_snappy(&case_dir.join(format!("{update}.ssz_snappy")))?,
+                    preset.sync_committee_size(),
+                )?;
+                // A rejected update leaves the store unchanged, which the checks below verify
+                let _ = store.process_update(update, current_slot);
+                checks
+            }
+            // The light client types are shared between the supported forks
+            SyncTestStep::UpgradeStore { checks } => checks,
+            SyncTestStep::ForceUpdate {} => unreachable!(),
+        };
+
+        store.check(&checks).map_err(|e| format!("step {i}: {e}"))?;
+    }
+
+    Ok(true)
+}
+
+/// Builds the client state from the `config.yaml` of a test case
+fn client_state_from_config(
+    config: &serde_yaml::Value,
+    preset: Preset,
+    genesis_validators_root: B256,
+) -> Result<ClientState, String> {
+    let fork = |name: &str| -> Result<Fork, String> {
+        Ok(Fork {
+            version: config_version(config, &format!("{name}_FORK_VERSION"))?,
+            epoch: config_u64(config, &format!("{name}_FORK_EPOCH"))?,
+        })
+    };
+
+    Ok(ClientState {
+        genesis_validators_root,
+        // MIN_SYNC_COMMITTEE_PARTICIPANTS is the same in all presets
+        min_sync_committee_participants: 1,
+        genesis_time: 0,
+        genesis_slot: 0,
+        fork_parameters: ForkParameters {
+            genesis_fork_version: config_version(config, "GENESIS_FORK_VERSION")?,
+            genesis_slot: 0,
+            altair: fork("ALTAIR")?,
+            bellatrix: fork("BELLATRIX")?,
+            capella: fork("CAPELLA")?,
+            deneb: fork("DENEB")?,
+            electra: fork("ELECTRA")?,
+        },
+        seconds_per_slot: config_u64(config, "SECONDS_PER_SLOT")?,
+        slots_per_epoch: preset.slots_per_epoch(),
+        epochs_per_sync_committee_period: preset.epochs_per_sync_committee_period(),
+        ..Default::default()
+    })
+}
+
+/// Reads an integer from the `config.yaml`, which may be quoted
+fn config_u64(config: &serde_yaml::Value, key: &str) -> Result<u64, String> {
+    let value = config
+        .get(key)
+        .ok_or_else(|| format!("missing config key {key}"))?;
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(|| format!("invalid config value for {key}"))
+}
+
+/// Reads a fork version from the `config.yaml`
+fn config_version(config: &serde_yaml::Value, key: &str) -> Result<B32, String> {
+    config
+        .get(key)
+        .and_then(serde_yaml::Value::as_str)
+        .ok_or_else(|| format!("missing config key {key}"))?
+        .parse()
+        .map_err(|e| format!("invalid config value for {key}: {e}"))
+}
+
+/// Reads and deserializes a yaml file
+fn read_yaml<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    serde_yaml::from_reader(file).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Reads and decompresses a snappy compressed SSZ file
+fn read_ssz_snappy(path: &Path) -> Result<Vec<u8>, String> {
+    let compressed = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    snap::raw::Decoder::new()
+        .decompress_vec(&compressed)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// A cursor over the fixed part of an SSZ container
+struct SszReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> SszReader<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + len)
+            .ok_or_else(|| format!("ssz: unexpected end of input at {}", self.position))?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn offset(&mut self) -> Result<usize, String> {
+        Ok(u32::from_le_bytes(self.take(OFFSET_SIZE)?.try_into().unwrap()) as usize)
+    }
+
+    fn fixed<const N: usize>(&mut self) -> Result<FixedBytes<N>, String> {
+        Ok(FixedBytes::from_slice(self.take(N)?))
+    }
+
+    fn branch(&mut self, depth: usize) -> Result<Vec<B256>, String> {
+        (0..depth).map(|_| self.fixed()).collect()
+    }
+
+    fn sync_committee(&mut self, size: usize) -> Result<SyncCommittee, String> {
+        Ok(SyncCommittee {
+            pubkeys: (0..size).map(|_| self.fixed()).collect::<Result<_, _>>()?,
+            aggregate_pubkey: self.fixed()?,
+        })
+    }
+
+    fn sync_aggregate(&mut self, size: usize) -> Result<SyncAggregate, String> {
+        Ok(SyncAggregate {
+            sync_committee_bits: Bytes::copy_from_slice(self.take(size / 8)?),
+            sync_committee_signature: self.fixed()?,
+        })
+    }
+}
+
+/// Returns the variable part of an SSZ container between two offsets
+fn ssz_slice(bytes: &[u8], start: usize, end: usize) -> Result<&[u8], String> {
+    bytes
+        .get(start..end)
+        .ok_or_else(|| format!("ssz: invalid offsets {start}..{end}"))
+}
+
+/// Returns the size of an SSZ encoded sync committee
+const fn sync_committee_ssz_size(size: usize) -> usize {
+    (size + 1) * 48
+}
+
+/// Returns the size of an SSZ encoded sync aggregate
+const fn sync_aggregate_ssz_size(size: usize) -> usize {
+    size / 8 + 96
+}
+
+/// Decodes an SSZ encoded `LightClientHeader` (Deneb and later)
+fn decode_light_client_header(bytes: &[u8]) -> Result<LightClientHeader, String> {
+    let mut reader = SszReader::new(bytes);
+    let beacon = BeaconBlockHeader {
+        slot: reader.u64()?,
+        proposer_index: reader.u64()?,
+        parent_root: reader.fixed()?,
+        state_root: reader.fixed()?,
+        body_root: reader.fixed()?,
+    };
+    let execution_offset = reader.offset()?;
+    let execution_branch = reader
+        .branch(4)?
+        .try_into()
+        .map_err(|_| "ssz: invalid execution branch".to_string())?;
+    let execution =
+        decode_execution_payload_header(ssz_slice(bytes, execution_offset, bytes.len())?)?;
+
+    Ok(LightClientHeader {
+        beacon,
+        execution,
+        execution_branch,
+    })
+}
+
+/// Decodes an SSZ encoded `ExecutionPayloadHeader` (Deneb and later)
+fn decode_execution_payload_header(bytes: &[u8]) -> Result<ExecutionPayloadHeader, String> {
+    let mut reader = SszReader::new(bytes);
+    let parent_hash = reader.fixed()?;
+    let fee_recipient = Address::from_slice(reader.take(20)?);
+    let state_root = reader.fixed()?;
+    let receipts_root = reader.fixed()?;
+    let logs_bloom = Bloom::from_slice(reader.take(256)?);
+    let prev_randao = reader.fixed()?;
+    let block_number = reader.u64()?;
+    let gas_limit = reader.u64()?;
+    let gas_used = reader.u64()?;
+    let timestamp = reader.u64()?;
+    let extra_data_offset = reader.offset()?;
+    let base_fee_per_gas = U256::from_le_slice(reader.take(32)?);
+    let block_hash = reader.fixed()?;
+    let transactions_root = reader.fixed()?;
+    let withdrawals_root = reader.fixed()?;
+    let blob_gas_used = reader.u64()?;
+    let excess_blob_gas = reader.u64()?;
+    if extra_data_offset != EXECUTION_PAYLOAD_HEADER_FIXED_SIZE {
+        return Err(format!(
+            "ssz: invalid extra data offset {extra_data_offset}"
+        ));
+    }
+
+    Ok(ExecutionPayloadHeader {
+        parent_hash,
+        fee_recipient,
+        state_root,
+        receipts_root,
+        logs_bloom,
+        prev_randao,
+        block_number,
+        gas_limit,
+        gas_used,
+        timestamp,
+        extra_data: Bytes::copy_from_slice(ssz_slice(bytes, extra_data_offset, bytes.len())?),
+        base_fee_per_gas,
+        block_hash,
+        transactions_root,
+        withdrawals_root,
+        blob_gas_used,
+        excess_blob_gas,
+    })
+}
+
+/// Decodes an SSZ encoded `LightClientBootstrap`
+///
+/// The depth of the sync committee branch differs between forks, so it is derived from the size of
+/// the fixed part of the container, which is the offset of the header.
+fn decode_bootstrap(
+    bytes: &[u8],
+    sync_committee_size: usize,
+) -> Result<LightClientBootstrap, String> {
+    let mut reader = SszReader::new(bytes);
+    let header_offset = reader.offset()?;
+    let branch_size = header_offset
+        .checked_sub(OFFSET_SIZE + sync_committee_ssz_size(sync_committee_size))
+        .filter(|size| size % 32 == 0)
+        .ok_or_else(|| format!("ssz: invalid bootstrap header offset {header_offset}"))?;
+
+    let current_sync_committee = reader.sync_committee(sync_committee_size)?;
+    let current_sync_committee_branch = reader.branch(branch_size / 32)?;
+    let header = decode_light_client_header(ssz_slice(bytes, header_offset, bytes.len())?)?;
+
+    Ok(LightClientBootstrap {
+        header,
+        current_sync_committee,
+        current_sync_committee_branch,
+    })
+}
+
+/// Decodes an SSZ encoded `LightClientUpdate`
+///
+/// The finality branch is always one level deeper than the next sync committee branch, which lets
+/// their depths be derived from the size of the fixed part of the container.
+/// An all zero next sync committee branch means that the update does not carry a next sync
+/// committee, as in `is_sync_committee_update`.
+fn decode_update(bytes: &[u8], sync_committee_size: usize) -> Result<LightClientUpdate, String> {
+    let mut reader = SszReader::new(bytes);
+    let attested_header_offset = reader.offset()?;
+    let branches_size = attested_header_offset
+        .checked_sub(
+            2 * OFFSET_SIZE
+                + sync_committee_ssz_size(sync_committee_size)
+                + sync_aggregate_ssz_size(sync_committee_size)
+                + 8
+                + 32,
+        )
+        .filter(|size| size % 64 == 0)
+        .ok_or_else(|| format!("ssz: invalid update header offset {attested_header_offset}"))?;
+    let next_sync_committee_branch_depth = branches_size / 64;
+
+    let next_sync_committee = reader.sync_committee(sync_committee_size)?;
+    let next_sync_committee_branch = reader.branch(next_sync_committee_branch_depth)?;
+    let finalized_header_offset = reader.offset()?;
+    let finality_branch = reader.branch(next_sync_committee_branch_depth + 1)?;
+    let sync_aggregate = reader.sync_aggregate(sync_committee_size)?;
+    let signature_slot = reader.u64()?;
+
+    let attested_header = decode_light_client_header(ssz_slice(
+        bytes,
+        attested_header_offset,
+        finalized_header_offset,
+    )?)?;
+    let finalized_header =
+        decode_light_client_header(ssz_slice(bytes, finalized_header_offset, bytes.len())?)?;
+
+    let is_sync_committee_update = next_sync_committee_branch
+        .iter()
+        .any(|node| *node != B256::ZERO);
+    let (next_sync_committee, next_sync_committee_branch) = if is_sync_committee_update {
+        (Some(next_sync_committee), Some(next_sync_committee_branch))
+    } else {
+        (None, None)
+    };
+
+    Ok(LightClientUpdate {
+        attested_header,
+        next_sync_committee,
+        next_sync_committee_branch,
+        finalized_header,
+        finality_branch,
+        sync_aggregate,
+        signature_slot,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_consensus_spec_tests_light_client_sync() {
+        let Some(root) = consensus_spec_tests_dir() else {
+            eprintln!(
+                "{CONSENSUS_SPEC_TESTS_DIR_ENV} is not set, skipping the consensus-spec-tests"
+            );
+            return;
+        };
+
+        let mut summary = SpecTestSummary::default();
+        for preset in [Preset::Minimal, Preset::Mainnet] {
+            for fork in SUPPORTED_FORKS {
+                let fork_summary = run_sync_tests(&root, preset, fork);
+                summary.passed.extend(fork_summary.passed);
+                summary.skipped.extend(fork_summary.skipped);
+                summary.failed.extend(fork_summary.failed);
+            }
+        }
+
+        for case in &summary.skipped {
+            eprintln!("skipped {case}: force updates are not supported");
+        }
+        for (case, err) in &summary.failed {
+            eprintln!("failed {case}: {err}");
+        }
+
+        assert!(
+            !summary.passed.is_empty() || !summary.failed.is_empty(),
+            "no light client sync test vectors found in {}",
+            root.display()
+        );
+        assert!(
+            summary.failed.is_empty(),
+            "{} of {} light client sync test cases failed",
+            summary.failed.len(),
+            summary.passed.len() + summary.failed.len()
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Semantic comparison of [`StepsFixture`]s
+//!
+//! Regenerated fixtures are JSON blobs of hex encoded transactions that cannot be reviewed as is.
+//! The steps are instead summarized into what the tests depend on, i.e. the client and consensus
+//! states, the slots of the headers and the keys of the proven paths, and the summaries of two
+//! fixtures are compared step by step.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{canonical::canonical_hash, header::Header};
+use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs;
+use ibc_proto_eureka::{
+    cosmos::tx::v1beta1::TxBody,
+    ibc::{
+        core::{
+            channel::v2::{MsgAcknowledgement, MsgRecvPacket, MsgTimeout, Packet},
+            client::v1::MsgUpdateClient,
+        },
+        lightclients::wasm::v1::ClientMessage,
+    },
+};
+use prost::Message;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::fixtures::{InitialState, RelayerMessages, Step, StepsFixture};
+
+/// The summary of a step of a fixture, which is what the tests depend on
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StepSummary {
+    /// The name of the step
+    pub name: String,
+    /// The scalar fields of the step, such as the fields of the client state
+    pub fields: BTreeMap<String, String>,
+    /// The finalized slots of the headers of the update client messages, in order
+    pub update_slots: Vec<u64>,
+    /// The proven paths of the packet messages, in order
+    pub proof_keys: Vec<String>,
+}
+
+impl StepSummary {
+    /// Summarizes a step
+    /// # Errors
+    /// Returns an error if the data of the step does not match its expected type, or if a message
+    /// of a relayer transaction cannot be decoded
+    pub fn of(step: &Step) -> Result<Self, String> {
+        let mut summary = Self {
+            name: step.name.clone(),
+            ..Default::default()
+        };
+        match step.name.as_str() {
+            "initial_state" => {
+                let initial_state: InitialState = from_value(&step.data)?;
+                summary.insert_fields("client_state", &initial_state.client_state)?;
+                summary.insert_fields("consensus_state", &initial_state.consensus_state)?;
+            }
+            "receive_packets" | "ack_packets" | "timeout_packets" => {
+                let relayer_messages: RelayerMessages = from_value(&step.data)?;
+                summary.insert_relayer_messages(&relayer_messages)?;
+            }
+            _ => {
+                summary.fields.insert(
+                    "data_sha256".to_string(),
+                    hex::encode(canonical_hash(&step.data).map_err(|e| e.to_string())?),
+                );
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Inserts the top level fields of `value` as `<prefix>.<field>`
+    fn insert_fields(&mut self, prefix: &str, value: &impl Serialize) -> Result<(), String> {
+        let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+        let Value::Object(fields) = value else {
+            return Err(format!("{prefix} is not an object"));
+        };
+        self.fields.extend(
+            fields
+                .into_iter()
+                .map(|(field, value)| (format!("{prefix}.{field}"), value.to_string())),
+        );
+        Ok(())
+    }
+
+    /// Summarizes the messages of a relayer transaction
+    fn insert_relayer_messages(
+        &mut self,
+        relayer_messages: &RelayerMessages,
+    ) -> Result<(), String> {
+        let tx_body_bz =
+            hex::decode(&relayer_messages.relayer_tx_body).map_err(|e| e.to_string())?;
+        let tx_body = TxBody::decode(tx_body_bz.as_slice()).map_err(|e| e.to_string())?;
+
+        let mut message_counts = BTreeMap::<&str, usize>::new();
+        for msg in &tx_body.messages {
+            let value = msg.value.as_slice();
+            match msg.type_url.as_str() {
+                "/ibc.core.client.v1.MsgUpdateClient" => {
+                    let msg = MsgUpdateClient::decode(value).map_err(|e| e.to_string())?;
+                    self.update_slots.push(header_slot(&msg)?);
+                }
+                "/ibc.core.channel.v2.MsgRecvPacket" => {
+                    let msg = MsgRecvPacket::decode(value).map_err(|e| e.to_string())?;
+                    self.proof_keys
+                        .push(proof_key(msg.packet, ProofKind::Commitment)?);
+                }
+                "/ibc.core.channel.v2.MsgAcknowledgement" => {
+                    let msg = MsgAcknowledgement::decode(value).map_err(|e| e.to_string())?;
+                    self.proof_keys.push(proof_key(msg.packet, ProofKind::Ack)?);
+                }
+                "/ibc.core.channel.v2.MsgTimeout" => {
+                    let msg = MsgTimeout::decode(value).map_err(|e| e.to_string())?;
+                    self.proof_keys
+                        .push(proof_key(msg.packet, ProofKind::Receipt)?);
+                }
+                type_url => return Err(format!("unknown message type: {type_url}")),
+            }
+            *message_counts.entry(msg.type_url.as_str()).or_default() += 1;
+        }
+
+        self.fields.extend(
+            message_counts
+                .into_iter()
+                .map(|(type_url, count)| (format!("messages.{type_url}"), count.to_string())),
+        );
+        Ok(())
+    }
+}
+
+/// The kind of path proven by a packet message
+#[derive(Clone, Copy)]
+enum ProofKind {
+    Commitment,
+    Ack,
+    Receipt,
+}
+
+/// Returns the proven path of a packet message as `<kind> <client>/<sequence>`
+fn proof_key(packet: Option<Packet>, kind: ProofKind) -> Result<String, String> {
+    let packet: IICS26RouterMsgs::Packet = packet.ok_or("packet message without packet")?.into();
+    let (kind, client_id) = match kind {
+        ProofKind::Commitment => ("commitment", &packet.sourceClient),
+        ProofKind::Ack => ("ack", &packet.destClient),
+        ProofKind::Receipt => ("receipt", &packet.destClient),
+    };
+    Ok(format!("{kind} {client_id}/{}", packet.sequence))
+}
+
+/// Returns the finalized slot of the header of an update client message
+fn header_slot(msg: &MsgUpdateClient) -> Result<u64, String> {
+    let client_message = msg
+        .client_message
+        .as_ref()
+        .ok_or("update client message without client message")?;
+    let client_message =
+        ClientMessage::decode(client_message.value.as_slice()).map_err(|e| e.to_string())?;
+    let header: Header = serde_json::from_slice(&client_message.data).map_err(|e| e.to_string())?;
+    Ok(header.consensus_update.finalized_header.beacon.slot)
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, String> {
+    serde_json::from_value(value.clone()).map_err(|e| e.to_string())
+}
+
+/// Compares two fixtures step by step
+/// # Returns
+/// The differences from `old` to `new`, one per line, which is empty if the fixtures are
+/// equivalent
+/// # Errors
+/// Returns an error if a step of either fixture cannot be summarized
+pub fn diff_fixtures(old: &StepsFixture, new: &StepsFixture) -> Result<Vec<String>, String> {
+    let mut diffs = Vec::new();
+    if old.version != new.version {
+        diffs.push(format!("version: {} -> {}", old.version, new.version));
+    }
+
+    for i in 0..old.steps.len().max(new.steps.len()) {
+        match (old.steps.get(i), new.steps.get(i)) {
+            (Some(old_step), Some(new_step)) => {
+                let prefix = format!("step {i} (`{}`)", new_step.name);
+                diffs.extend(
+                    diff_steps(&StepSummary::of(old_step)?, &StepSummary::of(new_step)?)
+                        .into_iter()
+                        .map(|diff| format!("{prefix}: {diff}")),
+                );
+            }
+            (Some(old_step), None) => {
+                diffs.push(format!("step {i} (`{}`): removed", old_step.name))
+            }
+            (None, Some(new_step)) => diffs.push(format!("step {i} (`{}`): added", new_step.name)),
+            (None, None) => unreachable!(),
+        }
+    }
+    Ok(diffs)
+}
+
+/// Compares the summaries of two steps
+fn diff_steps(old: &StepSummary, new: &StepSummary) -> Vec<String> {
+    let mut diffs = Vec::new();
+    if old.name != new.name {
+        diffs.push(format!("name: `{}` -> `{}`", old.name, new.name));
+    }
+
+    let fields: BTreeSet<&String> = old.fields.keys().chain(new.fields.keys()).collect();
+    for field in fields {
+        match (old.fields.get(field), new.fields.get(field)) {
+            (Some(old_value), Some(new_value)) if old_value != new_value => {
+                diffs.push(format!("{field}: {old_value} -> {new_value}"));
+            }
+            (Some(old_value), None) => diffs.push(format!("{field}: {old_value} -> (none)")),
+            (None, Some(new_value)) => diffs.push(format!("{field}: (none) -> {new_value}")),
+            _ => {}
+        }
+    }
+
+    if old.update_slots != new.update_slots {
+        diffs.push(format!(
+            "update slots: {:?} -> {:?}",
+            old.update_slots, new.update_slots
+        ));
+    }
+
+    diffs.extend(
+        old.proof_keys
+            .iter()
+            .filter(|key| !new.proof_keys.contains(key))
+            .map(|key| format!("proof key removed: {key}")),
+    );
+    diffs.extend(
+        new.proof_keys
+            .iter()
+            .filter(|key| !old.proof_keys.contains(key))
+            .map(|key| format!("proof key added: {key}")),
+    );
+    diffs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::fixtures;
+
+    #[test]
+    fn test_diff_identical_fixtures() {
+        let fixture = fixtures::load("TestICS20TransferNativeCosmosCoinsToEthereumAndBack_Groth16");
+        assert_eq!(
+            diff_fixtures(&fixture, &fixture).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_diff_fixtures() {
+        let old = fixtures::load("TestICS20TransferNativeCosmosCoinsToEthereumAndBack_Groth16");
+        let mut new = old.clone();
+        let mut initial_state: InitialState = new.get_data_at_step(0);
+        let latest_slot = initial_state.client_state.latest_slot;
+        initial_state.client_state.latest_slot += 1;
+        new.steps[0].data = serde_json::to_value(initial_state).unwrap();
+        let removed = new.steps.pop().unwrap();
+
+        let diffs = diff_fixtures(&old, &new).unwrap();
+        assert_eq!(
+            diffs,
+            vec![
+                format!(
+                    "step 0 (`initial_state`): client_state.latest_slot: {latest_slot} -> {}",
+                    latest_slot + 1
+                ),
+                format!("step {} (`{}`): removed", new.steps.len(), removed.name),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summarize_relayer_messages() {
+        let fixture = fixtures::load("TestMultiPeriodClientUpdateToCosmos");
+        let summaries = fixture
+            .steps
+            .iter()
+            .map(StepSummary::of)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let update_slots: Vec<u64> = summaries
+            .iter()
+            .flat_map(|summary| summary.update_slots.clone())
+            .collect();
+        assert!(!update_slots.is_empty());
+        assert!(update_slots.windows(2).all(|slots| slots[0] < slots[1]));
+    }
+}
@@ -17,9 +17,18 @@ use serde_json::Value;
 
 use crate::{client_state::ClientState, consensus_state::ConsensusState};
 
+/// The current schema version of [`StepsFixture`].
+///
+/// Bump this, and add an upgrade step to [`StepsFixture::upgrade`], whenever a change to the light
+/// client types changes how the fixtures need to be read.
+pub const STEPS_FIXTURE_VERSION: u32 = 1;
+
 /// A test fixture with an ordered list of light client operations from the e2e test
 #[derive(Serialize, Deserialize, JsonSchema, PartialEq, Eq, Clone, Debug)]
 pub struct StepsFixture {
+    /// version is the schema version of the fixture, fixtures without a version are version 0
+    #[serde(default)]
+    pub version: u32,
     /// steps is a list of light client operations
     pub steps: Vec<Step>,
 }
@@ -112,13 +121,79 @@ impl StepsFixture {
     {
         serde_json::from_value(self.steps[step].data.clone()).unwrap()
     }
+
+    /// Upgrades the fixture from an older schema version to [`STEPS_FIXTURE_VERSION`]
+    /// # Errors
+    /// Returns an error if the fixture has a newer, unsupported, schema version
+    pub fn upgrade(mut self) -> Result<Self, String> {
+        if self.version > STEPS_FIXTURE_VERSION {
+            return Err(format!(
+                "fixture schema version {} is newer than the supported version {STEPS_FIXTURE_VERSION}",
+                self.version
+            ));
+        }
+
+        if self.version == 0 {
+            // Version 1 only introduced the version field itself, the step data is unchanged
+            self.version = 1;
+        }
+
+        Ok(self)
+    }
+
+    /// Validates that the data of every step deserializes into the type expected for the step
+    /// # Errors
+    /// Returns an error describing the first step that does not match its expected type
+    pub fn validate(&self) -> Result<(), String> {
+        self.steps
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, step)| match step.name.as_str() {
+                "initial_state" => validate_step_data::<InitialState>(i, step),
+                "receive_packets" | "ack_packets" | "timeout_packets" => {
+                    validate_step_data::<RelayerMessages>(i, step)
+                }
+                _ => Ok(()),
+            })
+    }
 }
 
-/// load loads a test fixture from a JSON file
+fn validate_step_data<T>(index: usize, step: &Step) -> Result<(), String>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_value::<T>(step.data.clone())
+        .map(|_| ())
+        .map_err(|e| {
+            format!(
+                "step {index} (`{}`) does not match its schema: {e}",
+                step.name
+            )
+        })
+}
+
+/// load loads a test fixture from a JSON file, upgrading and validating it against the current
+/// schema version
 /// # Panics
-/// Panics if the file cannot be opened or the contents cannot be deserialized
+/// Panics if the file cannot be opened, the contents cannot be deserialized, or the fixture is
+/// incompatible with the current schema version
 #[must_use]
-pub fn load<T>(name: &str) -> T
+pub fn load(name: &str) -> StepsFixture {
+    let fixture: StepsFixture = load_json(name);
+    let fixture = fixture
+        .upgrade()
+        .unwrap_or_else(|e| panic!("incompatible fixture `{name}`: {e}"));
+    if let Err(e) = fixture.validate() {
+        panic!("invalid fixture `{name}` (schema version {STEPS_FIXTURE_VERSION}): {e}, regenerate the fixture or add an upgrade step");
+    }
+
+    fixture
+}
+
+/// `load_json` loads a JSON file from the fixtures directory
+/// # Panics
+/// Panics if the file cannot be opened or the contents cannot be deserialized
+fn load_json<T>(name: &str) -> T
 where
     T: serde::de::DeserializeOwned,
 {
@@ -131,3 +206,30 @@ where
     let file = std::fs::File::open(path).unwrap();
     serde_json::from_reader(file).unwrap()
 }
+
+#[cfg(test)]
+mod test {
+    use super::{load, StepsFixture, STEPS_FIXTURE_VERSION};
+
+    #[test]
+    fn test_load_upgrades_unversioned_fixture() {
+        let fixture = load("TestMultiPeriodClientUpdateToCosmos");
+        assert_eq!(fixture.version, STEPS_FIXTURE_VERSION);
+    }
+
+    #[test]
+    fn test_upgrade_rejects_newer_version() {
+        let fixture = StepsFixture {
+            version: STEPS_FIXTURE_VERSION + 1,
+            steps: vec![],
+        };
+        assert!(fixture.upgrade().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_mismatched_step() {
+        let mut fixture = load("TestMultiPeriodClientUpdateToCosmos");
+        fixture.steps[0].data = serde_json::json!({ "relayer_tx_body": "" });
+        assert!(fixture.validate().is_err());
+    }
+}
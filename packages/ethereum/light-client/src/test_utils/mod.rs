@@ -1,4 +1,11 @@
 //! This module defines test utilities for the Ethereum light client.
 
 pub mod bls_verifier;
+pub mod fixture_diff;
 pub mod fixtures;
+pub mod raw_inputs;
+pub mod storage_trie;
+pub mod update_generator;
+
+#[cfg(test)]
+mod consensus_spec_tests;
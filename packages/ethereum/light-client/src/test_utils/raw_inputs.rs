@@ -0,0 +1,369 @@
+//! The raw inputs the [`StepsFixture`]s are derived from
+//!
+//! The headers in the committed fixtures are encoded with the light client types of the time they
+//! were generated, so a change to [`Header`] or its fields breaks every fixture. The raw inputs
+//! keep what the headers were built from in the wire formats of the Beacon API and the execution
+//! JSON-RPC, i.e. the light client updates, the sync committees and the `eth_getProof` responses,
+//! which do not change with the light client types. The fixtures are regenerated from them with
+//! `operator fixtures regenerate`.
+//!
+//! Only the update client messages are rebuilt, the other messages of the relayer transactions
+//! and the steps that are not relayer transactions are kept as recorded.
+
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::{Bytes, B256};
+use ethereum_types::{
+    consensus::{light_client_header::LightClientUpdate, sync_committee::SyncCommittee},
+    execution::account_proof::AccountProof,
+};
+use ibc_proto_eureka::{
+    cosmos::tx::v1beta1::TxBody,
+    google::protobuf::Any,
+    ibc::{core::client::v1::MsgUpdateClient, lightclients::wasm::v1::ClientMessage},
+};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::header::{AccountUpdate, ActiveSyncCommittee, Header};
+
+use super::fixtures::{RelayerMessages, Step, StepsFixture, STEPS_FIXTURE_VERSION};
+
+/// The current schema version of [`RawFixtureInputs`].
+pub const RAW_INPUTS_VERSION: u32 = 1;
+
+/// The directory of the raw inputs, relative to the directory of the fixtures.
+pub const RAW_INPUTS_DIR: &str = "raw";
+
+const MSG_UPDATE_CLIENT_TYPE_URL: &str = "/ibc.core.client.v1.MsgUpdateClient";
+const WASM_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ClientMessage";
+
+/// The raw inputs of a fixture, step by step
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct RawFixtureInputs {
+    /// The schema version of the raw inputs
+    pub version: u32,
+    /// The raw inputs of the steps, in order
+    pub steps: Vec<RawStep>,
+}
+
+/// The raw inputs of a step
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct RawStep {
+    /// The name of the step
+    pub name: String,
+    /// The data of a step that is not a relayer transaction, kept as recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    /// The memo of the relayer transaction
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub memo: String,
+    /// The messages of the relayer transaction, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<RawMessage>,
+}
+
+/// A message of a relayer transaction
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RawMessage {
+    /// An update client message, rebuilt from the raw inputs of its header
+    UpdateClient {
+        /// The identifier of the updated client
+        client_id: String,
+        /// The signer of the message
+        signer: String,
+        /// The raw inputs of the header
+        header: RawHeaderInputs,
+    },
+    /// Any other message, kept as recorded
+    Other {
+        /// The type URL of the message
+        type_url: String,
+        /// The protobuf encoded message, encoded as hex
+        value: String,
+    },
+}
+
+/// The raw inputs a header is built from
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct RawHeaderInputs {
+    /// Whether the update is signed by the next sync committee of the trusted period
+    pub signed_by_next_sync_committee: bool,
+    /// The sync committee signing the update, as served by the Beacon API
+    pub sync_committee: Value,
+    /// The light client update, as served by the Beacon API
+    pub light_client_update: Value,
+    /// The `eth_getProof` response of the IBC contract at the finalized block
+    pub account_proof: Value,
+}
+
+/// The fields of an `eth_getProof` response the account update is built from
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthGetProofResponse {
+    storage_hash: B256,
+    account_proof: Vec<Bytes>,
+}
+
+impl RawHeaderInputs {
+    /// Records the raw inputs of a header
+    /// # Errors
+    /// Returns an error if the header cannot be encoded
+    pub fn record(header: &Header) -> Result<Self, String> {
+        Ok(Self {
+            signed_by_next_sync_committee: matches!(
+                header.active_sync_committee,
+                ActiveSyncCommittee::Next(_)
+            ),
+            sync_committee: to_value(header.active_sync_committee.sync_committee())?,
+            light_client_update: to_value(&header.consensus_update)?,
+            account_proof: to_value(&EthGetProofResponse {
+                storage_hash: header.account_update.account_proof.storage_root,
+                account_proof: header.account_update.account_proof.proof.clone(),
+            })?,
+        })
+    }
+
+    /// Builds the header with the current light client types
+    /// # Errors
+    /// Returns an error if a raw input cannot be decoded
+    pub fn header(&self) -> Result<Header, String> {
+        let sync_committee: SyncCommittee = from_value(&self.sync_committee)?;
+        let light_client_update: LightClientUpdate = from_value(&self.light_client_update)?;
+        let account_proof: EthGetProofResponse = from_value(&self.account_proof)?;
+
+        Ok(Header {
+            active_sync_committee: if self.signed_by_next_sync_committee {
+                ActiveSyncCommittee::Next(sync_committee)
+            } else {
+                ActiveSyncCommittee::Current(sync_committee)
+            },
+            consensus_update: light_client_update,
+            account_update: AccountUpdate {
+                account_proof: AccountProof {
+                    storage_root: account_proof.storage_hash,
+                    proof: account_proof.account_proof,
+                },
+            },
+        })
+    }
+}
+
+impl RawMessage {
+    /// Records a message of a relayer transaction
+    /// # Errors
+    /// Returns an error if an update client message does not carry a single header
+    pub fn record(msg: &Any) -> Result<Self, String> {
+        if msg.type_url != MSG_UPDATE_CLIENT_TYPE_URL {
+            return Ok(Self::Other {
+                type_url: msg.type_url.clone(),
+                value: hex::encode(&msg.value),
+            });
+        }
+
+        let msg_update_client =
+            MsgUpdateClient::decode(msg.value.as_slice()).map_err(|e| e.to_string())?;
+        let client_message = msg_update_client
+            .client_message
+            .ok_or("update client message without a client message")?;
+        let client_message =
+            ClientMessage::decode(client_message.value.as_slice()).map_err(|e| e.to_string())?;
+        let header: Header = serde_json::from_slice(&client_message.data)
+            .map_err(|e| format!("update client message without a single header: {e}"))?;
+
+        Ok(Self::UpdateClient {
+            client_id: msg_update_client.client_id,
+            signer: msg_update_client.signer,
+            header: RawHeaderInputs::record(&header)?,
+        })
+    }
+
+    /// Builds the message with the current light client types
+    /// # Errors
+    /// Returns an error if a raw input cannot be decoded
+    pub fn build(&self) -> Result<Any, String> {
+        match self {
+            Self::UpdateClient {
+                client_id,
+                signer,
+                header,
+            } => {
+                let client_message = ClientMessage {
+                    data: serde_json::to_vec(&header.header()?).map_err(|e| e.to_string())?,
+                };
+                Ok(Any {
+                    type_url: MSG_UPDATE_CLIENT_TYPE_URL.to_string(),
+                    value: MsgUpdateClient {
+                        client_id: client_id.clone(),
+                        client_message: Some(Any {
+                            type_url: WASM_CLIENT_MESSAGE_TYPE_URL.to_string(),
+                            value: client_message.encode_to_vec(),
+                        }),
+                        signer: signer.clone(),
+                    }
+                    .encode_to_vec(),
+                })
+            }
+            Self::Other { type_url, value } => Ok(Any {
+                type_url: type_url.clone(),
+                value: hex::decode(value).map_err(|e| e.to_string())?,
+            }),
+        }
+    }
+}
+
+impl RawFixtureInputs {
+    /// Records the raw inputs of a fixture
+    /// # Errors
+    /// Returns an error if a relayer transaction of the fixture cannot be decoded
+    pub fn record(fixture: &StepsFixture) -> Result<Self, String> {
+        let steps = fixture
+            .steps
+            .iter()
+            .map(|step| {
+                let Some(relayer_messages) = relayer_messages(step)? else {
+                    return Ok(RawStep {
+                        name: step.name.clone(),
+                        data: Some(step.data.clone()),
+                        memo: String::new(),
+                        messages: vec![],
+                    });
+                };
+
+                let tx_body = hex::decode(&relayer_messages.relayer_tx_body)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bz| TxBody::decode(bz.as_slice()).map_err(|e| e.to_string()))
+                    .map_err(|e| format!("step `{}`: invalid relayer tx: {e}", step.name))?;
+                Ok(RawStep {
+                    name: step.name.clone(),
+                    data: None,
+                    memo: tx_body.memo,
+                    messages: tx_body
+                        .messages
+                        .iter()
+                        .map(RawMessage::record)
+                        .collect::<Result<_, String>>()
+                        .map_err(|e| format!("step `{}`: {e}", step.name))?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            version: RAW_INPUTS_VERSION,
+            steps,
+        })
+    }
+
+    /// Regenerates the fixture with the current light client types
+    /// # Errors
+    /// Returns an error if the raw inputs have an unsupported version, or if a raw input cannot be
+    /// decoded
+    pub fn regenerate(&self) -> Result<StepsFixture, String> {
+        if self.version != RAW_INPUTS_VERSION {
+            return Err(format!(
+                "unsupported raw inputs version {}, expected {RAW_INPUTS_VERSION}",
+                self.version
+            ));
+        }
+
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                let data = match &step.data {
+                    Some(data) => data.clone(),
+                    None => {
+                        let tx_body = TxBody {
+                            messages: step
+                                .messages
+                                .iter()
+                                .map(RawMessage::build)
+                                .collect::<Result<_, String>>()
+                                .map_err(|e| format!("step `{}`: {e}", step.name))?,
+                            memo: step.memo.clone(),
+                            ..Default::default()
+                        };
+                        to_value(&RelayerMessages {
+                            relayer_tx_body: hex::encode(tx_body.encode_to_vec()),
+                        })?
+                    }
+                };
+                Ok(Step {
+                    name: step.name.clone(),
+                    data,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        let fixture = StepsFixture {
+            version: STEPS_FIXTURE_VERSION,
+            steps,
+        };
+        fixture.validate()?;
+        Ok(fixture)
+    }
+}
+
+/// Returns the path of the raw inputs of the fixture at `fixture_path`
+#[must_use]
+pub fn raw_inputs_path(fixture_path: &Path) -> PathBuf {
+    let mut path = fixture_path
+        .parent()
+        .map_or_else(PathBuf::new, Path::to_path_buf);
+    path.push(RAW_INPUTS_DIR);
+    if let Some(file_name) = fixture_path.file_name() {
+        path.push(file_name);
+    }
+    path
+}
+
+/// Returns the relayer messages of a step, if it is a relayer transaction
+fn relayer_messages(step: &Step) -> Result<Option<RelayerMessages>, String> {
+    match step.name.as_str() {
+        "receive_packets" | "ack_packets" | "timeout_packets" => from_value(&step.data)
+            .map(Some)
+            .map_err(|e| format!("step `{}`: {e}", step.name)),
+        _ => Ok(None),
+    }
+}
+
+fn to_value(value: &impl Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, String> {
+    T::deserialize(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::{fixture_diff::diff_fixtures, fixtures::load};
+
+    #[test]
+    fn test_regenerate_recorded_fixture() {
+        let fixture = load("TestICS20TransferERC20TokenfromEthereumToCosmosAndBack_Groth16");
+        let raw_inputs = RawFixtureInputs::record(&fixture).unwrap();
+        assert!(raw_inputs.steps.iter().any(|step| step
+            .messages
+            .iter()
+            .any(|msg| matches!(msg, RawMessage::UpdateClient { .. }))));
+
+        // The raw inputs survive a round trip through their JSON encoding
+        let raw_inputs: RawFixtureInputs =
+            serde_json::from_slice(&serde_json::to_vec(&raw_inputs).unwrap()).unwrap();
+        let regenerated = raw_inputs.regenerate().unwrap();
+        assert_eq!(regenerated.steps.len(), fixture.steps.len());
+        assert!(diff_fixtures(&fixture, &regenerated).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_raw_inputs_path() {
+        assert_eq!(
+            raw_inputs_path(Path::new("fixtures/Test_Electra_Fork.json")),
+            PathBuf::from("fixtures/raw/Test_Electra_Fork.json")
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! This module defines [`TrieNode`], a builder of minimal storage tries, and the corpus of storage
+//! proof edge cases built with it.
+//!
+//! The proofs served by execution nodes only cover the shapes of the tries of live contracts, so
+//! the edge cases of the Merkle Patricia trie verifier are built by hand instead: a leaf at the
+//! root, a trie held in a single node, leaves embedded in their parent, values of the maximal
+//! length and keys missing from the trie.
+
+use alloy_primitives::{keccak256, Bytes, B256, U256};
+use ethereum_types::execution::storage_proof::StorageProof;
+
+/// The length from which the encoding of a node is referenced by its hash instead of embedded in
+/// its parent.
+const HASHED_NODE_MIN_LENGTH: usize = 32;
+
+/// A node of a storage trie, with its paths in nibbles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrieNode {
+    /// A leaf holding the encoded value at the end of its path.
+    Leaf {
+        /// The remaining path of the leaf.
+        path: Vec<u8>,
+        /// The RLP encoded value.
+        value: Vec<u8>,
+    },
+    /// An extension sharing its path with the single child.
+    Extension {
+        /// The shared path.
+        path: Vec<u8>,
+        /// The child at the end of the shared path.
+        child: Box<TrieNode>,
+    },
+    /// A branch with a child per nibble.
+    Branch {
+        /// The children with their nibble.
+        children: Vec<(u8, TrieNode)>,
+    },
+}
+
+impl TrieNode {
+    /// Creates a leaf holding the storage `value` at the end of `path`.
+    #[must_use]
+    pub fn leaf(path: &[u8], value: U256) -> Self {
+        Self::Leaf {
+            path: path.to_vec(),
+            value: alloy_rlp::encode(value),
+        }
+    }
+
+    /// Creates an extension to `child` along `path`.
+    #[must_use]
+    pub fn extension(path: &[u8], child: Self) -> Self {
+        Self::Extension {
+            path: path.to_vec(),
+            child: Box::new(child),
+        }
+    }
+
+    /// Creates a branch with the given children.
+    #[must_use]
+    pub fn branch(children: impl IntoIterator<Item = (u8, Self)>) -> Self {
+        Self::Branch {
+            children: children.into_iter().collect(),
+        }
+    }
+
+    /// Returns the RLP encoding of the node.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Leaf { path, value } => rlp_list(&[
+                alloy_rlp::encode(hex_prefix(path, true).as_slice()),
+                alloy_rlp::encode(value.as_slice()),
+            ]),
+            Self::Extension { path, child } => rlp_list(&[
+                alloy_rlp::encode(hex_prefix(path, false).as_slice()),
+                child.reference(),
+            ]),
+            Self::Branch { children } => {
+                let mut items = vec![alloy_rlp::encode(b"".as_slice()); 17];
+                for (nibble, child) in children {
+                    items[usize::from(*nibble)] = child.reference();
+                }
+                rlp_list(&items)
+            }
+        }
+    }
+
+    /// Returns whether the node is embedded in its parent instead of referenced by its hash.
+    #[must_use]
+    pub fn is_embedded(&self) -> bool {
+        self.encode().len() < HASHED_NODE_MIN_LENGTH
+    }
+
+    /// Returns the root hash of the trie rooted at this node, which is always hashed.
+    #[must_use]
+    pub fn root(&self) -> B256 {
+        keccak256(self.encode())
+    }
+
+    /// Returns the encoded nodes of the trie rooted at this node that are referenced by their
+    /// hash, which make up the proofs of all of its keys.
+    #[must_use]
+    pub fn proof(&self) -> Vec<Bytes> {
+        let mut proof = vec![self.encode().into()];
+        self.collect_hashed_children(&mut proof);
+        proof
+    }
+
+    fn collect_hashed_children(&self, proof: &mut Vec<Bytes>) {
+        let children: Vec<&Self> = match self {
+            Self::Leaf { .. } => vec![],
+            Self::Extension { child, .. } => vec![child.as_ref()],
+            Self::Branch { children } => children.iter().map(|(_, child)| child).collect(),
+        };
+        for child in children {
+            if !child.is_embedded() {
+                proof.push(child.encode().into());
+            }
+            child.collect_hashed_children(proof);
+        }
+    }
+
+    /// Returns the reference to the node in its parent: the node itself if embedded, and its hash
+    /// otherwise.
+    fn reference(&self) -> Vec<u8> {
+        let encoded = self.encode();
+        if encoded.len() < HASHED_NODE_MIN_LENGTH {
+            encoded
+        } else {
+            alloy_rlp::encode(keccak256(encoded))
+        }
+    }
+}
+
+/// Returns the path of a storage key in the trie, the nibbles of its hash.
+#[must_use]
+pub fn key_nibbles(key: B256) -> Vec<u8> {
+    keccak256(key)
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// A storage proof edge case.
+#[derive(Clone, Debug)]
+pub struct StorageProofCase {
+    /// The name of the case.
+    pub name: &'static str,
+    /// The storage root of the trie of the case.
+    pub storage_root: B256,
+    /// The proof of the key, with the stored value or zero if the key is missing.
+    pub storage_proof: StorageProof,
+    /// Whether the key is stored in the trie.
+    pub is_member: bool,
+}
+
+impl StorageProofCase {
+    fn new(name: &'static str, key: B256, value: U256, trie: &TrieNode) -> Self {
+        Self {
+            name,
+            storage_root: trie.root(),
+            storage_proof: StorageProof {
+                key,
+                value,
+                proof: trie.proof(),
+            },
+            is_member: !value.is_zero(),
+        }
+    }
+}
+
+/// Returns the corpus of storage proof edge cases for `key`.
+#[must_use]
+pub fn storage_proof_corpus(key: B256) -> Vec<StorageProofCase> {
+    let path = key_nibbles(key);
+    // A nibble the paths of the siblings of the key start with
+    let other = |nibble: u8| (nibble + 1) % 16;
+    let small_value = U256::from(1);
+    let full_value = U256::MAX;
+
+    vec![
+        // The root is the leaf of the key
+        StorageProofCase::new(
+            "leaf_at_root",
+            key,
+            small_value,
+            &TrieNode::leaf(&path, small_value),
+        ),
+        // The key and its sibling are embedded in a branch embedded in the root, which is the
+        // only node of the trie
+        StorageProofCase::new(
+            "single_node_trie",
+            key,
+            small_value,
+            &TrieNode::extension(
+                &path[..62],
+                TrieNode::branch([
+                    (path[62], TrieNode::leaf(&path[63..], small_value)),
+                    (other(path[62]), TrieNode::leaf(&path[63..], small_value)),
+                ]),
+            ),
+        ),
+        // The leaf of the key is embedded in a hashed branch, next to a hashed sibling
+        StorageProofCase::new(
+            "branch_with_embedded_leaf",
+            key,
+            small_value,
+            &TrieNode::extension(
+                &path[..60],
+                TrieNode::branch([
+                    (path[60], TrieNode::leaf(&path[61..], small_value)),
+                    (other(path[60]), TrieNode::leaf(&path[61..], full_value)),
+                ]),
+            ),
+        ),
+        // The value of the key takes the full 32 bytes
+        StorageProofCase::new(
+            "value_32_bytes",
+            key,
+            full_value,
+            &TrieNode::branch([
+                (path[0], TrieNode::leaf(&path[1..], full_value)),
+                (other(path[0]), TrieNode::leaf(&path[1..], small_value)),
+            ]),
+        ),
+        // The branch on the path of the key has no child for its next nibble
+        StorageProofCase::new(
+            "exclusion_missing_child",
+            key,
+            U256::ZERO,
+            &TrieNode::branch([
+                (other(path[0]), TrieNode::leaf(&path[1..], full_value)),
+                (
+                    other(other(path[0])),
+                    TrieNode::leaf(&path[1..], small_value),
+                ),
+            ]),
+        ),
+        // The only leaf of the trie diverges from the key on its last nibble
+        StorageProofCase::new(
+            "exclusion_diverging_leaf",
+            key,
+            U256::ZERO,
+            &TrieNode::leaf(&[&path[..63], &[other(path[63])]].concat(), small_value),
+        ),
+    ]
+}
+
+/// Returns the trie holding the storage values of two distinct keys, which branches where their
+/// paths diverge.
+/// # Panics
+/// Panics if the keys are equal.
+#[must_use]
+pub fn two_key_trie((key_a, value_a): (B256, U256), (key_b, value_b): (B256, U256)) -> TrieNode {
+    let (path_a, path_b) = (key_nibbles(key_a), key_nibbles(key_b));
+    let shared = path_a
+        .iter()
+        .zip(&path_b)
+        .take_while(|(a, b)| a == b)
+        .count();
+    assert!(shared < path_a.len(), "the keys must be distinct");
+
+    let branch = TrieNode::branch([
+        (
+            path_a[shared],
+            TrieNode::leaf(&path_a[shared + 1..], value_a),
+        ),
+        (
+            path_b[shared],
+            TrieNode::leaf(&path_b[shared + 1..], value_b),
+        ),
+    ]);
+    if shared == 0 {
+        branch
+    } else {
+        TrieNode::extension(&path_a[..shared], branch)
+    }
+}
+
+/// Returns the hex prefix encoding of a path of nibbles.
+fn hex_prefix(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let (mut encoded, rest) = if path.len() % 2 == 1 {
+        (vec![flag | 0x10 | path[0]], &path[1..])
+    } else {
+        (vec![flag], path)
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+/// Returns the RLP list of the already encoded `items`.
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = vec![];
+    alloy_rlp::Header {
+        list: true,
+        payload_length: items.iter().map(Vec::len).sum(),
+    }
+    .encode(&mut out);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use ethereum_trie_db::trie_db::{
+        verify_storage_exclusion_proof, verify_storage_inclusion_proof,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_storage_proof_corpus() {
+        let key = B256::repeat_byte(0x42);
+        let corpus = storage_proof_corpus(key);
+
+        for case in &corpus {
+            let storage_proof = &case.storage_proof;
+            let inclusion = verify_storage_inclusion_proof(
+                &case.storage_root,
+                &storage_proof.key,
+                &alloy_rlp::encode(storage_proof.value),
+                storage_proof.proof.iter(),
+            );
+            let exclusion = verify_storage_exclusion_proof(
+                &case.storage_root,
+                &storage_proof.key,
+                storage_proof.proof.iter(),
+            );
+            assert_eq!(inclusion.is_ok(), case.is_member, "{}", case.name);
+            assert_eq!(exclusion.is_ok(), !case.is_member, "{}", case.name);
+        }
+
+        let case = |name| corpus.iter().find(|case| case.name == name).unwrap();
+        assert_eq!(case("leaf_at_root").storage_proof.proof.len(), 1);
+        assert_eq!(case("single_node_trie").storage_proof.proof.len(), 1);
+        // The extension, the branch and the hashed sibling
+        assert_eq!(
+            case("branch_with_embedded_leaf").storage_proof.proof.len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_hex_prefix() {
+        assert_eq!(hex_prefix(&[1, 2, 3, 4, 5], false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(
+            hex_prefix(&[0, 1, 2, 3, 4, 5], false),
+            vec![0x00, 0x01, 0x23, 0x45]
+        );
+        assert_eq!(
+            hex_prefix(&[0, 15, 1, 12, 11, 8], true),
+            vec![0x20, 0x0f, 0x1c, 0xb8]
+        );
+        assert_eq!(
+            hex_prefix(&[15, 1, 12, 11, 8], true),
+            vec![0x3f, 0x1c, 0xb8]
+        );
+    }
+
+    #[test]
+    fn test_embedded_nodes() {
+        let path = key_nibbles(B256::ZERO);
+        assert!(!TrieNode::leaf(&path, U256::from(1)).is_embedded());
+        assert!(TrieNode::leaf(&path[61..], U256::from(1)).is_embedded());
+        assert!(!TrieNode::leaf(&path[61..], U256::MAX).is_embedded());
+    }
+}
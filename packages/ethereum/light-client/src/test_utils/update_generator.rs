@@ -0,0 +1,621 @@
+//! This module defines [`UpdateGenerator`], which synthesizes light client updates across any
+//! number of sync committee periods.
+//!
+//! Capturing fixtures of a real network that span several sync committee periods takes days, so
+//! the period rotation is tested against synthetic chains instead. The sync committee of every
+//! period is made of deterministic BLS keys, and every update carries valid merkle branches into
+//! a synthetic beacon state, as well as a valid account proof of the IBC contract, so that the
+//! generated headers pass the full verification of the light client.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::{aliases::B32, keccak256, Address, Bytes, B256, U256};
+use ethereum_types::{
+    consensus::{
+        bls::{BlsPublicKey, BlsSignature},
+        domain::{compute_domain, DomainType},
+        fork::{Fork, ForkParameters},
+        light_client_header::{
+            BeaconBlockHeader, ExecutionPayloadHeader, LightClientHeader, LightClientUpdate,
+        },
+        merkle::EXECUTION_PAYLOAD_GINDEX,
+        signing_data::compute_signing_root,
+        sync_committee::{SyncAggregate, SyncCommittee},
+    },
+    execution::account_proof::AccountProof,
+};
+use sha2::{Digest, Sha256};
+use tree_hash::TreeHash;
+
+use crate::{
+    client_state::ClientState,
+    consensus_state::ConsensusState,
+    header::{AccountUpdate, ActiveSyncCommittee, Header},
+    sync_protocol_helpers::{finalized_root_gindex_at_slot, next_sync_committee_gindex_at_slot},
+};
+
+use super::bls_verifier::aggreagate;
+
+/// The number of slots per epoch of the generated chain, as in the minimal preset.
+pub const SLOTS_PER_EPOCH: u64 = 8;
+/// The number of epochs per sync committee period of the generated chain, as in the minimal
+/// preset.
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 8;
+/// The slot duration of the generated chain in seconds.
+pub const SECONDS_PER_SLOT: u64 = 6;
+/// The genesis time of the generated chain.
+pub const GENESIS_TIME: u64 = 1_700_000_000;
+
+/// Generates a chain of light client updates, signed by the deterministic sync committee of their
+/// period, with the Electra fork active from genesis.
+#[derive(Clone, Debug)]
+pub struct UpdateGenerator {
+    client_state: ClientState,
+    sync_committee_size: usize,
+}
+
+impl UpdateGenerator {
+    /// Creates a new `UpdateGenerator` with sync committees of `sync_committee_size` members.
+    /// # Panics
+    /// Panics if `sync_committee_size` is zero or not a multiple of 8.
+    #[must_use]
+    pub fn new(sync_committee_size: usize) -> Self {
+        assert!(
+            sync_committee_size > 0 && sync_committee_size % 8 == 0,
+            "the sync committee size must be a positive multiple of 8"
+        );
+
+        let fork = |version: u8| Fork {
+            version: B32::from([version, 0, 0, 1]),
+            epoch: 0,
+        };
+        let client_state = ClientState {
+            chain_id: 1337,
+            genesis_validators_root: B256::repeat_byte(0x42),
+            min_sync_committee_participants: 1,
+            genesis_time: GENESIS_TIME,
+            genesis_slot: 0,
+            fork_parameters: ForkParameters {
+                genesis_fork_version: B32::from([0, 0, 0, 1]),
+                genesis_slot: 0,
+                altair: fork(1),
+                bellatrix: fork(2),
+                capella: fork(3),
+                deneb: fork(4),
+                electra: fork(5),
+            },
+            seconds_per_slot: SECONDS_PER_SLOT,
+            slots_per_epoch: SLOTS_PER_EPOCH,
+            epochs_per_sync_committee_period: EPOCHS_PER_SYNC_COMMITTEE_PERIOD,
+            ibc_contract_address: Address::repeat_byte(0x11),
+            ..Default::default()
+        };
+
+        Self {
+            client_state,
+            sync_committee_size,
+        }
+    }
+
+    /// Returns the client state of the generated chain, at slot 0.
+    #[must_use]
+    pub const fn client_state(&self) -> &ClientState {
+        &self.client_state
+    }
+
+    /// Returns the number of slots in a sync committee period.
+    #[must_use]
+    pub const fn slots_per_period(&self) -> u64 {
+        self.client_state.slots_per_epoch * self.client_state.epochs_per_sync_committee_period
+    }
+
+    /// Returns the first slot of the sync committee `period`.
+    #[must_use]
+    pub const fn period_start_slot(&self, period: u64) -> u64 {
+        period * self.slots_per_period()
+    }
+
+    /// Returns the timestamp of `slot`.
+    #[must_use]
+    pub const fn timestamp_at_slot(&self, slot: u64) -> u64 {
+        self.client_state.genesis_time + slot * self.client_state.seconds_per_slot
+    }
+
+    /// Returns the sync committee of `period`.
+    /// # Panics
+    /// Panics if the public keys cannot be aggregated.
+    #[must_use]
+    pub fn sync_committee(&self, period: u64) -> SyncCommittee {
+        let pubkeys = (0..self.sync_committee_size)
+            .map(|index| {
+                let public_key =
+                    milagro_bls::PublicKey::from_secret_key(&secret_key(period, index));
+                BlsPublicKey::from(public_key.as_bytes())
+            })
+            .collect::<Vec<_>>();
+        let aggregate_pubkey = aggreagate(&pubkeys).unwrap();
+
+        SyncCommittee {
+            pubkeys,
+            aggregate_pubkey,
+        }
+    }
+
+    /// Returns the client and consensus states of a client trusting `slot`, which knows the next
+    /// sync committee.
+    #[must_use]
+    pub fn initial_states(&self, slot: u64) -> (ClientState, ConsensusState) {
+        let period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(slot);
+        let execution = self.execution_payload_header(slot);
+
+        let client_state = ClientState {
+            latest_slot: slot,
+            latest_execution_block_number: execution.block_number,
+            ..self.client_state.clone()
+        };
+        let consensus_state = ConsensusState {
+            slot,
+            state_root: execution.state_root,
+            storage_root: storage_root_at_slot(slot),
+            timestamp: execution.timestamp,
+            current_sync_committee: self.sync_committee(period).aggregate_pubkey,
+            next_sync_committee: Some(self.sync_committee(period + 1).aggregate_pubkey),
+        };
+
+        (client_state, consensus_state)
+    }
+
+    /// Returns a light client update of `finalized_slot`, attested at the slot preceding
+    /// `signature_slot` and signed by every member of the sync committee of the signature period.
+    /// # Panics
+    /// Panics if the slots are not ordered, or if the update cannot be signed.
+    #[must_use]
+    pub fn light_client_update(
+        &self,
+        finalized_slot: u64,
+        signature_slot: u64,
+        with_next_sync_committee: bool,
+    ) -> LightClientUpdate {
+        assert!(
+            finalized_slot < signature_slot,
+            "the finalized slot must precede the signature slot"
+        );
+        let attested_slot = signature_slot - 1;
+        let attested_period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(attested_slot);
+        let signature_period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(signature_slot);
+
+        let finalized_header =
+            self.light_client_header(finalized_slot, &SparseMerkleTree::default());
+        let next_sync_committee = self.sync_committee(attested_period + 1);
+
+        let finalized_root_gindex =
+            finalized_root_gindex_at_slot(&self.client_state, attested_slot).unwrap();
+        let next_sync_committee_gindex =
+            next_sync_committee_gindex_at_slot(&self.client_state, attested_slot).unwrap();
+        let mut attested_state = SparseMerkleTree::default();
+        attested_state.insert(
+            finalized_root_gindex,
+            finalized_header.beacon.tree_hash_root(),
+        );
+        attested_state.insert(
+            next_sync_committee_gindex,
+            next_sync_committee.tree_hash_root(),
+        );
+        let attested_header = self.light_client_header(attested_slot, &attested_state);
+
+        let sync_aggregate = self.sign(&attested_header.beacon, signature_slot, signature_period);
+
+        LightClientUpdate {
+            attested_header,
+            next_sync_committee: with_next_sync_committee.then_some(next_sync_committee),
+            next_sync_committee_branch: with_next_sync_committee
+                .then(|| attested_state.branch(next_sync_committee_gindex)),
+            finalized_header,
+            finality_branch: attested_state.branch(finalized_root_gindex),
+            sync_aggregate,
+            signature_slot,
+        }
+    }
+
+    /// Returns the light client update of `period`, finalized, attested and signed within the
+    /// period, with the next sync committee, like the updates served by the Beacon API.
+    #[must_use]
+    pub fn period_update(&self, period: u64) -> LightClientUpdate {
+        let start_slot = self.period_start_slot(period);
+        self.light_client_update(
+            start_slot + SLOTS_PER_EPOCH,
+            start_slot + 3 * SLOTS_PER_EPOCH + 1,
+            true,
+        )
+    }
+
+    /// Returns the account update of the IBC contract at `finalized_slot`.
+    #[must_use]
+    pub fn account_update(&self, finalized_slot: u64) -> AccountUpdate {
+        let (_, account_proof) = account_proof(
+            self.client_state.ibc_contract_address,
+            storage_root_at_slot(finalized_slot),
+        );
+        AccountUpdate { account_proof }
+    }
+
+    /// Returns the header of `update` for a client trusting `trusted_slot`, with the current sync
+    /// committee of the client if the update is signed in the trusted period, and the next one
+    /// otherwise.
+    #[must_use]
+    pub fn header(&self, trusted_slot: u64, update: LightClientUpdate) -> Header {
+        let trusted_period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(trusted_slot);
+        let signature_period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(update.signature_slot);
+        let sync_committee = self.sync_committee(signature_period);
+        let active_sync_committee = if signature_period == trusted_period {
+            ActiveSyncCommittee::Current(sync_committee)
+        } else {
+            ActiveSyncCommittee::Next(sync_committee)
+        };
+
+        Header {
+            active_sync_committee,
+            account_update: self.account_update(update.finalized_header.beacon.slot),
+            consensus_update: update,
+        }
+    }
+
+    /// Returns the headers advancing a client trusting `trusted_slot` one sync committee period at
+    /// a time, up to `target_period`.
+    #[must_use]
+    pub fn period_headers(&self, trusted_slot: u64, target_period: u64) -> Vec<Header> {
+        let trusted_period = self
+            .client_state
+            .compute_sync_committee_period_at_slot(trusted_slot);
+
+        let mut trusted_slot = trusted_slot;
+        (trusted_period + 1..=target_period)
+            .map(|period| {
+                let header = self.header(trusted_slot, self.period_update(period));
+                trusted_slot = header.consensus_update.finalized_header.beacon.slot;
+                header
+            })
+            .collect()
+    }
+
+    /// Returns the execution payload header at `slot`, with an execution block per slot.
+    fn execution_payload_header(&self, slot: u64) -> ExecutionPayloadHeader {
+        let (state_root, _) = account_proof(
+            self.client_state.ibc_contract_address,
+            storage_root_at_slot(slot),
+        );
+        ExecutionPayloadHeader {
+            state_root,
+            block_number: slot,
+            timestamp: self.timestamp_at_slot(slot),
+            block_hash: keccak256(slot.to_be_bytes()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the light client header at `slot`, with the given beacon state.
+    fn light_client_header(&self, slot: u64, state: &SparseMerkleTree) -> LightClientHeader {
+        let execution = self.execution_payload_header(slot);
+
+        let mut body = SparseMerkleTree::default();
+        body.insert(EXECUTION_PAYLOAD_GINDEX, execution.tree_hash_root());
+        let execution_branch = body
+            .branch(EXECUTION_PAYLOAD_GINDEX)
+            .try_into()
+            .expect("the execution branch has the depth of the execution payload gindex");
+
+        LightClientHeader {
+            beacon: BeaconBlockHeader {
+                slot,
+                proposer_index: 0,
+                parent_root: B256::ZERO,
+                state_root: state.root(),
+                body_root: body.root(),
+            },
+            execution,
+            execution_branch,
+        }
+    }
+
+    /// Returns the sync aggregate of the whole sync committee of `signature_period` over
+    /// `attested_header`.
+    fn sign(
+        &self,
+        attested_header: &BeaconBlockHeader,
+        signature_slot: u64,
+        signature_period: u64,
+    ) -> SyncAggregate {
+        let fork_version = self.client_state.fork_parameters.compute_fork_version(
+            self.client_state
+                .compute_epoch_at_slot(signature_slot.saturating_sub(1)),
+        );
+        let domain = compute_domain(
+            DomainType::SYNC_COMMITTEE,
+            Some(fork_version),
+            Some(self.client_state.genesis_validators_root),
+            self.client_state.fork_parameters.genesis_fork_version,
+        );
+        let signing_root = compute_signing_root(attested_header, domain);
+
+        let signatures = (0..self.sync_committee_size)
+            .map(|index| {
+                milagro_bls::Signature::new(
+                    signing_root.as_slice(),
+                    &secret_key(signature_period, index),
+                )
+            })
+            .collect::<Vec<_>>();
+        let aggregate_signature =
+            milagro_bls::AggregateSignature::aggregate(&signatures.iter().collect::<Vec<_>>());
+
+        SyncAggregate {
+            sync_committee_bits: Bytes::from(vec![0xff; self.sync_committee_size / 8]),
+            sync_committee_signature: BlsSignature::from(aggregate_signature.as_bytes()),
+        }
+    }
+}
+
+/// Returns the deterministic secret key of the member `index` of the sync committee of `period`.
+fn secret_key(period: u64, index: usize) -> milagro_bls::SecretKey {
+    let mut bytes = [0_u8; 32];
+    bytes[16..24].copy_from_slice(&(period + 1).to_be_bytes());
+    bytes[24..].copy_from_slice(&(index as u64 + 1).to_be_bytes());
+    milagro_bls::SecretKey::from_bytes(&bytes).unwrap()
+}
+
+/// Returns the storage root of the IBC contract at `slot`, which changes with every slot.
+fn storage_root_at_slot(slot: u64) -> B256 {
+    keccak256(slot.to_be_bytes())
+}
+
+/// Returns the state root of an execution state trie made of a single account with the given
+/// storage root, along with the proof of the account.
+fn account_proof(address: Address, storage_root: B256) -> (B256, AccountProof) {
+    let account = rlp_list(&[
+        alloy_rlp::encode(0_u64),
+        alloy_rlp::encode(U256::ZERO),
+        alloy_rlp::encode(storage_root),
+        alloy_rlp::encode(keccak256(b"")),
+    ]);
+
+    // The only leaf of the trie holds the full path, which has an even number of nibbles
+    let mut path = vec![0x20];
+    path.extend_from_slice(keccak256(address).as_slice());
+    let leaf = rlp_list(&[
+        alloy_rlp::encode(path.as_slice()),
+        alloy_rlp::encode(account.as_slice()),
+    ]);
+
+    (
+        keccak256(&leaf),
+        AccountProof {
+            storage_root,
+            proof: vec![leaf.into()],
+        },
+    )
+}
+
+/// Returns the RLP list of the already encoded `items`.
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = vec![];
+    alloy_rlp::Header {
+        list: true,
+        payload_length: items.iter().map(Vec::len).sum(),
+    }
+    .encode(&mut out);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+/// A sparse merkle tree, of a beacon state or block body, with leaves at generalized indices, where the subtrees without any leaf
+/// hash to zero.
+#[derive(Clone, Debug, Default)]
+struct SparseMerkleTree(BTreeMap<u64, B256>);
+
+impl SparseMerkleTree {
+    fn insert(&mut self, gindex: u64, leaf: B256) {
+        self.0.insert(gindex, leaf);
+    }
+
+    fn root(&self) -> B256 {
+        self.node(1)
+    }
+
+    /// Returns the merkle branch of the leaf at `gindex`, from the leaf up to the root.
+    fn branch(&self, gindex: u64) -> Vec<B256> {
+        let mut branch = vec![];
+        let mut gindex = gindex;
+        while gindex > 1 {
+            branch.push(self.node(gindex ^ 1));
+            gindex /= 2;
+        }
+        branch
+    }
+
+    fn node(&self, gindex: u64) -> B256 {
+        if let Some(leaf) = self.0.get(&gindex) {
+            return *leaf;
+        }
+        let has_leaves = self.0.keys().any(|&leaf_gindex| {
+            leaf_gindex > gindex && leaf_gindex >> (leaf_gindex.ilog2() - gindex.ilog2()) == gindex
+        });
+        if !has_leaves {
+            return B256::ZERO;
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(self.node(2 * gindex));
+        hasher.update(self.node(2 * gindex + 1));
+        B256::from_slice(&hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        test_utils::bls_verifier::TestBlsVerifier, update::update_consensus_state,
+        verify::verify_header,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_period_headers_rotate_sync_committees() {
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(1) + 5;
+        let (mut client_state, mut consensus_state) = generator.initial_states(trusted_slot);
+
+        let headers = generator.period_headers(trusted_slot, 5);
+        assert_eq!(headers.len(), 4);
+
+        for (period, header) in (2..).zip(headers) {
+            assert!(matches!(
+                header.active_sync_committee,
+                ActiveSyncCommittee::Next(_)
+            ));
+            verify_header(
+                &consensus_state,
+                &client_state,
+                generator.timestamp_at_slot(header.consensus_update.signature_slot),
+                &header,
+                TestBlsVerifier,
+            )
+            .unwrap();
+
+            let (_, new_consensus_state, new_client_state) =
+                update_consensus_state(consensus_state, client_state, &header).unwrap();
+            consensus_state = new_consensus_state;
+            client_state = new_client_state.unwrap();
+
+            assert_eq!(
+                client_state.compute_sync_committee_period_at_slot(consensus_state.slot),
+                period
+            );
+            assert_eq!(
+                consensus_state.current_sync_committee,
+                generator.sync_committee(period).aggregate_pubkey
+            );
+            assert_eq!(
+                consensus_state.next_sync_committee,
+                Some(generator.sync_committee(period + 1).aggregate_pubkey)
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_within_trusted_period() {
+        let generator = UpdateGenerator::new(8);
+        let trusted_slot = generator.period_start_slot(3);
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+
+        let update = generator.light_client_update(trusted_slot + 16, trusted_slot + 30, false);
+        let header = generator.header(trusted_slot, update);
+        assert!(matches!(
+            header.active_sync_committee,
+            ActiveSyncCommittee::Current(_)
+        ));
+        verify_header(
+            &consensus_state,
+            &client_state,
+            generator.timestamp_at_slot(trusted_slot + 30),
+            &header,
+            TestBlsVerifier,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_update_signed_by_wrong_sync_committee() {
+        let generator = UpdateGenerator::new(8);
+        let trusted_slot = generator.period_start_slot(1);
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+
+        // An update of two periods ahead cannot be verified by the client
+        let mut header = generator.header(trusted_slot, generator.period_update(3));
+        assert!(verify_header(
+            &consensus_state,
+            &client_state,
+            generator.timestamp_at_slot(header.consensus_update.signature_slot),
+            &header,
+            TestBlsVerifier,
+        )
+        .is_err());
+
+        // The next sync committee signing an update of the trusted period
+        header = generator.header(trusted_slot, generator.period_update(1));
+        header.active_sync_committee = ActiveSyncCommittee::Next(generator.sync_committee(2));
+        assert!(verify_header(
+            &consensus_state,
+            &client_state,
+            generator.timestamp_at_slot(header.consensus_update.signature_slot),
+            &header,
+            TestBlsVerifier,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_update_signed_in_next_period() {
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(1) + 5;
+        let next_period_start = generator.period_start_slot(2);
+
+        // Finalized in the trusted period and signed in the next one, attested at the end of the
+        // trusted period and at the start of the next one
+        for signature_slot in [next_period_start, next_period_start + 1] {
+            let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+            let update = generator.light_client_update(trusted_slot + 20, signature_slot, true);
+            let header = generator.header(trusted_slot, update);
+            assert!(matches!(
+                header.active_sync_committee,
+                ActiveSyncCommittee::Next(_)
+            ));
+            verify_header(
+                &consensus_state,
+                &client_state,
+                generator.timestamp_at_slot(signature_slot),
+                &header,
+                TestBlsVerifier,
+            )
+            .unwrap();
+
+            // The current sync committee did not sign the update
+            let mut current_header = header.clone();
+            current_header.active_sync_committee =
+                ActiveSyncCommittee::Current(generator.sync_committee(1));
+            assert!(verify_header(
+                &consensus_state,
+                &client_state,
+                generator.timestamp_at_slot(signature_slot),
+                &current_header,
+                TestBlsVerifier,
+            )
+            .is_err());
+
+            // The sync committees only rotate when the finalized header reaches the next period
+            let (slot, consensus_state, _) =
+                update_consensus_state(consensus_state, client_state, &header).unwrap();
+            assert_eq!(slot, trusted_slot + 20);
+            assert_eq!(
+                consensus_state.current_sync_committee,
+                generator.sync_committee(1).aggregate_pubkey
+            );
+            assert_eq!(
+                consensus_state.next_sync_committee,
+                Some(generator.sync_committee(2).aggregate_pubkey)
+            );
+        }
+    }
+}
@@ -1,8 +1,11 @@
 //! This module provides [`update_consensus_state`] function to update the consensus state
 
 use crate::{
-    client_state::ClientState, consensus_state::ConsensusState, error::EthereumIBCError,
-    header::Header,
+    client_state::ClientState,
+    consensus_state::ConsensusState,
+    error::EthereumIBCError,
+    header::{ActiveSyncCommittee, Header},
+    verify::validate_next_sync_committee,
 };
 
 /// Takes in the current client and consensus state and a new header and returns the updated
@@ -13,13 +16,17 @@ use crate::{
 ///
 /// Current implementation requires the client and consensus states to be updated since historical updates are not allowed.
 ///
+/// When the update rotates the sync committee, the rotated in `next_sync_committee` is verified
+/// against the attested state root before it is persisted, independently of `verify_header`.
+///
 /// # Errors
-/// Returns an error if the store period is not equal to the finalized period
+/// Returns an error if the store period is not equal to the finalized period, or if the
+/// next sync committee cannot be verified.
 #[allow(clippy::module_name_repetitions, clippy::needless_pass_by_value)]
 pub fn update_consensus_state(
     current_consensus_state: ConsensusState,
     current_client_state: ClientState,
-    header: Header,
+    header: &Header,
 ) -> Result<(u64, ConsensusState, Option<ClientState>), EthereumIBCError> {
     ensure!(
         current_client_state.latest_slot == current_consensus_state.slot,
@@ -41,10 +48,23 @@ pub fn update_consensus_state(
     if let Some(next_sync_committee) = current_consensus_state.next_sync_committee {
         // sync committee only changes when the period change
         if update_finalized_period == store_period + 1 {
+            if let ActiveSyncCommittee::Next(active_sync_committee) = &header.active_sync_committee
+            {
+                ensure!(
+                    active_sync_committee.aggregate_pubkey == next_sync_committee,
+                    EthereumIBCError::NextSyncCommitteeMismatch {
+                        expected: next_sync_committee,
+                        found: active_sync_committee.aggregate_pubkey,
+                    }
+                );
+            }
+            validate_next_sync_committee(&current_client_state, &header.consensus_update)?;
+
             new_consensus_state.current_sync_committee = next_sync_committee;
             new_consensus_state.next_sync_committee = header
                 .consensus_update
                 .next_sync_committee
+                .as_ref()
                 .map(|c| c.aggregate_pubkey);
         }
     } else {
@@ -53,9 +73,13 @@ pub fn update_consensus_state(
             update_finalized_period == store_period,
             EthereumIBCError::StorePeriodMustBeEqualToFinalizedPeriod
         );
+        if header.consensus_update.next_sync_committee.is_some() {
+            validate_next_sync_committee(&current_client_state, &header.consensus_update)?;
+        }
         new_consensus_state.next_sync_committee = header
             .consensus_update
             .next_sync_committee
+            .as_ref()
             .map(|c| c.aggregate_pubkey);
     }
 
@@ -0,0 +1,330 @@
+//! This module provides [`verify_upgrade`] function to verify the upgrade of the client to the
+//! client and consensus states committed by the IBC contract on Ethereum.
+//!
+//! Before an upgrade, the IBC contract commits the upgraded states under the upgrade paths of the
+//! slot the client is upgraded to, mirroring the upgrade paths of ibc-go. The slot is part of the
+//! committed client state, so the contract does not need to know the latest slot of the client on
+//! the counterparty chain when it commits the upgrade. Storage slots hold 32 bytes, so the
+//! committed value is the [canonical hash](crate::canonical) of each state, which does not depend
+//! on the JSON encoding the states are submitted in.
+
+use crate::{
+    client_state::ClientState, consensus_state::ConsensusState, error::EthereumIBCError,
+    membership::verify_membership,
+};
+
+/// The prefix of the upgrade paths in the IBC store.
+pub const UPGRADED_IBC_STATE_PREFIX: &str = "upgradedIBCState";
+/// The key of the upgraded client state under the upgrade path.
+pub const UPGRADED_CLIENT_KEY: &str = "upgradedClient";
+/// The key of the upgraded consensus state under the upgrade path.
+pub const UPGRADED_CONSENSUS_STATE_KEY: &str = "upgradedConsState";
+
+/// Returns the path the upgraded client state is committed under at `slot`.
+#[must_use]
+pub fn upgraded_client_state_path(slot: u64) -> Vec<u8> {
+    format!("{UPGRADED_IBC_STATE_PREFIX}/{slot}/{UPGRADED_CLIENT_KEY}").into_bytes()
+}
+
+/// Returns the path the upgraded consensus state is committed under at `slot`.
+#[must_use]
+pub fn upgraded_consensus_state_path(slot: u64) -> Vec<u8> {
+    format!("{UPGRADED_IBC_STATE_PREFIX}/{slot}/{UPGRADED_CONSENSUS_STATE_KEY}").into_bytes()
+}
+
+/// Verifies the upgrade of the client and returns the client and consensus states it is upgraded
+/// to.
+///
+/// * `client_state`: The current client state.
+/// * `trusted_consensus_state`: The consensus state at the latest slot of the client, the upgraded
+///   states are proven against under the upgrade paths of the upgraded slot.
+/// * `upgraded_client_state`: The upgraded client state, as committed by the IBC contract.
+/// * `upgraded_consensus_state`: The upgraded consensus state, as committed by the IBC contract.
+/// * `proof_upgraded_client`: The storage proof of the upgraded client state commitment.
+/// * `proof_upgraded_consensus_state`: The storage proof of the upgraded consensus state
+///   commitment.
+///
+/// The upgraded client state keeps the parameters chosen by the client rather than the chain: the
+/// minimum sync committee participation, the accepted client message encoding, the consensus state
+/// writes and retention, the minimum slot advance and the trusting period.
+///
+/// # Errors
+/// Returns an error if the upgraded states are invalid or their proofs cannot be verified.
+#[allow(clippy::module_name_repetitions, clippy::needless_pass_by_value)]
+pub fn verify_upgrade(
+    client_state: &ClientState,
+    trusted_consensus_state: &ConsensusState,
+    upgraded_client_state: ClientState,
+    upgraded_consensus_state: ConsensusState,
+    proof_upgraded_client: Vec<u8>,
+    proof_upgraded_consensus_state: Vec<u8>,
+) -> Result<(ClientState, ConsensusState), EthereumIBCError> {
+    ensure!(
+        upgraded_client_state.latest_slot > client_state.latest_slot,
+        EthereumIBCError::UpgradeSlotNotIncreasing {
+            current_slot: client_state.latest_slot,
+            upgraded_slot: upgraded_client_state.latest_slot,
+        }
+    );
+    ensure!(
+        upgraded_client_state.latest_slot == upgraded_consensus_state.slot,
+        EthereumIBCError::ClientAndConsensusSlotMismatch {
+            client_state_slot: upgraded_client_state.latest_slot,
+            consensus_state_slot: upgraded_consensus_state.slot,
+        }
+    );
+    ensure!(
+        !upgraded_client_state.is_frozen,
+        EthereumIBCError::UpgradedClientFrozen
+    );
+
+    let upgraded_slot = upgraded_client_state.latest_slot;
+    verify_membership(
+        trusted_consensus_state.clone(),
+        client_state.clone(),
+        proof_upgraded_client,
+        vec![upgraded_client_state_path(upgraded_slot)],
+        upgraded_client_state.canonical_hash()?.to_vec(),
+    )?;
+    verify_membership(
+        trusted_consensus_state.clone(),
+        client_state.clone(),
+        proof_upgraded_consensus_state,
+        vec![upgraded_consensus_state_path(upgraded_slot)],
+        upgraded_consensus_state.canonical_hash()?.to_vec(),
+    )?;
+
+    let new_client_state = ClientState {
+        min_sync_committee_participants: client_state.min_sync_committee_participants,
+        compressed_client_messages: client_state.compressed_client_messages,
+        consensus_state_writes: client_state.consensus_state_writes,
+        min_slot_advance: client_state.min_slot_advance,
+        consensus_state_retention: client_state.consensus_state_retention,
+        trusting_period: client_state.trusting_period,
+        ..upgraded_client_state
+    };
+
+    Ok((new_client_state, upgraded_consensus_state))
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::{B256, U256};
+    use ethereum_types::execution::storage_proof::StorageProof;
+
+    use crate::{
+        client_state::{ClientState, ConsensusStateWrites},
+        consensus_state::ConsensusState,
+        error::EthereumIBCError,
+        membership::evm_ics26_commitment_path,
+        test_utils::{storage_trie::two_key_trie, update_generator::UpdateGenerator},
+    };
+
+    use super::{upgraded_client_state_path, upgraded_consensus_state_path, verify_upgrade};
+
+    struct UpgradeCase {
+        client_state: ClientState,
+        consensus_state: ConsensusState,
+        upgraded_client_state: ClientState,
+        upgraded_consensus_state: ConsensusState,
+        proof_client: Vec<u8>,
+        proof_consensus: Vec<u8>,
+    }
+
+    fn upgrade_case() -> UpgradeCase {
+        let generator = UpdateGenerator::new(16);
+        let (mut client_state, mut consensus_state) =
+            generator.initial_states(generator.period_start_slot(1));
+        client_state.min_slot_advance = Some(8);
+        client_state.trusting_period = Some(86_400);
+        let upgraded_slot = generator.period_start_slot(2);
+        let (mut upgraded_client_state, mut upgraded_consensus_state) =
+            generator.initial_states(upgraded_slot);
+        upgraded_client_state.chain_id += 1;
+        upgraded_client_state.consensus_state_writes = ConsensusStateWrites::PeriodBoundaries;
+        upgraded_consensus_state.storage_root = B256::repeat_byte(0x42);
+
+        let commitment = |path: Vec<u8>, hash: B256| {
+            (
+                B256::from(evm_ics26_commitment_path(
+                    &path,
+                    client_state.ibc_commitment_slot,
+                )),
+                U256::from_be_bytes(hash.0),
+            )
+        };
+        let client_commitment = commitment(
+            upgraded_client_state_path(upgraded_slot),
+            upgraded_client_state.canonical_hash().unwrap(),
+        );
+        let consensus_commitment = commitment(
+            upgraded_consensus_state_path(upgraded_slot),
+            upgraded_consensus_state.canonical_hash().unwrap(),
+        );
+        let trie = two_key_trie(client_commitment, consensus_commitment);
+        consensus_state.storage_root = trie.root();
+
+        let proof = |(key, value)| {
+            serde_json::to_vec(&StorageProof {
+                key,
+                value,
+                proof: trie.proof(),
+            })
+            .unwrap()
+        };
+        UpgradeCase {
+            proof_client: proof(client_commitment),
+            proof_consensus: proof(consensus_commitment),
+            client_state,
+            consensus_state,
+            upgraded_client_state,
+            upgraded_consensus_state,
+        }
+    }
+
+    #[test]
+    fn test_upgrade_paths() {
+        // The paths committed by the `ICS26Router`
+        assert_eq!(
+            upgraded_client_state_path(42),
+            b"upgradedIBCState/42/upgradedClient"
+        );
+        assert_eq!(
+            upgraded_consensus_state_path(42),
+            b"upgradedIBCState/42/upgradedConsState"
+        );
+    }
+
+    #[test]
+    fn test_verify_upgrade() {
+        let case = upgrade_case();
+        let (new_client_state, new_consensus_state) = verify_upgrade(
+            &case.client_state,
+            &case.consensus_state,
+            case.upgraded_client_state.clone(),
+            case.upgraded_consensus_state.clone(),
+            case.proof_client,
+            case.proof_consensus,
+        )
+        .unwrap();
+
+        assert_eq!(new_consensus_state, case.upgraded_consensus_state);
+        assert_eq!(
+            new_client_state.chain_id,
+            case.upgraded_client_state.chain_id
+        );
+        assert_eq!(
+            new_client_state.latest_slot,
+            case.upgraded_client_state.latest_slot
+        );
+        // The parameters chosen by the client are kept
+        assert_eq!(new_client_state.min_slot_advance, Some(8));
+        assert_eq!(new_client_state.trusting_period, Some(86_400));
+        assert_eq!(
+            new_client_state.consensus_state_writes,
+            ConsensusStateWrites::All
+        );
+    }
+
+    #[test]
+    fn test_verify_upgrade_rejects_invalid_upgrades() {
+        let case = upgrade_case();
+        let verify = |upgraded_client_state: ClientState,
+                      upgraded_consensus_state: ConsensusState,
+                      proof_client: &[u8],
+                      proof_consensus: &[u8]| {
+            verify_upgrade(
+                &case.client_state,
+                &case.consensus_state,
+                upgraded_client_state,
+                upgraded_consensus_state,
+                proof_client.to_vec(),
+                proof_consensus.to_vec(),
+            )
+            .unwrap_err()
+        };
+
+        // Another upgraded client state than the committed one
+        let mut tampered_client_state = case.upgraded_client_state.clone();
+        tampered_client_state.chain_id += 1;
+        assert!(matches!(
+            verify(
+                tampered_client_state,
+                case.upgraded_consensus_state.clone(),
+                &case.proof_client,
+                &case.proof_consensus,
+            ),
+            EthereumIBCError::StoredValueMistmatch { .. }
+        ));
+
+        // The proofs of the two states swapped
+        assert!(matches!(
+            verify(
+                case.upgraded_client_state.clone(),
+                case.upgraded_consensus_state.clone(),
+                &case.proof_consensus,
+                &case.proof_client,
+            ),
+            EthereumIBCError::InvalidCommitmentKey(..)
+        ));
+
+        // The upgraded states moved to another slot than the one they are committed for
+        let mut moved_client_state = case.upgraded_client_state.clone();
+        moved_client_state.latest_slot += 1;
+        let mut moved_consensus_state = case.upgraded_consensus_state.clone();
+        moved_consensus_state.slot += 1;
+        assert!(matches!(
+            verify(
+                moved_client_state,
+                moved_consensus_state,
+                &case.proof_client,
+                &case.proof_consensus,
+            ),
+            EthereumIBCError::InvalidCommitmentKey(..)
+        ));
+
+        // An upgrade that does not advance the client
+        let mut stale_client_state = case.upgraded_client_state.clone();
+        stale_client_state.latest_slot = case.client_state.latest_slot;
+        let mut stale_consensus_state = case.upgraded_consensus_state.clone();
+        stale_consensus_state.slot = case.client_state.latest_slot;
+        assert_eq!(
+            verify(
+                stale_client_state,
+                stale_consensus_state,
+                &case.proof_client,
+                &case.proof_consensus,
+            ),
+            EthereumIBCError::UpgradeSlotNotIncreasing {
+                current_slot: case.client_state.latest_slot,
+                upgraded_slot: case.client_state.latest_slot,
+            }
+        );
+
+        // Upgraded states at different slots
+        let mut mismatched_consensus_state = case.upgraded_consensus_state.clone();
+        mismatched_consensus_state.slot += 1;
+        assert!(matches!(
+            verify(
+                case.upgraded_client_state.clone(),
+                mismatched_consensus_state,
+                &case.proof_client,
+                &case.proof_consensus,
+            ),
+            EthereumIBCError::ClientAndConsensusSlotMismatch { .. }
+        ));
+
+        // A frozen upgraded client
+        let mut frozen_client_state = case.upgraded_client_state.clone();
+        frozen_client_state.is_frozen = true;
+        assert_eq!(
+            verify(
+                frozen_client_state,
+                case.upgraded_consensus_state.clone(),
+                &case.proof_client,
+                &case.proof_consensus,
+            ),
+            EthereumIBCError::UpgradedClientFrozen
+        );
+    }
+}
@@ -13,6 +13,7 @@ use ethereum_types::consensus::{
 use tree_hash::TreeHash;
 
 use crate::{
+    bounds::check_header_bounds,
     client_state::ClientState,
     consensus_state::{ConsensusState, TrustedConsensusState},
     error::EthereumIBCError,
@@ -57,6 +58,14 @@ pub fn verify_header<V: BlsVerify>(
     header: &Header,
     bls_verifier: V,
 ) -> Result<(), EthereumIBCError> {
+    check_header_bounds(header)?;
+
+    // reject the updates advancing the client by too few slots before any signature is verified
+    client_state.verify_slot_advance(
+        consensus_state.slot,
+        header.consensus_update.finalized_header.beacon.slot,
+    )?;
+
     let trusted_consensus_state = TrustedConsensusState::new(
         consensus_state.clone(),
         header.active_sync_committee.clone(),
@@ -99,16 +108,14 @@ pub fn verify_header<V: BlsVerify>(
         }
     );
 
-    // check that if the period changes, then the next sync committee is provided
+    // check that if the period changes, then a valid next sync committee is provided, as checked
+    // again when the update is applied
     let update_finalized_period = client_state.compute_sync_committee_period_at_slot(
         header.consensus_update.finalized_header.beacon.slot,
     );
     let store_period = client_state.compute_sync_committee_period_at_slot(consensus_state.slot);
     if update_finalized_period > store_period {
-        ensure!(
-            header.consensus_update.next_sync_committee_branch.is_some(),
-            EthereumIBCError::ExpectedNextSyncCommitteeUpdate
-        );
+        validate_next_sync_committee(client_state, &header.consensus_update)?;
     }
 
     verify_account_storage_root(
@@ -311,22 +318,7 @@ pub fn validate_light_client_update<V: BlsVerify>(
         }
 
         // This validates the given next sync committee against the attested header's state root.
-        let next_sync_committee_gindex =
-            next_sync_committee_gindex_at_slot(client_state, update.attested_header.beacon.slot)?;
-        is_valid_normalized_merkle_branch(
-            update
-                .next_sync_committee
-                .as_ref()
-                .unwrap()
-                .tree_hash_root(),
-            &normalize_merkle_branch(
-                &update.next_sync_committee_branch.clone().unwrap(),
-                next_sync_committee_gindex,
-            ),
-            next_sync_committee_gindex,
-            update.attested_header.beacon.state_root,
-        )
-        .map_err(|e| EthereumIBCError::ValidateNextSyncCommitteeFailed(Box::new(e)))?;
+        validate_next_sync_committee(client_state, update)?;
     } else {
         ensure!(
             update.next_sync_committee.is_none(),
@@ -347,13 +339,9 @@ pub fn validate_light_client_update<V: BlsVerify>(
 
     // It's not mandatory for all of the members of the sync committee to participate. So we are extracting the
     // public keys of the ones who participated.
-    let participant_pubkeys = update
-        .sync_aggregate
-        .sync_committee_bits
-        .iter()
-        .flat_map(|byte| (0..8).map(move |i| (byte & (1 << i)) != 0))
-        .zip(sync_committee.pubkeys.iter())
-        .filter_map(|(included, pubkey)| included.then_some(*pubkey))
+    let participant_pubkeys = sync_committee
+        .participant_pubkeys(&update.sync_aggregate)
+        .copied()
         .collect::<Vec<_>>();
 
     let fork_version_slot = std::cmp::max(update.signature_slot, 1) - 1;
@@ -380,6 +368,32 @@ pub fn validate_light_client_update<V: BlsVerify>(
     Ok(())
 }
 
+/// Verifies the `next_sync_committee` of the `update` against the attested header's state root
+/// using the `next_sync_committee_branch`.
+/// # Errors
+/// Returns an error if the next sync committee or its branch is missing, or if the branch is invalid.
+pub fn validate_next_sync_committee(
+    client_state: &ClientState,
+    update: &LightClientUpdate,
+) -> Result<(), EthereumIBCError> {
+    let (Some(next_sync_committee), Some(next_sync_committee_branch)) = (
+        update.next_sync_committee.as_ref(),
+        update.next_sync_committee_branch.as_ref(),
+    ) else {
+        return Err(EthereumIBCError::ExpectedNextSyncCommitteeUpdate);
+    };
+
+    let next_sync_committee_gindex =
+        next_sync_committee_gindex_at_slot(client_state, update.attested_header.beacon.slot)?;
+    is_valid_normalized_merkle_branch(
+        next_sync_committee.tree_hash_root(),
+        &normalize_merkle_branch(next_sync_committee_branch, next_sync_committee_gindex),
+        next_sync_committee_gindex,
+        update.attested_header.beacon.state_root,
+    )
+    .map_err(|e| EthereumIBCError::ValidateNextSyncCommitteeFailed(Box::new(e)))
+}
+
 #[cfg(test)]
 mod test {
     use ibc_proto_eureka::ibc::lightclients::wasm::v1::ClientMessage;
@@ -388,6 +402,7 @@ mod test {
     use crate::test_utils::{
         bls_verifier::{aggreagate, fast_aggregate_verify, BlsError},
         fixtures::{self, InitialState, RelayerMessages},
+        update_generator::{UpdateGenerator, SLOTS_PER_EPOCH},
     };
 
     use super::*;
@@ -447,4 +462,129 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_validate_next_sync_committee_tampered_branch() {
+        let fixture: fixtures::StepsFixture = fixtures::load("TestMultiPeriodClientUpdateToCosmos");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+        let client_state = initial_state.client_state;
+
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, _, _) = relayer_messages.get_sdk_msgs();
+        let header = update_client_msgs
+            .iter()
+            .map(|msg| {
+                let client_msg =
+                    ClientMessage::decode(msg.client_message.clone().unwrap().value.as_slice())
+                        .unwrap();
+                serde_json::from_slice::<Header>(client_msg.data.as_slice()).unwrap()
+            })
+            .find(|header| header.consensus_update.next_sync_committee.is_some())
+            .expect("fixture should contain a header with a next sync committee");
+
+        validate_next_sync_committee(&client_state, &header.consensus_update).unwrap();
+
+        // Tamper with the branch
+        let mut tampered_update = header.consensus_update.clone();
+        tampered_update.next_sync_committee_branch.as_mut().unwrap()[0] = B256::repeat_byte(0xaa);
+        let err = validate_next_sync_committee(&client_state, &tampered_update).unwrap_err();
+        assert!(matches!(
+            err,
+            EthereumIBCError::ValidateNextSyncCommitteeFailed(_)
+        ));
+
+        // Tamper with the committee itself
+        let mut tampered_update = header.consensus_update.clone();
+        tampered_update
+            .next_sync_committee
+            .as_mut()
+            .unwrap()
+            .aggregate_pubkey = BlsPublicKey::default();
+        let err = validate_next_sync_committee(&client_state, &tampered_update).unwrap_err();
+        assert!(matches!(
+            err,
+            EthereumIBCError::ValidateNextSyncCommitteeFailed(_)
+        ));
+
+        // Missing branch
+        let mut tampered_update = header.consensus_update;
+        tampered_update.next_sync_committee_branch = None;
+        let err = validate_next_sync_committee(&client_state, &tampered_update).unwrap_err();
+        assert_eq!(err, EthereumIBCError::ExpectedNextSyncCommitteeUpdate);
+    }
+
+    #[test]
+    fn test_verify_header_min_slot_advance() {
+        let generator = UpdateGenerator::new(16);
+        let verify = |trusted_slot: u64, finalized_slot: u64, min_slot_advance: Option<u64>| {
+            let (mut client_state, consensus_state) = generator.initial_states(trusted_slot);
+            client_state.min_slot_advance = min_slot_advance;
+            let period_change = client_state.compute_sync_committee_period_at_slot(finalized_slot)
+                > client_state.compute_sync_committee_period_at_slot(trusted_slot);
+            let signature_slot = finalized_slot + SLOTS_PER_EPOCH + 1;
+            let update =
+                generator.light_client_update(finalized_slot, signature_slot, period_change);
+            verify_header(
+                &consensus_state,
+                &client_state,
+                generator.timestamp_at_slot(signature_slot),
+                &generator.header(trusted_slot, update),
+                TestBlsVerifier,
+            )
+        };
+
+        let min_slot_advance = Some(2 * SLOTS_PER_EPOCH);
+        let trusted_slot = generator.period_start_slot(2) + 9;
+        let finalized_slot = trusted_slot + SLOTS_PER_EPOCH;
+        verify(trusted_slot, finalized_slot, None).unwrap();
+        assert_eq!(
+            verify(trusted_slot, finalized_slot, min_slot_advance).unwrap_err(),
+            EthereumIBCError::InsufficientSlotAdvance {
+                consensus_state_slot: trusted_slot,
+                update_finalized_slot: finalized_slot,
+                min_slot_advance: 2 * SLOTS_PER_EPOCH,
+            }
+        );
+        verify(
+            trusted_slot,
+            trusted_slot + 2 * SLOTS_PER_EPOCH,
+            min_slot_advance,
+        )
+        .unwrap();
+
+        // An update to the next sync committee period is accepted regardless of its advance
+        let trusted_slot = generator.period_start_slot(3) - 2;
+        verify(trusted_slot, trusted_slot + 3, min_slot_advance).unwrap();
+    }
+
+    #[test]
+    fn test_verify_header_requires_next_sync_committee_across_periods() {
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(3) - 2;
+        let finalized_slot = trusted_slot + 3;
+        let signature_slot = finalized_slot + SLOTS_PER_EPOCH + 1;
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+        let update = generator.light_client_update(finalized_slot, signature_slot, true);
+        let verify = |update: LightClientUpdate| {
+            verify_header(
+                &consensus_state,
+                &client_state,
+                generator.timestamp_at_slot(signature_slot),
+                &generator.header(trusted_slot, update),
+                TestBlsVerifier,
+            )
+        };
+
+        verify(update.clone()).unwrap();
+
+        // An update crossing the period without the next sync committee
+        let mut stripped_update = update;
+        stripped_update.next_sync_committee = None;
+        stripped_update.next_sync_committee_branch = None;
+        assert_eq!(
+            verify(stripped_update).unwrap_err(),
+            EthereumIBCError::ExpectedNextSyncCommitteeUpdate
+        );
+    }
 }
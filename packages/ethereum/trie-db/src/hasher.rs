@@ -0,0 +1,88 @@
+//! Defines the keccak-256 backends used to verify storage proofs.
+//!
+//! The backend is selected at compile time. By default the portable [`Sha3Keccak`] backend is
+//! used. When the `cosmwasm` feature is enabled, `CosmWasmKeccak` is used instead, which hashes
+//! with the function registered by the `CosmWasm` contract. When the `tiny-keccak` feature is
+//! enabled, `TinyKeccak` is used, which is accelerated by the `tiny-keccak` precompile patch inside
+//! the SP1 zkVM, and takes precedence over the `cosmwasm` feature. Execution environments with
+//! other accelerated primitives can implement [`Keccak256Backend`] and use it through
+//! [`EthLayout`](crate::types::EthLayout) and [`KeccakHasher`](crate::types::KeccakHasher).
+
+use sha3::{Digest, Keccak256};
+
+/// A keccak-256 hash function implementation.
+pub trait Keccak256Backend: Send + Sync + 'static {
+    /// Performs a Keccak-256 hash on the given input.
+    fn keccak_256(input: &[u8]) -> [u8; 32];
+}
+
+/// Keccak-256 backend using the `sha3` crate.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sha3Keccak;
+
+impl Keccak256Backend for Sha3Keccak {
+    fn keccak_256(input: &[u8]) -> [u8; 32] {
+        Keccak256::digest(input).into()
+    }
+}
+
+/// Keccak-256 backend using the `tiny-keccak` crate.
+#[cfg(feature = "tiny-keccak")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TinyKeccak;
+
+#[cfg(feature = "tiny-keccak")]
+impl Keccak256Backend for TinyKeccak {
+    fn keccak_256(input: &[u8]) -> [u8; 32] {
+        use tiny_keccak::Hasher;
+
+        let mut output = [0u8; 32];
+        let mut hasher = tiny_keccak::Keccak::v256();
+        hasher.update(input);
+        hasher.finalize(&mut output);
+        output
+    }
+}
+
+/// Keccak-256 backend hashing with the function registered by the `CosmWasm` contract with
+/// [`CosmWasmKeccak::register_host`], such as one backed by an accelerated implementation of its
+/// host chain. The [`Sha3Keccak`] backend is used until a function is registered.
+#[cfg(feature = "cosmwasm")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CosmWasmKeccak;
+
+#[cfg(feature = "cosmwasm")]
+std::thread_local! {
+    static HOST_KECCAK_256: std::cell::Cell<Option<fn(&[u8]) -> [u8; 32]>> =
+        const { std::cell::Cell::new(None) };
+}
+
+#[cfg(feature = "cosmwasm")]
+impl CosmWasmKeccak {
+    /// Registers the keccak-256 function of the host, which the contract executes in a single
+    /// thread.
+    pub fn register_host(keccak_256: fn(&[u8]) -> [u8; 32]) {
+        HOST_KECCAK_256.with(|host| host.set(Some(keccak_256)));
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+impl Keccak256Backend for CosmWasmKeccak {
+    fn keccak_256(input: &[u8]) -> [u8; 32] {
+        HOST_KECCAK_256
+            .with(std::cell::Cell::get)
+            .map_or_else(|| Sha3Keccak::keccak_256(input), |host| host(input))
+    }
+}
+
+/// The keccak-256 backend selected by the enabled features.
+#[cfg(not(any(feature = "tiny-keccak", feature = "cosmwasm")))]
+pub type DefaultKeccak = Sha3Keccak;
+
+/// The keccak-256 backend selected by the enabled features.
+#[cfg(all(feature = "cosmwasm", not(feature = "tiny-keccak")))]
+pub type DefaultKeccak = CosmWasmKeccak;
+
+/// The keccak-256 backend selected by the enabled features.
+#[cfg(feature = "tiny-keccak")]
+pub type DefaultKeccak = TinyKeccak;
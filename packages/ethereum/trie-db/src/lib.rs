@@ -9,6 +9,7 @@
 )]
 
 mod error;
+pub mod hasher;
 pub mod trie_db;
 pub mod types;
 
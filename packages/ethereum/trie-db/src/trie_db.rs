@@ -8,7 +8,8 @@ use rlp_derive::RlpDecodable;
 use trie_db::{Trie, TrieDBBuilder};
 
 use crate::{
-    types::{keccak_256, EthLayout, KeccakHasher},
+    hasher::{DefaultKeccak, Keccak256Backend},
+    types::{EthLayout, KeccakHasher},
     TrieDBError,
 };
 
@@ -42,7 +43,20 @@ pub fn verify_storage_inclusion_proof(
     expected_value: &[u8],
     proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
 ) -> Result<(), TrieDBError> {
-    match get_node(H256(*root), key, proof)? {
+    verify_storage_inclusion_proof_with::<DefaultKeccak>(root, key, expected_value, proof)
+}
+
+/// Verifies the storage inclusion proof like [`verify_storage_inclusion_proof`], hashing with the
+/// keccak-256 backend `K`.
+/// # Errors
+/// Returns an error if the verification fails.
+pub fn verify_storage_inclusion_proof_with<K: Keccak256Backend>(
+    root: &[u8; 32],
+    key: &[u8; 32],
+    expected_value: &[u8],
+    proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> Result<(), TrieDBError> {
+    match get_node::<K>(H256(*root), key, proof)? {
         Some(value) if value == expected_value => Ok(()),
         Some(value) => Err(TrieDBError::ValueMismatch {
             expected: expected_value.into(),
@@ -69,7 +83,19 @@ pub fn verify_storage_exclusion_proof(
     key: &[u8; 32],
     proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
 ) -> Result<(), TrieDBError> {
-    match get_node(H256(*root), key, proof)? {
+    verify_storage_exclusion_proof_with::<DefaultKeccak>(root, key, proof)
+}
+
+/// Verifies the storage exclusion proof like [`verify_storage_exclusion_proof`], hashing with the
+/// keccak-256 backend `K`.
+/// # Errors
+/// Returns an error if the verification fails.
+pub fn verify_storage_exclusion_proof_with<K: Keccak256Backend>(
+    root: &[u8; 32],
+    key: &[u8; 32],
+    proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> Result<(), TrieDBError> {
+    match get_node::<K>(H256(*root), key, proof)? {
         Some(value) => Err(TrieDBError::ValueShouldBeMissing { value })?,
         None => Ok(()),
     }
@@ -90,11 +116,24 @@ pub fn verify_account_storage_root(
     address: Address,
     proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
     storage_root: B256,
+) -> Result<(), TrieDBError> {
+    verify_account_storage_root_with::<DefaultKeccak>(root, address, proof, storage_root)
+}
+
+/// Verifies the storage root of a contract like [`verify_account_storage_root`], hashing with the
+/// keccak-256 backend `K`.
+/// # Errors
+/// Returns an error if the verification fails.
+pub fn verify_account_storage_root_with<K: Keccak256Backend>(
+    root: B256,
+    address: Address,
+    proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    storage_root: B256,
 ) -> Result<(), TrieDBError> {
     let storage_root: H256 = H256(storage_root.into());
     let address: H160 = H160(address.into());
 
-    match get_node(H256(root.into()), address.as_ref(), proof)? {
+    match get_node::<K>(H256(root.into()), address.as_ref(), proof)? {
         Some(account) => {
             let account =
                 rlp::decode::<Account>(account.as_ref()).map_err(TrieDBError::RlpDecode)?;
@@ -112,17 +151,17 @@ pub fn verify_account_storage_root(
     }
 }
 
-fn get_node(
+fn get_node<K: Keccak256Backend>(
     root: H256,
     key: impl AsRef<[u8]>,
     proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
 ) -> Result<Option<Vec<u8>>, TrieDBError> {
-    let mut db = MemoryDB::<KeccakHasher, HashKey<_>, Vec<u8>>::default();
+    let mut db = MemoryDB::<KeccakHasher<K>, HashKey<_>, Vec<u8>>::default();
     proof.into_iter().for_each(|n| {
         db.insert(hash_db::EMPTY_PREFIX, n.as_ref());
     });
 
-    let trie = TrieDBBuilder::<EthLayout>::new(&db, &root).build();
-    trie.get(&keccak_256(key.as_ref()))
+    let trie = TrieDBBuilder::<EthLayout<K>>::new(&db, &root).build();
+    trie.get(&K::keccak_256(key.as_ref()))
         .map_err(|e| TrieDBError::GetTrieNodeFailed(e.to_string()))
 }
@@ -21,44 +21,49 @@ use std::{borrow::Borrow, marker::PhantomData, ops::Range};
 use hash_db::Hasher;
 use primitive_types::H256;
 use rlp::{DecoderError, Prototype, Rlp};
-use sha3::{Digest, Keccak256};
 use trie_db::{
     node::{NibbleSlicePlan, NodeHandlePlan, NodePlan, Value, ValuePlan},
     ChildReference, NodeCodec, TrieLayout,
 };
 
-/// Concrete implementation of `TrieLayout` for Ethereum
+use crate::hasher::{DefaultKeccak, Keccak256Backend};
+
+/// Concrete implementation of `TrieLayout` for Ethereum, generic over the keccak-256 backend
 #[derive(Default, Clone)]
-pub struct EthLayout;
+pub struct EthLayout<K: Keccak256Backend = DefaultKeccak> {
+    mark: PhantomData<K>,
+}
 
-impl TrieLayout for EthLayout {
+impl<K: Keccak256Backend> TrieLayout for EthLayout<K> {
     const USE_EXTENSION: bool = true;
     const ALLOW_EMPTY: bool = false;
     const MAX_INLINE_VALUE: Option<u32> = None;
-    type Hash = KeccakHasher;
-    type Codec = RlpNodeCodec<KeccakHasher>;
+    type Hash = KeccakHasher<K>;
+    type Codec = RlpNodeCodec<KeccakHasher<K>>;
 }
 
 use hash256_std_hasher::Hash256StdHasher;
 
-/// Concrete implementation of Hasher using Keccak 256-bit hashes
+/// Concrete implementation of Hasher using Keccak 256-bit hashes, generic over the backend
 #[derive(Debug)]
-pub struct KeccakHasher;
+pub struct KeccakHasher<K: Keccak256Backend = DefaultKeccak> {
+    mark: PhantomData<K>,
+}
 
-impl Hasher for KeccakHasher {
+impl<K: Keccak256Backend> Hasher for KeccakHasher<K> {
     type Out = H256;
     type StdHasher = Hash256StdHasher;
     const LENGTH: usize = 32;
 
     fn hash(x: &[u8]) -> Self::Out {
-        keccak_256(x).into()
+        K::keccak_256(x).into()
     }
 }
 
-/// Performs a Keccak-256 hash on the given input.
+/// Performs a Keccak-256 hash on the given input using the [`DefaultKeccak`] backend.
 #[must_use]
 pub fn keccak_256(input: &[u8]) -> [u8; 32] {
-    Keccak256::digest(input).into()
+    DefaultKeccak::keccak_256(input)
 }
 
 /// Concrete implementation of a `NodeCodec` with Rlp encoding, generic over the `Hasher`
@@ -98,11 +103,11 @@ fn decode_child_handle_plan<H: Hasher>(
 // `impl<H: Hasher> NodeCodec<H> for RlpNodeCodec<H> where H::Out: Decodable`
 // but due to the current limitations of Rust const evaluation we can't
 // do `const HASHED_NULL_NODE: H::Out = H::Out( … … )`. Perhaps one day soon?
-impl NodeCodec for RlpNodeCodec<KeccakHasher> {
+impl<K: Keccak256Backend> NodeCodec for RlpNodeCodec<KeccakHasher<K>> {
     type Error = DecoderError;
-    type HashOut = <KeccakHasher as Hasher>::Out;
+    type HashOut = <KeccakHasher<K> as Hasher>::Out;
 
-    fn hashed_null_node() -> <KeccakHasher as Hasher>::Out {
+    fn hashed_null_node() -> <KeccakHasher<K> as Hasher>::Out {
         HASHED_NULL_NODE
     }
 
@@ -150,7 +155,8 @@ impl NodeCodec for RlpNodeCodec<KeccakHasher> {
                         value: ValuePlan::Inline(value),
                     }
                 } else {
-                    let child = decode_child_handle_plan::<KeccakHasher>(value_rlp, value_offset)?;
+                    let child =
+                        decode_child_handle_plan::<KeccakHasher<K>>(value_rlp, value_offset)?;
                     NodePlan::Extension { partial, child }
                 })
             }
@@ -163,7 +169,7 @@ impl NodeCodec for RlpNodeCodec<KeccakHasher> {
                 for (i, child) in children.iter_mut().enumerate() {
                     let (child_rlp, child_offset) = r.at_with_offset(i)?;
                     if !child_rlp.is_empty() {
-                        *child = Some(decode_child_handle_plan::<KeccakHasher>(
+                        *child = Some(decode_child_handle_plan::<KeccakHasher<K>>(
                             child_rlp,
                             child_offset,
                         )?);
@@ -202,13 +208,13 @@ impl NodeCodec for RlpNodeCodec<KeccakHasher> {
     fn extension_node(
         _: impl Iterator<Item = u8>,
         _: usize,
-        _: ChildReference<<KeccakHasher as Hasher>::Out>,
+        _: ChildReference<<KeccakHasher<K> as Hasher>::Out>,
     ) -> Vec<u8> {
         unreachable!("Encoding extension nodes is not needed for proof verification.");
     }
 
     fn branch_node(
-        _: impl Iterator<Item = impl Borrow<Option<ChildReference<<KeccakHasher as Hasher>::Out>>>>,
+        _: impl Iterator<Item = impl Borrow<Option<ChildReference<<KeccakHasher<K> as Hasher>::Out>>>>,
         _: Option<Value>,
     ) -> Vec<u8> {
         unreachable!("Encoding branch nodes is not needed for proof verification.");
@@ -218,7 +224,7 @@ impl NodeCodec for RlpNodeCodec<KeccakHasher> {
         _partial: impl Iterator<Item = u8>,
         _number_nibble: usize,
         _children: impl Iterator<
-            Item = impl Borrow<Option<ChildReference<<KeccakHasher as Hasher>::Out>>>,
+            Item = impl Borrow<Option<ChildReference<<KeccakHasher<K> as Hasher>::Out>>>,
         >,
         _maybe_value: Option<Value>,
     ) -> Vec<u8> {
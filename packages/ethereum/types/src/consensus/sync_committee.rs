@@ -18,6 +18,20 @@ pub struct SyncCommittee {
     pub aggregate_pubkey: BlsPublicKey,
 }
 
+impl SyncCommittee {
+    /// Returns the public keys of the members of the sync committee that participated in the
+    /// aggregate, borrowed from the committee.
+    pub fn participant_pubkeys<'a>(
+        &'a self,
+        sync_aggregate: &'a SyncAggregate,
+    ) -> impl Iterator<Item = &'a BlsPublicKey> + 'a {
+        sync_aggregate
+            .participation()
+            .zip(self.pubkeys.iter())
+            .filter_map(|(participated, pubkey)| participated.then_some(pubkey))
+    }
+}
+
 /// The sync committee aggregate
 #[derive(Serialize, Deserialize, JsonSchema, PartialEq, Eq, Clone, Debug, Default)]
 pub struct SyncAggregate {
@@ -30,6 +44,14 @@ pub struct SyncAggregate {
 }
 
 impl SyncAggregate {
+    /// Returns whether each member of the sync committee participated, in the order of the
+    /// committee, without copying the participation bits.
+    pub fn participation(&self) -> impl Iterator<Item = bool> + '_ {
+        self.sync_committee_bits
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte & (1 << i)) != 0))
+    }
+
     /// Returns the number of bits that are set to `true`.
     #[must_use]
     pub fn num_sync_committe_participants(&self) -> u64 {
@@ -63,7 +85,7 @@ mod test {
     use tree_hash::TreeHash;
 
     use crate::consensus::{
-        bls::BlsSignature,
+        bls::{BlsPublicKey, BlsSignature},
         sync_committee::{SyncAggregate, SyncCommittee},
     };
 
@@ -156,6 +178,34 @@ mod test {
         assert!(sync_aggregate.validate_signature_supermajority());
     }
 
+    #[test]
+    fn test_participant_pubkeys() {
+        let sync_committee = SyncCommittee {
+            pubkeys: (0..10).map(BlsPublicKey::with_last_byte).collect(),
+            aggregate_pubkey: BlsPublicKey::default(),
+        };
+        let sync_aggregate = SyncAggregate {
+            sync_committee_bits: vec![0b00000101, 0b00000010].into(),
+            sync_committee_signature: BlsSignature::default(),
+        };
+
+        let participant_pubkeys = sync_committee
+            .participant_pubkeys(&sync_aggregate)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            participant_pubkeys,
+            vec![
+                &BlsPublicKey::with_last_byte(0),
+                &BlsPublicKey::with_last_byte(2),
+                &BlsPublicKey::with_last_byte(9),
+            ]
+        );
+        assert_eq!(
+            sync_aggregate.participation().filter(|bit| *bit).count() as u64,
+            sync_aggregate.num_sync_committe_participants()
+        );
+    }
+
     #[test]
     fn test_has_sufficient_participants() {
         let sync_aggregate = SyncAggregate {
@@ -0,0 +1,372 @@
+//! This module defines the submission of relay transactions to Cosmos SDK chains through authz.
+//!
+//! With an [`AuthzConfig`], the messages of a relay transaction are signed by a granter account
+//! and wrapped in a single `MsgExec` of the grantee, which is the only key the submitter holds. The
+//! grantee can then only submit the messages it was granted, and cannot move the other funds of the
+//! granter. The grants are checked with [`check_grants`] before every relay, so that an expired
+//! grant fails the relay with a clear error instead of a failing transaction, and grants that are
+//! about to expire raise a re-grant alert.
+
+use std::{collections::BTreeSet, time::SystemTime};
+
+use anyhow::Result;
+use cosmos_sdk_proto::{
+    cosmos::authz::v1beta1::{
+        GenericAuthorization, Grant, MsgExec, QueryGrantsRequest, QueryGrantsResponse,
+    },
+    Any as SdkAny,
+};
+use ibc_proto_eureka::{cosmos::tx::v1beta1::TxBody, google::protobuf::Any};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{Client, HttpClient};
+
+/// The type URL of `MsgExec`.
+pub const MSG_EXEC_TYPE_URL: &str = "/cosmos.authz.v1beta1.MsgExec";
+/// The type URL of `GenericAuthorization`.
+pub const GENERIC_AUTHORIZATION_TYPE_URL: &str = "/cosmos.authz.v1beta1.GenericAuthorization";
+/// The default time before the expiration of a grant from which a re-grant alert is raised.
+pub const DEFAULT_EXPIRY_ALERT_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// The configuration of the submission of relay transactions through authz. The signer address of
+/// the module is the granter.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthzConfig {
+    /// The address of the grantee, which submits the `MsgExec`.
+    pub grantee_address: String,
+    /// The time before the expiration of a grant from which a re-grant alert is raised, in
+    /// seconds.
+    #[serde(default = "default_expiry_alert_secs")]
+    pub expiry_alert_secs: u64,
+}
+
+const fn default_expiry_alert_secs() -> u64 {
+    DEFAULT_EXPIRY_ALERT_SECS
+}
+
+/// The state of the grant of a message type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GrantStatus {
+    /// The message type is granted, without expiration or beyond the alert window.
+    Granted,
+    /// The grant expires within the alert window, at the given unix timestamp.
+    Expiring(i64),
+    /// The grant expired at the given unix timestamp.
+    Expired(i64),
+    /// The message type is not granted.
+    Missing,
+}
+
+/// Returns the status of the grant of each message type, in the order of `msg_type_urls`.
+///
+/// Only generic authorizations are considered, since the relay messages have no typed
+/// authorization.
+#[must_use]
+pub fn check_grants(
+    grants: &[Grant],
+    msg_type_urls: &[String],
+    now: i64,
+    expiry_alert_secs: u64,
+) -> Vec<(String, GrantStatus)> {
+    let alert_from = now.saturating_add(i64::try_from(expiry_alert_secs).unwrap_or(i64::MAX));
+
+    msg_type_urls
+        .iter()
+        .map(|msg_type_url| {
+            let expirations = grants
+                .iter()
+                .filter(|grant| {
+                    granted_msg_type_url(grant).as_deref() == Some(msg_type_url.as_str())
+                })
+                .map(|grant| {
+                    grant
+                        .expiration
+                        .as_ref()
+                        .map(|expiration| expiration.seconds)
+                });
+
+            // The grant that expires last, where no expiration is the latest
+            let status = match expirations.max_by_key(|expiration| expiration.unwrap_or(i64::MAX)) {
+                None => GrantStatus::Missing,
+                Some(None) => GrantStatus::Granted,
+                Some(Some(expiration)) if expiration <= now => GrantStatus::Expired(expiration),
+                Some(Some(expiration)) if expiration <= alert_from => {
+                    GrantStatus::Expiring(expiration)
+                }
+                Some(Some(_)) => GrantStatus::Granted,
+            };
+            (msg_type_url.clone(), status)
+        })
+        .collect()
+}
+
+/// Returns the message type URL of a generic authorization.
+fn granted_msg_type_url(grant: &Grant) -> Option<String> {
+    let authorization = grant.authorization.as_ref()?;
+    if authorization.type_url != GENERIC_AUTHORIZATION_TYPE_URL {
+        return None;
+    }
+    GenericAuthorization::decode(authorization.value.as_slice())
+        .ok()
+        .map(|authorization| authorization.msg)
+}
+
+/// Wraps the messages of an encoded [`TxBody`] in a single `MsgExec` of the grantee.
+/// # Errors
+/// Returns an error if the transaction cannot be decoded.
+pub fn wrap_in_msg_exec(tx: &[u8], grantee: &str) -> Result<Vec<u8>> {
+    let mut tx_body = TxBody::decode(tx)?;
+
+    let msg_exec = MsgExec {
+        grantee: grantee.to_string(),
+        msgs: tx_body
+            .messages
+            .drain(..)
+            .map(|msg| SdkAny {
+                type_url: msg.type_url,
+                value: msg.value,
+            })
+            .collect(),
+    };
+    tx_body.messages = vec![Any {
+        type_url: MSG_EXEC_TYPE_URL.to_string(),
+        value: msg_exec.encode_to_vec(),
+    }];
+
+    Ok(tx_body.encode_to_vec())
+}
+
+/// Returns the messages of a transaction with the messages of its `MsgExec`s in their place, the
+/// messages as executed by the chain.
+/// # Errors
+/// Returns an error if a `MsgExec` cannot be decoded.
+pub fn unwrap_msg_exec(msgs: Vec<Any>) -> Result<Vec<Any>> {
+    let mut unwrapped = Vec::with_capacity(msgs.len());
+    for msg in msgs {
+        if msg.type_url == MSG_EXEC_TYPE_URL {
+            unwrapped.extend(
+                MsgExec::decode(msg.value.as_slice())?
+                    .msgs
+                    .into_iter()
+                    .map(|msg| Any {
+                        type_url: msg.type_url,
+                        value: msg.value,
+                    }),
+            );
+        } else {
+            unwrapped.push(msg);
+        }
+    }
+    Ok(unwrapped)
+}
+
+/// Fetches the grants from the granter to the grantee.
+/// # Errors
+/// Returns an error if the grants cannot be queried or decoded.
+pub async fn fetch_grants(
+    tm_client: &HttpClient,
+    granter: &str,
+    grantee: &str,
+) -> Result<Vec<Grant>> {
+    let abci_resp = tm_client
+        .abci_query(
+            Some("/cosmos.authz.v1beta1.Query/Grants".to_string()),
+            QueryGrantsRequest {
+                granter: granter.to_string(),
+                grantee: grantee.to_string(),
+                msg_type_url: String::new(),
+                pagination: None,
+            }
+            .encode_to_vec(),
+            None,
+            false,
+        )
+        .await?;
+
+    Ok(QueryGrantsResponse::decode(abci_resp.value.as_slice())?.grants)
+}
+
+/// Checks that the grantee is granted all the messages of a relay transaction, and wraps them in
+/// a `MsgExec`. Grants expiring within the alert window are logged as re-grant alerts.
+/// # Errors
+/// Returns an error if the grants cannot be fetched, or if a message type is not granted or its
+/// grant expired.
+pub async fn authorize_tx(
+    tm_client: &HttpClient,
+    config: &AuthzConfig,
+    granter: &str,
+    tx: &[u8],
+) -> Result<Vec<u8>> {
+    let msg_type_urls = TxBody::decode(tx)?
+        .messages
+        .into_iter()
+        .map(|msg| msg.type_url)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let grants = fetch_grants(tm_client, granter, &config.grantee_address).await?;
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs()
+        .try_into()?;
+
+    for (msg_type_url, status) in
+        check_grants(&grants, &msg_type_urls, now, config.expiry_alert_secs)
+    {
+        match status {
+            GrantStatus::Granted => {}
+            GrantStatus::Expiring(expiration) => tracing::warn!(
+                granter,
+                grantee = config.grantee_address,
+                msg_type_url,
+                expiration,
+                "The authz grant expires soon and must be renewed."
+            ),
+            GrantStatus::Expired(expiration) => anyhow::bail!(
+                "the authz grant of {msg_type_url} from {granter} to {} expired at {expiration}, it must be renewed",
+                config.grantee_address
+            ),
+            GrantStatus::Missing => anyhow::bail!(
+                "{msg_type_url} is not granted from {granter} to {}",
+                config.grantee_address
+            ),
+        }
+    }
+
+    wrap_in_msg_exec(tx, &config.grantee_address)
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_sdk_proto::Timestamp;
+
+    use super::*;
+
+    fn grant(msg_type_url: &str, expiration: Option<i64>) -> Grant {
+        Grant {
+            authorization: Some(SdkAny {
+                type_url: GENERIC_AUTHORIZATION_TYPE_URL.to_string(),
+                value: GenericAuthorization {
+                    msg: msg_type_url.to_string(),
+                }
+                .encode_to_vec(),
+            }),
+            expiration: expiration.map(|seconds| Timestamp { seconds, nanos: 0 }),
+        }
+    }
+
+    #[test]
+    fn test_check_grants() {
+        let grants = vec![
+            grant("/ibc.core.client.v1.MsgUpdateClient", None),
+            grant("/ibc.core.channel.v2.MsgRecvPacket", Some(1_500)),
+            grant("/ibc.core.channel.v2.MsgTimeout", Some(900)),
+            // The latest grant of a message type applies
+            grant("/ibc.core.channel.v2.MsgAcknowledgement", Some(900)),
+            grant("/ibc.core.channel.v2.MsgAcknowledgement", Some(5_000)),
+        ];
+        let msg_type_urls = [
+            "/ibc.core.client.v1.MsgUpdateClient",
+            "/ibc.core.channel.v2.MsgRecvPacket",
+            "/ibc.core.channel.v2.MsgTimeout",
+            "/ibc.core.channel.v2.MsgAcknowledgement",
+            "/cosmos.bank.v1beta1.MsgSend",
+        ]
+        .map(str::to_string);
+
+        let statuses = check_grants(&grants, &msg_type_urls, 1_000, 1_000)
+            .into_iter()
+            .map(|(_, status)| status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                GrantStatus::Granted,
+                GrantStatus::Expiring(1_500),
+                GrantStatus::Expired(900),
+                GrantStatus::Granted,
+                GrantStatus::Missing,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap_in_msg_exec() {
+        let msgs = vec![
+            Any {
+                type_url: "/ibc.core.client.v1.MsgUpdateClient".to_string(),
+                value: vec![1, 2, 3],
+            },
+            Any {
+                type_url: "/ibc.core.channel.v2.MsgRecvPacket".to_string(),
+                value: vec![4, 5],
+            },
+        ];
+        let tx = TxBody {
+            messages: msgs.clone(),
+            memo: "memo".to_string(),
+            ..Default::default()
+        }
+        .encode_to_vec();
+
+        let tx_body =
+            TxBody::decode(wrap_in_msg_exec(&tx, "cosmos1grantee").unwrap().as_slice()).unwrap();
+        assert_eq!(tx_body.memo, "memo");
+        assert_eq!(tx_body.messages.len(), 1);
+        assert_eq!(tx_body.messages[0].type_url, MSG_EXEC_TYPE_URL);
+
+        let msg_exec = MsgExec::decode(tx_body.messages[0].value.as_slice()).unwrap();
+        assert_eq!(msg_exec.grantee, "cosmos1grantee");
+        assert_eq!(
+            msg_exec
+                .msgs
+                .into_iter()
+                .map(|msg| (msg.type_url, msg.value))
+                .collect::<Vec<_>>(),
+            msgs.into_iter()
+                .map(|msg| (msg.type_url, msg.value))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_unwrap_msg_exec() {
+        let msgs = vec![
+            Any {
+                type_url: "/ibc.core.client.v1.MsgUpdateClient".to_string(),
+                value: vec![1, 2, 3],
+            },
+            Any {
+                type_url: "/ibc.core.channel.v2.MsgRecvPacket".to_string(),
+                value: vec![4, 5],
+            },
+        ];
+        let tx = TxBody {
+            messages: msgs.clone(),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        let mut wrapped =
+            TxBody::decode(wrap_in_msg_exec(&tx, "cosmos1grantee").unwrap().as_slice())
+                .unwrap()
+                .messages;
+
+        // The messages outside of a `MsgExec` are kept in place
+        let fee_claim = Any {
+            type_url: "/ibc.applications.fee.v2.MsgClaimPacketFees".to_string(),
+            value: vec![6],
+        };
+        wrapped.push(fee_claim.clone());
+        assert_eq!(
+            unwrap_msg_exec(wrapped).unwrap(),
+            [msgs, vec![fee_claim]].concat()
+        );
+
+        let malformed = Any {
+            type_url: MSG_EXEC_TYPE_URL.to_string(),
+            value: vec![0xff],
+        };
+        assert!(unwrap_msg_exec(vec![malformed]).is_err());
+    }
+}
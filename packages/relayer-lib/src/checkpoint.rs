@@ -0,0 +1,187 @@
+//! This module defines the strategies for selecting the checkpoint of a new Ethereum light client.
+//!
+//! A new light client trusts the sync committee of the beacon block it is bootstrapped from, so the
+//! checkpoint is selected with a [`CheckpointStrategy`] and cross-checked against independent
+//! Beacon API endpoints with [`ensure_checkpoint_agreement`] before the client is created.
+
+use std::str::FromStr;
+
+use anyhow::Result;
+use ethereum_apis::beacon_api::client::BeaconApiClient;
+use serde::{Deserialize, Serialize};
+
+/// The strategy for selecting the beacon block a new light client is bootstrapped from.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum CheckpointStrategy {
+    /// The latest finalized beacon block of the Beacon API of the relayer.
+    #[default]
+    LatestFinalized,
+    /// The latest finalized beacon block of a trusted weak subjectivity checkpoint provider, such
+    /// as a checkpoint sync endpoint, which serves the Beacon API.
+    Trusted {
+        /// The base URL of the Beacon API of the trusted provider.
+        url: String,
+    },
+    /// The beacon block at an explicit slot, which must be finalized.
+    Slot {
+        /// The slot of the beacon block.
+        slot: u64,
+    },
+}
+
+/// A checkpoint selected by a [`CheckpointStrategy`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The slot of the beacon block.
+    pub slot: u64,
+    /// The hex encoded root of the beacon block.
+    pub block_root: String,
+}
+
+impl FromStr for CheckpointStrategy {
+    type Err = anyhow::Error;
+
+    /// Parses a strategy from `finalized`, `slot:<slot>` or `trusted:<url>`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            None if s == "finalized" => Ok(Self::LatestFinalized),
+            Some(("slot", slot)) => Ok(Self::Slot {
+                slot: slot.parse()?,
+            }),
+            Some(("trusted", url)) if !url.is_empty() => Ok(Self::Trusted {
+                url: url.to_string(),
+            }),
+            _ => anyhow::bail!(
+                "invalid checkpoint strategy `{s}`, expected `finalized`, `slot:<slot>` or `trusted:<url>`"
+            ),
+        }
+    }
+}
+
+impl CheckpointStrategy {
+    /// Selects the checkpoint with the given Beacon API client, which is only used by the
+    /// strategies that do not name their own provider.
+    /// # Errors
+    /// Returns an error if the checkpoint cannot be fetched, or if an explicit slot is not
+    /// finalized yet.
+    pub async fn resolve(&self, beacon_api_client: &BeaconApiClient) -> Result<Checkpoint> {
+        match self {
+            Self::LatestFinalized => finalized_checkpoint(beacon_api_client).await,
+            Self::Trusted { url } => {
+                finalized_checkpoint(&BeaconApiClient::new(url.trim_end_matches('/').to_string()))
+                    .await
+            }
+            Self::Slot { slot } => {
+                let finalized = finalized_checkpoint(beacon_api_client).await?;
+                anyhow::ensure!(
+                    *slot <= finalized.slot,
+                    "checkpoint slot {slot} is not finalized (finalized slot: {})",
+                    finalized.slot
+                );
+
+                Ok(Checkpoint {
+                    slot: *slot,
+                    block_root: beacon_api_client
+                        .beacon_block_root(&slot.to_string())
+                        .await?,
+                })
+            }
+        }
+    }
+}
+
+/// Returns the checkpoint of the latest finalized beacon block of a Beacon API.
+async fn finalized_checkpoint(beacon_api_client: &BeaconApiClient) -> Result<Checkpoint> {
+    let slot = beacon_api_client
+        .beacon_block("finalized")
+        .await?
+        .message
+        .slot;
+    Ok(Checkpoint {
+        slot,
+        block_root: beacon_api_client
+            .beacon_block_root(&slot.to_string())
+            .await?,
+    })
+}
+
+/// Checks that every Beacon API has the checkpoint block at the checkpoint slot.
+/// # Errors
+/// Returns an error if a block root cannot be fetched, or if any of them disagrees.
+pub async fn ensure_checkpoint_agreement(
+    checkpoint: &Checkpoint,
+    sources: &[BeaconApiClient],
+) -> Result<()> {
+    let block_roots = futures::future::try_join_all(
+        sources
+            .iter()
+            .map(|source| source.beacon_block_root(&checkpoint.slot.to_string())),
+    )
+    .await?;
+
+    ensure_block_roots_agree(checkpoint, &block_roots)
+}
+
+/// Returns an error if any of the block roots, in the order of the sources, differs from the
+/// block root of the checkpoint.
+fn ensure_block_roots_agree(checkpoint: &Checkpoint, block_roots: &[String]) -> Result<()> {
+    block_roots
+        .iter()
+        .position(|block_root| !block_root.eq_ignore_ascii_case(&checkpoint.block_root))
+        .map_or(Ok(()), |source| {
+            Err(anyhow::anyhow!(
+                "checkpoint source #{source} reports block root {} at slot {}, expected {}",
+                block_roots[source],
+                checkpoint.slot,
+                checkpoint.block_root
+            ))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_checkpoint_strategy() {
+        assert_eq!(
+            "finalized".parse::<CheckpointStrategy>().unwrap(),
+            CheckpointStrategy::LatestFinalized
+        );
+        assert_eq!(
+            "slot:8192".parse::<CheckpointStrategy>().unwrap(),
+            CheckpointStrategy::Slot { slot: 8192 }
+        );
+        assert_eq!(
+            "trusted:https://sync.example.com"
+                .parse::<CheckpointStrategy>()
+                .unwrap(),
+            CheckpointStrategy::Trusted {
+                url: "https://sync.example.com".to_string()
+            }
+        );
+        assert!("slot:latest".parse::<CheckpointStrategy>().is_err());
+        assert!("trusted:".parse::<CheckpointStrategy>().is_err());
+        assert!("latest".parse::<CheckpointStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_block_roots_agree() {
+        let checkpoint = Checkpoint {
+            slot: 8192,
+            block_root: "0xabcd".to_string(),
+        };
+
+        assert!(ensure_block_roots_agree(&checkpoint, &[]).is_ok());
+        assert!(ensure_block_roots_agree(
+            &checkpoint,
+            &["0xabcd".to_string(), "0xABCD".to_string()]
+        )
+        .is_ok());
+        let err =
+            ensure_block_roots_agree(&checkpoint, &["0xabcd".to_string(), "0x1234".to_string()])
+                .unwrap_err();
+        assert!(err.to_string().contains("source #1"));
+    }
+}
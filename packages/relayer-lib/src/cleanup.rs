@@ -0,0 +1,271 @@
+//! This module defines the verification of the packet commitment cleanup on Ethereum.
+//!
+//! The `ICS26Router` deletes the commitment of a packet once its acknowledgement or timeout is
+//! applied. A commitment that outlives the landed ack points at a bug in the router or in an
+//! application, and would let the packet be acknowledged or timed out again. After relaying acks
+//! and timeouts to Ethereum, a module can watch the commitments of the relayed packets with
+//! [`verify_commitment_cleanup`] and report the stale ones.
+
+use std::{collections::HashSet, time::Duration};
+
+use alloy::{
+    primitives::{keccak256, B256},
+    providers::Provider,
+    rpc::types::Filter,
+    sol_types::SolEvent,
+};
+use anyhow::Result;
+use futures::future;
+use futures_timer::Delay;
+use ibc_eureka_solidity_types::ics26::{
+    router::{routerInstance, AckPacket, TimeoutPacket},
+    IICS26RouterMsgs::Packet,
+};
+use ibc_proto_eureka::ibc::core::channel::v2::Packet as ProtoPacket;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    introspection::{self, RelayedPacket},
+    tx_builder::plan::MsgKind,
+};
+
+/// The default interval between two polls of the commitments in seconds.
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 12;
+/// The default time to wait for the relayed acks and timeouts to land in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 15 * 60;
+
+/// The verification of the commitment cleanup of the packets acknowledged or timed out on
+/// Ethereum.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupCheck {
+    /// The interval between two polls of the commitments in seconds.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// The time to wait for the relayed acks and timeouts to land in seconds, after which the
+    /// packets that did not land are no longer watched.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for CleanupCheck {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: DEFAULT_POLL_INTERVAL_SECS,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+const fn default_poll_interval_secs() -> u64 {
+    DEFAULT_POLL_INTERVAL_SECS
+}
+
+const fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+/// The state of the commitment of a packet whose ack or timeout was relayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupOutcome {
+    /// The commitment was deleted.
+    Deleted,
+    /// The ack or timeout landed, but the commitment was not deleted.
+    Stale,
+    /// The ack or timeout did not land, and the commitment is still stored.
+    NotLanded,
+}
+
+/// The cleanup of the commitment of a single packet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketCleanup {
+    /// The relayed ack or timeout.
+    pub packet: RelayedPacket,
+    /// The state of the commitment of the packet.
+    pub outcome: CleanupOutcome,
+}
+
+/// Returns the relayed packets whose commitments are deleted when their message is applied, i.e.
+/// the acks and the timeouts.
+#[must_use]
+pub fn cleanup_packets(relayed_packets: Vec<RelayedPacket>) -> Vec<RelayedPacket> {
+    relayed_packets
+        .into_iter()
+        .filter(|packet| matches!(packet.kind, MsgKind::Acknowledgement | MsgKind::Timeout))
+        .collect()
+}
+
+/// Returns the hashed path of the commitment of a packet, as stored by the `ICS26Router`.
+#[must_use]
+pub fn hashed_commitment_path(source_client: &str, sequence: u64) -> B256 {
+    let packet: Packet = ProtoPacket {
+        sequence,
+        source_client: source_client.to_string(),
+        ..Default::default()
+    }
+    .into();
+    keccak256(packet.commitment_path())
+}
+
+/// Classifies the commitment cleanup of the packets, given the packets whose commitment is still
+/// stored and the packets whose ack or timeout landed.
+#[must_use]
+pub fn classify_cleanup(
+    packets: Vec<RelayedPacket>,
+    committed: &HashSet<RelayedPacket>,
+    landed: &HashSet<RelayedPacket>,
+) -> Vec<PacketCleanup> {
+    packets
+        .into_iter()
+        .map(|packet| {
+            let outcome = match (committed.contains(&packet), landed.contains(&packet)) {
+                (false, _) => CleanupOutcome::Deleted,
+                (true, true) => CleanupOutcome::Stale,
+                (true, false) => CleanupOutcome::NotLanded,
+            };
+            PacketCleanup { packet, outcome }
+        })
+        .collect()
+}
+
+/// Watches the commitments of the packets whose acks or timeouts were relayed, until every ack
+/// or timeout landed or the check times out, and logs an error for every stale commitment.
+///
+/// The landed acks and timeouts are searched from `start_block` on.
+/// # Errors
+/// Returns an error if the commitments or the router logs cannot be fetched.
+pub async fn verify_commitment_cleanup<P: Provider>(
+    ics26_router: &routerInstance<(), P>,
+    check: &CleanupCheck,
+    packets: Vec<RelayedPacket>,
+    start_block: u64,
+) -> Result<Vec<PacketCleanup>> {
+    let timeout = Duration::from_secs(check.timeout_secs);
+    let interval = Duration::from_secs(check.poll_interval_secs);
+
+    let start = std::time::Instant::now();
+    loop {
+        // The logs are fetched before the commitments, so that a packet landing in between is
+        // not reported as stale.
+        let event_filter = Filter::new()
+            .events([AckPacket::SIGNATURE, TimeoutPacket::SIGNATURE])
+            .address(*ics26_router.address())
+            .from_block(start_block);
+        let logs = ics26_router.provider().get_logs(&event_filter).await?;
+        let landed = introspection::eth_applied_packets(&logs);
+
+        let committed = future::try_join_all(packets.iter().map(|packet| async {
+            let commitment = ics26_router
+                .getCommitment(hashed_commitment_path(
+                    &packet.source_client,
+                    packet.sequence,
+                ))
+                .call()
+                .await?
+                ._0;
+            anyhow::Ok((packet, commitment))
+        }))
+        .await?
+        .into_iter()
+        .filter(|(_, commitment)| !commitment.is_zero())
+        .map(|(packet, _)| packet.clone())
+        .collect::<HashSet<_>>();
+
+        let cleanups = classify_cleanup(packets.clone(), &committed, &landed);
+        let pending = cleanups
+            .iter()
+            .any(|cleanup| cleanup.outcome == CleanupOutcome::NotLanded);
+        if !pending || start.elapsed() >= timeout {
+            for cleanup in &cleanups {
+                match cleanup.outcome {
+                    CleanupOutcome::Deleted => {}
+                    CleanupOutcome::Stale => tracing::error!(
+                        "The commitment of packet {} from client {} was not deleted after its {:?} landed",
+                        cleanup.packet.sequence,
+                        cleanup.packet.source_client,
+                        cleanup.packet.kind
+                    ),
+                    CleanupOutcome::NotLanded => tracing::warn!(
+                        "Timed out waiting for the {:?} of packet {} from client {} to land",
+                        cleanup.packet.kind,
+                        cleanup.packet.sequence,
+                        cleanup.packet.source_client
+                    ),
+                }
+            }
+            return Ok(cleanups);
+        }
+
+        Delay::new(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn packet(kind: MsgKind, sequence: u64) -> RelayedPacket {
+        RelayedPacket {
+            kind,
+            source_client: "client-0".to_string(),
+            sequence,
+        }
+    }
+
+    #[test]
+    fn test_cleanup_packets() {
+        let packets = cleanup_packets(vec![
+            packet(MsgKind::RecvPacket, 1),
+            packet(MsgKind::Acknowledgement, 2),
+            packet(MsgKind::Timeout, 3),
+        ]);
+        assert_eq!(
+            packets,
+            vec![
+                packet(MsgKind::Acknowledgement, 2),
+                packet(MsgKind::Timeout, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_cleanup() {
+        let packets = vec![
+            packet(MsgKind::Acknowledgement, 1),
+            packet(MsgKind::Acknowledgement, 2),
+            packet(MsgKind::Timeout, 3),
+            // Deleted by another relayer
+            packet(MsgKind::Acknowledgement, 4),
+        ];
+        let committed = HashSet::from([
+            packet(MsgKind::Acknowledgement, 2),
+            packet(MsgKind::Timeout, 3),
+        ]);
+        let landed = HashSet::from([
+            packet(MsgKind::Acknowledgement, 1),
+            packet(MsgKind::Acknowledgement, 2),
+        ]);
+
+        let outcomes = classify_cleanup(packets, &committed, &landed)
+            .into_iter()
+            .map(|cleanup| cleanup.outcome)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                CleanupOutcome::Deleted,
+                CleanupOutcome::Stale,
+                CleanupOutcome::NotLanded,
+                CleanupOutcome::Deleted,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hashed_commitment_path() {
+        let mut path = b"client-0".to_vec();
+        path.push(1);
+        path.extend_from_slice(&7_u64.to_be_bytes());
+        assert_eq!(hashed_commitment_path("client-0", 7), keccak256(path));
+    }
+}
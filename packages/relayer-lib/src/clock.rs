@@ -0,0 +1,97 @@
+//! This module defines the [`Clock`] the tx builders read the time from.
+//!
+//! The tx builders compute the timeouts of the relayed packets and wait for the light clients
+//! through a [`Clock`], which is the [`SystemClock`] in production. The tests use a
+//! [`ManualClock`] instead, whose time only moves when it is advanced or slept on, so that they
+//! neither depend on the wall clock nor sleep.
+
+use std::{
+    fmt::Debug,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures_timer::Delay;
+
+/// The source of time of the relayer.
+#[async_trait::async_trait]
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current time since the unix epoch.
+    /// # Errors
+    /// Returns an error if the current time is before the unix epoch.
+    fn now_since_unix(&self) -> anyhow::Result<Duration>;
+
+    /// Waits for `duration`.
+    async fn sleep(&self, duration: Duration);
+}
+
+/// The [`Clock`] of the operating system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[async_trait::async_trait]
+impl Clock for SystemClock {
+    fn now_since_unix(&self) -> anyhow::Result<Duration> {
+        Ok(SystemTime::now().duration_since(UNIX_EPOCH)?)
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        Delay::new(duration).await;
+    }
+}
+
+/// A [`Clock`] whose time only moves when advanced. Sleeping advances the clock by the slept
+/// duration and returns immediately.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now_since_unix: Mutex<Duration>,
+}
+
+impl ManualClock {
+    /// Creates a clock starting at `now_since_unix`.
+    #[must_use]
+    pub const fn new(now_since_unix: Duration) -> Self {
+        Self {
+            now_since_unix: Mutex::new(now_since_unix),
+        }
+    }
+
+    /// Advances the clock by `duration`.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    pub fn advance(&self, duration: Duration) {
+        *self.now_since_unix.lock().unwrap() += duration;
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for ManualClock {
+    fn now_since_unix(&self) -> anyhow::Result<Duration> {
+        Ok(*self.now_since_unix.lock().unwrap())
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(Duration::from_secs(1_700_000_000));
+        assert_eq!(
+            clock.now_since_unix().unwrap(),
+            Duration::from_secs(1_700_000_000)
+        );
+
+        clock.advance(Duration::from_secs(12));
+        futures::executor::block_on(clock.sleep(Duration::from_secs(30)));
+        assert_eq!(
+            clock.now_since_unix().unwrap(),
+            Duration::from_secs(1_700_000_042)
+        );
+    }
+}
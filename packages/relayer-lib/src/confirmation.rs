@@ -0,0 +1,86 @@
+//! This module defines the confirmation policies for source chain events.
+//!
+//! A confirmation policy decides the latest height at which events from a source chain are
+//! considered safe to relay. The listeners only scan for events at or below this height. The
+//! Ethereum tx builder waits for the events it relays to be confirmed, within the deadline of the
+//! relay, while the Cosmos SDK tx builders reject events above it.
+
+use alloy::{eips::BlockNumberOrTag, providers::Provider};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{Client, HttpClient};
+
+/// The confirmation policy for events sourced from Ethereum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum EthConfirmationPolicy {
+    /// Only events in finalized blocks are considered confirmed.
+    #[default]
+    Finalized,
+    /// Events are considered confirmed once `confirmations` blocks have been built on top.
+    Confirmations {
+        /// The number of blocks required on top of the event's block.
+        confirmations: u64,
+    },
+}
+
+/// The confirmation policy for events sourced from a Cosmos SDK chain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CosmosConfirmationPolicy {
+    /// The number of blocks the confirmed height lags behind the latest height.
+    #[serde(default)]
+    pub height_lag: u64,
+}
+
+impl EthConfirmationPolicy {
+    /// Returns the latest block number that is confirmed under this policy.
+    /// # Errors
+    /// Returns an error if the block number cannot be fetched.
+    pub async fn confirmed_block_number<P: Provider>(&self, provider: &P) -> Result<u64> {
+        match self {
+            Self::Finalized => Ok(provider
+                .get_block(BlockNumberOrTag::Finalized.into())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Finalized block not found"))?
+                .header
+                .number),
+            Self::Confirmations { confirmations } => Ok(provider
+                .get_block_number()
+                .await?
+                .saturating_sub(*confirmations)),
+        }
+    }
+}
+
+impl CosmosConfirmationPolicy {
+    /// Returns the latest height that is confirmed under this policy.
+    /// # Errors
+    /// Returns an error if the latest block cannot be fetched.
+    pub async fn confirmed_height(&self, tm_client: &HttpClient) -> Result<u64> {
+        Ok(tm_client
+            .latest_block()
+            .await?
+            .block
+            .header
+            .height
+            .value()
+            .saturating_sub(self.height_lag))
+    }
+}
+
+/// Returns an error if any of the event heights is above the confirmed height.
+/// # Errors
+/// Returns an error if an event is not yet confirmed.
+pub fn ensure_confirmed(
+    event_heights: impl IntoIterator<Item = u64>,
+    confirmed_height: u64,
+) -> Result<()> {
+    event_heights
+        .into_iter()
+        .find(|height| *height > confirmed_height)
+        .map_or(Ok(()), |height| {
+            Err(anyhow::anyhow!(
+                "Event at height {height} is not yet confirmed (confirmed height: {confirmed_height})"
+            ))
+        })
+}
@@ -1,7 +1,9 @@
 //! Define the events that can be retrieved by the relayer.
 
+use std::borrow::Cow;
+
 use alloy::{
-    primitives::{hex, Bytes},
+    primitives::{hex, Bytes, TxHash},
     rpc::types::Log,
     sol_types::{SolEvent, SolEventInterface},
 };
@@ -13,7 +15,7 @@ use ibc_proto_eureka::ibc::core::channel::v2::{Acknowledgement, Packet};
 use prost::Message;
 use tendermint::abci::Event as TmEvent;
 
-use super::cosmos_sdk;
+use super::{cosmos_sdk, PacketData};
 
 /// Events emitted by IBC Eureka implementations that the relayer is interested in.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,6 +25,10 @@ pub struct EurekaEventWithHeight {
     pub event: EurekaEvent,
     /// The height at which the event was emitted.
     pub height: u64,
+    /// The application data of the packet of the event, decoded when the event was parsed.
+    pub packet_data: Option<PacketData>,
+    /// The hash of the Ethereum transaction that emitted the event, if parsed from a log.
+    pub tx_hash: Option<TxHash>,
 }
 
 /// The event type
@@ -36,6 +42,28 @@ pub enum EurekaEvent {
 }
 
 impl EurekaEventWithHeight {
+    /// Creates an event emitted at `height`, decoding the application data of its packet.
+    #[must_use]
+    pub fn new(event: EurekaEvent, height: u64) -> Self {
+        let packet_data = Some(PacketData::decode(event.packet()));
+        Self {
+            event,
+            height,
+            packet_data,
+            tx_hash: None,
+        }
+    }
+
+    /// Returns the application data of the packet of the event, decoding it if the event was
+    /// not created with it.
+    #[must_use]
+    pub fn packet_data(&self) -> Cow<'_, PacketData> {
+        self.packet_data.as_ref().map_or_else(
+            || Cow::Owned(PacketData::decode(self.event.packet())),
+            Cow::Borrowed,
+        )
+    }
+
     /// Get the signature of the events for EVM.
     /// This is used to filter the logs.
     #[must_use]
@@ -44,6 +72,16 @@ impl EurekaEventWithHeight {
     }
 }
 
+impl EurekaEvent {
+    /// Returns the packet of the event.
+    #[must_use]
+    pub const fn packet(&self) -> &SolPacket {
+        match self {
+            Self::SendPacket(packet) | Self::WriteAcknowledgement(packet, _) => packet,
+        }
+    }
+}
+
 impl TryFrom<&Log> for EurekaEventWithHeight {
     type Error = anyhow::Error;
 
@@ -84,10 +122,12 @@ impl TryFrom<&Log> for EurekaEventWithHeight {
         }?;
 
         Ok(Self {
-            event: event_type,
-            height: log
-                .block_number
-                .ok_or_else(|| anyhow::anyhow!("Block number not found in log: {:?}", log))?,
+            tx_hash: log.transaction_hash,
+            ..Self::new(
+                event_type,
+                log.block_number
+                    .ok_or_else(|| anyhow::anyhow!("Block number not found in log: {:?}", log))?,
+            )
         })
     }
 }
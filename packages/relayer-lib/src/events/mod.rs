@@ -2,5 +2,10 @@
 
 pub mod cosmos_sdk;
 mod eureka;
+mod packet_data;
 
 pub use eureka::{EurekaEvent, EurekaEventWithHeight};
+pub use packet_data::{
+    decode_ics20_payload, Ics20Transfer, PacketData, ICS20_ABI_ENCODING, ICS20_JSON_ENCODING,
+    ICS20_TRANSFER_PORT,
+};
@@ -0,0 +1,212 @@
+//! Defines [`PacketData`], the application data of a packet decoded once when its event is parsed.
+//!
+//! The packet filters, the metrics and the value accounting of the relayer all need the ICS20
+//! transfers carried by the packets they handle. Decoding the ABI or JSON encoded payloads when
+//! the event is parsed spares each of them its own decoder.
+
+use alloy::{primitives::U256, sol_types::SolValue};
+use anyhow::Result;
+use ibc_eureka_solidity_types::{
+    ics26::IICS26RouterMsgs::{Packet, Payload},
+    msgs::IICS20TransferMsgs::FungibleTokenPacketData,
+};
+use serde::Deserialize;
+
+/// The port of the ICS20 transfer application.
+pub const ICS20_TRANSFER_PORT: &str = "transfer";
+/// The ABI encoding of ICS20 packet data.
+pub const ICS20_ABI_ENCODING: &str = "application/x-solidity-abi";
+/// The JSON encoding of ICS20 packet data.
+pub const ICS20_JSON_ENCODING: &str = "application/json";
+
+/// The application data of a packet, with its counterparty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PacketData {
+    /// The client on the source chain, tracking the destination chain.
+    pub source_client: String,
+    /// The client on the destination chain, tracking the source chain.
+    pub dest_client: String,
+    /// The sequence of the packet.
+    pub sequence: u64,
+    /// The transfers of the ICS20 payloads of the packet, in order.
+    pub transfers: Vec<Ics20Transfer>,
+    /// The decoding errors of the ICS20 payloads that could not be decoded.
+    pub malformed_transfers: Vec<String>,
+}
+
+/// The decoded data of an ICS20 payload.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ics20Transfer {
+    /// The denom of the transferred tokens.
+    pub denom: String,
+    /// The sender on the source chain.
+    pub sender: String,
+    /// The receiver on the destination chain.
+    pub receiver: String,
+    /// The transferred amount.
+    pub amount: U256,
+    /// The memo of the transfer.
+    pub memo: String,
+}
+
+impl PacketData {
+    /// Decodes the application data of a packet. The payloads of other applications than ICS20
+    /// are skipped.
+    #[must_use]
+    pub fn decode(packet: &Packet) -> Self {
+        let mut packet_data = Self {
+            source_client: packet.sourceClient.clone(),
+            dest_client: packet.destClient.clone(),
+            sequence: packet.sequence,
+            ..Default::default()
+        };
+        for payload in packet
+            .payloads
+            .iter()
+            .filter(|payload| payload.sourcePort == ICS20_TRANSFER_PORT)
+        {
+            match decode_ics20_payload(payload) {
+                Ok(transfer) => packet_data.transfers.push(transfer),
+                Err(e) => packet_data.malformed_transfers.push(e.to_string()),
+            }
+        }
+        packet_data
+    }
+
+    /// Returns the ICS20 transfers of the packet.
+    /// # Errors
+    /// Returns an error if an ICS20 payload of the packet could not be decoded.
+    pub fn checked_transfers(&self) -> Result<&[Ics20Transfer]> {
+        if let Some(e) = self.malformed_transfers.first() {
+            anyhow::bail!("malformed ICS20 payload in packet {}: {e}", self.sequence);
+        }
+        Ok(&self.transfers)
+    }
+}
+
+/// Decodes the data of an ICS20 payload.
+/// # Errors
+/// Returns an error if the payload has an unsupported encoding or cannot be decoded.
+pub fn decode_ics20_payload(payload: &Payload) -> Result<Ics20Transfer> {
+    match payload.encoding.as_str() {
+        ICS20_ABI_ENCODING => {
+            let data = FungibleTokenPacketData::abi_decode(&payload.value, true)?;
+            Ok(Ics20Transfer {
+                denom: data.denom,
+                sender: data.sender,
+                receiver: data.receiver,
+                amount: data.amount,
+                memo: data.memo,
+            })
+        }
+        ICS20_JSON_ENCODING => {
+            #[derive(Deserialize)]
+            struct JsonPacketData {
+                denom: String,
+                amount: String,
+                #[serde(default)]
+                sender: String,
+                #[serde(default)]
+                receiver: String,
+                #[serde(default)]
+                memo: String,
+            }
+
+            let data: JsonPacketData = serde_json::from_slice(&payload.value)?;
+            Ok(Ics20Transfer {
+                denom: data.denom,
+                sender: data.sender,
+                receiver: data.receiver,
+                amount: data.amount.parse()?,
+                memo: data.memo,
+            })
+        }
+        encoding => anyhow::bail!("unsupported ICS20 payload encoding: {encoding}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_packet_data() {
+        let abi_data = FungibleTokenPacketData {
+            denom: "uatom".to_string(),
+            sender: "cosmos1sender".to_string(),
+            receiver: "0xreceiver".to_string(),
+            amount: U256::from(42),
+            memo: "memo".to_string(),
+        };
+        let packet = Packet {
+            sequence: 7,
+            sourceClient: "client-0".to_string(),
+            destClient: "client-1".to_string(),
+            payloads: vec![
+                Payload {
+                    sourcePort: ICS20_TRANSFER_PORT.to_string(),
+                    encoding: ICS20_ABI_ENCODING.to_string(),
+                    value: abi_data.abi_encode().into(),
+                    ..Default::default()
+                },
+                Payload {
+                    sourcePort: ICS20_TRANSFER_PORT.to_string(),
+                    encoding: ICS20_JSON_ENCODING.to_string(),
+                    value: br#"{"denom":"uosmo","amount":"5","sender":"osmo1sender"}"#
+                        .to_vec()
+                        .into(),
+                    ..Default::default()
+                },
+                Payload {
+                    sourcePort: "other".to_string(),
+                    encoding: ICS20_JSON_ENCODING.to_string(),
+                    value: b"not json".to_vec().into(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let packet_data = PacketData::decode(&packet);
+        assert_eq!(packet_data.source_client, "client-0");
+        assert_eq!(packet_data.dest_client, "client-1");
+        assert_eq!(packet_data.sequence, 7);
+        assert_eq!(
+            packet_data.checked_transfers().unwrap(),
+            [
+                Ics20Transfer {
+                    denom: "uatom".to_string(),
+                    sender: "cosmos1sender".to_string(),
+                    receiver: "0xreceiver".to_string(),
+                    amount: U256::from(42),
+                    memo: "memo".to_string(),
+                },
+                Ics20Transfer {
+                    denom: "uosmo".to_string(),
+                    sender: "osmo1sender".to_string(),
+                    amount: U256::from(5),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_malformed_packet_data() {
+        let packet = Packet {
+            sequence: 3,
+            payloads: vec![Payload {
+                sourcePort: ICS20_TRANSFER_PORT.to_string(),
+                encoding: ICS20_ABI_ENCODING.to_string(),
+                value: vec![0xff].into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let packet_data = PacketData::decode(&packet);
+        assert!(packet_data.transfers.is_empty());
+        assert_eq!(packet_data.malformed_transfers.len(), 1);
+        assert!(packet_data.checked_transfers().is_err());
+    }
+}
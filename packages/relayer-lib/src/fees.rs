@@ -0,0 +1,238 @@
+//! This module defines the pricing of the fees of relay transactions to Cosmos SDK chains.
+//!
+//! By default the submitter pays the fees in the native denom of the chain. With a [`FeeConfig`],
+//! the fee of a relay transaction is quoted in another denom accepted by the chain, such as the IBC
+//! voucher of a bridged token, so that a relayer can operate on a chain where it only holds bridged
+//! tokens. The gas price is either fixed in the configuration, or queried from the fee market
+//! module of the chain with [`query_gas_price`], which prices every accepted denom dynamically.
+
+use anyhow::Result;
+use cosmos_sdk_proto::cosmos::base::v1beta1::{Coin, DecCoin};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{Client, HttpClient};
+
+/// The number of decimals of the Cosmos SDK decimals.
+pub const DEC_PRECISION: u32 = 18;
+/// The default gas adjustment, in percent of the planned gas.
+pub const DEFAULT_GAS_ADJUSTMENT_PERCENT: u64 = 130;
+/// The path of the gas price query of the fee market module.
+pub const FEE_MARKET_GAS_PRICE_PATH: &str = "/feemarket.feemarket.v1.Query/GasPrice";
+
+/// The fee payment of the relay transactions of a module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeConfig {
+    /// The denom the fees are paid in, e.g. an `ibc/` voucher denom.
+    pub denom: String,
+    /// The source of the gas price in `denom`.
+    pub gas_price: GasPriceSource,
+    /// The gas limit of a transaction, in percent of its planned gas.
+    #[serde(default = "default_gas_adjustment_percent")]
+    pub gas_adjustment_percent: u64,
+}
+
+const fn default_gas_adjustment_percent() -> u64 {
+    DEFAULT_GAS_ADJUSTMENT_PERCENT
+}
+
+/// The source of the gas price of a fee denom.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum GasPriceSource {
+    /// A fixed gas price.
+    Fixed {
+        /// The gas price as a decimal, e.g. `0.025`.
+        price: String,
+    },
+    /// The gas price of the fee market module of the chain, with a premium to stay above the
+    /// base fee when it rises between the quote and the inclusion of the transaction.
+    FeeMarket {
+        /// The premium over the queried gas price, in percent.
+        #[serde(default)]
+        premium_percent: u64,
+    },
+}
+
+/// The fee quote of a relay transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeQuote {
+    /// The gas limit of the transaction.
+    pub gas_limit: u64,
+    /// The fee of the transaction.
+    pub fee: Coin,
+}
+
+/// The gas price query of the fee market module.
+#[derive(Clone, PartialEq, Message)]
+struct GasPriceRequest {
+    /// The denom of the gas price.
+    #[prost(string, tag = "1")]
+    denom: String,
+}
+
+/// The gas price response of the fee market module.
+#[derive(Clone, PartialEq, Message)]
+struct GasPriceResponse {
+    /// The gas price.
+    #[prost(message, optional, tag = "1")]
+    price: Option<DecCoin>,
+}
+
+/// Parses a decimal, such as `0.025`, into its number of 10^-18 units.
+/// # Errors
+/// Returns an error if the decimal is malformed, has more than 18 decimals or overflows.
+pub fn parse_dec(dec: &str) -> Result<u128> {
+    let (integer, fraction) = dec.split_once('.').unwrap_or((dec, ""));
+    anyhow::ensure!(
+        !integer.is_empty()
+            && integer.bytes().all(|b| b.is_ascii_digit())
+            && fraction.bytes().all(|b| b.is_ascii_digit())
+            && fraction.len() <= DEC_PRECISION as usize,
+        "invalid decimal `{dec}`"
+    );
+
+    let scale = 10_u128.pow(DEC_PRECISION);
+    let fraction_units = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u128>()? * 10_u128.pow(DEC_PRECISION - u32::try_from(fraction.len())?)
+    };
+    integer
+        .parse::<u128>()?
+        .checked_mul(scale)
+        .and_then(|units| units.checked_add(fraction_units))
+        .ok_or_else(|| anyhow::anyhow!("decimal `{dec}` overflows"))
+}
+
+/// Returns the fee of `gas_limit` at a gas price in 10^-18 units, rounded up.
+#[must_use]
+pub fn fee_amount(gas_limit: u64, gas_price_units: u128) -> u128 {
+    u128::from(gas_limit)
+        .saturating_mul(gas_price_units)
+        .div_ceil(10_u128.pow(DEC_PRECISION))
+}
+
+/// Queries the gas price of a denom from the fee market module, in 10^-18 units.
+/// # Errors
+/// Returns an error if the query fails, e.g. because the chain has no fee market module or does
+/// not accept the denom.
+pub async fn query_gas_price(tm_client: &HttpClient, denom: &str) -> Result<u128> {
+    let abci_resp = tm_client
+        .abci_query(
+            Some(FEE_MARKET_GAS_PRICE_PATH.to_string()),
+            GasPriceRequest {
+                denom: denom.to_string(),
+            }
+            .encode_to_vec(),
+            None,
+            false,
+        )
+        .await?;
+    anyhow::ensure!(
+        abci_resp.code.is_ok(),
+        "gas price query for {denom} failed: {}",
+        abci_resp.log
+    );
+
+    let price = GasPriceResponse::decode(abci_resp.value.as_slice())?
+        .price
+        .ok_or_else(|| anyhow::anyhow!("no gas price returned for {denom}"))?;
+    anyhow::ensure!(
+        price.denom == denom,
+        "gas price returned in {}, expected {denom}",
+        price.denom
+    );
+    // The decimals are encoded as integers of 10^-18 units in protobuf
+    Ok(price.amount.parse()?)
+}
+
+impl FeeConfig {
+    /// Returns the gas limit of a transaction with the given planned gas.
+    #[must_use]
+    pub const fn gas_limit(&self, planned_gas: u64) -> u64 {
+        planned_gas.saturating_mul(self.gas_adjustment_percent) / 100
+    }
+
+    /// Returns the gas price in `denom`, in 10^-18 units.
+    /// # Errors
+    /// Returns an error if the fixed price is malformed, or if the price cannot be queried.
+    pub async fn gas_price(&self, tm_client: &HttpClient) -> Result<u128> {
+        match &self.gas_price {
+            GasPriceSource::Fixed { price } => parse_dec(price),
+            GasPriceSource::FeeMarket { premium_percent } => {
+                let price = query_gas_price(tm_client, &self.denom).await?;
+                Ok(price.saturating_mul(100 + u128::from(*premium_percent)) / 100)
+            }
+        }
+    }
+
+    /// Quotes the fee of a transaction with the given planned gas.
+    /// # Errors
+    /// Returns an error if the gas price cannot be determined.
+    pub async fn quote(&self, tm_client: &HttpClient, planned_gas: u64) -> Result<FeeQuote> {
+        let gas_limit = self.gas_limit(planned_gas);
+        let gas_price = self.gas_price(tm_client).await?;
+
+        Ok(FeeQuote {
+            gas_limit,
+            fee: Coin {
+                denom: self.denom.clone(),
+                amount: fee_amount(gas_limit, gas_price).to_string(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_dec() {
+        assert_eq!(parse_dec("0.025").unwrap(), 25_000_000_000_000_000);
+        assert_eq!(parse_dec("1").unwrap(), 1_000_000_000_000_000_000);
+        assert_eq!(parse_dec("1.5").unwrap(), 1_500_000_000_000_000_000);
+        assert_eq!(parse_dec("0.000000000000000001").unwrap(), 1);
+
+        assert!(parse_dec("").is_err());
+        assert!(parse_dec(".5").is_err());
+        assert!(parse_dec("-1").is_err());
+        assert!(parse_dec("0.0000000000000000001").is_err());
+        assert!(parse_dec("1e3").is_err());
+    }
+
+    #[test]
+    fn test_fee_amount() {
+        let config = FeeConfig {
+            denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+                .to_string(),
+            gas_price: GasPriceSource::Fixed {
+                price: "0.025".to_string(),
+            },
+            gas_adjustment_percent: DEFAULT_GAS_ADJUSTMENT_PERCENT,
+        };
+        assert_eq!(config.gas_limit(1_000_000), 1_300_000);
+        assert_eq!(fee_amount(1_300_000, parse_dec("0.025").unwrap()), 32_500);
+        // Fractions of the smallest unit are rounded up
+        assert_eq!(fee_amount(3, parse_dec("0.5").unwrap()), 2);
+        assert_eq!(fee_amount(0, parse_dec("0.5").unwrap()), 0);
+    }
+
+    #[test]
+    fn test_fee_config_serde() {
+        let config: FeeConfig = serde_json::from_str(
+            r#"{"denom": "ibc/ABCD", "gas_price": {"type": "fee_market", "premium_percent": 10}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.gas_price,
+            GasPriceSource::FeeMarket {
+                premium_percent: 10
+            }
+        );
+        assert_eq!(
+            config.gas_adjustment_percent,
+            DEFAULT_GAS_ADJUSTMENT_PERCENT
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! This module defines the monitoring of the finality lag of an Ethereum source chain.
+//!
+//! Packets from Ethereum can only be relayed once they are finalized, and once the light client
+//! on the target chain is updated past their block. The distance between the beacon head and the
+//! finalized slot, and between the finalized execution block and the latest execution block of the
+//! light client, are therefore the leading indicators of the relay latency. A [`FinalityLag`] is
+//! sampled periodically by the modules with a [`FinalityLagMonitor`].
+
+use anyhow::Result;
+use ethereum_apis::beacon_api::client::BeaconApiClient;
+use ethereum_light_client::client_state::ClientState;
+use serde::{Deserialize, Serialize};
+
+/// The default interval between two samples of the finality lag in seconds.
+pub const DEFAULT_SAMPLE_INTERVAL_SECS: u64 = 60;
+
+/// The monitoring of the finality lag of a module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityLagMonitor {
+    /// The interval between two samples in seconds.
+    #[serde(default = "default_sample_interval_secs")]
+    pub interval_secs: u64,
+    /// The Ethereum light clients on the target chain whose execution lag is tracked.
+    #[serde(default)]
+    pub client_ids: Vec<String>,
+    /// The finality lag in slots above which an alert is logged.
+    #[serde(default)]
+    pub alert_finality_lag_slots: Option<u64>,
+    /// The execution lag of a light client in blocks above which an alert is logged.
+    #[serde(default)]
+    pub alert_client_lag_blocks: Option<u64>,
+}
+
+impl Default for FinalityLagMonitor {
+    fn default() -> Self {
+        Self {
+            interval_secs: DEFAULT_SAMPLE_INTERVAL_SECS,
+            client_ids: Vec::new(),
+            alert_finality_lag_slots: None,
+            alert_client_lag_blocks: None,
+        }
+    }
+}
+
+const fn default_sample_interval_secs() -> u64 {
+    DEFAULT_SAMPLE_INTERVAL_SECS
+}
+
+/// A sample of the finality of the beacon chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityLag {
+    /// The slot of the beacon head.
+    pub head_slot: u64,
+    /// The latest finalized slot.
+    pub finalized_slot: u64,
+    /// The execution block number of the latest finalized slot.
+    pub finalized_block_number: u64,
+}
+
+impl FinalityLag {
+    /// Samples the beacon head and the latest finalized beacon block.
+    /// # Errors
+    /// Returns an error if the beacon blocks cannot be fetched.
+    pub async fn fetch(beacon_api_client: &BeaconApiClient) -> Result<Self> {
+        let head = beacon_api_client.beacon_block("head").await?;
+        let finalized = beacon_api_client.beacon_block("finalized").await?;
+
+        Ok(Self {
+            head_slot: head.message.slot,
+            finalized_slot: finalized.message.slot,
+            finalized_block_number: finalized.message.body.execution_payload.block_number,
+        })
+    }
+
+    /// Returns the number of slots between the beacon head and the latest finalized slot.
+    #[must_use]
+    pub const fn slots(&self) -> u64 {
+        self.head_slot.saturating_sub(self.finalized_slot)
+    }
+
+    /// Returns the number of finalized execution blocks the light client is behind.
+    #[must_use]
+    pub const fn client_lag_blocks(&self, client_state: &ClientState) -> u64 {
+        self.finalized_block_number
+            .saturating_sub(client_state.latest_execution_block_number)
+    }
+}
+
+impl FinalityLagMonitor {
+    /// Returns the alerts raised by a sample of the finality lag and of the execution lags of the
+    /// light clients, keyed by client id.
+    #[must_use]
+    pub fn alerts(&self, lag: &FinalityLag, client_lags: &[(String, u64)]) -> Vec<String> {
+        let mut alerts = Vec::new();
+        if let Some(max_slots) = self.alert_finality_lag_slots {
+            if lag.slots() > max_slots {
+                alerts.push(format!(
+                    "finality lags {} slots behind the head (head: {}, finalized: {}), above {max_slots}",
+                    lag.slots(),
+                    lag.head_slot,
+                    lag.finalized_slot
+                ));
+            }
+        }
+        if let Some(max_blocks) = self.alert_client_lag_blocks {
+            alerts.extend(
+                client_lags
+                    .iter()
+                    .filter(|(_, blocks)| *blocks > max_blocks)
+                    .map(|(client_id, blocks)| {
+                        format!(
+                            "client {client_id} lags {blocks} finalized blocks behind, above {max_blocks}"
+                        )
+                    }),
+            );
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_finality_lag_alerts() {
+        let lag = FinalityLag {
+            head_slot: 200,
+            finalized_slot: 100,
+            finalized_block_number: 90,
+        };
+        assert_eq!(lag.slots(), 100);
+
+        let client_lags = vec![("client-0".to_string(), 10), ("client-1".to_string(), 1)];
+
+        // No alert without thresholds
+        let monitor = FinalityLagMonitor::default();
+        assert!(monitor.alerts(&lag, &client_lags).is_empty());
+
+        let monitor = FinalityLagMonitor {
+            alert_finality_lag_slots: Some(64),
+            alert_client_lag_blocks: Some(5),
+            ..Default::default()
+        };
+        let alerts = monitor.alerts(&lag, &client_lags);
+        assert_eq!(alerts.len(), 2);
+        assert!(alerts[0].contains("100 slots"));
+        assert!(alerts[1].contains("client-0"));
+
+        // A head behind the finalized slot, as reported by a lagging node, is no lag
+        let lag = FinalityLag {
+            head_slot: 90,
+            ..lag
+        };
+        assert_eq!(lag.slots(), 0);
+    }
+}
@@ -0,0 +1,286 @@
+//! This module defines the protection of the relays toward Ethereum against base fee spikes.
+//!
+//! While the base fee of Ethereum exceeds the ceiling of a [`GasSpikePolicy`], the
+//! [`GasSpikeGuard`] defers the non-urgent messages of the relays, i.e. the acks, the timeouts and
+//! the recvs of the packets that do not time out soon. The recvs of the packets about to time out
+//! are still relayed, since deferring them would turn them into timeouts. The deferred relays are
+//! queued, and resumed once the base fee falls back below the ceiling.
+
+use std::sync::Mutex;
+
+use alloy::primitives::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{EurekaEvent, EurekaEventWithHeight};
+
+/// The default window before the timeout of a packet within which its recv is urgent, in seconds.
+pub const DEFAULT_URGENT_WINDOW_SECS: u64 = 30 * 60;
+/// The default interval between two samples of the base fee in seconds.
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 12;
+
+/// The gas spike policy of a relayer module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSpikePolicy {
+    /// The base fee ceiling in wei, above which the non-urgent messages are deferred.
+    pub max_base_fee: u128,
+    /// The window before the timeout of a packet within which its recv is urgent, in seconds.
+    #[serde(default = "default_urgent_window_secs")]
+    pub urgent_window_secs: u64,
+    /// The interval between two samples of the base fee in seconds.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+}
+
+const fn default_urgent_window_secs() -> u64 {
+    DEFAULT_URGENT_WINDOW_SECS
+}
+
+const fn default_poll_interval_secs() -> u64 {
+    DEFAULT_POLL_INTERVAL_SECS
+}
+
+/// The sequences of the packets whose messages were deferred.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeferredSequences {
+    /// The sequences of the deferred recvs, sent from the source chain.
+    pub recv: Vec<u64>,
+    /// The sequences of the deferred acks, sent from Ethereum.
+    pub ack: Vec<u64>,
+    /// The sequences of the deferred timeouts, sent from Ethereum.
+    pub timeout: Vec<u64>,
+}
+
+impl DeferredSequences {
+    /// Returns `true` if no message was deferred.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.recv.is_empty() && self.ack.is_empty() && self.timeout.is_empty()
+    }
+}
+
+/// A relay whose messages were deferred during a gas spike, resumed once it ends.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeferredRelay {
+    /// The source chain of the relay.
+    pub src_chain: String,
+    /// The destination chain of the relay.
+    pub dst_chain: String,
+    /// The client on the source chain.
+    pub src_client_id: String,
+    /// The client on the destination chain.
+    pub dst_client_id: String,
+    /// The source transactions of the relay.
+    pub source_tx_ids: Vec<Bytes>,
+    /// The timeout transactions of the relay.
+    pub timeout_tx_ids: Vec<Bytes>,
+    /// The sequences of the deferred packets.
+    pub sequences: DeferredSequences,
+    /// The unix timestamp at which the relay was deferred.
+    pub deferred_at: u64,
+}
+
+/// The state of a [`GasSpikeGuard`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSpikeState {
+    /// The latest sampled base fee in wei, if any.
+    pub base_fee: Option<u128>,
+    /// Whether the latest sampled base fee exceeds the ceiling.
+    pub spiking: bool,
+    /// The relays deferred during the current spike, oldest first.
+    pub deferred: Vec<DeferredRelay>,
+}
+
+/// Defers the non-urgent messages of the relays during base fee spikes.
+#[derive(Debug)]
+pub struct GasSpikeGuard {
+    policy: GasSpikePolicy,
+    state: Mutex<GasSpikeState>,
+}
+
+impl GasSpikeGuard {
+    /// Creates a guard for the given policy, which is not spiking until a base fee is sampled.
+    #[must_use]
+    pub fn new(policy: GasSpikePolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the policy of the guard.
+    #[must_use]
+    pub const fn policy(&self) -> &GasSpikePolicy {
+        &self.policy
+    }
+
+    /// Returns a snapshot of the state of the guard.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn state(&self) -> GasSpikeState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Records a sample of the base fee.
+    /// # Returns
+    /// The deferred relays to resume, if the sample ends a spike.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    pub fn observe_base_fee(&self, base_fee: u128) -> Vec<DeferredRelay> {
+        let mut state = self.state.lock().unwrap();
+        state.base_fee = Some(base_fee);
+        state.spiking = base_fee > self.policy.max_base_fee;
+        if state.spiking {
+            vec![]
+        } else {
+            std::mem::take(&mut state.deferred)
+        }
+    }
+
+    /// Queues a relay to resume once the spike ends.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    pub fn queue(&self, relay: DeferredRelay) {
+        self.state.lock().unwrap().deferred.push(relay);
+    }
+
+    /// Removes the non-urgent messages from the events of a relay while the base fee spikes.
+    ///
+    /// The `src_events` are the events of the source chain, which carry the recvs and the acks,
+    /// and the `target_events` are the events of Ethereum, which carry the timeouts.
+    /// # Returns
+    /// The sequences of the deferred packets, which is empty if the base fee does not spike.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    pub fn defer_non_urgent(
+        &self,
+        src_events: &mut Vec<EurekaEventWithHeight>,
+        target_events: &mut Vec<EurekaEventWithHeight>,
+        now: u64,
+    ) -> DeferredSequences {
+        let mut deferred = DeferredSequences::default();
+        if !self.state.lock().unwrap().spiking {
+            return deferred;
+        }
+
+        src_events.retain(|event| match &event.event {
+            EurekaEvent::SendPacket(packet) => {
+                let urgent = packet.timeoutTimestamp > now
+                    && packet.timeoutTimestamp - now <= self.policy.urgent_window_secs;
+                if !urgent {
+                    deferred.recv.push(packet.sequence);
+                }
+                urgent
+            }
+            EurekaEvent::WriteAcknowledgement(packet, _) => {
+                deferred.ack.push(packet.sequence);
+                false
+            }
+        });
+        deferred.timeout = target_events
+            .drain(..)
+            .map(|event| event.event.packet().sequence)
+            .collect();
+
+        deferred
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs::Packet;
+
+    use super::*;
+
+    fn guard() -> GasSpikeGuard {
+        GasSpikeGuard::new(GasSpikePolicy {
+            max_base_fee: 50_000_000_000,
+            urgent_window_secs: 600,
+            poll_interval_secs: DEFAULT_POLL_INTERVAL_SECS,
+        })
+    }
+
+    fn packet(sequence: u64, timeout_timestamp: u64) -> Packet {
+        Packet {
+            sequence,
+            timeoutTimestamp: timeout_timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn deferred_relay(src_chain: &str) -> DeferredRelay {
+        DeferredRelay {
+            src_chain: src_chain.to_string(),
+            dst_chain: "1".to_string(),
+            src_client_id: "08-wasm-0".to_string(),
+            dst_client_id: "cosmoshub-0".to_string(),
+            source_tx_ids: vec![],
+            timeout_tx_ids: vec![],
+            sequences: DeferredSequences::default(),
+            deferred_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_defer_non_urgent() {
+        let guard = guard();
+        let now = 1_000_000;
+        let mut src_events = vec![
+            // Times out within the urgent window
+            EurekaEventWithHeight::new(EurekaEvent::SendPacket(packet(1, now + 300)), 1),
+            // Times out later
+            EurekaEventWithHeight::new(EurekaEvent::SendPacket(packet(2, now + 3600)), 1),
+            EurekaEventWithHeight::new(
+                EurekaEvent::WriteAcknowledgement(packet(3, now + 300), vec![]),
+                1,
+            ),
+        ];
+        let mut target_events = vec![EurekaEventWithHeight::new(
+            EurekaEvent::SendPacket(packet(4, now - 1)),
+            1,
+        )];
+
+        // Nothing is deferred before the base fee spikes
+        let deferred = guard.defer_non_urgent(&mut src_events, &mut target_events, now);
+        assert!(deferred.is_empty());
+        assert_eq!(src_events.len(), 3);
+
+        assert!(guard.observe_base_fee(60_000_000_000).is_empty());
+        let deferred = guard.defer_non_urgent(&mut src_events, &mut target_events, now);
+        assert_eq!(
+            deferred,
+            DeferredSequences {
+                recv: vec![2],
+                ack: vec![3],
+                timeout: vec![4],
+            }
+        );
+        assert_eq!(src_events.len(), 1);
+        assert_eq!(src_events[0].event.packet().sequence, 1);
+        assert!(target_events.is_empty());
+    }
+
+    #[test]
+    fn test_resume_deferred_relays() {
+        let guard = guard();
+        assert!(guard.observe_base_fee(60_000_000_000).is_empty());
+        guard.queue(deferred_relay("cosmoshub-4"));
+        guard.queue(deferred_relay("osmosis-1"));
+
+        // Still spiking
+        assert!(guard.observe_base_fee(55_000_000_000).is_empty());
+        assert_eq!(guard.state().deferred.len(), 2);
+
+        // The deferred relays are resumed once, in order
+        let resumed = guard.observe_base_fee(40_000_000_000);
+        assert_eq!(
+            resumed,
+            vec![deferred_relay("cosmoshub-4"), deferred_relay("osmosis-1")]
+        );
+        assert!(guard.observe_base_fee(40_000_000_000).is_empty());
+
+        let state = guard.state();
+        assert!(!state.spiking);
+        assert_eq!(state.base_fee, Some(40_000_000_000));
+    }
+}
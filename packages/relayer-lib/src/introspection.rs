@@ -0,0 +1,260 @@
+//! This module defines the introspection of landed relay transactions.
+//!
+//! A relay transaction can succeed while some of its packets are silently skipped by the IBC
+//! handler, for example when another relayer delivered them first. The helpers in this module
+//! parse the events of the landed transaction to confirm which of the relayed packets were
+//! actually applied on the destination chain.
+
+use std::collections::HashSet;
+
+use alloy::{
+    primitives::{Address, TxHash},
+    providers::Provider,
+    rpc::types::Log,
+    sol_types::{SolCall, SolEventInterface, SolInterface},
+};
+use anyhow::Result;
+use ibc_eureka_solidity_types::ics26::router::{multicallCall, routerCalls, routerEvents};
+use ibc_proto_eureka::{
+    cosmos::tx::v1beta1::TxBody,
+    ibc::core::channel::v2::{MsgAcknowledgement, MsgRecvPacket, MsgTimeout, Packet},
+};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint::{abci::Event as TmEvent, Hash};
+use tendermint_rpc::{Client, HttpClient};
+
+use crate::{events::cosmos_sdk, tx_builder::plan::MsgKind};
+
+/// A packet message submitted in a relay transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RelayedPacket {
+    /// The kind of the packet message.
+    pub kind: MsgKind,
+    /// The source client of the packet.
+    pub source_client: String,
+    /// The sequence of the packet.
+    pub sequence: u64,
+}
+
+/// The outcome of a relayed packet message on the destination chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PacketOutcome {
+    /// The IBC handler applied the packet message.
+    Applied,
+    /// The IBC handler skipped the packet message without failing the transaction.
+    Skipped,
+}
+
+/// The outcome of a single packet message of a landed relay transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketApplication {
+    /// The relayed packet message.
+    pub packet: RelayedPacket,
+    /// The outcome of the packet message.
+    pub outcome: PacketOutcome,
+}
+
+/// Returns the packet messages in a relay transaction built for a Cosmos SDK chain.
+/// # Errors
+/// Returns an error if the transaction or any of its packet messages cannot be decoded.
+pub fn cosmos_relayed_packets(tx: &[u8]) -> Result<Vec<RelayedPacket>> {
+    TxBody::decode(tx)?
+        .messages
+        .into_iter()
+        .filter_map(|msg| {
+            let kind = MsgKind::from_cosmos_type_url(&msg.type_url);
+            let packet = match kind {
+                MsgKind::RecvPacket => MsgRecvPacket::decode(msg.value.as_slice())
+                    .map(|msg| msg.packet)
+                    .map_err(anyhow::Error::from),
+                MsgKind::Acknowledgement => MsgAcknowledgement::decode(msg.value.as_slice())
+                    .map(|msg| msg.packet)
+                    .map_err(anyhow::Error::from),
+                MsgKind::Timeout => MsgTimeout::decode(msg.value.as_slice())
+                    .map(|msg| msg.packet)
+                    .map_err(anyhow::Error::from),
+                MsgKind::UpdateClient | MsgKind::Other => return None,
+            };
+            Some(packet.and_then(|packet| {
+                let Packet {
+                    source_client,
+                    sequence,
+                    ..
+                } = packet.ok_or_else(|| anyhow::anyhow!("Packet message without a packet"))?;
+                Ok(RelayedPacket {
+                    kind,
+                    source_client,
+                    sequence,
+                })
+            }))
+        })
+        .collect()
+}
+
+/// Returns the packet calls in a relay transaction built for the `ICS26Router` on Ethereum.
+/// # Errors
+/// Returns an error if the multicall or any of its calls cannot be decoded.
+pub fn eth_relayed_packets(tx: &[u8]) -> Result<Vec<RelayedPacket>> {
+    multicallCall::abi_decode(tx, true)?
+        .data
+        .iter()
+        .filter_map(|call| {
+            let (kind, packet) = match routerCalls::abi_decode(call, true) {
+                Ok(routerCalls::recvPacket(call)) => (MsgKind::RecvPacket, call.msg_.packet),
+                Ok(routerCalls::ackPacket(call)) => (MsgKind::Acknowledgement, call.msg_.packet),
+                Ok(routerCalls::timeoutPacket(call)) => (MsgKind::Timeout, call.msg_.packet),
+                Ok(_) => return None,
+                Err(e) => return Some(Err(e.into())),
+            };
+            Some(Ok(RelayedPacket {
+                kind,
+                source_client: packet.sourceClient,
+                sequence: packet.sequence,
+            }))
+        })
+        .collect()
+}
+
+/// Returns the packet messages applied according to the events of a Cosmos SDK transaction.
+#[must_use]
+pub fn cosmos_applied_packets(events: &[TmEvent]) -> HashSet<RelayedPacket> {
+    events
+        .iter()
+        .filter_map(|event| {
+            let kind = match event.kind.as_str() {
+                cosmos_sdk::EVENT_TYPE_RECV_PACKET => MsgKind::RecvPacket,
+                cosmos_sdk::EVENT_TYPE_ACKNOWLEDGE_PACKET => MsgKind::Acknowledgement,
+                cosmos_sdk::EVENT_TYPE_TIMEOUT_PACKET => MsgKind::Timeout,
+                _ => return None,
+            };
+            let attribute = |key: &str| {
+                event
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key_str().is_ok_and(|k| k == key))
+                    .and_then(|attr| attr.value_str().ok())
+            };
+            Some(RelayedPacket {
+                kind,
+                source_client: attribute(cosmos_sdk::ATTRIBUTE_KEY_SRC_CLIENT)?.to_string(),
+                sequence: attribute(cosmos_sdk::ATTRIBUTE_KEY_SEQUENCE)?
+                    .parse()
+                    .ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the packet calls applied according to the `ICS26Router` logs of an Ethereum
+/// transaction.
+///
+/// A received packet is considered applied when its acknowledgement is written, while the router
+/// emits a `Noop` event instead for packets that were already delivered.
+#[must_use]
+pub fn eth_applied_packets(logs: &[Log]) -> HashSet<RelayedPacket> {
+    logs.iter()
+        .filter_map(|log| {
+            let (kind, packet) = match routerEvents::decode_log(&log.inner, true).ok()?.data {
+                routerEvents::WriteAcknowledgement(event) => (MsgKind::RecvPacket, event.packet),
+                routerEvents::AckPacket(event) => (MsgKind::Acknowledgement, event.packet),
+                routerEvents::TimeoutPacket(event) => (MsgKind::Timeout, event.packet),
+                _ => return None,
+            };
+            Some(RelayedPacket {
+                kind,
+                source_client: packet.sourceClient,
+                sequence: packet.sequence,
+            })
+        })
+        .collect()
+}
+
+/// Matches the relayed packet messages against the applied ones, logging a warning for every
+/// packet message that was skipped by the IBC handler.
+#[must_use]
+pub fn confirm_packet_application(
+    relayed: Vec<RelayedPacket>,
+    applied: &HashSet<RelayedPacket>,
+) -> Vec<PacketApplication> {
+    relayed
+        .into_iter()
+        .map(|packet| {
+            let outcome = if applied.contains(&packet) {
+                PacketOutcome::Applied
+            } else {
+                tracing::warn!(
+                    "{:?} for packet {} from client {} was skipped by the IBC handler",
+                    packet.kind,
+                    packet.sequence,
+                    packet.source_client
+                );
+                PacketOutcome::Skipped
+            };
+            PacketApplication { packet, outcome }
+        })
+        .collect()
+}
+
+/// Confirms which packet messages of the relay transaction `tx` were applied by the landed
+/// Cosmos SDK transaction `tx_hash`.
+/// # Errors
+/// Returns an error if the relay transaction cannot be decoded, or if the landed transaction
+/// cannot be fetched or has failed.
+pub async fn introspect_cosmos_tx(
+    tm_client: &HttpClient,
+    tx: &[u8],
+    tx_hash: Hash,
+) -> Result<Vec<PacketApplication>> {
+    let relayed = cosmos_relayed_packets(tx)?;
+
+    let tx_result = tm_client.tx(tx_hash, false).await?.tx_result;
+    if tx_result.code.is_err() {
+        anyhow::bail!(
+            "Transaction {tx_hash} failed with code {}: {}",
+            tx_result.code.value(),
+            tx_result.log
+        );
+    }
+
+    Ok(confirm_packet_application(
+        relayed,
+        &cosmos_applied_packets(&tx_result.events),
+    ))
+}
+
+/// Confirms which packet calls of the relay transaction `tx` were applied by the landed Ethereum
+/// transaction `tx_hash` to the `ICS26Router` at `ics26_address`.
+/// # Errors
+/// Returns an error if the relay transaction cannot be decoded, or if the landed transaction
+/// cannot be fetched or has reverted.
+pub async fn introspect_eth_tx<P: Provider>(
+    provider: &P,
+    ics26_address: Address,
+    tx: &[u8],
+    tx_hash: TxHash,
+) -> Result<Vec<PacketApplication>> {
+    let relayed = eth_relayed_packets(tx)?;
+
+    let receipt = provider
+        .get_transaction_receipt(tx_hash)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Transaction {tx_hash} has not been mined"))?;
+    if !receipt.status() {
+        anyhow::bail!("Transaction {tx_hash} reverted");
+    }
+
+    let router_logs = receipt
+        .inner
+        .logs()
+        .iter()
+        .filter(|log| log.address() == ics26_address)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    Ok(confirm_packet_application(
+        relayed,
+        &eth_applied_packets(&router_logs),
+    ))
+}
@@ -10,9 +10,39 @@
 )]
 
 use ibc_core_commitment_types as _;
+// The randomized tests only cover the Ethereum to Cosmos tx builder
+#[cfg(all(test, not(feature = "eth-to-cosmos")))]
+use rand as _;
 
+pub mod authz;
 pub mod chain;
+#[cfg(feature = "eth-to-cosmos")]
+pub mod checkpoint;
+pub mod cleanup;
+pub mod clock;
+pub mod confirmation;
 pub mod events;
+pub mod fees;
+#[cfg(feature = "eth-to-cosmos")]
+pub mod finality;
+pub mod gas_spike;
+pub mod introspection;
 pub mod listener;
+pub mod lookback;
+#[cfg(feature = "eth-to-cosmos")]
+pub mod misbehaviour;
+pub mod network;
+pub mod packet_archive;
+pub mod reconciliation;
+pub mod relay_hints;
+pub mod relay_scope;
+pub mod relayer_fees;
+pub mod retry;
+pub mod scheduler;
+#[cfg(feature = "eth-to-cosmos")]
+pub mod slot_timing;
+#[cfg(feature = "eth-to-cosmos")]
+pub mod sync_committee;
 pub mod tx_builder;
 mod utils;
+pub mod value_limit;
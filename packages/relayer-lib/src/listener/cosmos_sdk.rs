@@ -2,31 +2,61 @@
 
 use futures::future;
 use tendermint::{block::Height, Hash};
-use tendermint_rpc::{Client, HttpClient};
+use tendermint_rpc::{query::Query, Client, HttpClient, Order};
 
 use anyhow::Result;
 
 use crate::{
     chain::CosmosSdk,
-    events::{EurekaEvent, EurekaEventWithHeight},
+    confirmation::{ensure_confirmed, CosmosConfirmationPolicy},
+    events::{cosmos_sdk, EurekaEvent, EurekaEventWithHeight},
 };
 
 use super::ChainListenerService;
 
 /// The `ChainListener` listens for events on the Cosmos SDK chain.
-pub struct ChainListener(HttpClient);
+#[derive(Clone)]
+pub struct ChainListener {
+    /// The HTTP client for tendermint.
+    tm_client: HttpClient,
+    /// The confirmation policy for events.
+    confirmation_policy: CosmosConfirmationPolicy,
+}
 
 impl ChainListener {
     /// Create a new [`Self`] instance.
     #[must_use]
     pub const fn new(tm_client: HttpClient) -> Self {
-        Self(tm_client)
+        Self {
+            tm_client,
+            confirmation_policy: CosmosConfirmationPolicy { height_lag: 0 },
+        }
+    }
+
+    /// Set the confirmation policy for events.
+    #[must_use]
+    pub const fn with_confirmation_policy(
+        mut self,
+        confirmation_policy: CosmosConfirmationPolicy,
+    ) -> Self {
+        self.confirmation_policy = confirmation_policy;
+        self
     }
 
     /// Get the HTTP client for tendermint.
     #[must_use]
     pub const fn client(&self) -> &HttpClient {
-        &self.0
+        &self.tm_client
+    }
+
+    /// Get the latest height that is confirmed under the confirmation policy.
+    ///
+    /// # Errors
+    /// Returns an error if the latest height cannot be fetched.
+    pub async fn confirmed_height(&self) -> Result<u64> {
+        self.confirmation_policy
+            .confirmed_height(self.client())
+            .await
     }
 
     /// Get the chain ID.
@@ -48,17 +78,17 @@ impl ChainListener {
 #[async_trait::async_trait]
 impl ChainListenerService<CosmosSdk> for ChainListener {
     async fn fetch_tx_events(&self, tx_ids: Vec<Hash>) -> Result<Vec<EurekaEventWithHeight>> {
+        let confirmed_height = self.confirmed_height().await?;
+
         Ok(
             future::try_join_all(tx_ids.into_iter().map(|tx_id| async move {
                 let tx_response = self.client().tx(tx_id, false).await?;
                 let height = tx_response.height.value();
-                Ok::<_, tendermint_rpc::Error>(tx_response.tx_result.events.into_iter().filter_map(
+                ensure_confirmed([height], confirmed_height)?;
+                Ok::<_, anyhow::Error>(tx_response.tx_result.events.into_iter().filter_map(
                     move |e| {
                         let event_type = EurekaEvent::try_from(e).ok()?;
-                        Some(EurekaEventWithHeight {
-                            event: event_type,
-                            height,
-                        })
+                        Some(EurekaEventWithHeight::new(event_type, height))
                     },
                 ))
             }))
@@ -74,6 +104,8 @@ impl ChainListenerService<CosmosSdk> for ChainListener {
         start_height: u64,
         end_height: u64,
     ) -> Result<Vec<EurekaEventWithHeight>> {
+        let end_height = end_height.min(self.confirmed_height().await?);
+
         Ok(
             future::try_join_all((start_height..=end_height).map(|h| async move {
                 let height: Height = h.try_into()?;
@@ -88,10 +120,7 @@ impl ChainListenerService<CosmosSdk> for ChainListener {
                         .chain(resp.finalize_block_events)
                         .filter_map(move |e| {
                             let event_type = EurekaEvent::try_from(e).ok()?;
-                            Some(EurekaEventWithHeight {
-                                event: event_type,
-                                height: h,
-                            })
+                            Some(EurekaEventWithHeight::new(event_type, h))
                         }),
                 )
             }))
@@ -101,4 +130,61 @@ impl ChainListenerService<CosmosSdk> for ChainListener {
             .collect(),
         )
     }
+
+    async fn fetch_write_ack_txs(
+        &self,
+        source_client: &str,
+        sequences: &[u64],
+        start_height: u64,
+    ) -> Result<Vec<(Hash, u64)>> {
+        let confirmed_height = self.confirmed_height().await?;
+
+        Ok(
+            future::try_join_all(sequences.iter().map(|&sequence| async move {
+                let query = Query::eq(
+                    format!(
+                        "{}.{}",
+                        cosmos_sdk::EVENT_TYPE_WRITE_ACK,
+                        cosmos_sdk::ATTRIBUTE_KEY_SRC_CLIENT
+                    ),
+                    source_client,
+                )
+                .and_eq(
+                    format!(
+                        "{}.{}",
+                        cosmos_sdk::EVENT_TYPE_WRITE_ACK,
+                        cosmos_sdk::ATTRIBUTE_KEY_SEQUENCE
+                    ),
+                    sequence.to_string(),
+                )
+                .and_gte("tx.height", start_height);
+
+                Ok::<_, anyhow::Error>(
+                    self.client()
+                        .tx_search(query, false, 1, 1, Order::Ascending)
+                        .await?
+                        .txs
+                        .into_iter()
+                        .filter(|tx| tx.height.value() <= confirmed_height)
+                        .map(move |tx| (tx.hash, sequence)),
+                )
+            }))
+            .await?
+            .into_iter()
+            .flatten()
+            .collect(),
+        )
+    }
+
+    async fn block_timestamp(&self, height: u64) -> Result<u64> {
+        let height: Height = height.try_into()?;
+        let time = self
+            .client()
+            .commit(height)
+            .await?
+            .signed_header
+            .header
+            .time;
+        Ok(time.unix_timestamp().try_into()?)
+    }
 }
@@ -1,22 +1,31 @@
 //! This module defines the chain listener for 'solidity-ibc-eureka' contracts.
 
 use alloy::{
+    eips::BlockNumberOrTag,
     primitives::{Address, TxHash},
     providers::Provider,
     rpc::types::Filter,
+    sol_types::SolEvent,
 };
 use anyhow::{anyhow, Result};
 use futures::future;
-use ibc_eureka_solidity_types::ics26::router::routerInstance;
+use ibc_eureka_solidity_types::ics26::router::{routerInstance, WriteAcknowledgement};
 
-use crate::{chain::EthEureka, events::EurekaEventWithHeight};
+use crate::{
+    chain::EthEureka,
+    confirmation::EthConfirmationPolicy,
+    events::{EurekaEvent, EurekaEventWithHeight},
+};
 
 use super::ChainListenerService;
 
 /// The `ChainListenerService` listens for events on the Ethereum chain.
+#[derive(Clone)]
 pub struct ChainListener<P: Provider> {
     /// The IBC Eureka router instance.
     ics26_router: routerInstance<(), P>,
+    /// The confirmation policy for events.
+    confirmation_policy: EthConfirmationPolicy,
 }
 
 impl<P: Provider> ChainListener<P> {
@@ -24,8 +33,19 @@ impl<P: Provider> ChainListener<P> {
     pub const fn new(ics26_address: Address, provider: P) -> Self {
         Self {
             ics26_router: routerInstance::new(ics26_address, provider),
+            confirmation_policy: EthConfirmationPolicy::Finalized,
         }
     }
+
+    /// Set the confirmation policy for events.
+    #[must_use]
+    pub const fn with_confirmation_policy(
+        mut self,
+        confirmation_policy: EthConfirmationPolicy,
+    ) -> Self {
+        self.confirmation_policy = confirmation_policy;
+        self
+    }
 }
 
 impl<P> ChainListener<P>
@@ -43,6 +63,15 @@ where
             .await?
             .to_string())
     }
+
+    /// Get the latest block number that is confirmed under the confirmation policy.
+    /// # Errors
+    /// Returns an error if the block number cannot be fetched.
+    pub async fn confirmed_block_number(&self) -> Result<u64> {
+        self.confirmation_policy
+            .confirmed_block_number(self.ics26_router.provider())
+            .await
+    }
 }
 
 #[async_trait::async_trait]
@@ -50,15 +79,18 @@ impl<P> ChainListenerService<EthEureka> for ChainListener<P>
 where
     P: Provider,
 {
+    /// The events of the given transactions are returned whether they are confirmed or not, and
+    /// the tx builders wait for their confirmation before relaying them.
     async fn fetch_tx_events(&self, tx_ids: Vec<TxHash>) -> Result<Vec<EurekaEventWithHeight>> {
         Ok(
             future::try_join_all(tx_ids.into_iter().map(|tx_id| async move {
-                let block_hash = self
+                let tx = self
                     .ics26_router
                     .provider()
                     .get_transaction_by_hash(tx_id)
                     .await?
-                    .ok_or_else(|| anyhow!("Transaction {} not found", tx_id))?
+                    .ok_or_else(|| anyhow!("Transaction {} not found", tx_id))?;
+                let block_hash = tx
                     .block_hash
                     .ok_or_else(|| anyhow!("Transaction {} has not been mined", tx_id))?;
 
@@ -90,6 +122,11 @@ where
         start_height: u64,
         end_height: u64,
     ) -> Result<Vec<EurekaEventWithHeight>> {
+        let end_height = end_height.min(self.confirmed_block_number().await?);
+        if start_height > end_height {
+            return Ok(vec![]);
+        }
+
         let event_filter = Filter::new()
             .events(EurekaEventWithHeight::evm_signatures())
             .address(*self.ics26_router.address())
@@ -105,4 +142,50 @@ where
             .filter_map(|log| EurekaEventWithHeight::try_from(log).ok())
             .collect())
     }
+
+    async fn fetch_write_ack_txs(
+        &self,
+        source_client: &str,
+        sequences: &[u64],
+        start_height: u64,
+    ) -> Result<Vec<(TxHash, u64)>> {
+        let end_height = self.confirmed_block_number().await?;
+        if start_height > end_height {
+            return Ok(vec![]);
+        }
+
+        let event_filter = Filter::new()
+            .event(WriteAcknowledgement::SIGNATURE)
+            .address(*self.ics26_router.address())
+            .from_block(start_height)
+            .to_block(end_height);
+
+        Ok(self
+            .ics26_router
+            .provider()
+            .get_logs(&event_filter)
+            .await?
+            .iter()
+            .filter_map(|log| {
+                let EurekaEvent::WriteAcknowledgement(packet, _) =
+                    EurekaEventWithHeight::try_from(log).ok()?.event
+                else {
+                    return None;
+                };
+                (packet.sourceClient == source_client && sequences.contains(&packet.sequence))
+                    .then_some((log.transaction_hash?, packet.sequence))
+            })
+            .collect())
+    }
+
+    async fn block_timestamp(&self, height: u64) -> Result<u64> {
+        Ok(self
+            .ics26_router
+            .provider()
+            .get_block(BlockNumberOrTag::Number(height).into())
+            .await?
+            .ok_or_else(|| anyhow!("Block {} not found", height))?
+            .header
+            .timestamp)
+    }
 }
@@ -17,4 +17,17 @@ pub trait ChainListenerService<C: Chain> {
         start_height: C::Height,
         end_height: C::Height,
     ) -> Result<Vec<C::Event>>;
+
+    /// Find the transactions, at or above `start_height`, that wrote the acknowledgements of the
+    /// packets sent from `source_client` with the given sequences.
+    /// Returns the transaction ids with the sequences of the packets they acknowledged.
+    async fn fetch_write_ack_txs(
+        &self,
+        source_client: &str,
+        sequences: &[u64],
+        start_height: C::Height,
+    ) -> Result<Vec<(C::TxId, u64)>>;
+
+    /// Fetch the timestamp of the block at `height`, in unix seconds.
+    async fn block_timestamp(&self, height: C::Height) -> Result<u64>;
 }
@@ -0,0 +1,171 @@
+//! This module defines the [`MaxLookback`] of the scans of the history of a route.
+//!
+//! The scans of the history of a chain, such as the reconciliation of the pending packets or the
+//! backfill of a light client, reach as deep as their own configuration says. A misconfigured
+//! route, e.g. with a lookback larger than the height of the chain or a light client created at
+//! a checkpoint years old, would scan years of history. The max lookback of a route bounds every
+//! such scan by a number of blocks and by the age of the blocks, and the scans deeper than it are
+//! trimmed to the newest blocks, unless the operator overrides it for an intentional deep
+//! recovery scan.
+
+use anyhow::Result;
+use futures::future;
+use serde::{Deserialize, Serialize};
+
+use crate::{chain::Chain, listener::ChainListenerService};
+
+/// The number of blocks whose timestamps are compared to estimate the block time of a chain.
+pub const BLOCK_TIME_SAMPLE_BLOCKS: u64 = 1_000;
+
+/// The max lookback of the scans of a route. The scans are not bounded if neither limit is set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaxLookback {
+    /// The maximum number of blocks below the latest height that are scanned.
+    #[serde(default)]
+    pub max_blocks: Option<u64>,
+    /// The maximum age of the scanned blocks, in seconds.
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+}
+
+impl MaxLookback {
+    /// Returns whether the scans are bounded.
+    #[must_use]
+    pub const fn is_bounded(&self) -> bool {
+        self.max_blocks.is_some() || self.max_age_secs.is_some()
+    }
+
+    /// Returns the maximum number of blocks scanned on a chain producing a block every
+    /// `block_time_millis` milliseconds. The max age is ignored if the block time is unknown.
+    #[must_use]
+    pub fn max_blocks_at(&self, block_time_millis: Option<u64>) -> Option<u64> {
+        let max_blocks_by_age =
+            self.max_age_secs
+                .zip(block_time_millis)
+                .map(|(max_age_secs, block_time_millis)| {
+                    max_age_secs.saturating_mul(1_000) / block_time_millis.max(1)
+                });
+        match (self.max_blocks, max_blocks_by_age) {
+            (Some(max_blocks), Some(max_blocks_by_age)) => Some(max_blocks.min(max_blocks_by_age)),
+            (max_blocks, max_blocks_by_age) => max_blocks.or(max_blocks_by_age),
+        }
+    }
+
+    /// Trims a lookback of `lookback_blocks` to the maximum number of blocks scanned on a chain
+    /// producing a block every `block_time_millis` milliseconds.
+    #[must_use]
+    pub fn trim(&self, lookback_blocks: u64, block_time_millis: Option<u64>) -> u64 {
+        self.max_blocks_at(block_time_millis)
+            .map_or(lookback_blocks, |max_blocks| {
+                lookback_blocks.min(max_blocks)
+            })
+    }
+}
+
+/// Returns the lookback of a scan of `lookback_blocks` blocks below `height` of the chain of
+/// `listener`, trimmed to the max lookback of the route unless `deep_scan` overrides it.
+///
+/// If the max lookback has a max age, the block time of the chain is estimated from the blocks
+/// below `height`.
+/// # Errors
+/// Returns an error if the block timestamps cannot be fetched.
+pub async fn trimmed_lookback<C, L>(
+    listener: &L,
+    max_lookback: &MaxLookback,
+    height: u64,
+    lookback_blocks: u64,
+    deep_scan: bool,
+) -> Result<u64>
+where
+    C: Chain<Height = u64>,
+    L: ChainListenerService<C> + Sync,
+{
+    if deep_scan || !max_lookback.is_bounded() {
+        return Ok(lookback_blocks);
+    }
+
+    let block_time_millis = if max_lookback.max_age_secs.is_some() {
+        estimate_block_time_millis(listener, height).await?
+    } else {
+        None
+    };
+    let trimmed = max_lookback.trim(lookback_blocks, block_time_millis);
+    if trimmed < lookback_blocks {
+        tracing::warn!(
+            "Trimmed a scan of {lookback_blocks} blocks below height {height} to the max \
+             lookback of {trimmed} blocks of the route"
+        );
+    }
+    Ok(trimmed)
+}
+
+/// Estimates the block time of a chain, in milliseconds, from the timestamps of the blocks below
+/// `height`. Returns `None` if the chain has a single block.
+/// # Errors
+/// Returns an error if the block timestamps cannot be fetched.
+pub async fn estimate_block_time_millis<C, L>(listener: &L, height: u64) -> Result<Option<u64>>
+where
+    C: Chain<Height = u64>,
+    L: ChainListenerService<C> + Sync,
+{
+    let sample_start = height.saturating_sub(BLOCK_TIME_SAMPLE_BLOCKS).max(1);
+    if sample_start >= height {
+        return Ok(None);
+    }
+
+    let (start_timestamp, end_timestamp) = future::try_join(
+        listener.block_timestamp(sample_start),
+        listener.block_timestamp(height),
+    )
+    .await?;
+    Ok(Some(
+        end_timestamp
+            .saturating_sub(start_timestamp)
+            .saturating_mul(1_000)
+            / (height - sample_start),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_max_blocks_at() {
+        assert_eq!(MaxLookback::default().max_blocks_at(Some(6_000)), None);
+
+        let by_blocks = MaxLookback {
+            max_blocks: Some(50_000),
+            max_age_secs: None,
+        };
+        assert_eq!(by_blocks.max_blocks_at(None), Some(50_000));
+
+        // A day of 6 second blocks
+        let by_age = MaxLookback {
+            max_blocks: None,
+            max_age_secs: Some(86_400),
+        };
+        assert_eq!(by_age.max_blocks_at(Some(6_000)), Some(14_400));
+        assert_eq!(by_age.max_blocks_at(None), None);
+        // Sub-second blocks with whole second timestamps do not divide by zero
+        assert_eq!(by_age.max_blocks_at(Some(0)), Some(86_400_000));
+
+        let both = MaxLookback {
+            max_blocks: Some(10_000),
+            max_age_secs: Some(86_400),
+        };
+        assert_eq!(both.max_blocks_at(Some(6_000)), Some(10_000));
+        assert_eq!(both.max_blocks_at(Some(12_000)), Some(7_200));
+    }
+
+    #[test]
+    fn test_trim() {
+        let max_lookback = MaxLookback {
+            max_blocks: Some(10_000),
+            max_age_secs: None,
+        };
+        assert_eq!(max_lookback.trim(u64::MAX, None), 10_000);
+        assert_eq!(max_lookback.trim(500, None), 500);
+        assert_eq!(MaxLookback::default().trim(u64::MAX, None), u64::MAX);
+    }
+}
@@ -0,0 +1,249 @@
+//! This module defines the guard against freezing a healthy Ethereum light client with bogus
+//! misbehaviour evidence.
+//!
+//! Submitting misbehaviour freezes the light client on the target chain, which halts every
+//! connection built on it until governance recovers the client. Like the slashing protection of a
+//! validator, the relayer therefore refuses to submit evidence on the word of a single beacon node:
+//! the evidence is first verified locally with the light client core with [`verify_evidence`], and
+//! must then be corroborated by a [`MisbehaviourGuard`], either with an explicit confirmation of
+//! the operator or by independent Beacon API sources agreeing with one side of the conflict.
+
+use alloy::primitives::B256;
+use anyhow::Result;
+use ethereum_apis::beacon_api::client::BeaconApiClient;
+use ethereum_light_client::{
+    client_state::ClientState, consensus_state::ConsensusState, header::ActiveSyncCommittee,
+    misbehaviour::verify_misbehaviour,
+};
+use ethereum_types::consensus::light_client_header::LightClientUpdate;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::bls::BlstVerifier;
+
+/// The corroboration required before misbehaviour evidence is submitted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MisbehaviourGuard {
+    /// Whether the operator confirmed the evidence, which allows submitting it without any
+    /// additional source.
+    pub confirmed: bool,
+    /// The base URLs of independent Beacon APIs, all of which must agree with one of the
+    /// conflicting updates.
+    pub sources: Vec<String>,
+}
+
+/// The misbehaviour evidence of an Ethereum light client, encoded as expected by the
+/// `CheckForMisbehaviour` entry point of the light client contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MisbehaviourEvidence {
+    /// The slot of the trusted consensus state.
+    pub trusted_slot: u64,
+    /// The trusted sync committee, active or next.
+    pub sync_committee: ActiveSyncCommittee,
+    /// The first light client update.
+    pub update_1: LightClientUpdate,
+    /// The second conflicting light client update.
+    pub update_2: LightClientUpdate,
+}
+
+impl MisbehaviourEvidence {
+    /// Returns the slot of the attested headers of the conflicting updates.
+    #[must_use]
+    pub const fn attested_slot(&self) -> u64 {
+        self.update_1.attested_header.beacon.slot
+    }
+
+    /// Returns the execution state roots of the attested headers of the conflicting updates.
+    #[must_use]
+    pub const fn attested_state_roots(&self) -> [B256; 2] {
+        [
+            self.update_1.attested_header.execution.state_root,
+            self.update_2.attested_header.execution.state_root,
+        ]
+    }
+}
+
+/// Verifies the misbehaviour evidence locally with the light client core, as the light client on
+/// the target chain would.
+/// # Errors
+/// Returns an error if the client is already frozen, or if the evidence does not prove
+/// misbehaviour.
+pub fn verify_evidence(
+    client_state: &ClientState,
+    consensus_state: &ConsensusState,
+    evidence: &MisbehaviourEvidence,
+    current_timestamp: u64,
+) -> Result<()> {
+    anyhow::ensure!(
+        !client_state.is_frozen,
+        "the ethereum light client is already frozen"
+    );
+    anyhow::ensure!(
+        consensus_state.slot == evidence.trusted_slot,
+        "the trusted consensus state is at slot {}, but the evidence trusts slot {}",
+        consensus_state.slot,
+        evidence.trusted_slot
+    );
+
+    verify_misbehaviour(
+        client_state,
+        consensus_state,
+        &evidence.sync_committee,
+        &evidence.update_1,
+        &evidence.update_2,
+        current_timestamp,
+        BlstVerifier,
+    )
+    .map_err(|err| anyhow::anyhow!("misbehaviour evidence failed local verification: {err}"))
+}
+
+/// Checks that the execution state roots reported by the independent sources at the attested
+/// slot all agree with one of the conflicting updates.
+/// # Errors
+/// Returns an error if any source disagrees with both updates, or if the sources disagree with
+/// each other.
+pub fn ensure_sources_corroborate(
+    evidence: &MisbehaviourEvidence,
+    source_state_roots: &[B256],
+) -> Result<()> {
+    let attested_state_roots = evidence.attested_state_roots();
+    if let Some(source) = source_state_roots
+        .iter()
+        .position(|state_root| !attested_state_roots.contains(state_root))
+    {
+        anyhow::bail!(
+            "misbehaviour source #{source} reports state root {} at slot {}, which matches neither of the conflicting updates",
+            source_state_roots[source],
+            evidence.attested_slot()
+        );
+    }
+    if let Some(source) = source_state_roots
+        .iter()
+        .position(|state_root| *state_root != source_state_roots[0])
+    {
+        anyhow::bail!(
+            "misbehaviour sources #0 and #{source} disagree on the state root at slot {}",
+            evidence.attested_slot()
+        );
+    }
+    Ok(())
+}
+
+impl MisbehaviourGuard {
+    /// Checks that the evidence is corroborated, by the sources if any are configured, and by the
+    /// confirmation of the operator otherwise.
+    /// # Errors
+    /// Returns an error if the evidence is neither confirmed nor corroborated by a source, or if
+    /// a source cannot be queried or does not corroborate it.
+    pub async fn ensure_corroborated(&self, evidence: &MisbehaviourEvidence) -> Result<()> {
+        if self.sources.is_empty() {
+            anyhow::ensure!(
+                self.confirmed,
+                "misbehaviour evidence at slot {} requires a confirmation or an independent beacon api source",
+                evidence.attested_slot()
+            );
+            tracing::warn!(
+                "Misbehaviour evidence at slot {} is only corroborated by the operator confirmation",
+                evidence.attested_slot()
+            );
+            return Ok(());
+        }
+
+        let source_state_roots = futures::future::try_join_all(self.sources.iter().map(|url| {
+            let source = BeaconApiClient::new(url.trim_end_matches('/').to_string());
+            async move {
+                let beacon_block = source
+                    .beacon_block(&evidence.attested_slot().to_string())
+                    .await?;
+                anyhow::Ok(beacon_block.message.body.execution_payload.state_root)
+            }
+        }))
+        .await?;
+
+        ensure_sources_corroborate(evidence, &source_state_roots)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::primitives::FixedBytes;
+    use ethereum_types::consensus::sync_committee::SyncCommittee;
+
+    use super::*;
+
+    fn evidence() -> MisbehaviourEvidence {
+        let mut update_1 = LightClientUpdate::default();
+        update_1.attested_header.beacon.slot = 64;
+        update_1.attested_header.execution.state_root = B256::with_last_byte(1);
+        let mut update_2 = update_1.clone();
+        update_2.attested_header.execution.state_root = B256::with_last_byte(2);
+
+        MisbehaviourEvidence {
+            trusted_slot: 32,
+            sync_committee: ActiveSyncCommittee::Current(SyncCommittee::default()),
+            update_1,
+            update_2,
+        }
+    }
+
+    #[test]
+    fn test_ensure_sources_corroborate() {
+        let evidence = evidence();
+
+        assert!(ensure_sources_corroborate(&evidence, &[B256::with_last_byte(1)]).is_ok());
+        assert!(ensure_sources_corroborate(
+            &evidence,
+            &[B256::with_last_byte(2), B256::with_last_byte(2)]
+        )
+        .is_ok());
+
+        // A source on neither side of the conflict
+        let err = ensure_sources_corroborate(
+            &evidence,
+            &[B256::with_last_byte(1), B256::with_last_byte(3)],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("source #1"));
+
+        // Sources on different sides of the conflict
+        assert!(ensure_sources_corroborate(
+            &evidence,
+            &[B256::with_last_byte(1), B256::with_last_byte(2)]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_guard_requires_confirmation_without_sources() {
+        let evidence = evidence();
+
+        let guard = MisbehaviourGuard::default();
+        assert!(futures::executor::block_on(guard.ensure_corroborated(&evidence)).is_err());
+
+        let guard = MisbehaviourGuard {
+            confirmed: true,
+            ..Default::default()
+        };
+        assert!(futures::executor::block_on(guard.ensure_corroborated(&evidence)).is_ok());
+    }
+
+    #[test]
+    fn test_verify_evidence_rejects_frozen_clients() {
+        let client_state = ClientState {
+            is_frozen: true,
+            ..Default::default()
+        };
+        let consensus_state = ConsensusState {
+            slot: 32,
+            state_root: B256::ZERO,
+            storage_root: B256::ZERO,
+            timestamp: 0,
+            current_sync_committee: FixedBytes::ZERO,
+            next_sync_committee: None,
+        };
+        let err = verify_evidence(&client_state, &consensus_state, &evidence(), 0).unwrap_err();
+        assert!(err.to_string().contains("frozen"));
+    }
+}
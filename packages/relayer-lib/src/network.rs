@@ -0,0 +1,259 @@
+//! This module defines the guards against relaying between networks that do not match the light
+//! clients, e.g. when an RPC endpoint of the configuration points to the wrong network.
+//!
+//! The chain IDs reported by the RPC endpoints are checked against the configured ones when a
+//! relayer module starts. The light client of every relay is checked against the networks the
+//! relayer is connected to before its first relay, and again once the recheck interval elapsed.
+//! The Ethereum light clients can also be checked against the commitment storage slot declared by
+//! the `ICS26Router`, which may move with a contract upgrade.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::B256;
+#[cfg(feature = "eth-to-cosmos")]
+use alloy::primitives::U256;
+use anyhow::{ensure, Result};
+#[cfg(feature = "eth-to-cosmos")]
+use ethereum_light_client::client_state::ClientState as EthClientState;
+use serde::{Deserialize, Serialize};
+
+/// The default interval after which a verified light client is checked again.
+const DEFAULT_RECHECK_INTERVAL_SECS: u64 = 300;
+
+/// The configuration of the network guard of a relayer module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct NetworkGuardConfig {
+    /// The expected chain ID of the source chain. Not checked if unset.
+    pub source_chain_id: Option<String>,
+    /// The expected chain ID of the target chain. Not checked if unset.
+    pub target_chain_id: Option<String>,
+    /// The expected genesis validators root of the Ethereum beacon chain. Not checked if unset.
+    pub genesis_validators_root: Option<B256>,
+    /// The interval, in seconds, after which a verified light client is checked again.
+    pub recheck_interval_secs: u64,
+}
+
+impl Default for NetworkGuardConfig {
+    fn default() -> Self {
+        Self {
+            source_chain_id: None,
+            target_chain_id: None,
+            genesis_validators_root: None,
+            recheck_interval_secs: DEFAULT_RECHECK_INTERVAL_SECS,
+        }
+    }
+}
+
+impl NetworkGuardConfig {
+    /// Checks the chain IDs reported by the RPC endpoints against the configured ones.
+    /// # Errors
+    /// Returns an error if a chain ID does not match the configured one.
+    pub fn ensure_expected_chain_ids(
+        &self,
+        source_chain_id: &str,
+        target_chain_id: &str,
+    ) -> Result<()> {
+        if let Some(expected) = &self.source_chain_id {
+            ensure_chain_id_matches("source chain", expected, source_chain_id)?;
+        }
+        if let Some(expected) = &self.target_chain_id {
+            ensure_chain_id_matches("target chain", expected, target_chain_id)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the genesis validators root reported by the Beacon API against the configured one.
+    /// # Errors
+    /// Returns an error if the genesis validators root does not match the configured one.
+    pub fn ensure_expected_genesis_validators_root(
+        &self,
+        genesis_validators_root: B256,
+    ) -> Result<()> {
+        if let Some(expected) = self.genesis_validators_root {
+            ensure!(
+                expected == genesis_validators_root,
+                "beacon chain genesis validators root mismatch: configured {expected}, but the beacon api reports {genesis_validators_root}"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the chain ID of a network matches the expected one.
+/// # Errors
+/// Returns an error if the chain IDs differ.
+pub fn ensure_chain_id_matches(network: &str, expected: &str, found: &str) -> Result<()> {
+    ensure!(
+        expected == found,
+        "{network} chain id mismatch: expected {expected}, but found {found}"
+    );
+    Ok(())
+}
+
+/// Checks that an Ethereum light client tracks the network with the given chain ID and genesis
+/// validators root.
+/// # Errors
+/// Returns an error if the client state tracks another network.
+#[cfg(feature = "eth-to-cosmos")]
+pub fn ensure_ethereum_client_matches(
+    client_state: &EthClientState,
+    chain_id: u64,
+    genesis_validators_root: B256,
+) -> Result<()> {
+    ensure_chain_id_matches(
+        "ethereum light client",
+        &client_state.chain_id.to_string(),
+        &chain_id.to_string(),
+    )?;
+    ensure!(
+        client_state.genesis_validators_root == genesis_validators_root,
+        "ethereum light client genesis validators root mismatch: the client tracks {}, but the beacon api reports {genesis_validators_root}",
+        client_state.genesis_validators_root
+    );
+    Ok(())
+}
+
+/// Checks that an Ethereum light client proves the commitments at the storage slot declared by
+/// the `ICS26Router` it tracks.
+/// # Errors
+/// Returns an error if the client state has another commitment slot.
+#[cfg(feature = "eth-to-cosmos")]
+pub fn ensure_commitment_slot_matches(
+    client_state: &EthClientState,
+    router_slot: U256,
+) -> Result<()> {
+    ensure!(
+        client_state.ibc_commitment_slot == router_slot,
+        "ethereum light client commitment slot mismatch: the client proves slot {:#x}, but the router at {} declares {router_slot:#x}",
+        client_state.ibc_commitment_slot,
+        client_state.ibc_contract_address
+    );
+    Ok(())
+}
+
+/// Keeps track of the light clients verified against the connected networks.
+#[allow(clippy::module_name_repetitions)]
+pub struct NetworkGuard {
+    /// The interval after which a verified light client is checked again.
+    recheck_interval: Duration,
+    /// The time of the last successful check of each light client.
+    last_checked: Mutex<HashMap<String, Instant>>,
+}
+
+impl NetworkGuard {
+    /// Create a new [`NetworkGuard`] from its configuration.
+    #[must_use]
+    pub fn new(config: &NetworkGuardConfig) -> Self {
+        Self {
+            recheck_interval: Duration::from_secs(config.recheck_interval_secs),
+            last_checked: Mutex::default(),
+        }
+    }
+
+    /// Runs `check` for the light client if it was not verified within the recheck interval.
+    /// # Errors
+    /// Returns the error of `check`, in which case the light client is checked again on the next
+    /// call.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    pub async fn check<F, Fut>(&self, client_id: &str, check: F) -> Result<()>
+    where
+        F: FnOnce() -> Fut + Send,
+        Fut: Future<Output = Result<()>> + Send,
+    {
+        let due = match self.last_checked.lock().unwrap().get(client_id) {
+            Some(checked_at) => checked_at.elapsed() >= self.recheck_interval,
+            None => true,
+        };
+        if !due {
+            return Ok(());
+        }
+
+        match check().await {
+            Ok(()) => {
+                self.last_checked
+                    .lock()
+                    .unwrap()
+                    .insert(client_id.to_string(), Instant::now());
+                Ok(())
+            }
+            Err(e) => {
+                self.last_checked.lock().unwrap().remove(client_id);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_expected_chain_ids() {
+        let config = NetworkGuardConfig {
+            source_chain_id: Some("11155111".to_string()),
+            target_chain_id: None,
+            ..Default::default()
+        };
+
+        assert!(config
+            .ensure_expected_chain_ids("11155111", "cosmoshub-4")
+            .is_ok());
+        assert!(config
+            .ensure_expected_chain_ids("1", "cosmoshub-4")
+            .is_err());
+    }
+
+    #[cfg(feature = "eth-to-cosmos")]
+    #[test]
+    fn test_ensure_ethereum_client_matches() {
+        let client_state = EthClientState {
+            chain_id: 11_155_111,
+            genesis_validators_root: B256::with_last_byte(1),
+            ..Default::default()
+        };
+
+        assert!(
+            ensure_ethereum_client_matches(&client_state, 11_155_111, B256::with_last_byte(1))
+                .is_ok()
+        );
+        assert!(ensure_ethereum_client_matches(&client_state, 1, B256::with_last_byte(1)).is_err());
+        assert!(
+            ensure_ethereum_client_matches(&client_state, 11_155_111, B256::with_last_byte(2))
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "eth-to-cosmos")]
+    #[test]
+    fn test_ensure_commitment_slot_matches() {
+        let client_state = EthClientState {
+            ibc_commitment_slot: U256::from(7),
+            ..Default::default()
+        };
+
+        assert!(ensure_commitment_slot_matches(&client_state, U256::from(7)).is_ok());
+        assert!(ensure_commitment_slot_matches(&client_state, U256::from(8)).is_err());
+    }
+
+    #[test]
+    fn test_network_guard_rechecks_failed_clients() {
+        let guard = NetworkGuard::new(&NetworkGuardConfig::default());
+        let check = |result: Result<()>| {
+            futures::executor::block_on(guard.check("08-wasm-0", || async move { result }))
+        };
+
+        assert!(check(Err(anyhow::anyhow!("mismatch"))).is_err());
+        assert!(check(Ok(())).is_ok());
+        // A verified client is not checked again within the recheck interval
+        assert!(check(Err(anyhow::anyhow!("mismatch"))).is_ok());
+    }
+}
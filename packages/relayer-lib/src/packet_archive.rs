@@ -0,0 +1,465 @@
+//! This module defines the [`PacketArchive`] of the pre-images of the relayed packets.
+//!
+//! The chains only store the commitments of the packets, which are deleted once the packets are
+//! acknowledged or timed out, and the nodes eventually prune the events carrying the packets. The
+//! archive keeps the pre-image of every packet seen by the relayer, keyed by its commitment, so
+//! that operators can tell what exactly was in a packet long after the chains forgot it.
+//!
+//! Every pre-image is stored as a JSON file named after the hex encoded commitment, and is checked
+//! against its commitment when it is read back.
+//!
+//! The archive also drives the cleanup of the packets orphaned by a frozen or removed counterparty
+//! client: the [`OutstandingPackets`] sent from a client are timed out on the healthy side from
+//! their archived pre-images, since the transactions that sent them are not known.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::{Bytes, B256};
+use anyhow::{ensure, Context, Result};
+use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs::{Packet as SolPacket, Payload};
+use serde::{Deserialize, Serialize};
+
+use crate::events::{EurekaEvent, EurekaEventWithHeight};
+
+/// The pre-image of a payload of a packet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadPreimage {
+    /// The port of the application on the source chain.
+    pub source_port: String,
+    /// The port of the application on the destination chain.
+    pub destination_port: String,
+    /// The version of the application.
+    pub version: String,
+    /// The encoding of the value.
+    pub encoding: String,
+    /// The application data.
+    pub value: Bytes,
+}
+
+/// The pre-image of a packet commitment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketPreimage {
+    /// The packet commitment.
+    pub commitment: B256,
+    /// The identifier of the chain the packet was sent from.
+    pub source_chain: String,
+    /// The identifier of the chain the packet was sent to.
+    pub destination_chain: String,
+    /// The client identifier on the source chain.
+    pub source_client: String,
+    /// The client identifier on the destination chain.
+    pub destination_client: String,
+    /// The sequence of the packet.
+    pub sequence: u64,
+    /// The timeout timestamp of the packet, in seconds.
+    pub timeout_timestamp: u64,
+    /// The payloads of the packet.
+    pub payloads: Vec<PayloadPreimage>,
+    /// The height of the event the pre-image was taken from.
+    pub event_height: u64,
+    /// The unix timestamp at which the pre-image was archived.
+    pub archived_at: u64,
+}
+
+impl PacketPreimage {
+    /// Creates the pre-image of a packet.
+    #[must_use]
+    pub fn new(
+        packet: &SolPacket,
+        source_chain: &str,
+        destination_chain: &str,
+        event_height: u64,
+        archived_at: u64,
+    ) -> Self {
+        Self {
+            commitment: B256::from_slice(&packet.commitment()),
+            source_chain: source_chain.to_string(),
+            destination_chain: destination_chain.to_string(),
+            source_client: packet.sourceClient.clone(),
+            destination_client: packet.destClient.clone(),
+            sequence: packet.sequence,
+            timeout_timestamp: packet.timeoutTimestamp,
+            payloads: packet
+                .payloads
+                .iter()
+                .map(|payload| PayloadPreimage {
+                    source_port: payload.sourcePort.clone(),
+                    destination_port: payload.destPort.clone(),
+                    version: payload.version.clone(),
+                    encoding: payload.encoding.clone(),
+                    value: payload.value.clone(),
+                })
+                .collect(),
+            event_height,
+            archived_at,
+        }
+    }
+
+    /// Returns the packet of the pre-image.
+    #[must_use]
+    pub fn packet(&self) -> SolPacket {
+        SolPacket {
+            sequence: self.sequence,
+            sourceClient: self.source_client.clone(),
+            destClient: self.destination_client.clone(),
+            timeoutTimestamp: self.timeout_timestamp,
+            payloads: self
+                .payloads
+                .iter()
+                .map(|payload| Payload {
+                    sourcePort: payload.source_port.clone(),
+                    destPort: payload.destination_port.clone(),
+                    version: payload.version.clone(),
+                    encoding: payload.encoding.clone(),
+                    value: payload.value.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the send event of the packet of the pre-image.
+    ///
+    /// The height of the event is the height the pre-image was taken at, which is on the
+    /// destination chain if it was archived from the acknowledgement.
+    #[must_use]
+    pub fn send_event(&self) -> EurekaEventWithHeight {
+        EurekaEventWithHeight::new(EurekaEvent::SendPacket(self.packet()), self.event_height)
+    }
+
+    /// Checks that the packet of the pre-image hashes to its commitment.
+    /// # Errors
+    /// Returns an error if the commitment does not match.
+    pub fn verify(&self) -> Result<()> {
+        let commitment = B256::from_slice(&self.packet().commitment());
+        ensure!(
+            commitment == self.commitment,
+            "packet pre-image hashes to {commitment}, not to its commitment {}",
+            self.commitment
+        );
+        Ok(())
+    }
+}
+
+/// The archived packets sent from a client to a counterparty client, by sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OutstandingPackets {
+    /// The packets whose timeout has passed, which can be timed out.
+    pub timed_out: Vec<PacketPreimage>,
+    /// The packets whose timeout has not passed yet.
+    pub pending: Vec<PacketPreimage>,
+}
+
+impl OutstandingPackets {
+    /// Selects the packets sent on `source_chain` from `source_client` to `destination_client`
+    /// among the pre-images, and splits them by whether their timeout has passed at `now`, in
+    /// seconds. At most `max_packets` timed out packets are kept, lowest sequences first.
+    ///
+    /// The pre-images do not tell whether the packets were acknowledged or timed out already: the
+    /// timeouts of such packets are no-ops on the chains.
+    #[must_use]
+    pub fn select(
+        mut preimages: Vec<PacketPreimage>,
+        source_chain: &str,
+        source_client: &str,
+        destination_client: &str,
+        now: u64,
+        max_packets: usize,
+    ) -> Self {
+        preimages.retain(|preimage| {
+            preimage.source_chain == source_chain
+                && preimage.source_client == source_client
+                && preimage.destination_client == destination_client
+        });
+        preimages.sort_by_key(|preimage| preimage.sequence);
+
+        let (mut timed_out, pending): (Vec<_>, Vec<_>) = preimages
+            .into_iter()
+            .partition(|preimage| now >= preimage.timeout_timestamp);
+        timed_out.truncate(max_packets);
+        Self { timed_out, pending }
+    }
+}
+
+/// The archive of the packet pre-images, stored in a directory.
+#[derive(Clone, Debug)]
+pub struct PacketArchive {
+    dir: PathBuf,
+}
+
+impl PacketArchive {
+    /// Opens the archive stored in `dir`, creating the directory if needed.
+    /// # Errors
+    /// Returns an error if the directory cannot be created.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create packet archive {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the directory of the archive.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Archives a pre-image, and returns whether it was not archived yet. The first pre-image of a
+    /// commitment is kept.
+    /// # Errors
+    /// Returns an error if the pre-image cannot be written.
+    pub fn archive(&self, preimage: &PacketPreimage) -> Result<bool> {
+        let path = self.path(preimage.commitment);
+        if path.exists() {
+            return Ok(false);
+        }
+
+        // Written to a temporary file first, so that a crash never leaves a truncated pre-image
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(preimage)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(true)
+    }
+
+    /// Archives the pre-images of the packets of the events emitted on `chain_id`, whose
+    /// counterparty is `counterparty_chain_id`, and returns the number of new pre-images.
+    /// # Errors
+    /// Returns an error if a pre-image cannot be written.
+    pub fn archive_events(
+        &self,
+        events: &[EurekaEventWithHeight],
+        chain_id: &str,
+        counterparty_chain_id: &str,
+        archived_at: u64,
+    ) -> Result<usize> {
+        let mut archived = 0;
+        for event in events {
+            // The acknowledgements are written on the destination chain of their packets
+            let preimage = match &event.event {
+                EurekaEvent::SendPacket(packet) => PacketPreimage::new(
+                    packet,
+                    chain_id,
+                    counterparty_chain_id,
+                    event.height,
+                    archived_at,
+                ),
+                EurekaEvent::WriteAcknowledgement(packet, _) => PacketPreimage::new(
+                    packet,
+                    counterparty_chain_id,
+                    chain_id,
+                    event.height,
+                    archived_at,
+                ),
+            };
+            if self.archive(&preimage)? {
+                archived += 1;
+            }
+        }
+        Ok(archived)
+    }
+
+    /// Returns the pre-image of a packet commitment, if archived.
+    /// # Errors
+    /// Returns an error if the pre-image cannot be read, or does not hash to the commitment.
+    pub fn get(&self, commitment: B256) -> Result<Option<PacketPreimage>> {
+        let path = self.path(commitment);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let preimage: PacketPreimage = serde_json::from_slice(&fs::read(&path)?)
+            .with_context(|| format!("failed to decode packet pre-image {}", path.display()))?;
+        ensure!(
+            preimage.commitment == commitment,
+            "packet pre-image {} is archived under another commitment",
+            path.display()
+        );
+        preimage.verify()?;
+        Ok(Some(preimage))
+    }
+
+    /// Returns all the archived pre-images.
+    /// # Errors
+    /// Returns an error if the directory cannot be read, or if a pre-image cannot be read or does
+    /// not hash to its commitment.
+    pub fn list(&self) -> Result<Vec<PacketPreimage>> {
+        let mut preimages = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            // The temporary files of interrupted writes are skipped
+            if !path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                continue;
+            }
+
+            let preimage: PacketPreimage = serde_json::from_slice(&fs::read(&path)?)
+                .with_context(|| format!("failed to decode packet pre-image {}", path.display()))?;
+            ensure!(
+                path == self.path(preimage.commitment),
+                "packet pre-image {} is archived under another commitment",
+                path.display()
+            );
+            preimage.verify()?;
+            preimages.push(preimage);
+        }
+        Ok(preimages)
+    }
+
+    /// Returns the path of the pre-image of a commitment.
+    fn path(&self, commitment: B256) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", alloy::hex::encode(commitment)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn packet(sequence: u64) -> SolPacket {
+        SolPacket {
+            sequence,
+            sourceClient: "client-0".to_string(),
+            destClient: "08-wasm-0".to_string(),
+            timeoutTimestamp: 1_700_000_000,
+            payloads: vec![Payload {
+                sourcePort: "transfer".to_string(),
+                destPort: "transfer".to_string(),
+                version: "ics20-1".to_string(),
+                encoding: "application/x-solidity-abi".to_string(),
+                value: Bytes::from_static(b"payload"),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_archive_events() {
+        let dir =
+            std::env::temp_dir().join(format!("packet-archive-{}-{}", std::process::id(), line!()));
+        let archive = PacketArchive::open(&dir).unwrap();
+
+        let events = vec![
+            EurekaEventWithHeight::new(EurekaEvent::SendPacket(packet(1)), 10),
+            EurekaEventWithHeight::new(EurekaEvent::WriteAcknowledgement(packet(2), vec![]), 11),
+        ];
+        assert_eq!(
+            archive
+                .archive_events(&events, "ethereum", "cosmoshub", 100)
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            archive
+                .archive_events(&events, "ethereum", "cosmoshub", 200)
+                .unwrap(),
+            0
+        );
+
+        let commitment = B256::from_slice(&packet(1).commitment());
+        let preimage = archive.get(commitment).unwrap().unwrap();
+        assert_eq!(preimage.packet(), packet(1));
+        assert_eq!(preimage.source_chain, "ethereum");
+        assert_eq!(preimage.archived_at, 100);
+
+        let acked = archive
+            .get(B256::from_slice(&packet(2).commitment()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(acked.source_chain, "cosmoshub");
+        assert_eq!(acked.destination_chain, "ethereum");
+        assert!(archive.get(B256::ZERO).unwrap().is_none());
+
+        // A tampered pre-image no longer hashes to its commitment
+        let mut tampered = preimage;
+        tampered.timeout_timestamp += 1;
+        fs::write(
+            archive.path(commitment),
+            serde_json::to_vec(&tampered).unwrap(),
+        )
+        .unwrap();
+        assert!(archive.get(commitment).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_outstanding_packets() {
+        let dir =
+            std::env::temp_dir().join(format!("packet-archive-{}-{}", std::process::id(), line!()));
+        let archive = PacketArchive::open(&dir).unwrap();
+
+        let mut pending = packet(4);
+        pending.timeoutTimestamp = 1_800_000_000;
+        let mut other_route = packet(5);
+        other_route.destClient = "08-wasm-1".to_string();
+        let events = [packet(3), packet(1), pending, other_route, packet(2)]
+            .into_iter()
+            .map(|packet| EurekaEventWithHeight::new(EurekaEvent::SendPacket(packet), 10))
+            .collect::<Vec<_>>();
+        archive
+            .archive_events(&events, "ethereum", "cosmoshub", 100)
+            .unwrap();
+        // The packets sent from the counterparty chain are on another route
+        archive
+            .archive_events(
+                &[EurekaEventWithHeight::new(
+                    EurekaEvent::WriteAcknowledgement(packet(6), vec![]),
+                    11,
+                )],
+                "ethereum",
+                "cosmoshub",
+                100,
+            )
+            .unwrap();
+        fs::write(dir.join("interrupted.json.tmp"), b"{").unwrap();
+
+        let preimages = archive.list().unwrap();
+        assert_eq!(preimages.len(), 6);
+
+        let outstanding = OutstandingPackets::select(
+            preimages.clone(),
+            "cosmoshub",
+            "client-0",
+            "08-wasm-0",
+            1_700_000_000,
+            2,
+        );
+        assert_eq!(outstanding.timed_out.len(), 1);
+        assert_eq!(outstanding.timed_out[0].sequence, 6);
+
+        let outstanding = OutstandingPackets::select(
+            preimages,
+            "ethereum",
+            "client-0",
+            "08-wasm-0",
+            1_700_000_000,
+            2,
+        );
+        assert_eq!(
+            outstanding
+                .timed_out
+                .iter()
+                .map(|preimage| preimage.sequence)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            outstanding
+                .pending
+                .iter()
+                .map(|preimage| preimage.sequence)
+                .collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert_eq!(
+            outstanding.timed_out[0].send_event().event,
+            EurekaEvent::SendPacket(packet(1))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
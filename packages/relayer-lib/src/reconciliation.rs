@@ -0,0 +1,235 @@
+//! This module defines the reconciliation of the acks written before the relayer started.
+//!
+//! The [`AckSchedule`](crate::scheduler::AckSchedule) of a module only covers the packets it
+//! relayed itself, so the acknowledgements written while no relayer was watching, e.g. before a
+//! relayer was (re)started, are never relayed back unless the submitter requests them. The
+//! [`AckReconciliation`] of a module instead scans the packets sent on the source chain within a
+//! bounded window, keeps the ones whose commitment is still stored, and finds the transactions
+//! that wrote their acknowledgements on the destination chain.
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use futures::future;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::Chain,
+    events::{EurekaEvent, EurekaEventWithHeight},
+    listener::ChainListenerService,
+    scheduler::AwaitedAcks,
+};
+
+/// The default number of blocks scanned by the reconciliation on each chain.
+pub const DEFAULT_LOOKBACK_BLOCKS: u64 = 10_000;
+/// The default number of blocks whose events are fetched at once.
+pub const DEFAULT_SCAN_BATCH_BLOCKS: u64 = 100;
+/// The default maximum number of packets reconciled per route.
+pub const DEFAULT_MAX_PACKETS: usize = 100;
+
+/// The reconciliation of the acks written on the destination chain that were never relayed back.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AckReconciliation {
+    /// The routes of the packets whose acks are reconciled.
+    pub routes: Vec<ReconciledRoute>,
+    /// The number of blocks of the source chain scanned for the sent packets. A value larger than
+    /// the height of the chain scans it from genesis.
+    #[serde(default = "default_lookback_blocks")]
+    pub lookback_blocks: u64,
+    /// The number of blocks of the destination chain searched for the acknowledgements.
+    #[serde(default = "default_lookback_blocks")]
+    pub ack_lookback_blocks: u64,
+    /// The number of blocks whose events are fetched at once.
+    #[serde(default = "default_scan_batch_blocks")]
+    pub scan_batch_blocks: u64,
+    /// The maximum number of packets reconciled per route, oldest first.
+    #[serde(default = "default_max_packets")]
+    pub max_packets: usize,
+    /// Whether the scans ignore the [`MaxLookback`](crate::lookback::MaxLookback) of the module,
+    /// for an intentional deep recovery scan.
+    #[serde(default)]
+    pub deep_scan: bool,
+}
+
+/// The route of the packets relayed by a module, whose acks are relayed back.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconciledRoute {
+    /// The chain the packets are sent from.
+    pub src_chain: String,
+    /// The chain the packets are received on, which writes the acknowledgements.
+    pub dst_chain: String,
+    /// The client on the source chain, which the packets are sent from.
+    pub src_client_id: String,
+    /// The client on the destination chain.
+    pub dst_client_id: String,
+}
+
+const fn default_lookback_blocks() -> u64 {
+    DEFAULT_LOOKBACK_BLOCKS
+}
+
+const fn default_scan_batch_blocks() -> u64 {
+    DEFAULT_SCAN_BATCH_BLOCKS
+}
+
+const fn default_max_packets() -> usize {
+    DEFAULT_MAX_PACKETS
+}
+
+/// Returns the first height of a window of `lookback_blocks` blocks ending at `height`.
+#[must_use]
+pub fn lookback_start(height: u64, lookback_blocks: u64) -> u64 {
+    height.saturating_sub(lookback_blocks).max(1)
+}
+
+/// Splits the inclusive range of heights into consecutive inclusive ranges of at most
+/// `batch_blocks` blocks.
+#[must_use]
+pub fn scan_ranges(start_height: u64, end_height: u64, batch_blocks: u64) -> Vec<(u64, u64)> {
+    let batch_blocks = batch_blocks.max(1);
+    (start_height..=end_height)
+        .step_by(usize::try_from(batch_blocks).unwrap_or(usize::MAX))
+        .map(|start| {
+            (
+                start,
+                start.saturating_add(batch_blocks - 1).min(end_height),
+            )
+        })
+        .collect()
+}
+
+/// Returns the sequences of the packets sent from `source_client` among the events.
+#[must_use]
+pub fn sent_sequences(events: &[EurekaEventWithHeight], source_client: &str) -> BTreeSet<u64> {
+    events
+        .iter()
+        .filter_map(|event| match &event.event {
+            EurekaEvent::SendPacket(packet) if packet.sourceClient == source_client => {
+                Some(packet.sequence)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Finds the transactions that wrote the acknowledgements of the packets sent from
+/// `source_client` that were never relayed back.
+///
+/// The source chain is scanned oldest first from `lookback_blocks` blocks below `src_height`, and
+/// the sent packets whose commitment is still stored, according to `is_committed`, are kept until
+/// `max_packets` are found. Their acknowledgements are then searched on the destination chain
+/// from `ack_lookback_blocks` blocks below `dst_height`. The packets that were not received yet
+/// are left to the recv relays.
+/// # Errors
+/// Returns an error if either chain cannot be queried.
+pub async fn find_unrelayed_acks<S, D, LS, LD, F, Fut>(
+    src_listener: &LS,
+    dst_listener: &LD,
+    reconciliation: &AckReconciliation,
+    source_client: &str,
+    src_height: u64,
+    dst_height: u64,
+    is_committed: F,
+) -> Result<Vec<D::TxId>>
+where
+    S: Chain<Event = EurekaEventWithHeight, Height = u64>,
+    D: Chain<Height = u64>,
+    D::TxId: PartialEq,
+    LS: ChainListenerService<S> + Sync,
+    LD: ChainListenerService<D> + Sync,
+    F: Fn(u64) -> Fut,
+    Fut: std::future::Future<Output = Result<bool>>,
+{
+    let mut committed = Vec::new();
+    let start_height = lookback_start(src_height, reconciliation.lookback_blocks);
+    for (start, end) in scan_ranges(start_height, src_height, reconciliation.scan_batch_blocks) {
+        if committed.len() >= reconciliation.max_packets {
+            break;
+        }
+
+        let events = src_listener.fetch_events(start, end).await?;
+        let is_committed = &is_committed;
+        let checked =
+            future::try_join_all(sent_sequences(&events, source_client).into_iter().map(
+                |sequence| async move { anyhow::Ok((sequence, is_committed(sequence).await?)) },
+            ))
+            .await?;
+        committed.extend(
+            checked
+                .into_iter()
+                .filter_map(|(sequence, is_committed)| is_committed.then_some(sequence)),
+        );
+    }
+    committed.truncate(reconciliation.max_packets);
+    if committed.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut awaited = AwaitedAcks::new(committed.iter().copied());
+    awaited.observe(
+        dst_listener
+            .fetch_write_ack_txs(
+                source_client,
+                &committed,
+                lookback_start(dst_height, reconciliation.ack_lookback_blocks),
+            )
+            .await?,
+    );
+    if !awaited.is_complete() {
+        tracing::info!(
+            sequences = ?awaited.remaining(),
+            "The packets without an acknowledgement are left to the recv relays."
+        );
+    }
+    Ok(awaited.into_tx_ids())
+}
+
+#[cfg(test)]
+mod test {
+    use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs::Packet;
+
+    use super::*;
+
+    #[test]
+    fn test_scan_ranges() {
+        assert_eq!(
+            scan_ranges(1, 250, 100),
+            vec![(1, 100), (101, 200), (201, 250)]
+        );
+        assert_eq!(scan_ranges(5, 5, 100), vec![(5, 5)]);
+        assert_eq!(scan_ranges(10, 9, 100), vec![]);
+        assert_eq!(scan_ranges(1, 3, 0), vec![(1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn test_lookback_start() {
+        assert_eq!(lookback_start(20_000, 10_000), 10_000);
+        // Scans from genesis if the lookback exceeds the height
+        assert_eq!(lookback_start(500, 10_000), 1);
+    }
+
+    #[test]
+    fn test_sent_sequences() {
+        let packet = |source_client: &str, sequence| Packet {
+            sourceClient: source_client.to_string(),
+            sequence,
+            ..Default::default()
+        };
+        let events = vec![
+            EurekaEventWithHeight::new(EurekaEvent::SendPacket(packet("client-0", 3)), 10),
+            EurekaEventWithHeight::new(EurekaEvent::SendPacket(packet("client-1", 4)), 10),
+            EurekaEventWithHeight::new(
+                EurekaEvent::WriteAcknowledgement(packet("client-0", 5), vec![]),
+                11,
+            ),
+            EurekaEventWithHeight::new(EurekaEvent::SendPacket(packet("client-0", 1)), 12),
+        ];
+
+        assert_eq!(
+            sent_sequences(&events, "client-0")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+    }
+}
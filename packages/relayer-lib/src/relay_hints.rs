@@ -0,0 +1,247 @@
+//! This module defines the relay hints that users attach to their ICS20 packets.
+//!
+//! An ICS20 packet can carry a relay hint in its memo, e.g.
+//! `{"relay":{"priority":"high","fee_bump_percent":20,"bypass_filters":true}}`, to request express
+//! relaying. A relayer module only honors the hints if it has a [`RelayHintPolicy`]:
+//! - Packets with a higher priority are relayed first within a relay transaction.
+//! - The fee bump, capped by the policy, is reported to the submitter of the transaction.
+//! - Packets bypassing the filters are relayed even if the request filters them out.
+
+use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs::Packet;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{EurekaEvent, EurekaEventWithHeight, PacketData};
+
+/// The relay priority of a packet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayPriority {
+    /// Relayed after the other packets.
+    Low,
+    /// The priority of packets without a hint.
+    #[default]
+    Normal,
+    /// Relayed before the other packets.
+    High,
+}
+
+impl RelayPriority {
+    /// Returns the name of the priority, as written in the memo.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+}
+
+/// The relay hint of a packet, read from the `relay` field of its ICS20 memo.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayHint {
+    /// The relay priority of the packet.
+    #[serde(default)]
+    pub priority: RelayPriority,
+    /// The fee bump, in percent, that the user pays for the relay of the packet.
+    #[serde(default)]
+    pub fee_bump_percent: u64,
+    /// Whether the packet is relayed even if the relay request filters it out.
+    #[serde(default)]
+    pub bypass_filters: bool,
+}
+
+/// The relay hint policy of a relayer module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RelayHintPolicy {
+    /// The maximum fee bump, in percent, reported to the submitter.
+    #[serde(default)]
+    pub max_fee_bump_percent: u64,
+    /// Whether packets may bypass the packet filters of the relay requests.
+    #[serde(default)]
+    pub allow_filter_bypass: bool,
+}
+
+/// The relay hints of the packets of a relay.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RelayHints {
+    /// The highest priority of the relayed packets.
+    pub priority: RelayPriority,
+    /// The highest fee bump of the relayed packets, capped by the policy.
+    pub fee_bump_percent: u64,
+    /// The sequences of the packets that bypassed the filters of the request.
+    pub bypassed_sequences: Vec<u64>,
+}
+
+impl RelayHintPolicy {
+    /// Applies the relay hints of the packets sent from `src_client_id` in `src_events`.
+    ///
+    /// The events are ordered by the priority of their packets, and the packets bypassing the
+    /// filters are added to `src_packet_seqs` if it filters them out.
+    #[must_use]
+    pub fn apply(
+        &self,
+        src_events: &mut [EurekaEventWithHeight],
+        src_client_id: &str,
+        src_packet_seqs: &mut Vec<u64>,
+    ) -> RelayHints {
+        let packet_hint = |event: &EurekaEventWithHeight| match &event.event {
+            EurekaEvent::SendPacket(packet) if packet.sourceClient == src_client_id => {
+                packet_data_relay_hint(&event.packet_data())
+            }
+            _ => None,
+        };
+
+        // Stable, so packets of the same priority keep their order
+        src_events.sort_by_key(|event| {
+            std::cmp::Reverse(packet_hint(event).unwrap_or_default().priority)
+        });
+
+        let mut hints = RelayHints::default();
+        for event in src_events.iter() {
+            let (Some(hint), EurekaEvent::SendPacket(packet)) = (packet_hint(event), &event.event)
+            else {
+                continue;
+            };
+
+            hints.priority = hints.priority.max(hint.priority);
+            hints.fee_bump_percent = hints
+                .fee_bump_percent
+                .max(hint.fee_bump_percent.min(self.max_fee_bump_percent));
+            if hint.bypass_filters
+                && self.allow_filter_bypass
+                && !src_packet_seqs.is_empty()
+                && !src_packet_seqs.contains(&packet.sequence)
+            {
+                src_packet_seqs.push(packet.sequence);
+                hints.bypassed_sequences.push(packet.sequence);
+            }
+        }
+
+        hints
+    }
+}
+
+/// Returns the relay hint in the memo of the first ICS20 payload of a packet, if any.
+#[must_use]
+pub fn packet_relay_hint(packet: &Packet) -> Option<RelayHint> {
+    packet_data_relay_hint(&PacketData::decode(packet))
+}
+
+/// Returns the relay hint in the memo of the first ICS20 transfer of decoded packet data, if any.
+fn packet_data_relay_hint(packet_data: &PacketData) -> Option<RelayHint> {
+    packet_data
+        .transfers
+        .iter()
+        .find_map(|transfer| memo_relay_hint(&transfer.memo))
+}
+
+/// Returns the relay hint in the `relay` field of a JSON memo, if any.
+#[must_use]
+pub fn memo_relay_hint(memo: &str) -> Option<RelayHint> {
+    #[derive(Deserialize)]
+    struct Memo {
+        relay: RelayHint,
+    }
+
+    serde_json::from_str::<Memo>(memo)
+        .ok()
+        .map(|memo| memo.relay)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::{primitives::U256, sol_types::SolValue};
+    use ibc_eureka_solidity_types::{
+        ics26::IICS26RouterMsgs::Payload, msgs::IICS20TransferMsgs::FungibleTokenPacketData,
+    };
+
+    use super::*;
+    use crate::events::{ICS20_ABI_ENCODING, ICS20_TRANSFER_PORT};
+
+    fn send_packet_event(sequence: u64, memo: &str) -> EurekaEventWithHeight {
+        let data = FungibleTokenPacketData {
+            denom: "uatom".to_string(),
+            sender: "sender".to_string(),
+            receiver: "receiver".to_string(),
+            amount: U256::from(1),
+            memo: memo.to_string(),
+        };
+        EurekaEventWithHeight::new(
+            EurekaEvent::SendPacket(Packet {
+                sequence,
+                sourceClient: "client-0".to_string(),
+                payloads: vec![Payload {
+                    sourcePort: ICS20_TRANSFER_PORT.to_string(),
+                    encoding: ICS20_ABI_ENCODING.to_string(),
+                    value: data.abi_encode().into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            1,
+        )
+    }
+
+    fn sequences(events: &[EurekaEventWithHeight]) -> Vec<u64> {
+        events
+            .iter()
+            .filter_map(|event| match &event.event {
+                EurekaEvent::SendPacket(packet) => Some(packet.sequence),
+                EurekaEvent::WriteAcknowledgement(..) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_memo_relay_hint() {
+        assert_eq!(
+            memo_relay_hint(r#"{"relay":{"priority":"high"}}"#),
+            Some(RelayHint {
+                priority: RelayPriority::High,
+                ..Default::default()
+            })
+        );
+        assert_eq!(memo_relay_hint(r#"{"wasm":{}}"#), None);
+        assert_eq!(memo_relay_hint("not json"), None);
+    }
+
+    #[test]
+    fn test_apply_relay_hints() {
+        let mut events = vec![
+            send_packet_event(1, ""),
+            send_packet_event(2, r#"{"relay":{"priority":"low"}}"#),
+            send_packet_event(
+                3,
+                r#"{"relay":{"priority":"high","fee_bump_percent":50,"bypass_filters":true}}"#,
+            ),
+            send_packet_event(4, ""),
+        ];
+        let policy = RelayHintPolicy {
+            max_fee_bump_percent: 20,
+            allow_filter_bypass: true,
+        };
+        let mut src_packet_seqs = vec![1, 2];
+
+        let hints = policy.apply(&mut events, "client-0", &mut src_packet_seqs);
+        assert_eq!(sequences(&events), vec![3, 1, 4, 2]);
+        assert_eq!(hints.priority, RelayPriority::High);
+        assert_eq!(hints.fee_bump_percent, 20);
+        assert_eq!(hints.bypassed_sequences, vec![3]);
+        assert_eq!(src_packet_seqs, vec![1, 2, 3]);
+
+        // Without a filter, all packets are relayed anyway
+        let mut src_packet_seqs = vec![];
+        let hints = policy.apply(&mut events, "client-0", &mut src_packet_seqs);
+        assert!(hints.bypassed_sequences.is_empty());
+        assert!(src_packet_seqs.is_empty());
+
+        // The bypass must be allowed by the policy
+        let mut src_packet_seqs = vec![1];
+        let hints = RelayHintPolicy::default().apply(&mut events, "client-0", &mut src_packet_seqs);
+        assert!(hints.bypassed_sequences.is_empty());
+        assert_eq!(src_packet_seqs, vec![1]);
+    }
+}
@@ -0,0 +1,246 @@
+//! This module defines the [`RelayScope`] the sub-tasks of a relay run in.
+//!
+//! Building a relay waits for the finality of its events, builds the light client headers and
+//! fetches the membership proofs, each of which can take minutes. The caller of a relay bounds it
+//! with a deadline, and can abort it with a [`RelayCancellation`], e.g. when the API request that
+//! triggered it is dropped. The tx builders run every sub-task of a relay through
+//! [`RelayScope::run`], which interrupts it with a [`RelayAborted`] error once the deadline passes
+//! or the relay is cancelled. The sub-tasks are futures owned by the relay, never detached tasks,
+//! so an interrupted relay drops all of them, with their pending requests, before returning.
+
+use std::{
+    fmt,
+    future::Future,
+    pin::pin,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use futures::{
+    channel::oneshot,
+    future::{self, Either, FutureExt, Shared},
+};
+use futures_timer::Delay;
+use serde::{Deserialize, Serialize};
+
+/// A sub-task of a relay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayStage {
+    /// Waiting for the finality of the relayed events on the source chain.
+    FinalityWait,
+    /// Building the light client headers of the relay.
+    HeaderBuild,
+    /// Fetching or generating the proofs of the relayed packets.
+    ProofFetch,
+}
+
+impl fmt::Display for RelayStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FinalityWait => write!(f, "waiting for finality"),
+            Self::HeaderBuild => write!(f, "building the headers"),
+            Self::ProofFetch => write!(f, "fetching the proofs"),
+        }
+    }
+}
+
+/// The error returned when a relay is interrupted by its [`RelayScope`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RelayAborted {
+    /// The deadline of the relay passed.
+    #[error("the relay missed its deadline while {0}")]
+    DeadlineExceeded(RelayStage),
+    /// The relay was cancelled by its caller.
+    #[error("the relay was cancelled while {0}")]
+    Cancelled(RelayStage),
+}
+
+/// The handle cancelling the relays of a [`RelayScope`]. Dropping the handle does not cancel them.
+#[derive(Debug)]
+pub struct RelayCancellation(oneshot::Sender<()>);
+
+impl RelayCancellation {
+    /// Cancels the relays of the scope.
+    pub fn cancel(self) {
+        // The relays may have completed and dropped the scope already
+        let _ = self.0.send(());
+    }
+}
+
+/// The scope of the sub-tasks of a relay, with its deadline and cancellation.
+#[derive(Clone, Default)]
+pub struct RelayScope {
+    /// The instant the relay is interrupted at, if any.
+    deadline: Option<Instant>,
+    /// Resolves to `Ok` once the relay is cancelled.
+    cancellation: Option<Shared<oneshot::Receiver<()>>>,
+}
+
+impl fmt::Debug for RelayScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelayScope")
+            .field("deadline", &self.deadline)
+            .field("cancellable", &self.cancellation.is_some())
+            .finish()
+    }
+}
+
+impl RelayScope {
+    /// Returns a scope interrupting the relay `timeout` from now.
+    #[must_use]
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            deadline: Instant::now().checked_add(timeout),
+            cancellation: None,
+        }
+    }
+
+    /// Returns the scope with a deadline, keeping the earlier of the two if it already has one.
+    #[must_use]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(self.deadline.map_or(deadline, |d| d.min(deadline)));
+        self
+    }
+
+    /// Returns the scope with a cancellation, and the handle cancelling it.
+    #[must_use]
+    pub fn cancellable(mut self) -> (Self, RelayCancellation) {
+        let (sender, receiver) = oneshot::channel();
+        self.cancellation = Some(receiver.shared());
+        (self, RelayCancellation(sender))
+    }
+
+    /// Returns the deadline of the scope, if any.
+    #[must_use]
+    pub const fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the time left until the deadline, if any.
+    #[must_use]
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns an error if the relay is already interrupted, without starting a sub-task.
+    /// # Errors
+    /// Returns a [`RelayAborted`] error if the deadline passed or the relay was cancelled.
+    pub fn ensure_active(&self, stage: RelayStage) -> Result<()> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(|cancellation| matches!(cancellation.peek(), Some(Ok(()))))
+        {
+            return Err(RelayAborted::Cancelled(stage).into());
+        }
+        if self.remaining() == Some(Duration::ZERO) {
+            return Err(RelayAborted::DeadlineExceeded(stage).into());
+        }
+        Ok(())
+    }
+
+    /// Runs a sub-task of the relay until it completes, the deadline passes or the relay is
+    /// cancelled. An interrupted sub-task is dropped before this returns.
+    /// # Errors
+    /// Returns the error of the sub-task, or a [`RelayAborted`] error if it was interrupted.
+    pub async fn run<T, F>(&self, stage: RelayStage, task: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        self.ensure_active(stage)?;
+
+        match future::select(pin!(task), pin!(self.interrupted(stage))).await {
+            Either::Left((result, _)) => result,
+            Either::Right((aborted, _)) => {
+                tracing::warn!("Interrupted the relay: {aborted}");
+                Err(aborted.into())
+            }
+        }
+    }
+
+    /// Resolves once the deadline passes or the relay is cancelled.
+    async fn interrupted(&self, stage: RelayStage) -> RelayAborted {
+        let deadline = async {
+            match self.remaining() {
+                Some(remaining) => Delay::new(remaining).await,
+                None => future::pending().await,
+            }
+        };
+        let cancelled = async {
+            match self.cancellation.clone() {
+                // A dropped handle never cancels the relay
+                Some(cancellation) if cancellation.await.is_ok() => {}
+                _ => future::pending().await,
+            }
+        };
+
+        match future::select(pin!(deadline), pin!(cancelled)).await {
+            Either::Left(((), _)) => RelayAborted::DeadlineExceeded(stage),
+            Either::Right(((), _)) => RelayAborted::Cancelled(stage),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[test]
+    fn test_relay_scope_completes_within_deadline() {
+        let scope = RelayScope::with_timeout(Duration::from_secs(60));
+        let result = block_on(scope.run(RelayStage::ProofFetch, async { anyhow::Ok(7) }));
+        assert_eq!(result.unwrap(), 7);
+
+        // Without a deadline or a cancellation, the sub-tasks are never interrupted
+        let result =
+            block_on(RelayScope::default().run(RelayStage::HeaderBuild, async { anyhow::Ok(()) }));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_relay_scope_deadline() {
+        let scope = RelayScope::with_timeout(Duration::from_millis(10));
+        let err = block_on(scope.run(RelayStage::FinalityWait, future::pending::<Result<()>>()))
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RelayAborted>(),
+            Some(&RelayAborted::DeadlineExceeded(RelayStage::FinalityWait))
+        );
+
+        // The next sub-tasks are not started
+        let err = scope.ensure_active(RelayStage::ProofFetch).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RelayAborted>(),
+            Some(&RelayAborted::DeadlineExceeded(RelayStage::ProofFetch))
+        );
+
+        // The earlier deadline is kept
+        let later = Instant::now() + Duration::from_secs(60);
+        assert!(scope.clone().with_deadline(later).deadline() < Some(later));
+    }
+
+    #[test]
+    fn test_relay_scope_cancellation() {
+        let (scope, cancellation) = RelayScope::default().cancellable();
+        assert!(scope.ensure_active(RelayStage::HeaderBuild).is_ok());
+
+        let relay = scope.run(RelayStage::HeaderBuild, future::pending::<Result<()>>());
+        cancellation.cancel();
+        let err = block_on(relay).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RelayAborted>(),
+            Some(&RelayAborted::Cancelled(RelayStage::HeaderBuild))
+        );
+
+        // Dropping the handle does not cancel the relay
+        let (scope, cancellation) = RelayScope::default().cancellable();
+        drop(cancellation);
+        let result = block_on(scope.run(RelayStage::ProofFetch, async { anyhow::Ok(()) }));
+        assert!(result.is_ok());
+        assert!(scope.ensure_active(RelayStage::ProofFetch).is_ok());
+    }
+}
@@ -0,0 +1,346 @@
+//! This module defines the claiming of the relayer fees escrowed by the fee middleware of a Cosmos
+//! SDK chain.
+//!
+//! A fee middleware, in the spirit of ICS-29, escrows the fees of the relayers of a packet when it
+//! is sent: a receive fee, an acknowledgement fee and a timeout fee. IBC v2 has no channel version
+//! to negotiate the middleware on, so the relayer learns whether a packet is incentivized by
+//! querying the fee middleware of the chain the packet was sent from with [`query_packet_fees`].
+//! The fees are held by the sending chain, so they are claimed with a [`MsgClaimPacketFees`] in
+//! the same transaction as the acknowledgement or the timeout of the packet, which the middleware
+//! pays out once the packet commitment is deleted: the receive and acknowledgement fees of an
+//! acknowledged packet, and the timeout fee of a timed out one. The packets of a chain without a
+//! fee middleware are relayed without claims.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+use ibc_proto_eureka::{
+    google::protobuf::Any,
+    ibc::core::channel::v2::{MsgAcknowledgement, MsgTimeout},
+};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{Client, HttpClient};
+
+/// The path of the packet fees query of the fee middleware.
+pub const PACKET_FEES_PATH: &str = "/ibc.applications.fee.v2.Query/PacketFees";
+/// The type URL of the fee claim message of the fee middleware.
+pub const MSG_CLAIM_PACKET_FEES_TYPE_URL: &str = "/ibc.applications.fee.v2.MsgClaimPacketFees";
+
+/// The claiming of the relayer fees of the relayed packets.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayerFeeConfig {
+    /// The address the claimed fees are paid to. Defaults to the signer address.
+    #[serde(default)]
+    pub payee: Option<String>,
+}
+
+/// The relay a fee is claimed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeClaimKind {
+    /// The acknowledgement of the packet, paying its receive and acknowledgement fees.
+    Acknowledgement,
+    /// The timeout of the packet, paying its timeout fee.
+    Timeout,
+}
+
+/// A relayed packet whose fees can be claimed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimablePacket {
+    /// The relay the fees are claimed with.
+    pub kind: FeeClaimKind,
+    /// The source client of the packet, on the chain holding the fees.
+    pub client_id: String,
+    /// The sequence of the packet.
+    pub sequence: u64,
+}
+
+/// The packet fees query of the fee middleware.
+#[derive(Clone, PartialEq, Message)]
+struct QueryPacketFeesRequest {
+    /// The source client of the packet.
+    #[prost(string, tag = "1")]
+    client_id: String,
+    /// The sequence of the packet.
+    #[prost(uint64, tag = "2")]
+    sequence: u64,
+}
+
+/// The packet fees response of the fee middleware.
+#[derive(Clone, PartialEq, Message)]
+struct QueryPacketFeesResponse {
+    /// The fees escrowed for the packet, one per incentivizing account.
+    #[prost(message, repeated, tag = "1")]
+    fees: Vec<PacketFee>,
+}
+
+/// A fee escrowed for the relayers of a packet.
+#[derive(Clone, PartialEq, Message)]
+pub struct PacketFee {
+    /// The fee paid for the receive of the packet.
+    #[prost(message, repeated, tag = "1")]
+    pub recv_fee: Vec<Coin>,
+    /// The fee paid for the acknowledgement of the packet.
+    #[prost(message, repeated, tag = "2")]
+    pub ack_fee: Vec<Coin>,
+    /// The fee paid for the timeout of the packet.
+    #[prost(message, repeated, tag = "3")]
+    pub timeout_fee: Vec<Coin>,
+    /// The address the unpaid fees are refunded to.
+    #[prost(string, tag = "4")]
+    pub refund_address: String,
+}
+
+/// The fee claim message of the fee middleware.
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct MsgClaimPacketFees {
+    /// The source client of the packet.
+    #[prost(string, tag = "1")]
+    pub client_id: String,
+    /// The sequence of the packet.
+    #[prost(uint64, tag = "2")]
+    pub sequence: u64,
+    /// The address the fees are paid to.
+    #[prost(string, tag = "3")]
+    pub payee: String,
+    /// The signer of the message.
+    #[prost(string, tag = "4")]
+    pub signer: String,
+}
+
+impl PacketFee {
+    /// Returns the fee paid by a claim of `kind`.
+    #[must_use]
+    pub fn claimable(&self, kind: FeeClaimKind) -> Vec<Coin> {
+        match kind {
+            FeeClaimKind::Acknowledgement => {
+                [self.recv_fee.as_slice(), self.ack_fee.as_slice()].concat()
+            }
+            FeeClaimKind::Timeout => self.timeout_fee.clone(),
+        }
+    }
+}
+
+/// Returns the sum of the fees paid by a claim of `kind` over all the escrowed fees of a packet,
+/// by denom. The zero amounts are left out.
+/// # Errors
+/// Returns an error if an amount is malformed or the sum overflows.
+pub fn claimable_fees(fees: &[PacketFee], kind: FeeClaimKind) -> Result<Vec<Coin>> {
+    let mut totals = BTreeMap::<String, u128>::new();
+    for coin in fees.iter().flat_map(|fee| fee.claimable(kind)) {
+        let amount = coin.amount.parse::<u128>()?;
+        let total = totals.entry(coin.denom).or_default();
+        *total = total
+            .checked_add(amount)
+            .ok_or_else(|| anyhow::anyhow!("fee amount overflows"))?;
+    }
+    Ok(totals
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0)
+        .map(|(denom, amount)| Coin {
+            denom,
+            amount: amount.to_string(),
+        })
+        .collect())
+}
+
+/// Returns the packets of the acknowledgements and the timeouts of a relay, whose fees are held
+/// by the chain the relay is submitted to.
+#[must_use]
+pub fn claimable_packets(
+    ack_msgs: &[MsgAcknowledgement],
+    timeout_msgs: &[MsgTimeout],
+) -> Vec<ClaimablePacket> {
+    let acked = ack_msgs
+        .iter()
+        .filter_map(|msg| msg.packet.as_ref())
+        .map(|packet| (FeeClaimKind::Acknowledgement, packet));
+    let timed_out = timeout_msgs
+        .iter()
+        .filter_map(|msg| msg.packet.as_ref())
+        .map(|packet| (FeeClaimKind::Timeout, packet));
+    acked
+        .chain(timed_out)
+        .map(|(kind, packet)| ClaimablePacket {
+            kind,
+            client_id: packet.source_client.clone(),
+            sequence: packet.sequence,
+        })
+        .collect()
+}
+
+/// Queries the fees escrowed for a packet from the fee middleware.
+/// # Errors
+/// Returns an error if the query fails, e.g. because the chain has no fee middleware.
+pub async fn query_packet_fees(
+    tm_client: &HttpClient,
+    client_id: &str,
+    sequence: u64,
+) -> Result<Vec<PacketFee>> {
+    let abci_resp = tm_client
+        .abci_query(
+            Some(PACKET_FEES_PATH.to_string()),
+            QueryPacketFeesRequest {
+                client_id: client_id.to_string(),
+                sequence,
+            }
+            .encode_to_vec(),
+            None,
+            false,
+        )
+        .await?;
+    anyhow::ensure!(
+        abci_resp.code.is_ok(),
+        "packet fees query for {client_id}/{sequence} failed: {}",
+        abci_resp.log
+    );
+
+    Ok(QueryPacketFeesResponse::decode(abci_resp.value.as_slice())?.fees)
+}
+
+/// Returns the fee claim messages of the packets whose fees are escrowed by the fee middleware of
+/// the chain of `tm_client`, to be appended to the relay transaction after the acknowledgements
+/// and the timeouts of the packets.
+///
+/// Claiming never fails a relay: the packets whose fees cannot be queried are relayed without
+/// claims, and the chain is assumed to have no fee middleware if the first query fails.
+pub async fn fee_claim_msgs(
+    tm_client: &HttpClient,
+    config: &RelayerFeeConfig,
+    signer_address: &str,
+    packets: &[ClaimablePacket],
+) -> Vec<Any> {
+    let payee = config.payee.as_deref().unwrap_or(signer_address);
+    let mut claim_msgs = Vec::new();
+    for (i, packet) in packets.iter().enumerate() {
+        let fees = match query_packet_fees(tm_client, &packet.client_id, packet.sequence).await {
+            Ok(fees) => fees,
+            Err(e) if i == 0 => {
+                tracing::debug!("No fee middleware to claim the relayer fees from: {e}");
+                return Vec::new();
+            }
+            Err(e) => {
+                tracing::warn!("Skipping the fee claim of a packet: {e}");
+                continue;
+            }
+        };
+        let claimable = match claimable_fees(&fees, packet.kind) {
+            Ok(claimable) if claimable.is_empty() => continue,
+            Ok(claimable) => claimable,
+            Err(e) => {
+                tracing::warn!("Skipping the fee claim of a packet: {e}");
+                continue;
+            }
+        };
+
+        tracing::info!(
+            client_id = packet.client_id,
+            sequence = packet.sequence,
+            kind = ?packet.kind,
+            fees = ?claimable,
+            payee,
+            "Claiming the relayer fees of the packet."
+        );
+        claim_msgs.push(Any {
+            type_url: MSG_CLAIM_PACKET_FEES_TYPE_URL.to_string(),
+            value: MsgClaimPacketFees {
+                client_id: packet.client_id.clone(),
+                sequence: packet.sequence,
+                payee: payee.to_string(),
+                signer: signer_address.to_string(),
+            }
+            .encode_to_vec(),
+        });
+    }
+    claim_msgs
+}
+
+#[cfg(test)]
+mod test {
+    use ibc_proto_eureka::ibc::core::channel::v2::Packet;
+
+    use super::*;
+
+    fn coin(denom: &str, amount: u128) -> Coin {
+        Coin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_claimable_fees() {
+        let fees = [
+            PacketFee {
+                recv_fee: vec![coin("uatom", 100)],
+                ack_fee: vec![coin("uatom", 50), coin("ibc/ABCD", 7)],
+                timeout_fee: vec![coin("uatom", 20)],
+                refund_address: "cosmos1refund".to_string(),
+            },
+            PacketFee {
+                recv_fee: vec![coin("uatom", 10)],
+                ack_fee: vec![],
+                timeout_fee: vec![coin("uatom", 0)],
+                refund_address: "cosmos1other".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            claimable_fees(&fees, FeeClaimKind::Acknowledgement).unwrap(),
+            vec![coin("ibc/ABCD", 7), coin("uatom", 160)]
+        );
+        assert_eq!(
+            claimable_fees(&fees, FeeClaimKind::Timeout).unwrap(),
+            vec![coin("uatom", 20)]
+        );
+        // Nothing to claim without escrowed fees
+        assert!(claimable_fees(&[], FeeClaimKind::Timeout)
+            .unwrap()
+            .is_empty());
+
+        let malformed = PacketFee {
+            timeout_fee: vec![Coin {
+                denom: "uatom".to_string(),
+                amount: "-1".to_string(),
+            }],
+            ..Default::default()
+        };
+        assert!(claimable_fees(&[malformed], FeeClaimKind::Timeout).is_err());
+    }
+
+    #[test]
+    fn test_claimable_packets() {
+        let packet = |sequence| Packet {
+            sequence,
+            source_client: "07-tendermint-0".to_string(),
+            destination_client: "08-wasm-0".to_string(),
+            ..Default::default()
+        };
+        let ack_msgs = [MsgAcknowledgement {
+            packet: Some(packet(1)),
+            ..Default::default()
+        }];
+        let timeout_msgs = [MsgTimeout {
+            packet: Some(packet(2)),
+            ..Default::default()
+        }];
+
+        assert_eq!(
+            claimable_packets(&ack_msgs, &timeout_msgs),
+            vec![
+                ClaimablePacket {
+                    kind: FeeClaimKind::Acknowledgement,
+                    client_id: "07-tendermint-0".to_string(),
+                    sequence: 1,
+                },
+                ClaimablePacket {
+                    kind: FeeClaimKind::Timeout,
+                    client_id: "07-tendermint-0".to_string(),
+                    sequence: 2,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! This module classifies the failures of the relays, so that the submitters know whether to
+//! retry them.
+//!
+//! The relayer builds the transactions and the submitters broadcast them, so both sides see
+//! failures: the errors of the relay requests, the ABCI results and `CometBFT` broadcast errors of
+//! the Cosmos SDK chains, and the JSON-RPC errors and reverts of Ethereum. They are all mapped to a
+//! [`RetryClass`] here, so that the Cosmos and Ethereum submitters and the API of the relayer
+//! agree on what to do next.
+
+use std::fmt;
+
+use alloy::sol_types::SolInterface;
+use ibc_eureka_solidity_types::{
+    ics26::router::routerErrors, sp1_ics07::sp1_ics07_tendermint::sp1_ics07_tendermintErrors,
+};
+use serde::{Deserialize, Serialize};
+
+/// The codespace of the errors of the Cosmos SDK.
+pub const SDK_CODESPACE: &str = "sdk";
+/// The prefix of the error codes of the Ethereum light client contract, see
+/// `programs/cw-ics08-wasm-eth/src/error.rs`.
+pub const ETHWASM_ERROR_CODE_PREFIX: &str = "[ethwasm-";
+
+/// What to do after a relay failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryClass {
+    /// The same transaction can be submitted again later, e.g. after the mempool drained or with
+    /// a higher fee or another nonce.
+    Retryable,
+    /// The transaction is no longer valid, but a relay built again from the same request can
+    /// succeed, e.g. once the proofs are built against the new state of the light client.
+    NeedsRebuild,
+    /// The relay cannot succeed without the intervention of an operator, or has nothing left to
+    /// relay.
+    Fatal,
+}
+
+impl fmt::Display for RetryClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Retryable => "retryable",
+            Self::NeedsRebuild => "needs_rebuild",
+            Self::Fatal => "fatal",
+        })
+    }
+}
+
+/// The patterns of the failure messages, checked in order, with their class. The messages are
+/// lowercased before they are matched.
+const MESSAGE_PATTERNS: &[(&str, RetryClass)] = &[
+    // The states of the light clients moved on while the relay was built or waited
+    ("consensus state not found", RetryClass::NeedsRebuild),
+    ("no consensus state found", RetryClass::NeedsRebuild),
+    (
+        "historical updates are not allowed",
+        RetryClass::NeedsRebuild,
+    ),
+    (
+        "update advances the client by too few slots",
+        RetryClass::NeedsRebuild,
+    ),
+    ("timeout elapsed", RetryClass::NeedsRebuild),
+    ("tx too large", RetryClass::NeedsRebuild),
+    ("exceeds block gas limit", RetryClass::NeedsRebuild),
+    ("oversized data", RetryClass::NeedsRebuild),
+    // The clients that need an operator
+    ("client is already frozen", RetryClass::Fatal),
+    ("client state is frozen", RetryClass::Fatal),
+    ("client is not active", RetryClass::Fatal),
+    ("insufficient funds", RetryClass::Fatal),
+    // The Cosmos SDK and CometBFT broadcast errors
+    ("mempool is full", RetryClass::Retryable),
+    ("tx already exists in cache", RetryClass::Retryable),
+    ("account sequence mismatch", RetryClass::Retryable),
+    ("insufficient fee", RetryClass::Retryable),
+    ("out of gas", RetryClass::Retryable),
+    ("timed out waiting for tx", RetryClass::Retryable),
+    // The Ethereum transaction pool errors
+    ("nonce too low", RetryClass::Retryable),
+    ("nonce too high", RetryClass::Retryable),
+    ("already known", RetryClass::Retryable),
+    ("replacement transaction underpriced", RetryClass::Retryable),
+    ("transaction underpriced", RetryClass::Retryable),
+    (
+        "max fee per gas less than block base fee",
+        RetryClass::Retryable,
+    ),
+    ("intrinsic gas too low", RetryClass::Retryable),
+    // The transport errors of the RPC endpoints, and the waits of the relayer
+    ("connection refused", RetryClass::Retryable),
+    ("connection reset", RetryClass::Retryable),
+    ("timed out", RetryClass::Retryable),
+    ("timeout exceeded", RetryClass::Retryable),
+    ("too many requests", RetryClass::Retryable),
+    ("rate limit", RetryClass::Retryable),
+    ("service unavailable", RetryClass::Retryable),
+    ("not yet confirmed", RetryClass::Retryable),
+];
+
+/// Classifies the failure of a relay from its message, such as the error of a relay request, the
+/// log of a rejected Cosmos SDK transaction or a JSON-RPC error of Ethereum. Unknown failures are
+/// [`RetryClass::Fatal`], so that they are never retried in a loop.
+#[must_use]
+pub fn classify_message(message: &str) -> RetryClass {
+    if let Some(code) = ethwasm_error_code(message) {
+        return classify_ethwasm_code(code);
+    }
+
+    let lowercase = message.to_lowercase();
+    if lowercase.contains("revert") {
+        return revert_data(message).map_or(RetryClass::Fatal, |data| classify_eth_revert(&data));
+    }
+
+    MESSAGE_PATTERNS
+        .iter()
+        .find(|(pattern, _)| lowercase.contains(pattern))
+        .map_or(RetryClass::Fatal, |(_, class)| *class)
+}
+
+/// Classifies the failure of a relay request of the relayer, including its causes.
+#[must_use]
+pub fn classify_error(err: &anyhow::Error) -> RetryClass {
+    #[cfg(feature = "eth-to-cosmos")]
+    if err.is::<crate::tx_builder::eth_to_cosmos::StaleProof>() {
+        return RetryClass::NeedsRebuild;
+    }
+    if err.is::<crate::relay_scope::RelayAborted>() {
+        return RetryClass::Retryable;
+    }
+
+    classify_message(&format!("{err:#}"))
+}
+
+/// Classifies the ABCI result of a rejected Cosmos SDK transaction, from its codespace, code and
+/// log. The errors of the Cosmos SDK are classified by their code, and the others by their log.
+#[must_use]
+pub fn classify_abci(codespace: &str, code: u32, log: &str) -> RetryClass {
+    if codespace != SDK_CODESPACE {
+        return classify_message(log);
+    }
+
+    match code {
+        // invalid sequence, out of gas, insufficient fee, tx in mempool cache, mempool is full,
+        // wrong sequence, tx timeout
+        3 | 11 | 13 | 19 | 20 | 32 | 42 => RetryClass::Retryable,
+        // tx too large, tx timeout height
+        21 | 30 => RetryClass::NeedsRebuild,
+        // The messages are executed with the internal code, whose log carries the error of the
+        // module that failed
+        1 => classify_message(log),
+        _ => RetryClass::Fatal,
+    }
+}
+
+/// Classifies the revert data of a transaction of Ethereum, from the errors of the `ICS26Router`
+/// and of the `SP1ICS07Tendermint` light client, which the router bubbles up.
+#[must_use]
+pub fn classify_eth_revert(data: &[u8]) -> RetryClass {
+    if let Ok(err) = routerErrors::abi_decode(data, true) {
+        return match err {
+            // The packet timed out, and can be relayed as a timeout instead
+            routerErrors::IBCInvalidTimeoutTimestamp(_) => RetryClass::NeedsRebuild,
+            _ => RetryClass::Fatal,
+        };
+    }
+
+    if let Ok(err) = sp1_ics07_tendermintErrors::abi_decode(data, true) {
+        return match err {
+            // The clock of Ethereum is behind the proven block
+            sp1_ics07_tendermintErrors::ProofIsInTheFuture(_) => RetryClass::Retryable,
+            sp1_ics07_tendermintErrors::ProofIsTooOld(_)
+            | sp1_ics07_tendermintErrors::ConsensusStateNotFound(_)
+            | sp1_ics07_tendermintErrors::ConsensusStateHashMismatch(_)
+            | sp1_ics07_tendermintErrors::ConsensusStateRootMismatch(_)
+            | sp1_ics07_tendermintErrors::ProofHeightMismatch(_)
+            | sp1_ics07_tendermintErrors::KeyValuePairNotInCache(_) => RetryClass::NeedsRebuild,
+            _ => RetryClass::Fatal,
+        };
+    }
+
+    RetryClass::Fatal
+}
+
+/// Classifies an error code of the Ethereum light client contract.
+const fn classify_ethwasm_code(code: u32) -> RetryClass {
+    match code {
+        // verify (non-)membership failed, verify client message failed, update client state
+        // failed, consensus state not found, invalid header batch size, compressed client message
+        // not accepted
+        11 | 12 | 13 | 14 | 19 | 26 | 27 => RetryClass::NeedsRebuild,
+        _ => RetryClass::Fatal,
+    }
+}
+
+/// Returns the code of the first error of the Ethereum light client contract in `message`.
+fn ethwasm_error_code(message: &str) -> Option<u32> {
+    let (_, rest) = message.split_once(ETHWASM_ERROR_CODE_PREFIX)?;
+    let (code, _) = rest.split_once(']')?;
+    code.parse().ok()
+}
+
+/// Returns the revert data of a JSON-RPC error, which is the first hex string of at least a
+/// selector after the revert.
+fn revert_data(message: &str) -> Option<Vec<u8>> {
+    let start = message.to_lowercase().find("revert")?;
+    message[start..]
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|token| token.strip_prefix("0x"))
+        .find(|hex| hex.len() >= 8 && hex.len() % 2 == 0)
+        .and_then(|hex| alloy::hex::decode(hex).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::{
+        primitives::{hex, U256},
+        sol_types::SolError,
+    };
+    use ibc_eureka_solidity_types::{ics26::router, sp1_ics07::sp1_ics07_tendermint};
+
+    use super::*;
+
+    #[test]
+    fn test_classify_abci() {
+        assert_eq!(
+            classify_abci("sdk", 20, "mempool is full"),
+            RetryClass::Retryable
+        );
+        assert_eq!(
+            classify_abci("sdk", 21, "tx too large"),
+            RetryClass::NeedsRebuild
+        );
+        assert_eq!(
+            classify_abci("sdk", 5, "insufficient funds"),
+            RetryClass::Fatal
+        );
+        assert_eq!(
+            classify_abci(
+                "sdk",
+                1,
+                "failed to execute message; message index: 1: [ethwasm-19] consensus state not found: wasm contract call failed"
+            ),
+            RetryClass::NeedsRebuild
+        );
+        assert_eq!(
+            classify_abci(
+                "08-wasm",
+                4,
+                "[ethwasm-16] client is already frozen: wasm contract call failed"
+            ),
+            RetryClass::Fatal
+        );
+        assert_eq!(
+            classify_abci(
+                "client",
+                7,
+                "consensus state not found: invalid proof height"
+            ),
+            RetryClass::NeedsRebuild
+        );
+    }
+
+    #[test]
+    fn test_classify_message() {
+        assert_eq!(
+            classify_message("nonce too low: next nonce 7, tx nonce 6"),
+            RetryClass::Retryable
+        );
+        assert_eq!(
+            classify_message("error sending request: connection refused"),
+            RetryClass::Retryable
+        );
+        assert_eq!(
+            classify_message("insufficient funds for gas * price + value"),
+            RetryClass::Fatal
+        );
+        assert_eq!(classify_message("something unexpected"), RetryClass::Fatal);
+    }
+
+    #[test]
+    fn test_classify_eth_revert() {
+        let too_old = sp1_ics07_tendermint::ProofIsTooOld {
+            now: U256::from(100),
+            proofTimestamp: U256::from(1),
+        }
+        .abi_encode();
+        assert_eq!(classify_eth_revert(&too_old), RetryClass::NeedsRebuild);
+
+        let message = format!(
+            "server returned an error response: error code 3: execution reverted, data: \"0x{}\"",
+            hex::encode(&too_old)
+        );
+        assert_eq!(classify_message(&message), RetryClass::NeedsRebuild);
+
+        let not_found = router::IBCClientNotFound {
+            clientId: "client-0".to_string(),
+        }
+        .abi_encode();
+        assert_eq!(classify_eth_revert(&not_found), RetryClass::Fatal);
+        assert_eq!(classify_message("execution reverted"), RetryClass::Fatal);
+    }
+}
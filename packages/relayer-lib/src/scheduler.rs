@@ -0,0 +1,194 @@
+//! This module defines the dependencies between the routes of a relayer.
+//!
+//! The acks of a round trip depend on the recvs of the opposite route: an ack can only be relayed
+//! once the recv transaction landed on the destination chain and wrote the acknowledgement. A
+//! relayer module with an [`AckSchedule`] waits for the acknowledgements of the packets it relayed
+//! with [`wait_for_write_acks`], so that the ack relay is triggered as soon as they are written.
+
+use std::{collections::BTreeSet, time::Duration};
+
+use anyhow::Result;
+use futures_timer::Delay;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::Chain, introspection::RelayedPacket, listener::ChainListenerService,
+    tx_builder::plan::MsgKind,
+};
+
+/// The default interval between two polls of the destination chain in seconds.
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 6;
+/// The default time to wait for the acknowledgements in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10 * 60;
+
+/// The schedule of the ack relays that depend on the recvs relayed by a module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AckSchedule {
+    /// The interval between two polls of the destination chain in seconds.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// The time to wait for the acknowledgements in seconds, after which the remaining packets are
+    /// left to the submitter.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for AckSchedule {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: DEFAULT_POLL_INTERVAL_SECS,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+const fn default_poll_interval_secs() -> u64 {
+    DEFAULT_POLL_INTERVAL_SECS
+}
+
+const fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+/// The packets whose acknowledgements are awaited, and the transactions that wrote the
+/// acknowledgements found so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AwaitedAcks<TxId> {
+    remaining: BTreeSet<u64>,
+    tx_ids: Vec<TxId>,
+}
+
+impl<TxId: PartialEq> AwaitedAcks<TxId> {
+    /// Awaits the acknowledgements of the packets with the given sequences.
+    #[must_use]
+    pub fn new(sequences: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            remaining: sequences.into_iter().collect(),
+            tx_ids: Vec::new(),
+        }
+    }
+
+    /// Records the transactions that wrote the acknowledgements of some packets.
+    /// Acknowledgements of packets that are not awaited are ignored.
+    pub fn observe(&mut self, found: impl IntoIterator<Item = (TxId, u64)>) {
+        for (tx_id, sequence) in found {
+            if self.remaining.remove(&sequence) && !self.tx_ids.contains(&tx_id) {
+                self.tx_ids.push(tx_id);
+            }
+        }
+    }
+
+    /// Returns the sequences of the packets whose acknowledgements were not found yet.
+    #[must_use]
+    pub fn remaining(&self) -> Vec<u64> {
+        self.remaining.iter().copied().collect()
+    }
+
+    /// Returns `true` if all the acknowledgements were found.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    /// Returns the transactions that wrote the acknowledgements found so far, in the order they
+    /// were found.
+    #[must_use]
+    pub fn into_tx_ids(self) -> Vec<TxId> {
+        self.tx_ids
+    }
+}
+
+/// Returns the sequences of the packets sent from `source_client` that are received by a relay
+/// transaction.
+#[must_use]
+pub fn recv_sequences(relayed_packets: &[RelayedPacket], source_client: &str) -> Vec<u64> {
+    relayed_packets
+        .iter()
+        .filter(|packet| {
+            packet.kind == MsgKind::RecvPacket && packet.source_client == source_client
+        })
+        .map(|packet| packet.sequence)
+        .collect()
+}
+
+/// Polls the destination chain until the acknowledgements of all the packets are written, or
+/// until the schedule times out.
+///
+/// Returns the transactions that wrote the acknowledgements found, which is empty if none were
+/// written in time.
+/// # Errors
+/// Returns an error if the destination chain cannot be queried.
+pub async fn wait_for_write_acks<C, L>(
+    listener: &L,
+    schedule: &AckSchedule,
+    source_client: &str,
+    sequences: Vec<u64>,
+    start_height: C::Height,
+) -> Result<Vec<C::TxId>>
+where
+    C: Chain,
+    C::TxId: PartialEq,
+    L: ChainListenerService<C> + Sync,
+{
+    let mut awaited = AwaitedAcks::new(sequences);
+    let timeout = Duration::from_secs(schedule.timeout_secs);
+    let interval = Duration::from_secs(schedule.poll_interval_secs);
+
+    let start = std::time::Instant::now();
+    while !awaited.is_complete() && start.elapsed() < timeout {
+        let found = listener
+            .fetch_write_ack_txs(source_client, &awaited.remaining(), start_height.clone())
+            .await?;
+        awaited.observe(found);
+
+        if !awaited.is_complete() {
+            Delay::new(interval).await;
+        }
+    }
+
+    if !awaited.is_complete() {
+        tracing::warn!(
+            sequences = ?awaited.remaining(),
+            "Timed out waiting for the acknowledgements of the relayed packets."
+        );
+    }
+    Ok(awaited.into_tx_ids())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_awaited_acks() {
+        let mut awaited = AwaitedAcks::new([1, 2, 3]);
+        assert_eq!(awaited.remaining(), vec![1, 2, 3]);
+
+        // A single transaction can acknowledge several packets, and unknown packets are ignored
+        awaited.observe([("tx-a", 1), ("tx-a", 3), ("tx-b", 7)]);
+        assert_eq!(awaited.remaining(), vec![2]);
+        assert!(!awaited.is_complete());
+
+        // Packets are only recorded once
+        awaited.observe([("tx-c", 1), ("tx-d", 2)]);
+        assert!(awaited.is_complete());
+        assert_eq!(awaited.into_tx_ids(), vec!["tx-a", "tx-d"]);
+    }
+
+    #[test]
+    fn test_recv_sequences() {
+        let packet = |kind, source_client: &str, sequence| RelayedPacket {
+            kind,
+            source_client: source_client.to_string(),
+            sequence,
+        };
+        let relayed_packets = vec![
+            packet(MsgKind::RecvPacket, "client-0", 1),
+            packet(MsgKind::Acknowledgement, "client-0", 2),
+            packet(MsgKind::RecvPacket, "client-1", 3),
+            packet(MsgKind::RecvPacket, "client-0", 4),
+        ];
+
+        assert_eq!(recv_sequences(&relayed_packets, "client-0"), vec![1, 4]);
+    }
+}
@@ -0,0 +1,185 @@
+//! This module defines the slot timing of a beacon chain, from which the relayer derives its
+//! waiting and polling intervals.
+//!
+//! Devnets often run with fewer seconds per slot or slots per epoch than mainnet, so the intervals
+//! are expressed in slots and epochs and converted with the [`SlotTiming`] served by the spec
+//! endpoint of the Beacon API. The light clients are checked against the same timing with
+//! [`SlotTiming::ensure_client_matches`], since a client with other parameters computes the wrong
+//! slots and periods.
+
+use std::time::Duration;
+
+use anyhow::{ensure, Result};
+use ethereum_light_client::client_state::ClientState as EthClientState;
+use ethereum_types::consensus::spec::Spec;
+use serde::{Deserialize, Serialize};
+
+/// The number of epochs to wait for the finality of a block, about 45 minutes on mainnet.
+pub const FINALITY_TIMEOUT_EPOCHS: u64 = 7;
+/// The number of epochs after the epoch following a block until the block is expected to be
+/// finalized: the following epoch is justified, and finalized one epoch later.
+pub const FINALITY_DEPTH_EPOCHS: u64 = 2;
+/// The number of slots to wait for the target chain to reach the signature slot of an update,
+/// about 15 minutes on mainnet.
+pub const SIGNATURE_SLOT_TIMEOUT_SLOTS: u64 = 75;
+
+/// The slot timing of a beacon chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotTiming {
+    /// The number of seconds per slot.
+    pub seconds_per_slot: u64,
+    /// The number of slots per epoch.
+    pub slots_per_epoch: u64,
+    /// The number of epochs per sync committee period.
+    pub epochs_per_sync_committee_period: u64,
+}
+
+impl Default for SlotTiming {
+    /// The slot timing of mainnet.
+    fn default() -> Self {
+        Self {
+            seconds_per_slot: 12,
+            slots_per_epoch: 32,
+            epochs_per_sync_committee_period: 256,
+        }
+    }
+}
+
+impl From<&Spec> for SlotTiming {
+    fn from(spec: &Spec) -> Self {
+        Self {
+            seconds_per_slot: spec.seconds_per_slot,
+            slots_per_epoch: spec.slots_per_epoch,
+            epochs_per_sync_committee_period: spec.epochs_per_sync_committee_period,
+        }
+    }
+}
+
+impl SlotTiming {
+    /// Returns the duration of a number of slots.
+    #[must_use]
+    pub const fn slots(&self, slots: u64) -> Duration {
+        Duration::from_secs(slots.saturating_mul(self.seconds_per_slot))
+    }
+
+    /// Returns the duration of a number of epochs.
+    #[must_use]
+    pub const fn epochs(&self, epochs: u64) -> Duration {
+        self.slots(epochs.saturating_mul(self.slots_per_epoch))
+    }
+
+    /// Returns the interval between two polls of the beacon chain, which is one slot.
+    #[must_use]
+    pub const fn poll_interval(&self) -> Duration {
+        self.slots(1)
+    }
+
+    /// Returns the time to wait for the finality of a block.
+    #[must_use]
+    pub const fn finality_timeout(&self) -> Duration {
+        self.epochs(FINALITY_TIMEOUT_EPOCHS)
+    }
+
+    /// Returns the slot at which the block at `slot` is expected to be covered by the finality
+    /// updates, which is [`FINALITY_DEPTH_EPOCHS`] after the start of the epoch following it.
+    #[must_use]
+    pub fn expected_finality_slot(&self, slot: u64) -> u64 {
+        let epoch = slot / self.slots_per_epoch.max(1);
+        epoch
+            .saturating_add(1 + FINALITY_DEPTH_EPOCHS)
+            .saturating_mul(self.slots_per_epoch)
+    }
+
+    /// Returns the expected time until the block at `slot` is finalized, when the beacon chain is
+    /// at `current_slot`.
+    #[must_use]
+    pub fn time_to_finality(&self, slot: u64, current_slot: u64) -> Duration {
+        self.slots(
+            self.expected_finality_slot(slot)
+                .saturating_sub(current_slot),
+        )
+    }
+
+    /// Returns the time to wait for the target chain to reach the signature slot of an update.
+    #[must_use]
+    pub const fn signature_slot_timeout(&self) -> Duration {
+        self.slots(SIGNATURE_SLOT_TIMEOUT_SLOTS)
+    }
+
+    /// Checks that an Ethereum light client uses the slot timing of the beacon chain.
+    /// # Errors
+    /// Returns an error if any of the parameters differs.
+    pub fn ensure_client_matches(&self, client_state: &EthClientState) -> Result<()> {
+        let client_timing = Self {
+            seconds_per_slot: client_state.seconds_per_slot,
+            slots_per_epoch: client_state.slots_per_epoch,
+            epochs_per_sync_committee_period: client_state.epochs_per_sync_committee_period,
+        };
+        ensure!(
+            client_timing == *self,
+            "ethereum light client slot timing mismatch: the client uses {client_timing:?}, but the beacon api reports {self:?}"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slot_timing_intervals() {
+        let mainnet = SlotTiming::default();
+        assert_eq!(mainnet.poll_interval(), Duration::from_secs(12));
+        assert_eq!(mainnet.finality_timeout(), Duration::from_secs(7 * 32 * 12));
+        assert_eq!(
+            mainnet.signature_slot_timeout(),
+            Duration::from_secs(15 * 60)
+        );
+
+        // A minimal preset devnet is proportionally faster
+        let devnet = SlotTiming {
+            seconds_per_slot: 2,
+            slots_per_epoch: 8,
+            epochs_per_sync_committee_period: 8,
+        };
+        assert_eq!(devnet.poll_interval(), Duration::from_secs(2));
+        assert_eq!(devnet.finality_timeout(), Duration::from_secs(7 * 8 * 2));
+    }
+
+    #[test]
+    fn test_time_to_finality() {
+        let mainnet = SlotTiming::default();
+        // A block in epoch 3 is finalized at the start of epoch 6
+        assert_eq!(mainnet.expected_finality_slot(100), 192);
+        assert_eq!(mainnet.expected_finality_slot(96), 192);
+        assert_eq!(mainnet.expected_finality_slot(95), 160);
+        assert_eq!(
+            mainnet.time_to_finality(100, 110),
+            Duration::from_secs(82 * 12)
+        );
+        assert_eq!(mainnet.time_to_finality(100, 200), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_ensure_client_matches() {
+        let devnet = SlotTiming {
+            seconds_per_slot: 2,
+            slots_per_epoch: 8,
+            epochs_per_sync_committee_period: 8,
+        };
+        let client_state = EthClientState {
+            seconds_per_slot: 2,
+            slots_per_epoch: 8,
+            epochs_per_sync_committee_period: 8,
+            ..Default::default()
+        };
+        assert!(devnet.ensure_client_matches(&client_state).is_ok());
+
+        let client_state = EthClientState {
+            seconds_per_slot: 12,
+            ..client_state
+        };
+        assert!(devnet.ensure_client_matches(&client_state).is_err());
+    }
+}
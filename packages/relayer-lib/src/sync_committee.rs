@@ -0,0 +1,338 @@
+//! This module defines the [`SyncCommitteeStore`] of the sync committees verified by a relayer.
+//!
+//! The tx builder of the Ethereum light clients needs the sync committee signing every update it
+//! submits, which it otherwise learns from the light client update of the previous period. The
+//! committees of the headers it verified locally are kept in the store, and exported as
+//! [`SyncCommitteeSnapshot`]s, so that other relayer instances can import them instead of fetching
+//! and verifying the previous periods again.
+//!
+//! Imported committees are checked against their aggregate public key and are only used for the
+//! beacon chain they were exported from. They are not trusted further: every header built with
+//! them is still verified locally against the trusted committees of the light client before it is
+//! submitted, and by the light client itself.
+//!
+//! The store also keeps the sync committee participation of the recent verified headers, so that
+//! a degraded participation is seen before the updates fall below the threshold of the light
+//! client.
+
+use std::{collections::BTreeMap, sync::RwLock};
+
+use alloy::primitives::B256;
+use anyhow::{ensure, Result};
+use ethereum_light_client::{client_state::ClientState, header::Header, verify::BlsVerify};
+use ethereum_types::consensus::sync_committee::SyncCommittee;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::bls::BlstVerifier;
+
+/// A verified sync committee of a beacon chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCommitteeSnapshot {
+    /// The genesis validators root of the beacon chain.
+    pub genesis_validators_root: B256,
+    /// The sync committee period.
+    pub period: u64,
+    /// The sync committee of the period.
+    pub sync_committee: SyncCommittee,
+}
+
+impl SyncCommitteeSnapshot {
+    /// Checks that the aggregate public key of the committee is the aggregate of its public keys.
+    /// # Errors
+    /// Returns an error if the committee is empty, if a public key is invalid, or if the aggregate
+    /// public key does not match.
+    pub fn verify(&self) -> Result<()> {
+        ensure!(
+            !self.sync_committee.pubkeys.is_empty(),
+            "sync committee of period {} is empty",
+            self.period
+        );
+        let aggregate_pubkey = BlstVerifier.aggregate(&self.sync_committee.pubkeys)?;
+        ensure!(
+            aggregate_pubkey == self.sync_committee.aggregate_pubkey,
+            "sync committee of period {} has aggregate public key {}, but its public keys aggregate to {aggregate_pubkey}",
+            self.period,
+            self.sync_committee.aggregate_pubkey
+        );
+        Ok(())
+    }
+}
+
+/// The number of verified headers whose sync committee participation is kept, by chain.
+pub const SYNC_COMMITTEE_PARTICIPATION_HISTORY: usize = 256;
+
+/// The hook called with the sync committee participation of every newly verified header.
+pub type ParticipationHook = fn(participation: &SyncCommitteeParticipation);
+
+/// The sync committee participation of a verified header.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCommitteeParticipation {
+    /// The chain ID of the Ethereum chain.
+    pub chain_id: u64,
+    /// The finalized slot of the header.
+    pub finalized_slot: u64,
+    /// The slot of the sync committee signature.
+    pub signature_slot: u64,
+    /// The number of sync committee members that signed the header.
+    pub participants: u64,
+    /// The size of the sync committee.
+    pub committee_size: u64,
+    /// The minimum number of participants required by the light client.
+    pub min_participants: u64,
+}
+
+impl SyncCommitteeParticipation {
+    /// Returns the number of participants above the minimum required by the light client, which
+    /// is negative if the header would be rejected.
+    #[must_use]
+    pub fn margin(&self) -> i64 {
+        i64::try_from(self.participants)
+            .unwrap_or(i64::MAX)
+            .saturating_sub(i64::try_from(self.min_participants).unwrap_or(i64::MAX))
+    }
+}
+
+/// The sync committees verified by a relayer, by beacon chain and period.
+#[derive(Debug, Default)]
+pub struct SyncCommitteeStore {
+    committees: RwLock<BTreeMap<(B256, u64), SyncCommittee>>,
+    participation: RwLock<BTreeMap<(u64, u64), SyncCommitteeParticipation>>,
+    on_participation: Option<ParticipationHook>,
+}
+
+impl SyncCommitteeStore {
+    /// Sets the hook called with the sync committee participation of every newly verified header
+    #[must_use]
+    pub const fn with_participation_hook(mut self, on_participation: ParticipationHook) -> Self {
+        self.on_participation = Some(on_participation);
+        self
+    }
+
+    /// Returns the sync committee of `period` of the beacon chain, if known.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn get(&self, genesis_validators_root: B256, period: u64) -> Option<SyncCommittee> {
+        self.committees
+            .read()
+            .unwrap()
+            .get(&(genesis_validators_root, period))
+            .cloned()
+    }
+
+    /// Returns the known sync committees of the beacon chain, by period.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn committees(&self, genesis_validators_root: B256) -> BTreeMap<u64, SyncCommittee> {
+        self.committees
+            .read()
+            .unwrap()
+            .range((genesis_validators_root, 0)..=(genesis_validators_root, u64::MAX))
+            .map(|((_, period), committee)| (*period, committee.clone()))
+            .collect()
+    }
+
+    /// Returns the sync committee participation of the recent verified headers of the chain, by
+    /// finalized slot.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn participation(&self, chain_id: u64) -> Vec<SyncCommitteeParticipation> {
+        self.participation
+            .read()
+            .unwrap()
+            .range((chain_id, 0)..=(chain_id, u64::MAX))
+            .map(|(_, participation)| participation.clone())
+            .collect()
+    }
+
+    /// Records the sync committees of headers verified against the light client with
+    /// `client_state`: the committee signing each header, and the next committee it carries.
+    /// The sync committee participation of each header is recorded as well.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    pub fn record_verified_headers(&self, client_state: &ClientState, headers: &[Header]) {
+        self.record_participation(client_state, headers);
+
+        let genesis_validators_root = client_state.genesis_validators_root;
+        let mut committees = self.committees.write().unwrap();
+        for header in headers {
+            let update = &header.consensus_update;
+            let signature_period =
+                client_state.compute_sync_committee_period_at_slot(update.signature_slot);
+            committees.insert(
+                (genesis_validators_root, signature_period),
+                header.active_sync_committee.sync_committee().clone(),
+            );
+
+            if let Some(next_sync_committee) = &update.next_sync_committee {
+                let attested_period = client_state
+                    .compute_sync_committee_period_at_slot(update.attested_header.beacon.slot);
+                committees.insert(
+                    (genesis_validators_root, attested_period + 1),
+                    next_sync_committee.clone(),
+                );
+            }
+        }
+    }
+
+    /// Records the sync committee participation of verified headers, and calls the participation
+    /// hook for the headers that were not recorded yet.
+    fn record_participation(&self, client_state: &ClientState, headers: &[Header]) {
+        let chain_id = client_state.chain_id;
+        let mut recorded = Vec::new();
+        {
+            let mut history = self.participation.write().unwrap();
+            for header in headers {
+                let update = &header.consensus_update;
+                let participation = SyncCommitteeParticipation {
+                    chain_id,
+                    finalized_slot: update.finalized_header.beacon.slot,
+                    signature_slot: update.signature_slot,
+                    participants: update.sync_aggregate.num_sync_committe_participants(),
+                    committee_size: update.sync_aggregate.sync_committee_size(),
+                    min_participants: client_state.min_sync_committee_participants,
+                };
+                let key = (chain_id, participation.finalized_slot);
+                if history.insert(key, participation.clone()).is_none() {
+                    recorded.push(participation);
+                }
+            }
+
+            let stale = history
+                .range((chain_id, 0)..=(chain_id, u64::MAX))
+                .count()
+                .saturating_sub(SYNC_COMMITTEE_PARTICIPATION_HISTORY);
+            let stale_keys = history
+                .range((chain_id, 0)..=(chain_id, u64::MAX))
+                .take(stale)
+                .map(|(key, _)| *key)
+                .collect::<Vec<_>>();
+            for key in stale_keys {
+                history.remove(&key);
+            }
+        }
+
+        if let Some(on_participation) = self.on_participation {
+            recorded.iter().for_each(on_participation);
+        }
+    }
+
+    /// Returns the snapshots of all the known sync committees.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn export(&self) -> Vec<SyncCommitteeSnapshot> {
+        self.committees
+            .read()
+            .unwrap()
+            .iter()
+            .map(
+                |((genesis_validators_root, period), sync_committee)| SyncCommitteeSnapshot {
+                    genesis_validators_root: *genesis_validators_root,
+                    period: *period,
+                    sync_committee: sync_committee.clone(),
+                },
+            )
+            .collect()
+    }
+
+    /// Imports the snapshots exported by another relayer, and returns the number of sync
+    /// committees that were not known yet. Nothing is imported if any snapshot is rejected.
+    /// # Errors
+    /// Returns an error if a snapshot fails [`SyncCommitteeSnapshot::verify`], or conflicts with
+    /// a known sync committee of the same period.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    pub fn import(&self, snapshots: Vec<SyncCommitteeSnapshot>) -> Result<usize> {
+        snapshots
+            .iter()
+            .try_for_each(SyncCommitteeSnapshot::verify)?;
+
+        let mut committees = self.committees.write().unwrap();
+        for snapshot in &snapshots {
+            if let Some(known) =
+                committees.get(&(snapshot.genesis_validators_root, snapshot.period))
+            {
+                ensure!(
+                    *known == snapshot.sync_committee,
+                    "sync committee of period {} conflicts with the known one",
+                    snapshot.period
+                );
+            }
+        }
+
+        let mut imported = 0;
+        for snapshot in snapshots {
+            let key = (snapshot.genesis_validators_root, snapshot.period);
+            if committees.insert(key, snapshot.sync_committee).is_none() {
+                imported += 1;
+            }
+        }
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use blst::min_pk::SecretKey;
+    use ethereum_types::consensus::bls::BlsPublicKey;
+
+    use super::*;
+
+    fn sync_committee(seed: u8) -> SyncCommittee {
+        let pubkeys = (0..4)
+            .map(|i| {
+                let secret_key = SecretKey::key_gen(&[seed.wrapping_add(i); 32], &[]).unwrap();
+                BlsPublicKey::from(secret_key.sk_to_pk().compress())
+            })
+            .collect::<Vec<_>>();
+        let aggregate_pubkey = BlstVerifier.aggregate(&pubkeys).unwrap();
+        SyncCommittee {
+            pubkeys,
+            aggregate_pubkey,
+        }
+    }
+
+    #[test]
+    fn test_import_export() {
+        let root = B256::repeat_byte(1);
+        let snapshots = vec![
+            SyncCommitteeSnapshot {
+                genesis_validators_root: root,
+                period: 7,
+                sync_committee: sync_committee(7),
+            },
+            SyncCommitteeSnapshot {
+                genesis_validators_root: root,
+                period: 8,
+                sync_committee: sync_committee(8),
+            },
+        ];
+
+        let store = SyncCommitteeStore::default();
+        assert_eq!(store.import(snapshots.clone()).unwrap(), 2);
+        assert_eq!(store.import(snapshots.clone()).unwrap(), 0);
+        assert_eq!(store.export(), snapshots);
+        assert_eq!(store.get(root, 8), Some(sync_committee(8)));
+        assert_eq!(store.get(B256::repeat_byte(2), 8), None);
+        assert_eq!(store.committees(root).len(), 2);
+
+        let conflicting = SyncCommitteeSnapshot {
+            genesis_validators_root: root,
+            period: 8,
+            sync_committee: sync_committee(9),
+        };
+        assert!(store.import(vec![conflicting]).is_err());
+
+        let mut forged = SyncCommitteeSnapshot {
+            genesis_validators_root: root,
+            period: 9,
+            sync_committee: sync_committee(9),
+        };
+        forged.sync_committee.aggregate_pubkey = sync_committee(10).aggregate_pubkey;
+        assert!(store.import(vec![forged]).is_err());
+        assert_eq!(store.get(root, 9), None);
+    }
+}
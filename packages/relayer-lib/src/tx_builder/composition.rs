@@ -0,0 +1,256 @@
+//! This module defines [`TxComposition`], how the client updates and the packet messages of a
+//! relay are composed into transactions.
+//!
+//! By default a relay is a single atomic transaction, in which the client updates are applied
+//! before the packet messages proven against them. Some chains favor smaller transactions, e.g.
+//! because of their mempool or block gas limits, so a relay can instead be [`TxComposition::Split`]
+//! into a transaction with the client updates and a transaction with the packet messages, which
+//! must be submitted in that order since the packets are proven against the updated client.
+//!
+//! Independently of the composition, a transaction exceeding the [`TxSizeLimit`] of its chain is
+//! split into consecutive transactions fitting within the limit, also submitted in order.
+
+#[cfg(feature = "cosmos-to-eth")]
+use alloy::sol_types::SolCall;
+use anyhow::Result;
+#[cfg(feature = "cosmos-to-eth")]
+use ibc_eureka_solidity_types::ics26::router::multicallCall;
+use ibc_proto_eureka::cosmos::tx::v1beta1::TxBody;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use super::plan::{GasEstimates, MsgKind, TxSizeLimit};
+use crate::utils::cosmos;
+#[cfg(feature = "cosmos-to-eth")]
+use crate::utils::eth_eureka;
+
+/// The composition of the client updates and the packet messages of a relay into transactions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxComposition {
+    /// The client updates and the packet messages are submitted in a single atomic transaction.
+    #[default]
+    Atomic,
+    /// The client updates are submitted in a first transaction, and the packet messages in a
+    /// second transaction.
+    Split,
+}
+
+/// A relay transaction split into its client updates and its packet messages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitTx {
+    /// The transaction with the client updates, to be submitted first.
+    pub update_tx: Vec<u8>,
+    /// The transaction with the packet messages, to be submitted once the updates are included.
+    pub packet_tx: Vec<u8>,
+}
+
+/// Splits a relay transaction to a Cosmos SDK chain into its client updates and its packet
+/// messages, keeping the order of the messages within each transaction.
+/// # Returns
+/// `None` if the transaction has no client update or no packet message, in which case it is
+/// submitted as is.
+/// # Errors
+/// Returns an error if the transaction is not a valid tx body.
+pub fn split_cosmos_tx(tx: &[u8]) -> Result<Option<SplitTx>> {
+    let tx_body = TxBody::decode(tx)?;
+    let (update_msgs, packet_msgs): (Vec<_>, Vec<_>) = tx_body
+        .messages
+        .into_iter()
+        .partition(|msg| MsgKind::from_cosmos_type_url(&msg.type_url) == MsgKind::UpdateClient);
+    if update_msgs.is_empty() || packet_msgs.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(SplitTx {
+        update_tx: TxBody {
+            messages: update_msgs,
+            ..Default::default()
+        }
+        .encode_to_vec(),
+        packet_tx: TxBody {
+            messages: packet_msgs,
+            ..Default::default()
+        }
+        .encode_to_vec(),
+    }))
+}
+
+/// Splits a relay transaction to a Cosmos SDK chain into consecutive transactions fitting within
+/// the size limit, keeping the order of the messages.
+/// # Returns
+/// The transactions to submit in order, which is the transaction itself if it fits.
+/// # Errors
+/// Returns an error if the transaction is not a valid tx body, or if a single message exceeds the
+/// limit.
+pub fn split_cosmos_tx_by_size(tx: &[u8], limit: &TxSizeLimit) -> Result<Vec<Vec<u8>>> {
+    let plan = cosmos::plan_tx_body(tx, &GasEstimates::COSMOS_SDK)?;
+    let batches = plan.split_by_size(limit)?;
+    if batches.len() == 1 {
+        return Ok(vec![tx.to_vec()]);
+    }
+
+    let tx_body = TxBody::decode(tx)?;
+    Ok(batches
+        .into_iter()
+        .map(|batch| {
+            TxBody {
+                messages: tx_body.messages[batch].to_vec(),
+                ..tx_body.clone()
+            }
+            .encode_to_vec()
+        })
+        .collect())
+}
+
+/// Splits a multicall relay transaction to Ethereum into consecutive multicalls fitting within the
+/// size limit, keeping the order of the calls.
+/// # Returns
+/// The transactions to submit in order, which is the transaction itself if it fits.
+/// # Errors
+/// Returns an error if the transaction is not a valid multicall, or if a single call exceeds the
+/// limit.
+#[cfg(feature = "cosmos-to-eth")]
+pub fn split_multicall_tx_by_size(tx: &[u8], limit: &TxSizeLimit) -> Result<Vec<Vec<u8>>> {
+    let multicall = multicallCall::abi_decode(tx, true)?;
+    let plan = super::plan::TxPlan::new(
+        eth_eureka::multicall_msg_kinds(&multicall)?,
+        &GasEstimates::ETHEREUM,
+    );
+    let batches = plan.split_by_size(limit)?;
+    if batches.len() == 1 {
+        return Ok(vec![tx.to_vec()]);
+    }
+
+    Ok(batches
+        .into_iter()
+        .map(|batch| {
+            multicallCall {
+                data: multicall.data[batch].to_vec(),
+            }
+            .abi_encode()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use ibc_proto_eureka::google::protobuf::Any;
+
+    use super::*;
+
+    fn msg(type_url: &str, value: u8) -> Any {
+        Any {
+            type_url: type_url.to_string(),
+            value: vec![value],
+        }
+    }
+
+    #[test]
+    fn test_split_cosmos_tx() {
+        let tx = TxBody {
+            messages: vec![
+                msg("/ibc.core.client.v1.MsgUpdateClient", 1),
+                msg("/ibc.core.client.v1.MsgUpdateClient", 2),
+                msg("/ibc.core.channel.v2.MsgTimeout", 3),
+                msg("/ibc.core.channel.v2.MsgRecvPacket", 4),
+            ],
+            ..Default::default()
+        }
+        .encode_to_vec();
+
+        let split = split_cosmos_tx(&tx).unwrap().unwrap();
+        let update_tx = TxBody::decode(split.update_tx.as_slice()).unwrap();
+        let packet_tx = TxBody::decode(split.packet_tx.as_slice()).unwrap();
+        assert_eq!(
+            update_tx.messages,
+            vec![
+                msg("/ibc.core.client.v1.MsgUpdateClient", 1),
+                msg("/ibc.core.client.v1.MsgUpdateClient", 2),
+            ]
+        );
+        assert_eq!(
+            packet_tx.messages,
+            vec![
+                msg("/ibc.core.channel.v2.MsgTimeout", 3),
+                msg("/ibc.core.channel.v2.MsgRecvPacket", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_cosmos_tx_without_both_parts() {
+        let updates_only = TxBody {
+            messages: vec![msg("/ibc.core.client.v1.MsgUpdateClient", 1)],
+            ..Default::default()
+        }
+        .encode_to_vec();
+        assert_eq!(split_cosmos_tx(&updates_only).unwrap(), None);
+
+        let packets_only = TxBody {
+            messages: vec![msg("/ibc.core.channel.v2.MsgAcknowledgement", 1)],
+            ..Default::default()
+        }
+        .encode_to_vec();
+        assert_eq!(split_cosmos_tx(&packets_only).unwrap(), None);
+
+        assert!(split_cosmos_tx(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn test_split_cosmos_tx_by_size() {
+        let messages: Vec<Any> = (0..5)
+            .map(|i| Any {
+                type_url: "/ibc.core.channel.v2.MsgRecvPacket".to_string(),
+                value: vec![i; 100],
+            })
+            .collect();
+        let tx = TxBody {
+            messages: messages.clone(),
+            memo: "relayed".to_string(),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        let msg_size = cosmos::tx_body_msg_size(&messages[0]);
+
+        // Fits within the limit
+        let limit = TxSizeLimit {
+            max_tx_bytes: tx.len(),
+            reserved_bytes: 0,
+        };
+        assert_eq!(
+            split_cosmos_tx_by_size(&tx, &limit).unwrap(),
+            vec![tx.clone()]
+        );
+
+        // Two messages per transaction
+        let limit = TxSizeLimit {
+            max_tx_bytes: 2 * msg_size + 10,
+            reserved_bytes: 10,
+        };
+        let txs = split_cosmos_tx_by_size(&tx, &limit).unwrap();
+        let tx_bodies: Vec<TxBody> = txs
+            .iter()
+            .map(|tx| TxBody::decode(tx.as_slice()).unwrap())
+            .collect();
+        assert_eq!(
+            tx_bodies
+                .iter()
+                .map(|tx_body| tx_body.messages.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                messages[0..2].to_vec(),
+                messages[2..4].to_vec(),
+                messages[4..5].to_vec()
+            ]
+        );
+        assert!(tx_bodies.iter().all(|tx_body| tx_body.memo == "relayed"));
+
+        // A single message exceeding the limit
+        let limit = TxSizeLimit {
+            max_tx_bytes: msg_size - 1,
+            reserved_bytes: 0,
+        };
+        assert!(split_cosmos_tx_by_size(&tx, &limit).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! This module defines [`TxBuilder`] which is responsible for building transactions to be sent to
 //! the Cosmos SDK chain from events received from another Cosmos SDK chain.
 
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use anyhow::Result;
 use ibc_core_host_types::identifiers::ChainId;
@@ -15,15 +15,23 @@ use ibc_proto_eureka::{
     },
 };
 use prost::Message;
-use tendermint_rpc::HttpClient;
+use tendermint_rpc::{Client, HttpClient};
 
 use crate::{
     chain::CosmosSdk,
+    clock::{Clock, SystemClock},
+    confirmation::{ensure_confirmed, CosmosConfirmationPolicy},
     events::EurekaEventWithHeight,
+    network,
+    relay_scope::{RelayScope, RelayStage},
+    relayer_fees::{self, RelayerFeeConfig},
     utils::cosmos::{self},
 };
 
-use super::r#trait::TxBuilderService;
+use super::{
+    plan::{GasEstimates, TxPlan},
+    r#trait::TxBuilderService,
+};
 
 /// The `TxBuilder` produces txs to [`CosmosSdk`] based on events from [`CosmosSdk`].
 #[allow(dead_code)]
@@ -34,12 +42,21 @@ pub struct TxBuilder {
     pub target_tm_client: HttpClient,
     /// The signer address for the Cosmos messages.
     pub signer_address: String,
+    /// The confirmation policy for source events.
+    pub confirmation_policy: CosmosConfirmationPolicy,
+    /// The gas estimates used to plan relay transactions.
+    pub gas_estimates: GasEstimates,
+    /// The claiming of the relayer fees of the acknowledged and the timed out packets. The fees
+    /// are not claimed if unset.
+    pub relayer_fees: Option<RelayerFeeConfig>,
+    /// The clock the timeouts are computed with.
+    pub clock: Arc<dyn Clock>,
 }
 
 impl TxBuilder {
     /// Creates a new `TxBuilder`.
     #[must_use]
-    pub const fn new(
+    pub fn new(
         source_tm_client: HttpClient,
         target_tm_client: HttpClient,
         signer_address: String,
@@ -48,8 +65,70 @@ impl TxBuilder {
             source_tm_client,
             target_tm_client,
             signer_address,
+            confirmation_policy: CosmosConfirmationPolicy { height_lag: 0 },
+            gas_estimates: GasEstimates::COSMOS_SDK,
+            relayer_fees: None,
+            clock: Arc::new(SystemClock),
         }
     }
+
+    /// Set the clock the timeouts are computed with.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the confirmation policy for source events.
+    #[must_use]
+    pub const fn with_confirmation_policy(
+        mut self,
+        confirmation_policy: CosmosConfirmationPolicy,
+    ) -> Self {
+        self.confirmation_policy = confirmation_policy;
+        self
+    }
+
+    /// Set the gas estimates used to plan relay transactions.
+    #[must_use]
+    pub const fn with_gas_estimates(mut self, gas_estimates: GasEstimates) -> Self {
+        self.gas_estimates = gas_estimates;
+        self
+    }
+
+    /// Set the claiming of the relayer fees of the relayed packets.
+    #[must_use]
+    pub fn with_relayer_fees(mut self, relayer_fees: Option<RelayerFeeConfig>) -> Self {
+        self.relayer_fees = relayer_fees;
+        self
+    }
+
+    /// Checks that the tendermint light client on the target chain tracks the source chain.
+    /// # Errors
+    /// Returns an error if the client state or the chain ID cannot be fetched, or if the client
+    /// tracks another chain.
+    pub async fn ensure_client_network(&self, client_id: String) -> Result<()> {
+        let client_state = ClientState::decode(
+            self.target_tm_client
+                .client_state(client_id)
+                .await?
+                .value
+                .as_slice(),
+        )?;
+        let chain_id = self
+            .source_tm_client
+            .latest_block()
+            .await?
+            .block
+            .header
+            .chain_id;
+
+        network::ensure_chain_id_matches(
+            "tendermint light client",
+            &client_state.chain_id,
+            chain_id.as_str(),
+        )
+    }
 }
 
 #[async_trait::async_trait]
@@ -57,6 +136,7 @@ impl TxBuilderService<CosmosSdk, CosmosSdk> for TxBuilder {
     #[tracing::instrument(skip_all)]
     async fn relay_events(
         &self,
+        scope: &RelayScope,
         src_events: Vec<EurekaEventWithHeight>,
         target_events: Vec<EurekaEventWithHeight>,
         src_client_id: String,
@@ -72,14 +152,26 @@ impl TxBuilderService<CosmosSdk, CosmosSdk> for TxBuilder {
                 .as_slice(),
         )?;
 
-        let target_light_block = self.source_tm_client.get_light_block(None).await?;
+        let confirmed_height = self
+            .confirmation_policy
+            .confirmed_height(&self.source_tm_client)
+            .await?;
+        ensure_confirmed(src_events.iter().map(|e| e.height), confirmed_height)?;
+
+        let target_light_block = scope
+            .run(
+                RelayStage::HeaderBuild,
+                self.source_tm_client
+                    .get_light_block(Some(confirmed_height)),
+            )
+            .await?;
         let revision_height = target_light_block.height().value();
         let revision_number = client_state
             .latest_height
             .ok_or_else(|| anyhow::anyhow!("No latest height found"))?
             .revision_number;
 
-        let now_since_unix = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+        let now_since_unix = self.clock.now_since_unix()?;
 
         let mut timeout_msgs = cosmos::target_events_to_timeout_msgs(
             target_events,
@@ -105,23 +197,28 @@ impl TxBuilderService<CosmosSdk, CosmosSdk> for TxBuilder {
             revision_height,
         };
 
-        cosmos::inject_tendermint_proofs(
-            &mut recv_msgs,
-            &mut ack_msgs,
-            &mut timeout_msgs,
-            &self.source_tm_client,
-            &target_height,
-        )
-        .await?;
+        scope
+            .run(
+                RelayStage::ProofFetch,
+                cosmos::inject_tendermint_proofs(
+                    &mut recv_msgs,
+                    &mut ack_msgs,
+                    &mut timeout_msgs,
+                    &self.source_tm_client,
+                    &target_height,
+                ),
+            )
+            .await?;
 
-        let trusted_light_block = self
-            .source_tm_client
-            .get_light_block(Some(
-                client_state
-                    .latest_height
-                    .ok_or_else(|| anyhow::anyhow!("No latest height found"))?
-                    .revision_height,
-            ))
+        let trusted_height = client_state
+            .latest_height
+            .ok_or_else(|| anyhow::anyhow!("No latest height found"))?
+            .revision_height;
+        let trusted_light_block = scope
+            .run(
+                RelayStage::HeaderBuild,
+                self.source_tm_client.get_light_block(Some(trusted_height)),
+            )
             .await?;
         let proposed_header = target_light_block.into_header(&trusted_light_block);
         let update_msg = MsgUpdateClient {
@@ -130,10 +227,24 @@ impl TxBuilderService<CosmosSdk, CosmosSdk> for TxBuilder {
             signer: self.signer_address.clone(),
         };
 
+        let claim_msgs = match &self.relayer_fees {
+            Some(relayer_fees) => {
+                relayer_fees::fee_claim_msgs(
+                    &self.target_tm_client,
+                    relayer_fees,
+                    &self.signer_address,
+                    &relayer_fees::claimable_packets(&ack_msgs, &timeout_msgs),
+                )
+                .await
+            }
+            None => Vec::new(),
+        };
+
         let all_msgs = std::iter::once(Any::from_msg(&update_msg))
             .chain(timeout_msgs.into_iter().map(|m| Any::from_msg(&m)))
             .chain(recv_msgs.into_iter().map(|m| Any::from_msg(&m)))
             .chain(ack_msgs.into_iter().map(|m| Any::from_msg(&m)))
+            .chain(claim_msgs.into_iter().map(Ok))
             .collect::<Result<Vec<_>, _>>()?;
         if all_msgs.len() == 1 {
             // The update message is the only message.
@@ -217,4 +328,9 @@ impl TxBuilderService<CosmosSdk, CosmosSdk> for TxBuilder {
         }
         .encode_to_vec())
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn plan_tx(&self, tx: &[u8]) -> Result<TxPlan> {
+        cosmos::plan_tx_body(tx, &self.gas_estimates)
+    }
 }
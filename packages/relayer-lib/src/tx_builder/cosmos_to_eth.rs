@@ -1,7 +1,7 @@
 //! This module defines [`TxBuilder`] which is responsible for building transactions to be sent to
 //! the Ethereum chain from events received from the Cosmos SDK chain.
 
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use alloy::{
     primitives::{keccak256, Address},
@@ -24,17 +24,24 @@ use sp1_ics07_tendermint_prover::{
     prover::{Sp1Prover, SupportedZkAlgorithm},
 };
 use sp1_sdk::HashableKey;
-use tendermint_rpc::HttpClient;
+use tendermint_rpc::{Client, HttpClient};
 
 use sp1_prover::components::SP1ProverComponents;
 
 use crate::{
     chain::{CosmosSdk, EthEureka},
+    clock::{Clock, SystemClock},
+    confirmation::{ensure_confirmed, CosmosConfirmationPolicy},
     events::EurekaEventWithHeight,
+    network,
+    relay_scope::{RelayScope, RelayStage},
     utils::eth_eureka::{self, inject_sp1_proof},
 };
 
-use super::r#trait::TxBuilderService;
+use super::{
+    plan::{GasEstimates, TxPlan},
+    r#trait::TxBuilderService,
+};
 
 /// The `TxBuilder` produces txs to [`EthEureka`] based on events from [`CosmosSdk`].
 #[allow(dead_code)]
@@ -51,6 +58,12 @@ where
     pub sp1_prover: Sp1Prover<C>,
     /// The SP1 programs for ICS07 Tendermint.
     pub sp1_programs: SP1ICS07TendermintPrograms,
+    /// The confirmation policy for source events.
+    pub confirmation_policy: CosmosConfirmationPolicy,
+    /// The gas estimates used to plan relay transactions.
+    pub gas_estimates: GasEstimates,
+    /// The clock the timeouts are computed with.
+    pub clock: Arc<dyn Clock>,
 }
 
 impl<P, C> TxBuilder<P, C>
@@ -71,9 +84,36 @@ where
             tm_client,
             sp1_prover: sp1_prover.into(),
             sp1_programs,
+            confirmation_policy: CosmosConfirmationPolicy::default(),
+            gas_estimates: GasEstimates::ETHEREUM,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Set the clock the timeouts are computed with.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the confirmation policy for source events.
+    #[must_use]
+    pub const fn with_confirmation_policy(
+        mut self,
+        confirmation_policy: CosmosConfirmationPolicy,
+    ) -> Self {
+        self.confirmation_policy = confirmation_policy;
+        self
+    }
+
+    /// Set the gas estimates used to plan relay transactions.
+    #[must_use]
+    pub const fn with_gas_estimates(mut self, gas_estimates: GasEstimates) -> Self {
+        self.gas_estimates = gas_estimates;
+        self
+    }
+
     /// Get the client state for a given client ID.
     /// # Errors
     /// Returns an error if the client state cannot be retrieved.
@@ -88,6 +128,21 @@ where
         )
     }
 
+    /// Checks that the tendermint light client on Ethereum tracks the chain of the tendermint RPC.
+    /// # Errors
+    /// Returns an error if the client state or the chain ID cannot be fetched, or if the client
+    /// tracks another chain.
+    pub async fn ensure_client_network(&self, client_id: String) -> Result<()> {
+        let client_state = self.client_state(client_id).await?;
+        let chain_id = self.tm_client.latest_block().await?.block.header.chain_id;
+
+        network::ensure_chain_id_matches(
+            "tendermint light client",
+            &client_state.chainId,
+            chain_id.as_str(),
+        )
+    }
+
     /// Get the metadata for the transaction builder.
     pub fn metadata(&self) -> HashMap<String, String> {
         HashMap::from([
@@ -128,6 +183,7 @@ where
     #[tracing::instrument(skip_all)]
     async fn relay_events(
         &self,
+        scope: &RelayScope,
         src_events: Vec<EurekaEventWithHeight>,
         dest_events: Vec<EurekaEventWithHeight>,
         src_client_id: String,
@@ -135,9 +191,20 @@ where
         src_packet_seqs: Vec<u64>,
         dst_packet_seqs: Vec<u64>,
     ) -> Result<Vec<u8>> {
-        let now_since_unix = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+        let now_since_unix = self.clock.now_since_unix()?;
 
-        let latest_light_block = self.tm_client.get_light_block(None).await?;
+        let confirmed_height = self
+            .confirmation_policy
+            .confirmed_height(&self.tm_client)
+            .await?;
+        ensure_confirmed(src_events.iter().map(|e| e.height), confirmed_height)?;
+
+        let latest_light_block = scope
+            .run(
+                RelayStage::HeaderBuild,
+                self.tm_client.get_light_block(Some(confirmed_height)),
+            )
+            .await?;
         let revision_height = latest_light_block.height().value();
         let chain_id =
             ChainId::from_str(latest_light_block.signed_header.header.chain_id.as_str())?;
@@ -177,16 +244,20 @@ where
 
         let client_state = self.client_state(dst_client_id).await?;
 
-        inject_sp1_proof(
-            &self.sp1_prover,
-            &self.sp1_programs.update_client_and_membership,
-            &mut all_msgs,
-            &self.tm_client,
-            latest_light_block,
-            client_state,
-            now_since_unix.as_nanos(),
-        )
-        .await?;
+        scope
+            .run(
+                RelayStage::ProofFetch,
+                inject_sp1_proof(
+                    &self.sp1_prover,
+                    &self.sp1_programs.update_client_and_membership,
+                    &mut all_msgs,
+                    &self.tm_client,
+                    latest_light_block,
+                    client_state,
+                    now_since_unix.as_nanos(),
+                ),
+            )
+            .await?;
 
         let calls = all_msgs.into_iter().map(|msg| match msg {
             routerCalls::timeoutPacket(call) => call.abi_encode(),
@@ -279,4 +350,22 @@ where
         .calldata()
         .to_vec())
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn plan_tx(&self, tx: &[u8]) -> Result<TxPlan> {
+        let multicall = multicallCall::abi_decode(tx, true)?;
+        let msg_kinds = eth_eureka::multicall_msg_kinds(&multicall)?;
+
+        let simulated_gas = self
+            .ics26_router
+            .multicall(multicall.data)
+            .estimate_gas()
+            .await
+            .inspect_err(|e| tracing::debug!("Failed to simulate the relay tx: {e}"))
+            .ok();
+
+        Ok(TxPlan::new(msg_kinds, &self.gas_estimates)
+            .with_simulated_gas(simulated_gas)
+            .with_size(tx.len()))
+    }
 }
@@ -1,7 +1,11 @@
 //! This module defines [`TxBuilder`] which is responsible for building transactions to be sent to
 //! the Cosmos SDK chain from events received from Ethereum.
 
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap},
+    future::Future,
+    sync::{Arc, Mutex},
+};
 
 use alloy::{
     hex,
@@ -9,15 +13,23 @@ use alloy::{
     providers::Provider,
 };
 use anyhow::Result;
-use ethereum_apis::{beacon_api::client::BeaconApiClient, eth_api::client::EthApiClient};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ethereum_apis::{
+    beacon_api::client::BeaconApiClient,
+    eth_api::client::{pinned_block, EthApiClient},
+};
 use ethereum_light_client::{
     client_state::ClientState,
     consensus_state::ConsensusState,
     header::{AccountUpdate, ActiveSyncCommittee, Header},
+    update::update_consensus_state,
+    verify::verify_header,
 };
 use ethereum_types::{
     consensus::{
+        genesis::Genesis,
         light_client_header::{LightClientFinalityUpdate, LightClientUpdate},
+        spec::Spec,
         sync_committee::SyncCommittee,
     },
     execution::account_proof::AccountProof,
@@ -35,15 +47,67 @@ use ibc_proto_eureka::{
     },
 };
 use prost::Message;
+use serde::{Deserialize, Serialize};
 use tendermint_rpc::{Client, HttpClient};
 
-use super::r#trait::TxBuilderService;
+use super::{
+    plan::{GasEstimates, TxPlan},
+    r#trait::TxBuilderService,
+};
 use crate::{
     chain::{CosmosSdk, EthEureka},
+    checkpoint::{self, Checkpoint, CheckpointStrategy},
+    clock::{Clock, SystemClock},
+    confirmation::EthConfirmationPolicy,
     events::EurekaEventWithHeight,
-    utils::{cosmos, wait_for_condition},
+    misbehaviour::{self, MisbehaviourEvidence, MisbehaviourGuard},
+    network,
+    relay_scope::{RelayScope, RelayStage},
+    relayer_fees::{self, RelayerFeeConfig},
+    slot_timing::SlotTiming,
+    sync_committee::SyncCommitteeStore,
+    utils::{
+        bls::BlstVerifier,
+        cosmos::{self, RelayMsgs},
+        wait_for_condition, wait_for_condition_adaptive, ConditionPoll,
+    },
 };
 
+/// The default number of times a relay is rebuilt when a consensus state its proofs are verified
+/// against is pruned from the light client while it is built.
+pub const DEFAULT_STALE_PROOF_RETRIES: u32 = 2;
+
+/// The default number of epochs an [`UpdatePlan`] is reused for by the relays to its client.
+pub const DEFAULT_UPDATE_PLAN_EPOCHS: u64 = 1;
+
+/// The error returned when the consensus state the membership proofs of a relay are verified
+/// against is no longer stored by the light client, e.g. because it was pruned.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("the consensus state of client {client_id} at slot {slot} was pruned")]
+pub struct StaleProof {
+    /// The identifier of the light client.
+    pub client_id: String,
+    /// The slot of the missing consensus state.
+    pub slot: u64,
+}
+
+/// Returns whether an error, or the log of a rejected transaction, reports a missing consensus
+/// state, which makes the membership proofs verified against it stale.
+#[must_use]
+pub fn is_missing_consensus_state(err: &str) -> bool {
+    let err = err.to_lowercase();
+    err.contains("consensus state not found") || err.contains("no consensus state found")
+}
+
+/// An update of a catch-up of a stale Ethereum light client, covering a sync committee period.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatchUpUpdate {
+    /// The finalized slot the client is advanced to by the update.
+    pub finalized_slot: u64,
+    /// The update message.
+    pub msg: MsgUpdateClient,
+}
+
 /// The `TxBuilder` produces txs to [`CosmosSdk`] based on events from [`EthEureka`].
 pub struct TxBuilder<P>
 where
@@ -59,6 +123,167 @@ where
     pub tm_client: HttpClient,
     /// The signer address for the Cosmos messages.
     pub signer_address: String,
+    /// The options of the built relays.
+    pub config: TxBuilderConfig,
+    /// The latest update plan of every client.
+    update_plans: Mutex<HashMap<String, UpdatePlan>>,
+    /// The clock the timeouts are computed with.
+    pub clock: Arc<dyn Clock>,
+    /// The sync committees verified by the relayer, which spare fetching the update of the
+    /// previous period to learn the committee of the trusted period.
+    pub sync_committees: Arc<SyncCommitteeStore>,
+}
+
+/// The options of the relays built by a [`TxBuilder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxBuilderConfig {
+    /// The confirmation policy for source events.
+    pub confirmation_policy: EthConfirmationPolicy,
+    /// The gas estimates used to plan relay transactions.
+    pub gas_estimates: GasEstimates,
+    /// Whether to submit the intermediate light client updates within a sync committee period,
+    /// which keeps the consensus state timestamps of the client continuous.
+    pub intermediate_updates: bool,
+    /// The slot timing of the beacon chain, from which the waiting intervals are derived.
+    pub slot_timing: SlotTiming,
+    /// The strategy selecting the height the membership proofs are anchored at.
+    pub proof_height_strategy: ProofHeightStrategy,
+    /// Whether to zlib compress the client messages for the light clients that accept them.
+    pub compress_client_messages: bool,
+    /// The number of times a relay is rebuilt against the latest height of the client when a
+    /// consensus state its proofs are verified against is pruned.
+    pub stale_proof_retries: u32,
+    /// Whether the commitment storage slot is queried from the `ICS26Router` instead of derived
+    /// from its storage layout, and the light clients are checked against it.
+    pub query_commitment_slot: bool,
+    /// The number of epochs an update plan is reused for. The plans are not reused if zero.
+    pub update_plan_epochs: u64,
+    /// The address the light client updates are attributed to in the envelope of their client
+    /// messages. The updates are not attributed if unset.
+    pub update_relayer: Option<String>,
+    /// The claiming of the relayer fees of the acknowledged and the timed out packets. The fees
+    /// are not claimed if unset.
+    pub relayer_fees: Option<RelayerFeeConfig>,
+}
+
+impl Default for TxBuilderConfig {
+    fn default() -> Self {
+        Self {
+            confirmation_policy: EthConfirmationPolicy::default(),
+            gas_estimates: GasEstimates::COSMOS_SDK,
+            intermediate_updates: false,
+            slot_timing: SlotTiming::default(),
+            proof_height_strategy: ProofHeightStrategy::default(),
+            compress_client_messages: false,
+            stale_proof_retries: DEFAULT_STALE_PROOF_RETRIES,
+            query_commitment_slot: false,
+            update_plan_epochs: DEFAULT_UPDATE_PLAN_EPOCHS,
+            update_relayer: None,
+            relayer_fees: None,
+        }
+    }
+}
+
+/// The strategy selecting the consensus height the membership proofs of the relayed packets are
+/// anchored at, when the light client has to be updated to prove them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofHeightStrategy {
+    /// The proofs are anchored at the latest finalized slot, so that the next relays are less
+    /// likely to need another client update.
+    #[default]
+    Latest,
+    /// The proofs are anchored at the lowest selected update covering the packets, so that the
+    /// proof height only depends on the relayed packets.
+    Minimal,
+}
+
+impl ProofHeightStrategy {
+    /// Truncates the selected light client updates according to the strategy. With
+    /// [`Self::Minimal`], the updates after the first one covering `min_block_number` are dropped.
+    #[must_use]
+    pub fn apply<T>(
+        self,
+        mut selected: Vec<(T, LightClientUpdate)>,
+        min_block_number: u64,
+    ) -> Vec<(T, LightClientUpdate)> {
+        if self == Self::Minimal {
+            if let Some(covering) = selected.iter().position(|(_, update)| {
+                update.finalized_header.execution.block_number >= min_block_number
+            }) {
+                selected.truncate(covering + 1);
+            }
+        }
+        selected
+    }
+}
+
+/// The light client headers selected to update a client, and the slot the membership proofs of
+/// the relayed packets are anchored at once they are applied.
+///
+/// Selecting the headers queries the beacon chain for the finality and the sync committee
+/// updates, and the execution chain for the account proofs. A plan is therefore kept and reused
+/// by the next relays to the same client within its validity window, as long as the client was
+/// not updated in between and the plan covers their packets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdatePlan {
+    /// The latest slot of the client the headers are applied on top of.
+    pub trusted_slot: u64,
+    /// The headers updating the client, applied in order.
+    pub headers: Vec<Header>,
+    /// The finalized slot of the last header, or the trusted slot without headers.
+    pub proof_slot: u64,
+    /// The timestamp until which the plan is reused, in seconds.
+    pub valid_until: u64,
+}
+
+impl UpdatePlan {
+    /// Creates the plan of the headers applied on top of `trusted_slot`.
+    #[must_use]
+    pub fn new(trusted_slot: u64, headers: Vec<Header>, valid_until: u64) -> Self {
+        let proof_slot = headers.last().map_or(trusted_slot, |header| {
+            header.consensus_update.finalized_header.beacon.slot
+        });
+        Self {
+            trusted_slot,
+            headers,
+            proof_slot,
+            valid_until,
+        }
+    }
+
+    /// Returns whether the plan can be reused at `now` by a relay to a client at `trusted_slot`
+    /// whose packets need the execution block `min_block_number`.
+    ///
+    /// With [`ProofHeightStrategy::Minimal`], the plan is only reused if its last header is the
+    /// first one covering the packets, so that the proof height still only depends on them.
+    #[must_use]
+    pub fn is_reusable(
+        &self,
+        trusted_slot: u64,
+        min_block_number: u64,
+        proof_height_strategy: ProofHeightStrategy,
+        now: u64,
+    ) -> bool {
+        let block_number = |header: &Header| {
+            header
+                .consensus_update
+                .finalized_header
+                .execution
+                .block_number
+        };
+        let covers = self
+            .headers
+            .last()
+            .is_some_and(|header| block_number(header) >= min_block_number);
+        let first_covering = proof_height_strategy == ProofHeightStrategy::Latest
+            || !self
+                .headers
+                .iter()
+                .nth_back(1)
+                .is_some_and(|header| block_number(header) >= min_block_number);
+        self.trusted_slot == trusted_slot && now < self.valid_until && covers && first_covering
+    }
 }
 
 /// The `MockTxBuilder` produces txs to [`CosmosSdk`] based on events from [`EthEureka`]
@@ -70,19 +295,23 @@ pub struct MockTxBuilder<P: Provider + Clone> {
     pub ics26_router: routerInstance<(), P>,
     /// The signer address for the Cosmos messages.
     pub signer_address: String,
+    /// The clock the timeouts are computed with.
+    pub clock: Arc<dyn Clock>,
 }
 
 impl<P> TxBuilder<P>
 where
     P: Provider + Clone,
 {
-    /// Create a new [`TxBuilder`] instance.
+    /// Create a new [`TxBuilder`] instance, building the relays with the options of `config`.
+    #[must_use]
     pub fn new(
         ics26_address: Address,
         provider: P,
         beacon_api_url: String,
         tm_client: HttpClient,
         signer_address: String,
+        config: TxBuilderConfig,
     ) -> Self {
         Self {
             eth_client: EthApiClient::new(provider.clone()),
@@ -90,7 +319,50 @@ where
             ics26_router: routerInstance::new(ics26_address, provider),
             tm_client,
             signer_address,
+            config,
+            update_plans: Mutex::default(),
+            clock: Arc::new(SystemClock),
+            sync_committees: Arc::default(),
+        }
+    }
+
+    /// Set the clock the timeouts are computed with.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the store of the verified sync committees, e.g. to share it between tx builders.
+    #[must_use]
+    pub fn with_sync_committees(mut self, sync_committees: Arc<SyncCommitteeStore>) -> Self {
+        self.sync_committees = sync_committees;
+        self
+    }
+
+    /// Set the Beacon API client, e.g. to use a custom HTTP client.
+    #[must_use]
+    pub fn with_beacon_api_client(mut self, beacon_api_client: BeaconApiClient) -> Self {
+        self.beacon_api_client = beacon_api_client;
+        self
+    }
+
+    /// Returns the storage slot of the commitments of the `ICS26Router`, which is queried from the
+    /// router if `query_commitment_slot` is set, and derived from its storage layout otherwise.
+    /// # Errors
+    /// Returns an error if the router cannot be queried.
+    pub async fn ibc_commitment_slot(&self) -> Result<U256> {
+        if !self.config.query_commitment_slot {
+            return Ok(U256::from_be_slice(&ICS26_IBC_STORAGE_SLOT));
         }
+
+        let slot = self
+            .ics26_router
+            .getCommitmentStorageSlot()
+            .call()
+            .await?
+            ._0;
+        Ok(U256::from_be_bytes(slot.0))
     }
 
     /// Fetch the Ethereum client state from the light client on cosmos.
@@ -102,20 +374,202 @@ where
         Ok(serde_json::from_slice(&wasm_client_state.data)?)
     }
 
-    async fn get_sync_commitee_for_finalized_slot(
+    /// Fetch the Ethereum consensus state at the given revision number and slot from the light
+    /// client on cosmos.
+    /// # Errors
+    /// Returns an error if the consensus state cannot be fetched or decoded.
+    pub async fn ethereum_consensus_state(
         &self,
-        finalized_slot: u64,
-    ) -> Result<SyncCommittee> {
-        let block_root = self
+        client_id: String,
+        revision_number: u64,
+        slot: u64,
+    ) -> Result<ConsensusState> {
+        let wasm_consensus_state_any = self
+            .tm_client
+            .consensus_state(client_id, revision_number, slot)
+            .await?;
+        let wasm_consensus_state =
+            WasmConsensusState::decode(wasm_consensus_state_any.value.as_slice())?;
+        Ok(serde_json::from_slice(&wasm_consensus_state.data)?)
+    }
+
+    /// Fetch the consensus state the membership proofs of a relay are verified against.
+    /// # Errors
+    /// Returns a [`StaleProof`] error if the light client no longer stores the consensus state, and
+    /// an error if it cannot be fetched or decoded.
+    async fn proof_consensus_state(
+        &self,
+        client_id: &str,
+        revision_number: u64,
+        slot: u64,
+    ) -> Result<ConsensusState> {
+        self.ethereum_consensus_state(client_id.to_string(), revision_number, slot)
+            .await
+            .map_err(|err| {
+                if is_missing_consensus_state(&err.to_string()) {
+                    StaleProof {
+                        client_id: client_id.to_string(),
+                        slot,
+                    }
+                    .into()
+                } else {
+                    err
+                }
+            })
+    }
+
+    /// Checks that the Ethereum light client on cosmos tracks the network of the execution RPC and
+    /// the Beacon API, with the slot timing of the beacon chain, and the commitment slot declared
+    /// by the router if `query_commitment_slot` is set.
+    /// # Errors
+    /// Returns an error if the networks cannot be queried, or if the client tracks another network.
+    pub async fn ensure_client_network(&self, client_id: String) -> Result<()> {
+        let client_state = self.ethereum_client_state(client_id).await?;
+        let chain_id = self.ics26_router.provider().get_chain_id().await?;
+        let genesis_validators_root = self
             .beacon_api_client
-            .beacon_block_root(&format!("{finalized_slot}"))
+            .genesis()
+            .await?
+            .data
+            .genesis_validators_root;
+
+        network::ensure_ethereum_client_matches(&client_state, chain_id, genesis_validators_root)?;
+        if self.config.query_commitment_slot {
+            network::ensure_commitment_slot_matches(
+                &client_state,
+                self.ibc_commitment_slot().await?,
+            )?;
+        }
+        self.config.slot_timing.ensure_client_matches(&client_state)
+    }
+
+    /// Derives a fresh Ethereum client state at `revision_number` and consensus state from the
+    /// light client bootstrap of the latest finalized beacon block.
+    /// # Errors
+    /// Returns an error if the bootstrap or any of the required proofs cannot be fetched.
+    pub async fn bootstrap_ethereum_states(
+        &self,
+        revision_number: u64,
+    ) -> Result<(ClientState, ConsensusState)> {
+        let checkpoint = CheckpointStrategy::LatestFinalized
+            .resolve(&self.beacon_api_client)
             .await?;
-        let light_client_bootstrap = self
+        self.bootstrap_ethereum_states_at(&checkpoint, revision_number)
+            .await
+    }
+
+    /// Selects a checkpoint with the given strategy and checks it against the Beacon API of the
+    /// relayer and the additional `sources`.
+    /// # Errors
+    /// Returns an error if the checkpoint cannot be selected, or if any source disagrees with it.
+    pub async fn select_checkpoint(
+        &self,
+        strategy: &CheckpointStrategy,
+        sources: &[BeaconApiClient],
+    ) -> Result<Checkpoint> {
+        let checkpoint = strategy.resolve(&self.beacon_api_client).await?;
+        checkpoint::ensure_checkpoint_agreement(
+            &checkpoint,
+            std::slice::from_ref(&self.beacon_api_client),
+        )
+        .await?;
+        checkpoint::ensure_checkpoint_agreement(&checkpoint, sources).await?;
+
+        tracing::info!(
+            "Selected checkpoint at slot {} with block root {}, verified against {} additional sources",
+            checkpoint.slot,
+            checkpoint.block_root,
+            sources.len()
+        );
+        Ok(checkpoint)
+    }
+
+    /// Derives a fresh Ethereum client state at `revision_number` and consensus state from the
+    /// light client bootstrap of the checkpoint beacon block.
+    /// # Errors
+    /// Returns an error if the bootstrap or any of the required proofs cannot be fetched, or if the
+    /// bootstrap does not match the checkpoint.
+    pub async fn bootstrap_ethereum_states_at(
+        &self,
+        checkpoint: &Checkpoint,
+        revision_number: u64,
+    ) -> Result<(ClientState, ConsensusState)> {
+        let genesis = self.beacon_api_client.genesis().await?.data;
+        let spec = self.beacon_api_client.spec().await?.data;
+        let beacon_block = self
+            .beacon_api_client
+            .beacon_block(&checkpoint.block_root)
+            .await?
+            .message;
+
+        tracing::info!("Bootstrapping client at slot: {}", beacon_block.slot);
+
+        let bootstrap = self
             .beacon_api_client
-            .light_client_bootstrap(&block_root)
+            .light_client_bootstrap(&checkpoint.block_root)
             .await?
             .data;
-        Ok(light_client_bootstrap.current_sync_committee)
+
+        if bootstrap.header.beacon.slot != checkpoint.slot || beacon_block.slot != checkpoint.slot {
+            anyhow::bail!(
+                "Light client bootstrap slot {} does not match checkpoint slot {}",
+                bootstrap.header.beacon.slot,
+                checkpoint.slot
+            );
+        }
+
+        if bootstrap.header.execution.block_number
+            != beacon_block.body.execution_payload.block_number
+        {
+            anyhow::bail!(
+                "Light client bootstrap block number does not match execution block number"
+            );
+        }
+
+        let eth_client_state = ClientState {
+            chain_id: self.ics26_router.provider().get_chain_id().await?,
+            latest_slot: bootstrap.header.beacon.slot,
+            is_frozen: false,
+            ibc_commitment_slot: self.ibc_commitment_slot().await?,
+            ibc_contract_address: *self.ics26_router.address(),
+            latest_execution_block_number: bootstrap.header.execution.block_number,
+            revision_number,
+            ..beacon_client_state(&genesis, &spec)?
+        };
+
+        let contract_proof = self
+            .eth_client
+            .get_proof(
+                &self.ics26_router.address().to_string(),
+                vec![],
+                pinned_block(bootstrap.header.execution.block_hash),
+            )
+            .await?;
+
+        let latest_period =
+            eth_client_state.compute_sync_committee_period_at_slot(eth_client_state.latest_slot);
+        let next_sync_committee = self
+            .beacon_api_client
+            .light_client_updates(latest_period, 1)
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("No light client updates found for the latest period"))?
+            .data
+            .next_sync_committee
+            .ok_or_else(|| {
+                anyhow::anyhow!("No next sync committee found in the light client update")
+            })?;
+
+        let eth_consensus_state = ConsensusState {
+            slot: eth_client_state.latest_slot,
+            state_root: bootstrap.header.execution.state_root,
+            storage_root: contract_proof.storage_hash,
+            timestamp: bootstrap.header.execution.timestamp,
+            current_sync_committee: bootstrap.current_sync_committee.aggregate_pubkey,
+            next_sync_committee: Some(next_sync_committee.aggregate_pubkey),
+        };
+
+        Ok((eth_client_state, eth_consensus_state))
     }
 
     /// Fetches light client updates from the Beacon API for synchronizing between the trusted and target periods.
@@ -124,6 +578,10 @@ where
     /// then retrieves all light client updates needed to advance the light client from the trusted period
     /// to the target period. These updates contain validator signatures and sync committee data needed
     /// to verify the consensus transition.
+    ///
+    /// The update for the period preceding the trusted period is included as well, since its
+    /// `next_sync_committee` is the committee active in the trusted period, unless that committee
+    /// is already in the sync committee store.
     async fn get_light_client_updates(
         &self,
         client_state: &ClientState,
@@ -135,53 +593,87 @@ where
         let target_period = client_state
             .compute_sync_committee_period_at_slot(finality_update.finalized_header.beacon.slot);
 
+        let start_period = if self
+            .sync_committees
+            .get(client_state.genesis_validators_root, trusted_period)
+            .is_some()
+        {
+            trusted_period
+        } else {
+            trusted_period.saturating_sub(1)
+        };
+
         tracing::debug!(
             "Getting light client updates from period {} to {}",
-            trusted_period,
-            target_period - trusted_period + 1
+            start_period,
+            target_period
         );
         Ok(self
             .beacon_api_client
-            .light_client_updates(trusted_period, target_period - trusted_period + 1)
+            .light_client_updates(start_period, target_period - start_period + 1)
             .await?
             .into_iter()
             .map(|resp| resp.data)
             .collect::<Vec<_>>())
     }
 
+    /// Waits until the execution block `target_block_number` is finalized.
+    ///
+    /// The target block is expected to be finalized [`crate::slot_timing::FINALITY_DEPTH_EPOCHS`]
+    /// after the epoch following its slot, so the beacon chain is only polled again once that
+    /// time has passed, and then every slot.
     async fn wait_for_light_client_readiness(&self, target_block_number: u64) -> Result<()> {
-        // Wait until we find a finality update that meets our criteria and capture it
-        // This way we avoid making an extra call at the end
-        wait_for_condition(
-            Duration::from_secs(45 * 60),
-            Duration::from_secs(10),
+        wait_for_condition_adaptive(
+            self.clock.as_ref(),
+            self.config.slot_timing.finality_timeout(),
             || async {
-                tracing::debug!(
-                    "Waiting for finality beyond target block number: {}",
-                    target_block_number
-                );
-
                 let finality_update = self.beacon_api_client.finality_update().await?.data;
-                if finality_update.finalized_header.execution.block_number < target_block_number {
+                let finalized_header = &finality_update.finalized_header;
+                let finalized_block_number = finalized_header.execution.block_number;
+                if finalized_block_number >= target_block_number {
                     tracing::info!(
-                        "Waiting for finality: current finality execution block number: {}, Target execution block number: {}",
-                        finality_update.finalized_header.execution.block_number,
-                        target_block_number
+                        "Finality update found at execution block number: {}",
+                        finalized_block_number
                     );
-                    return Ok(false);
+                    return Ok(ConditionPoll::Met);
                 }
 
-                // Return the update itself when the condition is met
+                // Every slot after the finalized one is assumed to have a block, so missed slots
+                // only make the target block expected earlier than it is.
+                let target_slot = finalized_header
+                    .beacon
+                    .slot
+                    .saturating_add(target_block_number - finalized_block_number);
+                let time_to_finality = self
+                    .config
+                    .slot_timing
+                    .time_to_finality(target_slot, finality_update.signature_slot)
+                    .max(self.config.slot_timing.poll_interval());
                 tracing::info!(
-                    "Finality update found at execution block number: {}",
-                    finality_update.finalized_header.execution.block_number
+                    "Waiting for finality: current finality execution block number: {}, Target execution block number: {}, expected in {} seconds",
+                    finalized_block_number,
+                    target_block_number,
+                    time_to_finality.as_secs()
                 );
-                Ok(true)
+                Ok(ConditionPoll::RetryAfter(time_to_finality))
             },
         )
-        .await?;
+        .await
+    }
 
-        Ok(())
+    /// Waits until the execution block `block_number` is confirmed under the confirmation policy.
+    async fn wait_for_confirmation(&self, block_number: u64) -> Result<()> {
+        wait_for_confirmation(
+            self.clock.as_ref(),
+            &self.config.slot_timing,
+            block_number,
+            || {
+                self.config
+                    .confirmation_policy
+                    .confirmed_block_number(self.ics26_router.provider())
+            },
+        )
+        .await
     }
 
     async fn light_client_update_to_header(
@@ -195,13 +687,21 @@ where
             update.finalized_header.beacon.slot,
         );
 
-        let block_hex = format!("0x{:x}", update.finalized_header.execution.block_number);
+        let execution = &update.finalized_header.execution;
         let ibc_contract_address: String = ethereum_client_state.ibc_contract_address.to_string();
 
-        tracing::debug!("Getting account proof for execution block {}", block_hex);
+        tracing::debug!(
+            "Getting account proof for execution block {} ({})",
+            execution.block_number,
+            execution.block_hash
+        );
         let proof = self
             .eth_client
-            .get_proof(&ibc_contract_address, vec![], block_hex)
+            .get_proof(
+                &ibc_contract_address,
+                vec![],
+                pinned_block(execution.block_hash),
+            )
             .await?;
 
         let account_update = AccountUpdate {
@@ -218,144 +718,511 @@ where
         })
     }
 
-    #[tracing::instrument(skip_all)]
-    async fn get_update_headers(&self, ethereum_client_state: &ClientState) -> Result<Vec<Header>> {
-        let finality_update = self.beacon_api_client.finality_update().await?.data;
+    /// Builds the `MsgUpdateClient`s needed to advance the Ethereum light client `client_id` to
+    /// at least the execution block `target_block`, independently of any packet relaying.
+    ///
+    /// Waits until `target_block` is finalized and until the Cosmos SDK chain has caught up with
+    /// the signature slot of the last update, so the returned messages can be submitted as is.
+    /// An empty list is returned if the client is already at or beyond `target_block`.
+    /// # Errors
+    /// Returns an error if the updates cannot be fetched, fail local verification or cannot be
+    /// encoded.
+    #[tracing::instrument(skip(self))]
+    pub async fn build_update_client_msgs(
+        &self,
+        client_id: String,
+        target_block: u64,
+    ) -> Result<Vec<MsgUpdateClient>> {
+        let ethereum_client_state = self.ethereum_client_state(client_id.clone()).await?;
+        if target_block <= ethereum_client_state.latest_execution_block_number {
+            tracing::info!(
+                "Client {} is already at execution block {}, no update needed",
+                client_id,
+                ethereum_client_state.latest_execution_block_number
+            );
+            return Ok(vec![]);
+        }
 
-        let mut headers = vec![];
+        self.wait_for_light_client_readiness(target_block).await?;
 
-        let light_client_updates = self
-            .get_light_client_updates(ethereum_client_state, finality_update.clone())
+        let now = self.clock.now_since_unix()?;
+        let (ethereum_client_state, headers) = self
+            .verified_update_headers(client_id.clone(), now.as_secs(), target_block)
             .await?;
 
-        let mut latest_trusted_slot = ethereum_client_state.latest_slot;
-        let mut latest_period =
-            ethereum_client_state.compute_sync_committee_period_at_slot(latest_trusted_slot);
-        tracing::debug!("Latest trusted sync committee period: {}", latest_period);
-
-        for update in &light_client_updates {
-            tracing::debug!(
-                "Processing light client update for finalized slot {} with trusted slot {}",
-                update.finalized_header.beacon.slot,
-                latest_trusted_slot
-            );
+        self.wait_for_signature_slot(&ethereum_client_state, &headers)
+            .await?;
 
-            if update.finalized_header.beacon.slot <= latest_trusted_slot {
-                tracing::debug!(
-                    "Skipping unnecessary update for slot {}",
-                    update.finalized_header.beacon.slot
-                );
-                continue;
-            }
+        self.update_client_msgs(&client_id, &ethereum_client_state, &headers)
+    }
 
-            // TODO: Not sure
-            let update_period = ethereum_client_state
-                .compute_sync_committee_period_at_slot(update.finalized_header.beacon.slot);
-            if update_period == latest_period {
-                tracing::debug!(
-                    "Skipping header with same sync committee period for slot {}",
-                    update.finalized_header.beacon.slot
-                );
-                continue;
-            }
+    /// Builds the `MsgUpdateClient` submitting misbehaviour evidence against the Ethereum light
+    /// client `client_id`, which freezes the client.
+    ///
+    /// The evidence is verified locally against the trusted consensus state of the client, and
+    /// must be corroborated by the `guard`, so that a faulty beacon node cannot get a healthy
+    /// client frozen.
+    /// # Errors
+    /// Returns an error if the client states cannot be fetched, if the evidence fails local
+    /// verification, or if it is not corroborated.
+    #[tracing::instrument(skip(self, evidence, guard))]
+    pub async fn build_misbehaviour_msg(
+        &self,
+        client_id: String,
+        evidence: &MisbehaviourEvidence,
+        guard: &MisbehaviourGuard,
+    ) -> Result<MsgUpdateClient> {
+        let ethereum_client_state = self.ethereum_client_state(client_id.clone()).await?;
+        let ethereum_consensus_state = self
+            .ethereum_consensus_state(
+                client_id.clone(),
+                ethereum_client_state.revision_number,
+                evidence.trusted_slot,
+            )
+            .await?;
 
-            let previous_next_sync_committee = self
-                .get_sync_commitee_for_finalized_slot(update.finalized_header.beacon.slot)
-                .await?;
+        let now = self.clock.now_since_unix()?;
+        misbehaviour::verify_evidence(
+            &ethereum_client_state,
+            &ethereum_consensus_state,
+            evidence,
+            now.as_secs(),
+        )?;
+        guard.ensure_corroborated(evidence).await?;
+
+        tracing::warn!(
+            "Submitting misbehaviour evidence at slot {} against client {client_id}",
+            evidence.attested_slot()
+        );
 
-            let active_sync_committee = ActiveSyncCommittee::Next(previous_next_sync_committee);
-            let header = self
-                .light_client_update_to_header(
-                    ethereum_client_state,
-                    active_sync_committee.clone(),
-                    update.clone(),
-                )
-                .await?;
-            tracing::debug!(
-                "Added header for slot from light client updates {}
-                Header: {:?}",
-                update.finalized_header.beacon.slot,
-                header
-            );
-            headers.push(header);
-            latest_period = update_period;
-            latest_trusted_slot = update.finalized_header.beacon.slot;
-        }
+        let evidence_bz = serde_json::to_vec(evidence)?;
+        Ok(MsgUpdateClient {
+            client_id,
+            client_message: Some(Any::from_msg(&ClientMessage { data: evidence_bz })?),
+            signer: self.signer_address.clone(),
+        })
+    }
 
-        // If the latest header is earlier than the finality update, we need to add a header for the finality update.
-        if headers.last().is_none_or(|last_header| {
-            last_header.consensus_update.finalized_header.beacon.slot
-                < finality_update.finalized_header.beacon.slot
-        }) {
-            let finality_update_sync_committee = self
-                .get_sync_commitee_for_finalized_slot(finality_update.attested_header.beacon.slot)
-                .await?;
-            // TODO: Add asserts to make sure they are in the correct period
-            let active_sync_committee =
-                ActiveSyncCommittee::Current(finality_update_sync_committee.clone());
+    /// Builds the `MsgUpdateClient`s that backfill the consensus states of the Ethereum light
+    /// client `client_id`, from its latest slot up to the latest finalized slot, with a consensus
+    /// state for every sync committee period in between.
+    ///
+    /// This is meant for a client freshly created at an older checkpoint, so that packets sent
+    /// shortly before its creation can still be proven. The headers form a linked chain, each
+    /// trusting the state produced by the previous one, and are submitted in header batches of at
+    /// most `max_batch_size` headers, which the light client verifies and applies in order.
+    /// # Errors
+    /// Returns an error if the updates cannot be fetched, fail local verification or cannot be
+    /// encoded.
+    #[tracing::instrument(skip(self))]
+    pub async fn build_backfill_msgs(
+        &self,
+        client_id: String,
+        max_batch_size: usize,
+    ) -> Result<Vec<MsgUpdateClient>> {
+        anyhow::ensure!(max_batch_size > 0, "the header batch size must be positive");
+
+        let (ethereum_client_state, headers) =
+            self.verified_period_headers(client_id.clone()).await?;
+        self.wait_for_signature_slot(&ethereum_client_state, &headers)
+            .await?;
 
-            let header = self
-                .light_client_update_to_header(
-                    ethereum_client_state,
-                    active_sync_committee.clone(),
-                    finality_update.clone().into(),
-                )
-                .await?;
-            tracing::debug!(
-                "Added header for slot from finality update {}: {}",
-                finality_update.finalized_header.beacon.slot,
-                serde_json::to_string(&header)?
-            );
-            headers.push(header);
-        }
+        tracing::info!(
+            "Backfilling {} consensus states of client {} from slot {}",
+            headers.len(),
+            client_id,
+            ethereum_client_state.latest_slot
+        );
 
-        Ok(headers)
+        headers
+            .chunks(max_batch_size)
+            .map(|batch| self.header_batch_msg(&client_id, &ethereum_client_state, batch))
+            .collect()
     }
-}
 
-/// The key for the checksum hex in the parameters map.
-const CHECKSUM_HEX: &str = "checksum_hex";
-
-#[async_trait::async_trait]
-impl<P> TxBuilderService<EthEureka, CosmosSdk> for TxBuilder<P>
-where
-    P: Provider + Clone,
-{
-    #[tracing::instrument(skip_all)]
-    async fn relay_events(
+    /// Builds the next `MsgUpdateClient`s that advance the stale Ethereum light client
+    /// `client_id` towards the latest finalized slot, one sync committee period per update.
+    ///
+    /// Unlike [`Self::build_backfill_msgs`], the updates are meant to be submitted in separate
+    /// transactions, so that a client lagging many periods behind is caught up with transactions
+    /// small enough to fit in a block. At most `max_updates` updates are built, the next ones
+    /// being built from the client state once these are applied.
+    /// # Errors
+    /// Returns an error if the updates cannot be fetched, fail local verification or cannot be
+    /// encoded.
+    #[tracing::instrument(skip(self))]
+    pub async fn build_catch_up_msgs(
         &self,
-        src_events: Vec<EurekaEventWithHeight>,
-        dest_events: Vec<EurekaEventWithHeight>,
-        src_client_id: String,
-        dst_client_id: String,
-        src_packet_seqs: Vec<u64>,
-        dst_packet_seqs: Vec<u64>,
-    ) -> Result<Vec<u8>> {
-        let now_since_unix = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
-        let mut ethereum_client_state = self.ethereum_client_state(dst_client_id.clone()).await?;
-        let latest_block_number = self.eth_client.get_block_number().await?;
-
-        let max_src_block_number = src_events.iter().map(|e| e.height).max();
-
-        let mut timeout_msgs = cosmos::target_events_to_timeout_msgs(
-            dest_events,
-            &src_client_id,
-            &dst_client_id,
-            &dst_packet_seqs,
-            &self.signer_address,
-            now_since_unix.as_secs(),
-        );
+        client_id: String,
+        max_updates: usize,
+    ) -> Result<Vec<CatchUpUpdate>> {
+        let (ethereum_client_state, mut headers) =
+            self.verified_period_headers(client_id.clone()).await?;
+        headers.truncate(max_updates);
+        self.wait_for_signature_slot(&ethereum_client_state, &headers)
+            .await?;
 
-        let (mut recv_msgs, mut ack_msgs) = cosmos::src_events_to_recv_and_ack_msgs(
-            src_events,
-            &src_client_id,
-            &dst_client_id,
-            &src_packet_seqs,
-            &dst_packet_seqs,
-            &self.signer_address,
-            now_since_unix.as_secs(),
+        tracing::info!(
+            "Catching up client {} from slot {} with {} updates",
+            client_id,
+            ethereum_client_state.latest_slot,
+            headers.len()
         );
 
+        headers
+            .iter()
+            .map(|header| {
+                Ok(CatchUpUpdate {
+                    finalized_slot: header.consensus_update.finalized_header.beacon.slot,
+                    msg: self.header_batch_msg(
+                        &client_id,
+                        &ethereum_client_state,
+                        std::slice::from_ref(header),
+                    )?,
+                })
+            })
+            .collect()
+    }
+
+    /// Fetches the current client state of `client_id` and the headers of every sync committee
+    /// period up to the latest finalized slot, verified locally as a linked chain.
+    async fn verified_period_headers(
+        &self,
+        client_id: String,
+    ) -> Result<(ClientState, Vec<Header>)> {
+        let ethereum_client_state = self.ethereum_client_state(client_id.clone()).await?;
+        let ethereum_consensus_state = self
+            .ethereum_consensus_state(
+                client_id,
+                ethereum_client_state.revision_number,
+                ethereum_client_state.latest_slot,
+            )
+            .await?;
+        let headers = self
+            .get_update_headers(
+                &ethereum_client_state,
+                &ethereum_consensus_state,
+                ethereum_client_state.latest_execution_block_number + 1,
+                true,
+                ProofHeightStrategy::Latest,
+            )
+            .await?;
+
+        let now = self.clock.now_since_unix()?;
+        verify_update_headers(
+            ethereum_client_state.clone(),
+            ethereum_consensus_state,
+            &headers,
+            now.as_secs(),
+        )?;
+        self.sync_committees
+            .record_verified_headers(&ethereum_client_state, &headers);
+
+        Ok((ethereum_client_state, headers))
+    }
+
+    /// Builds the `MsgUpdateClient` carrying a linked header batch.
+    fn header_batch_msg(
+        &self,
+        client_id: &str,
+        ethereum_client_state: &ClientState,
+        batch: &[Header],
+    ) -> Result<MsgUpdateClient> {
+        let client_msg = Any::from_msg(&ClientMessage {
+            data: self.encode_client_message(
+                ethereum_client_state,
+                header_batch_client_message(batch, self.config.update_relayer.as_deref())?,
+            ),
+        })?;
+        Ok(MsgUpdateClient {
+            client_id: client_id.to_string(),
+            client_message: Some(client_msg),
+            signer: self.signer_address.clone(),
+        })
+    }
+
+    /// Fetches the current client state of `client_id` and the headers to update it to the
+    /// latest finalized slot, verified locally against the trusted consensus state.
+    /// The last header covers at least the execution block `min_block_number`.
+    async fn verified_update_headers(
+        &self,
+        client_id: String,
+        current_timestamp: u64,
+        min_block_number: u64,
+    ) -> Result<(ClientState, Vec<Header>)> {
+        let ethereum_client_state = self.ethereum_client_state(client_id.clone()).await?;
+        let ethereum_consensus_state = self
+            .ethereum_consensus_state(
+                client_id,
+                ethereum_client_state.revision_number,
+                ethereum_client_state.latest_slot,
+            )
+            .await?;
+        let headers = self
+            .get_update_headers(
+                &ethereum_client_state,
+                &ethereum_consensus_state,
+                min_block_number,
+                self.config.intermediate_updates,
+                self.config.proof_height_strategy,
+            )
+            .await?;
+        verify_update_headers(
+            ethereum_client_state.clone(),
+            ethereum_consensus_state,
+            &headers,
+            current_timestamp,
+        )?;
+        self.sync_committees
+            .record_verified_headers(&ethereum_client_state, &headers);
+
+        Ok((ethereum_client_state, headers))
+    }
+
+    /// Returns the update plan of `client_id` reusable by a relay needing the execution block
+    /// `min_block_number`, if any.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    fn reusable_update_plan(
+        &self,
+        client_id: &str,
+        trusted_slot: u64,
+        min_block_number: u64,
+        now: u64,
+    ) -> Option<UpdatePlan> {
+        self.update_plans
+            .lock()
+            .unwrap()
+            .get(client_id)
+            .filter(|plan| {
+                plan.is_reusable(
+                    trusted_slot,
+                    min_block_number,
+                    self.config.proof_height_strategy,
+                    now,
+                )
+            })
+            .cloned()
+    }
+
+    /// Selects the headers updating `client_id` to cover the execution block `min_block_number`,
+    /// and keeps their plan for the next relays to the client.
+    /// # Panics
+    /// Panics if the lock is poisoned.
+    async fn new_update_plan(
+        &self,
+        client_id: String,
+        min_block_number: u64,
+    ) -> Result<(ClientState, UpdatePlan)> {
+        let now = self.clock.now_since_unix()?.as_secs();
+        let (ethereum_client_state, headers) = self
+            .verified_update_headers(client_id.clone(), now, min_block_number)
+            .await?;
+        let valid_until = now
+            + self
+                .config
+                .slot_timing
+                .epochs(self.config.update_plan_epochs)
+                .as_secs();
+        let plan = UpdatePlan::new(ethereum_client_state.latest_slot, headers, valid_until);
+
+        if self.config.update_plan_epochs > 0 {
+            self.update_plans
+                .lock()
+                .unwrap()
+                .insert(client_id, plan.clone());
+        }
+        Ok((ethereum_client_state, plan))
+    }
+
+    /// Wraps the headers into `MsgUpdateClient`s for `client_id`.
+    fn update_client_msgs(
+        &self,
+        client_id: &str,
+        ethereum_client_state: &ClientState,
+        headers: &[Header],
+    ) -> Result<Vec<MsgUpdateClient>> {
+        headers
+            .iter()
+            .map(|header| -> Result<MsgUpdateClient> {
+                // The attributed headers are wrapped in an envelope, the others are sent raw
+                let header_bz = match &self.config.update_relayer {
+                    Some(relayer) => enveloped_client_message(
+                        CLIENT_MESSAGE_TYPE_HEADER,
+                        &serde_json::to_vec(&header)?,
+                        Some(relayer),
+                    )?,
+                    None => serde_json::to_vec(&header)?,
+                };
+                let client_msg = Any::from_msg(&ClientMessage {
+                    data: self.encode_client_message(ethereum_client_state, header_bz),
+                })?;
+                Ok(MsgUpdateClient {
+                    client_id: client_id.to_string(),
+                    client_message: Some(client_msg),
+                    signer: self.signer_address.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Compresses the encoded client message if compression is enabled and accepted by the client.
+    fn encode_client_message(&self, ethereum_client_state: &ClientState, bz: Vec<u8>) -> Vec<u8> {
+        if self.config.compress_client_messages && ethereum_client_state.compressed_client_messages
+        {
+            compress_client_message(&bz)
+        } else {
+            bz
+        }
+    }
+
+    /// Waits until the slot computed from the latest block time of the Cosmos SDK chain is past
+    /// the signature slot of the last header, since the light client rejects updates from the
+    /// future.
+    async fn wait_for_signature_slot(
+        &self,
+        ethereum_client_state: &ClientState,
+        headers: &[Header],
+    ) -> Result<()> {
+        let Some(latest_signature_slot) = headers.last().map(|h| h.consensus_update.signature_slot)
+        else {
+            return Ok(());
+        };
+
+        wait_for_condition(
+            self.clock.as_ref(),
+            self.config.slot_timing.signature_slot_timeout(),
+            self.config.slot_timing.poll_interval(),
+            || async {
+                let latests_tm_block = self.tm_client.latest_block().await?;
+                let latest_onchain_timestamp = latests_tm_block.block.header.time.unix_timestamp();
+                let calculated_slot = ethereum_client_state
+                    .compute_slot_at_timestamp(latest_onchain_timestamp.try_into()?)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Failed to compute slot at timestamp {latest_onchain_timestamp}"
+                        )
+                    })?;
+                tracing::debug!(
+                    "Waiting for target chain to catch up to slot {}",
+                    calculated_slot
+                );
+                Ok(calculated_slot > latest_signature_slot)
+            },
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_update_headers(
+        &self,
+        ethereum_client_state: &ClientState,
+        ethereum_consensus_state: &ConsensusState,
+        min_block_number: u64,
+        intermediate_updates: bool,
+        proof_height_strategy: ProofHeightStrategy,
+    ) -> Result<Vec<Header>> {
+        let finality_update = self.beacon_api_client.finality_update().await?.data;
+
+        let light_client_updates = self
+            .get_light_client_updates(ethereum_client_state, finality_update.clone())
+            .await?;
+
+        let selected_updates = select_light_client_updates_with_committees(
+            ethereum_client_state,
+            ethereum_consensus_state.next_sync_committee.is_some(),
+            &light_client_updates,
+            &self
+                .sync_committees
+                .committees(ethereum_client_state.genesis_validators_root),
+            finality_update.into(),
+            min_block_number,
+            intermediate_updates,
+        )?;
+        let selected_updates = proof_height_strategy.apply(selected_updates, min_block_number);
+
+        let mut headers = Vec::with_capacity(selected_updates.len());
+        for (active_sync_committee, update) in selected_updates {
+            let finalized_slot = update.finalized_header.beacon.slot;
+            let header = self
+                .light_client_update_to_header(ethereum_client_state, active_sync_committee, update)
+                .await?;
+            tracing::debug!(
+                "Added header for finalized slot {}: {}",
+                finalized_slot,
+                serde_json::to_string(&header)?
+            );
+            headers.push(header);
+        }
+
+        Ok(headers)
+    }
+
+    /// Builds the relay transaction of [`TxBuilderService::relay_events`] once.
+    /// # Errors
+    /// Returns a [`StaleProof`] error if a consensus state the proofs are verified against was
+    /// pruned from the light client while the transaction was built.
+    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+    async fn build_relay_tx(
+        &self,
+        scope: &RelayScope,
+        src_events: Vec<EurekaEventWithHeight>,
+        dest_events: Vec<EurekaEventWithHeight>,
+        src_client_id: String,
+        dst_client_id: String,
+        src_packet_seqs: Vec<u64>,
+        dst_packet_seqs: Vec<u64>,
+    ) -> Result<Vec<u8>> {
+        let now_since_unix = self.clock.now_since_unix()?;
+        let mut ethereum_client_state = self.ethereum_client_state(dst_client_id.clone()).await?;
+
+        let has_events = !src_events.is_empty() || !dest_events.is_empty();
+        // Without any events, the client is updated to the latest confirmed block.
+        let latest_confirmed_block_number = if has_events {
+            None
+        } else {
+            Some(
+                self.config
+                    .confirmation_policy
+                    .confirmed_block_number(self.ics26_router.provider())
+                    .await?,
+            )
+        };
+        let max_event_block_number = src_events.iter().map(|e| e.height).max();
+        let trusted_slot = ethereum_client_state.latest_slot;
+
+        let max_src_block_number = src_events
+            .iter()
+            .map(|e| e.height)
+            .filter(|&height| height > ethereum_client_state.latest_execution_block_number)
+            .max();
+        // The transactions of the source events the client does not cover yet, whose inclusion is
+        // checked along with the proofs of their messages
+        let mut source_txs = src_events
+            .iter()
+            .filter(|e| e.height > ethereum_client_state.latest_execution_block_number)
+            .filter_map(|e| Some((e.tx_hash?, e.height)))
+            .collect::<Vec<_>>();
+        source_txs.sort_unstable();
+        source_txs.dedup();
+
+        let (ready_msgs, pending_msgs) = partition_relay_msgs(
+            &ethereum_client_state,
+            src_events,
+            dest_events,
+            &src_client_id,
+            &dst_client_id,
+            &src_packet_seqs,
+            &dst_packet_seqs,
+            &self.signer_address,
+            now_since_unix.as_secs(),
+        );
+        let RelayMsgs {
+            mut timeout_msgs,
+            mut recv_msgs,
+            mut ack_msgs,
+        } = pending_msgs;
+
         let max_timeout_slot = timeout_msgs
             .iter()
             .filter_map(|e| {
@@ -385,59 +1252,199 @@ where
 
         let minimum_block_number = max_src_block_number
             .into_iter()
-            .chain(max_timeout_block_number.into_iter())
+            .chain(max_timeout_block_number)
             .max()
-            .unwrap_or(latest_block_number);
+            .or(latest_confirmed_block_number);
 
         tracing::info!(
-            "Relaying events from Ethereum to Cosmos for client {}, target block number: {}, client state latest slot: {}",
+            "Relaying events from Ethereum to Cosmos for client {}, target block number: {:?}, client state latest slot: {}, events ready to relay: #{}",
             dst_client_id,
             minimum_block_number,
-            ethereum_client_state.latest_slot,
+            trusted_slot,
+            ready_msgs.len(),
         );
 
-        // get updates if necessary
-        let headers = if minimum_block_number > ethereum_client_state.latest_execution_block_number
-        {
-            self.wait_for_light_client_readiness(minimum_block_number)
+        // Generate the proofs for the ready events at the trusted slot while waiting for finality
+        // of the pending events, so that the polling waits overlap with useful work.
+        let ready_proofs = scope.run(RelayStage::ProofFetch, async {
+            let RelayMsgs {
+                mut timeout_msgs,
+                mut recv_msgs,
+                mut ack_msgs,
+            } = ready_msgs;
+            if !(recv_msgs.is_empty() && ack_msgs.is_empty() && timeout_msgs.is_empty()) {
+                cosmos::inject_ethereum_proofs(
+                    &mut recv_msgs,
+                    &mut ack_msgs,
+                    &mut timeout_msgs,
+                    &[],
+                    &self.eth_client,
+                    &self.beacon_api_client,
+                    &ethereum_client_state.ibc_contract_address.to_string(),
+                    ethereum_client_state.ibc_commitment_slot,
+                    Height {
+                        revision_number: ethereum_client_state.revision_number,
+                        revision_height: trusted_slot,
+                    },
+                )
                 .await?;
-            // Update the client state and consensus state, in case they have changed while we were waiting
-            ethereum_client_state = self.ethereum_client_state(dst_client_id.clone()).await?;
-            self.get_update_headers(&ethereum_client_state).await?
-        } else {
-            vec![]
+                let trusted_consensus_state = self
+                    .proof_consensus_state(
+                        &dst_client_id,
+                        ethereum_client_state.revision_number,
+                        trusted_slot,
+                    )
+                    .await?;
+                cosmos::audit_ethereum_proofs(
+                    &recv_msgs,
+                    &ack_msgs,
+                    &timeout_msgs,
+                    &ethereum_client_state,
+                    &trusted_consensus_state,
+                )?;
+            }
+            anyhow::Ok((recv_msgs, ack_msgs, timeout_msgs))
+        });
+
+        // get updates if necessary, once the source events are confirmed
+        let updates = async {
+            if let Some(max_event_block_number) = max_event_block_number {
+                scope
+                    .run(
+                        RelayStage::FinalityWait,
+                        self.wait_for_confirmation(max_event_block_number),
+                    )
+                    .await?;
+            }
+
+            match minimum_block_number {
+                Some(minimum_block_number)
+                    if minimum_block_number
+                        > ethereum_client_state.latest_execution_block_number =>
+                {
+                    if let Some(plan) = self.reusable_update_plan(
+                        &dst_client_id,
+                        trusted_slot,
+                        minimum_block_number,
+                        now_since_unix.as_secs(),
+                    ) {
+                        tracing::info!(
+                            "Reusing the update plan of client {} to slot {}",
+                            dst_client_id,
+                            plan.proof_slot
+                        );
+                        return Ok((ethereum_client_state.clone(), plan));
+                    }
+
+                    scope
+                        .run(
+                            RelayStage::FinalityWait,
+                            self.wait_for_light_client_readiness(minimum_block_number),
+                        )
+                        .await?;
+                    // Update the client state and consensus state, in case they have changed while we were waiting
+                    scope
+                        .run(
+                            RelayStage::HeaderBuild,
+                            self.new_update_plan(dst_client_id.clone(), minimum_block_number),
+                        )
+                        .await
+                }
+                _ => Ok((
+                    ethereum_client_state.clone(),
+                    UpdatePlan::new(trusted_slot, vec![], now_since_unix.as_secs()),
+                )),
+            }
         };
 
-        let proof_slot = headers
-            .last()
-            .map_or(ethereum_client_state.latest_slot, |h| {
-                h.consensus_update.finalized_header.beacon.slot
-            });
-
-        cosmos::inject_ethereum_proofs(
-            &mut recv_msgs,
-            &mut ack_msgs,
-            &mut timeout_msgs,
-            &self.eth_client,
-            &self.beacon_api_client,
-            &ethereum_client_state.ibc_contract_address.to_string(),
-            ethereum_client_state.ibc_commitment_slot,
+        let ((ready_recv_msgs, ready_ack_msgs, ready_timeout_msgs), (updated_client_state, plan)) =
+            futures::try_join!(ready_proofs, updates)?;
+        ethereum_client_state = updated_client_state;
+        let UpdatePlan {
+            headers,
             proof_slot,
-        )
-        .await?;
+            ..
+        } = plan;
+
+        if !(recv_msgs.is_empty() && ack_msgs.is_empty() && timeout_msgs.is_empty()) {
+            scope
+                .run(
+                    RelayStage::ProofFetch,
+                    cosmos::inject_ethereum_proofs(
+                        &mut recv_msgs,
+                        &mut ack_msgs,
+                        &mut timeout_msgs,
+                        &source_txs,
+                        &self.eth_client,
+                        &self.beacon_api_client,
+                        &ethereum_client_state.ibc_contract_address.to_string(),
+                        ethereum_client_state.ibc_commitment_slot,
+                        Height {
+                            revision_number: ethereum_client_state.revision_number,
+                            revision_height: proof_slot,
+                        },
+                    ),
+                )
+                .await?;
 
-        let update_msgs = headers
-            .iter()
-            .map(|header| -> Result<MsgUpdateClient> {
-                let header_bz = serde_json::to_vec(&header)?;
-                let client_msg = Any::from_msg(&ClientMessage { data: header_bz })?;
-                Ok(MsgUpdateClient {
-                    client_id: dst_client_id.clone(),
-                    client_message: Some(client_msg),
-                    signer: self.signer_address.clone(),
-                })
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+            // The proofs are verified against the storage root the last header will store, or
+            // against the stored consensus state if the client is not updated.
+            let proof_consensus_state = match headers.last() {
+                Some(header) => ConsensusState {
+                    slot: proof_slot,
+                    storage_root: header.account_update.account_proof.storage_root,
+                    ..self
+                        .proof_consensus_state(
+                            &dst_client_id,
+                            ethereum_client_state.revision_number,
+                            trusted_slot,
+                        )
+                        .await?
+                },
+                None => {
+                    self.proof_consensus_state(
+                        &dst_client_id,
+                        ethereum_client_state.revision_number,
+                        proof_slot,
+                    )
+                    .await?
+                }
+            };
+            cosmos::audit_ethereum_proofs(
+                &recv_msgs,
+                &ack_msgs,
+                &timeout_msgs,
+                &ethereum_client_state,
+                &proof_consensus_state,
+            )?;
+        }
+
+        // Without updates, every proof is verified against the stored consensus state at the
+        // trusted slot, which the light client may prune while the tx is waiting.
+        let proves_at_trusted_slot = !(ready_recv_msgs.is_empty()
+            && ready_ack_msgs.is_empty()
+            && ready_timeout_msgs.is_empty())
+            || (headers.is_empty()
+                && !(recv_msgs.is_empty() && ack_msgs.is_empty() && timeout_msgs.is_empty()));
+
+        let recv_msgs = [ready_recv_msgs, recv_msgs].concat();
+        let ack_msgs = [ready_ack_msgs, ack_msgs].concat();
+        let timeout_msgs = [ready_timeout_msgs, timeout_msgs].concat();
+
+        let update_msgs =
+            self.update_client_msgs(&dst_client_id, &ethereum_client_state, &headers)?;
+        let claim_msgs = match &self.config.relayer_fees {
+            Some(relayer_fees) => {
+                relayer_fees::fee_claim_msgs(
+                    &self.tm_client,
+                    relayer_fees,
+                    &self.signer_address,
+                    &relayer_fees::claimable_packets(&ack_msgs, &timeout_msgs),
+                )
+                .await
+            }
+            None => Vec::new(),
+        };
 
         let all_msgs = update_msgs
             .into_iter()
@@ -445,6 +1452,7 @@ where
             .chain(timeout_msgs.iter().map(Any::from_msg))
             .chain(recv_msgs.iter().map(Any::from_msg))
             .chain(ack_msgs.iter().map(Any::from_msg))
+            .chain(claim_msgs.into_iter().map(Ok))
             .collect::<Result<Vec<_>, _>>()?;
 
         let tx_body = TxBody {
@@ -452,31 +1460,26 @@ where
             ..Default::default()
         };
 
-        let latest_signature_slot = headers.last().map(|h| h.consensus_update.signature_slot);
-
         // Final check to make sure the target chain's calculated slot is greater than our latest
         // update's signature slot
-        wait_for_condition(
-            Duration::from_secs(15 * 60),
-            Duration::from_secs(10),
-            || async {
-                if headers.is_empty() {
-                    return Ok(true);
-                }
-
-                let latests_tm_block = self.tm_client.latest_block().await?;
-                let latest_onchain_timestamp = latests_tm_block.block.header.time.unix_timestamp();
-                let calculated_slot = ethereum_client_state
-                    .compute_slot_at_timestamp(latest_onchain_timestamp.try_into().unwrap())
-                    .unwrap();
-                tracing::debug!(
-                    "Waiting for target chain to catch up to slot {}",
-                    calculated_slot
-                );
-                Ok(calculated_slot > latest_signature_slot.unwrap())
-            },
-        )
-        .await?;
+        scope
+            .run(
+                RelayStage::FinalityWait,
+                self.wait_for_signature_slot(&ethereum_client_state, &headers),
+            )
+            .await?;
+        if proves_at_trusted_slot {
+            scope
+                .run(
+                    RelayStage::ProofFetch,
+                    self.proof_consensus_state(
+                        &dst_client_id,
+                        ethereum_client_state.revision_number,
+                        trusted_slot,
+                    ),
+                )
+                .await?;
+        }
 
         let initial_period = ethereum_client_state
             .compute_sync_committee_period_at_slot(ethereum_client_state.latest_slot);
@@ -503,63 +1506,667 @@ where
 
         Ok(tx_body.encode_to_vec())
     }
+}
+
+/// Returns a client state populated with the genesis, the fork schedule and the time parameters
+/// of the beacon chain, as served by the genesis and the spec endpoints of the Beacon API. The
+/// remaining fields are left to their default.
+///
+/// The two endpoints are checked against each other, since a Beacon API behind a misconfigured
+/// proxy or a node with a custom network config can serve a genesis and a spec of different
+/// networks.
+/// # Errors
+/// Returns an error if the genesis does not match the spec, or if the spec has unset time
+/// parameters or an unordered fork schedule.
+pub fn beacon_client_state(genesis: &Genesis, spec: &Spec) -> Result<ClientState> {
+    anyhow::ensure!(
+        genesis.genesis_fork_version == spec.genesis_fork_version,
+        "beacon api genesis fork version {} does not match the genesis fork version {} of the spec",
+        genesis.genesis_fork_version,
+        spec.genesis_fork_version
+    );
+    anyhow::ensure!(
+        !genesis.genesis_validators_root.is_zero() && genesis.genesis_time != 0,
+        "beacon api genesis is unset"
+    );
+    anyhow::ensure!(
+        spec.seconds_per_slot != 0
+            && spec.slots_per_epoch != 0
+            && spec.epochs_per_sync_committee_period != 0
+            && spec.sync_committee_size != 0,
+        "beacon api spec has unset time parameters: {:?}",
+        SlotTiming::from(spec)
+    );
+
+    let fork_parameters = spec.to_fork_parameters();
+    let fork_epochs = [
+        fork_parameters.altair.epoch,
+        fork_parameters.bellatrix.epoch,
+        fork_parameters.capella.epoch,
+        fork_parameters.deneb.epoch,
+        fork_parameters.electra.epoch,
+    ];
+    anyhow::ensure!(
+        fork_epochs.windows(2).all(|epochs| epochs[0] <= epochs[1]),
+        "beacon api spec fork schedule is not ordered: {fork_epochs:?}"
+    );
+
+    Ok(ClientState {
+        genesis_validators_root: genesis.genesis_validators_root,
+        min_sync_committee_participants: spec.sync_committee_size.div_ceil(3),
+        genesis_time: genesis.genesis_time,
+        genesis_slot: spec.genesis_slot,
+        fork_parameters,
+        seconds_per_slot: spec.seconds_per_slot,
+        slots_per_epoch: spec.slots_per_epoch,
+        epochs_per_sync_committee_period: spec.epochs_per_sync_committee_period,
+        ..Default::default()
+    })
+}
+
+/// Selects the light client updates, with their active sync committees, that advance the client
+/// from its trusted slot to the finalized slot of `finality_update`.
+///
+/// The sync committee period is advanced one period at a time with the period updates, since the
+/// light client can only verify signatures of the current and the next sync committee. Within a
+/// period, a period update is only selected if it teaches the client its next sync committee,
+/// unless `intermediate_updates` is set, in which case every newer period update is selected. The
+/// finality update is always selected last if it is newer than the selected updates, so the last
+/// update covers the latest finalized block.
+///
+/// If the client has a `min_slot_advance`, the updates advancing it by fewer slots within a sync
+/// committee period are skipped, since the client would reject them.
+///
+/// When there are competing updates for a period, only the best one according to
+/// [`is_better_update`] is considered.
+///
+/// # Errors
+/// Returns an error if the updates leave a gap in the sync committee periods, if the sync
+/// committee signing an update is unknown, or if the last selected update does not cover
+/// `min_block_number`.
+pub fn select_light_client_updates(
+    client_state: &ClientState,
+    next_sync_committee_known: bool,
+    light_client_updates: &[LightClientUpdate],
+    finality_update: LightClientUpdate,
+    min_block_number: u64,
+    intermediate_updates: bool,
+) -> Result<Vec<(ActiveSyncCommittee, LightClientUpdate)>> {
+    select_light_client_updates_with_committees(
+        client_state,
+        next_sync_committee_known,
+        light_client_updates,
+        &BTreeMap::new(),
+        finality_update,
+        min_block_number,
+        intermediate_updates,
+    )
+}
+
+/// Selects the light client updates like [`select_light_client_updates`], with the sync
+/// committees already known by period, e.g. from a [`SyncCommitteeStore`], used when no update of
+/// the previous period teaches the committee signing an update.
+/// # Errors
+/// See [`select_light_client_updates`].
+pub fn select_light_client_updates_with_committees(
+    client_state: &ClientState,
+    next_sync_committee_known: bool,
+    light_client_updates: &[LightClientUpdate],
+    known_sync_committees: &BTreeMap<u64, SyncCommittee>,
+    finality_update: LightClientUpdate,
+    min_block_number: u64,
+    intermediate_updates: bool,
+) -> Result<Vec<(ActiveSyncCommittee, LightClientUpdate)>> {
+    let light_client_updates = &best_light_client_updates(client_state, light_client_updates);
+    let mut period_updates = light_client_updates.iter().collect::<Vec<_>>();
+    period_updates.sort_by_key(|update| update.finalized_header.beacon.slot);
+
+    let mut store_slot = client_state.latest_slot;
+    let mut store_block_number = client_state.latest_execution_block_number;
+    let mut store_period = client_state.compute_sync_committee_period_at_slot(store_slot);
+    let mut next_sync_committee_known = next_sync_committee_known;
+    let mut selected = vec![];
+
+    for update in period_updates.into_iter().chain([&finality_update]) {
+        let finalized_slot = update.finalized_header.beacon.slot;
+        if finalized_slot <= store_slot {
+            tracing::debug!("Skipping update for already trusted slot {finalized_slot}");
+            continue;
+        }
+
+        let is_finality_update = std::ptr::eq(update, &finality_update);
+        let update_period = client_state.compute_sync_committee_period_at_slot(finalized_slot);
+        if update_period > store_period + 1
+            || (update_period > store_period && !next_sync_committee_known)
+            || (update_period > store_period && is_finality_update)
+        {
+            anyhow::bail!(
+                "No light client update to advance from period {store_period} to period {update_period} (finalized slot {finalized_slot})"
+            );
+        }
+
+        let learns_next_sync_committee =
+            !next_sync_committee_known && update.next_sync_committee.is_some();
+        if update_period == store_period
+            && !is_finality_update
+            && !learns_next_sync_committee
+            && !intermediate_updates
+        {
+            tracing::debug!(
+                "Skipping intermediate update with same sync committee period for slot {finalized_slot}"
+            );
+            continue;
+        }
+
+        if update_period == store_period
+            && client_state
+                .min_slot_advance
+                .is_some_and(|min_slot_advance| finalized_slot - store_slot < min_slot_advance)
+        {
+            tracing::debug!(
+                "Skipping update for slot {finalized_slot}, which advances the client by less than its minimum slot advance"
+            );
+            continue;
+        }
+
+        // The committee is selected by the period of the signature, not of the finalized header:
+        // an update finalized at the end of the trusted period may be signed by the next committee
+        let signature_period =
+            client_state.compute_sync_committee_period_at_slot(update.signature_slot);
+        let active_sync_committee = if signature_period == store_period {
+            ActiveSyncCommittee::Current(sync_committee_at_slot(
+                client_state,
+                light_client_updates,
+                known_sync_committees,
+                update.signature_slot,
+            )?)
+        } else if signature_period == store_period + 1 && next_sync_committee_known {
+            ActiveSyncCommittee::Next(sync_committee_at_slot(
+                client_state,
+                light_client_updates,
+                known_sync_committees,
+                update.signature_slot,
+            )?)
+        } else {
+            anyhow::bail!(
+                "Update for finalized slot {finalized_slot} is signed in period {signature_period}, which is unknown to the client at period {store_period}"
+            );
+        };
+
+        next_sync_committee_known = if update_period > store_period {
+            update.next_sync_committee.is_some()
+        } else {
+            next_sync_committee_known || update.next_sync_committee.is_some()
+        };
+        store_slot = finalized_slot;
+        store_block_number = update.finalized_header.execution.block_number;
+        store_period = update_period;
+        selected.push((active_sync_committee, update.clone()));
+    }
+
+    anyhow::ensure!(
+        store_block_number >= min_block_number,
+        "The selected updates only reach execution block {store_block_number}, but block {min_block_number} is required"
+    );
+
+    Ok(selected)
+}
+
+/// Returns the best light client update of every sync committee period, according to
+/// [`is_better_update`]. The period of an update is the period of its attested header.
+#[must_use]
+pub fn best_light_client_updates(
+    client_state: &ClientState,
+    light_client_updates: &[LightClientUpdate],
+) -> Vec<LightClientUpdate> {
+    let mut best_updates: BTreeMap<u64, &LightClientUpdate> = BTreeMap::new();
+    for update in light_client_updates {
+        let period =
+            client_state.compute_sync_committee_period_at_slot(update.attested_header.beacon.slot);
+        match best_updates.entry(period) {
+            Entry::Vacant(entry) => {
+                entry.insert(update);
+            }
+            Entry::Occupied(mut entry) => {
+                if is_better_update(client_state, update, entry.get()) {
+                    tracing::debug!(
+                        "Replacing the update of period {period} at attested slot {} with a better update at attested slot {}",
+                        entry.get().attested_header.beacon.slot,
+                        update.attested_header.beacon.slot
+                    );
+                    entry.insert(update);
+                }
+            }
+        }
+    }
+
+    best_updates.into_values().cloned().collect()
+}
+
+/// Returns whether `new_update` is better than `old_update`, ranking competing updates by their
+/// sync committee participation, whether they prove the sync committee relevant to their period,
+/// and their finality.
+///
+/// See spec: <https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/light-client/sync-protocol.md#is_better_update>
+#[must_use]
+pub fn is_better_update(
+    client_state: &ClientState,
+    new_update: &LightClientUpdate,
+    old_update: &LightClientUpdate,
+) -> bool {
+    let period_at_slot = |slot| client_state.compute_sync_committee_period_at_slot(slot);
+
+    // Compare supermajority (> 2/3) sync committee participation
+    let max_active_participants = new_update.sync_aggregate.sync_committee_size();
+    let new_num_active_participants = new_update.sync_aggregate.num_sync_committe_participants();
+    let old_num_active_participants = old_update.sync_aggregate.num_sync_committe_participants();
+    let new_has_supermajority = new_num_active_participants * 3 >= max_active_participants * 2;
+    let old_has_supermajority = old_num_active_participants * 3 >= max_active_participants * 2;
+    if new_has_supermajority != old_has_supermajority {
+        return new_has_supermajority;
+    }
+    if !new_has_supermajority && new_num_active_participants != old_num_active_participants {
+        return new_num_active_participants > old_num_active_participants;
+    }
+
+    // Compare presence of relevant sync committee
+    let has_relevant_sync_committee = |update: &LightClientUpdate| {
+        update.next_sync_committee.is_some()
+            && period_at_slot(update.attested_header.beacon.slot)
+                == period_at_slot(update.signature_slot)
+    };
+    let new_has_relevant_sync_committee = has_relevant_sync_committee(new_update);
+    let old_has_relevant_sync_committee = has_relevant_sync_committee(old_update);
+    if new_has_relevant_sync_committee != old_has_relevant_sync_committee {
+        return new_has_relevant_sync_committee;
+    }
+
+    // Compare indication of any finality
+    let new_has_finality = !new_update.finality_branch.is_empty();
+    let old_has_finality = !old_update.finality_branch.is_empty();
+    if new_has_finality != old_has_finality {
+        return new_has_finality;
+    }
+
+    // Compare sync committee finality
+    if new_has_finality {
+        let has_sync_committee_finality = |update: &LightClientUpdate| {
+            period_at_slot(update.finalized_header.beacon.slot)
+                == period_at_slot(update.attested_header.beacon.slot)
+        };
+        let new_has_sync_committee_finality = has_sync_committee_finality(new_update);
+        let old_has_sync_committee_finality = has_sync_committee_finality(old_update);
+        if new_has_sync_committee_finality != old_has_sync_committee_finality {
+            return new_has_sync_committee_finality;
+        }
+    }
+
+    // Tiebreaker 1: Sync committee participation beyond supermajority
+    if new_num_active_participants != old_num_active_participants {
+        return new_num_active_participants > old_num_active_participants;
+    }
+
+    // Tiebreaker 2: Prefer older data (fewer changes to best)
+    if new_update.attested_header.beacon.slot != old_update.attested_header.beacon.slot {
+        return new_update.attested_header.beacon.slot < old_update.attested_header.beacon.slot;
+    }
+    new_update.signature_slot < old_update.signature_slot
+}
+
+/// Returns the sync committee active at `slot`, derived from the chain of light client updates.
+///
+/// Every light client update carries the `next_sync_committee` (proven by its branch against the
+/// attested state), so the committee for a period is the one rotated in by the previous period's
+/// update. This avoids fetching bootstrap data, which beacon nodes may prune. The known committees
+/// are used when the update of the previous period is not among the updates.
+fn sync_committee_at_slot(
+    client_state: &ClientState,
+    updates: &[LightClientUpdate],
+    known_sync_committees: &BTreeMap<u64, SyncCommittee>,
+    slot: u64,
+) -> Result<SyncCommittee> {
+    let period = client_state.compute_sync_committee_period_at_slot(slot);
+    updates
+        .iter()
+        .find(|update| {
+            client_state.compute_sync_committee_period_at_slot(update.attested_header.beacon.slot)
+                + 1
+                == period
+        })
+        .and_then(|update| update.next_sync_committee.clone())
+        .or_else(|| known_sync_committees.get(&period).cloned())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No light client update with a next sync committee found for period {}",
+                period.saturating_sub(1)
+            )
+        })
+}
+
+/// Splits the messages built from the relayed events into the messages that can be proven at the
+/// trusted slot of the client, and the messages that need to wait for a client update.
+///
+/// Together, the two sets contain exactly the messages of [`cosmos::events_to_relay_msgs`].
+#[allow(clippy::too_many_arguments)]
+#[must_use]
+pub fn partition_relay_msgs(
+    client_state: &ClientState,
+    src_events: Vec<EurekaEventWithHeight>,
+    dest_events: Vec<EurekaEventWithHeight>,
+    src_client_id: &str,
+    dst_client_id: &str,
+    src_packet_seqs: &[u64],
+    dst_packet_seqs: &[u64],
+    signer_address: &str,
+    now: u64,
+) -> (RelayMsgs, RelayMsgs) {
+    // Events that are already covered by the trusted consensus state can be proven right away,
+    // while the remaining events need to wait for finality and client updates.
+    let (ready_src_events, pending_src_events): (Vec<_>, Vec<_>) = src_events
+        .into_iter()
+        .partition(|e| e.height <= client_state.latest_execution_block_number);
+
+    let ready = cosmos::events_to_relay_msgs(
+        ready_src_events,
+        vec![],
+        src_client_id,
+        dst_client_id,
+        src_packet_seqs,
+        dst_packet_seqs,
+        signer_address,
+        now,
+    );
+    let pending = cosmos::events_to_relay_msgs(
+        pending_src_events,
+        dest_events,
+        src_client_id,
+        dst_client_id,
+        src_packet_seqs,
+        dst_packet_seqs,
+        signer_address,
+        now,
+    );
+
+    let (ready_timeout_msgs, pending_timeout_msgs): (Vec<_>, Vec<_>) =
+        pending.timeout_msgs.into_iter().partition(|msg| {
+            msg.packet
+                .as_ref()
+                .and_then(|packet| client_state.compute_slot_at_timestamp(packet.timeout_timestamp))
+                .is_some_and(|timeout_slot| timeout_slot <= client_state.latest_slot)
+        });
+
+    (
+        RelayMsgs {
+            timeout_msgs: ready_timeout_msgs,
+            ..ready
+        },
+        RelayMsgs {
+            timeout_msgs: pending_timeout_msgs,
+            ..pending
+        },
+    )
+}
+
+/// The version of the client message envelope of the Ethereum light client.
+const CLIENT_MESSAGE_VERSION: u32 = 1;
+/// The envelope type of a header of the Ethereum light client.
+const CLIENT_MESSAGE_TYPE_HEADER: &str = "header";
+/// The envelope type of a header batch of the Ethereum light client.
+const CLIENT_MESSAGE_TYPE_HEADER_BATCH: &str = "header_batch";
+
+/// The client message envelope of the Ethereum light client, which tags the encoded message with
+/// its version and type.
+#[derive(Serialize)]
+struct ClientMessageEnvelope<'a> {
+    version: u32,
+    #[serde(rename = "type")]
+    message_type: &'a str,
+    /// The base64 encoded message.
+    data: String,
+    /// The address the updates of the message are attributed to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    relayer: Option<&'a str>,
+}
+
+/// Wraps an encoded client message in an envelope of the given type, attributed to `relayer`.
+/// Waits until the execution block `block_number` is confirmed, as polled every slot with
+/// `confirmed_block_number`, for at most the finality timeout of `slot_timing`.
+async fn wait_for_confirmation<F, Fut>(
+    clock: &dyn Clock,
+    slot_timing: &SlotTiming,
+    block_number: u64,
+    mut confirmed_block_number: F,
+) -> Result<()>
+where
+    F: FnMut() -> Fut + Send,
+    Fut: Future<Output = Result<u64>> + Send,
+{
+    wait_for_condition(
+        clock,
+        slot_timing.finality_timeout(),
+        slot_timing.poll_interval(),
+        || {
+            let poll = confirmed_block_number();
+            async move {
+                let confirmed_block_number = poll.await?;
+                tracing::debug!(
+                    "Waiting for confirmation: confirmed block number: {}, event block number: {}",
+                    confirmed_block_number,
+                    block_number
+                );
+                Ok(confirmed_block_number >= block_number)
+            }
+        },
+    )
+    .await
+}
+
+fn enveloped_client_message(
+    message_type: &str,
+    data: &[u8],
+    relayer: Option<&str>,
+) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(&ClientMessageEnvelope {
+        version: CLIENT_MESSAGE_VERSION,
+        message_type,
+        data: BASE64.encode(data),
+        relayer,
+    })?)
+}
+
+/// Encodes the headers as a header batch client message, applied in order by the light client.
+fn header_batch_client_message(headers: &[Header], relayer: Option<&str>) -> Result<Vec<u8>> {
+    enveloped_client_message(
+        CLIENT_MESSAGE_TYPE_HEADER_BATCH,
+        &serde_json::to_vec(headers)?,
+        relayer,
+    )
+}
+
+/// The magic byte prefixing a zlib compressed client message, which a JSON message never starts
+/// with.
+const COMPRESSED_CLIENT_MESSAGE_PREFIX: u8 = 0x01;
+/// The zlib compression level of the client messages, from 0 to 10.
+const CLIENT_MESSAGE_COMPRESSION_LEVEL: u8 = 9;
+
+/// Compresses an encoded client message with zlib, prefixed with the magic byte the light client
+/// detects compressed messages with.
+fn compress_client_message(bz: &[u8]) -> Vec<u8> {
+    let mut compressed = vec![COMPRESSED_CLIENT_MESSAGE_PREFIX];
+    compressed.extend(miniz_oxide::deflate::compress_to_vec_zlib(
+        bz,
+        CLIENT_MESSAGE_COMPRESSION_LEVEL,
+    ));
+    compressed
+}
+
+/// Runs the light client verification over the headers locally, applying them in order on top of
+/// the trusted states, so that invalid updates served by the beacon node are rejected before any
+/// gas is spent on submitting them.
+fn verify_update_headers(
+    mut client_state: ClientState,
+    mut consensus_state: ConsensusState,
+    headers: &[Header],
+    current_timestamp: u64,
+) -> Result<()> {
+    for header in headers {
+        let finalized_slot = header.consensus_update.finalized_header.beacon.slot;
+
+        verify_header(
+            &consensus_state,
+            &client_state,
+            current_timestamp,
+            header,
+            BlstVerifier,
+        )
+        .map_err(|err| {
+            anyhow::anyhow!("Header for finalized slot {finalized_slot} failed verification: {err}")
+        })?;
+
+        let (_, updated_consensus_state, updated_client_state) =
+            update_consensus_state(consensus_state, client_state.clone(), header).map_err(
+                |err| {
+                    anyhow::anyhow!(
+                        "Header for finalized slot {finalized_slot} could not be applied: {err}"
+                    )
+                },
+            )?;
+        consensus_state = updated_consensus_state;
+        client_state = updated_client_state.unwrap_or(client_state);
+
+        tracing::debug!("Verified header for finalized slot {finalized_slot}");
+    }
+
+    Ok(())
+}
+
+/// The key for the checksum hex in the parameters map.
+const CHECKSUM_HEX: &str = "checksum_hex";
+/// The key for the optional revision number of the client heights in the parameters map.
+const REVISION_NUMBER: &str = "revision_number";
+/// The key for the optional checkpoint strategy in the parameters map, see [`CheckpointStrategy`].
+const CHECKPOINT: &str = "checkpoint";
+/// The key for the optional comma separated Beacon API URLs the checkpoint is verified against.
+const CHECKPOINT_SOURCES: &str = "checkpoint_sources";
+/// The key for the optional flag accepting compressed client messages in the parameters map.
+const COMPRESSED_CLIENT_MESSAGES: &str = "compressed_client_messages";
+/// The key for the optional consensus state write policy of header batches in the parameters map,
+/// see [`ethereum_light_client::client_state::ConsensusStateWrites`].
+const CONSENSUS_STATE_WRITES: &str = "consensus_state_writes";
+/// The key for the optional number of slots the consensus states are kept for in the parameters
+/// map.
+const CONSENSUS_STATE_RETENTION: &str = "consensus_state_retention";
+/// The key for the optional minimum slot advance of the updates in the parameters map.
+const MIN_SLOT_ADVANCE: &str = "min_slot_advance";
+/// The key for the optional number of seconds the client expires after its latest consensus state
+/// in the parameters map.
+const TRUSTING_PERIOD: &str = "trusting_period";
+
+#[async_trait::async_trait]
+impl<P> TxBuilderService<EthEureka, CosmosSdk> for TxBuilder<P>
+where
+    P: Provider + Clone,
+{
+    #[tracing::instrument(skip_all)]
+    async fn relay_events(
+        &self,
+        scope: &RelayScope,
+        src_events: Vec<EurekaEventWithHeight>,
+        dest_events: Vec<EurekaEventWithHeight>,
+        src_client_id: String,
+        dst_client_id: String,
+        src_packet_seqs: Vec<u64>,
+        dst_packet_seqs: Vec<u64>,
+    ) -> Result<Vec<u8>> {
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .build_relay_tx(
+                    scope,
+                    src_events.clone(),
+                    dest_events.clone(),
+                    src_client_id.clone(),
+                    dst_client_id.clone(),
+                    src_packet_seqs.clone(),
+                    dst_packet_seqs.clone(),
+                )
+                .await;
+            match result {
+                // The proofs are rebuilt against the new latest height of the client, with the
+                // updates it needs, instead of handing out a transaction that will be rejected
+                Err(err) if attempt < self.config.stale_proof_retries && err.is::<StaleProof>() => {
+                    attempt += 1;
+                    tracing::warn!("{err}, rebuilding the relay (attempt {attempt})");
+                }
+                result => return result,
+            }
+        }
+    }
 
     #[tracing::instrument(skip_all)]
     async fn create_client(&self, parameters: &HashMap<String, String>) -> Result<Vec<u8>> {
         parameters
             .keys()
-            .find(|k| k.as_str() != CHECKSUM_HEX)
+            .find(|k| {
+                ![
+                    CHECKSUM_HEX,
+                    REVISION_NUMBER,
+                    CHECKPOINT,
+                    CHECKPOINT_SOURCES,
+                    COMPRESSED_CLIENT_MESSAGES,
+                    CONSENSUS_STATE_WRITES,
+                    MIN_SLOT_ADVANCE,
+                    TRUSTING_PERIOD,
+                ]
+                .contains(&k.as_str())
+            })
             .map_or(Ok(()), |param| {
                 Err(anyhow::anyhow!(
-                    "Unexpected parameter: `{param}`, only `{CHECKSUM_HEX}` is allowed"
+                    "Unexpected parameter: `{param}`, only `{CHECKSUM_HEX}`, `{REVISION_NUMBER}`, `{CHECKPOINT}`, `{CHECKPOINT_SOURCES}`, `{COMPRESSED_CLIENT_MESSAGES}`, `{CONSENSUS_STATE_WRITES}`, `{MIN_SLOT_ADVANCE}` and `{TRUSTING_PERIOD}` are allowed"
                 ))
             })?;
 
-        let genesis = self.beacon_api_client.genesis().await?.data;
-        let spec = self.beacon_api_client.spec().await?.data;
-        let beacon_block = self
-            .beacon_api_client
-            .beacon_block("finalized")
-            .await?
-            .message;
-
-        tracing::info!("Creating client at slot: {}", beacon_block.slot);
-
-        let block_root = self
-            .beacon_api_client
-            .beacon_block_root(&format!("{}", beacon_block.slot))
+        let strategy = parameters
+            .get(CHECKPOINT)
+            .map(|strategy| strategy.parse::<CheckpointStrategy>())
+            .transpose()?
+            .unwrap_or_default();
+        let sources = parameters
+            .get(CHECKPOINT_SOURCES)
+            .into_iter()
+            .flat_map(|urls| urls.split(','))
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| BeaconApiClient::new(url.trim_end_matches('/').to_string()))
+            .collect::<Vec<_>>();
+
+        let revision_number = parameters
+            .get(REVISION_NUMBER)
+            .map(|revision_number| revision_number.parse())
+            .transpose()?
+            .unwrap_or_default();
+
+        let checkpoint = self.select_checkpoint(&strategy, &sources).await?;
+        let (mut eth_client_state, eth_consensus_state) = self
+            .bootstrap_ethereum_states_at(&checkpoint, revision_number)
             .await?;
-
-        let bootstrap = self
-            .beacon_api_client
-            .light_client_bootstrap(&block_root)
-            .await?
-            .data;
-
-        if bootstrap.header.execution.block_number
-            != beacon_block.body.execution_payload.block_number
-        {
-            anyhow::bail!(
-                "Light client bootstrap block number does not match execution block number"
-            );
+        if let Some(compressed_client_messages) = parameters.get(COMPRESSED_CLIENT_MESSAGES) {
+            eth_client_state.compressed_client_messages = compressed_client_messages.parse()?;
+        }
+        if let Some(consensus_state_writes) = parameters.get(CONSENSUS_STATE_WRITES) {
+            eth_client_state.consensus_state_writes =
+                serde_json::from_value(serde_json::Value::String(consensus_state_writes.clone()))?;
+        }
+        if let Some(min_slot_advance) = parameters.get(MIN_SLOT_ADVANCE) {
+            eth_client_state.min_slot_advance = Some(min_slot_advance.parse()?);
+        }
+        if let Some(consensus_state_retention) = parameters.get(CONSENSUS_STATE_RETENTION) {
+            eth_client_state.consensus_state_retention = Some(consensus_state_retention.parse()?);
+        }
+        if let Some(trusting_period) = parameters.get(TRUSTING_PERIOD) {
+            eth_client_state.trusting_period = Some(trusting_period.parse()?);
         }
 
-        let eth_client_state = ClientState {
-            chain_id: self.ics26_router.provider().get_chain_id().await?,
-            genesis_validators_root: genesis.genesis_validators_root,
-            min_sync_committee_participants: spec.sync_committee_size.div_ceil(3),
-            genesis_time: genesis.genesis_time,
-            genesis_slot: spec.genesis_slot,
-            fork_parameters: spec.to_fork_parameters(),
-            seconds_per_slot: spec.seconds_per_slot,
-            slots_per_epoch: spec.slots_per_epoch,
-            epochs_per_sync_committee_period: spec.epochs_per_sync_committee_period,
-            latest_slot: bootstrap.header.beacon.slot,
-            is_frozen: false,
-            ibc_commitment_slot: U256::from_be_slice(&ICS26_IBC_STORAGE_SLOT),
-            ibc_contract_address: *self.ics26_router.address(),
-            latest_execution_block_number: bootstrap.header.execution.block_number,
-        };
         let client_state = WasmClientState {
             data: serde_json::to_vec(&eth_client_state)?,
             checksum: hex::decode(
@@ -568,42 +2175,11 @@ where
                     .ok_or_else(|| anyhow::anyhow!("Missing `{CHECKSUM_HEX}` parameter"))?,
             )?,
             latest_height: Some(Height {
-                revision_number: 0,
+                revision_number: eth_client_state.revision_number,
                 revision_height: eth_client_state.latest_slot,
             }),
         };
 
-        let contract_proof = self
-            .eth_client
-            .get_proof(
-                &self.ics26_router.address().to_string(),
-                vec![],
-                format!("0x{:x}", eth_client_state.latest_execution_block_number),
-            )
-            .await?;
-
-        let latest_period =
-            eth_client_state.compute_sync_committee_period_at_slot(eth_client_state.latest_slot);
-        let next_sync_committee = self
-            .beacon_api_client
-            .light_client_updates(latest_period, 1)
-            .await?
-            .pop()
-            .ok_or_else(|| anyhow::anyhow!("No light client updates found for the latest period"))?
-            .data
-            .next_sync_committee
-            .ok_or_else(|| {
-                anyhow::anyhow!("No next sync committee found in the light client update")
-            })?;
-
-        let eth_consensus_state = ConsensusState {
-            slot: eth_client_state.latest_slot,
-            state_root: bootstrap.header.execution.state_root,
-            storage_root: contract_proof.storage_hash,
-            timestamp: bootstrap.header.execution.timestamp,
-            current_sync_committee: bootstrap.current_sync_committee.aggregate_pubkey,
-            next_sync_committee: Some(next_sync_committee.aggregate_pubkey),
-        };
         let consensus_state = WasmConsensusState {
             data: serde_json::to_vec(&eth_consensus_state)?,
         };
@@ -620,6 +2196,11 @@ where
         }
         .encode_to_vec())
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn plan_tx(&self, tx: &[u8]) -> Result<TxPlan> {
+        cosmos::plan_tx_body(tx, &self.config.gas_estimates)
+    }
 }
 
 impl<P: Provider + Clone> MockTxBuilder<P> {
@@ -629,8 +2210,16 @@ impl<P: Provider + Clone> MockTxBuilder<P> {
             eth_client: EthApiClient::new(provider.clone()),
             ics26_router: routerInstance::new(ics26_address, provider),
             signer_address,
+            clock: Arc::new(SystemClock),
         }
     }
+
+    /// Set the clock the timeouts are computed with.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -641,6 +2230,7 @@ where
     #[tracing::instrument(skip_all)]
     async fn relay_events(
         &self,
+        _scope: &RelayScope,
         src_events: Vec<EurekaEventWithHeight>,
         dest_events: Vec<EurekaEventWithHeight>,
         src_client_id: String,
@@ -653,19 +2243,15 @@ where
             dst_client_id
         );
 
-        let now_since_unix = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
-
-        let mut timeout_msgs = cosmos::target_events_to_timeout_msgs(
-            dest_events,
-            &src_client_id,
-            &dst_client_id,
-            &dst_packet_seqs,
-            &self.signer_address,
-            now_since_unix.as_secs(),
-        );
+        let now_since_unix = self.clock.now_since_unix()?;
 
-        let (mut recv_msgs, mut ack_msgs) = cosmos::src_events_to_recv_and_ack_msgs(
+        let RelayMsgs {
+            mut timeout_msgs,
+            mut recv_msgs,
+            mut ack_msgs,
+        } = cosmos::events_to_relay_msgs(
             src_events,
+            dest_events,
             &src_client_id,
             &dst_client_id,
             &src_packet_seqs,
@@ -698,12 +2284,17 @@ where
     async fn create_client(&self, parameters: &HashMap<String, String>) -> Result<Vec<u8>> {
         parameters
             .keys()
-            .find(|k| k.as_str() != CHECKSUM_HEX)
+            .find(|k| ![CHECKSUM_HEX, REVISION_NUMBER].contains(&k.as_str()))
             .map_or(Ok(()), |param| {
                 Err(anyhow::anyhow!(
-                    "Unexpected parameter: `{param}`, only `{CHECKSUM_HEX}` is allowed"
+                    "Unexpected parameter: `{param}`, only `{CHECKSUM_HEX}` and `{REVISION_NUMBER}` are allowed"
                 ))
             })?;
+        let revision_number = parameters
+            .get(REVISION_NUMBER)
+            .map(|revision_number| revision_number.parse())
+            .transpose()?
+            .unwrap_or_default();
 
         let client_state = WasmClientState {
             data: b"test".to_vec(),
@@ -713,7 +2304,7 @@ where
                     .ok_or_else(|| anyhow::anyhow!("Missing `{CHECKSUM_HEX}` parameter"))?,
             )?,
             latest_height: Some(Height {
-                revision_number: 0,
+                revision_number,
                 revision_height: 1,
             }),
         };
@@ -733,4 +2324,771 @@ where
         }
         .encode_to_vec())
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn plan_tx(&self, tx: &[u8]) -> Result<TxPlan> {
+        cosmos::plan_tx_body(tx, &GasEstimates::COSMOS_SDK)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use alloy::{
+        primitives::{aliases::B32, Bytes, B256},
+        providers::RootProvider,
+    };
+    use ethereum_light_client::test_utils::update_generator::UpdateGenerator;
+    use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs::Packet;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{clock::ManualClock, events::EurekaEvent};
+
+    const CLIENT_IDS: [&str; 3] = ["client-0", "client-1", "client-2"];
+    const SIGNER: &str = "cosmos1signer";
+    const GENESIS_TIME: u64 = 1_000;
+
+    fn random_client_id(rng: &mut StdRng) -> String {
+        CLIENT_IDS[rng.random_range(0..CLIENT_IDS.len())].to_string()
+    }
+
+    fn random_seqs(rng: &mut StdRng) -> Vec<u64> {
+        if rng.random_bool(0.5) {
+            vec![]
+        } else {
+            (1..=10).filter(|_| rng.random_bool(0.5)).collect()
+        }
+    }
+
+    fn random_events(rng: &mut StdRng, now: u64) -> Vec<EurekaEventWithHeight> {
+        (0..rng.random_range(0..24))
+            .map(|_| {
+                // Timeouts are picked around `now` to exercise the timeout boundaries
+                let packet = Packet {
+                    sequence: rng.random_range(1..=10),
+                    sourceClient: random_client_id(rng),
+                    destClient: random_client_id(rng),
+                    timeoutTimestamp: now - 24 + rng.random_range(0..=48),
+                    payloads: vec![],
+                };
+                let event = if rng.random_bool(0.5) {
+                    EurekaEvent::SendPacket(packet)
+                } else {
+                    EurekaEvent::WriteAcknowledgement(packet, vec![Bytes::from_static(b"ack")])
+                };
+                EurekaEventWithHeight::new(event, rng.random_range(1..=40))
+            })
+            .collect()
+    }
+
+    fn sorted_encodings<M: Message>(msgs: &[M]) -> Vec<Vec<u8>> {
+        let mut encodings = msgs.iter().map(Message::encode_to_vec).collect::<Vec<_>>();
+        encodings.sort();
+        encodings
+    }
+
+    #[test]
+    fn test_partition_relay_msgs_matches_mock_msgs() {
+        for seed in 0..512 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let client_state = ClientState {
+                genesis_time: GENESIS_TIME,
+                seconds_per_slot: 2,
+                latest_slot: rng.random_range(0..40),
+                latest_execution_block_number: rng.random_range(0..40),
+                ..Default::default()
+            };
+            let now = GENESIS_TIME + rng.random_range(24..80);
+            let src_events = random_events(&mut rng, now);
+            let dest_events = random_events(&mut rng, now);
+            let src_client_id = random_client_id(&mut rng);
+            let dst_client_id = random_client_id(&mut rng);
+            let src_packet_seqs = random_seqs(&mut rng);
+            let dst_packet_seqs = random_seqs(&mut rng);
+
+            let mock_msgs = cosmos::events_to_relay_msgs(
+                src_events.clone(),
+                dest_events.clone(),
+                &src_client_id,
+                &dst_client_id,
+                &src_packet_seqs,
+                &dst_packet_seqs,
+                SIGNER,
+                now,
+            );
+            let (ready_msgs, pending_msgs) = partition_relay_msgs(
+                &client_state,
+                src_events,
+                dest_events,
+                &src_client_id,
+                &dst_client_id,
+                &src_packet_seqs,
+                &dst_packet_seqs,
+                SIGNER,
+                now,
+            );
+
+            assert!(ready_msgs.timeout_msgs.iter().all(|msg| {
+                let timeout_timestamp = msg.packet.as_ref().unwrap().timeout_timestamp;
+                client_state
+                    .compute_slot_at_timestamp(timeout_timestamp)
+                    .is_some_and(|slot| slot <= client_state.latest_slot)
+            }));
+
+            let msgs = ready_msgs.concat(pending_msgs);
+            assert_eq!(msgs.len(), mock_msgs.len(), "seed {seed}");
+            assert_eq!(
+                sorted_encodings(&msgs.timeout_msgs),
+                sorted_encodings(&mock_msgs.timeout_msgs),
+                "timeout msgs diverge for seed {seed}"
+            );
+            assert_eq!(
+                sorted_encodings(&msgs.recv_msgs),
+                sorted_encodings(&mock_msgs.recv_msgs),
+                "recv msgs diverge for seed {seed}"
+            );
+            assert_eq!(
+                sorted_encodings(&msgs.ack_msgs),
+                sorted_encodings(&mock_msgs.ack_msgs),
+                "ack msgs diverge for seed {seed}"
+            );
+        }
+    }
+
+    fn sync_committee_client_state(latest_slot: u64) -> ClientState {
+        // A sync committee period is 10 slots long
+        ClientState {
+            slots_per_epoch: 1,
+            epochs_per_sync_committee_period: 10,
+            latest_slot,
+            latest_execution_block_number: latest_slot,
+            ..Default::default()
+        }
+    }
+
+    fn light_client_update(
+        attested_slot: u64,
+        finalized_slot: u64,
+        signature_slot: u64,
+        with_next_sync_committee: bool,
+    ) -> LightClientUpdate {
+        let mut update = LightClientUpdate {
+            signature_slot,
+            next_sync_committee: with_next_sync_committee.then(SyncCommittee::default),
+            ..Default::default()
+        };
+        update.attested_header.beacon.slot = attested_slot;
+        update.finalized_header.beacon.slot = finalized_slot;
+        // Execution block numbers follow the slots
+        update.finalized_header.execution.block_number = finalized_slot;
+        update
+    }
+
+    fn period_updates() -> Vec<LightClientUpdate> {
+        vec![
+            light_client_update(5, 3, 6, true),
+            light_client_update(15, 14, 16, true),
+            light_client_update(25, 22, 26, true),
+        ]
+    }
+
+    fn selected_slots(selected: &[(ActiveSyncCommittee, LightClientUpdate)]) -> Vec<u64> {
+        selected
+            .iter()
+            .map(|(_, update)| update.finalized_header.beacon.slot)
+            .collect()
+    }
+
+    #[test]
+    fn test_select_light_client_updates_skips_intermediate_updates() {
+        let client_state = sync_committee_client_state(12);
+        let finality_update = light_client_update(28, 26, 29, false);
+
+        let selected = select_light_client_updates(
+            &client_state,
+            true,
+            &period_updates(),
+            finality_update.clone(),
+            26,
+            false,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![22, 26]);
+        assert!(matches!(selected[0].0, ActiveSyncCommittee::Next(_)));
+        assert!(matches!(selected[1].0, ActiveSyncCommittee::Current(_)));
+
+        let selected = select_light_client_updates(
+            &client_state,
+            true,
+            &period_updates(),
+            finality_update,
+            26,
+            true,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![14, 22, 26]);
+    }
+
+    #[test]
+    fn test_select_light_client_updates_min_slot_advance() {
+        let mut client_state = sync_committee_client_state(12);
+        client_state.min_slot_advance = Some(5);
+
+        // The updates of slots 14 and 26 advance the client by too few slots within their period,
+        // while the update of slot 22 moves it to the next period
+        let selected = select_light_client_updates(
+            &client_state,
+            true,
+            &period_updates(),
+            light_client_update(28, 26, 29, false),
+            22,
+            true,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![22]);
+
+        let selected = select_light_client_updates(
+            &client_state,
+            true,
+            &period_updates(),
+            light_client_update(28, 27, 29, false),
+            27,
+            true,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![22, 27]);
+    }
+
+    #[test]
+    fn test_select_light_client_updates_with_known_committees() {
+        // Without the update of period 0, the committee of the trusted period 1 is unknown
+        let client_state = sync_committee_client_state(12);
+        let light_client_updates = &period_updates()[1..];
+        let finality_update = light_client_update(28, 26, 29, false);
+        assert!(select_light_client_updates(
+            &client_state,
+            true,
+            light_client_updates,
+            finality_update.clone(),
+            26,
+            true,
+        )
+        .is_err());
+
+        let known_sync_committees = BTreeMap::from([(1, SyncCommittee::default())]);
+        let selected = select_light_client_updates_with_committees(
+            &client_state,
+            true,
+            light_client_updates,
+            &known_sync_committees,
+            finality_update,
+            26,
+            true,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![14, 22, 26]);
+    }
+
+    #[test]
+    fn test_select_light_client_updates_learns_next_sync_committee() {
+        // Without a next sync committee, the client cannot verify the update of the next period
+        // before the update of its own period is applied.
+        let selected = select_light_client_updates(
+            &sync_committee_client_state(12),
+            false,
+            &period_updates(),
+            light_client_update(28, 26, 29, false),
+            26,
+            false,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![14, 22, 26]);
+        assert!(matches!(selected[0].0, ActiveSyncCommittee::Current(_)));
+    }
+
+    #[test]
+    fn test_select_light_client_updates_signed_in_next_period() {
+        // The finality update is finalized in the trusted period, but signed in the next one.
+        let selected = select_light_client_updates(
+            &sync_committee_client_state(12),
+            true,
+            &period_updates()[..2],
+            light_client_update(19, 18, 20, false),
+            18,
+            false,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![18]);
+        assert!(matches!(selected[0].0, ActiveSyncCommittee::Next(_)));
+
+        // Without a next sync committee, the update of the trusted period teaches it first
+        let selected = select_light_client_updates(
+            &sync_committee_client_state(12),
+            false,
+            &period_updates()[..2],
+            light_client_update(19, 18, 20, false),
+            18,
+            false,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![14, 18]);
+        assert!(matches!(selected[0].0, ActiveSyncCommittee::Current(_)));
+        assert!(matches!(selected[1].0, ActiveSyncCommittee::Next(_)));
+
+        // The next sync committee cannot be learned from an update older than the trusted slot
+        let err = select_light_client_updates(
+            &sync_committee_client_state(16),
+            false,
+            &period_updates()[..2],
+            light_client_update(19, 18, 20, false),
+            18,
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("signed in period 2"));
+    }
+
+    fn with_participants(mut update: LightClientUpdate, participants: usize) -> LightClientUpdate {
+        // A sync committee of 16 members
+        let mut bits = vec![0_u8; 2];
+        for i in 0..participants {
+            bits[i / 8] |= 1 << (i % 8);
+        }
+        update.sync_aggregate.sync_committee_bits = bits.into();
+        update
+    }
+
+    #[test]
+    fn test_is_better_update() {
+        let client_state = sync_committee_client_state(12);
+        let update = light_client_update(15, 14, 16, true);
+
+        // A supermajority wins over more participants without one
+        assert!(is_better_update(
+            &client_state,
+            &with_participants(update.clone(), 11),
+            &with_participants(update.clone(), 10),
+        ));
+        assert!(!is_better_update(
+            &client_state,
+            &with_participants(update.clone(), 10),
+            &with_participants(update.clone(), 11),
+        ));
+
+        // The relevant sync committee wins over more participants beyond the supermajority
+        let without_sync_committee = light_client_update(15, 14, 16, false);
+        assert!(is_better_update(
+            &client_state,
+            &with_participants(update.clone(), 11),
+            &with_participants(without_sync_committee.clone(), 16),
+        ));
+
+        // A sync committee signed in the next period is not relevant to the attested period
+        let signed_in_next_period = light_client_update(19, 18, 20, true);
+        assert!(is_better_update(
+            &client_state,
+            &with_participants(update.clone(), 11),
+            &with_participants(signed_in_next_period, 16),
+        ));
+
+        // Older data wins ties
+        let older = light_client_update(13, 12, 14, true);
+        assert!(is_better_update(
+            &client_state,
+            &with_participants(older.clone(), 12),
+            &with_participants(update.clone(), 12),
+        ));
+        assert!(!is_better_update(
+            &client_state,
+            &with_participants(update, 12),
+            &with_participants(older, 12),
+        ));
+    }
+
+    #[test]
+    fn test_select_light_client_updates_picks_best_update_per_period() {
+        let client_state = sync_committee_client_state(12);
+        let mut updates = period_updates()
+            .into_iter()
+            .map(|update| with_participants(update, 16))
+            .collect::<Vec<_>>();
+        // A competing low participation update of period 1, which would be selected first
+        updates.insert(
+            1,
+            with_participants(light_client_update(14, 13, 15, true), 4),
+        );
+
+        let best = best_light_client_updates(&client_state, &updates);
+        assert_eq!(best.len(), 3);
+        assert_eq!(best[1].finalized_header.beacon.slot, 14);
+
+        let selected = select_light_client_updates(
+            &client_state,
+            false,
+            &updates,
+            with_participants(light_client_update(28, 26, 29, false), 16),
+            26,
+            false,
+        )
+        .unwrap();
+        assert_eq!(selected_slots(&selected), vec![14, 22, 26]);
+    }
+
+    #[test]
+    fn test_proof_height_strategy() {
+        let client_state = sync_committee_client_state(12);
+        let finality_update = light_client_update(28, 26, 29, false);
+        let select = |min_block_number, intermediate_updates| {
+            select_light_client_updates(
+                &client_state,
+                true,
+                &period_updates(),
+                finality_update.clone(),
+                min_block_number,
+                intermediate_updates,
+            )
+            .unwrap()
+        };
+
+        // The latest strategy always anchors the proofs at the finalized slot
+        let selected = ProofHeightStrategy::Latest.apply(select(20, false), 20);
+        assert_eq!(selected_slots(&selected), vec![22, 26]);
+
+        // The minimal strategy stops at the first update covering the packets
+        let selected = ProofHeightStrategy::Minimal.apply(select(20, false), 20);
+        assert_eq!(selected_slots(&selected), vec![22]);
+        let selected = ProofHeightStrategy::Minimal.apply(select(14, true), 14);
+        assert_eq!(selected_slots(&selected), vec![14]);
+        let selected = ProofHeightStrategy::Minimal.apply(select(26, false), 26);
+        assert_eq!(selected_slots(&selected), vec![22, 26]);
+    }
+
+    #[test]
+    fn test_update_plan_is_reusable() {
+        let headers = [22, 26]
+            .into_iter()
+            .map(|finalized_slot| Header {
+                active_sync_committee: ActiveSyncCommittee::Current(SyncCommittee::default()),
+                consensus_update: light_client_update(
+                    finalized_slot + 2,
+                    finalized_slot,
+                    finalized_slot + 3,
+                    false,
+                ),
+                account_update: AccountUpdate::default(),
+            })
+            .collect::<Vec<_>>();
+        let plan = UpdatePlan::new(12, headers, 1_000);
+        assert_eq!(plan.proof_slot, 26);
+        assert_eq!(UpdatePlan::new(12, vec![], 1_000).proof_slot, 12);
+
+        let latest = ProofHeightStrategy::Latest;
+        assert!(plan.is_reusable(12, 20, latest, 999));
+        assert!(plan.is_reusable(12, 26, latest, 999));
+        // The plan does not cover the packets
+        assert!(!plan.is_reusable(12, 27, latest, 999));
+        // The client was updated since the plan was selected
+        assert!(!plan.is_reusable(22, 26, latest, 999));
+        // The validity window of the plan passed
+        assert!(!plan.is_reusable(12, 26, latest, 1_000));
+
+        // A lower header already covers the packets, which the minimal strategy anchors at
+        let minimal = ProofHeightStrategy::Minimal;
+        assert!(!plan.is_reusable(12, 20, minimal, 999));
+        assert!(plan.is_reusable(12, 23, minimal, 999));
+    }
+
+    #[test]
+    fn test_select_light_client_updates_requires_min_block_number() {
+        let result = select_light_client_updates(
+            &sync_committee_client_state(12),
+            true,
+            &period_updates(),
+            light_client_update(28, 26, 29, false),
+            30,
+            false,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_select_light_client_updates_through_generated_periods() {
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(1) + 9;
+        let (mut client_state, mut consensus_state) = generator.initial_states(trusted_slot);
+
+        // The update of the period preceding the trusted period holds the trusted sync committee
+        let period_updates = (0..=4)
+            .map(|period| generator.period_update(period))
+            .collect::<Vec<_>>();
+        let finality_slot = generator.period_start_slot(4) + 40;
+        let finality_update =
+            generator.light_client_update(finality_slot, finality_slot + 10, false);
+
+        let selected = select_light_client_updates(
+            &client_state,
+            true,
+            &period_updates,
+            finality_update,
+            finality_slot,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            selected_slots(&selected),
+            vec![
+                period_updates[2].finalized_header.beacon.slot,
+                period_updates[3].finalized_header.beacon.slot,
+                period_updates[4].finalized_header.beacon.slot,
+                finality_slot,
+            ]
+        );
+
+        // Every selected update is accepted by the light client, in order
+        for (active_sync_committee, update) in selected {
+            let header = Header {
+                active_sync_committee,
+                account_update: generator.account_update(update.finalized_header.beacon.slot),
+                consensus_update: update,
+            };
+            verify_header(
+                &consensus_state,
+                &client_state,
+                generator.timestamp_at_slot(header.consensus_update.signature_slot),
+                &header,
+                BlstVerifier,
+            )
+            .unwrap();
+
+            let (_, updated_consensus_state, updated_client_state) =
+                update_consensus_state(consensus_state, client_state, &header).unwrap();
+            consensus_state = updated_consensus_state;
+            client_state = updated_client_state.unwrap();
+        }
+        assert_eq!(client_state.latest_slot, finality_slot);
+        assert_eq!(
+            consensus_state.current_sync_committee,
+            generator.sync_committee(4).aggregate_pubkey
+        );
+    }
+
+    #[test]
+    fn test_header_batch_client_message() {
+        let client_message: serde_json::Value =
+            serde_json::from_slice(&header_batch_client_message(&[], None).unwrap()).unwrap();
+        assert_eq!(
+            client_message,
+            serde_json::json!({
+                "version": 1,
+                "type": "header_batch",
+                "data": BASE64.encode(b"[]"),
+            })
+        );
+
+        let client_message: serde_json::Value = serde_json::from_slice(
+            &header_batch_client_message(&[], Some("cosmos1relayer")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(client_message["relayer"], "cosmos1relayer");
+    }
+
+    #[test]
+    fn test_compress_client_message() {
+        let client_message = header_batch_client_message(&[], None).unwrap();
+        let compressed = compress_client_message(&client_message);
+        assert_eq!(compressed[0], COMPRESSED_CLIENT_MESSAGE_PREFIX);
+        assert_eq!(
+            miniz_oxide::inflate::decompress_to_vec_zlib(&compressed[1..]).unwrap(),
+            client_message
+        );
+    }
+
+    /// Returns the tx builder of the tests with the given options. The nodes it points to are
+    /// only connected to when queried.
+    fn tx_builder(config: TxBuilderConfig) -> TxBuilder<RootProvider> {
+        TxBuilder::new(
+            Address::ZERO,
+            RootProvider::new_http("http://localhost:8545".parse().unwrap()),
+            "http://localhost:5052".to_string(),
+            HttpClient::new("http://localhost:26657").unwrap(),
+            SIGNER.to_string(),
+            config,
+        )
+    }
+
+    #[test]
+    fn test_update_client_msgs_with_config() {
+        let generator = UpdateGenerator::new(16);
+        let (mut client_state, _) = generator.initial_states(generator.period_start_slot(1));
+        client_state.compressed_client_messages = true;
+        let headers = generator.period_headers(client_state.latest_slot, 3);
+        let raw_header = serde_json::to_vec(&headers[0]).unwrap();
+        let client_message = |config: TxBuilderConfig| {
+            let msgs = tx_builder(config)
+                .update_client_msgs("08-wasm-0", &client_state, &headers)
+                .unwrap();
+            assert_eq!(msgs.len(), headers.len());
+            assert_eq!(msgs[0].signer, SIGNER);
+            ClientMessage::decode(msgs[0].client_message.as_ref().unwrap().value.as_slice())
+                .unwrap()
+                .data
+        };
+
+        // The headers are sent raw by default
+        assert_eq!(client_message(TxBuilderConfig::default()), raw_header);
+
+        // The attributed headers are wrapped in an envelope
+        let attributed = client_message(TxBuilderConfig {
+            update_relayer: Some("cosmos1relayer".to_string()),
+            ..Default::default()
+        });
+        let envelope: serde_json::Value = serde_json::from_slice(&attributed).unwrap();
+        assert_eq!(envelope["type"], CLIENT_MESSAGE_TYPE_HEADER);
+        assert_eq!(envelope["relayer"], "cosmos1relayer");
+        assert_eq!(envelope["data"], BASE64.encode(&raw_header));
+
+        // The compressed headers are only sent to the clients that accept them
+        let compressed = client_message(TxBuilderConfig {
+            compress_client_messages: true,
+            ..Default::default()
+        });
+        assert_eq!(compressed[0], COMPRESSED_CLIENT_MESSAGE_PREFIX);
+        assert_eq!(
+            miniz_oxide::inflate::decompress_to_vec_zlib(&compressed[1..]).unwrap(),
+            raw_header
+        );
+        client_state.compressed_client_messages = false;
+        assert_eq!(
+            client_message(TxBuilderConfig {
+                compress_client_messages: true,
+                ..Default::default()
+            }),
+            raw_header
+        );
+    }
+
+    #[test]
+    fn test_ibc_commitment_slot_is_derived_by_default() {
+        // The router is not queried, so no node is needed
+        let slot = futures::executor::block_on(
+            tx_builder(TxBuilderConfig::default()).ibc_commitment_slot(),
+        )
+        .unwrap();
+        assert_eq!(slot, U256::from_be_slice(&ICS26_IBC_STORAGE_SLOT));
+    }
+
+    #[test]
+    fn test_beacon_client_state() {
+        let genesis = Genesis {
+            genesis_time: 1_695_902_400,
+            genesis_validators_root: B256::with_last_byte(1),
+            genesis_fork_version: B32::new([1, 1, 112, 0]),
+        };
+        let spec = Spec {
+            seconds_per_slot: 12,
+            slots_per_epoch: 32,
+            epochs_per_sync_committee_period: 256,
+            sync_committee_size: 512,
+            genesis_fork_version: B32::new([1, 1, 112, 0]),
+            altair_fork_epoch: 0,
+            bellatrix_fork_epoch: 0,
+            capella_fork_epoch: 256,
+            deneb_fork_epoch: 29_696,
+            electra_fork_epoch: 115_968,
+            ..Default::default()
+        };
+
+        let client_state = beacon_client_state(&genesis, &spec).unwrap();
+        assert_eq!(client_state.genesis_time, genesis.genesis_time);
+        assert_eq!(
+            client_state.genesis_validators_root,
+            genesis.genesis_validators_root
+        );
+        assert_eq!(client_state.min_sync_committee_participants, 171);
+        assert_eq!(client_state.fork_parameters, spec.to_fork_parameters());
+        assert_eq!(client_state.slots_per_epoch, 32);
+
+        // A genesis served for another network than the spec
+        let other_genesis = Genesis {
+            genesis_fork_version: B32::new([0, 0, 0, 0]),
+            ..genesis.clone()
+        };
+        assert!(beacon_client_state(&other_genesis, &spec).is_err());
+
+        // A spec without time parameters
+        let unset_spec = Spec {
+            seconds_per_slot: 0,
+            ..spec.clone()
+        };
+        assert!(beacon_client_state(&genesis, &unset_spec).is_err());
+
+        // A fork schedule out of order
+        let unordered_spec = Spec {
+            deneb_fork_epoch: 100,
+            ..spec
+        };
+        assert!(beacon_client_state(&genesis, &unordered_spec).is_err());
+    }
+
+    #[test]
+    fn test_wait_for_confirmation_of_unfinalized_event() {
+        let clock = ManualClock::new(Duration::from_secs(1_700_000_000));
+        let slot_timing = SlotTiming::default();
+
+        // The finalized block advances by an epoch every few polls until it covers the event
+        let mut finalized_block_numbers = [32, 64, 64, 96].into_iter();
+        let mut polls = 0;
+        futures::executor::block_on(wait_for_confirmation(&clock, &slot_timing, 90, || {
+            polls += 1;
+            let finalized_block_number = finalized_block_numbers.next();
+            async move {
+                finalized_block_number.ok_or_else(|| anyhow::anyhow!("no finalized block"))
+            }
+        }))
+        .unwrap();
+        assert_eq!(polls, 4);
+        assert_eq!(
+            clock.now_since_unix().unwrap(),
+            Duration::from_secs(1_700_000_000) + slot_timing.slots(3)
+        );
+
+        // An event that is never confirmed times out after the finality timeout
+        let result = futures::executor::block_on(wait_for_confirmation(
+            &clock,
+            &slot_timing,
+            90,
+            || async { Ok(32) },
+        ));
+        assert!(result.is_err());
+        assert_eq!(
+            clock.now_since_unix().unwrap(),
+            Duration::from_secs(1_700_000_000)
+                + slot_timing.slots(3)
+                + slot_timing.finality_timeout()
+        );
+    }
+
+    #[test]
+    fn test_is_missing_consensus_state() {
+        // The error of the consensus state query
+        assert!(is_missing_consensus_state("No consensus state found"));
+        // The rejection log of the light client contract
+        assert!(is_missing_consensus_state(
+            "failed to execute message; message index: 1: consensus state not found: execute wasm contract failed"
+        ));
+        assert!(!is_missing_consensus_state("client state not found"));
+        assert!(!is_missing_consensus_state(
+            "membership proof verification failed"
+        ));
+
+        let err: anyhow::Error = StaleProof {
+            client_id: "08-wasm-0".to_string(),
+            slot: 64,
+        }
+        .into();
+        assert!(err.is::<StaleProof>());
+        assert!(!anyhow::anyhow!("No consensus state found").is::<StaleProof>());
+    }
 }
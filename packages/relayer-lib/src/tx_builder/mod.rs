@@ -1,10 +1,13 @@
 //! This module defines the [`TxBuilderService`] trait and some of its implementations.
 //! This interface is used to generate proofs and submit transactions to a chain.
 
+pub mod composition;
 pub mod cosmos_to_cosmos;
-#[cfg(feature = "sp1-toolchain")]
+#[cfg(feature = "sp1-prover")]
 pub mod cosmos_to_eth;
+#[cfg(feature = "eth-to-cosmos")]
 pub mod eth_to_cosmos;
+pub mod plan;
 mod r#trait;
 
 #[allow(clippy::module_name_repetitions)]
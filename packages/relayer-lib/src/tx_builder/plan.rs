@@ -0,0 +1,265 @@
+//! This module defines [`TxPlan`], the gas planning report of a relay transaction.
+//!
+//! Every message in a relay transaction is annotated with an estimated gas cost based on its
+//! kind, so that operators and batching logic can reason about the transaction before it is
+//! submitted. When the tx builder can simulate the transaction, the measured gas is included too.
+//!
+//! The serialized size of every message is tracked as well, so that a transaction exceeding the
+//! [`TxSizeLimit`] of its chain is planned as consecutive transactions fitting within the limit
+//! instead of failing when it is broadcast.
+
+use std::ops::Range;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// The kind of a message in a relay transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MsgKind {
+    /// A light client update.
+    UpdateClient,
+    /// A packet receive.
+    RecvPacket,
+    /// A packet acknowledgement.
+    Acknowledgement,
+    /// A packet timeout.
+    Timeout,
+    /// Any other message.
+    Other,
+}
+
+/// The estimated gas cost of each message kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasEstimates {
+    /// The estimated gas of a light client update.
+    pub update_client: u64,
+    /// The estimated gas of a packet receive.
+    pub recv_packet: u64,
+    /// The estimated gas of a packet acknowledgement.
+    pub acknowledgement: u64,
+    /// The estimated gas of a packet timeout.
+    pub timeout: u64,
+    /// The estimated gas of any other message.
+    pub other: u64,
+}
+
+/// The gas annotation of a single message in a relay transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MsgGasEstimate {
+    /// The kind of the message.
+    pub kind: MsgKind,
+    /// The estimated gas of the message.
+    pub estimated_gas: u64,
+    /// The bytes the message adds to the serialized transaction.
+    #[serde(default)]
+    pub size: usize,
+}
+
+/// The planning report of a relay transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxPlan {
+    /// The gas annotations of the messages, in the order they appear in the transaction.
+    pub msgs: Vec<MsgGasEstimate>,
+    /// The sum of the estimated gas of all messages.
+    pub estimated_gas: u64,
+    /// The gas measured by simulating the transaction, if available.
+    pub simulated_gas: Option<u64>,
+    /// The size of the serialized transaction in bytes.
+    #[serde(default)]
+    pub size: usize,
+}
+
+/// The size limit of the relay transactions submitted to a chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxSizeLimit {
+    /// The maximum size of a transaction in bytes, e.g. the `max_tx_bytes` of a Cosmos SDK chain
+    /// or the calldata budget of an Ethereum transaction.
+    pub max_tx_bytes: usize,
+    /// The bytes reserved for the parts of a transaction not built by the relayer, such as the
+    /// signatures and the fee of a Cosmos SDK transaction or the envelope of an Ethereum one.
+    #[serde(default)]
+    pub reserved_bytes: usize,
+}
+
+impl MsgKind {
+    /// Returns the message kind of a Cosmos SDK message from its type url.
+    #[must_use]
+    pub fn from_cosmos_type_url(type_url: &str) -> Self {
+        match type_url.rsplit('.').next() {
+            Some("MsgUpdateClient") => Self::UpdateClient,
+            Some("MsgRecvPacket") => Self::RecvPacket,
+            Some("MsgAcknowledgement") => Self::Acknowledgement,
+            Some("MsgTimeout") => Self::Timeout,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl GasEstimates {
+    /// The default estimates for messages submitted to a Cosmos SDK chain.
+    ///
+    /// Client updates are sized for the Ethereum wasm light client, which verifies the sync
+    /// committee signature of every update.
+    pub const COSMOS_SDK: Self = Self {
+        update_client: 1_000_000,
+        recv_packet: 200_000,
+        acknowledgement: 150_000,
+        timeout: 150_000,
+        other: 100_000,
+    };
+
+    /// The default estimates for calls submitted to the `ICS26Router` on Ethereum.
+    ///
+    /// The client update is proven together with the packets, so its cost is attributed to the
+    /// packet calls rather than a separate message.
+    pub const ETHEREUM: Self = Self {
+        update_client: 0,
+        recv_packet: 300_000,
+        acknowledgement: 200_000,
+        timeout: 200_000,
+        other: 100_000,
+    };
+
+    /// Returns the estimated gas of a message of the given kind.
+    #[must_use]
+    pub const fn gas(&self, kind: MsgKind) -> u64 {
+        match kind {
+            MsgKind::UpdateClient => self.update_client,
+            MsgKind::RecvPacket => self.recv_packet,
+            MsgKind::Acknowledgement => self.acknowledgement,
+            MsgKind::Timeout => self.timeout,
+            MsgKind::Other => self.other,
+        }
+    }
+}
+
+impl TxSizeLimit {
+    /// The default limit of a Cosmos SDK chain, the default `max_tx_bytes` of the `CometBFT`
+    /// mempool, with room for the signatures and the fee.
+    pub const COSMOS_SDK: Self = Self {
+        max_tx_bytes: 1_048_576,
+        reserved_bytes: 2_048,
+    };
+
+    /// The default limit of Ethereum, the largest transaction accepted by the geth transaction
+    /// pool, with room for the envelope of the transaction and the multicall encoding.
+    pub const ETHEREUM: Self = Self {
+        max_tx_bytes: 131_072,
+        reserved_bytes: 1_024,
+    };
+
+    /// Returns the bytes available to the messages of a transaction.
+    #[must_use]
+    pub const fn msgs_budget(&self) -> usize {
+        self.max_tx_bytes.saturating_sub(self.reserved_bytes)
+    }
+}
+
+impl TxPlan {
+    /// Creates a plan for a transaction with messages of the given kinds and sizes.
+    #[must_use]
+    pub fn new(msgs: impl IntoIterator<Item = (MsgKind, usize)>, estimates: &GasEstimates) -> Self {
+        let msgs = msgs
+            .into_iter()
+            .map(|(kind, size)| MsgGasEstimate {
+                kind,
+                estimated_gas: estimates.gas(kind),
+                size,
+            })
+            .collect::<Vec<_>>();
+        let estimated_gas = msgs.iter().map(|msg| msg.estimated_gas).sum();
+        let size = msgs.iter().map(|msg| msg.size).sum();
+
+        Self {
+            msgs,
+            estimated_gas,
+            simulated_gas: None,
+            size,
+        }
+    }
+
+    /// Sets the size of the serialized transaction.
+    #[must_use]
+    pub const fn with_size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Splits the messages of the transaction into consecutive batches, each fitting within the
+    /// size limit. The batches are to be submitted in order, since the packet messages are proven
+    /// against the client updates preceding them.
+    /// # Returns
+    /// The index ranges of the messages of each batch, which is a single batch of all the
+    /// messages if the transaction fits within the limit.
+    /// # Errors
+    /// Returns an error if a single message exceeds the limit.
+    pub fn split_by_size(&self, limit: &TxSizeLimit) -> Result<Vec<Range<usize>>> {
+        let budget = limit.msgs_budget();
+        let mut batches = Vec::new();
+        let mut start = 0;
+        let mut batch_size = 0;
+        for (i, msg) in self.msgs.iter().enumerate() {
+            anyhow::ensure!(
+                msg.size <= budget,
+                "message {i} ({:?}) of {} bytes exceeds the size limit of {} bytes",
+                msg.kind,
+                msg.size,
+                budget
+            );
+            if batch_size + msg.size > budget {
+                batches.push(start..i);
+                start = i;
+                batch_size = 0;
+            }
+            batch_size += msg.size;
+        }
+        batches.push(start..self.msgs.len());
+        Ok(batches)
+    }
+
+    /// Sets the gas measured by simulating the transaction.
+    #[must_use]
+    pub const fn with_simulated_gas(mut self, simulated_gas: Option<u64>) -> Self {
+        self.simulated_gas = simulated_gas;
+        self
+    }
+
+    /// Returns the number of messages of the given kind.
+    #[must_use]
+    pub fn count(&self, kind: MsgKind) -> usize {
+        self.msgs.iter().filter(|msg| msg.kind == kind).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn plan(sizes: &[usize]) -> TxPlan {
+        TxPlan::new(
+            sizes.iter().map(|size| (MsgKind::RecvPacket, *size)),
+            &GasEstimates::COSMOS_SDK,
+        )
+    }
+
+    #[test]
+    fn test_split_by_size() {
+        let limit = TxSizeLimit {
+            max_tx_bytes: 110,
+            reserved_bytes: 10,
+        };
+
+        assert_eq!(
+            plan(&[30, 30, 30]).split_by_size(&limit).unwrap(),
+            vec![0..3]
+        );
+        assert_eq!(
+            plan(&[60, 40, 50, 100, 1]).split_by_size(&limit).unwrap(),
+            vec![0..2, 2..3, 3..4, 4..5]
+        );
+        assert_eq!(plan(&[]).split_by_size(&limit).unwrap(), vec![0..0]);
+        // A single message exceeding the limit cannot be split
+        assert!(plan(&[30, 101]).split_by_size(&limit).is_err());
+    }
+}
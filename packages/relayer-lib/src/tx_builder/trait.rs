@@ -1,4 +1,5 @@
-use crate::chain::Chain;
+use super::plan::TxPlan;
+use crate::{chain::Chain, relay_scope::RelayScope};
 use anyhow::Result;
 use std::collections::HashMap;
 
@@ -9,7 +10,11 @@ pub trait TxBuilderService<A: Chain, B: Chain> {
     /// Generate a transaction to chain A based on the events from chain A and chain B.
     /// Events from chain A are often used for timeout purposes and can be left empty.
     ///
+    /// The finality wait, the header build and the proof fetch of the relay run within `scope`,
+    /// which interrupts them once its deadline passes or the relay is cancelled.
+    ///
     /// # Arguments
+    /// - `scope` - The scope of the sub-tasks of the relay.
     /// - `src_events` - The events from chain B.
     /// - `target_events` - The events from chain A.
     /// - `src_client_id` - The client ID on chain B.
@@ -21,8 +26,10 @@ pub trait TxBuilderService<A: Chain, B: Chain> {
     ///
     /// # Returns
     /// The relay transaction bytes.
+    #[allow(clippy::too_many_arguments)]
     async fn relay_events(
         &self,
+        scope: &RelayScope,
         src_events: Vec<A::Event>,
         target_events: Vec<B::Event>,
         src_client_id: String,
@@ -39,4 +46,14 @@ pub trait TxBuilderService<A: Chain, B: Chain> {
     /// # Returns
     /// The relay transaction bytes.
     async fn create_client(&self, parameters: &HashMap<String, String>) -> Result<Vec<u8>>;
+
+    /// Annotate the messages of a transaction produced by [`Self::relay_events`] with their
+    /// estimated gas, including the simulated gas of the whole transaction if available.
+    ///
+    /// # Arguments
+    /// - `tx` - The relay transaction bytes.
+    ///
+    /// # Returns
+    /// The planning report of the transaction.
+    async fn plan_tx(&self, tx: &[u8]) -> Result<TxPlan>;
 }
@@ -0,0 +1,63 @@
+//! This module defines [`BlstVerifier`], a native BLS verifier used to verify beacon light client
+//! updates in the relayer before they are submitted.
+
+use alloy::primitives::B256;
+use anyhow::anyhow;
+use blst::{
+    min_pk::{AggregatePublicKey, PublicKey, Signature},
+    BLST_ERROR,
+};
+use ethereum_light_client::verify::BlsVerify;
+use ethereum_types::consensus::bls::{BlsPublicKey, BlsSignature};
+
+/// The domain separation tag used for BLS signatures on the Ethereum consensus layer.
+const ETH_BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// The BLS verifier backed by `blst`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlstVerifier;
+
+impl BlsVerify for BlstVerifier {
+    type Error = anyhow::Error;
+
+    fn fast_aggregate_verify(
+        &self,
+        public_keys: &[BlsPublicKey],
+        msg: B256,
+        signature: BlsSignature,
+    ) -> Result<(), Self::Error> {
+        let public_keys = decode_public_keys(public_keys)?;
+        let public_keys = public_keys.iter().collect::<Vec<_>>();
+
+        let signature = Signature::sig_validate(signature.as_slice(), true)
+            .map_err(|err| anyhow!("Invalid BLS signature: {err:?}"))?;
+
+        match signature.fast_aggregate_verify(false, msg.as_slice(), ETH_BLS_DST, &public_keys) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            err => Err(anyhow!("BLS signature verification failed: {err:?}")),
+        }
+    }
+
+    fn aggregate(&self, public_keys: &[BlsPublicKey]) -> Result<BlsPublicKey, Self::Error> {
+        let public_keys = decode_public_keys(public_keys)?;
+        let public_keys = public_keys.iter().collect::<Vec<_>>();
+
+        let aggregate_public_key = AggregatePublicKey::aggregate(&public_keys, false)
+            .map_err(|err| anyhow!("Failed to aggregate BLS public keys: {err:?}"))?;
+
+        Ok(BlsPublicKey::from(
+            aggregate_public_key.to_public_key().compress(),
+        ))
+    }
+}
+
+/// Decodes and validates the compressed BLS public keys.
+fn decode_public_keys(public_keys: &[BlsPublicKey]) -> anyhow::Result<Vec<PublicKey>> {
+    public_keys
+        .iter()
+        .map(|public_key| {
+            PublicKey::key_validate(public_key.as_slice())
+                .map_err(|err| anyhow!("Invalid BLS public key {public_key}: {err:?}"))
+        })
+        .collect()
+}
@@ -1,23 +1,127 @@
 //! Relayer utilities for `CosmosSDK` chains.
 
-use alloy::{hex, primitives::U256, providers::Provider};
+#[cfg(feature = "eth-to-cosmos")]
+use alloy::{
+    hex,
+    primitives::{TxHash, U256},
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+};
 use anyhow::Result;
-use ethereum_apis::{beacon_api::client::BeaconApiClient, eth_api::client::EthApiClient};
-use ethereum_light_client::membership::evm_ics26_commitment_path;
+#[cfg(feature = "eth-to-cosmos")]
+use ethereum_apis::{
+    beacon_api::client::BeaconApiClient,
+    eth_api::{
+        client::{pinned_block, EthApiClient, ProofRequest},
+        error::EthClientError,
+    },
+};
+#[cfg(feature = "eth-to-cosmos")]
+use ethereum_light_client::{
+    client_state::ClientState,
+    consensus_state::ConsensusState,
+    membership::{evm_ics26_commitment_path, verify_membership, verify_non_membership},
+};
+#[cfg(feature = "eth-to-cosmos")]
 use ethereum_types::execution::storage_proof::StorageProof;
 use futures::future;
+#[cfg(feature = "eth-to-cosmos")]
 use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs::Packet;
 use ibc_eureka_utils::rpc::TendermintRpcExt;
 use ibc_proto_eureka::{
+    cosmos::tx::v1beta1::TxBody,
+    google::protobuf::Any,
     ibc::core::{
         channel::v2::{Acknowledgement, MsgAcknowledgement, MsgRecvPacket, MsgTimeout},
         client::v1::Height,
     },
     Protobuf,
 };
+use prost::Message;
 use tendermint_rpc::HttpClient;
 
-use crate::events::{EurekaEvent, EurekaEventWithHeight};
+use crate::{
+    events::{EurekaEvent, EurekaEventWithHeight},
+    tx_builder::plan::{GasEstimates, MsgKind, TxPlan},
+};
+
+/// The messages built from relayed events, before any proofs are injected.
+#[cfg(feature = "eth-to-cosmos")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelayMsgs {
+    /// The timeout messages built from the target events.
+    pub timeout_msgs: Vec<MsgTimeout>,
+    /// The recv messages built from the source events.
+    pub recv_msgs: Vec<MsgRecvPacket>,
+    /// The ack messages built from the source events.
+    pub ack_msgs: Vec<MsgAcknowledgement>,
+}
+
+#[cfg(feature = "eth-to-cosmos")]
+impl RelayMsgs {
+    /// Returns `true` if there are no messages to relay.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.timeout_msgs.is_empty() && self.recv_msgs.is_empty() && self.ack_msgs.is_empty()
+    }
+
+    /// Returns the total number of messages.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.timeout_msgs.len() + self.recv_msgs.len() + self.ack_msgs.len()
+    }
+
+    /// Appends the messages of `other` after the messages of `self`.
+    #[must_use]
+    pub fn concat(mut self, other: Self) -> Self {
+        self.timeout_msgs.extend(other.timeout_msgs);
+        self.recv_msgs.extend(other.recv_msgs);
+        self.ack_msgs.extend(other.ack_msgs);
+        self
+    }
+}
+
+/// Converts the source and target events to the [`RelayMsgs`] relayed to the destination chain.
+///
+/// This is the pure event to message mapping shared by the tx builders, see
+/// [`target_events_to_timeout_msgs`] and [`src_events_to_recv_and_ack_msgs`] for the arguments.
+#[allow(clippy::too_many_arguments)]
+#[must_use]
+#[cfg(feature = "eth-to-cosmos")]
+pub fn events_to_relay_msgs(
+    src_events: Vec<EurekaEventWithHeight>,
+    target_events: Vec<EurekaEventWithHeight>,
+    src_client_id: &str,
+    dst_client_id: &str,
+    src_packet_seqs: &[u64],
+    dst_packet_seqs: &[u64],
+    signer_address: &str,
+    now: u64,
+) -> RelayMsgs {
+    let timeout_msgs = target_events_to_timeout_msgs(
+        target_events,
+        src_client_id,
+        dst_client_id,
+        dst_packet_seqs,
+        signer_address,
+        now,
+    );
+    let (recv_msgs, ack_msgs) = src_events_to_recv_and_ack_msgs(
+        src_events,
+        src_client_id,
+        dst_client_id,
+        src_packet_seqs,
+        dst_packet_seqs,
+        signer_address,
+        now,
+    );
+
+    RelayMsgs {
+        timeout_msgs,
+        recv_msgs,
+        ack_msgs,
+    }
+}
 
 /// Converts a list of [`EurekaEvent`]s to a list of [`MsgTimeout`]s.
 ///
@@ -138,11 +242,11 @@ pub async fn inject_tendermint_proofs(
     target_height: &Height,
 ) -> Result<()> {
     future::try_join_all(recv_msgs.iter_mut().map(|msg| async {
-        let packet: Packet = msg.packet.clone().unwrap().into();
-        let commitment_path = packet.commitment_path();
+        let packet = msg.packet.as_ref().unwrap();
         let (value, proof) = source_tm_client
-            .prove_path(
-                &[b"ibc".to_vec(), commitment_path],
+            .prove_packet_commitment(
+                &packet.source_client,
+                packet.sequence,
                 target_height.revision_height,
             )
             .await?;
@@ -157,10 +261,13 @@ pub async fn inject_tendermint_proofs(
     .await?;
 
     future::try_join_all(ack_msgs.iter_mut().map(|msg| async {
-        let packet: Packet = msg.packet.clone().unwrap().into();
-        let ack_path = packet.ack_commitment_path();
+        let packet = msg.packet.as_ref().unwrap();
         let (value, proof) = source_tm_client
-            .prove_path(&[b"ibc".to_vec(), ack_path], target_height.revision_height)
+            .prove_packet_acknowledgement(
+                &packet.destination_client,
+                packet.sequence,
+                target_height.revision_height,
+            )
             .await?;
         if value.is_empty() {
             anyhow::bail!("Membership value is empty")
@@ -173,11 +280,11 @@ pub async fn inject_tendermint_proofs(
     .await?;
 
     future::try_join_all(timeout_msgs.iter_mut().map(|msg| async {
-        let packet: Packet = msg.packet.clone().unwrap().into();
-        let receipt_path = packet.receipt_commitment_path();
+        let packet = msg.packet.as_ref().unwrap();
         let (value, proof) = source_tm_client
-            .prove_path(
-                &[b"ibc".to_vec(), receipt_path],
+            .prove_packet_receipt(
+                &packet.destination_client,
+                packet.sequence,
                 target_height.revision_height,
             )
             .await?;
@@ -194,123 +301,255 @@ pub async fn inject_tendermint_proofs(
     Ok(())
 }
 
+/// Injects the Ethereum proofs of the messages at the proof height.
+///
+/// The proofs are fetched in a single JSON-RPC batch along with the current block number and the
+/// receipts of the `source_txs`, the transactions and block numbers of the relayed source events,
+/// to check that these events are still included where they were observed.
+/// # Errors
+/// Returns an error if a proof cannot be fetched or does not prove the expected value, if the
+/// node is behind the proof height, or if a source event is no longer included.
+#[cfg(feature = "eth-to-cosmos")]
 #[allow(clippy::too_many_arguments)]
 pub async fn inject_ethereum_proofs<P: Provider + Clone>(
     recv_msgs: &mut [MsgRecvPacket],
     ack_msgs: &mut [MsgAcknowledgement],
     timeout_msgs: &mut [MsgTimeout],
+    source_txs: &[(TxHash, u64)],
     eth_client: &EthApiClient<P>,
     beacon_api_client: &BeaconApiClient,
     ibc_contrct_address: &str,
     ibc_contract_slot: U256,
-    proof_slot: u64,
+    proof_height: Height,
 ) -> Result<()> {
     let current_beacon_block = beacon_api_client
-        .beacon_block(&format!("{proof_slot:?}"))
+        .beacon_block(&format!("{:?}", proof_height.revision_height))
         .await?;
+    let execution_payload = current_beacon_block.message.body.execution_payload;
 
-    let proof_block_number = current_beacon_block
-        .message
-        .body
-        .execution_payload
-        .block_number;
+    // Pin the proofs to the execution block of the proof height, so that load balanced providers
+    // cannot answer them from another block or fork.
+    let proof_block = pinned_block(execution_payload.block_hash);
 
-    let proof_slot_height = Height {
-        revision_number: 0,
-        revision_height: proof_slot,
-    };
-    // recv messages
-    future::try_join_all(recv_msgs.iter_mut().map(|msg| async {
-        let packet: Packet = msg.packet.clone().unwrap().into();
-        let commitment_path = packet.commitment_path();
-        let storage_proof = get_commitment_proof(
-            eth_client,
-            ibc_contrct_address,
-            proof_block_number,
-            commitment_path,
-            ibc_contract_slot,
+    let paths = commitment_paths(recv_msgs, ack_msgs, timeout_msgs)?;
+    let proof_requests = paths
+        .iter()
+        .map(|path| ProofRequest {
+            address: ibc_contrct_address.to_string(),
+            storage_keys: vec![hex::encode(
+                evm_ics26_commitment_path(path, ibc_contract_slot).to_be_bytes_vec(),
+            )],
+            block: proof_block,
+        })
+        .collect();
+
+    let response = eth_client
+        .batch(
+            proof_requests,
+            source_txs.iter().map(|(tx_hash, _)| *tx_hash).collect(),
         )
         .await?;
+
+    let block_number = response.block_number?;
+    if block_number < execution_payload.block_number {
+        anyhow::bail!(
+            "The execution node is at block {block_number}, behind the proof block {}",
+            execution_payload.block_number
+        );
+    }
+    ensure_source_txs_included(source_txs, response.receipts)?;
+
+    let mut storage_proofs = response
+        .proofs
+        .into_iter()
+        .map(|proof| -> Result<StorageProof> {
+            let storage_proof = proof?
+                .storage_proof
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("No storage proof in the response"))?;
+            Ok(StorageProof {
+                key: storage_proof.key.as_b256(),
+                value: storage_proof.value,
+                proof: storage_proof.proof,
+            })
+        });
+    let mut next_proof = || -> Result<StorageProof> {
+        storage_proofs
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Missing proof in the batch response"))?
+    };
+
+    for msg in recv_msgs {
+        let storage_proof = next_proof()?;
         if storage_proof.value.is_zero() {
             anyhow::bail!("Membership value is empty")
         }
-
         msg.proof_commitment = serde_json::to_vec(&storage_proof)?;
-        msg.proof_height = Some(proof_slot_height);
-        anyhow::Ok(())
-    }))
-    .await?;
+        msg.proof_height = Some(proof_height);
+    }
 
-    // ack messages
-    future::try_join_all(ack_msgs.iter_mut().map(|msg| async {
-        let packet: Packet = msg.packet.clone().unwrap().into();
-        let ack_path = packet.ack_commitment_path();
-        let storage_proof = get_commitment_proof(
-            eth_client,
-            ibc_contrct_address,
-            proof_block_number,
-            ack_path,
-            ibc_contract_slot,
-        )
-        .await?;
+    for msg in ack_msgs {
+        let storage_proof = next_proof()?;
         if storage_proof.value.is_zero() {
             anyhow::bail!("Membership value is empty")
         }
-
         msg.proof_acked = serde_json::to_vec(&storage_proof)?;
-        msg.proof_height = Some(proof_slot_height);
-        anyhow::Ok(())
-    }))
-    .await?;
+        msg.proof_height = Some(proof_height);
+    }
 
-    // timeout messages
-    future::try_join_all(timeout_msgs.iter_mut().map(|msg| async {
-        let packet: Packet = msg.packet.clone().unwrap().into();
-        let receipt_path = packet.receipt_commitment_path();
-        let storage_proof = get_commitment_proof(
-            eth_client,
-            ibc_contrct_address,
-            proof_block_number,
-            receipt_path,
-            ibc_contract_slot,
-        )
-        .await?;
+    for msg in timeout_msgs {
+        let storage_proof = next_proof()?;
         if !storage_proof.value.is_zero() {
             anyhow::bail!("Non-Membership value is empty")
         }
         msg.proof_unreceived = serde_json::to_vec(&storage_proof)?;
-        msg.proof_height = Some(proof_slot_height);
-        anyhow::Ok(())
-    }))
-    .await?;
+        msg.proof_height = Some(proof_height);
+    }
 
     Ok(())
 }
 
-async fn get_commitment_proof<P: Provider + Clone>(
-    eth_client: &EthApiClient<P>,
-    ibc_contrct_address: &str,
-    block_number: u64,
-    path: Vec<u8>,
-    slot: U256,
-) -> Result<StorageProof> {
-    let storage_key = evm_ics26_commitment_path(&path, slot);
-    let storage_key_be_bytes = storage_key.to_be_bytes_vec();
-    let storage_key_hex = hex::encode(storage_key_be_bytes);
-    let block_hex = format!("0x{block_number:x}");
-
-    let proof = eth_client
-        .get_proof(ibc_contrct_address, vec![storage_key_hex], block_hex)
-        .await?;
-    let storage_proof = proof.storage_proof.first().unwrap();
+/// Returns the commitment paths proven by the recv, ack and timeout messages, in this order.
+#[cfg(feature = "eth-to-cosmos")]
+fn commitment_paths(
+    recv_msgs: &[MsgRecvPacket],
+    ack_msgs: &[MsgAcknowledgement],
+    timeout_msgs: &[MsgTimeout],
+) -> Result<Vec<Vec<u8>>> {
+    let packet = |packet: &Option<ibc_proto_eureka::ibc::core::channel::v2::Packet>| {
+        packet
+            .clone()
+            .map(Packet::from)
+            .ok_or_else(|| anyhow::anyhow!("Message without a packet"))
+    };
+    recv_msgs
+        .iter()
+        .map(|msg| Ok(packet(&msg.packet)?.commitment_path()))
+        .chain(
+            ack_msgs
+                .iter()
+                .map(|msg| Ok(packet(&msg.packet)?.ack_commitment_path())),
+        )
+        .chain(
+            timeout_msgs
+                .iter()
+                .map(|msg| Ok(packet(&msg.packet)?.receipt_commitment_path())),
+        )
+        .collect()
+}
+
+/// Checks that the source transactions are still included in the blocks their events were
+/// observed at, given their receipts.
+#[cfg(feature = "eth-to-cosmos")]
+fn ensure_source_txs_included(
+    source_txs: &[(TxHash, u64)],
+    receipts: Vec<Result<Option<TransactionReceipt>, EthClientError>>,
+) -> Result<()> {
+    for ((tx_hash, event_block_number), receipt) in source_txs.iter().zip(receipts) {
+        match receipt?.and_then(|receipt| receipt.block_number) {
+            Some(block_number) if block_number == *event_block_number => {}
+            Some(block_number) => anyhow::bail!(
+                "The source transaction {tx_hash} moved from block {event_block_number} to block {block_number}"
+            ),
+            None => anyhow::bail!(
+                "The source transaction {tx_hash} of block {event_block_number} is no longer included"
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies the injected Ethereum proofs of the messages locally, the way the light client on the
+/// target chain verifies them, against the consensus state at their proof height.
+///
+/// A single invalid proof fails the whole relay transaction on-chain, so all the messages are
+/// audited and the failures are reported per packet.
+/// # Errors
+/// Returns an error listing every message whose proof does not verify.
+#[cfg(feature = "eth-to-cosmos")]
+pub fn audit_ethereum_proofs(
+    recv_msgs: &[MsgRecvPacket],
+    ack_msgs: &[MsgAcknowledgement],
+    timeout_msgs: &[MsgTimeout],
+    client_state: &ClientState,
+    consensus_state: &ConsensusState,
+) -> Result<()> {
+    let verify_inclusion = |path: Vec<u8>, proof: &[u8], value: Option<Vec<u8>>| -> Result<()> {
+        // Without an expected value, the proven value only has to be included.
+        let value = match value {
+            Some(value) => value,
+            None => serde_json::from_slice::<StorageProof>(proof)?
+                .value
+                .to_be_bytes_vec(),
+        };
+        verify_membership(
+            consensus_state.clone(),
+            client_state.clone(),
+            proof.to_vec(),
+            vec![path],
+            value,
+        )?;
+        Ok(())
+    };
+
+    let recv_failures = recv_msgs.iter().filter_map(|msg| {
+        let packet: Packet = msg.packet.clone()?.into();
+        verify_inclusion(
+            packet.commitment_path(),
+            &msg.proof_commitment,
+            Some(packet.commitment()),
+        )
+        .err()
+        .map(|e| audit_failure("recv", &packet, &e))
+    });
+    // The acknowledgement commitment is computed by the source chain, so only its inclusion is
+    // audited.
+    let ack_failures = ack_msgs.iter().filter_map(|msg| {
+        let packet: Packet = msg.packet.clone()?.into();
+        verify_inclusion(packet.ack_commitment_path(), &msg.proof_acked, None)
+            .err()
+            .map(|e| audit_failure("ack", &packet, &e))
+    });
+    let timeout_failures = timeout_msgs.iter().filter_map(|msg| {
+        let packet: Packet = msg.packet.clone()?.into();
+        verify_non_membership(
+            consensus_state.clone(),
+            client_state.clone(),
+            msg.proof_unreceived.clone(),
+            vec![packet.receipt_commitment_path()],
+        )
+        .err()
+        .map(|e| audit_failure("timeout", &packet, &e.into()))
+    });
 
-    Ok(StorageProof {
-        key: storage_proof.key.as_b256(),
-        value: storage_proof.value,
-        proof: storage_proof.proof.clone(),
-    })
+    let failures = recv_failures
+        .chain(ack_failures)
+        .chain(timeout_failures)
+        .collect::<Vec<_>>();
+    if !failures.is_empty() {
+        anyhow::bail!(
+            "{} of the relayed proofs would fail verification at slot {}: {}",
+            failures.len(),
+            consensus_state.slot,
+            failures.join("; ")
+        );
+    }
+
+    Ok(())
 }
 
+/// Formats the audit failure of the proof of a packet.
+#[cfg(feature = "eth-to-cosmos")]
+fn audit_failure(kind: &str, packet: &Packet, err: &anyhow::Error) -> String {
+    format!(
+        "{kind} packet {} from {} to {}: {err}",
+        packet.sequence, packet.sourceClient, packet.destClient
+    )
+}
+
+#[cfg(feature = "eth-to-cosmos")]
 pub fn inject_mock_proofs(
     recv_msgs: &mut [MsgRecvPacket],
     ack_msgs: &mut [MsgAcknowledgement],
@@ -331,3 +570,83 @@ pub fn inject_mock_proofs(
         msg.proof_height = Some(Height::default());
     }
 }
+
+/// Annotates the messages of an encoded [`TxBody`] with their estimated gas and size.
+/// # Errors
+/// Returns an error if the transaction body cannot be decoded.
+pub fn plan_tx_body(tx: &[u8], gas_estimates: &GasEstimates) -> Result<TxPlan> {
+    let tx_body = TxBody::decode(tx)?;
+    Ok(TxPlan::new(
+        tx_body.messages.iter().map(|msg| {
+            (
+                MsgKind::from_cosmos_type_url(&msg.type_url),
+                tx_body_msg_size(msg),
+            )
+        }),
+        gas_estimates,
+    )
+    .with_size(tx.len()))
+}
+
+/// Returns the bytes a message adds to an encoded [`TxBody`], i.e. its encoding with the tag and
+/// the length of the `messages` field.
+#[must_use]
+pub fn tx_body_msg_size(msg: &Any) -> usize {
+    let len = msg.encoded_len();
+    1 + prost::length_delimiter_len(len) + len
+}
+
+#[cfg(all(test, feature = "eth-to-cosmos"))]
+mod test {
+    use alloy::primitives::{FixedBytes, B256};
+    use ibc_proto_eureka::ibc::core::channel::v2::Packet as ProtoPacket;
+
+    use super::*;
+
+    #[test]
+    fn test_audit_ethereum_proofs() {
+        let client_state = ClientState::default();
+        let consensus_state = ConsensusState {
+            slot: 42,
+            state_root: B256::ZERO,
+            storage_root: B256::repeat_byte(1),
+            timestamp: 0,
+            current_sync_committee: FixedBytes::ZERO,
+            next_sync_committee: None,
+        };
+        let packet = |sequence| ProtoPacket {
+            sequence,
+            source_client: "client-0".to_string(),
+            destination_client: "08-wasm-0".to_string(),
+            ..Default::default()
+        };
+        // A proof of the right key and value, but from another storage trie
+        let proof_of = |packet: ProtoPacket| {
+            let packet: Packet = packet.into();
+            serde_json::to_vec(&StorageProof {
+                key: evm_ics26_commitment_path(
+                    &packet.commitment_path(),
+                    client_state.ibc_commitment_slot,
+                )
+                .into(),
+                value: U256::from_be_slice(&packet.commitment()),
+                proof: vec![],
+            })
+            .unwrap()
+        };
+
+        assert!(audit_ethereum_proofs(&[], &[], &[], &client_state, &consensus_state).is_ok());
+
+        let recv_msgs = [1, 2].map(|sequence| MsgRecvPacket {
+            packet: Some(packet(sequence)),
+            proof_commitment: proof_of(packet(sequence)),
+            ..Default::default()
+        });
+        let err = audit_ethereum_proofs(&recv_msgs, &[], &[], &client_state, &consensus_state)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("2 of the relayed proofs would fail verification at slot 42"));
+        assert!(err.contains("recv packet 1 from client-0 to 08-wasm-0"));
+        assert!(err.contains("recv packet 2 from client-0 to 08-wasm-0"));
+    }
+}
@@ -1,31 +1,44 @@
 //! Relayer utilities for `solidity-ibc-eureka` chains.
+//!
+//! The proofs of the messages are generated with the SP1 prover, behind the `sp1-prover` feature.
 
-use alloy::{primitives::Bytes, sol_types::SolValue};
+#[cfg(feature = "sp1-prover")]
+use alloy::sol_types::SolValue;
+use alloy::{primitives::Bytes, sol_types::SolInterface};
 use anyhow::Result;
+#[cfg(feature = "sp1-prover")]
 use futures::future;
-use ibc_eureka_solidity_types::{
-    ics26::{
-        router::{ackPacketCall, recvPacketCall, routerCalls},
-        IICS02ClientMsgs::Height,
-        IICS26RouterMsgs::{MsgAckPacket, MsgRecvPacket, MsgTimeoutPacket},
-    },
-    msgs::{
-        IICS07TendermintMsgs::ClientState,
-        IMembershipMsgs::{KVPair, MembershipProof, SP1MembershipAndUpdateClientProof},
-        ISP1Msgs::SP1Proof,
-    },
+use ibc_eureka_solidity_types::ics26::{
+    router::{ackPacketCall, multicallCall, recvPacketCall, routerCalls},
+    IICS02ClientMsgs::Height,
+    IICS26RouterMsgs::{MsgAckPacket, MsgRecvPacket, MsgTimeoutPacket},
 };
+#[cfg(feature = "sp1-prover")]
+use ibc_eureka_solidity_types::msgs::{
+    IICS07TendermintMsgs::ClientState,
+    IMembershipMsgs::{KVPair, MembershipProof, SP1MembershipAndUpdateClientProof},
+    ISP1Msgs::SP1Proof,
+};
+#[cfg(feature = "sp1-prover")]
 use ibc_eureka_utils::{light_block::LightBlockExt, rpc::TendermintRpcExt};
+#[cfg(feature = "sp1-prover")]
 use sp1_ics07_tendermint_prover::{
     programs::UpdateClientAndMembershipProgram,
     prover::{SP1ICS07TendermintProver, Sp1Prover},
 };
+#[cfg(feature = "sp1-prover")]
 use sp1_prover::components::SP1ProverComponents;
+#[cfg(feature = "sp1-prover")]
 use sp1_sdk::HashableKey;
+#[cfg(feature = "sp1-prover")]
 use tendermint_light_client_verifier::types::LightBlock;
+#[cfg(feature = "sp1-prover")]
 use tendermint_rpc::HttpClient;
 
-use crate::events::{EurekaEvent, EurekaEventWithHeight};
+use crate::{
+    events::{EurekaEvent, EurekaEventWithHeight},
+    tx_builder::plan::MsgKind,
+};
 
 /// Converts a list of [`EurekaEvent`]s to a list of [`routerCalls::timeoutPacket`]s with empty
 /// proofs.
@@ -120,6 +133,7 @@ pub fn src_events_to_recv_and_ack_msgs(
 /// Generates and injects an SP1 proof into the first message in `msgs`.
 /// # Errors
 /// Returns an error if the sp1 proof cannot be generated.
+#[cfg(feature = "sp1-prover")]
 pub async fn inject_sp1_proof<C: SP1ProverComponents>(
     sp1_prover: &Sp1Prover<C>,
     uc_and_mem_program: &UpdateClientAndMembershipProgram,
@@ -193,3 +207,30 @@ pub async fn inject_sp1_proof<C: SP1ProverComponents>(
 
     Ok(())
 }
+
+/// Returns the message kinds of the calls batched in a [`multicallCall`], with the bytes each
+/// call adds to the calldata of the multicall.
+/// # Errors
+/// Returns an error if any of the calls cannot be decoded as a [`routerCalls`].
+pub fn multicall_msg_kinds(multicall: &multicallCall) -> Result<Vec<(MsgKind, usize)>> {
+    multicall
+        .data
+        .iter()
+        .map(|call| {
+            let kind = match routerCalls::abi_decode(call, true)? {
+                routerCalls::recvPacket(_) => MsgKind::RecvPacket,
+                routerCalls::ackPacket(_) => MsgKind::Acknowledgement,
+                routerCalls::timeoutPacket(_) => MsgKind::Timeout,
+                _ => MsgKind::Other,
+            };
+            Ok((kind, multicall_call_size(call)))
+        })
+        .collect()
+}
+
+/// Returns the bytes a call adds to the calldata of a [`multicallCall`], i.e. its offset, its
+/// length and its data padded to 32 bytes.
+#[must_use]
+pub const fn multicall_call_size(call: &Bytes) -> usize {
+    64 + call.len().div_ceil(32) * 32
+}
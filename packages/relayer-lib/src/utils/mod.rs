@@ -1,13 +1,19 @@
 //! This module contains the utilities for relayer implementations.
 
-use futures_timer::Delay;
+#[cfg(feature = "eth-to-cosmos")]
 use std::future::Future;
-use std::time::{Duration, Instant};
+#[cfg(feature = "eth-to-cosmos")]
+use std::time::Duration;
 
-/// Retries an operation until the condition is met or a timeout occurs.
+#[cfg(feature = "eth-to-cosmos")]
+use crate::clock::Clock;
+
+/// Retries an operation until the condition is met or a timeout occurs, as measured by `clock`.
 ///
 /// The basic version just checks for a boolean condition.
+#[cfg(feature = "eth-to-cosmos")]
 pub async fn wait_for_condition<F, Fut>(
+    clock: &dyn Clock,
     timeout: Duration,
     interval: Duration,
     mut condition: F,
@@ -16,8 +22,8 @@ where
     F: FnMut() -> Fut + Send,
     Fut: Future<Output = anyhow::Result<bool>> + Send,
 {
-    let start = Instant::now();
-    while start.elapsed() < timeout {
+    let start = clock.now_since_unix()?;
+    while clock.now_since_unix()?.saturating_sub(start) < timeout {
         if condition().await? {
             return Ok(());
         }
@@ -26,10 +32,144 @@ where
             "Condition not met. Waiting for {} seconds before retrying",
             interval.as_secs()
         );
-        Delay::new(interval).await;
+        clock.sleep(interval).await;
     }
     anyhow::bail!("Timeout exceeded")
 }
 
+/// The outcome of a poll of [`wait_for_condition_adaptive`].
+#[cfg(feature = "eth-to-cosmos")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConditionPoll {
+    /// The condition is met.
+    Met,
+    /// The condition is not met, and is not expected to be met before the given duration.
+    RetryAfter(Duration),
+}
+
+/// Retries an operation until the condition is met or a timeout occurs, as measured by `clock`.
+///
+/// Unlike [`wait_for_condition`], the condition returns how long to wait before the next poll, so
+/// that a condition expected to be met far in the future is not polled at a fixed interval. The
+/// waits are capped to the remaining time, and the condition is polled a last time at the
+/// timeout.
+#[cfg(feature = "eth-to-cosmos")]
+pub async fn wait_for_condition_adaptive<F, Fut>(
+    clock: &dyn Clock,
+    timeout: Duration,
+    mut condition: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut() -> Fut + Send,
+    Fut: Future<Output = anyhow::Result<ConditionPoll>> + Send,
+{
+    let start = clock.now_since_unix()?;
+    loop {
+        let ConditionPoll::RetryAfter(interval) = condition().await? else {
+            return Ok(());
+        };
+
+        let elapsed = clock.now_since_unix()?.saturating_sub(start);
+        if elapsed >= timeout {
+            anyhow::bail!("Timeout exceeded");
+        }
+        let interval = interval.min(timeout - elapsed);
+        tracing::debug!(
+            "Condition not met. Waiting for {} seconds before retrying",
+            interval.as_secs()
+        );
+        clock.sleep(interval).await;
+    }
+}
+
+#[cfg(feature = "eth-to-cosmos")]
+pub mod bls;
 pub mod cosmos;
+#[cfg(feature = "cosmos-to-eth")]
 pub mod eth_eureka;
+
+#[cfg(all(test, feature = "eth-to-cosmos"))]
+mod test {
+    use crate::clock::ManualClock;
+
+    use super::*;
+
+    #[test]
+    fn test_wait_for_condition() {
+        let clock = ManualClock::new(Duration::from_secs(1_700_000_000));
+        let mut polls = 0;
+        futures::executor::block_on(wait_for_condition(
+            &clock,
+            Duration::from_secs(60),
+            Duration::from_secs(12),
+            || {
+                polls += 1;
+                let ready = polls == 3;
+                async move { Ok(ready) }
+            },
+        ))
+        .unwrap();
+        assert_eq!(polls, 3);
+        assert_eq!(
+            clock.now_since_unix().unwrap(),
+            Duration::from_secs(1_700_000_024)
+        );
+
+        // Times out after the polls within the timeout, without sleeping
+        let mut polls = 0;
+        let result = futures::executor::block_on(wait_for_condition(
+            &clock,
+            Duration::from_secs(60),
+            Duration::from_secs(12),
+            || {
+                polls += 1;
+                async { Ok(false) }
+            },
+        ));
+        assert!(result.is_err());
+        assert_eq!(polls, 5);
+    }
+
+    #[test]
+    fn test_wait_for_condition_adaptive() {
+        let clock = ManualClock::new(Duration::from_secs(1_700_000_000));
+        let mut polls = 0;
+        futures::executor::block_on(wait_for_condition_adaptive(
+            &clock,
+            Duration::from_secs(600),
+            || {
+                polls += 1;
+                let poll = match polls {
+                    1 => ConditionPoll::RetryAfter(Duration::from_secs(300)),
+                    2 => ConditionPoll::RetryAfter(Duration::from_secs(12)),
+                    _ => ConditionPoll::Met,
+                };
+                async move { Ok(poll) }
+            },
+        ))
+        .unwrap();
+        assert_eq!(polls, 3);
+        assert_eq!(
+            clock.now_since_unix().unwrap(),
+            Duration::from_secs(1_700_000_312)
+        );
+
+        // The waits are capped to the timeout, and the condition is polled at the timeout
+        let start = clock.now_since_unix().unwrap();
+        let mut polls = 0;
+        let result = futures::executor::block_on(wait_for_condition_adaptive(
+            &clock,
+            Duration::from_secs(600),
+            || {
+                polls += 1;
+                async { Ok(ConditionPoll::RetryAfter(Duration::from_secs(500))) }
+            },
+        ));
+        assert!(result.is_err());
+        assert_eq!(polls, 3);
+        assert_eq!(
+            clock.now_since_unix().unwrap() - start,
+            Duration::from_secs(600)
+        );
+    }
+}
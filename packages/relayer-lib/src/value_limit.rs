@@ -0,0 +1,520 @@
+//! This module defines the value at risk policy of the relayer.
+//!
+//! The [`ValueRateLimiter`] limits the total value, per denom, that the relayer relays toward a
+//! chain within a time window. Relays that would exceed a limit are held back until an operator
+//! approves them, which protects bridge operators during suspected incidents.
+//!
+//! The [`TxValueCap`] bounds the value, per denom, included in a single relay transaction, so
+//! that a failed or reorged transaction affects a bounded amount of user value.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+};
+
+use alloy::primitives::{keccak256, U256};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{EurekaEvent, EurekaEventWithHeight, Ics20Transfer};
+pub use crate::events::{ICS20_ABI_ENCODING, ICS20_JSON_ENCODING, ICS20_TRANSFER_PORT};
+
+/// The default length of the rate limiting window in seconds.
+pub const DEFAULT_WINDOW_SECS: u64 = 60 * 60;
+
+/// The value at risk policy of a relayer module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueLimitPolicy {
+    /// The length of the rate limiting window in seconds.
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// The maximum value relayed per denom within the window.
+    /// Denoms without a limit are not rate limited.
+    pub limits: HashMap<String, U256>,
+}
+
+/// The cap on the value, per denom, of the packets included in a single relay transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxValueCap {
+    /// The maximum value per denom included in a transaction.
+    /// Denoms without a cap are not capped.
+    pub limits: HashMap<String, U256>,
+}
+
+/// A token transfer relayed by the relayer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    /// The denom of the transferred tokens.
+    pub denom: String,
+    /// The transferred amount.
+    pub amount: U256,
+}
+
+/// A relay that is held back until it is approved by an operator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingRelay {
+    /// The identifier of the relay, used to approve it.
+    pub relay_id: String,
+    /// The transfers of the relay.
+    pub transfers: Vec<Transfer>,
+    /// The denoms whose limits would be exceeded by the relay.
+    pub exceeded_denoms: Vec<String>,
+    /// The unix timestamp at which the relay was first held back.
+    pub requested_at: u64,
+}
+
+/// The error returned when a relay exceeds the value at risk policy.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("relay {relay_id} exceeds the value limits for {exceeded_denoms:?} and requires manual approval")]
+pub struct ValueLimitExceeded {
+    /// The identifier of the relay, used to approve it.
+    pub relay_id: String,
+    /// The denoms whose limits would be exceeded by the relay.
+    pub exceeded_denoms: Vec<String>,
+}
+
+/// A portable snapshot of the state of a [`ValueRateLimiter`], used to migrate the rate limiting
+/// window and the pending approvals between relayer instances.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueLimiterSnapshot {
+    /// The admitted transfers with the unix timestamp at which they were admitted.
+    pub history: Vec<(u64, Transfer)>,
+    /// The relays waiting for an approval.
+    pub pending: Vec<PendingRelay>,
+    /// The relays that were approved but not yet relayed.
+    pub approved: Vec<String>,
+}
+
+/// Enforces a [`ValueLimitPolicy`] on the relays of a relayer module.
+#[derive(Debug)]
+pub struct ValueRateLimiter {
+    policy: ValueLimitPolicy,
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    /// The admitted transfers with the unix timestamp at which they were admitted.
+    history: VecDeque<(u64, Transfer)>,
+    /// The relays waiting for an approval.
+    pending: HashMap<String, PendingRelay>,
+    /// The relays that were approved but not yet relayed.
+    approved: HashSet<String>,
+}
+
+const fn default_window_secs() -> u64 {
+    DEFAULT_WINDOW_SECS
+}
+
+impl ValueRateLimiter {
+    /// Create a new [`ValueRateLimiter`] enforcing the given policy.
+    #[must_use]
+    pub fn new(policy: ValueLimitPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the policy enforced by the limiter.
+    #[must_use]
+    pub const fn policy(&self) -> &ValueLimitPolicy {
+        &self.policy
+    }
+
+    /// Admits a relay if its transfers fit within the limits of the current window, or if it was
+    /// approved. Admitted transfers count toward the limits of the window.
+    ///
+    /// A relay that exceeds the limits is recorded as pending until it is approved.
+    /// # Errors
+    /// Returns [`ValueLimitExceeded`] if the relay exceeds the limits and is not approved.
+    /// # Panics
+    /// Panics if the state lock is poisoned.
+    pub fn admit(
+        &self,
+        relay_id: &str,
+        transfers: &[Transfer],
+        now: u64,
+    ) -> Result<(), ValueLimitExceeded> {
+        let mut state = self.state.lock().unwrap();
+        while state.history.front().is_some_and(|(admitted_at, _)| {
+            admitted_at.saturating_add(self.policy.window_secs) <= now
+        }) {
+            state.history.pop_front();
+        }
+
+        if !state.approved.remove(relay_id) {
+            let exceeded_denoms = self.exceeded_denoms(&state.history, transfers);
+            if !exceeded_denoms.is_empty() {
+                state
+                    .pending
+                    .entry(relay_id.to_string())
+                    .or_insert_with(|| PendingRelay {
+                        relay_id: relay_id.to_string(),
+                        transfers: transfers.to_vec(),
+                        exceeded_denoms: exceeded_denoms.clone(),
+                        requested_at: now,
+                    });
+                return Err(ValueLimitExceeded {
+                    relay_id: relay_id.to_string(),
+                    exceeded_denoms,
+                });
+            }
+        }
+
+        state.pending.remove(relay_id);
+        state
+            .history
+            .extend(transfers.iter().map(|transfer| (now, transfer.clone())));
+        Ok(())
+    }
+
+    /// Approves a pending relay, so that it is admitted the next time it is relayed.
+    /// Returns `false` if there is no pending relay with the given identifier.
+    /// # Panics
+    /// Panics if the state lock is poisoned.
+    pub fn approve(&self, relay_id: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.remove(relay_id).is_none() {
+            return false;
+        }
+        state.approved.insert(relay_id.to_string());
+        true
+    }
+
+    /// Returns the relays waiting for an approval.
+    /// # Panics
+    /// Panics if the state lock is poisoned.
+    #[must_use]
+    pub fn pending(&self) -> Vec<PendingRelay> {
+        let mut pending = self
+            .state
+            .lock()
+            .unwrap()
+            .pending
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        pending.sort_by_key(|relay| relay.requested_at);
+        pending
+    }
+
+    /// Returns a snapshot of the state of the limiter.
+    /// # Panics
+    /// Panics if the state lock is poisoned.
+    #[must_use]
+    pub fn snapshot(&self) -> ValueLimiterSnapshot {
+        let state = self.state.lock().unwrap();
+        let mut pending = state.pending.values().cloned().collect::<Vec<_>>();
+        pending.sort_by(|a, b| (a.requested_at, &a.relay_id).cmp(&(b.requested_at, &b.relay_id)));
+        let mut approved = state.approved.iter().cloned().collect::<Vec<_>>();
+        approved.sort();
+
+        ValueLimiterSnapshot {
+            history: state.history.iter().cloned().collect(),
+            pending,
+            approved,
+        }
+    }
+
+    /// Replaces the state of the limiter with a snapshot.
+    /// # Panics
+    /// Panics if the state lock is poisoned.
+    pub fn restore(&self, snapshot: ValueLimiterSnapshot) {
+        let mut history = snapshot.history;
+        history.sort_by_key(|(admitted_at, _)| *admitted_at);
+
+        *self.state.lock().unwrap() = LimiterState {
+            history: history.into(),
+            pending: snapshot
+                .pending
+                .into_iter()
+                .map(|relay| (relay.relay_id.clone(), relay))
+                .collect(),
+            approved: snapshot.approved.into_iter().collect(),
+        };
+    }
+
+    /// Returns the denoms whose limits would be exceeded by adding `transfers` to `history`.
+    fn exceeded_denoms(
+        &self,
+        history: &VecDeque<(u64, Transfer)>,
+        transfers: &[Transfer],
+    ) -> Vec<String> {
+        let mut totals: HashMap<&str, U256> = HashMap::new();
+        for transfer in history.iter().map(|(_, t)| t).chain(transfers) {
+            let total = totals.entry(&transfer.denom).or_default();
+            *total = total.saturating_add(transfer.amount);
+        }
+
+        let mut exceeded_denoms = transfers
+            .iter()
+            .map(|transfer| transfer.denom.as_str())
+            .filter(|denom| {
+                self.policy
+                    .limits
+                    .get(*denom)
+                    .is_some_and(|limit| totals[denom] > *limit)
+            })
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        exceeded_denoms.sort();
+        exceeded_denoms.dedup();
+        exceeded_denoms
+    }
+}
+
+impl TxValueCap {
+    /// Removes the packets sent from `src_client_id` that do not fit within the cap from
+    /// `src_events`, and returns their sequences so that they can be relayed in a later
+    /// transaction.
+    ///
+    /// Packets are included in the order of the events. The first packet is always included, even
+    /// if it exceeds the cap on its own, so that every relay makes progress. Packets filtered out
+    /// by `src_packet_seqs` are not relayed and do not count toward the cap.
+    ///
+    /// # Errors
+    /// Returns an error if an ICS20 payload cannot be decoded.
+    pub fn apply(
+        &self,
+        src_events: &mut Vec<EurekaEventWithHeight>,
+        src_client_id: &str,
+        src_packet_seqs: &[u64],
+    ) -> Result<Vec<u64>> {
+        let mut totals: HashMap<String, U256> = HashMap::new();
+        let mut included_any = false;
+        let mut deferred = Vec::new();
+
+        let mut events = Vec::with_capacity(src_events.len());
+        for event in src_events.drain(..) {
+            let EurekaEvent::SendPacket(packet) = &event.event else {
+                events.push(event);
+                continue;
+            };
+            if packet.sourceClient != src_client_id
+                || (!src_packet_seqs.is_empty() && !src_packet_seqs.contains(&packet.sequence))
+            {
+                events.push(event);
+                continue;
+            }
+
+            let transfers: Vec<Transfer> = event
+                .packet_data()
+                .checked_transfers()?
+                .iter()
+                .map(Into::into)
+                .collect();
+
+            let mut packet_totals = totals.clone();
+            for transfer in &transfers {
+                let total = packet_totals.entry(transfer.denom.clone()).or_default();
+                *total = total.saturating_add(transfer.amount);
+            }
+            let exceeds = transfers.iter().any(|transfer| {
+                self.limits
+                    .get(&transfer.denom)
+                    .is_some_and(|limit| packet_totals[&transfer.denom] > *limit)
+            });
+
+            if exceeds && included_any {
+                deferred.push(packet.sequence);
+                continue;
+            }
+
+            totals = packet_totals;
+            included_any = true;
+            events.push(event);
+        }
+
+        *src_events = events;
+        Ok(deferred)
+    }
+}
+
+/// Returns the identifier of a relay of the given source txs, which is used to approve it.
+/// The identifier does not depend on the order of the source txs.
+#[must_use]
+pub fn relay_id(src_client_id: &str, source_tx_ids: &[Vec<u8>]) -> String {
+    let mut source_tx_ids = source_tx_ids.to_vec();
+    source_tx_ids.sort();
+
+    let mut preimage = src_client_id.as_bytes().to_vec();
+    source_tx_ids
+        .iter()
+        .for_each(|tx_id| preimage.extend_from_slice(tx_id));
+    keccak256(preimage).to_string()
+}
+
+/// Returns the ICS20 transfers of the packets sent from `src_client_id` in `src_events`.
+///
+/// # Errors
+/// Returns an error if an ICS20 payload cannot be decoded.
+pub fn src_events_to_transfers(
+    src_events: &[EurekaEventWithHeight],
+    src_client_id: &str,
+) -> Result<Vec<Transfer>> {
+    let mut transfers = Vec::new();
+    for event in src_events {
+        match &event.event {
+            EurekaEvent::SendPacket(packet) if packet.sourceClient == src_client_id => {
+                transfers.extend(
+                    event
+                        .packet_data()
+                        .checked_transfers()?
+                        .iter()
+                        .map(Transfer::from),
+                );
+            }
+            _ => {}
+        }
+    }
+    Ok(transfers)
+}
+
+impl From<&Ics20Transfer> for Transfer {
+    fn from(transfer: &Ics20Transfer) -> Self {
+        Self {
+            denom: transfer.denom.clone(),
+            amount: transfer.amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn transfer(denom: &str, amount: u64) -> Transfer {
+        Transfer {
+            denom: denom.to_string(),
+            amount: U256::from(amount),
+        }
+    }
+
+    fn limiter() -> ValueRateLimiter {
+        ValueRateLimiter::new(ValueLimitPolicy {
+            window_secs: 100,
+            limits: HashMap::from([("uatom".to_string(), U256::from(1_000))]),
+        })
+    }
+
+    #[test]
+    fn test_admit_within_window_limits() {
+        let limiter = limiter();
+
+        assert!(limiter.admit("a", &[transfer("uatom", 600)], 0).is_ok());
+        // Unlimited denoms are always admitted
+        assert!(limiter
+            .admit("b", &[transfer("wei", 1_000_000)], 10)
+            .is_ok());
+
+        let err = limiter
+            .admit("c", &[transfer("uatom", 500)], 50)
+            .unwrap_err();
+        assert_eq!(err.exceeded_denoms, vec!["uatom".to_string()]);
+        assert_eq!(limiter.pending().len(), 1);
+
+        // Once the first transfer leaves the window, the relay fits again
+        assert!(limiter.admit("c", &[transfer("uatom", 500)], 101).is_ok());
+        assert!(limiter.pending().is_empty());
+    }
+
+    #[test]
+    fn test_approve_pending_relay() {
+        let limiter = limiter();
+
+        assert!(!limiter.approve("a"));
+        assert!(limiter.admit("a", &[transfer("uatom", 2_000)], 0).is_err());
+        assert!(limiter.approve("a"));
+        assert!(limiter.pending().is_empty());
+
+        // The approval is consumed by the next relay
+        assert!(limiter.admit("a", &[transfer("uatom", 2_000)], 1).is_ok());
+        assert!(limiter.admit("a", &[transfer("uatom", 2_000)], 2).is_err());
+    }
+
+    #[test]
+    fn test_tx_value_cap() {
+        use alloy::sol_types::SolValue;
+        use ibc_eureka_solidity_types::{
+            ics26::IICS26RouterMsgs::{Packet, Payload},
+            msgs::IICS20TransferMsgs::FungibleTokenPacketData,
+        };
+
+        let send_packet_event = |sequence: u64, amount: u64| {
+            let data = FungibleTokenPacketData {
+                denom: "uatom".to_string(),
+                sender: "sender".to_string(),
+                receiver: "receiver".to_string(),
+                amount: U256::from(amount),
+                memo: String::new(),
+            };
+            EurekaEventWithHeight::new(
+                EurekaEvent::SendPacket(Packet {
+                    sequence,
+                    sourceClient: "client-0".to_string(),
+                    payloads: vec![Payload {
+                        sourcePort: ICS20_TRANSFER_PORT.to_string(),
+                        encoding: ICS20_ABI_ENCODING.to_string(),
+                        value: data.abi_encode().into(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }),
+                1,
+            )
+        };
+        let sequences = |events: &[EurekaEventWithHeight]| {
+            events
+                .iter()
+                .filter_map(|event| match &event.event {
+                    EurekaEvent::SendPacket(packet) => Some(packet.sequence),
+                    EurekaEvent::WriteAcknowledgement(..) => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let cap = TxValueCap {
+            limits: HashMap::from([("uatom".to_string(), U256::from(1_000))]),
+        };
+
+        let mut events = vec![
+            send_packet_event(1, 600),
+            send_packet_event(2, 600),
+            send_packet_event(3, 400),
+            send_packet_event(4, 1),
+        ];
+        let deferred = cap.apply(&mut events, "client-0", &[]).unwrap();
+        assert_eq!(sequences(&events), vec![1, 3]);
+        assert_eq!(deferred, vec![2, 4]);
+
+        // A single packet above the cap is still relayed on its own
+        let mut events = vec![send_packet_event(1, 5_000), send_packet_event(2, 1)];
+        let deferred = cap.apply(&mut events, "client-0", &[]).unwrap();
+        assert_eq!(sequences(&events), vec![1]);
+        assert_eq!(deferred, vec![2]);
+
+        // Filtered out packets do not count toward the cap
+        let mut events = vec![send_packet_event(1, 600), send_packet_event(2, 600)];
+        let deferred = cap.apply(&mut events, "client-0", &[2]).unwrap();
+        assert_eq!(sequences(&events), vec![1, 2]);
+        assert!(deferred.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let limiter = limiter();
+        assert!(limiter.admit("a", &[transfer("uatom", 600)], 0).is_ok());
+        assert!(limiter.admit("b", &[transfer("uatom", 600)], 10).is_err());
+        assert!(limiter.admit("c", &[transfer("uatom", 600)], 20).is_err());
+        assert!(limiter.approve("c"));
+
+        let snapshot = limiter.snapshot();
+        let restored = ValueRateLimiter::new(limiter.policy().clone());
+        restored.restore(serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap());
+        assert_eq!(restored.snapshot(), snapshot);
+
+        // The restored window and approvals are enforced
+        assert!(restored.admit("b", &[transfer("uatom", 600)], 30).is_err());
+        assert!(restored.admit("c", &[transfer("uatom", 600)], 30).is_ok());
+    }
+}
@@ -0,0 +1,115 @@
+//! This module defines the helpers to compute the denominations and vouchers that an ICS20
+//! transfer results in on the destination chain.
+
+use alloy::{primitives::Address, providers::Provider};
+use anyhow::Result;
+use ibc_eureka_solidity_types::ics20::ics20_transfer::ics20_transferInstance;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The default port identifier of the ICS20 transfer application.
+pub const TRANSFER_PORT_ID: &str = "transfer";
+/// The prefix of IBC voucher denominations on Cosmos SDK chains.
+pub const IBC_DENOM_PREFIX: &str = "ibc/";
+
+/// The denomination of a token after it is received on the destination chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceivedDenom {
+    /// The full denomination path on the destination chain.
+    pub path: String,
+    /// Whether the token is returning to the chain it originates from, in which case it is
+    /// unescrowed instead of minted as a voucher.
+    pub is_returning: bool,
+}
+
+/// Computes the denomination of a token with `denom` after it is sent from `src_port` and
+/// `src_client` and received on `dst_port` and `dst_client`.
+#[must_use]
+pub fn received_denom(
+    src_port: &str,
+    src_client: &str,
+    dst_port: &str,
+    dst_client: &str,
+    denom: &str,
+) -> ReceivedDenom {
+    denom
+        .strip_prefix(&format!("{src_port}/{src_client}/"))
+        .map_or_else(
+            || ReceivedDenom {
+                path: format!("{dst_port}/{dst_client}/{denom}"),
+                is_returning: false,
+            },
+            |base_denom| ReceivedDenom {
+                path: base_denom.to_string(),
+                is_returning: true,
+            },
+        )
+}
+
+/// Returns the denomination of the `denom_path` in the bank module of a Cosmos SDK chain.
+///
+/// Paths without any hops are base denominations and returned as is, other paths are hashed into
+/// an `ibc/{hash}` voucher denomination. Base denominations that contain `/` are not supported.
+#[must_use]
+pub fn cosmos_denom(denom_path: &str) -> String {
+    if denom_path.contains('/') {
+        format!(
+            "{IBC_DENOM_PREFIX}{}",
+            hex::encode_upper(Sha256::digest(denom_path.as_bytes()))
+        )
+    } else {
+        denom_path.to_string()
+    }
+}
+
+/// Returns the ERC20 contract representing the `received` denomination on Ethereum.
+///
+/// Returning tokens are represented by their original ERC20 contract, other tokens by the
+/// `IBCERC20` voucher contract deployed by `ICS20Transfer` when the denomination is first received.
+/// # Errors
+/// Returns an error if the voucher contract has not been deployed yet or cannot be queried.
+pub async fn eth_voucher_contract<P: Provider + Clone>(
+    ics20_transfer: &ics20_transferInstance<(), P>,
+    received: &ReceivedDenom,
+) -> Result<Address> {
+    if received.is_returning {
+        return Ok(received.path.parse()?);
+    }
+
+    Ok(ics20_transfer
+        .ibcERC20Contract(received.path.clone())
+        .call()
+        .await?
+        ._0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_received_denom() {
+        let received = received_denom("transfer", "client-0", "transfer", "08-wasm-0", "uatom");
+        assert_eq!(received.path, "transfer/08-wasm-0/uatom");
+        assert!(!received.is_returning);
+
+        let returned = received_denom(
+            "transfer",
+            "08-wasm-0",
+            "transfer",
+            "client-0",
+            &received.path,
+        );
+        assert_eq!(returned.path, "uatom");
+        assert!(returned.is_returning);
+    }
+
+    #[test]
+    fn test_cosmos_denom() {
+        assert_eq!(cosmos_denom("uatom"), "uatom");
+        assert_eq!(
+            cosmos_denom("transfer/channel-0/uatom"),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+    }
+}
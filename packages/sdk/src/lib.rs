@@ -0,0 +1,15 @@
+//! SDK for integrating with IBC Eureka.
+
+#![doc = include_str!("../README.md")]
+#![deny(
+    clippy::nursery,
+    clippy::pedantic,
+    warnings,
+    missing_docs,
+    unused_crate_dependencies
+)]
+
+pub mod denom;
+pub mod packet;
+pub mod progress;
+pub mod transfer;
@@ -0,0 +1,126 @@
+//! This module defines the queries for the status of IBC Eureka packets.
+
+use alloy::{
+    primitives::{keccak256, Address},
+    providers::Provider,
+};
+use anyhow::Result;
+use ibc_eureka_solidity_types::ics26::{router::routerInstance, IICS26RouterMsgs::Packet};
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{Client, HttpClient};
+
+/// The store key of the IBC module on Cosmos SDK chains.
+const COSMOS_IBC_STORE_KEY: &str = "ibc";
+
+/// The status of a packet, derived from the provable stores of the source and destination chains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PacketStatus {
+    /// The packet is sent but not yet received on the destination chain.
+    Sent,
+    /// The packet is received on the destination chain, but its acknowledgement is not yet relayed
+    /// back to the source chain.
+    Received,
+    /// The packet is received and its acknowledgement is relayed back to the source chain.
+    Acknowledged,
+    /// The packet is timed out on the source chain.
+    TimedOut,
+}
+
+impl PacketStatus {
+    /// Returns whether the status can no longer change.
+    #[must_use]
+    pub const fn is_final(self) -> bool {
+        matches!(self, Self::Acknowledged | Self::TimedOut)
+    }
+}
+
+/// A chain that hosts the provable IBC Eureka store.
+#[async_trait::async_trait]
+pub trait PacketStore: Send + Sync {
+    /// Returns the commitment stored under the IBC `path`, if any.
+    /// # Errors
+    /// Returns an error if the store cannot be queried.
+    async fn commitment(&self, path: Vec<u8>) -> Result<Option<Vec<u8>>>;
+}
+
+/// The provable store of the `ICS26Router` contract on Ethereum.
+pub struct EthPacketStore<P: Provider + Clone> {
+    /// The IBC Eureka router instance.
+    pub ics26_router: routerInstance<(), P>,
+}
+
+/// The provable store of the IBC module on a Cosmos SDK chain.
+pub struct CosmosPacketStore {
+    /// The HTTP client for the Cosmos SDK chain.
+    pub tm_client: HttpClient,
+}
+
+impl<P: Provider + Clone> EthPacketStore<P> {
+    /// Create a new [`EthPacketStore`] instance.
+    pub const fn new(ics26_address: Address, provider: P) -> Self {
+        Self {
+            ics26_router: routerInstance::new(ics26_address, provider),
+        }
+    }
+}
+
+impl CosmosPacketStore {
+    /// Create a new [`CosmosPacketStore`] instance.
+    #[must_use]
+    pub const fn new(tm_client: HttpClient) -> Self {
+        Self { tm_client }
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: Provider + Clone> PacketStore for EthPacketStore<P> {
+    async fn commitment(&self, path: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        let commitment = self
+            .ics26_router
+            .getCommitment(keccak256(path))
+            .call()
+            .await?
+            ._0;
+
+        Ok((!commitment.is_zero()).then(|| commitment.to_vec()))
+    }
+}
+
+#[async_trait::async_trait]
+impl PacketStore for CosmosPacketStore {
+    async fn commitment(&self, path: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        let res = self
+            .tm_client
+            .abci_query(
+                Some(format!("store/{COSMOS_IBC_STORE_KEY}/key")),
+                path,
+                None,
+                false,
+            )
+            .await?;
+
+        Ok((!res.value.is_empty()).then_some(res.value))
+    }
+}
+
+/// Queries the status of `packet`, sent from the `src` chain to the `dst` chain.
+/// # Errors
+/// Returns an error if either of the stores cannot be queried.
+pub async fn packet_status(
+    src: &impl PacketStore,
+    dst: &impl PacketStore,
+    packet: &Packet,
+) -> Result<PacketStatus> {
+    let (commitment, receipt) = futures::try_join!(
+        src.commitment(packet.commitment_path()),
+        dst.commitment(packet.receipt_commitment_path()),
+    )?;
+
+    Ok(match (commitment.is_some(), receipt.is_some()) {
+        (true, false) => PacketStatus::Sent,
+        (true, true) => PacketStatus::Received,
+        (false, true) => PacketStatus::Acknowledged,
+        (false, false) => PacketStatus::TimedOut,
+    })
+}
@@ -0,0 +1,38 @@
+//! This module defines the subscription to the relay progress of a packet.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::{stream, Stream};
+use futures_timer::Delay;
+use ibc_eureka_solidity_types::ics26::IICS26RouterMsgs::Packet;
+
+use crate::packet::{packet_status, PacketStatus, PacketStore};
+
+/// Subscribes to the relay progress of `packet`, sent from the `src` chain to the `dst` chain.
+///
+/// The stores are polled every `interval`, and the stream yields the status of the packet
+/// whenever it changes. The stream ends after yielding a final status or an error.
+pub fn relay_progress<'a>(
+    src: &'a impl PacketStore,
+    dst: &'a impl PacketStore,
+    packet: &'a Packet,
+    interval: Duration,
+) -> impl Stream<Item = Result<PacketStatus>> + 'a {
+    stream::unfold(
+        (None, false),
+        move |(last_status, done): (Option<PacketStatus>, bool)| async move {
+            if done {
+                return None;
+            }
+
+            loop {
+                match packet_status(src, dst, packet).await {
+                    Ok(status) if Some(status) == last_status => Delay::new(interval).await,
+                    Ok(status) => return Some((Ok(status), (Some(status), status.is_final()))),
+                    Err(err) => return Some((Err(err), (last_status, true))),
+                }
+            }
+        },
+    )
+}
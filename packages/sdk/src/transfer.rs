@@ -0,0 +1,105 @@
+//! This module defines the instructions to send an ICS20 transfer and have it relayed.
+
+use alloy::{
+    primitives::{Address, Bytes},
+    rpc::types::TransactionRequest,
+    sol_types::SolCall,
+};
+use ibc_eureka_solidity_types::ics20::{
+    erc20::approveCall, ics20_transfer::sendTransferCall, IICS20TransferMsgs::SendTransferMsg,
+};
+use serde::{Deserialize, Serialize};
+
+/// The request to relay the packets of a transaction, mirroring the relayer's `RelayByTx` API.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayRequest {
+    /// The source chain identifier.
+    pub src_chain: String,
+    /// The destination chain identifier.
+    pub dst_chain: String,
+    /// The transactions on the source chain whose packets should be relayed.
+    pub source_tx_ids: Vec<Vec<u8>>,
+    /// The transactions on the destination chain whose packets should be timed out.
+    pub timeout_tx_ids: Vec<Vec<u8>>,
+    /// The client identifier on the source chain.
+    pub src_client_id: String,
+    /// The client identifier on the destination chain.
+    pub dst_client_id: String,
+    /// The source chain packet sequences to relay, no filtering if empty.
+    pub src_packet_sequences: Vec<u64>,
+    /// The destination chain packet sequences to relay, no filtering if empty.
+    pub dst_packet_sequences: Vec<u64>,
+}
+
+/// The instructions to send an ICS20 transfer from Ethereum and have it relayed.
+#[derive(Clone, Debug)]
+pub struct EthTransferWithRelay {
+    /// The transaction approving the `ICS20Transfer` contract to spend the transferred tokens.
+    pub approve_tx: TransactionRequest,
+    /// The transaction sending the transfer.
+    pub send_transfer_tx: TransactionRequest,
+    /// The relay request, to be completed with the hash of the send transfer transaction.
+    pub relay_request: RelayRequest,
+}
+
+impl RelayRequest {
+    /// Create a new [`RelayRequest`] without any transactions.
+    #[must_use]
+    pub fn new(
+        src_chain: impl Into<String>,
+        dst_chain: impl Into<String>,
+        src_client_id: impl Into<String>,
+        dst_client_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            src_chain: src_chain.into(),
+            dst_chain: dst_chain.into(),
+            src_client_id: src_client_id.into(),
+            dst_client_id: dst_client_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a source chain transaction whose packets should be relayed.
+    #[must_use]
+    pub fn with_source_tx(mut self, tx_id: impl Into<Vec<u8>>) -> Self {
+        self.source_tx_ids.push(tx_id.into());
+        self
+    }
+}
+
+/// Builds the instructions to send the ICS20 transfer `msg` through the `ICS20Transfer` contract at
+/// `ics20_address`, and have it relayed from `src_chain` to the client `dst_client_id` on `dst_chain`.
+#[must_use]
+pub fn eth_transfer_with_relay(
+    ics20_address: Address,
+    msg: SendTransferMsg,
+    src_chain: impl Into<String>,
+    dst_chain: impl Into<String>,
+    dst_client_id: impl Into<String>,
+) -> EthTransferWithRelay {
+    let approve_call = approveCall {
+        spender: ics20_address,
+        value: msg.amount,
+    };
+    let approve_tx = TransactionRequest::default()
+        .to(msg.denom)
+        .input(Bytes::from(approve_call.abi_encode()).into());
+
+    let relay_request = RelayRequest::new(
+        src_chain,
+        dst_chain,
+        msg.sourceClient.clone(),
+        dst_client_id,
+    );
+
+    let send_transfer_tx = TransactionRequest::default()
+        .to(ics20_address)
+        .input(Bytes::from(sendTransferCall { msg_: msg }.abi_encode()).into());
+
+    EthTransferWithRelay {
+        approve_tx,
+        send_transfer_tx,
+        relay_request,
+    }
+}
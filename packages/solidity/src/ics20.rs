@@ -0,0 +1,27 @@
+//! Solidity types for ICS20Transfer.sol
+
+#[cfg(feature = "rpc")]
+alloy_sol_types::sol!(
+    #[sol(rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    ics20_transfer,
+    "../../abi/ICS20Transfer.json"
+);
+
+// NOTE: Some environments won't compile with the `rpc` features.
+#[cfg(not(feature = "rpc"))]
+alloy_sol_types::sol!(
+    #[derive(Debug, PartialEq, Eq)]
+    ics20_transfer,
+    "../../abi/ICS20Transfer.json"
+);
+
+#[cfg(feature = "rpc")]
+alloy_sol_types::sol!(
+    #[sol(rpc)]
+    erc20,
+    "../../abi/ERC20.json"
+);
+
+#[cfg(not(feature = "rpc"))]
+alloy_sol_types::sol!(erc20, "../../abi/ERC20.json");
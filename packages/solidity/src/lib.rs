@@ -2,6 +2,7 @@
 
 #![deny(clippy::nursery, clippy::pedantic, warnings)]
 
+pub mod ics20;
 pub mod ics26;
 pub mod msgs;
 pub mod sp1_ics07;
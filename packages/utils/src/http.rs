@@ -0,0 +1,163 @@
+//! Configuration of the HTTP clients used to connect to RPC and API endpoints.
+
+use std::{collections::HashMap, fmt, time::Duration};
+
+use anyhow::Result;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+/// The default timeout for establishing a connection in seconds.
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// The default timeout for a request in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// The timeout after which idle pooled connections are closed.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The configuration shared by the HTTP clients of the RPC and API endpoints, such as the proxy,
+/// the trusted certificates and the authentication of each endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// The URL of the proxy to send all requests through.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// The path to a PEM encoded CA certificate to trust in addition to the built-in roots.
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    /// The authentication of the endpoints, keyed by URL prefix.
+    /// The longest prefix matching an endpoint URL is used.
+    #[serde(default)]
+    pub auth: HashMap<String, HttpAuth>,
+    /// The timeout for establishing a connection in seconds.
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// The timeout for a request in seconds.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// The authentication of an HTTP endpoint, sent in the `Authorization` header.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpAuth {
+    /// A bearer token.
+    Bearer(String),
+    /// A username and an optional password.
+    Basic {
+        /// The username.
+        username: String,
+        /// The password.
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+const fn default_connect_timeout_secs() -> u64 {
+    DEFAULT_CONNECT_TIMEOUT_SECS
+}
+
+const fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            ca_cert_path: None,
+            auth: HashMap::new(),
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl fmt::Debug for HttpAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Credentials are never printed
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(<redacted>)"),
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl HttpAuth {
+    /// Returns the value of the `Authorization` header.
+    #[must_use]
+    pub fn header_value(&self) -> String {
+        match self {
+            Self::Bearer(token) => format!("Bearer {token}"),
+            Self::Basic { username, password } => {
+                let credentials = format!("{username}:{}", password.as_deref().unwrap_or_default());
+                format!("Basic {}", BASE64_STANDARD.encode(credentials))
+            }
+        }
+    }
+}
+
+/// Builds an HTTP client from the configuration with the given version of `reqwest`.
+macro_rules! build_client {
+    ($reqwest:ident, $config:expr, $url:expr) => {{
+        let config: &HttpClientConfig = $config;
+        let mut builder = $reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT);
+
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy($reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(ca_cert) = config.read_ca_cert()? {
+            builder = builder.add_root_certificate($reqwest::Certificate::from_pem(&ca_cert)?);
+        }
+        if let Some(auth) = config.auth_for($url) {
+            let mut value = $reqwest::header::HeaderValue::from_str(&auth.header_value())?;
+            value.set_sensitive(true);
+            let mut headers = $reqwest::header::HeaderMap::new();
+            headers.insert($reqwest::header::AUTHORIZATION, value);
+            builder = builder.default_headers(headers);
+        }
+
+        Ok(builder.build()?)
+    }};
+}
+
+impl HttpClientConfig {
+    /// Returns the authentication of the endpoint at `url`, if any.
+    #[must_use]
+    pub fn auth_for(&self, url: &str) -> Option<&HttpAuth> {
+        self.auth
+            .iter()
+            .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, auth)| auth)
+    }
+
+    /// Builds the HTTP client for the endpoint at `url`.
+    /// # Errors
+    /// Returns an error if the proxy, the CA certificate or the authentication is invalid.
+    pub fn build_client(&self, url: &str) -> Result<reqwest::Client> {
+        build_client!(reqwest, self, url)
+    }
+
+    /// Builds the HTTP client for the endpoint at `url` with `reqwest` 0.11, which is used by the
+    /// Tendermint RPC client.
+    /// # Errors
+    /// Returns an error if the proxy, the CA certificate or the authentication is invalid.
+    pub fn build_client_0_11(&self, url: &str) -> Result<reqwest_0_11::Client> {
+        build_client!(reqwest_0_11, self, url)
+    }
+
+    /// Reads the configured CA certificate.
+    fn read_ca_cert(&self) -> Result<Option<Vec<u8>>> {
+        self.ca_cert_path
+            .as_ref()
+            .map(|path| {
+                std::fs::read(path).map_err(|e| anyhow::anyhow!("failed to read {path}: {e}"))
+            })
+            .transpose()
+    }
+}
@@ -0,0 +1,118 @@
+//! This module defines the ICS20 denominations of the tokens transferred between Ethereum and
+//! Cosmos SDK chains.
+//!
+//! A token received from another chain is identified by its full denom path, which prefixes the
+//! base denom with a `{port}/{client}/` hop for every chain it was received on. On Cosmos SDK
+//! chains, ibc-go represents the voucher with the [`ibc_denom`] hash of the path. On Ethereum, the
+//! `ICS20Transfer` contract deploys an `IBCERC20` contract whose name and symbol are the path.
+
+use sha2::{Digest, Sha256};
+
+/// The ICS20 port id.
+pub const ICS20_PORT_ID: &str = "transfer";
+/// The prefix of the ibc-go voucher denoms.
+pub const IBC_DENOM_PREFIX: &str = "ibc/";
+
+/// The metadata of the `IBCERC20` contract of a voucher on Ethereum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IbcErc20Metadata {
+    /// The ERC20 name of the token.
+    pub name: String,
+    /// The ERC20 symbol of the token.
+    pub symbol: String,
+    /// The full denom path of the token.
+    pub full_denom_path: String,
+}
+
+/// Returns the full denom path of a denom received through the given port and client.
+#[must_use]
+pub fn full_denom_path(port_id: &str, client_id: &str, denom: &str) -> String {
+    format!("{port_id}/{client_id}/{denom}")
+}
+
+/// Returns the ibc-go voucher denom of a full denom path, `ibc/{SHA256(path)}` in upper case hex.
+#[must_use]
+pub fn ibc_denom(full_denom_path: &str) -> String {
+    let hash = Sha256::digest(full_denom_path.as_bytes());
+    let hash_hex = hash
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<String>();
+    format!("{IBC_DENOM_PREFIX}{hash_hex}")
+}
+
+/// Returns the voucher denom on a Cosmos SDK chain of an ERC20 token from Ethereum, received
+/// through the ICS20 port and the given client of the Cosmos SDK chain.
+///
+/// The `ICS20Transfer` contract sends native ERC20 tokens with their lower case hex address as
+/// denom, so the address is normalized before hashing.
+#[must_use]
+pub fn erc20_voucher_denom(erc20_address: &str, cosmos_client_id: &str) -> String {
+    ibc_denom(&full_denom_path(
+        ICS20_PORT_ID,
+        cosmos_client_id,
+        &erc20_address.to_lowercase(),
+    ))
+}
+
+/// Returns the metadata of the `IBCERC20` contract of a Cosmos SDK denom, received through the
+/// ICS20 port and the given client on Ethereum.
+///
+/// The denom is the one sent by the Cosmos SDK chain, i.e. the full denom path of a voucher
+/// rather than its `ibc/` hash.
+#[must_use]
+pub fn ibc_erc20_metadata(cosmos_denom: &str, eth_client_id: &str) -> IbcErc20Metadata {
+    let full_denom_path = full_denom_path(ICS20_PORT_ID, eth_client_id, cosmos_denom);
+    IbcErc20Metadata {
+        name: full_denom_path.clone(),
+        symbol: full_denom_path.clone(),
+        full_denom_path,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ibc_denom() {
+        // The ATOM voucher on Osmosis
+        assert_eq!(
+            ibc_denom("transfer/channel-0/uatom"),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+    }
+
+    #[test]
+    fn test_erc20_voucher_denom() {
+        let expected = "ibc/593C4E4C125282B16D3856FDDA87474296009A0175913A8780CEC9DD8B01B2D4";
+        assert_eq!(
+            erc20_voucher_denom("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "08-wasm-0"),
+            expected
+        );
+        // Checksummed addresses are sent in lower case by the contract
+        assert_eq!(
+            erc20_voucher_denom("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "08-wasm-0"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_ibc_erc20_metadata() {
+        let metadata = ibc_erc20_metadata("uatom", "client-0");
+        assert_eq!(metadata.name, "transfer/client-0/uatom");
+        assert_eq!(metadata.symbol, "transfer/client-0/uatom");
+        assert_eq!(metadata.full_denom_path, "transfer/client-0/uatom");
+
+        // A voucher forwarded from Ethereum to another chain keeps its full path
+        let metadata = ibc_erc20_metadata("transfer/08-wasm-0/transfer/client-0/uatom", "client-1");
+        assert_eq!(
+            metadata.full_denom_path,
+            "transfer/client-1/transfer/08-wasm-0/transfer/client-0/uatom"
+        );
+        assert_eq!(
+            ibc_denom("transfer/08-wasm-0/transfer/client-0/uatom"),
+            "ibc/B5F894E29258A77388F9E2CFA57550D041B39E39915DCA593415F0AD177254C0"
+        );
+    }
+}
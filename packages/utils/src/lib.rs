@@ -2,6 +2,8 @@
 #![deny(clippy::nursery, clippy::pedantic, warnings, missing_docs)]
 
 pub mod eth;
+pub mod http;
+pub mod ics20;
 pub mod light_block;
 pub mod merkle;
 pub mod rpc;
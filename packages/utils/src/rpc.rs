@@ -1,7 +1,7 @@
 //! RPC client for interacting with a Tendermint node.
 
 use core::str::FromStr;
-use std::{collections::HashMap, env, time::Duration};
+use std::{collections::HashMap, env};
 
 use anyhow::Result;
 
@@ -16,6 +16,9 @@ use ibc_core_client_types::proto::v1::{
     QueryConsensusStateResponse,
 };
 use ibc_core_commitment_types::merkle::MerkleProof;
+use ibc_proto_eureka::ibc::core::client::v2::{
+    CounterpartyInfo, QueryCounterpartyInfoRequest, QueryCounterpartyInfoResponse,
+};
 use tendermint::{
     block::{signed_header::SignedHeader, Height},
     validator::Set,
@@ -23,7 +26,16 @@ use tendermint::{
 use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
 use tendermint_rpc::{Client, HttpClient, Paging, Url};
 
-use crate::merkle::convert_tm_to_ics_merkle_proof;
+use crate::{http::HttpClientConfig, merkle::convert_tm_to_ics_merkle_proof};
+
+/// The store key of the IBC module.
+const IBC_STORE_KEY: &[u8] = b"ibc";
+/// The key prefix byte of the IBC Eureka packet commitments.
+const PACKET_COMMITMENT_KEY_PREFIX: u8 = 1;
+/// The key prefix byte of the IBC Eureka packet receipts.
+const PACKET_RECEIPT_KEY_PREFIX: u8 = 2;
+/// The key prefix byte of the IBC Eureka packet acknowledgements.
+const PACKET_ACKNOWLEDGEMENT_KEY_PREFIX: u8 = 3;
 
 /// An extension trait for [`HttpClient`] that provides additional methods for
 /// obtaining light blocks.
@@ -32,6 +44,15 @@ pub trait TendermintRpcExt {
     /// Creates a new instance of the Tendermint RPC client from the given URL.
     fn from_rpc_url(rpc_url: &str) -> Self;
 
+    /// Creates a new instance of the Tendermint RPC client from the given URL, with an HTTP
+    /// client built from `http_config`.
+    ///
+    /// # Errors
+    /// Returns an error if the URL or the HTTP client configuration is invalid.
+    fn from_rpc_url_with_config(rpc_url: &str, http_config: &HttpClientConfig) -> Result<Self>
+    where
+        Self: Sized;
+
     /// Creates a new instance of the Tendermint RPC client from the environment variables.
     ///
     /// # Panics
@@ -51,31 +72,54 @@ pub trait TendermintRpcExt {
     async fn client_state(&self, client_id: String) -> Result<Any>;
     /// Fetches the Ethereum consensus state from the light client on cosmos.
     /// If the revision height is 0, the latest height is fetched.
-    async fn consensus_state(&self, client_id: String, revision_height: u64) -> Result<Any>;
+    async fn consensus_state(
+        &self,
+        client_id: String,
+        revision_number: u64,
+        revision_height: u64,
+    ) -> Result<Any>;
     /// Proves a path in the chain's Merkle tree and returns the value at the path and the proof.
     /// If the value is empty, then this is a non-inclusion proof.
     async fn prove_path(&self, path: &[Vec<u8>], height: u64) -> Result<(Vec<u8>, MerkleProof)>;
+    /// Fetches the IBC Eureka counterparty info of a client from the Tendermint node.
+    async fn counterparty_info(&self, client_id: String) -> Result<CounterpartyInfo>;
+    /// Proves the commitment of the packet with `sequence` sent by `client_id` at `height`.
+    /// If the value is empty, then this is a non-inclusion proof.
+    async fn prove_packet_commitment(
+        &self,
+        client_id: &str,
+        sequence: u64,
+        height: u64,
+    ) -> Result<(Vec<u8>, MerkleProof)>;
+    /// Proves the receipt of the packet with `sequence` received by `client_id` at `height`.
+    /// If the value is empty, then this is a non-inclusion proof.
+    async fn prove_packet_receipt(
+        &self,
+        client_id: &str,
+        sequence: u64,
+        height: u64,
+    ) -> Result<(Vec<u8>, MerkleProof)>;
+    /// Proves the acknowledgement of the packet with `sequence` received by `client_id` at
+    /// `height`. If the value is empty, then this is a non-inclusion proof.
+    async fn prove_packet_acknowledgement(
+        &self,
+        client_id: &str,
+        sequence: u64,
+        height: u64,
+    ) -> Result<(Vec<u8>, MerkleProof)>;
 }
 
 #[async_trait::async_trait]
 impl TendermintRpcExt for HttpClient {
     fn from_rpc_url(rpc_url: &str) -> Self {
-        Self::builder(
-            Url::from_str(rpc_url)
-                .expect("Failed to parse tendermint RPC URL")
-                .try_into()
-                .expect("Failed to convert tendermint RPC URL"),
-        )
-        .client(
-            reqwest_0_11::ClientBuilder::new()
-                .connect_timeout(Duration::from_secs(10))
-                .timeout(Duration::from_secs(30))
-                .pool_idle_timeout(Duration::from_secs(10))
-                .build()
-                .expect("Failed to create reqwest client"),
-        )
-        .build()
-        .expect("Failed to create tendermint HTTP client")
+        Self::from_rpc_url_with_config(rpc_url, &HttpClientConfig::default())
+            .expect("Failed to create tendermint HTTP client")
+    }
+
+    fn from_rpc_url_with_config(rpc_url: &str, http_config: &HttpClientConfig) -> Result<Self> {
+        Ok(Self::builder(Url::from_str(rpc_url)?.try_into()?)
+            .client(http_config.build_client_0_11(rpc_url)?)
+            .build()?)
     }
 
     fn from_env() -> Self {
@@ -154,13 +198,18 @@ impl TendermintRpcExt for HttpClient {
             .ok_or_else(|| anyhow::anyhow!("No client state found"))
     }
 
-    async fn consensus_state(&self, client_id: String, revision_height: u64) -> Result<Any> {
+    async fn consensus_state(
+        &self,
+        client_id: String,
+        revision_number: u64,
+        revision_height: u64,
+    ) -> Result<Any> {
         let abci_resp = self
             .abci_query(
                 Some("/ibc.core.client.v1.Query/ConsensusState".to_string()),
                 QueryConsensusStateRequest {
                     client_id,
-                    revision_number: 0,
+                    revision_number,
                     revision_height,
                     latest_height: revision_height == 0,
                 }
@@ -204,6 +253,79 @@ impl TendermintRpcExt for HttpClient {
 
         anyhow::Ok((res.value, vm_proof))
     }
+
+    async fn counterparty_info(&self, client_id: String) -> Result<CounterpartyInfo> {
+        let abci_resp = self
+            .abci_query(
+                Some("/ibc.core.client.v2.Query/CounterpartyInfo".to_string()),
+                QueryCounterpartyInfoRequest { client_id }.encode_to_vec(),
+                None,
+                false,
+            )
+            .await?;
+
+        QueryCounterpartyInfoResponse::decode(abci_resp.value.as_slice())?
+            .counterparty_info
+            .ok_or_else(|| anyhow::anyhow!("No counterparty info found"))
+    }
+
+    async fn prove_packet_commitment(
+        &self,
+        client_id: &str,
+        sequence: u64,
+        height: u64,
+    ) -> Result<(Vec<u8>, MerkleProof)> {
+        self.prove_path(
+            &[
+                IBC_STORE_KEY.to_vec(),
+                packet_key(client_id, PACKET_COMMITMENT_KEY_PREFIX, sequence),
+            ],
+            height,
+        )
+        .await
+    }
+
+    async fn prove_packet_receipt(
+        &self,
+        client_id: &str,
+        sequence: u64,
+        height: u64,
+    ) -> Result<(Vec<u8>, MerkleProof)> {
+        self.prove_path(
+            &[
+                IBC_STORE_KEY.to_vec(),
+                packet_key(client_id, PACKET_RECEIPT_KEY_PREFIX, sequence),
+            ],
+            height,
+        )
+        .await
+    }
+
+    async fn prove_packet_acknowledgement(
+        &self,
+        client_id: &str,
+        sequence: u64,
+        height: u64,
+    ) -> Result<(Vec<u8>, MerkleProof)> {
+        self.prove_path(
+            &[
+                IBC_STORE_KEY.to_vec(),
+                packet_key(client_id, PACKET_ACKNOWLEDGEMENT_KEY_PREFIX, sequence),
+            ],
+            height,
+        )
+        .await
+    }
+}
+
+/// Returns the IBC Eureka store key of a packet, i.e. `client_id || key_prefix || sequence`.
+fn packet_key(client_id: &str, key_prefix: u8, sequence: u64) -> Vec<u8> {
+    [
+        client_id.as_bytes(),
+        &[key_prefix],
+        sequence.to_be_bytes().as_slice(),
+    ]
+    .concat()
 }
 
 /// Sorts the signatures in the signed header based on the descending order of validators' power.
@@ -0,0 +1,23 @@
+//! Feeds arbitrary client messages into the `VerifyClientMessage` query of the contract.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use cosmwasm_std::testing::mock_env;
+use cw_ics08_wasm_eth::{
+    contract::query,
+    msg::{QueryMsg, VerifyClientMessageMsg},
+};
+use cw_ics08_wasm_eth_fuzz::{instantiated_deps, Deps};
+use libfuzzer_sys::fuzz_target;
+
+static DEPS: LazyLock<Deps> = LazyLock::new(|| instantiated_deps().0);
+
+fuzz_target!(|data: &[u8]| {
+    // Queries do not modify the store, so the contract is only instantiated once
+    let msg = QueryMsg::VerifyClientMessage(VerifyClientMessageMsg {
+        client_message: data.to_vec().into(),
+    });
+    let _ = query(DEPS.as_ref(), mock_env(), msg);
+});
@@ -0,0 +1,75 @@
+//! Feeds arbitrary proofs, key paths and values into the `VerifyMembership` and
+//! `VerifyNonMembership` sudo messages of the contract.
+
+#![no_main]
+
+use std::cell::RefCell;
+
+use cosmwasm_std::{testing::mock_env, Binary};
+use cw_ics08_wasm_eth::{
+    contract::sudo,
+    msg::{Height, MerklePath, SudoMsg, VerifyMembershipMsg, VerifyNonMembershipMsg},
+};
+use cw_ics08_wasm_eth_fuzz::{instantiated_deps, Deps};
+use libfuzzer_sys::fuzz_target;
+
+thread_local! {
+    static DEPS: RefCell<(Deps, u64)> = RefCell::new(instantiated_deps());
+}
+
+/// Splits the input into length prefixed chunks, the last chunk takes the remaining bytes.
+fn split(data: &[u8], chunks: usize) -> Vec<&[u8]> {
+    let mut rest = data;
+    let mut split = Vec::with_capacity(chunks);
+    for _ in 1..chunks {
+        let Some((&len, tail)) = rest.split_first() else {
+            break;
+        };
+        let (chunk, tail) = tail.split_at(usize::from(len).min(tail.len()));
+        split.push(chunk);
+        rest = tail;
+    }
+    split.push(rest);
+    split
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else {
+        return;
+    };
+    let chunks = split(data, 3);
+    let (key_path, value, proof) = match chunks.as_slice() {
+        [key_path, value, proof] => (*key_path, *value, *proof),
+        _ => return,
+    };
+
+    DEPS.with_borrow_mut(|(deps, latest_slot)| {
+        // Proofs are verified against the only consensus state of the client
+        let height = Height {
+            revision_number: 0,
+            revision_height: *latest_slot,
+        };
+        let merkle_path = MerklePath {
+            key_path: vec![Binary::from(key_path)],
+        };
+        let msg = if selector & 1 == 0 {
+            SudoMsg::VerifyMembership(VerifyMembershipMsg {
+                height,
+                delay_time_period: 0,
+                delay_block_period: 0,
+                proof: Binary::from(proof),
+                merkle_path,
+                value: Binary::from(value),
+            })
+        } else {
+            SudoMsg::VerifyNonMembership(VerifyNonMembershipMsg {
+                height,
+                delay_time_period: 0,
+                delay_block_period: 0,
+                proof: Binary::from(proof),
+                merkle_path,
+            })
+        };
+        let _ = sudo(deps.as_mut(), mock_env(), msg);
+    });
+});
@@ -0,0 +1,99 @@
+//! Shared setup of the fuzz targets of the Ethereum light client contract.
+
+use std::marker::PhantomData;
+
+use alloy_primitives::B256;
+use cosmwasm_std::{
+    testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier,
+        MockQuerierCustomHandlerResult, MockStorage,
+    },
+    to_json_binary, Binary, ContractResult, OwnedDeps, SystemError, SystemResult,
+};
+use cw_ics08_wasm_eth::{
+    contract::instantiate, custom_query::EthereumCustomQuery, msg::InstantiateMsg,
+};
+use ethereum_light_client::test_utils::{
+    bls_verifier::{aggreagate, fast_aggregate_verify},
+    fixtures::{self, InitialState},
+};
+use ethereum_types::consensus::bls::{BlsPublicKey, BlsSignature};
+
+/// The fixture whose initial state the contract is instantiated with.
+const FIXTURE: &str = "TestICS20TransferERC20TokenfromEthereumToCosmosAndBack_Groth16";
+
+/// The mocked dependencies of the contract.
+pub type Deps =
+    OwnedDeps<MockStorage, MockApi, MockQuerier<EthereumCustomQuery>, EthereumCustomQuery>;
+
+/// Answers the BLS queries of the contract like the host chain does, returning errors instead of
+/// panicking on malformed inputs.
+fn custom_query_handler(query: &EthereumCustomQuery) -> MockQuerierCustomHandlerResult {
+    let result = match query {
+        EthereumCustomQuery::AggregateVerify {
+            public_keys,
+            message,
+            signature,
+        } => (|| {
+            let public_keys = public_keys
+                .iter()
+                .map(|pk| BlsPublicKey::try_from(pk.as_slice()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            let message = B256::try_from(message.as_slice()).map_err(|e| e.to_string())?;
+            let signature =
+                BlsSignature::try_from(signature.as_slice()).map_err(|e| e.to_string())?;
+            fast_aggregate_verify(&public_keys, message, signature).map_err(|e| e.to_string())?;
+            to_json_binary(&true).map_err(|e| e.to_string())
+        })(),
+        EthereumCustomQuery::Aggregate { public_keys } => (|| {
+            let public_keys = public_keys
+                .iter()
+                .map(|pk| BlsPublicKey::try_from(pk.as_slice()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            let aggregate_pubkey = aggreagate(&public_keys).map_err(|e| e.to_string())?;
+            to_json_binary(&Binary::from(aggregate_pubkey.as_slice())).map_err(|e| e.to_string())
+        })(),
+    };
+
+    match result {
+        Ok(response) => SystemResult::Ok(ContractResult::Ok(response)),
+        Err(error) => SystemResult::Err(SystemError::InvalidRequest {
+            error,
+            request: Binary::default(),
+        }),
+    }
+}
+
+/// Returns the dependencies of a contract instantiated with the initial state of the fixture,
+/// along with the latest slot of the client.
+/// # Panics
+/// Panics if the fixture cannot be loaded or the contract cannot be instantiated.
+#[must_use]
+pub fn instantiated_deps() -> (Deps, u64) {
+    let deps = mock_dependencies();
+    let mut deps = OwnedDeps {
+        storage: deps.storage,
+        api: deps.api,
+        querier: MockQuerier::<EthereumCustomQuery>::new(&[])
+            .with_custom_handler(custom_query_handler),
+        custom_query_type: PhantomData,
+    };
+
+    let initial_state: InitialState = fixtures::load(FIXTURE).get_data_at_step(0);
+    let latest_slot = initial_state.client_state.latest_slot;
+    let creator = deps.api.addr_make("creator");
+    let msg = InstantiateMsg {
+        client_state: serde_json::to_vec(&initial_state.client_state)
+            .unwrap()
+            .into(),
+        consensus_state: serde_json::to_vec(&initial_state.consensus_state)
+            .unwrap()
+            .into(),
+        checksum: b"fuzz".into(),
+    };
+    instantiate(deps.as_mut(), mock_env(), message_info(&creator, &[]), msg).unwrap();
+
+    (deps, latest_slot)
+}
@@ -0,0 +1,16 @@
+//! Writes the JSON schemas of the contract messages to the `schema` directory.
+//!
+//! Run with `just generate-cw-ics08-wasm-eth-schema`.
+
+use cosmwasm_schema::write_api;
+use cw_ics08_wasm_eth::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
+
+fn main() {
+    write_api! {
+        instantiate: InstantiateMsg,
+        execute: ExecuteMsg,
+        query: QueryMsg,
+        migrate: MigrateMsg,
+        sudo: SudoMsg,
+    }
+}
@@ -0,0 +1,377 @@
+//! This module decodes the client messages passed to `VerifyClientMessage`, `UpdateState` and
+//! `CheckForMisbehaviour`.
+//!
+//! A client message is a [`ClientMessageEnvelope`], which tags the encoded message with a version
+//! and a type, so that new message types can be introduced without breaking existing relayers.
+//! The envelope may also attribute the updates of the message to a relayer.
+//! Raw JSON headers and misbehaviour messages are still accepted during a deprecation window.
+//! A header batch, which backfills the consensus states of a linked chain of headers in a single
+//! update, is only accepted in an envelope.
+//!
+//! Clients created with `compressed_client_messages` also accept zlib compressed client messages,
+//! which shrink the update transactions several times over. A compressed message is prefixed with
+//! the [`COMPRESSED_CLIENT_MESSAGE_PREFIX`] magic byte, which a JSON message never starts with,
+//! and is rejected if it inflates beyond [`MAX_DECOMPRESSED_CLIENT_MESSAGE_SIZE`]. zstd is not
+//! supported, as no implementation builds to wasm without a C toolchain.
+
+use ethereum_light_client::header::Header;
+
+use crate::{
+    msg::{ClientMessageEnvelope, EthereumMisbehaviourMsg},
+    ContractError,
+};
+
+/// The current version of the client message envelope
+pub const CLIENT_MESSAGE_VERSION: u32 = 1;
+/// The envelope type of a JSON encoded [`Header`]
+pub const CLIENT_MESSAGE_TYPE_HEADER: &str = "header";
+/// The envelope type of a JSON encoded [`EthereumMisbehaviourMsg`]
+pub const CLIENT_MESSAGE_TYPE_MISBEHAVIOUR: &str = "misbehaviour";
+/// The envelope type of a JSON encoded list of [`Header`]s, applied in order
+pub const CLIENT_MESSAGE_TYPE_HEADER_BATCH: &str = "header_batch";
+/// The maximum number of headers in a header batch
+pub const MAX_HEADER_BATCH_SIZE: usize = 32;
+/// The magic byte prefixing a zlib compressed client message
+pub const COMPRESSED_CLIENT_MESSAGE_PREFIX: u8 = 0x01;
+/// The maximum size of a decompressed client message, which bounds the memory a compressed
+/// message can expand to
+pub const MAX_DECOMPRESSED_CLIENT_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// A decoded client message
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    /// A header to update the client with
+    Header(Box<Header>),
+    /// Evidence of misbehaviour
+    Misbehaviour(Box<EthereumMisbehaviourMsg>),
+    /// Headers to update the client with, each trusting the state produced by the previous one
+    HeaderBatch(Vec<Header>),
+}
+
+impl ClientMessage {
+    /// Decodes a client message, either wrapped in a [`ClientMessageEnvelope`] or, during the
+    /// deprecation window, as a raw JSON header or misbehaviour message.
+    ///
+    /// Compressed messages are decompressed first if `accept_compressed` is set, i.e. if the
+    /// client state accepts them.
+    /// # Errors
+    /// Returns an error if the envelope version or type is not supported, if the message
+    /// cannot be deserialized, or if a compressed message is not accepted or cannot be
+    /// decompressed within the size limit
+    pub fn decode(bz: &[u8], accept_compressed: bool) -> Result<Self, ContractError> {
+        Self::decode_attributed(bz, accept_compressed).map(|(message, _)| message)
+    }
+
+    /// Decodes a client message like [`Self::decode`], along with the relayer its envelope
+    /// attributes it to, if any
+    /// # Errors
+    /// Returns an error if the message cannot be decoded
+    pub fn decode_attributed(
+        bz: &[u8],
+        accept_compressed: bool,
+    ) -> Result<(Self, Option<String>), ContractError> {
+        if let Some((&COMPRESSED_CLIENT_MESSAGE_PREFIX, compressed)) = bz.split_first() {
+            if !accept_compressed {
+                return Err(ContractError::CompressedClientMessageNotAccepted);
+            }
+            let decompressed = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(
+                compressed,
+                MAX_DECOMPRESSED_CLIENT_MESSAGE_SIZE,
+            )
+            .map_err(|err| ContractError::DecompressClientMessageFailed(err.to_string()))?;
+            return Self::decode_json(&decompressed);
+        }
+
+        Self::decode_json(bz)
+    }
+
+    /// Decodes an uncompressed client message, along with the relayer of its envelope
+    fn decode_json(bz: &[u8]) -> Result<(Self, Option<String>), ContractError> {
+        if let Ok(envelope) = serde_json::from_slice::<ClientMessageEnvelope>(bz) {
+            return Ok((Self::from_envelope(&envelope)?, envelope.relayer));
+        }
+
+        if let Ok(header) = serde_json::from_slice::<Header>(bz) {
+            return Ok((Self::Header(Box::new(header)), None));
+        }
+        if let Ok(misbehaviour) = serde_json::from_slice::<EthereumMisbehaviourMsg>(bz) {
+            return Ok((Self::Misbehaviour(Box::new(misbehaviour)), None));
+        }
+
+        Err(ContractError::InvalidClientMessage)
+    }
+
+    /// Decodes the message wrapped in an envelope
+    fn from_envelope(envelope: &ClientMessageEnvelope) -> Result<Self, ContractError> {
+        if envelope.version != CLIENT_MESSAGE_VERSION {
+            return Err(ContractError::UnsupportedClientMessageVersion(
+                envelope.version,
+            ));
+        }
+
+        match envelope.message_type.as_str() {
+            CLIENT_MESSAGE_TYPE_HEADER => Ok(Self::Header(Box::new(
+                serde_json::from_slice(&envelope.data)
+                    .map_err(ContractError::DeserializeClientMessageFailed)?,
+            ))),
+            CLIENT_MESSAGE_TYPE_MISBEHAVIOUR => Ok(Self::Misbehaviour(Box::new(
+                serde_json::from_slice(&envelope.data)
+                    .map_err(ContractError::DeserializeEthMisbehaviourFailed)?,
+            ))),
+            CLIENT_MESSAGE_TYPE_HEADER_BATCH => {
+                let headers: Vec<Header> = serde_json::from_slice(&envelope.data)
+                    .map_err(ContractError::DeserializeClientMessageFailed)?;
+                if headers.is_empty() || headers.len() > MAX_HEADER_BATCH_SIZE {
+                    return Err(ContractError::InvalidHeaderBatchSize {
+                        size: headers.len(),
+                        max: MAX_HEADER_BATCH_SIZE,
+                    });
+                }
+                Ok(Self::HeaderBatch(headers))
+            }
+            message_type => Err(ContractError::UnsupportedClientMessageType(
+                message_type.to_string(),
+            )),
+        }
+    }
+
+    /// Returns the header, or an error if the message is not a header
+    /// # Errors
+    /// Returns an error if the message is misbehaviour or a header batch
+    pub fn into_header(self) -> Result<Header, ContractError> {
+        match self {
+            Self::Header(header) => Ok(*header),
+            Self::Misbehaviour(_) | Self::HeaderBatch(_) => {
+                Err(ContractError::InvalidClientMessage)
+            }
+        }
+    }
+
+    /// Returns the headers to apply in order, or an error if the message is misbehaviour
+    /// # Errors
+    /// Returns an error if the message is misbehaviour
+    pub fn into_headers(self) -> Result<Vec<Header>, ContractError> {
+        match self {
+            Self::Header(header) => Ok(vec![*header]),
+            Self::HeaderBatch(headers) => Ok(headers),
+            Self::Misbehaviour(_) => Err(ContractError::InvalidClientMessage),
+        }
+    }
+
+    /// Returns the misbehaviour, or an error if the message is not misbehaviour
+    /// # Errors
+    /// Returns an error if the message is a header
+    pub fn into_misbehaviour(self) -> Result<EthereumMisbehaviourMsg, ContractError> {
+        match self {
+            Self::Misbehaviour(misbehaviour) => Ok(*misbehaviour),
+            Self::Header(_) | Self::HeaderBatch(_) => Err(ContractError::InvalidClientMessage),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Binary;
+    use ethereum_light_client::test_utils::fixtures::{self, RelayerMessages, StepsFixture};
+    use ibc_proto::ibc::lightclients::wasm::v1::ClientMessage as WasmClientMessage;
+    use prost::Message;
+
+    use super::*;
+
+    fn header_bz() -> Vec<u8> {
+        let fixture: StepsFixture =
+            fixtures::load("TestICS20TransferNativeCosmosCoinsToEthereumAndBack_Groth16");
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, _, _) = relayer_messages.get_sdk_msgs();
+
+        WasmClientMessage::decode(
+            update_client_msgs[0]
+                .client_message
+                .clone()
+                .unwrap()
+                .value
+                .as_slice(),
+        )
+        .unwrap()
+        .data
+    }
+
+    fn envelope(version: u32, message_type: &str, data: Vec<u8>) -> Vec<u8> {
+        serde_json::to_vec(&ClientMessageEnvelope {
+            version,
+            message_type: message_type.to_string(),
+            data: Binary::from(data),
+            relayer: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_client_message() {
+        let header_bz = header_bz();
+        let header: Header = serde_json::from_slice(&header_bz).unwrap();
+
+        // Enveloped header
+        let decoded = ClientMessage::decode(
+            &envelope(
+                CLIENT_MESSAGE_VERSION,
+                CLIENT_MESSAGE_TYPE_HEADER,
+                header_bz.clone(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert_eq!(header, decoded.into_header().unwrap());
+
+        // Raw header, accepted during the deprecation window
+        let decoded = ClientMessage::decode(&header_bz, false).unwrap();
+        assert_eq!(header, decoded.clone().into_header().unwrap());
+        assert!(matches!(
+            decoded.into_misbehaviour(),
+            Err(ContractError::InvalidClientMessage)
+        ));
+    }
+
+    #[test]
+    fn test_decode_attributed_client_message() {
+        let header_bz = header_bz();
+        let header: Header = serde_json::from_slice(&header_bz).unwrap();
+
+        let attributed = serde_json::to_vec(&ClientMessageEnvelope {
+            version: CLIENT_MESSAGE_VERSION,
+            message_type: CLIENT_MESSAGE_TYPE_HEADER.to_string(),
+            data: Binary::from(header_bz.clone()),
+            relayer: Some("cosmos1relayer".to_string()),
+        })
+        .unwrap();
+        let (decoded, relayer) = ClientMessage::decode_attributed(&attributed, false).unwrap();
+        assert_eq!(header, decoded.into_header().unwrap());
+        assert_eq!(relayer.as_deref(), Some("cosmos1relayer"));
+
+        // The messages without an envelope are not attributed
+        let (_, relayer) = ClientMessage::decode_attributed(&header_bz, false).unwrap();
+        assert!(relayer.is_none());
+    }
+
+    #[test]
+    fn test_decode_header_batch() {
+        let header: Header = serde_json::from_slice(&header_bz()).unwrap();
+
+        let batch = vec![header.clone(), header.clone()];
+        let decoded = ClientMessage::decode(
+            &envelope(
+                CLIENT_MESSAGE_VERSION,
+                CLIENT_MESSAGE_TYPE_HEADER_BATCH,
+                serde_json::to_vec(&batch).unwrap(),
+            ),
+            false,
+        )
+        .unwrap();
+        assert_eq!(batch, decoded.clone().into_headers().unwrap());
+        assert!(matches!(
+            decoded.into_header(),
+            Err(ContractError::InvalidClientMessage)
+        ));
+
+        // A single header is applied as a batch of one
+        let decoded = ClientMessage::decode(&header_bz(), false).unwrap();
+        assert_eq!(vec![header.clone()], decoded.into_headers().unwrap());
+
+        for size in [0, MAX_HEADER_BATCH_SIZE + 1] {
+            let err = ClientMessage::decode(
+                &envelope(
+                    CLIENT_MESSAGE_VERSION,
+                    CLIENT_MESSAGE_TYPE_HEADER_BATCH,
+                    serde_json::to_vec(&vec![header.clone(); size]).unwrap(),
+                ),
+                false,
+            )
+            .unwrap_err();
+            assert!(matches!(
+                err,
+                ContractError::InvalidHeaderBatchSize { size: found, .. } if found == size
+            ));
+        }
+    }
+
+    #[test]
+    fn test_decode_unsupported_client_message() {
+        let header_bz = header_bz();
+
+        let err = ClientMessage::decode(
+            &envelope(
+                CLIENT_MESSAGE_VERSION + 1,
+                CLIENT_MESSAGE_TYPE_HEADER,
+                header_bz.clone(),
+            ),
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::UnsupportedClientMessageVersion(version) if version == CLIENT_MESSAGE_VERSION + 1
+        ));
+
+        let err = ClientMessage::decode(
+            &envelope(CLIENT_MESSAGE_VERSION, "ssz_header", header_bz),
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::UnsupportedClientMessageType(message_type) if message_type == "ssz_header"
+        ));
+
+        let err = ClientMessage::decode(
+            &envelope(
+                CLIENT_MESSAGE_VERSION,
+                CLIENT_MESSAGE_TYPE_MISBEHAVIOUR,
+                b"{}".to_vec(),
+            ),
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::DeserializeEthMisbehaviourFailed(_)
+        ));
+
+        assert!(matches!(
+            ClientMessage::decode(b"not a client message", false),
+            Err(ContractError::InvalidClientMessage)
+        ));
+    }
+
+    #[test]
+    fn test_decode_compressed_client_message() {
+        let header_bz = header_bz();
+        let header: Header = serde_json::from_slice(&header_bz).unwrap();
+
+        let mut compressed = vec![COMPRESSED_CLIENT_MESSAGE_PREFIX];
+        compressed.extend(miniz_oxide::deflate::compress_to_vec_zlib(&header_bz, 9));
+        assert!(compressed.len() < header_bz.len());
+
+        let decoded = ClientMessage::decode(&compressed, true).unwrap();
+        assert_eq!(header, decoded.into_header().unwrap());
+
+        // Uncompressed messages are still accepted by clients accepting compressed ones
+        let decoded = ClientMessage::decode(&header_bz, true).unwrap();
+        assert_eq!(header, decoded.into_header().unwrap());
+
+        assert!(matches!(
+            ClientMessage::decode(&compressed, false),
+            Err(ContractError::CompressedClientMessageNotAccepted)
+        ));
+
+        // A message inflating beyond the size limit is rejected
+        let mut bomb = vec![COMPRESSED_CLIENT_MESSAGE_PREFIX];
+        bomb.extend(miniz_oxide::deflate::compress_to_vec_zlib(
+            &vec![b' '; MAX_DECOMPRESSED_CLIENT_MESSAGE_SIZE + 1],
+            9,
+        ));
+        assert!(matches!(
+            ClientMessage::decode(&bomb, true),
+            Err(ContractError::DecompressClientMessageFailed(_))
+        ));
+    }
+}
@@ -1,9 +1,11 @@
 //! This module contains the `CosmWasm` entrypoints for the 08-wasm smart contract
 
-use cosmwasm_std::{entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{
+    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+};
 
 use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
-use crate::{custom_query::EthereumCustomQuery, instantiate, msg::MigrateMsg, query};
+use crate::{custom_query::EthereumCustomQuery, instantiate, msg::MigrateMsg, query, state};
 use crate::{sudo, ContractError};
 
 /// The version of the contracts state.
@@ -49,12 +51,16 @@ pub fn sudo(
         SudoMsg::VerifyNonMembership(verify_non_membership_msg) => {
             sudo::verify_non_membership(deps.as_ref(), verify_non_membership_msg)?
         }
-        SudoMsg::UpdateState(update_state_msg) => sudo::update_state(deps, update_state_msg)?,
+        SudoMsg::UpdateState(update_state_msg) => {
+            return sudo::update_state(deps, update_state_msg);
+        }
         SudoMsg::UpdateStateOnMisbehaviour(misbehaviour_msg) => {
             sudo::misbehaviour(deps, misbehaviour_msg)?
         }
-        SudoMsg::VerifyUpgradeAndUpdateState(_) => todo!(),
-        SudoMsg::MigrateClientStore(_) => todo!(),
+        SudoMsg::VerifyUpgradeAndUpdateState(verify_upgrade_msg) => {
+            sudo::verify_upgrade_and_update_state(deps, verify_upgrade_msg)?
+        }
+        SudoMsg::MigrateClientStore(_) => sudo::migrate_client_store(deps)?,
     };
 
     Ok(Response::default().set_data(result))
@@ -92,7 +98,30 @@ pub fn query(
         QueryMsg::TimestampAtHeight(timestamp_at_height_msg) => {
             query::timestamp_at_height(deps, timestamp_at_height_msg)
         }
-        QueryMsg::Status(_) => query::status(deps),
+        QueryMsg::Status(_) => query::status(deps, env),
+        QueryMsg::SyncCommitteeRoots(sync_committee_roots_msg) => {
+            query::sync_committee_roots(deps, sync_committee_roots_msg)
+        }
+        QueryMsg::SyncCommitteeParticipation(_) => query::sync_committee_participation(deps),
+        QueryMsg::UpdateRelayer(update_relayer_msg) => {
+            query::update_relayer(deps, update_relayer_msg)
+        }
+        QueryMsg::VerifyMembershipAt(verify_membership_at_msg) => {
+            query::verify_membership_at(deps, verify_membership_at_msg)
+        }
+        QueryMsg::ConsensusHeights(consensus_heights_msg) => {
+            query::consensus_heights(deps, consensus_heights_msg)
+        }
+        QueryMsg::LatestHeight(_) => query::latest_height(deps),
+        // The sudo handlers only read the store, and the result is encoded for other contracts
+        QueryMsg::VerifyMembership(verify_membership_msg) => {
+            sudo::verify_membership(deps, verify_membership_msg)?;
+            Ok(to_json_binary(&())?)
+        }
+        QueryMsg::VerifyNonMembership(verify_non_membership_msg) => {
+            sudo::verify_non_membership(deps, verify_non_membership_msg)?;
+            Ok(to_json_binary(&())?)
+        }
     }
 }
 
@@ -109,6 +138,17 @@ pub fn migrate(
     // Check if the state version is older than the current one and update it
     cw2::ensure_from_older_version(deps.storage, CONTRACT_NAME, STATE_VERSION)?;
 
+    // Key the records of the previous versions by revision, and index the heights of their
+    // consensus states, once
+    // A contract without a client state has no consensus states to index.
+    if let Ok(eth_client_state) = state::get_eth_client_state(deps.storage) {
+        let revision_number = eth_client_state.revision_number;
+        state::migrate_slot_keyed_records(deps.storage, revision_number);
+        if state::get_earliest_consensus_height(deps.storage, revision_number).is_none() {
+            state::index_consensus_heights(deps.storage, revision_number);
+        }
+    }
+
     // Re-initialize the client if needed.
     if let Some(instantiate_msg) = msg.instantiate_msg {
         instantiate::client(deps.storage, instantiate_msg)?;
@@ -127,7 +167,7 @@ mod tests {
             Storage,
         };
         use ethereum_light_client::{
-            client_state::ClientState as EthClientState,
+            client_state::{ClientState as EthClientState, ConsensusStateWrites},
             consensus_state::ConsensusState as EthConsensusState,
         };
         use ethereum_types::consensus::fork::{Fork, ForkParameters};
@@ -187,9 +227,15 @@ mod tests {
                 epochs_per_sync_committee_period: 0,
                 latest_slot: 42,
                 latest_execution_block_number: 38,
+                revision_number: 0,
                 ibc_commitment_slot: U256::from(0),
                 ibc_contract_address: Address::default(),
                 is_frozen: false,
+                compressed_client_messages: false,
+                consensus_state_writes: ConsensusStateWrites::All,
+                min_slot_advance: None,
+                consensus_state_retention: None,
+                trusting_period: None,
             };
             let client_state_bz: Vec<u8> = serde_json::to_vec(&client_state).unwrap();
 
@@ -225,7 +271,7 @@ mod tests {
             assert_eq!(msg.checksum, actual_client_state.checksum);
             assert_eq!(msg.client_state, actual_client_state.data);
             assert_eq!(
-                0,
+                client_state.revision_number,
                 actual_client_state.latest_height.unwrap().revision_number
             );
             assert_eq!(
@@ -235,7 +281,9 @@ mod tests {
 
             let actual_wasm_consensus_state_any_bz = deps
                 .storage
-                .get(consensus_db_key(consensus_state.slot).as_bytes())
+                .get(
+                    consensus_db_key(client_state.revision_number, consensus_state.slot).as_bytes(),
+                )
                 .unwrap();
             let actual_wasm_consensus_state_any =
                 Any::decode(actual_wasm_consensus_state_any_bz.as_slice()).unwrap();
@@ -251,14 +299,14 @@ mod tests {
     }
 
     mod integration_tests {
-        use alloy_primitives::{Address, FixedBytes, B256, U256};
+        use alloy_primitives::{keccak256, Address, FixedBytes, B256, U256};
         use cosmwasm_std::{
             coins,
             testing::{message_info, mock_env},
             Binary, Storage, Timestamp,
         };
         use ethereum_light_client::{
-            client_state::ClientState as EthClientState,
+            client_state::{ClientState as EthClientState, ConsensusStateWrites},
             consensus_state::ConsensusState as EthConsensusState,
             header::Header,
             test_utils::fixtures::{
@@ -268,7 +316,9 @@ mod tests {
         use ethereum_types::consensus::fork::{Fork, ForkParameters};
         use ibc_proto::{
             google::protobuf::Any,
-            ibc::lightclients::wasm::v1::{ClientMessage, ClientState as WasmClientState},
+            ibc::lightclients::wasm::v1::{
+                ClientMessage, ClientState as WasmClientState, ConsensusState as WasmConsensusState,
+            },
         };
         use prost::Message;
 
@@ -276,9 +326,19 @@ mod tests {
             contract::{instantiate, migrate, query, sudo},
             msg::{
                 Height, InstantiateMsg, MerklePath, MigrateMsg, QueryMsg, SudoMsg, UpdateStateMsg,
-                UpdateStateResult, VerifyClientMessageMsg, VerifyMembershipMsg,
+                UpdateStateOnMisbehaviourMsg, UpdateStateResult, VerifyClientMessageMsg,
+                VerifyMembershipMsg,
+            },
+            state::{
+                consensus_heights_db_key, get_consensus_heights, get_earliest_consensus_height,
+                get_eth_consensus_state, get_frozen_at, get_sync_committee_participation,
+                get_sync_committee_roots, get_update_relayer, has_misbehaviour_evidence,
+                record_sync_committee_participation, store_consensus_state, store_frozen_at,
+                store_sync_committee_roots, store_update_relayer, SyncCommitteeParticipation,
+                SyncCommitteeRoots, CONSENSUS_HEIGHTS_KEY_PREFIX, HOST_CLIENT_STATE_KEY,
+                SYNC_COMMITTEE_PARTICIPATION_COUNT_KEY, SYNC_COMMITTEE_ROOTS_KEY_PREFIX,
+                UPDATE_RELAYER_KEY_PREFIX,
             },
-            state::HOST_CLIENT_STATE_KEY,
             test::mk_deps,
         };
 
@@ -369,7 +429,7 @@ mod tests {
 
             let query_verify_membership_msg = SudoMsg::VerifyMembership(VerifyMembershipMsg {
                 height: Height {
-                    revision_number: 0,
+                    revision_number: recv_msgs[0].proof_height.unwrap().revision_number,
                     revision_height: recv_msgs[0].proof_height.unwrap().revision_height,
                 },
                 delay_time_period: 0,
@@ -467,7 +527,7 @@ mod tests {
 
             let query_verify_membership_msg = SudoMsg::VerifyMembership(VerifyMembershipMsg {
                 height: Height {
-                    revision_number: 0,
+                    revision_number: recv_msgs[0].proof_height.unwrap().revision_number,
                     revision_height: recv_msgs[0].proof_height.unwrap().revision_height,
                 },
                 delay_time_period: 0,
@@ -566,7 +626,7 @@ mod tests {
 
             let query_verify_membership_msg = SudoMsg::VerifyMembership(VerifyMembershipMsg {
                 height: Height {
-                    revision_number: 0,
+                    revision_number: recv_msgs[0].proof_height.unwrap().revision_number,
                     revision_height: recv_msgs[0].proof_height.unwrap().revision_height,
                 },
                 delay_time_period: 0,
@@ -637,7 +697,7 @@ mod tests {
 
             let query_verify_membership_msg = SudoMsg::VerifyMembership(VerifyMembershipMsg {
                 height: Height {
-                    revision_number: 0,
+                    revision_number: recv_msgs[0].proof_height.unwrap().revision_number,
                     revision_height: recv_msgs[0].proof_height.unwrap().revision_height,
                 },
                 delay_time_period: 0,
@@ -677,6 +737,33 @@ mod tests {
 
             instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+            // The records stored by the versions predating the height index, and the ones keyed
+            // by slot only
+            let revision_number = client_state.revision_number;
+            let slot = consensus_state.slot;
+            let roots = SyncCommitteeRoots {
+                current: Some(B256::repeat_byte(0x01)),
+                next: None,
+            };
+            deps.storage
+                .remove(&consensus_heights_db_key(revision_number, slot));
+            deps.storage.set(
+                &[CONSENSUS_HEIGHTS_KEY_PREFIX.as_bytes(), &slot.to_be_bytes()].concat(),
+                &slot.to_be_bytes(),
+            );
+            deps.storage.set(
+                format!("{SYNC_COMMITTEE_ROOTS_KEY_PREFIX}/{slot}").as_bytes(),
+                &serde_json::to_vec(&roots).unwrap(),
+            );
+            deps.storage.set(
+                format!("{UPDATE_RELAYER_KEY_PREFIX}/{slot}").as_bytes(),
+                creator.as_str().as_bytes(),
+            );
+            assert_eq!(
+                None,
+                get_earliest_consensus_height(deps.as_ref().storage, revision_number)
+            );
+
             // Migrate without any changes (i.e. same state version)
             migrate(
                 deps.as_mut(),
@@ -686,6 +773,33 @@ mod tests {
                 },
             )
             .unwrap();
+
+            // The heights of the stored consensus states are indexed, and the records are keyed by
+            // revision
+            assert_eq!(
+                Some(slot),
+                get_earliest_consensus_height(deps.as_ref().storage, revision_number)
+            );
+            assert_eq!(
+                vec![slot],
+                get_consensus_heights(&deps.storage, revision_number, None, 10)
+            );
+            assert_eq!(
+                roots,
+                get_sync_committee_roots(&deps.storage, revision_number, slot).unwrap()
+            );
+            assert_eq!(
+                Some(creator.to_string()),
+                get_update_relayer(&deps.storage, revision_number, slot)
+            );
+            assert!(deps
+                .storage
+                .get(format!("{SYNC_COMMITTEE_ROOTS_KEY_PREFIX}/{slot}").as_bytes())
+                .is_none());
+            assert!(deps
+                .storage
+                .get(format!("{UPDATE_RELAYER_KEY_PREFIX}/{slot}").as_bytes())
+                .is_none());
         }
 
         #[test]
@@ -729,9 +843,15 @@ mod tests {
                 epochs_per_sync_committee_period: 0,
                 latest_slot: 42,
                 latest_execution_block_number: 38,
+                revision_number: 0,
                 ibc_commitment_slot: U256::from(0),
                 ibc_contract_address: Address::default(),
                 is_frozen: false,
+                compressed_client_messages: false,
+                consensus_state_writes: ConsensusStateWrites::All,
+                min_slot_advance: None,
+                consensus_state_retention: None,
+                trusting_period: None,
             };
             let client_state_bz: Vec<u8> = serde_json::to_vec(&client_state).unwrap();
 
@@ -793,5 +913,150 @@ mod tests {
                 client_state.latest_slot
             );
         }
+
+        #[test]
+        fn test_migrate_with_instantiate_clears_previous_client() {
+            let mut deps = mk_deps();
+            let creator = deps.api.addr_make("creator");
+            let info = message_info(&creator, &coins(1, "uatom"));
+
+            let fixture: StepsFixture = fixtures::load("Test_Electra_Fork");
+            let initial_state: InitialState = fixture.get_data_at_step(0);
+            let slot = initial_state.consensus_state.slot;
+            let revision_number = initial_state.client_state.revision_number;
+
+            let msg = InstantiateMsg {
+                client_state: Binary::from(
+                    serde_json::to_vec(&initial_state.client_state).unwrap(),
+                ),
+                consensus_state: Binary::from(
+                    serde_json::to_vec(&initial_state.consensus_state).unwrap(),
+                ),
+                checksum: b"checksum".into(),
+            };
+            instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+            // The previous client was updated past the slot it is re-initialized at, then frozen
+            let stale_slot = slot + 8;
+            store_consensus_state(
+                &mut deps.storage,
+                &WasmConsensusState {
+                    data: serde_json::to_vec(&initial_state.consensus_state).unwrap(),
+                },
+                revision_number,
+                stale_slot,
+            )
+            .unwrap();
+            let roots = SyncCommitteeRoots {
+                current: Some(B256::repeat_byte(0x01)),
+                next: None,
+            };
+            store_sync_committee_roots(&mut deps.storage, revision_number, slot, &roots).unwrap();
+            store_sync_committee_roots(&mut deps.storage, revision_number, stale_slot, &roots)
+                .unwrap();
+            store_update_relayer(&mut deps.storage, revision_number, stale_slot, &creator);
+            store_frozen_at(&mut deps.storage, stale_slot);
+
+            migrate(
+                deps.as_mut(),
+                mock_env(),
+                MigrateMsg {
+                    instantiate_msg: Some(msg),
+                },
+            )
+            .unwrap();
+
+            // Only the consensus state the client is re-initialized with remains
+            assert_eq!(
+                vec![slot],
+                get_consensus_heights(&deps.storage, revision_number, None, 10)
+            );
+            assert!(get_eth_consensus_state(&deps.storage, revision_number, stale_slot).is_err());
+            assert!(get_eth_consensus_state(&deps.storage, revision_number, slot).is_ok());
+            assert_eq!(None, get_frozen_at(&deps.storage));
+            assert_eq!(
+                None,
+                get_update_relayer(&deps.storage, revision_number, stale_slot)
+            );
+            assert_eq!(
+                SyncCommitteeRoots::default(),
+                get_sync_committee_roots(&deps.storage, revision_number, slot).unwrap()
+            );
+            assert_eq!(
+                SyncCommitteeRoots::default(),
+                get_sync_committee_roots(&deps.storage, revision_number, stale_slot).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_migrate_with_instantiate_after_misbehaviour() {
+            let mut deps = mk_deps();
+            let creator = deps.api.addr_make("creator");
+            let info = message_info(&creator, &coins(1, "uatom"));
+
+            let fixture: StepsFixture = fixtures::load("Test_Electra_Fork");
+            let initial_state: InitialState = fixture.get_data_at_step(0);
+            let slot = initial_state.consensus_state.slot;
+
+            let msg = InstantiateMsg {
+                client_state: Binary::from(
+                    serde_json::to_vec(&initial_state.client_state).unwrap(),
+                ),
+                consensus_state: Binary::from(
+                    serde_json::to_vec(&initial_state.consensus_state).unwrap(),
+                ),
+                checksum: b"checksum".into(),
+            };
+            instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+            // The previous client applied an update, then was frozen by misbehaviour
+            record_sync_committee_participation(
+                &mut deps.storage,
+                &SyncCommitteeParticipation {
+                    slot,
+                    signature_slot: slot + 1,
+                    participants: 400,
+                    committee_size: 512,
+                },
+            )
+            .unwrap();
+            let misbehaviour_msg = || {
+                SudoMsg::UpdateStateOnMisbehaviour(UpdateStateOnMisbehaviourMsg {
+                    client_message: Binary::from(b"evidence".to_vec()),
+                })
+            };
+            sudo(deps.as_mut(), mock_env(), misbehaviour_msg()).unwrap();
+            assert!(has_misbehaviour_evidence(
+                &deps.storage,
+                keccak256(b"evidence")
+            ));
+
+            migrate(
+                deps.as_mut(),
+                mock_env(),
+                MigrateMsg {
+                    instantiate_msg: Some(msg),
+                },
+            )
+            .unwrap();
+
+            // The re-initialized client keeps none of the records of the previous client
+            assert!(!has_misbehaviour_evidence(
+                &deps.storage,
+                keccak256(b"evidence")
+            ));
+            assert!(get_sync_committee_participation(&deps.storage)
+                .unwrap()
+                .is_empty());
+            assert!(deps
+                .storage
+                .get(SYNC_COMMITTEE_PARTICIPATION_COUNT_KEY.as_bytes())
+                .is_none());
+            assert_eq!(None, get_frozen_at(&deps.storage));
+
+            // The same evidence freezes the re-initialized client again
+            sudo(deps.as_mut(), mock_env(), misbehaviour_msg()).unwrap();
+            assert_eq!(Some(slot), get_frozen_at(&deps.storage));
+        }
     }
 }
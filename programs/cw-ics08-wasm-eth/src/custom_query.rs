@@ -1,11 +1,13 @@
 //! This module contains the custom `CosmWasm` query for the Ethereum light client
 
 use alloy_primitives::B256;
-use cosmwasm_std::{Binary, CustomQuery, QuerierWrapper, QueryRequest};
+use cosmwasm_std::{Api, Binary, CustomQuery, QuerierWrapper, QueryRequest};
 use ethereum_light_client::verify::BlsVerify;
 use ethereum_types::consensus::bls::{BlsPublicKey, BlsSignature};
 use thiserror::Error;
 
+use crate::debug::{debug_print, debug_print_result};
+
 /// The custom query for the Ethereum light client
 /// This is used to verify BLS signatures in `CosmosSDK`
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -34,6 +36,8 @@ impl CustomQuery for EthereumCustomQuery {}
 pub struct BlsVerifier<'a> {
     /// The `CosmWasm` querier
     pub querier: QuerierWrapper<'a, EthereumCustomQuery>,
+    /// The `CosmWasm` api, which the signature checks are traced with
+    pub api: &'a dyn Api,
 }
 
 /// The error type for the BLS verifier
@@ -66,50 +70,71 @@ impl BlsVerify for BlsVerifier<'_> {
         msg: B256,
         signature: BlsSignature,
     ) -> Result<(), Self::Error> {
+        // The branches of the update are verified before its signature
+        debug_print(self.api, || {
+            format!(
+                "branches verified, checking the aggregate signature of {} public keys over signing root {msg}",
+                public_keys.len()
+            )
+        });
+        let result = self.query_fast_aggregate_verify(public_keys, msg, signature);
+        debug_print_result(self.api, "signature check", &result);
+        result
+    }
+
+    fn aggregate(&self, public_keys: &[BlsPublicKey]) -> Result<BlsPublicKey, Self::Error> {
         let binary_public_keys: Vec<Binary> = public_keys
             .iter()
             .map(|p| Binary::from(p.to_vec()))
             .collect();
 
         let request: QueryRequest<EthereumCustomQuery> =
-            QueryRequest::Custom(EthereumCustomQuery::AggregateVerify {
+            QueryRequest::Custom(EthereumCustomQuery::Aggregate {
                 public_keys: binary_public_keys,
-                message: Binary::from(msg.to_vec()),
-                signature: Binary::from(signature.to_vec()),
             });
 
-        let is_valid: bool = self
+        let aggregate_key: Binary = self
             .querier
             .query(&request)
             .map_err(|e| BlsVerifierError::FastAggregateVerify(e.to_string()))?;
 
-        if !is_valid {
-            return Err(BlsVerifierError::InvalidSignature {
-                public_keys: public_keys.to_vec(),
-                msg,
-                signature,
-            });
-        }
-
-        Ok(())
+        Ok(BlsPublicKey::try_from(aggregate_key.as_slice())?)
     }
+}
 
-    fn aggregate(&self, public_keys: &[BlsPublicKey]) -> Result<BlsPublicKey, Self::Error> {
+impl BlsVerifier<'_> {
+    /// Verifies the aggregate signature with the [`EthereumCustomQuery::AggregateVerify`] query
+    fn query_fast_aggregate_verify(
+        &self,
+        public_keys: &[BlsPublicKey],
+        msg: B256,
+        signature: BlsSignature,
+    ) -> Result<(), BlsVerifierError> {
         let binary_public_keys: Vec<Binary> = public_keys
             .iter()
             .map(|p| Binary::from(p.to_vec()))
             .collect();
 
         let request: QueryRequest<EthereumCustomQuery> =
-            QueryRequest::Custom(EthereumCustomQuery::Aggregate {
+            QueryRequest::Custom(EthereumCustomQuery::AggregateVerify {
                 public_keys: binary_public_keys,
+                message: Binary::from(msg.to_vec()),
+                signature: Binary::from(signature.to_vec()),
             });
 
-        let aggregate_key: Binary = self
+        let is_valid: bool = self
             .querier
             .query(&request)
             .map_err(|e| BlsVerifierError::FastAggregateVerify(e.to_string()))?;
 
-        Ok(BlsPublicKey::try_from(aggregate_key.as_slice())?)
+        if !is_valid {
+            return Err(BlsVerifierError::InvalidSignature {
+                public_keys: public_keys.to_vec(),
+                msg,
+                signature,
+            });
+        }
+
+        Ok(())
     }
 }
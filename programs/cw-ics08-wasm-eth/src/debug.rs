@@ -0,0 +1,68 @@
+//! This module contains the traces of the verification milestones
+//!
+//! With the `debug-print` feature, the milestones of the header verification, the signature checks
+//! and the walks of the storage proofs are traced with `deps.api.debug`, which local wasmd nodes
+//! print to their logs. Without it, the traces are compiled out and their messages never built.
+
+use cosmwasm_std::Api;
+use ethereum_light_client::header::Header;
+
+/// The prefix of the traces of the contract
+pub const DEBUG_PRINT_PREFIX: &str = "[ethwasm]";
+
+/// Traces a verification milestone if the `debug-print` feature is enabled
+/// The message is only built when it is traced
+pub fn debug_print(api: &dyn Api, message: impl FnOnce() -> String) {
+    #[cfg(feature = "debug-print")]
+    api.debug(&format!("{DEBUG_PRINT_PREFIX} {}", message()));
+    #[cfg(not(feature = "debug-print"))]
+    let _ = (api, message);
+}
+
+/// Traces the outcome of a verification milestone if the `debug-print` feature is enabled
+pub fn debug_print_result<T, E: std::fmt::Display>(
+    api: &dyn Api,
+    milestone: &str,
+    result: &Result<T, E>,
+) {
+    debug_print(api, || match result {
+        Ok(_) => format!("{milestone}: ok"),
+        Err(e) => format!("{milestone}: failed: {e}"),
+    });
+}
+
+/// Returns the summary of a header traced before it is verified
+#[must_use]
+pub fn header_summary(header: &Header) -> String {
+    let update = &header.consensus_update;
+    format!(
+        "finalized slot {}, attested slot {}, signature slot {}, {} of {} sync committee participants, next sync committee {}",
+        update.finalized_header.beacon.slot,
+        update.attested_header.beacon.slot,
+        update.signature_slot,
+        update.sync_aggregate.num_sync_committe_participants(),
+        update.sync_aggregate.sync_committee_size(),
+        if update.next_sync_committee.is_some() {
+            "included"
+        } else {
+            "not included"
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::testing::MockApi;
+
+    use super::*;
+
+    #[test]
+    fn test_debug_print_only_builds_traced_messages() {
+        let mut built = false;
+        debug_print(&MockApi::default(), || {
+            built = true;
+            "milestone".to_string()
+        });
+        assert_eq!(built, cfg!(feature = "debug-print"));
+    }
+}
@@ -1,73 +1,244 @@
 //! Defines the [`ContractError`] type.
+//!
+//! Every error carries a stable numeric code, which prefixes its message as `[ethwasm-<code>]`.
+//! 08-wasm only surfaces the message of a failed contract call, wrapped in its own errors, so the
+//! code lets the relayer classify the failure without matching on the wording of the messages.
+//! The codes must never be reassigned, and new variants take the next unused code.
 
 use cosmwasm_std::StdError;
 use ethereum_light_client::error::EthereumIBCError;
 use thiserror::Error;
 
+/// The prefix of the error code in the messages of the [`ContractError`]s.
+pub const ERROR_CODE_PREFIX: &str = "[ethwasm-";
+
 #[derive(Error, Debug)]
 #[allow(missing_docs, clippy::module_name_repetitions)]
 pub enum ContractError {
-    #[error("{0}")]
+    #[error("[ethwasm-1] {0}")]
     Std(#[from] StdError),
 
-    #[error("unauthorized")]
+    #[error("[ethwasm-2] unauthorized")]
     Unauthorized,
 
-    #[error("client state latest height and slot are not equal")]
+    #[error("[ethwasm-3] client state latest height and slot are not equal")]
     ClientStateSlotMismatch,
 
-    #[error("client and consensus state mismatch")]
+    #[error("[ethwasm-4] client and consensus state mismatch")]
     ClientAndConsensusStateMismatch,
 
-    #[error("serializing client state failed: {0}")]
+    #[error("[ethwasm-5] serializing client state failed: {0}")]
     SerializeClientStateFailed(#[source] serde_json::Error),
 
-    #[error("serializing consensus state failed: {0}")]
+    #[error("[ethwasm-6] serializing consensus state failed: {0}")]
     SerializeConsensusStateFailed(#[source] serde_json::Error),
 
-    #[error("deserializing client state failed: {0}")]
+    #[error("[ethwasm-7] deserializing client state failed: {0}")]
     DeserializeClientStateFailed(#[source] serde_json::Error),
 
-    #[error("deserializing consensus state failed: {0}")]
+    #[error("[ethwasm-8] deserializing consensus state failed: {0}")]
     DeserializeConsensusStateFailed(#[source] serde_json::Error),
 
-    #[error("deserializing client message failed: {0}")]
+    #[error("[ethwasm-9] deserializing client message failed: {0}")]
     DeserializeClientMessageFailed(#[source] serde_json::Error),
 
-    #[error("deserializing ethereum misbehaviour message failed: {0}")]
+    #[error("[ethwasm-10] deserializing ethereum misbehaviour message failed: {0}")]
     DeserializeEthMisbehaviourFailed(#[source] serde_json::Error),
 
-    #[error("verify membership failed: {0}")]
+    #[error("[ethwasm-11] verify membership failed: {0}")]
     VerifyMembershipFailed(#[source] EthereumIBCError),
 
-    #[error("verify non-membership failed: {0}")]
+    #[error("[ethwasm-12] verify non-membership failed: {0}")]
     VerifyNonMembershipFailed(#[source] EthereumIBCError),
 
-    #[error("verify client message failed: {0}")]
+    #[error("[ethwasm-13] verify client message failed: {0}")]
     VerifyClientMessageFailed(#[source] EthereumIBCError),
 
-    #[error("update client state failed: {0}")]
+    #[error("[ethwasm-14] update client state failed: {0}")]
     UpdateClientStateFailed(#[source] EthereumIBCError),
 
-    #[error("unsupported fork version")]
+    #[error("[ethwasm-15] unsupported fork version")]
     UnsupportedForkVersion(#[source] EthereumIBCError),
 
-    #[error("client state not found")]
+    #[error("[ethwasm-16] client is already frozen")]
+    AlreadyFrozen,
+
+    #[error("[ethwasm-17] revision number mismatch (expected: {expected}, found: {found})")]
+    RevisionNumberMismatch { expected: u64, found: u64 },
+
+    #[error("[ethwasm-18] client state not found")]
     ClientStateNotFound,
 
-    #[error("consensus state not found")]
+    #[error("[ethwasm-19] consensus state not found")]
     ConsensusStateNotFound,
 
     // Generic translation errors
-    #[error("prost encoding error: {0}")]
+    #[error("[ethwasm-20] prost encoding error: {0}")]
     ProstEncodeError(#[from] prost::EncodeError),
 
-    #[error("prost decoding error: {0}")]
+    #[error("[ethwasm-21] prost decoding error: {0}")]
     ProstDecodeError(#[from] prost::DecodeError),
 
-    #[error("serde json error: {0}")]
+    #[error("[ethwasm-22] serde json error: {0}")]
     SerdeJsonError(#[from] serde_json::Error),
 
-    #[error("invalid client message")]
+    #[error("[ethwasm-23] invalid client message")]
     InvalidClientMessage,
+
+    #[error("[ethwasm-24] unsupported client message version: {0}")]
+    UnsupportedClientMessageVersion(u32),
+
+    #[error("[ethwasm-25] unsupported client message type: {0}")]
+    UnsupportedClientMessageType(String),
+
+    #[error("[ethwasm-26] invalid header batch size {size}, expected between 1 and {max}")]
+    InvalidHeaderBatchSize { size: usize, max: usize },
+
+    #[error("[ethwasm-27] compressed client messages are not accepted by this client")]
+    CompressedClientMessageNotAccepted,
+
+    #[error("[ethwasm-28] decompressing client message failed: {0}")]
+    DecompressClientMessageFailed(String),
+
+    #[error("[ethwasm-29] invalid relayer address: {0}")]
+    InvalidRelayerAddress(String),
+
+    #[error("[ethwasm-30] verify upgrade failed: {0}")]
+    VerifyUpgradeFailed(#[source] EthereumIBCError),
+
+    #[error("[ethwasm-31] check substitute failed: {0}")]
+    CheckSubstituteFailed(#[source] EthereumIBCError),
+}
+
+impl ContractError {
+    /// Returns the stable code of the error, which prefixes its message.
+    #[must_use]
+    pub const fn code(&self) -> u32 {
+        match self {
+            Self::Std(_) => 1,
+            Self::Unauthorized => 2,
+            Self::ClientStateSlotMismatch => 3,
+            Self::ClientAndConsensusStateMismatch => 4,
+            Self::SerializeClientStateFailed(_) => 5,
+            Self::SerializeConsensusStateFailed(_) => 6,
+            Self::DeserializeClientStateFailed(_) => 7,
+            Self::DeserializeConsensusStateFailed(_) => 8,
+            Self::DeserializeClientMessageFailed(_) => 9,
+            Self::DeserializeEthMisbehaviourFailed(_) => 10,
+            Self::VerifyMembershipFailed(_) => 11,
+            Self::VerifyNonMembershipFailed(_) => 12,
+            Self::VerifyClientMessageFailed(_) => 13,
+            Self::UpdateClientStateFailed(_) => 14,
+            Self::UnsupportedForkVersion(_) => 15,
+            Self::AlreadyFrozen => 16,
+            Self::RevisionNumberMismatch { .. } => 17,
+            Self::ClientStateNotFound => 18,
+            Self::ConsensusStateNotFound => 19,
+            Self::ProstEncodeError(_) => 20,
+            Self::ProstDecodeError(_) => 21,
+            Self::SerdeJsonError(_) => 22,
+            Self::InvalidClientMessage => 23,
+            Self::UnsupportedClientMessageVersion(_) => 24,
+            Self::UnsupportedClientMessageType(_) => 25,
+            Self::InvalidHeaderBatchSize { .. } => 26,
+            Self::CompressedClientMessageNotAccepted => 27,
+            Self::DecompressClientMessageFailed(_) => 28,
+            Self::InvalidRelayerAddress(_) => 29,
+            Self::VerifyUpgradeFailed(_) => 30,
+            Self::CheckSubstituteFailed(_) => 31,
+        }
+    }
+}
+
+/// Returns the code of the first [`ContractError`] in `message`, which may be wrapped in the
+/// errors of the host chain.
+#[must_use]
+pub fn parse_error_code(message: &str) -> Option<u32> {
+    let (_, rest) = message.split_once(ERROR_CODE_PREFIX)?;
+    let (code, _) = rest.split_once(']')?;
+    code.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use ibc_proto::ibc::core::client::v1::Height;
+    use prost::Message;
+
+    use super::*;
+
+    fn json_error() -> serde_json::Error {
+        serde_json::from_str::<u64>("not json").unwrap_err()
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let eth_error = || EthereumIBCError::StorageProofDecode;
+        let errors = vec![
+            ContractError::Std(StdError::generic_err("generic")),
+            ContractError::Unauthorized,
+            ContractError::ClientStateSlotMismatch,
+            ContractError::ClientAndConsensusStateMismatch,
+            ContractError::SerializeClientStateFailed(json_error()),
+            ContractError::SerializeConsensusStateFailed(json_error()),
+            ContractError::DeserializeClientStateFailed(json_error()),
+            ContractError::DeserializeConsensusStateFailed(json_error()),
+            ContractError::DeserializeClientMessageFailed(json_error()),
+            ContractError::DeserializeEthMisbehaviourFailed(json_error()),
+            ContractError::VerifyMembershipFailed(eth_error()),
+            ContractError::VerifyNonMembershipFailed(eth_error()),
+            ContractError::VerifyClientMessageFailed(eth_error()),
+            ContractError::UpdateClientStateFailed(eth_error()),
+            ContractError::UnsupportedForkVersion(eth_error()),
+            ContractError::AlreadyFrozen,
+            ContractError::RevisionNumberMismatch {
+                expected: 1,
+                found: 2,
+            },
+            ContractError::ClientStateNotFound,
+            ContractError::ConsensusStateNotFound,
+            ContractError::ProstEncodeError(
+                Height {
+                    revision_number: 1,
+                    revision_height: 1,
+                }
+                .encode(&mut [0_u8; 0].as_mut_slice())
+                .unwrap_err(),
+            ),
+            ContractError::ProstDecodeError(Height::decode([0xff_u8].as_slice()).unwrap_err()),
+            ContractError::SerdeJsonError(json_error()),
+            ContractError::InvalidClientMessage,
+            ContractError::UnsupportedClientMessageVersion(2),
+            ContractError::UnsupportedClientMessageType("type".to_string()),
+            ContractError::InvalidHeaderBatchSize { size: 0, max: 8 },
+            ContractError::CompressedClientMessageNotAccepted,
+            ContractError::DecompressClientMessageFailed("corrupt".to_string()),
+            ContractError::InvalidRelayerAddress("relayer".to_string()),
+            ContractError::VerifyUpgradeFailed(eth_error()),
+            ContractError::CheckSubstituteFailed(eth_error()),
+        ];
+
+        let codes: BTreeSet<u32> = errors.iter().map(ContractError::code).collect();
+        assert_eq!(codes.len(), errors.len(), "error codes must be unique");
+        for error in &errors {
+            let message = error.to_string();
+            assert!(
+                message.starts_with(&format!("{ERROR_CODE_PREFIX}{}] ", error.code())),
+                "{message}"
+            );
+            assert_eq!(parse_error_code(&message), Some(error.code()));
+        }
+    }
+
+    #[test]
+    fn test_parse_wrapped_error_code() {
+        let message = format!(
+            "failed to execute message; message index: 0: {}: wasm contract call failed",
+            ContractError::AlreadyFrozen
+        );
+        assert_eq!(parse_error_code(&message), Some(16));
+        assert_eq!(parse_error_code("out of gas"), None);
+        assert_eq!(parse_error_code("[ethwasm-x] unknown"), None);
+    }
 }
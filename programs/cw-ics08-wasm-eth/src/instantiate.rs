@@ -14,7 +14,7 @@ use ibc_proto::ibc::{
 
 use crate::{
     msg::InstantiateMsg,
-    state::{store_client_state, store_consensus_state},
+    state::{remove_client_records, store_client_state, store_consensus_state},
     ContractError,
 };
 
@@ -32,7 +32,7 @@ pub fn client(storage: &mut dyn Storage, msg: InstantiateMsg) -> Result<(), Cont
         checksum: msg.checksum.into(),
         data: client_state_bz,
         latest_height: Some(IbcProtoHeight {
-            revision_number: 0,
+            revision_number: client_state.revision_number,
             revision_height: client_state.latest_slot,
         }),
     };
@@ -60,8 +60,16 @@ pub fn client(storage: &mut dyn Storage, msg: InstantiateMsg) -> Result<(), Cont
         )
         .map_err(ContractError::UnsupportedForkVersion)?;
 
+    // A client re-initialized by a migration starts afresh, without the consensus states and the
+    // records of the previous client
+    remove_client_records(storage);
     store_client_state(storage, &wasm_client_state)?;
-    store_consensus_state(storage, &wasm_consensus_state, consensus_state.slot)?;
+    store_consensus_state(
+        storage,
+        &wasm_consensus_state,
+        client_state.revision_number,
+        consensus_state.slot,
+    )?;
 
     Ok(())
 }
@@ -7,8 +7,10 @@
     unused_crate_dependencies
 )]
 
+pub mod client_message;
 pub mod contract;
 pub mod custom_query;
+mod debug;
 mod error;
 pub mod instantiate;
 pub mod msg;
@@ -1,4 +1,8 @@
 //! The messages that are passed between the contract and the ibc-go module
+//!
+//! The message schemas follow the 08-wasm keeper of ibc-go v8 to v10. The message shapes of older
+//! keepers are accepted as well, through serde aliases of their current equivalent, so a single
+//! build of the contract runs on every supported keeper.
 #![allow(clippy::module_name_repetitions)]
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
@@ -34,7 +38,9 @@ pub enum SudoMsg {
     UpdateStateOnMisbehaviour(UpdateStateOnMisbehaviourMsg),
     /// The message to verify the upgrade and update the state
     VerifyUpgradeAndUpdateState(VerifyUpgradeAndUpdateStateMsg),
-    /// The message to migrate the client store
+    /// The message to migrate the client store, sent as `CheckSubstituteAndUpdateState` by the
+    /// keepers predating it
+    #[serde(alias = "check_substitute_and_update_state")]
     MigrateClientStore(MigrateClientStoreMsg),
 }
 
@@ -57,6 +63,39 @@ pub enum QueryMsg {
     /// The message to get the status
     #[returns[StatusResult]]
     Status(StatusMsg),
+
+    /// The message to get the sync committee roots of a consensus state
+    #[returns[SyncCommitteeRootsResult]]
+    SyncCommitteeRoots(SyncCommitteeRootsMsg),
+
+    /// The message to get the sync committee participation of the latest applied updates
+    #[returns[SyncCommitteeParticipationResult]]
+    SyncCommitteeParticipation(SyncCommitteeParticipationMsg),
+
+    /// The message to get the relayer the update of a consensus state is attributed to
+    #[returns[UpdateRelayerResult]]
+    UpdateRelayer(UpdateRelayerMsg),
+
+    /// The message to verify membership against a provided consensus state, without reading or
+    /// writing the stored consensus states
+    #[returns[()]]
+    VerifyMembershipAt(VerifyMembershipAtMsg),
+
+    /// The message to get the heights of the stored consensus states, in ascending order
+    #[returns[ConsensusHeightsResult]]
+    ConsensusHeights(ConsensusHeightsMsg),
+
+    /// The message to get the latest height, for other contracts
+    #[returns[LatestHeightResult]]
+    LatestHeight(LatestHeightMsg),
+
+    /// The message to verify membership, for other contracts
+    #[returns[()]]
+    VerifyMembership(VerifyMembershipMsg),
+
+    /// The message to verify non-membership, for other contracts
+    #[returns[()]]
+    VerifyNonMembership(VerifyNonMembershipMsg),
 }
 
 /// The message to migrate the contract
@@ -72,12 +111,15 @@ pub struct VerifyMembershipMsg {
     /// The proof height
     pub height: Height,
     /// The delay time period (unused)
+    #[serde(default)]
     pub delay_time_period: u64,
     /// The delay block period (unused)
+    #[serde(default)]
     pub delay_block_period: u64,
     /// The proof bytes
     pub proof: Binary,
     /// The path to the value
+    #[serde(alias = "path")]
     pub merkle_path: MerklePath,
     /// The value to verify
     pub value: Binary,
@@ -89,12 +131,15 @@ pub struct VerifyNonMembershipMsg {
     /// The proof height
     pub height: Height,
     /// The delay time period (unused)
+    #[serde(default)]
     pub delay_time_period: u64,
     /// The delay block period (unused)
+    #[serde(default)]
     pub delay_block_period: u64,
     /// The proof bytes
     pub proof: Binary,
     /// The path to the empty value
+    #[serde(alias = "path")]
     pub merkle_path: MerklePath,
 }
 
@@ -129,6 +174,22 @@ pub struct VerifyUpgradeAndUpdateStateMsg {
 #[cw_serde]
 pub struct MigrateClientStoreMsg {}
 
+/// The versioned envelope of a client message
+#[cw_serde]
+pub struct ClientMessageEnvelope {
+    /// The version of the envelope
+    pub version: u32,
+    /// The type of the wrapped message, such as `header` or `misbehaviour`
+    #[serde(rename = "type")]
+    pub message_type: String,
+    /// The encoded message
+    pub data: Binary,
+    /// The address of the relayer the updates of the message are attributed to, on the chain
+    /// hosting the client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relayer: Option<String>,
+}
+
 /// The misbehaviour message for the ethereum light client
 #[cw_serde]
 pub struct EthereumMisbehaviourMsg {
@@ -167,11 +228,58 @@ pub struct TimestampAtHeightMsg {
 #[cw_serde]
 pub struct StatusMsg {}
 
+/// The message to get the sync committee roots of a consensus state
+#[cw_serde]
+pub struct SyncCommitteeRootsMsg {
+    /// The height of the consensus state
+    pub height: Height,
+}
+
+/// The message to get the sync committee participation of the latest applied updates
+#[cw_serde]
+pub struct SyncCommitteeParticipationMsg {}
+
+/// The message to get the relayer the update of a consensus state is attributed to
+#[cw_serde]
+pub struct UpdateRelayerMsg {
+    /// The height of the consensus state
+    pub height: Height,
+}
+
+/// The message to get a page of the heights of the stored consensus states
+#[cw_serde]
+pub struct ConsensusHeightsMsg {
+    /// The height after which the page starts, from the earliest height if unset
+    #[serde(default)]
+    pub start_after: Option<Height>,
+    /// The maximum number of heights in the page
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// The latest height query message
+#[cw_serde]
+pub struct LatestHeightMsg {}
+
+/// The message to verify membership against a provided consensus state
+#[cw_serde]
+pub struct VerifyMembershipAtMsg {
+    /// The consensus state to verify against, encoded like the consensus state of the client
+    pub consensus_state: Binary,
+    /// The proof bytes
+    pub proof: Binary,
+    /// The path to the value
+    #[serde(alias = "path")]
+    pub merkle_path: MerklePath,
+    /// The value to verify
+    pub value: Binary,
+}
+
 /// Height of the ethereum chain
 #[cw_serde]
 pub struct Height {
     /// The revision that the client is currently on
-    /// Always zero in the ethereum light client
+    /// Must match the revision number of the ethereum client state
     #[serde(default)]
     pub revision_number: u64,
     /// The execution height of ethereum chain
@@ -194,10 +302,24 @@ pub struct MerklePath {
 }
 
 /// The response to the status query
+///
+/// `ibc-go` only reads `status`, the other fields let tooling display why a client is not active.
 #[cw_serde]
 pub struct StatusResult {
     /// The status of the client
     pub status: String,
+    /// The latest slot of the client
+    pub latest_slot: u64,
+    /// The timestamp of the latest consensus state (in nanoseconds)
+    pub latest_timestamp: u64,
+    /// The latest slot of the client when it was frozen, if frozen
+    pub frozen_at: Option<u64>,
+    /// The timestamp the client expired at (in nanoseconds), if expired.
+    /// A client without a trusting period does not expire, so this is always `None` for it.
+    pub expired_at: Option<u64>,
+    /// The height of the earliest stored consensus state, the lowest height packets can be proven
+    /// at
+    pub earliest_height: Option<Height>,
 }
 
 /// The client status types
@@ -220,6 +342,22 @@ impl std::fmt::Display for Status {
     }
 }
 
+/// The response to the consensus heights query
+#[cw_serde]
+pub struct ConsensusHeightsResult {
+    /// The heights of the stored consensus states, in ascending order
+    pub heights: Vec<Height>,
+}
+
+/// The response to the latest height query
+#[cw_serde]
+pub struct LatestHeightResult {
+    /// The latest height of the client
+    pub height: Height,
+    /// The timestamp of the latest consensus state (in nanoseconds)
+    pub timestamp: u64,
+}
+
 /// The response to the check for misbehaviour query
 #[cw_serde]
 pub struct CheckForMisbehaviourResult {
@@ -233,3 +371,105 @@ pub struct TimestampAtHeightResult {
     /// The timestamp at the height (in nanoseconds)
     pub timestamp: u64,
 }
+
+/// The response to the sync committee roots query, with the roots as hex strings
+#[cw_serde]
+pub struct SyncCommitteeRootsResult {
+    /// The hash tree root of the current sync committee, if known
+    pub current_sync_committee_root: Option<String>,
+    /// The hash tree root of the next sync committee, if known
+    pub next_sync_committee_root: Option<String>,
+}
+
+/// The response to the sync committee participation query
+///
+/// A participation trending down towards `min_sync_committee_participants` is an early warning
+/// that the updates are about to be rejected by the client.
+#[cw_serde]
+pub struct SyncCommitteeParticipationResult {
+    /// The minimum number of sync committee participants of an update accepted by the client
+    pub min_sync_committee_participants: u64,
+    /// The participation of the latest applied updates, oldest first
+    pub updates: Vec<UpdateParticipation>,
+}
+
+/// The response to the update relayer query
+#[cw_serde]
+pub struct UpdateRelayerResult {
+    /// The relayer the update was attributed to by its client message, if any
+    pub relayer: Option<String>,
+}
+
+/// The sync committee participation of an applied update
+#[cw_serde]
+pub struct UpdateParticipation {
+    /// The finalized slot of the update
+    pub slot: u64,
+    /// The slot the sync aggregate of the update was signed at
+    pub signature_slot: u64,
+    /// The number of sync committee members that signed the update
+    pub participants: u64,
+    /// The size of the sync committee
+    pub committee_size: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_sudo_msgs() {
+        let msg: SudoMsg =
+            serde_json::from_str(r#"{"check_substitute_and_update_state":{}}"#).unwrap();
+        assert_eq!(msg, SudoMsg::MigrateClientStore(MigrateClientStoreMsg {}));
+
+        let msg: SudoMsg = serde_json::from_str(r#"{"migrate_client_store":{}}"#).unwrap();
+        assert_eq!(msg, SudoMsg::MigrateClientStore(MigrateClientStoreMsg {}));
+    }
+
+    #[test]
+    fn test_legacy_verify_membership_msg() {
+        let msg: SudoMsg = serde_json::from_str(
+            r#"{"verify_membership":{"height":{"revision_height":42},"proof":"","path":{"key_path":[]},"value":""}}"#,
+        )
+        .unwrap();
+        let SudoMsg::VerifyMembership(msg) = msg else {
+            panic!("unexpected sudo message: {msg:?}");
+        };
+        assert_eq!(msg.height.revision_height, 42);
+        assert_eq!(msg.delay_time_period, 0);
+        assert_eq!(msg.delay_block_period, 0);
+    }
+
+    #[test]
+    fn test_schema() {
+        let api = cosmwasm_schema::generate_api! {
+            instantiate: InstantiateMsg,
+            execute: ExecuteMsg,
+            query: QueryMsg,
+            migrate: MigrateMsg,
+            sudo: SudoMsg,
+        };
+
+        // Every query has the schema of its response
+        let responses = api.responses.clone().unwrap();
+        for query in [
+            "verify_client_message",
+            "check_for_misbehaviour",
+            "timestamp_at_height",
+            "status",
+            "sync_committee_roots",
+            "update_relayer",
+            "verify_membership_at",
+            "consensus_heights",
+            "latest_height",
+            "verify_membership",
+            "verify_non_membership",
+        ] {
+            assert!(responses.contains_key(query), "no response for {query}");
+        }
+
+        let schema = api.render().to_string().unwrap();
+        assert!(schema.contains("check_substitute_and_update_state"));
+    }
+}
@@ -1,17 +1,35 @@
 //! This module contains the query message handlers
 
 use cosmwasm_std::{to_json_binary, Binary, Deps, Env};
+use ethereum_light_client::{
+    consensus_state::ConsensusState as EthConsensusState, update::update_consensus_state,
+};
 
 use crate::{
+    client_message::ClientMessage,
     custom_query::{BlsVerifier, EthereumCustomQuery},
+    debug::{debug_print, debug_print_result, header_summary},
     msg::{
-        CheckForMisbehaviourMsg, CheckForMisbehaviourResult, EthereumMisbehaviourMsg, Status,
-        StatusResult, TimestampAtHeightMsg, TimestampAtHeightResult, VerifyClientMessageMsg,
+        CheckForMisbehaviourMsg, CheckForMisbehaviourResult, ConsensusHeightsMsg,
+        ConsensusHeightsResult, Height, LatestHeightResult, Status, StatusResult,
+        SyncCommitteeParticipationResult, SyncCommitteeRootsMsg, SyncCommitteeRootsResult,
+        TimestampAtHeightMsg, TimestampAtHeightResult, UpdateParticipation, UpdateRelayerMsg,
+        UpdateRelayerResult, VerifyClientMessageMsg, VerifyMembershipAtMsg,
+    },
+    state::{
+        get_consensus_heights, get_earliest_consensus_height, get_eth_client_state,
+        get_eth_consensus_state, get_frozen_at, get_sync_committee_participation,
+        get_sync_committee_roots, get_update_relayer,
     },
-    state::{get_eth_client_state, get_eth_consensus_state},
+    sudo::{ensure_revision_number, is_duplicate_update, proof_walk_summary},
     ContractError,
 };
 
+/// The number of heights returned by the consensus heights query when no limit is given
+pub const DEFAULT_CONSENSUS_HEIGHTS_LIMIT: u32 = 30;
+/// The maximum number of heights returned by the consensus heights query
+pub const MAX_CONSENSUS_HEIGHTS_LIMIT: u32 = 100;
+
 /// Verifies the client message (header) that will be used for updating the state of the light client
 /// The actual verification logic is done in the ethereum light client package
 /// # Errors
@@ -28,44 +46,112 @@ pub fn verify_client_message(
 
     let bls_verifier = BlsVerifier {
         querier: deps.querier,
+        api: deps.api,
     };
 
-    if let Ok(header) = serde_json::from_slice(&verify_client_message_msg.client_message) {
-        let eth_consensus_state =
-            get_eth_consensus_state(deps.storage, eth_client_state.latest_slot)?;
-
-        ethereum_light_client::verify::verify_header(
-            &eth_consensus_state,
-            &eth_client_state,
-            env.block.time.seconds(),
-            &header,
-            bls_verifier,
-        )
-        .map_err(ContractError::VerifyClientMessageFailed)?;
-
-        return Ok(Binary::default());
+    match ClientMessage::decode(
+        &verify_client_message_msg.client_message,
+        eth_client_state.compressed_client_messages,
+    )? {
+        // An update that was already applied, e.g. by another relayer, needs no verification
+        ClientMessage::Header(header)
+            if is_duplicate_update(deps.storage, &eth_client_state, &header) =>
+        {
+            debug_print(deps.api, || {
+                format!("duplicate header: {}", header_summary(&header))
+            });
+        }
+        ClientMessage::Header(header) => {
+            let eth_consensus_state = get_eth_consensus_state(
+                deps.storage,
+                eth_client_state.revision_number,
+                eth_client_state.latest_slot,
+            )?;
+
+            debug_print(deps.api, || {
+                format!("verifying header: {}", header_summary(&header))
+            });
+            let result = ethereum_light_client::verify::verify_header(
+                &eth_consensus_state,
+                &eth_client_state,
+                env.block.time.seconds(),
+                &header,
+                bls_verifier,
+            );
+            debug_print_result(deps.api, "header verification", &result);
+            result.map_err(ContractError::VerifyClientMessageFailed)?;
+        }
+        // Every header is verified against the state produced by the previous one, as they are
+        // applied in order, and the headers that were already applied are skipped like a single
+        // one
+        ClientMessage::HeaderBatch(headers) => {
+            let mut eth_client_state = eth_client_state;
+            let mut eth_consensus_state = get_eth_consensus_state(
+                deps.storage,
+                eth_client_state.revision_number,
+                eth_client_state.latest_slot,
+            )?;
+
+            let batch_size = headers.len();
+            for (i, header) in headers.into_iter().enumerate() {
+                if is_duplicate_update(deps.storage, &eth_client_state, &header) {
+                    debug_print(deps.api, || {
+                        format!(
+                            "duplicate header {} of {batch_size}: {}",
+                            i + 1,
+                            header_summary(&header)
+                        )
+                    });
+                    continue;
+                }
+                debug_print(deps.api, || {
+                    format!(
+                        "verifying header {} of {batch_size}: {}",
+                        i + 1,
+                        header_summary(&header)
+                    )
+                });
+                let result = ethereum_light_client::verify::verify_header(
+                    &eth_consensus_state,
+                    &eth_client_state,
+                    env.block.time.seconds(),
+                    &header,
+                    BlsVerifier {
+                        querier: deps.querier,
+                        api: deps.api,
+                    },
+                );
+                debug_print_result(deps.api, "header verification", &result);
+                result.map_err(ContractError::VerifyClientMessageFailed)?;
+
+                let (_, updated_consensus_state, updated_client_state) =
+                    update_consensus_state(eth_consensus_state, eth_client_state.clone(), &header)
+                        .map_err(ContractError::UpdateClientStateFailed)?;
+                eth_consensus_state = updated_consensus_state;
+                eth_client_state = updated_client_state.unwrap_or(eth_client_state);
+            }
+        }
+        ClientMessage::Misbehaviour(misbehaviour) => {
+            let eth_consensus_state = get_eth_consensus_state(
+                deps.storage,
+                eth_client_state.revision_number,
+                misbehaviour.trusted_slot,
+            )?;
+
+            ethereum_light_client::misbehaviour::verify_misbehaviour(
+                &eth_client_state,
+                &eth_consensus_state,
+                &misbehaviour.sync_committee,
+                &misbehaviour.update_1,
+                &misbehaviour.update_2,
+                env.block.time.seconds(),
+                bls_verifier,
+            )
+            .map_err(ContractError::VerifyClientMessageFailed)?;
+        }
     }
 
-    if let Ok(misbehaviour) =
-        serde_json::from_slice::<EthereumMisbehaviourMsg>(&verify_client_message_msg.client_message)
-    {
-        let eth_consensus_state = get_eth_consensus_state(deps.storage, misbehaviour.trusted_slot)?;
-
-        ethereum_light_client::misbehaviour::verify_misbehaviour(
-            &eth_client_state,
-            &eth_consensus_state,
-            &misbehaviour.sync_committee,
-            &misbehaviour.update_1,
-            &misbehaviour.update_2,
-            env.block.time.seconds(),
-            bls_verifier,
-        )
-        .map_err(ContractError::VerifyClientMessageFailed)?;
-
-        return Ok(Binary::default());
-    }
-
-    Err(ContractError::InvalidClientMessage)
+    Ok(Binary::default())
 }
 
 /// Checks for misbehaviour. Returning an error means no misbehaviour was found.
@@ -80,16 +166,21 @@ pub fn check_for_misbehaviour(
     env: Env,
     check_for_misbehaviour_msg: CheckForMisbehaviourMsg,
 ) -> Result<Binary, ContractError> {
-    let misbehaviour = serde_json::from_slice::<EthereumMisbehaviourMsg>(
-        &check_for_misbehaviour_msg.client_message,
-    )
-    .map_err(ContractError::DeserializeEthMisbehaviourFailed)?;
-
     let eth_client_state = get_eth_client_state(deps.storage)?;
-    let eth_consensus_state = get_eth_consensus_state(deps.storage, misbehaviour.trusted_slot)?;
+    let misbehaviour = ClientMessage::decode(
+        &check_for_misbehaviour_msg.client_message,
+        eth_client_state.compressed_client_messages,
+    )?
+    .into_misbehaviour()?;
+    let eth_consensus_state = get_eth_consensus_state(
+        deps.storage,
+        eth_client_state.revision_number,
+        misbehaviour.trusted_slot,
+    )?;
 
     let bls_verifier = BlsVerifier {
         querier: deps.querier,
+        api: deps.api,
     };
 
     ethereum_light_client::misbehaviour::verify_misbehaviour(
@@ -118,8 +209,13 @@ pub fn timestamp_at_height(
     deps: Deps<EthereumCustomQuery>,
     timestamp_at_height_msg: TimestampAtHeightMsg,
 ) -> Result<Binary, ContractError> {
-    let eth_consensus_state =
-        get_eth_consensus_state(deps.storage, timestamp_at_height_msg.height.revision_height)?;
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    ensure_revision_number(&eth_client_state, &timestamp_at_height_msg.height)?;
+    let eth_consensus_state = get_eth_consensus_state(
+        deps.storage,
+        eth_client_state.revision_number,
+        timestamp_at_height_msg.height.revision_height,
+    )?;
 
     let nano_timestamp = eth_consensus_state.timestamp * 1_000_000_000; // ibc-go expects nanoseconds
 
@@ -128,47 +224,264 @@ pub fn timestamp_at_height(
     })?)
 }
 
-/// Gets the status of the light client
+/// Gets the hash tree roots of the current and next sync committees of the consensus state at a
+/// height, so that they can be compared across clients and chains
+/// # Returns
+/// The roots as hex strings, a root is left out until a header carrying its sync committee was
+/// applied
+/// # Errors
+/// Errors if the revision number does not match, or if there is no consensus state at the height.
+pub fn sync_committee_roots(
+    deps: Deps<EthereumCustomQuery>,
+    sync_committee_roots_msg: SyncCommitteeRootsMsg,
+) -> Result<Binary, ContractError> {
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    ensure_revision_number(&eth_client_state, &sync_committee_roots_msg.height)?;
+    let slot = sync_committee_roots_msg.height.revision_height;
+    // Only the roots of existing consensus states are reported
+    get_eth_consensus_state(deps.storage, eth_client_state.revision_number, slot)?;
+
+    let roots = get_sync_committee_roots(deps.storage, eth_client_state.revision_number, slot)?;
+    Ok(to_json_binary(&SyncCommitteeRootsResult {
+        current_sync_committee_root: roots.current.map(|root| root.to_string()),
+        next_sync_committee_root: roots.next.map(|root| root.to_string()),
+    })?)
+}
+
+/// Gets the relayer the update of a consensus state is attributed to by its client message, so
+/// that chains can reward the relayers of the updates from on-chain data
+/// # Errors
+/// Returns an error if the revision number does not match, or if there is no consensus state at
+/// the height
+/// # Returns
+/// The relayer of the update, if it was attributed
+pub fn update_relayer(
+    deps: Deps<EthereumCustomQuery>,
+    update_relayer_msg: UpdateRelayerMsg,
+) -> Result<Binary, ContractError> {
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    ensure_revision_number(&eth_client_state, &update_relayer_msg.height)?;
+    let slot = update_relayer_msg.height.revision_height;
+    // Only the relayers of existing consensus states are reported
+    get_eth_consensus_state(deps.storage, eth_client_state.revision_number, slot)?;
+
+    Ok(to_json_binary(&UpdateRelayerResult {
+        relayer: get_update_relayer(deps.storage, eth_client_state.revision_number, slot),
+    })?)
+}
+
+/// Gets the sync committee participation of the latest applied updates, along with the minimum
+/// participation accepted by the client, so that a degrading participation is noticed before the
+/// updates start failing
+/// # Errors
+/// Returns an error if the client state or the recorded participation cannot be read
+/// # Returns
+/// The participation of the latest applied updates, oldest first
+pub fn sync_committee_participation(
+    deps: Deps<EthereumCustomQuery>,
+) -> Result<Binary, ContractError> {
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    let updates = get_sync_committee_participation(deps.storage)?
+        .into_iter()
+        .map(|participation| UpdateParticipation {
+            slot: participation.slot,
+            signature_slot: participation.signature_slot,
+            participants: participation.participants,
+            committee_size: participation.committee_size,
+        })
+        .collect();
+
+    Ok(to_json_binary(&SyncCommitteeParticipationResult {
+        min_sync_committee_participants: eth_client_state.min_sync_committee_participants,
+        updates,
+    })?)
+}
+
+/// Verifies the membership of a value against the state root of the provided consensus state,
+/// so that other contracts and off-chain services can reuse the proof verification of the client.
+/// The consensus state is trusted as is: the stored consensus states are neither read nor written,
+/// only the client state is read for the location of the commitments.
+/// # Errors
+/// Returns an error if the consensus state cannot be deserialized, or if the membership proof
+/// verification fails
 /// # Returns
-/// The current status of the client
+/// An empty JSON result
+pub fn verify_membership_at(
+    deps: Deps<EthereumCustomQuery>,
+    verify_membership_at_msg: VerifyMembershipAtMsg,
+) -> Result<Binary, ContractError> {
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    let eth_consensus_state: EthConsensusState =
+        serde_json::from_slice(&verify_membership_at_msg.consensus_state)
+            .map_err(ContractError::DeserializeConsensusStateFailed)?;
+
+    debug_print(deps.api, || {
+        proof_walk_summary(
+            "membership",
+            &Height {
+                revision_number: eth_client_state.revision_number,
+                revision_height: eth_consensus_state.slot,
+            },
+            &verify_membership_at_msg.proof,
+            &verify_membership_at_msg.merkle_path.key_path,
+        )
+    });
+    let result = ethereum_light_client::membership::verify_membership(
+        eth_consensus_state,
+        eth_client_state,
+        verify_membership_at_msg.proof.into(),
+        verify_membership_at_msg
+            .merkle_path
+            .key_path
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        verify_membership_at_msg.value.into(),
+    );
+    debug_print_result(deps.api, "membership proof walk", &result);
+    result.map_err(ContractError::VerifyMembershipFailed)?;
+
+    // Encoded as JSON, so that other contracts can decode the result of the query
+    Ok(to_json_binary(&())?)
+}
+
+/// Gets the latest height of the light client, and the timestamp of its consensus state
 /// # Errors
-/// Errors if the client state can't be deserialized.
-pub fn status(deps: Deps<EthereumCustomQuery>) -> Result<Binary, ContractError> {
+/// Errors if the client or the latest consensus state can't be deserialized.
+pub fn latest_height(deps: Deps<EthereumCustomQuery>) -> Result<Binary, ContractError> {
     let eth_client_state = get_eth_client_state(deps.storage)?;
+    let eth_consensus_state = get_eth_consensus_state(
+        deps.storage,
+        eth_client_state.revision_number,
+        eth_client_state.latest_slot,
+    )?;
+
+    Ok(to_json_binary(&LatestHeightResult {
+        height: Height {
+            revision_number: eth_client_state.revision_number,
+            revision_height: eth_client_state.latest_slot,
+        },
+        timestamp: eth_consensus_state.timestamp * 1_000_000_000, // ibc-go expects nanoseconds
+    })?)
+}
 
-    if eth_client_state.is_frozen {
-        return Ok(to_json_binary(&StatusResult {
-            status: Status::Frozen.to_string(),
-        })?);
+/// Gets a page of the heights of the stored consensus states of the current revision, in ascending
+/// order, read from the height index without scanning the consensus states
+/// # Errors
+/// Errors if the client state can't be deserialized, or if the revision number of the start height
+/// does not match the one of the client
+pub fn consensus_heights(
+    deps: Deps<EthereumCustomQuery>,
+    consensus_heights_msg: ConsensusHeightsMsg,
+) -> Result<Binary, ContractError> {
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    if let Some(start_after) = &consensus_heights_msg.start_after {
+        ensure_revision_number(&eth_client_state, start_after)?;
     }
+    let limit = consensus_heights_msg
+        .limit
+        .unwrap_or(DEFAULT_CONSENSUS_HEIGHTS_LIMIT)
+        .min(MAX_CONSENSUS_HEIGHTS_LIMIT);
+
+    let heights = get_consensus_heights(
+        deps.storage,
+        eth_client_state.revision_number,
+        consensus_heights_msg
+            .start_after
+            .map(|height| height.revision_height),
+        limit as usize,
+    )
+    .into_iter()
+    .map(|slot| Height {
+        revision_number: eth_client_state.revision_number,
+        revision_height: slot,
+    })
+    .collect();
+
+    Ok(to_json_binary(&ConsensusHeightsResult { heights })?)
+}
+
+/// Gets the status of the light client
+/// # Returns
+/// The current status of the client, along with the latest slot and timestamp and when the
+/// client was frozen or expired
+/// # Errors
+/// Errors if the client or the latest consensus state can't be deserialized.
+#[allow(clippy::needless_pass_by_value)]
+pub fn status(deps: Deps<EthereumCustomQuery>, env: Env) -> Result<Binary, ContractError> {
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    let eth_consensus_state = get_eth_consensus_state(
+        deps.storage,
+        eth_client_state.revision_number,
+        eth_client_state.latest_slot,
+    )?;
+
+    // The client expires once its trusting period elapsed since its latest consensus state
+    let expired_at = eth_client_state
+        .expires_at(eth_consensus_state.timestamp)
+        .filter(|expires_at| env.block.time.seconds() >= *expires_at);
+    let status = if eth_client_state.is_frozen {
+        Status::Frozen
+    } else if expired_at.is_some() {
+        Status::Expired
+    } else {
+        Status::Active
+    };
 
     Ok(to_json_binary(&StatusResult {
-        status: Status::Active.to_string(),
+        status: status.to_string(),
+        latest_slot: eth_client_state.latest_slot,
+        latest_timestamp: eth_consensus_state.timestamp * 1_000_000_000, // ibc-go expects nanoseconds
+        frozen_at: get_frozen_at(deps.storage),
+        expired_at: expired_at.map(|expired_at| expired_at.saturating_mul(1_000_000_000)),
+        earliest_height: get_earliest_consensus_height(
+            deps.storage,
+            eth_client_state.revision_number,
+        )
+        .map(|slot| Height {
+            revision_number: eth_client_state.revision_number,
+            revision_height: slot,
+        }),
     })?)
 }
 
 #[cfg(test)]
 mod tests {
+    use alloy_primitives::U256;
     use cosmwasm_std::{
         coins, from_json,
         testing::{message_info, mock_env},
-        Binary, Timestamp,
+        Binary, Deps, Timestamp,
     };
     use ethereum_light_client::{
         header::Header,
-        test_utils::fixtures::{self, InitialState, RelayerMessages, StepsFixture},
+        membership::evm_ics26_commitment_path,
+        test_utils::{
+            fixtures::{self, InitialState, RelayerMessages, StepsFixture},
+            storage_trie::storage_proof_corpus,
+            update_generator::UpdateGenerator,
+        },
     };
     use ibc_proto::ibc::lightclients::wasm::v1::ClientMessage;
     use prost::Message;
+    use tree_hash::TreeHash;
 
     use crate::{
         contract::{instantiate, query},
+        custom_query::EthereumCustomQuery,
         msg::{
-            Height, QueryMsg, StatusMsg, StatusResult, TimestampAtHeightMsg,
-            TimestampAtHeightResult, VerifyClientMessageMsg,
+            Height, MerklePath, QueryMsg, StatusMsg, StatusResult, SyncCommitteeParticipationMsg,
+            SyncCommitteeParticipationResult, SyncCommitteeRootsMsg, SyncCommitteeRootsResult,
+            TimestampAtHeightMsg, TimestampAtHeightResult, UpdateStateMsg, VerifyClientMessageMsg,
+            VerifyMembershipAtMsg,
         },
         query::timestamp_at_height,
+        state::{
+            get_eth_client_state, get_eth_consensus_state, record_sync_committee_participation,
+            SyncCommitteeParticipation, SYNC_COMMITTEE_PARTICIPATION_HISTORY,
+        },
         test::mk_deps,
+        ContractError,
     };
 
     use super::verify_client_message;
@@ -260,7 +573,7 @@ mod tests {
             deps.as_ref(),
             TimestampAtHeightMsg {
                 height: Height {
-                    revision_number: 0,
+                    revision_number: client_state.revision_number,
                     revision_height: consensus_state.slot,
                 },
             },
@@ -273,6 +586,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_timestamp_at_height_revision_number_mismatch() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let fixture: StepsFixture =
+            fixtures::load("TestICS20TransferNativeCosmosCoinsToEthereumAndBack_Groth16");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+
+        let client_state = initial_state.client_state;
+        let consensus_state = initial_state.consensus_state;
+
+        let client_state_bz: Vec<u8> = serde_json::to_vec(&client_state).unwrap();
+        let consensus_state_bz: Vec<u8> = serde_json::to_vec(&consensus_state).unwrap();
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(client_state_bz),
+            consensus_state: Binary::from(consensus_state_bz),
+            checksum: b"checksum".into(),
+        };
+
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let err = timestamp_at_height(
+            deps.as_ref(),
+            TimestampAtHeightMsg {
+                height: Height {
+                    revision_number: client_state.revision_number + 1,
+                    revision_height: consensus_state.slot,
+                },
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RevisionNumberMismatch { expected, found }
+                if expected == client_state.revision_number && found == expected + 1
+        ));
+    }
+
     #[test]
     fn test_status() {
         let mut deps = mk_deps();
@@ -300,6 +655,314 @@ mod tests {
 
         let res = query(deps.as_ref(), mock_env(), QueryMsg::Status(StatusMsg {})).unwrap();
         let status_response: StatusResult = from_json(&res).unwrap();
+        assert_eq!(
+            StatusResult {
+                status: "Active".to_string(),
+                latest_slot: client_state.latest_slot,
+                latest_timestamp: consensus_state.timestamp * 1_000_000_000,
+                frozen_at: None,
+                expired_at: None,
+                earliest_height: Some(Height {
+                    revision_number: client_state.revision_number,
+                    revision_height: client_state.latest_slot,
+                }),
+            },
+            status_response
+        );
+    }
+
+    #[test]
+    fn test_status_expired() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let fixture: StepsFixture =
+            fixtures::load("TestICS20TransferNativeCosmosCoinsToEthereumAndBack_Groth16");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+
+        let mut client_state = initial_state.client_state;
+        client_state.trusting_period = Some(86_400);
+        let consensus_state = initial_state.consensus_state;
+        let expires_at = consensus_state.timestamp + 86_400;
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let query_status = |deps: Deps<EthereumCustomQuery>, now: u64| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(now);
+            let res = query(deps, env, QueryMsg::Status(StatusMsg {})).unwrap();
+            from_json::<StatusResult>(&res).unwrap()
+        };
+
+        // The client is active until its trusting period elapsed
+        let status_response = query_status(deps.as_ref(), expires_at - 1);
         assert_eq!("Active", status_response.status);
+        assert_eq!(None, status_response.expired_at);
+
+        let status_response = query_status(deps.as_ref(), expires_at);
+        assert_eq!("Expired", status_response.status);
+        assert_eq!(Some(expires_at * 1_000_000_000), status_response.expired_at);
+
+        // A frozen client is reported as frozen, along with when it expired
+        crate::sudo::misbehaviour(
+            deps.as_mut(),
+            crate::msg::UpdateStateOnMisbehaviourMsg {
+                client_message: Binary::default(),
+            },
+        )
+        .unwrap();
+        let status_response = query_status(deps.as_ref(), expires_at);
+        assert_eq!("Frozen", status_response.status);
+        assert_eq!(Some(expires_at * 1_000_000_000), status_response.expired_at);
+    }
+
+    fn query_sync_committee_roots(
+        deps: Deps<EthereumCustomQuery>,
+        slot: u64,
+    ) -> Result<SyncCommitteeRootsResult, ContractError> {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::SyncCommitteeRoots(SyncCommitteeRootsMsg {
+                height: Height {
+                    revision_number: get_eth_client_state(deps.storage)?.revision_number,
+                    revision_height: slot,
+                },
+            }),
+        )?;
+        Ok(from_json(res).unwrap())
+    }
+
+    #[test]
+    fn test_sync_committee_roots() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(8);
+        let trusted_slot = generator.period_start_slot(1) + 3;
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // The roots of the initial sync committees are unknown
+        assert_eq!(
+            query_sync_committee_roots(deps.as_ref(), trusted_slot).unwrap(),
+            SyncCommitteeRootsResult {
+                current_sync_committee_root: None,
+                next_sync_committee_root: None,
+            }
+        );
+
+        for (period, header) in (2..).zip(generator.period_headers(trusted_slot, 4)) {
+            let slot = header.consensus_update.finalized_header.beacon.slot;
+            crate::sudo::update_state(
+                deps.as_mut(),
+                UpdateStateMsg {
+                    client_message: Binary::from(serde_json::to_vec(&header).unwrap()),
+                },
+            )
+            .unwrap();
+
+            assert_eq!(
+                query_sync_committee_roots(deps.as_ref(), slot).unwrap(),
+                SyncCommitteeRootsResult {
+                    current_sync_committee_root: Some(
+                        generator
+                            .sync_committee(period)
+                            .tree_hash_root()
+                            .to_string()
+                    ),
+                    next_sync_committee_root: Some(
+                        generator
+                            .sync_committee(period + 1)
+                            .tree_hash_root()
+                            .to_string()
+                    ),
+                }
+            );
+        }
+
+        // There are no roots without a consensus state
+        let err = query_sync_committee_roots(deps.as_ref(), trusted_slot + 1).unwrap_err();
+        assert!(matches!(err, ContractError::ConsensusStateNotFound));
+    }
+
+    #[test]
+    fn test_sync_committee_participation() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(8);
+        let trusted_slot = generator.period_start_slot(1) + 3;
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let query_participation = |deps: Deps<EthereumCustomQuery>| {
+            let res = query(
+                deps,
+                mock_env(),
+                QueryMsg::SyncCommitteeParticipation(SyncCommitteeParticipationMsg {}),
+            )
+            .unwrap();
+            from_json::<SyncCommitteeParticipationResult>(res).unwrap()
+        };
+        assert!(query_participation(deps.as_ref()).updates.is_empty());
+
+        let headers = generator.period_headers(trusted_slot, 4);
+        for header in &headers {
+            crate::sudo::update_state(
+                deps.as_mut(),
+                UpdateStateMsg {
+                    client_message: Binary::from(serde_json::to_vec(header).unwrap()),
+                },
+            )
+            .unwrap();
+        }
+
+        let participation = query_participation(deps.as_ref());
+        assert_eq!(
+            participation.min_sync_committee_participants,
+            client_state.min_sync_committee_participants
+        );
+        assert_eq!(participation.updates.len(), headers.len());
+        for (update, header) in participation.updates.iter().zip(&headers) {
+            let sync_aggregate = &header.consensus_update.sync_aggregate;
+            assert_eq!(
+                update.slot,
+                header.consensus_update.finalized_header.beacon.slot
+            );
+            assert_eq!(
+                update.signature_slot,
+                header.consensus_update.signature_slot
+            );
+            assert_eq!(
+                update.participants,
+                sync_aggregate.num_sync_committe_participants()
+            );
+            assert_eq!(update.committee_size, sync_aggregate.sync_committee_size());
+        }
+
+        // Only the latest updates are kept
+        for slot in 0..SYNC_COMMITTEE_PARTICIPATION_HISTORY {
+            record_sync_committee_participation(
+                deps.as_mut().storage,
+                &SyncCommitteeParticipation {
+                    slot: 1_000 + slot,
+                    signature_slot: 1_001 + slot,
+                    participants: 1,
+                    committee_size: 8,
+                },
+            )
+            .unwrap();
+        }
+        let updates = query_participation(deps.as_ref()).updates;
+        assert_eq!(
+            u64::try_from(updates.len()).unwrap(),
+            SYNC_COMMITTEE_PARTICIPATION_HISTORY
+        );
+        assert_eq!(updates.first().unwrap().slot, 1_000);
+        assert_eq!(
+            updates.last().unwrap().slot,
+            1_000 + SYNC_COMMITTEE_PARTICIPATION_HISTORY - 1
+        );
+    }
+
+    #[test]
+    fn test_verify_membership_at() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(1);
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let path = b"stateless/commitments".to_vec();
+        let case = storage_proof_corpus(evm_ics26_commitment_path(
+            &path,
+            client_state.ibc_commitment_slot,
+        ))
+        .into_iter()
+        .find(|case| case.is_member)
+        .unwrap();
+
+        // A consensus state the client never stored
+        let mut provided_consensus_state = consensus_state;
+        provided_consensus_state.slot = trusted_slot + 100;
+        provided_consensus_state.storage_root = case.storage_root;
+        let verify_membership_at = |value: U256| {
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::VerifyMembershipAt(VerifyMembershipAtMsg {
+                    consensus_state: Binary::from(
+                        serde_json::to_vec(&provided_consensus_state).unwrap(),
+                    ),
+                    proof: serde_json::to_vec(&case.storage_proof).unwrap().into(),
+                    merkle_path: MerklePath {
+                        key_path: vec![path.clone().into()],
+                    },
+                    value: value.to_be_bytes_vec().into(),
+                }),
+            )
+        };
+
+        verify_membership_at(case.storage_proof.value).unwrap();
+        assert!(matches!(
+            verify_membership_at(case.storage_proof.value + U256::from(1)),
+            Err(ContractError::VerifyMembershipFailed(_))
+        ));
+        // The provided consensus state is not stored
+        assert!(matches!(
+            get_eth_consensus_state(
+                deps.as_ref().storage,
+                client_state.revision_number,
+                provided_consensus_state.slot
+            ),
+            Err(ContractError::ConsensusStateNotFound)
+        ));
+
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VerifyMembershipAt(VerifyMembershipAtMsg {
+                consensus_state: Binary::from(b"not a consensus state".to_vec()),
+                proof: serde_json::to_vec(&case.storage_proof).unwrap().into(),
+                merkle_path: MerklePath {
+                    key_path: vec![path.into()],
+                },
+                value: case.storage_proof.value.to_be_bytes_vec().into(),
+            }),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::DeserializeConsensusStateFailed(_)
+        ));
     }
 }
@@ -1,6 +1,7 @@
 //! State management for the Ethereum light client
 
-use cosmwasm_std::Storage;
+use alloy_primitives::B256;
+use cosmwasm_std::{Addr, Order, Record, Storage};
 use ethereum_light_client::client_state::ClientState as EthClientState;
 use ethereum_light_client::consensus_state::ConsensusState as EthConsensusState;
 use ibc_proto::{
@@ -10,6 +11,7 @@ use ibc_proto::{
     },
 };
 use prost::Message;
+use serde::{Deserialize, Serialize};
 
 use crate::ContractError;
 
@@ -17,11 +19,166 @@ use crate::ContractError;
 pub const HOST_CLIENT_STATE_KEY: &str = "clientState";
 /// The store key used by `ibc-go` to store the consensus states
 pub const HOST_CONSENSUS_STATES_KEY: &str = "consensusStates";
+/// The store key used to record the latest slot of the client when it was frozen
+pub const FROZEN_AT_KEY: &str = "frozenAt";
+/// The store key prefix used to record the processed misbehaviour evidence
+pub const MISBEHAVIOUR_EVIDENCE_KEY_PREFIX: &str = "misbehaviourEvidence";
+/// The store key prefix used to record the sync committee roots of the consensus states
+pub const SYNC_COMMITTEE_ROOTS_KEY_PREFIX: &str = "syncCommitteeRoots";
+/// The store key prefix used to record the sync committee participation of the applied updates
+pub const SYNC_COMMITTEE_PARTICIPATION_KEY_PREFIX: &str = "syncCommitteeParticipation";
+/// The store key used to count the applied updates whose participation was recorded
+pub const SYNC_COMMITTEE_PARTICIPATION_COUNT_KEY: &str = "syncCommitteeParticipationCount";
+/// The number of latest applied updates whose sync committee participation is kept
+pub const SYNC_COMMITTEE_PARTICIPATION_HISTORY: u64 = 256;
+/// The store key prefix used to record the relayers the updates of the consensus states are
+/// attributed to
+pub const UPDATE_RELAYER_KEY_PREFIX: &str = "updateRelayer";
+/// The store key prefix of the ordered index of the heights of the stored consensus states
+pub const CONSENSUS_HEIGHTS_KEY_PREFIX: &str = "consensusHeights/";
+/// The maximum number of consensus states pruned by a single update
+pub const MAX_PRUNED_CONSENSUS_STATES: usize = 32;
+/// The key prefix of the store of the subject client in the store passed by `ibc-go` when the
+/// client store is migrated
+pub const SUBJECT_CLIENT_STORE_PREFIX: &str = "subject/";
+/// The key prefix of the store of the substitute client in the store passed by `ibc-go` when the
+/// client store is migrated
+pub const SUBSTITUTE_CLIENT_STORE_PREFIX: &str = "substitute/";
 
-/// The key used to store the consensus states by height
+/// The store of a client within the store passed by `ibc-go` when the client store is migrated,
+/// which holds the stores of the subject and the substitute clients under their key prefixes
+pub struct ClientStore<'a> {
+    storage: &'a mut dyn Storage,
+    prefix: &'static str,
+}
+
+impl<'a> ClientStore<'a> {
+    /// The store of the subject client
+    #[must_use]
+    pub fn subject(storage: &'a mut dyn Storage) -> Self {
+        Self {
+            storage,
+            prefix: SUBJECT_CLIENT_STORE_PREFIX,
+        }
+    }
+
+    /// The store of the substitute client, which `ibc-go` only allows to read
+    #[must_use]
+    pub fn substitute(storage: &'a mut dyn Storage) -> Self {
+        Self {
+            storage,
+            prefix: SUBSTITUTE_CLIENT_STORE_PREFIX,
+        }
+    }
+
+    fn key(&self, key: &[u8]) -> Vec<u8> {
+        [self.prefix.as_bytes(), key].concat()
+    }
+}
+
+impl Storage for ClientStore<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.storage.get(&self.key(key))
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        let start = self.key(start.unwrap_or_default());
+        let end = end.map_or_else(|| prefix_end(self.prefix.as_bytes()), |end| self.key(end));
+        let prefix_len = self.prefix.len();
+        Box::new(
+            self.storage
+                .range(Some(&start), Some(&end), order)
+                .map(move |(key, value)| (key[prefix_len..].to_vec(), value)),
+        )
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.storage.set(&self.key(key), value);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.storage.remove(&self.key(key));
+    }
+}
+
+/// The hash tree roots of the sync committees of a consensus state, when known
+///
+/// The consensus states only hold the aggregate public keys of the sync committees, so the roots
+/// are learned from the full sync committees carried by the headers.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncCommitteeRoots {
+    /// The hash tree root of the current sync committee
+    pub current: Option<B256>,
+    /// The hash tree root of the next sync committee
+    pub next: Option<B256>,
+}
+
+/// The sync committee participation of an applied update
+///
+/// The records are kept in a ring of [`SYNC_COMMITTEE_PARTICIPATION_HISTORY`] entries, so that
+/// recording the participation of an update costs a single write.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyncCommitteeParticipation {
+    /// The finalized slot of the update
+    pub slot: u64,
+    /// The slot the sync aggregate of the update was signed at
+    pub signature_slot: u64,
+    /// The number of sync committee members that signed the update
+    pub participants: u64,
+    /// The size of the sync committee
+    pub committee_size: u64,
+}
+
+/// The key used to store the consensus states by height, as read by ibc-go
 #[must_use]
-pub fn consensus_db_key(slot: u64) -> String {
-    format!("{}/{}-{}", HOST_CONSENSUS_STATES_KEY, 0, slot)
+pub fn consensus_db_key(revision_number: u64, slot: u64) -> String {
+    format!("{HOST_CONSENSUS_STATES_KEY}/{revision_number}-{slot}")
+}
+
+/// The key used to index the consensus states by height
+///
+/// The revision number and the slot are big endian encoded, so that the keys are ordered like the
+/// heights, the heights of the previous revisions first
+#[must_use]
+pub fn consensus_heights_db_key(revision_number: u64, slot: u64) -> Vec<u8> {
+    [
+        CONSENSUS_HEIGHTS_KEY_PREFIX.as_bytes(),
+        &revision_number.to_be_bytes(),
+        &slot.to_be_bytes(),
+    ]
+    .concat()
+}
+
+/// The key used to record processed misbehaviour evidence by its hash
+#[must_use]
+pub fn misbehaviour_evidence_db_key(evidence_hash: B256) -> String {
+    format!("{MISBEHAVIOUR_EVIDENCE_KEY_PREFIX}/{evidence_hash}")
+}
+
+/// The key used to record the sync committee roots of the consensus states by height
+#[must_use]
+pub fn sync_committee_roots_db_key(revision_number: u64, slot: u64) -> String {
+    format!("{SYNC_COMMITTEE_ROOTS_KEY_PREFIX}/{revision_number}-{slot}")
+}
+
+/// The key used to record the relayer of the update of a consensus state by height
+#[must_use]
+pub fn update_relayer_db_key(revision_number: u64, slot: u64) -> String {
+    format!("{UPDATE_RELAYER_KEY_PREFIX}/{revision_number}-{slot}")
+}
+
+/// The key used to record the sync committee participation of an applied update by its index
+#[must_use]
+pub fn sync_committee_participation_db_key(index: u64) -> String {
+    format!(
+        "{SYNC_COMMITTEE_PARTICIPATION_KEY_PREFIX}/{}",
+        index % SYNC_COMMITTEE_PARTICIPATION_HISTORY
+    )
 }
 
 /// Get the Wasm client state
@@ -60,10 +217,11 @@ pub fn get_eth_client_state(storage: &dyn Storage) -> Result<EthClientState, Con
 #[allow(clippy::module_name_repetitions)]
 pub fn get_eth_consensus_state(
     storage: &dyn Storage,
+    revision_number: u64,
     slot: u64,
 ) -> Result<EthConsensusState, ContractError> {
     let wasm_consensus_state_any_bz = storage
-        .get(consensus_db_key(slot).as_bytes())
+        .get(consensus_db_key(revision_number, slot).as_bytes())
         .ok_or(ContractError::ConsensusStateNotFound)?;
     let wasm_consensus_state_any = Any::decode(wasm_consensus_state_any_bz.as_slice())?;
     let wasm_consensus_state =
@@ -79,17 +237,210 @@ pub fn get_eth_consensus_state(
 pub fn store_consensus_state(
     storage: &mut dyn Storage,
     wasm_consensus_state: &WasmConsensusState,
+    revision_number: u64,
     slot: u64,
 ) -> Result<(), ContractError> {
     let wasm_consensus_state_any = Any::from_msg(wasm_consensus_state)?;
     storage.set(
-        consensus_db_key(slot).as_bytes(),
+        consensus_db_key(revision_number, slot).as_bytes(),
         wasm_consensus_state_any.encode_to_vec().as_slice(),
     );
+    index_consensus_height(storage, revision_number, slot);
 
     Ok(())
 }
 
+/// Get the slots of the stored consensus states of `revision_number` after `start_after`, in
+/// ascending order
+#[must_use]
+pub fn get_consensus_heights(
+    storage: &dyn Storage,
+    revision_number: u64,
+    start_after: Option<u64>,
+    limit: usize,
+) -> Vec<u64> {
+    let start = match start_after {
+        Some(slot) => match slot.checked_add(1) {
+            Some(next) => next,
+            None => return vec![],
+        },
+        None => 0,
+    };
+    consensus_heights_from(storage, &consensus_heights_db_key(revision_number, start))
+        .take_while(|(revision, _)| *revision == revision_number)
+        .map(|(_, slot)| slot)
+        .take(limit)
+        .collect()
+}
+
+/// Get the slot of the earliest stored consensus state of `revision_number`, if any
+#[must_use]
+pub fn get_earliest_consensus_height(storage: &dyn Storage, revision_number: u64) -> Option<u64> {
+    get_consensus_heights(storage, revision_number, None, 1)
+        .first()
+        .copied()
+}
+
+/// Remove the consensus states of the previous revisions and the ones of `revision_number` below
+/// `before_slot`, along with the records kept for them, oldest first and at most `limit` of them
+/// # Returns
+/// The revision numbers and slots of the removed consensus states
+pub fn prune_consensus_states(
+    storage: &mut dyn Storage,
+    revision_number: u64,
+    before_slot: u64,
+    limit: usize,
+) -> Vec<(u64, u64)> {
+    let pruned: Vec<(u64, u64)> =
+        consensus_heights_from(storage, CONSENSUS_HEIGHTS_KEY_PREFIX.as_bytes())
+            .take_while(|height| *height < (revision_number, before_slot))
+            .take(limit)
+            .collect();
+
+    for (revision, slot) in &pruned {
+        storage.remove(consensus_db_key(*revision, *slot).as_bytes());
+        storage.remove(sync_committee_roots_db_key(*revision, *slot).as_bytes());
+        storage.remove(update_relayer_db_key(*revision, *slot).as_bytes());
+        storage.remove(&consensus_heights_db_key(*revision, *slot));
+    }
+    pruned
+}
+
+/// Index the consensus states stored before the index was introduced, by scanning the consensus
+/// states once
+/// # Returns
+/// The number of indexed consensus states
+pub fn index_consensus_heights(storage: &mut dyn Storage, revision_number: u64) -> usize {
+    let prefix = format!("{HOST_CONSENSUS_STATES_KEY}/{revision_number}-");
+    let end = prefix_end(prefix.as_bytes());
+    let slots: Vec<u64> = storage
+        .range_keys(Some(prefix.as_bytes()), Some(&end), Order::Ascending)
+        .filter_map(|key| std::str::from_utf8(&key[prefix.len()..]).ok()?.parse().ok())
+        .collect();
+
+    for slot in &slots {
+        index_consensus_height(storage, revision_number, *slot);
+    }
+    slots.len()
+}
+
+/// Move the records the previous versions kept by slot only under the keys of `revision_number`,
+/// and drop their index of the consensus heights, which is rebuilt by
+/// [`index_consensus_heights`]
+/// # Returns
+/// The number of migrated keys
+pub fn migrate_slot_keyed_records(storage: &mut dyn Storage, revision_number: u64) -> usize {
+    let legacy_index_key_len = CONSENSUS_HEIGHTS_KEY_PREFIX.len() + std::mem::size_of::<u64>();
+    let end = prefix_end(CONSENSUS_HEIGHTS_KEY_PREFIX.as_bytes());
+    let legacy_index_keys: Vec<Vec<u8>> = storage
+        .range_keys(
+            Some(CONSENSUS_HEIGHTS_KEY_PREFIX.as_bytes()),
+            Some(&end),
+            Order::Ascending,
+        )
+        .filter(|key| key.len() == legacy_index_key_len)
+        .collect();
+    for key in &legacy_index_keys {
+        storage.remove(key);
+    }
+
+    let mut migrated = legacy_index_keys.len();
+    for (key_prefix, db_key) in [
+        (
+            SYNC_COMMITTEE_ROOTS_KEY_PREFIX,
+            sync_committee_roots_db_key as fn(u64, u64) -> String,
+        ),
+        (UPDATE_RELAYER_KEY_PREFIX, update_relayer_db_key),
+    ] {
+        let prefix = format!("{key_prefix}/");
+        let end = prefix_end(prefix.as_bytes());
+        // The keys written by the previous versions hold the slot only
+        let records: Vec<(u64, Vec<u8>)> = storage
+            .range(Some(prefix.as_bytes()), Some(&end), Order::Ascending)
+            .filter_map(|(key, value)| {
+                let slot = std::str::from_utf8(&key[prefix.len()..])
+                    .ok()?
+                    .parse()
+                    .ok()?;
+                Some((slot, value))
+            })
+            .collect();
+        for (slot, value) in &records {
+            storage.remove(format!("{prefix}{slot}").as_bytes());
+            storage.set(db_key(revision_number, *slot).as_bytes(), value);
+        }
+        migrated += records.len();
+    }
+    migrated
+}
+
+/// Remove the consensus states of a previous client, along with the records kept for them, its
+/// processed misbehaviour evidence, its sync committee participation and its frozen marker, so
+/// that a re-initialized client does not report them
+/// # Returns
+/// The number of removed keys
+pub fn remove_client_records(storage: &mut dyn Storage) -> usize {
+    let prefixes = [
+        format!("{HOST_CONSENSUS_STATES_KEY}/"),
+        CONSENSUS_HEIGHTS_KEY_PREFIX.to_string(),
+        format!("{SYNC_COMMITTEE_ROOTS_KEY_PREFIX}/"),
+        format!("{UPDATE_RELAYER_KEY_PREFIX}/"),
+        format!("{MISBEHAVIOUR_EVIDENCE_KEY_PREFIX}/"),
+        format!("{SYNC_COMMITTEE_PARTICIPATION_KEY_PREFIX}/"),
+    ];
+    let keys: Vec<Vec<u8>> = prefixes
+        .iter()
+        .flat_map(|prefix| {
+            let end = prefix_end(prefix.as_bytes());
+            storage
+                .range_keys(Some(prefix.as_bytes()), Some(&end), Order::Ascending)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    for key in &keys {
+        storage.remove(key);
+    }
+    storage.remove(SYNC_COMMITTEE_PARTICIPATION_COUNT_KEY.as_bytes());
+    remove_frozen_at(storage);
+    keys.len()
+}
+
+/// Index the consensus height, the value holds the big endian encoded revision number and slot
+fn index_consensus_height(storage: &mut dyn Storage, revision_number: u64, slot: u64) {
+    let key = consensus_heights_db_key(revision_number, slot);
+    storage.set(&key, &key[CONSENSUS_HEIGHTS_KEY_PREFIX.len()..]);
+}
+
+/// Iterate over the revision numbers and slots of the indexed consensus heights from the `start`
+/// key, in ascending order
+fn consensus_heights_from<'a>(
+    storage: &'a dyn Storage,
+    start: &[u8],
+) -> impl Iterator<Item = (u64, u64)> + 'a {
+    let end = prefix_end(CONSENSUS_HEIGHTS_KEY_PREFIX.as_bytes());
+    storage
+        .range_values(Some(start), Some(&end), Order::Ascending)
+        .filter_map(|bz| {
+            let height: [u8; 16] = bz.try_into().ok()?;
+            let (revision_number, slot) = height.split_at(8);
+            Some((
+                u64::from_be_bytes(revision_number.try_into().ok()?),
+                u64::from_be_bytes(slot.try_into().ok()?),
+            ))
+        })
+}
+
+/// Returns the smallest key greater than all the keys starting with `prefix`
+fn prefix_end(prefix: &[u8]) -> Vec<u8> {
+    let mut end = prefix.to_vec();
+    // The prefixes end with an ASCII separator, which is never 0xff
+    if let Some(last) = end.last_mut() {
+        *last += 1;
+    }
+    end
+}
+
 /// Store the client state
 /// # Errors
 /// Returns an error if the client state cannot be serialized into an Any
@@ -106,3 +457,134 @@ pub fn store_client_state(
 
     Ok(())
 }
+
+/// Returns whether the misbehaviour evidence with the given hash has already been processed
+#[must_use]
+pub fn has_misbehaviour_evidence(storage: &dyn Storage, evidence_hash: B256) -> bool {
+    storage
+        .get(misbehaviour_evidence_db_key(evidence_hash).as_bytes())
+        .is_some()
+}
+
+/// Record the misbehaviour evidence with the given hash as processed
+pub fn store_misbehaviour_evidence(storage: &mut dyn Storage, evidence_hash: B256) {
+    storage.set(misbehaviour_evidence_db_key(evidence_hash).as_bytes(), &[1]);
+}
+
+/// Get the latest slot of the client when it was frozen, if it was frozen
+#[must_use]
+pub fn get_frozen_at(storage: &dyn Storage) -> Option<u64> {
+    storage
+        .get(FROZEN_AT_KEY.as_bytes())
+        .and_then(|bz| bz.try_into().ok())
+        .map(u64::from_be_bytes)
+}
+
+/// Record the latest slot of the client when it was frozen
+pub fn store_frozen_at(storage: &mut dyn Storage, slot: u64) {
+    storage.set(FROZEN_AT_KEY.as_bytes(), &slot.to_be_bytes());
+}
+
+/// Remove the record of the latest slot of the client when it was frozen, once it is recovered
+pub fn remove_frozen_at(storage: &mut dyn Storage) {
+    storage.remove(FROZEN_AT_KEY.as_bytes());
+}
+
+/// Get the sync committee roots of the consensus state at a given height, which are unknown if
+/// none were recorded
+/// # Errors
+/// Returns an error if the recorded roots cannot be deserialized
+pub fn get_sync_committee_roots(
+    storage: &dyn Storage,
+    revision_number: u64,
+    slot: u64,
+) -> Result<SyncCommitteeRoots, ContractError> {
+    storage
+        .get(sync_committee_roots_db_key(revision_number, slot).as_bytes())
+        .map_or_else(
+            || Ok(SyncCommitteeRoots::default()),
+            |bz| Ok(serde_json::from_slice(&bz)?),
+        )
+}
+
+/// Record the sync committee roots of the consensus state at a given height
+/// # Errors
+/// Returns an error if the roots cannot be serialized
+pub fn store_sync_committee_roots(
+    storage: &mut dyn Storage,
+    revision_number: u64,
+    slot: u64,
+    roots: &SyncCommitteeRoots,
+) -> Result<(), ContractError> {
+    storage.set(
+        sync_committee_roots_db_key(revision_number, slot).as_bytes(),
+        &serde_json::to_vec(roots)?,
+    );
+    Ok(())
+}
+
+/// Get the relayer the update of the consensus state at a given height is attributed to, if any
+#[must_use]
+pub fn get_update_relayer(
+    storage: &dyn Storage,
+    revision_number: u64,
+    slot: u64,
+) -> Option<String> {
+    storage
+        .get(update_relayer_db_key(revision_number, slot).as_bytes())
+        .and_then(|bz| String::from_utf8(bz).ok())
+}
+
+/// Record the relayer the update of the consensus state at a given height is attributed to
+pub fn store_update_relayer(
+    storage: &mut dyn Storage,
+    revision_number: u64,
+    slot: u64,
+    relayer: &Addr,
+) {
+    storage.set(
+        update_relayer_db_key(revision_number, slot).as_bytes(),
+        relayer.as_str().as_bytes(),
+    );
+}
+
+/// Record the sync committee participation of an applied update, replacing the oldest record
+/// once the history is full
+/// # Errors
+/// Returns an error if the participation cannot be serialized
+pub fn record_sync_committee_participation(
+    storage: &mut dyn Storage,
+    participation: &SyncCommitteeParticipation,
+) -> Result<(), ContractError> {
+    let count = get_sync_committee_participation_count(storage);
+    storage.set(
+        sync_committee_participation_db_key(count).as_bytes(),
+        &serde_json::to_vec(participation)?,
+    );
+    storage.set(
+        SYNC_COMMITTEE_PARTICIPATION_COUNT_KEY.as_bytes(),
+        &(count + 1).to_be_bytes(),
+    );
+    Ok(())
+}
+
+/// Get the sync committee participation of the latest applied updates, oldest first
+/// # Errors
+/// Returns an error if a record cannot be deserialized
+pub fn get_sync_committee_participation(
+    storage: &dyn Storage,
+) -> Result<Vec<SyncCommitteeParticipation>, ContractError> {
+    let count = get_sync_committee_participation_count(storage);
+    (count.saturating_sub(SYNC_COMMITTEE_PARTICIPATION_HISTORY)..count)
+        .filter_map(|index| storage.get(sync_committee_participation_db_key(index).as_bytes()))
+        .map(|bz| Ok(serde_json::from_slice(&bz)?))
+        .collect()
+}
+
+/// Get the number of applied updates whose participation was recorded
+fn get_sync_committee_participation_count(storage: &dyn Storage) -> u64 {
+    storage
+        .get(SYNC_COMMITTEE_PARTICIPATION_COUNT_KEY.as_bytes())
+        .and_then(|bz| bz.try_into().ok())
+        .map_or(0, u64::from_be_bytes)
+}
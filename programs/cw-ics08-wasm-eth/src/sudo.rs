@@ -1,25 +1,101 @@
 //! This module contains the sudo message handlers
 
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut};
-use ethereum_light_client::update::update_consensus_state;
+use alloy_primitives::{keccak256, B256};
+use cosmwasm_std::{ensure, to_json_binary, Addr, Binary, Deps, DepsMut, Event, Response, Storage};
+use ethereum_light_client::{
+    client_state::{ClientState as EthClientState, ConsensusStateWrites},
+    consensus_state::ConsensusState as EthConsensusState,
+    header::{ActiveSyncCommittee, Header},
+    recovery::check_substitute,
+    update::update_consensus_state,
+    upgrade::verify_upgrade,
+};
+use ethereum_types::consensus::bls::BlsPublicKey;
 use ibc_proto::ibc::{
     core::client::v1::Height as IbcProtoHeight,
     lightclients::wasm::v1::ConsensusState as WasmConsensusState,
 };
+use tree_hash::TreeHash;
 
 use crate::{
+    client_message::ClientMessage,
     custom_query::EthereumCustomQuery,
+    debug::{debug_print, debug_print_result},
     msg::{
         Height, UpdateStateMsg, UpdateStateOnMisbehaviourMsg, UpdateStateResult,
-        VerifyMembershipMsg, VerifyNonMembershipMsg,
+        VerifyMembershipMsg, VerifyNonMembershipMsg, VerifyUpgradeAndUpdateStateMsg,
     },
     state::{
-        get_eth_client_state, get_eth_consensus_state, get_wasm_client_state, store_client_state,
-        store_consensus_state,
+        get_eth_client_state, get_eth_consensus_state, get_sync_committee_roots,
+        get_wasm_client_state, has_misbehaviour_evidence, prune_consensus_states,
+        record_sync_committee_participation, remove_frozen_at, store_client_state,
+        store_consensus_state, store_frozen_at, store_misbehaviour_evidence,
+        store_sync_committee_roots, store_update_relayer, ClientStore, SyncCommitteeParticipation,
+        SyncCommitteeRoots, MAX_PRUNED_CONSENSUS_STATES,
     },
     ContractError,
 };
 
+/// The event emitted when an update rotates the sync committee of the light client
+pub const EVENT_TYPE_SYNC_COMMITTEE_ROTATION: &str = "sync_committee_rotation";
+/// The attribute key for the sync committee period the rotated committee is active in
+pub const ATTRIBUTE_KEY_SYNC_COMMITTEE_PERIOD: &str = "sync_committee_period";
+/// The attribute key for the hash tree root of the rotated sync committee
+pub const ATTRIBUTE_KEY_SYNC_COMMITTEE_ROOT: &str = "sync_committee_root";
+/// The event emitted when an update is a duplicate of an already applied update
+pub const EVENT_TYPE_DUPLICATE_UPDATE: &str = "duplicate_update";
+/// The attribute key for the slot of the consensus state of an update, which already existed for
+/// a duplicate update
+pub const ATTRIBUTE_KEY_CONSENSUS_SLOT: &str = "consensus_slot";
+/// The event emitted when an applied update is attributed to a relayer
+pub const EVENT_TYPE_UPDATE_RELAYER: &str = "update_relayer";
+/// The attribute key for the relayer an update is attributed to
+pub const ATTRIBUTE_KEY_RELAYER: &str = "relayer";
+/// The event emitted when an update prunes the consensus states older than the retention of the
+/// client
+pub const EVENT_TYPE_CONSENSUS_STATES_PRUNED: &str = "consensus_states_pruned";
+/// The attribute key for the comma separated heights of the pruned consensus states, formatted as
+/// `{revision_number}-{slot}`
+pub const ATTRIBUTE_KEY_PRUNED_HEIGHTS: &str = "pruned_heights";
+
+/// Ensures that the revision number of `height` matches the one of the client state
+/// # Errors
+/// Returns an error if the revision numbers differ
+pub fn ensure_revision_number(
+    eth_client_state: &EthClientState,
+    height: &Height,
+) -> Result<(), ContractError> {
+    ensure!(
+        height.revision_number == eth_client_state.revision_number,
+        ContractError::RevisionNumberMismatch {
+            expected: eth_client_state.revision_number,
+            found: height.revision_number,
+        }
+    );
+    Ok(())
+}
+
+/// Returns whether the consensus state that `header` would produce is already stored for its
+/// finalized slot, as happens when several relayers submit the same update concurrently
+#[must_use]
+pub fn is_duplicate_update(
+    storage: &dyn Storage,
+    eth_client_state: &EthClientState,
+    header: &Header,
+) -> bool {
+    let finalized_header = &header.consensus_update.finalized_header;
+    get_eth_consensus_state(
+        storage,
+        eth_client_state.revision_number,
+        finalized_header.beacon.slot,
+    )
+    .is_ok_and(|consensus_state| {
+        consensus_state.state_root == finalized_header.execution.state_root
+            && consensus_state.storage_root == header.account_update.account_proof.storage_root
+            && consensus_state.timestamp == finalized_header.execution.timestamp
+    })
+}
+
 /// Verify the membership of a value at a given height
 /// # Errors
 /// Returns an error if the membership proof verification fails
@@ -30,10 +106,22 @@ pub fn verify_membership(
     verify_membership_msg: VerifyMembershipMsg,
 ) -> Result<Binary, ContractError> {
     let eth_client_state = get_eth_client_state(deps.storage)?;
-    let eth_consensus_state =
-        get_eth_consensus_state(deps.storage, verify_membership_msg.height.revision_height)?;
+    ensure_revision_number(&eth_client_state, &verify_membership_msg.height)?;
+    let eth_consensus_state = get_eth_consensus_state(
+        deps.storage,
+        eth_client_state.revision_number,
+        verify_membership_msg.height.revision_height,
+    )?;
 
-    ethereum_light_client::membership::verify_membership(
+    debug_print(deps.api, || {
+        proof_walk_summary(
+            "membership",
+            &verify_membership_msg.height,
+            &verify_membership_msg.proof,
+            &verify_membership_msg.merkle_path.key_path,
+        )
+    });
+    let result = ethereum_light_client::membership::verify_membership(
         eth_consensus_state,
         eth_client_state,
         verify_membership_msg.proof.into(),
@@ -44,8 +132,9 @@ pub fn verify_membership(
             .map(Into::into)
             .collect(),
         verify_membership_msg.value.into(),
-    )
-    .map_err(ContractError::VerifyMembershipFailed)?;
+    );
+    debug_print_result(deps.api, "membership proof walk", &result);
+    result.map_err(ContractError::VerifyMembershipFailed)?;
 
     Ok(Binary::default())
 }
@@ -60,12 +149,22 @@ pub fn verify_non_membership(
     verify_non_membership_msg: VerifyNonMembershipMsg,
 ) -> Result<Binary, ContractError> {
     let eth_client_state = get_eth_client_state(deps.storage)?;
+    ensure_revision_number(&eth_client_state, &verify_non_membership_msg.height)?;
     let eth_consensus_state = get_eth_consensus_state(
         deps.storage,
+        eth_client_state.revision_number,
         verify_non_membership_msg.height.revision_height,
     )?;
 
-    ethereum_light_client::membership::verify_non_membership(
+    debug_print(deps.api, || {
+        proof_walk_summary(
+            "non-membership",
+            &verify_non_membership_msg.height,
+            &verify_non_membership_msg.proof,
+            &verify_non_membership_msg.merkle_path.key_path,
+        )
+    });
+    let result = ethereum_light_client::membership::verify_non_membership(
         eth_consensus_state,
         eth_client_state,
         verify_non_membership_msg.proof.into(),
@@ -75,61 +174,336 @@ pub fn verify_non_membership(
             .into_iter()
             .map(Into::into)
             .collect(),
-    )
-    .map_err(ContractError::VerifyNonMembershipFailed)?;
+    );
+    debug_print_result(deps.api, "non-membership proof walk", &result);
+    result.map_err(ContractError::VerifyNonMembershipFailed)?;
 
     Ok(Binary::default())
 }
 
+/// Returns the summary of a storage proof traced before it is walked
+#[must_use]
+pub fn proof_walk_summary(
+    kind: &str,
+    height: &Height,
+    proof: &Binary,
+    key_path: &[Binary],
+) -> String {
+    format!(
+        "walking the {kind} storage proof of {} bytes at slot {} for path [{}]",
+        proof.len(),
+        height.revision_height,
+        key_path
+            .iter()
+            .map(hex::encode)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
 /// Update the state of the light client
 /// This function is always called after the verify client message, so
 /// we can assume the client message is valid and that the consensus state can be updated
+/// The headers of a header batch are applied in order, storing the consensus state of each, or
+/// only the ones selected by the [`ConsensusStateWrites`] policy of the client
+/// If an update rotates the sync committee, a [`EVENT_TYPE_SYNC_COMMITTEE_ROTATION`] event
+/// with the root of the applied committee is emitted
+/// If an identical consensus state already exists for the slot, the existing height is returned
+/// with a [`EVENT_TYPE_DUPLICATE_UPDATE`] event instead of an error
+/// If the envelope of the client message attributes it to a relayer, the relayer is recorded
+/// along with every stored consensus state, and a [`EVENT_TYPE_UPDATE_RELAYER`] event is emitted
+/// for every applied update. The duplicate updates stay attributed to the first relayer
 /// # Errors
-/// Returns an error if deserialization failes or if the light client update logic fails
+/// Returns an error if deserialization failes, if the relayer is not a valid address or if the
+/// light client update logic fails
 /// # Returns
-/// The updated slot (called height in regular IBC terms)
+/// A response with the updated slots (called heights in regular IBC terms) as data
 #[allow(clippy::needless_pass_by_value)]
 pub fn update_state(
     deps: DepsMut<EthereumCustomQuery>,
     update_state_msg: UpdateStateMsg,
-) -> Result<Binary, ContractError> {
-    let header_bz: Vec<u8> = update_state_msg.client_message.into();
-    let header = serde_json::from_slice(&header_bz)
-        .map_err(ContractError::DeserializeClientMessageFailed)?;
-
+) -> Result<Response, ContractError> {
     let eth_client_state = get_eth_client_state(deps.storage)?;
-    let eth_consensus_state = get_eth_consensus_state(deps.storage, eth_client_state.latest_slot)?;
+    let (client_message, relayer) = ClientMessage::decode_attributed(
+        &update_state_msg.client_message,
+        eth_client_state.compressed_client_messages,
+    )?;
+    let headers = client_message.into_headers()?;
+    let relayer = relayer
+        .map(|relayer| deps.api.addr_validate(&relayer))
+        .transpose()
+        .map_err(|err| ContractError::InvalidRelayerAddress(err.to_string()))?;
+    let persisted = persisted_updates(
+        &eth_client_state,
+        &headers
+            .iter()
+            .map(|header| header.consensus_update.finalized_header.beacon.slot)
+            .collect::<Vec<_>>(),
+    );
+
+    let mut trusted = TrustedState {
+        consensus_state: get_eth_consensus_state(
+            deps.storage,
+            eth_client_state.revision_number,
+            eth_client_state.latest_slot,
+        )?,
+        sync_committee_roots: get_sync_committee_roots(
+            deps.storage,
+            eth_client_state.revision_number,
+            eth_client_state.latest_slot,
+        )?,
+        client_state: eth_client_state,
+    };
+    let mut response = Response::default();
+    let mut heights = Vec::with_capacity(headers.len());
+    for (header, persist) in headers.iter().zip(persisted) {
+        let (height, events) = apply_header(
+            deps.storage,
+            &mut trusted,
+            header,
+            persist,
+            relayer.as_ref(),
+        )?;
+        heights.push(height);
+        response = response.add_events(events);
+    }
+    if let Some(event) = prune_expired_consensus_states(deps.storage, &trusted.client_state) {
+        response = response.add_event(event);
+    }
+
+    Ok(response.set_data(to_json_binary(&UpdateStateResult { heights })?))
+}
+
+/// Prunes the consensus states older than the retention of the client, if any, at most
+/// [`MAX_PRUNED_CONSENSUS_STATES`] per update so that the cost of an update stays bounded
+/// The consensus state at the latest slot is never pruned
+fn prune_expired_consensus_states(
+    storage: &mut dyn Storage,
+    eth_client_state: &EthClientState,
+) -> Option<Event> {
+    let retention = eth_client_state.consensus_state_retention?;
+    let pruned = prune_consensus_states(
+        storage,
+        eth_client_state.revision_number,
+        eth_client_state.latest_slot.saturating_sub(retention),
+        MAX_PRUNED_CONSENSUS_STATES,
+    );
+    if pruned.is_empty() {
+        return None;
+    }
+
+    let pruned_heights = pruned
+        .iter()
+        .map(|(revision_number, slot)| format!("{revision_number}-{slot}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    Some(
+        Event::new(EVENT_TYPE_CONSENSUS_STATES_PRUNED)
+            .add_attribute(ATTRIBUTE_KEY_PRUNED_HEIGHTS, pruned_heights),
+    )
+}
+
+/// The latest state of the light client that the headers of a batch are applied on top of, which
+/// is only persisted for the updates selected by the [`ConsensusStateWrites`] policy
+struct TrustedState {
+    client_state: EthClientState,
+    consensus_state: EthConsensusState,
+    sync_committee_roots: SyncCommitteeRoots,
+}
+
+/// Returns whether the consensus state of each update, given by its finalized slot, is persisted
+/// under the [`ConsensusStateWrites`] policy of the client
+/// The newest consensus state is always persisted, since further updates are applied on top of it
+fn persisted_updates(eth_client_state: &EthClientState, slots: &[u64]) -> Vec<bool> {
+    slots
+        .iter()
+        .enumerate()
+        .map(|(i, slot)| match eth_client_state.consensus_state_writes {
+            ConsensusStateWrites::All => true,
+            ConsensusStateWrites::PeriodBoundaries => {
+                let period = |slot| eth_client_state.compute_sync_committee_period_at_slot(slot);
+                slots.get(i + 1).map(|next_slot| period(*next_slot)) != Some(period(*slot))
+            }
+        })
+        .collect()
+}
+
+/// Applies a single header on top of the trusted state, persisting the updated states and the
+/// relayer of the update if `persist` is set, and returns the updated height with the events it
+/// emits
+fn apply_header(
+    storage: &mut dyn Storage,
+    trusted: &mut TrustedState,
+    header: &Header,
+    persist: bool,
+    relayer: Option<&Addr>,
+) -> Result<(Height, Vec<Event>), ContractError> {
+    let eth_client_state = trusted.client_state.clone();
+    if is_duplicate_update(storage, &eth_client_state, header) {
+        let slot = header.consensus_update.finalized_header.beacon.slot;
+        return Ok((
+            Height {
+                revision_number: eth_client_state.revision_number,
+                revision_height: slot,
+            },
+            vec![Event::new(EVENT_TYPE_DUPLICATE_UPDATE)
+                .add_attribute(ATTRIBUTE_KEY_CONSENSUS_SLOT, slot.to_string())],
+        ));
+    }
+
+    let eth_consensus_state = trusted.consensus_state.clone();
+    let previous_sync_committee = eth_consensus_state.current_sync_committee;
+
+    // The roots of the sync committees carried by the header, along with the known roots of the
+    // trusted consensus state
+    let mut known_roots = vec![(
+        header
+            .active_sync_committee
+            .sync_committee()
+            .aggregate_pubkey,
+        header.active_sync_committee.tree_hash_root(),
+    )];
+    known_roots.extend(
+        header
+            .consensus_update
+            .next_sync_committee
+            .as_ref()
+            .map(|committee| (committee.aggregate_pubkey, committee.tree_hash_root())),
+    );
+    known_roots.extend(known_sync_committee_roots(
+        &eth_consensus_state,
+        trusted.sync_committee_roots,
+    ));
 
     let (updated_slot, updated_consensus_state, updated_client_state) =
-        update_consensus_state(eth_consensus_state, eth_client_state, header)
+        update_consensus_state(eth_consensus_state, eth_client_state.clone(), header)
             .map_err(ContractError::UpdateClientStateFailed)?;
 
-    let consensus_state_bz: Vec<u8> = serde_json::to_vec(&updated_consensus_state)
-        .map_err(ContractError::SerializeConsensusStateFailed)?;
-    let wasm_consensus_state = WasmConsensusState {
-        data: consensus_state_bz,
-    };
-    store_consensus_state(deps.storage, &wasm_consensus_state, updated_slot)?;
+    let sync_aggregate = &header.consensus_update.sync_aggregate;
+    record_sync_committee_participation(
+        storage,
+        &SyncCommitteeParticipation {
+            slot: updated_slot,
+            signature_slot: header.consensus_update.signature_slot,
+            participants: sync_aggregate.num_sync_committe_participants(),
+            committee_size: sync_aggregate.sync_committee_size(),
+        },
+    )?;
+
+    let mut events = vec![];
+    if matches!(header.active_sync_committee, ActiveSyncCommittee::Next(_))
+        && updated_consensus_state.current_sync_committee != previous_sync_committee
+    {
+        events.push(
+            Event::new(EVENT_TYPE_SYNC_COMMITTEE_ROTATION)
+                .add_attribute(
+                    ATTRIBUTE_KEY_SYNC_COMMITTEE_PERIOD,
+                    eth_client_state
+                        .compute_sync_committee_period_at_slot(updated_slot)
+                        .to_string(),
+                )
+                .add_attribute(
+                    ATTRIBUTE_KEY_SYNC_COMMITTEE_ROOT,
+                    header.active_sync_committee.tree_hash_root().to_string(),
+                ),
+        );
+    }
+
+    let updated_roots = sync_committee_roots(&updated_consensus_state, &known_roots);
+    if persist {
+        let consensus_state_bz: Vec<u8> = serde_json::to_vec(&updated_consensus_state)
+            .map_err(ContractError::SerializeConsensusStateFailed)?;
+        let wasm_consensus_state = WasmConsensusState {
+            data: consensus_state_bz,
+        };
+        store_consensus_state(
+            storage,
+            &wasm_consensus_state,
+            eth_client_state.revision_number,
+            updated_slot,
+        )?;
+        store_sync_committee_roots(
+            storage,
+            eth_client_state.revision_number,
+            updated_slot,
+            &updated_roots,
+        )?;
+        if let Some(relayer) = relayer {
+            store_update_relayer(
+                storage,
+                eth_client_state.revision_number,
+                updated_slot,
+                relayer,
+            );
+        }
+    }
+    if let Some(relayer) = relayer {
+        events.push(
+            Event::new(EVENT_TYPE_UPDATE_RELAYER)
+                .add_attribute(ATTRIBUTE_KEY_CONSENSUS_SLOT, updated_slot.to_string())
+                .add_attribute(ATTRIBUTE_KEY_RELAYER, relayer.as_str()),
+        );
+    }
 
     if let Some(client_state) = updated_client_state {
-        let client_state_bz: Vec<u8> =
-            serde_json::to_vec(&client_state).map_err(ContractError::SerializeClientStateFailed)?;
+        if persist {
+            let client_state_bz: Vec<u8> = serde_json::to_vec(&client_state)
+                .map_err(ContractError::SerializeClientStateFailed)?;
 
-        let mut wasm_client_state = get_wasm_client_state(deps.storage)?;
-        wasm_client_state.data = client_state_bz;
-        wasm_client_state.latest_height = Some(IbcProtoHeight {
-            revision_number: 0,
-            revision_height: updated_slot,
-        });
-        store_client_state(deps.storage, &wasm_client_state)?;
+            let mut wasm_client_state = get_wasm_client_state(storage)?;
+            wasm_client_state.data = client_state_bz;
+            wasm_client_state.latest_height = Some(IbcProtoHeight {
+                revision_number: client_state.revision_number,
+                revision_height: updated_slot,
+            });
+            store_client_state(storage, &wasm_client_state)?;
+        }
+
+        *trusted = TrustedState {
+            client_state,
+            consensus_state: updated_consensus_state,
+            sync_committee_roots: updated_roots,
+        };
     }
 
-    Ok(to_json_binary(&UpdateStateResult {
-        heights: vec![Height {
-            revision_number: 0,
+    Ok((
+        Height {
+            revision_number: eth_client_state.revision_number,
             revision_height: updated_slot,
-        }],
-    })?)
+        },
+        events,
+    ))
+}
+
+/// Returns the aggregate public keys of the sync committees of a consensus state whose roots are
+/// known, along with their roots
+fn known_sync_committee_roots(
+    consensus_state: &EthConsensusState,
+    roots: SyncCommitteeRoots,
+) -> impl Iterator<Item = (BlsPublicKey, B256)> {
+    roots
+        .current
+        .map(|root| (consensus_state.current_sync_committee, root))
+        .into_iter()
+        .chain(consensus_state.next_sync_committee.zip(roots.next))
+}
+
+/// Returns the roots of the sync committees of a consensus state, looked up by aggregate public
+/// key in `known_roots`
+fn sync_committee_roots(
+    consensus_state: &EthConsensusState,
+    known_roots: &[(BlsPublicKey, B256)],
+) -> SyncCommitteeRoots {
+    let root_of = |aggregate_pubkey: BlsPublicKey| {
+        known_roots
+            .iter()
+            .find_map(|(key, root)| (*key == aggregate_pubkey).then_some(*root))
+    };
+
+    SyncCommitteeRoots {
+        current: root_of(consensus_state.current_sync_committee),
+        next: consensus_state.next_sync_committee.and_then(root_of),
+    }
 }
 
 /// Update the state of the light client on misbehaviour
@@ -138,9 +512,19 @@ pub fn update_state(
 #[allow(clippy::needless_pass_by_value)]
 pub fn misbehaviour(
     deps: DepsMut<EthereumCustomQuery>,
-    _msg: UpdateStateOnMisbehaviourMsg,
+    msg: UpdateStateOnMisbehaviourMsg,
 ) -> Result<Binary, ContractError> {
+    // Reject evidence that has already been processed, and any evidence once the client is frozen,
+    // so that misbehaviour cannot be resubmitted to spam events and state writes
+    let evidence_hash = keccak256(&msg.client_message);
+    if has_misbehaviour_evidence(deps.storage, evidence_hash) {
+        return Err(ContractError::AlreadyFrozen);
+    }
+
     let mut eth_client_state = get_eth_client_state(deps.storage)?;
+    if eth_client_state.is_frozen {
+        return Err(ContractError::AlreadyFrozen);
+    }
     eth_client_state.is_frozen = true;
 
     let client_state_bz: Vec<u8> =
@@ -150,20 +534,195 @@ pub fn misbehaviour(
     wasm_client_state.data = client_state_bz;
 
     store_client_state(deps.storage, &wasm_client_state)?;
+    store_misbehaviour_evidence(deps.storage, evidence_hash);
+    store_frozen_at(deps.storage, eth_client_state.latest_slot);
+
+    Ok(Binary::default())
+}
+
+/// Verify the upgrade of the client and replace its state with the upgraded one
+/// # Errors
+/// Returns an error if the upgraded states cannot be deserialized or the upgrade verification
+/// fails
+#[allow(clippy::needless_pass_by_value)]
+pub fn verify_upgrade_and_update_state(
+    deps: DepsMut<EthereumCustomQuery>,
+    msg: VerifyUpgradeAndUpdateStateMsg,
+) -> Result<Binary, ContractError> {
+    let eth_client_state = get_eth_client_state(deps.storage)?;
+    if eth_client_state.is_frozen {
+        return Err(ContractError::AlreadyFrozen);
+    }
+    let eth_consensus_state = get_eth_consensus_state(
+        deps.storage,
+        eth_client_state.revision_number,
+        eth_client_state.latest_slot,
+    )?;
+
+    let upgraded_client_state: EthClientState =
+        serde_json::from_slice(&msg.upgrade_client_state)
+            .map_err(ContractError::DeserializeClientStateFailed)?;
+    let upgraded_consensus_state: EthConsensusState =
+        serde_json::from_slice(&msg.upgrade_consensus_state)
+            .map_err(ContractError::DeserializeConsensusStateFailed)?;
+
+    let (new_client_state, new_consensus_state) = verify_upgrade(
+        &eth_client_state,
+        &eth_consensus_state,
+        upgraded_client_state,
+        upgraded_consensus_state,
+        msg.proof_upgrade_client.into(),
+        msg.proof_upgrade_consensus_state.into(),
+    )
+    .map_err(ContractError::VerifyUpgradeFailed)?;
+    new_client_state
+        .verify_supported_fork_at_epoch(
+            new_client_state.compute_epoch_at_slot(new_client_state.latest_slot),
+        )
+        .map_err(ContractError::UnsupportedForkVersion)?;
+
+    let mut wasm_client_state = get_wasm_client_state(deps.storage)?;
+    wasm_client_state.data =
+        serde_json::to_vec(&new_client_state).map_err(ContractError::SerializeClientStateFailed)?;
+    wasm_client_state.latest_height = Some(IbcProtoHeight {
+        revision_number: new_client_state.revision_number,
+        revision_height: new_client_state.latest_slot,
+    });
+    let wasm_consensus_state = WasmConsensusState {
+        data: serde_json::to_vec(&new_consensus_state)
+            .map_err(ContractError::SerializeConsensusStateFailed)?,
+    };
+
+    store_client_state(deps.storage, &wasm_client_state)?;
+    store_consensus_state(
+        deps.storage,
+        &wasm_consensus_state,
+        new_client_state.revision_number,
+        new_consensus_state.slot,
+    )?;
+    // The roots of the upgraded sync committees are unknown until the next update
+    store_sync_committee_roots(
+        deps.storage,
+        new_client_state.revision_number,
+        new_consensus_state.slot,
+        &SyncCommitteeRoots::default(),
+    )?;
+
+    Ok(Binary::default())
+}
+
+/// Recovers the frozen or expired subject client by replacing its state with the latest state of
+/// the substitute client, once `ibc-go` checked that the substitute is active
+/// # Errors
+/// Returns an error if the substitute client cannot recover the subject client
+pub fn migrate_client_store(deps: DepsMut<EthereumCustomQuery>) -> Result<Binary, ContractError> {
+    let substitute_store = ClientStore::substitute(&mut *deps.storage);
+    let substitute_client_state = get_eth_client_state(&substitute_store)?;
+    let substitute_consensus_state = get_eth_consensus_state(
+        &substitute_store,
+        substitute_client_state.revision_number,
+        substitute_client_state.latest_slot,
+    )?;
+    let substitute_roots = get_sync_committee_roots(
+        &substitute_store,
+        substitute_client_state.revision_number,
+        substitute_client_state.latest_slot,
+    )?;
+
+    let mut subject_store = ClientStore::subject(deps.storage);
+    let subject_client_state = get_eth_client_state(&subject_store)?;
+    let recovered_client_state = check_substitute(
+        &subject_client_state,
+        &substitute_client_state,
+        &substitute_consensus_state,
+    )
+    .map_err(ContractError::CheckSubstituteFailed)?;
+    recovered_client_state
+        .verify_supported_fork_at_epoch(
+            recovered_client_state.compute_epoch_at_slot(recovered_client_state.latest_slot),
+        )
+        .map_err(ContractError::UnsupportedForkVersion)?;
+
+    let mut wasm_client_state = get_wasm_client_state(&subject_store)?;
+    wasm_client_state.data = serde_json::to_vec(&recovered_client_state)
+        .map_err(ContractError::SerializeClientStateFailed)?;
+    wasm_client_state.latest_height = Some(IbcProtoHeight {
+        revision_number: recovered_client_state.revision_number,
+        revision_height: recovered_client_state.latest_slot,
+    });
+    let wasm_consensus_state = WasmConsensusState {
+        data: serde_json::to_vec(&substitute_consensus_state)
+            .map_err(ContractError::SerializeConsensusStateFailed)?,
+    };
+
+    store_client_state(&mut subject_store, &wasm_client_state)?;
+    store_consensus_state(
+        &mut subject_store,
+        &wasm_consensus_state,
+        recovered_client_state.revision_number,
+        substitute_consensus_state.slot,
+    )?;
+    store_sync_committee_roots(
+        &mut subject_store,
+        recovered_client_state.revision_number,
+        substitute_consensus_state.slot,
+        &substitute_roots,
+    )?;
+    remove_frozen_at(&mut subject_store);
 
     Ok(Binary::default())
 }
 
 #[cfg(test)]
 mod tests {
+    use alloy_primitives::{B256, U256};
     use cosmwasm_std::{
         coins, from_json,
         testing::{message_info, mock_env},
-        Binary,
+        Binary, Deps, Timestamp,
+    };
+    use ethereum_light_client::{
+        client_state::{ClientState as EthClientState, ConsensusStateWrites},
+        consensus_state::ConsensusState as EthConsensusState,
+        error::EthereumIBCError,
+        header::Header,
+        membership::evm_ics26_commitment_path,
+        test_utils::{
+            fixtures::{self, InitialState, RelayerMessages, StepsFixture},
+            storage_trie::{key_nibbles, storage_proof_corpus, two_key_trie, TrieNode},
+            update_generator::{UpdateGenerator, SLOTS_PER_EPOCH},
+        },
+        upgrade::{upgraded_client_state_path, upgraded_consensus_state_path},
     };
-    use ethereum_light_client::test_utils::fixtures::{self, InitialState, StepsFixture};
+    use ethereum_types::execution::storage_proof::StorageProof;
+    use ibc_proto::ibc::lightclients::wasm::v1::{
+        ClientMessage, ConsensusState as WasmConsensusState,
+    };
+    use prost::Message;
 
-    use crate::{contract::instantiate, test::mk_deps};
+    use crate::{
+        client_message::{CLIENT_MESSAGE_TYPE_HEADER_BATCH, CLIENT_MESSAGE_VERSION},
+        contract::instantiate,
+        custom_query::EthereumCustomQuery,
+        msg::{
+            ClientMessageEnvelope, ConsensusHeightsMsg, ConsensusHeightsResult, Height, MerklePath,
+            StatusResult, UpdateStateMsg, UpdateStateResult, VerifyClientMessageMsg,
+            VerifyMembershipMsg, VerifyNonMembershipMsg, VerifyUpgradeAndUpdateStateMsg,
+        },
+        state::{
+            get_frozen_at, get_sync_committee_roots, store_frozen_at, store_sync_committee_roots,
+            ClientStore, SyncCommitteeRoots,
+        },
+        test::mk_deps,
+        ContractError,
+    };
+
+    use super::{
+        ATTRIBUTE_KEY_CONSENSUS_SLOT, ATTRIBUTE_KEY_PRUNED_HEIGHTS, ATTRIBUTE_KEY_RELAYER,
+        ATTRIBUTE_KEY_SYNC_COMMITTEE_PERIOD, ATTRIBUTE_KEY_SYNC_COMMITTEE_ROOT,
+        EVENT_TYPE_CONSENSUS_STATES_PRUNED, EVENT_TYPE_DUPLICATE_UPDATE,
+        EVENT_TYPE_SYNC_COMMITTEE_ROTATION, EVENT_TYPE_UPDATE_RELAYER,
+    };
 
     #[test]
     fn test_misbehaviour() {
@@ -199,8 +758,1369 @@ mod tests {
         assert!(eth_client_state.is_frozen);
 
         // Query status
-        let res = crate::query::status(deps.as_ref()).unwrap();
+        let res = crate::query::status(deps.as_ref(), mock_env()).unwrap();
         let status_result: crate::msg::StatusResult = from_json(res).unwrap();
         assert_eq!("Frozen", status_result.status);
+        assert_eq!(Some(client_state.latest_slot), status_result.frozen_at);
+
+        // Resubmitting the same evidence is rejected
+        let msg = crate::msg::UpdateStateOnMisbehaviourMsg {
+            client_message: Binary::default(),
+        };
+        let err = crate::sudo::misbehaviour(deps.as_mut(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyFrozen));
+
+        // Submitting different evidence to a frozen client is rejected as well
+        let msg = crate::msg::UpdateStateOnMisbehaviourMsg {
+            client_message: Binary::from(b"other evidence".to_vec()),
+        };
+        let err = crate::sudo::misbehaviour(deps.as_mut(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyFrozen));
+    }
+
+    #[test]
+    fn test_update_state_with_sync_committee_rotation() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let fixture: StepsFixture = fixtures::load("TestMultiPeriodClientUpdateToCosmos");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+
+        let client_state = initial_state.client_state;
+        let consensus_state = initial_state.consensus_state;
+
+        let client_state_bz: Vec<u8> = serde_json::to_vec(&client_state).unwrap();
+        let consensus_state_bz: Vec<u8> = serde_json::to_vec(&consensus_state).unwrap();
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(client_state_bz),
+            consensus_state: Binary::from(consensus_state_bz),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, _, _) = relayer_messages.get_sdk_msgs();
+        let headers = update_client_msgs
+            .iter()
+            .map(|msg| {
+                let client_msg =
+                    ClientMessage::decode(msg.client_message.clone().unwrap().value.as_slice())
+                        .unwrap();
+                serde_json::from_slice(client_msg.data.as_slice()).unwrap()
+            })
+            .collect::<Vec<Header>>();
+
+        let mut rotations = 0;
+        for header in headers {
+            let eth_client_state =
+                crate::state::get_eth_client_state(deps.as_ref().storage).unwrap();
+            let store_period = eth_client_state
+                .compute_sync_committee_period_at_slot(eth_client_state.latest_slot);
+            let update_period = eth_client_state.compute_sync_committee_period_at_slot(
+                header.consensus_update.finalized_header.beacon.slot,
+            );
+
+            if update_period == store_period + 1 {
+                // A tampered next sync committee branch must be rejected
+                let mut tampered_header = header.clone();
+                tampered_header
+                    .consensus_update
+                    .next_sync_committee_branch
+                    .as_mut()
+                    .unwrap()[0] = B256::repeat_byte(0xaa);
+                let msg = UpdateStateMsg {
+                    client_message: Binary::from(serde_json::to_vec(&tampered_header).unwrap()),
+                };
+                let err = super::update_state(deps.as_mut(), msg).unwrap_err();
+                assert!(matches!(
+                    err,
+                    ContractError::UpdateClientStateFailed(
+                        EthereumIBCError::ValidateNextSyncCommitteeFailed(_)
+                    )
+                ));
+            }
+
+            let msg = UpdateStateMsg {
+                client_message: Binary::from(serde_json::to_vec(&header).unwrap()),
+            };
+            let res = super::update_state(deps.as_mut(), msg).unwrap();
+
+            let rotation_event = res
+                .events
+                .iter()
+                .find(|e| e.ty == EVENT_TYPE_SYNC_COMMITTEE_ROTATION);
+            if update_period == store_period + 1 {
+                rotations += 1;
+                let rotation_event = rotation_event.expect("rotation event should be emitted");
+                let root = rotation_event
+                    .attributes
+                    .iter()
+                    .find(|a| a.key == ATTRIBUTE_KEY_SYNC_COMMITTEE_ROOT)
+                    .unwrap();
+                assert_eq!(
+                    header.active_sync_committee.tree_hash_root().to_string(),
+                    root.value
+                );
+
+                // The rotated committee is persisted as the current sync committee
+                let eth_consensus_state = crate::state::get_eth_consensus_state(
+                    deps.as_ref().storage,
+                    eth_client_state.revision_number,
+                    header.consensus_update.finalized_header.beacon.slot,
+                )
+                .unwrap();
+                assert_eq!(
+                    header
+                        .active_sync_committee
+                        .sync_committee()
+                        .aggregate_pubkey,
+                    eth_consensus_state.current_sync_committee
+                );
+            } else {
+                assert!(rotation_event.is_none());
+            }
+        }
+        assert!(rotations > 0);
+    }
+
+    #[test]
+    fn test_update_state_through_generated_periods() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(2) + 9;
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        for (period, header) in (3..).zip(generator.period_headers(trusted_slot, 8)) {
+            let client_message = Binary::from(serde_json::to_vec(&header).unwrap());
+
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(
+                generator.timestamp_at_slot(header.consensus_update.signature_slot),
+            );
+            crate::query::verify_client_message(
+                deps.as_ref(),
+                env,
+                VerifyClientMessageMsg {
+                    client_message: client_message.clone(),
+                },
+            )
+            .unwrap();
+
+            let res =
+                super::update_state(deps.as_mut(), UpdateStateMsg { client_message }).unwrap();
+            let rotation_event = res
+                .events
+                .iter()
+                .find(|e| e.ty == EVENT_TYPE_SYNC_COMMITTEE_ROTATION)
+                .expect("rotation event should be emitted");
+            let rotated_period = rotation_event
+                .attributes
+                .iter()
+                .find(|a| a.key == ATTRIBUTE_KEY_SYNC_COMMITTEE_PERIOD)
+                .unwrap();
+            assert_eq!(period.to_string(), rotated_period.value);
+
+            let eth_consensus_state = crate::state::get_eth_consensus_state(
+                deps.as_ref().storage,
+                client_state.revision_number,
+                header.consensus_update.finalized_header.beacon.slot,
+            )
+            .unwrap();
+            assert_eq!(
+                generator.sync_committee(period).aggregate_pubkey,
+                eth_consensus_state.current_sync_committee
+            );
+            assert_eq!(
+                Some(generator.sync_committee(period + 1).aggregate_pubkey),
+                eth_consensus_state.next_sync_committee
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_zero_revision_number() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(2) + 9;
+        let (mut client_state, mut consensus_state) = generator.initial_states(trusted_slot);
+        client_state.revision_number = 2;
+
+        // The trusted storage root holds a packet commitment to relay
+        let packet_path = b"commitments".to_vec();
+        let packet_key = evm_ics26_commitment_path(&packet_path, client_state.ibc_commitment_slot);
+        let packet_trie = TrieNode::leaf(&key_nibbles(packet_key.into()), U256::from(1));
+        consensus_state.storage_root = packet_trie.root();
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // The consensus states are stored under the keys of their heights, as read by ibc-go
+        let consensus_state_key = |revision_number, slot| {
+            crate::state::consensus_db_key(revision_number, slot).into_bytes()
+        };
+        assert!(deps
+            .as_ref()
+            .storage
+            .get(&consensus_state_key(2, trusted_slot))
+            .is_some());
+        assert!(deps
+            .as_ref()
+            .storage
+            .get(&consensus_state_key(0, trusted_slot))
+            .is_none());
+
+        // The packet is relayed at the height of the client revision only
+        let verify_membership = |deps: Deps<EthereumCustomQuery>, revision_number| {
+            super::verify_membership(
+                deps,
+                VerifyMembershipMsg {
+                    height: Height {
+                        revision_number,
+                        revision_height: trusted_slot,
+                    },
+                    delay_time_period: 0,
+                    delay_block_period: 0,
+                    proof: serde_json::to_vec(&StorageProof {
+                        key: packet_key.into(),
+                        value: U256::from(1),
+                        proof: packet_trie.proof(),
+                    })
+                    .unwrap()
+                    .into(),
+                    merkle_path: MerklePath {
+                        key_path: vec![packet_path.clone().into()],
+                    },
+                    value: U256::from(1).to_be_bytes_vec().into(),
+                },
+            )
+        };
+        verify_membership(deps.as_ref(), 2).unwrap();
+        assert!(matches!(
+            verify_membership(deps.as_ref(), 0).unwrap_err(),
+            ContractError::RevisionNumberMismatch {
+                expected: 2,
+                found: 0
+            }
+        ));
+
+        // The updated heights keep the revision number of the client
+        let header = generator.period_headers(trusted_slot, 3).remove(0);
+        let updated_slot = header.consensus_update.finalized_header.beacon.slot;
+        let res = super::update_state(
+            deps.as_mut(),
+            UpdateStateMsg {
+                client_message: Binary::from(serde_json::to_vec(&header).unwrap()),
+            },
+        )
+        .unwrap();
+        let result: UpdateStateResult = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(
+            vec![Height {
+                revision_number: 2,
+                revision_height: updated_slot,
+            }],
+            result.heights
+        );
+        assert!(deps
+            .as_ref()
+            .storage
+            .get(&consensus_state_key(2, updated_slot))
+            .is_some());
+        let wasm_client_state = crate::state::get_wasm_client_state(deps.as_ref().storage).unwrap();
+        assert_eq!(2, wasm_client_state.latest_height.unwrap().revision_number);
+
+        // The queries are answered at the heights of the client revision
+        let latest_height: crate::msg::LatestHeightResult =
+            from_json(crate::query::latest_height(deps.as_ref()).unwrap()).unwrap();
+        assert_eq!(
+            Height {
+                revision_number: 2,
+                revision_height: updated_slot,
+            },
+            latest_height.height
+        );
+        let timestamp_at_height = |revision_number| {
+            crate::query::timestamp_at_height(
+                deps.as_ref(),
+                crate::msg::TimestampAtHeightMsg {
+                    height: Height {
+                        revision_number,
+                        revision_height: updated_slot,
+                    },
+                },
+            )
+        };
+        let timestamp: crate::msg::TimestampAtHeightResult =
+            from_json(timestamp_at_height(2).unwrap()).unwrap();
+        assert_eq!(
+            header.consensus_update.finalized_header.execution.timestamp * 1_000_000_000,
+            timestamp.timestamp
+        );
+        assert!(matches!(
+            timestamp_at_height(0).unwrap_err(),
+            ContractError::RevisionNumberMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_update_state_signed_in_next_period() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(1) + 9;
+        let (client_state, consensus_state) = generator.initial_states(trusted_slot);
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Finalized in the trusted period, but signed by the next sync committee
+        let signature_slot = generator.period_start_slot(2) + 1;
+        let header = generator.header(
+            trusted_slot,
+            generator.light_client_update(trusted_slot + 20, signature_slot, false),
+        );
+        let client_message = Binary::from(serde_json::to_vec(&header).unwrap());
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(generator.timestamp_at_slot(signature_slot));
+        crate::query::verify_client_message(
+            deps.as_ref(),
+            env,
+            VerifyClientMessageMsg {
+                client_message: client_message.clone(),
+            },
+        )
+        .unwrap();
+
+        let res = super::update_state(deps.as_mut(), UpdateStateMsg { client_message }).unwrap();
+        assert!(!res
+            .events
+            .iter()
+            .any(|e| e.ty == EVENT_TYPE_SYNC_COMMITTEE_ROTATION));
+
+        let eth_consensus_state = crate::state::get_eth_consensus_state(
+            deps.as_ref().storage,
+            client_state.revision_number,
+            trusted_slot + 20,
+        )
+        .unwrap();
+        assert_eq!(
+            generator.sync_committee(1).aggregate_pubkey,
+            eth_consensus_state.current_sync_committee
+        );
+        assert_eq!(
+            Some(generator.sync_committee(2).aggregate_pubkey),
+            eth_consensus_state.next_sync_committee
+        );
+    }
+
+    #[test]
+    fn test_update_state_with_header_batch() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let relayer = deps.api.addr_make("relayer");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let fixture: StepsFixture = fixtures::load("TestMultiPeriodClientUpdateToCosmos");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&initial_state.client_state).unwrap()),
+            consensus_state: Binary::from(
+                serde_json::to_vec(&initial_state.consensus_state).unwrap(),
+            ),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, _, _) = relayer_messages.get_sdk_msgs();
+        let headers = update_client_msgs
+            .iter()
+            .map(|msg| {
+                let client_msg =
+                    ClientMessage::decode(msg.client_message.clone().unwrap().value.as_slice())
+                        .unwrap();
+                serde_json::from_slice(client_msg.data.as_slice()).unwrap()
+            })
+            .collect::<Vec<Header>>();
+        assert!(headers.len() > 1);
+
+        let envelope = |relayer: &str| {
+            Binary::from(
+                serde_json::to_vec(&ClientMessageEnvelope {
+                    version: CLIENT_MESSAGE_VERSION,
+                    message_type: CLIENT_MESSAGE_TYPE_HEADER_BATCH.to_string(),
+                    data: Binary::from(serde_json::to_vec(&headers).unwrap()),
+                    relayer: Some(relayer.to_string()),
+                })
+                .unwrap(),
+            )
+        };
+        let client_message = envelope(relayer.as_str());
+
+        let mut env = mock_env();
+        env.block.time = cosmwasm_std::Timestamp::from_seconds(
+            headers
+                .last()
+                .unwrap()
+                .consensus_update
+                .attested_header
+                .execution
+                .timestamp
+                + 1000,
+        );
+        crate::query::verify_client_message(
+            deps.as_ref(),
+            env,
+            crate::msg::VerifyClientMessageMsg {
+                client_message: client_message.clone(),
+            },
+        )
+        .unwrap();
+
+        // The updates cannot be attributed to an invalid address
+        let err = super::update_state(
+            deps.as_mut(),
+            UpdateStateMsg {
+                client_message: envelope("not an address"),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRelayerAddress(_)));
+
+        let res = super::update_state(deps.as_mut(), UpdateStateMsg { client_message }).unwrap();
+        let result: UpdateStateResult = from_json(res.data.unwrap()).unwrap();
+        let slots = headers
+            .iter()
+            .map(|header| header.consensus_update.finalized_header.beacon.slot)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            slots,
+            result
+                .heights
+                .iter()
+                .map(|height| height.revision_height)
+                .collect::<Vec<_>>()
+        );
+
+        // The consensus state of every header of the batch is backfilled, and attributed to the
+        // relayer
+        for slot in &slots {
+            assert!(crate::state::get_eth_consensus_state(
+                deps.as_ref().storage,
+                initial_state.client_state.revision_number,
+                *slot
+            )
+            .is_ok());
+            assert_eq!(
+                crate::state::get_update_relayer(
+                    deps.as_ref().storage,
+                    initial_state.client_state.revision_number,
+                    *slot
+                )
+                .as_deref(),
+                Some(relayer.as_str())
+            );
+        }
+        let attributed_slots = res
+            .events
+            .iter()
+            .filter(|e| e.ty == EVENT_TYPE_UPDATE_RELAYER)
+            .map(|e| {
+                assert!(e
+                    .attributes
+                    .iter()
+                    .any(|a| a.key == ATTRIBUTE_KEY_RELAYER && a.value == relayer.as_str()));
+                e.attributes
+                    .iter()
+                    .find(|a| a.key == ATTRIBUTE_KEY_CONSENSUS_SLOT)
+                    .unwrap()
+                    .value
+                    .parse::<u64>()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(slots, attributed_slots);
+        let eth_client_state = crate::state::get_eth_client_state(deps.as_ref().storage).unwrap();
+        assert_eq!(eth_client_state.latest_slot, *slots.last().unwrap());
+    }
+
+    #[test]
+    fn test_header_batch_overlapping_stored_state() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let fixture: StepsFixture = fixtures::load("TestMultiPeriodClientUpdateToCosmos");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&initial_state.client_state).unwrap()),
+            consensus_state: Binary::from(
+                serde_json::to_vec(&initial_state.consensus_state).unwrap(),
+            ),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, _, _) = relayer_messages.get_sdk_msgs();
+        let headers = update_client_msgs
+            .iter()
+            .map(|msg| {
+                let client_msg =
+                    ClientMessage::decode(msg.client_message.clone().unwrap().value.as_slice())
+                        .unwrap();
+                serde_json::from_slice(client_msg.data.as_slice()).unwrap()
+            })
+            .collect::<Vec<Header>>();
+        assert!(headers.len() > 1);
+        let slots = headers
+            .iter()
+            .map(|header| header.consensus_update.finalized_header.beacon.slot)
+            .collect::<Vec<_>>();
+
+        // Another relayer already applied the first header of the batch
+        super::update_state(
+            deps.as_mut(),
+            UpdateStateMsg {
+                client_message: Binary::from(serde_json::to_vec(&headers[0]).unwrap()),
+            },
+        )
+        .unwrap();
+
+        let client_message = Binary::from(
+            serde_json::to_vec(&ClientMessageEnvelope {
+                version: CLIENT_MESSAGE_VERSION,
+                message_type: CLIENT_MESSAGE_TYPE_HEADER_BATCH.to_string(),
+                data: Binary::from(serde_json::to_vec(&headers).unwrap()),
+                relayer: None,
+            })
+            .unwrap(),
+        );
+        let mut env = mock_env();
+        env.block.time = cosmwasm_std::Timestamp::from_seconds(
+            headers
+                .last()
+                .unwrap()
+                .consensus_update
+                .attested_header
+                .execution
+                .timestamp
+                + 1000,
+        );
+        crate::query::verify_client_message(
+            deps.as_ref(),
+            env,
+            crate::msg::VerifyClientMessageMsg {
+                client_message: client_message.clone(),
+            },
+        )
+        .unwrap();
+
+        // The applied header is skipped, and the rest of the batch is applied on top of it
+        let res = super::update_state(deps.as_mut(), UpdateStateMsg { client_message }).unwrap();
+        let result: UpdateStateResult = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(
+            slots,
+            result
+                .heights
+                .iter()
+                .map(|height| height.revision_height)
+                .collect::<Vec<_>>()
+        );
+        let duplicate_slots = res
+            .events
+            .iter()
+            .filter(|e| e.ty == EVENT_TYPE_DUPLICATE_UPDATE)
+            .map(|e| {
+                e.attributes
+                    .iter()
+                    .find(|a| a.key == ATTRIBUTE_KEY_CONSENSUS_SLOT)
+                    .unwrap()
+                    .value
+                    .parse::<u64>()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![slots[0]], duplicate_slots);
+        let eth_client_state = crate::state::get_eth_client_state(deps.as_ref().storage).unwrap();
+        assert_eq!(eth_client_state.latest_slot, *slots.last().unwrap());
+    }
+
+    #[test]
+    fn test_update_state_with_period_boundary_writes() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(2) + 9;
+        let (mut client_state, consensus_state) = generator.initial_states(trusted_slot);
+        client_state.consensus_state_writes = ConsensusStateWrites::PeriodBoundaries;
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // A period update followed by an update within the same period, for each period
+        let mut headers = vec![];
+        let mut trusted_slot = trusted_slot;
+        for period in 3..=5 {
+            let period_header = generator.header(trusted_slot, generator.period_update(period));
+            let start_slot = generator.period_start_slot(period);
+            let header = generator.header(
+                period_header.consensus_update.finalized_header.beacon.slot,
+                generator.light_client_update(
+                    start_slot + 2 * SLOTS_PER_EPOCH,
+                    start_slot + 4 * SLOTS_PER_EPOCH + 1,
+                    false,
+                ),
+            );
+            trusted_slot = header.consensus_update.finalized_header.beacon.slot;
+            headers.extend([period_header, header]);
+        }
+
+        let client_message = Binary::from(
+            serde_json::to_vec(&ClientMessageEnvelope {
+                version: CLIENT_MESSAGE_VERSION,
+                message_type: CLIENT_MESSAGE_TYPE_HEADER_BATCH.to_string(),
+                data: Binary::from(serde_json::to_vec(&headers).unwrap()),
+                relayer: None,
+            })
+            .unwrap(),
+        );
+        let res = super::update_state(deps.as_mut(), UpdateStateMsg { client_message }).unwrap();
+        let result: UpdateStateResult = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(result.heights.len(), headers.len());
+
+        // Only the last consensus state of each period is stored
+        for (i, header) in headers.iter().enumerate() {
+            let slot = header.consensus_update.finalized_header.beacon.slot;
+            assert_eq!(
+                crate::state::get_eth_consensus_state(
+                    deps.as_ref().storage,
+                    client_state.revision_number,
+                    slot
+                )
+                .is_ok(),
+                i % 2 == 1,
+                "consensus state at slot {slot}"
+            );
+        }
+        let eth_client_state = crate::state::get_eth_client_state(deps.as_ref().storage).unwrap();
+        assert_eq!(eth_client_state.latest_slot, trusted_slot);
+        assert_eq!(
+            crate::state::get_eth_consensus_state(
+                deps.as_ref().storage,
+                eth_client_state.revision_number,
+                trusted_slot
+            )
+            .unwrap()
+            .current_sync_committee,
+            generator.sync_committee(5).aggregate_pubkey
+        );
+    }
+
+    #[test]
+    fn test_duplicate_update_state() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let fixture: StepsFixture =
+            fixtures::load("TestICS20TransferNativeCosmosCoinsToEthereumAndBack_Groth16");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+
+        let client_state_bz: Vec<u8> = serde_json::to_vec(&initial_state.client_state).unwrap();
+        let consensus_state_bz: Vec<u8> =
+            serde_json::to_vec(&initial_state.consensus_state).unwrap();
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(client_state_bz),
+            consensus_state: Binary::from(consensus_state_bz),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, _, _) = relayer_messages.get_sdk_msgs();
+        let header_bz = ClientMessage::decode(
+            update_client_msgs[0]
+                .client_message
+                .clone()
+                .unwrap()
+                .value
+                .as_slice(),
+        )
+        .unwrap()
+        .data;
+        let header: Header = serde_json::from_slice(&header_bz).unwrap();
+        let slot = header.consensus_update.finalized_header.beacon.slot;
+
+        let msg = UpdateStateMsg {
+            client_message: Binary::from(header_bz.clone()),
+        };
+        let first = super::update_state(deps.as_mut(), msg.clone()).unwrap();
+        assert!(first
+            .events
+            .iter()
+            .all(|e| e.ty != EVENT_TYPE_DUPLICATE_UPDATE));
+
+        // Another relayer submitting the same update gets the existing height back
+        let mut env = mock_env();
+        env.block.time = cosmwasm_std::Timestamp::from_seconds(
+            header.consensus_update.attested_header.execution.timestamp + 1000,
+        );
+        crate::query::verify_client_message(
+            deps.as_ref(),
+            env,
+            crate::msg::VerifyClientMessageMsg {
+                client_message: Binary::from(header_bz),
+            },
+        )
+        .unwrap();
+        let second = super::update_state(deps.as_mut(), msg).unwrap();
+        assert_eq!(first.data, second.data);
+        let duplicate_event = second
+            .events
+            .iter()
+            .find(|e| e.ty == EVENT_TYPE_DUPLICATE_UPDATE)
+            .expect("duplicate update event should be emitted");
+        assert_eq!(
+            slot.to_string(),
+            duplicate_event
+                .attributes
+                .iter()
+                .find(|a| a.key == ATTRIBUTE_KEY_CONSENSUS_SLOT)
+                .unwrap()
+                .value
+        );
+
+        // An update with a different consensus state at the same slot is still rejected
+        let mut conflicting_header = header;
+        conflicting_header
+            .consensus_update
+            .finalized_header
+            .execution
+            .state_root = B256::repeat_byte(0xaa);
+        let msg = UpdateStateMsg {
+            client_message: Binary::from(serde_json::to_vec(&conflicting_header).unwrap()),
+        };
+        super::update_state(deps.as_mut(), msg).unwrap_err();
+    }
+
+    #[test]
+    fn test_verify_membership_corpus() {
+        let generator = UpdateGenerator::new(16);
+        let slot = generator.period_start_slot(1);
+        let path = b"corpus/commitments".to_vec();
+
+        for case in storage_proof_corpus(evm_ics26_commitment_path(
+            &path,
+            generator.client_state().ibc_commitment_slot,
+        )) {
+            let mut deps = mk_deps();
+            let creator = deps.api.addr_make("creator");
+            let info = message_info(&creator, &coins(1, "uatom"));
+
+            let (client_state, mut consensus_state) = generator.initial_states(slot);
+            consensus_state.storage_root = case.storage_root;
+            let msg = crate::msg::InstantiateMsg {
+                client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+                consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+                checksum: b"checksum".into(),
+            };
+            instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+            let height = Height {
+                revision_number: client_state.revision_number,
+                revision_height: slot,
+            };
+            let merkle_path = MerklePath {
+                key_path: vec![path.clone().into()],
+            };
+            let verify_membership = |storage_proof: &StorageProof| {
+                super::verify_membership(
+                    deps.as_ref(),
+                    VerifyMembershipMsg {
+                        height: height.clone(),
+                        delay_time_period: 0,
+                        delay_block_period: 0,
+                        proof: serde_json::to_vec(storage_proof).unwrap().into(),
+                        merkle_path: merkle_path.clone(),
+                        value: storage_proof.value.to_be_bytes_vec().into(),
+                    },
+                )
+            };
+            let verify_non_membership = |storage_proof: &StorageProof| {
+                super::verify_non_membership(
+                    deps.as_ref(),
+                    VerifyNonMembershipMsg {
+                        height: height.clone(),
+                        delay_time_period: 0,
+                        delay_block_period: 0,
+                        proof: serde_json::to_vec(storage_proof).unwrap().into(),
+                        merkle_path: merkle_path.clone(),
+                    },
+                )
+            };
+
+            if case.is_member {
+                verify_membership(&case.storage_proof)
+                    .unwrap_or_else(|e| panic!("{}: {e}", case.name));
+                assert!(
+                    matches!(
+                        verify_non_membership(&StorageProof {
+                            value: U256::ZERO,
+                            ..case.storage_proof.clone()
+                        }),
+                        Err(ContractError::VerifyNonMembershipFailed(_))
+                    ),
+                    "{}",
+                    case.name
+                );
+                // Another value at the key
+                assert!(
+                    matches!(
+                        verify_membership(&StorageProof {
+                            value: case.storage_proof.value - U256::from(1),
+                            ..case.storage_proof.clone()
+                        }),
+                        Err(ContractError::VerifyMembershipFailed(_))
+                    ),
+                    "{}",
+                    case.name
+                );
+            } else {
+                verify_non_membership(&case.storage_proof)
+                    .unwrap_or_else(|e| panic!("{}: {e}", case.name));
+                assert!(
+                    matches!(
+                        verify_membership(&StorageProof {
+                            value: U256::from(1),
+                            ..case.storage_proof.clone()
+                        }),
+                        Err(ContractError::VerifyMembershipFailed(_))
+                    ),
+                    "{}",
+                    case.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_upgrade_and_update_state() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let slot = generator.period_start_slot(1);
+        let upgraded_slot = generator.period_start_slot(2);
+        let (mut client_state, mut consensus_state) = generator.initial_states(slot);
+        client_state.min_slot_advance = Some(SLOTS_PER_EPOCH);
+        let (mut upgraded_client_state, mut upgraded_consensus_state) =
+            generator.initial_states(upgraded_slot);
+        upgraded_client_state.chain_id += 1;
+        upgraded_client_state.consensus_state_writes = ConsensusStateWrites::PeriodBoundaries;
+
+        // The upgraded storage root holds a packet commitment to prove after the upgrade
+        let packet_path = b"upgraded/commitments".to_vec();
+        let packet_key = evm_ics26_commitment_path(&packet_path, client_state.ibc_commitment_slot);
+        let packet_trie = TrieNode::leaf(&key_nibbles(packet_key.into()), U256::from(1));
+        upgraded_consensus_state.storage_root = packet_trie.root();
+
+        // The IBC contract commits the canonical hashes of the upgraded states
+        let commitment = |path: Vec<u8>, hash: B256| {
+            (
+                B256::from(evm_ics26_commitment_path(
+                    &path,
+                    client_state.ibc_commitment_slot,
+                )),
+                U256::from_be_bytes(hash.0),
+            )
+        };
+        let client_commitment = commitment(
+            upgraded_client_state_path(upgraded_slot),
+            upgraded_client_state.canonical_hash().unwrap(),
+        );
+        let consensus_commitment = commitment(
+            upgraded_consensus_state_path(upgraded_slot),
+            upgraded_consensus_state.canonical_hash().unwrap(),
+        );
+        let upgrade_trie = two_key_trie(client_commitment, consensus_commitment);
+        consensus_state.storage_root = upgrade_trie.root();
+        let proof = |(key, value)| -> Binary {
+            serde_json::to_vec(&StorageProof {
+                key,
+                value,
+                proof: upgrade_trie.proof(),
+            })
+            .unwrap()
+            .into()
+        };
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let upgrade_msg = VerifyUpgradeAndUpdateStateMsg {
+            upgrade_client_state: serde_json::to_vec(&upgraded_client_state).unwrap().into(),
+            upgrade_consensus_state: serde_json::to_vec(&upgraded_consensus_state)
+                .unwrap()
+                .into(),
+            proof_upgrade_client: proof(client_commitment),
+            proof_upgrade_consensus_state: proof(consensus_commitment),
+        };
+
+        // The proofs of the two states swapped are rejected, and leave the state untouched
+        let err = super::verify_upgrade_and_update_state(
+            deps.as_mut(),
+            VerifyUpgradeAndUpdateStateMsg {
+                proof_upgrade_client: upgrade_msg.proof_upgrade_consensus_state.clone(),
+                proof_upgrade_consensus_state: upgrade_msg.proof_upgrade_client.clone(),
+                ..upgrade_msg.clone()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::VerifyUpgradeFailed(EthereumIBCError::InvalidCommitmentKey(..))
+        ));
+        assert_eq!(
+            crate::state::get_eth_client_state(deps.as_ref().storage).unwrap(),
+            client_state
+        );
+
+        super::verify_upgrade_and_update_state(deps.as_mut(), upgrade_msg.clone()).unwrap();
+
+        let new_client_state = crate::state::get_eth_client_state(deps.as_ref().storage).unwrap();
+        assert_eq!(upgraded_slot, new_client_state.latest_slot);
+        assert_eq!(upgraded_client_state.chain_id, new_client_state.chain_id);
+        // The parameters chosen by the client are kept
+        assert_eq!(Some(SLOTS_PER_EPOCH), new_client_state.min_slot_advance);
+        assert_eq!(
+            ConsensusStateWrites::All,
+            new_client_state.consensus_state_writes
+        );
+        let wasm_client_state = crate::state::get_wasm_client_state(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            upgraded_slot,
+            wasm_client_state.latest_height.unwrap().revision_height
+        );
+        assert_eq!(
+            upgraded_consensus_state,
+            crate::state::get_eth_consensus_state(
+                deps.as_ref().storage,
+                upgraded_client_state.revision_number,
+                upgraded_slot
+            )
+            .unwrap()
+        );
+
+        // The packets committed after the upgrade are proven against the upgraded consensus state
+        super::verify_membership(
+            deps.as_ref(),
+            VerifyMembershipMsg {
+                height: Height {
+                    revision_number: new_client_state.revision_number,
+                    revision_height: upgraded_slot,
+                },
+                delay_time_period: 0,
+                delay_block_period: 0,
+                proof: serde_json::to_vec(&StorageProof {
+                    key: packet_key.into(),
+                    value: U256::from(1),
+                    proof: packet_trie.proof(),
+                })
+                .unwrap()
+                .into(),
+                merkle_path: MerklePath {
+                    key_path: vec![packet_path.into()],
+                },
+                value: U256::from(1).to_be_bytes_vec().into(),
+            },
+        )
+        .unwrap();
+
+        // The upgrade cannot be replayed on the upgraded client
+        let err = super::verify_upgrade_and_update_state(deps.as_mut(), upgrade_msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::VerifyUpgradeFailed(EthereumIBCError::UpgradeSlotNotIncreasing { .. })
+        ));
+    }
+
+    #[test]
+    fn test_update_state_prunes_expired_consensus_states() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let retention = generator.period_start_slot(1);
+        let trusted_slot = generator.period_start_slot(2) + 9;
+        let (mut client_state, consensus_state) = generator.initial_states(trusted_slot);
+        client_state.consensus_state_retention = Some(retention);
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let query_heights = |deps: Deps<EthereumCustomQuery>, start_after: Option<u64>| {
+            let res = crate::query::consensus_heights(
+                deps,
+                ConsensusHeightsMsg {
+                    start_after: start_after.map(|slot| Height {
+                        revision_number: client_state.revision_number,
+                        revision_height: slot,
+                    }),
+                    limit: Some(2),
+                },
+            )
+            .unwrap();
+            from_json::<ConsensusHeightsResult>(res)
+                .unwrap()
+                .heights
+                .into_iter()
+                .map(|height| height.revision_height)
+                .collect::<Vec<_>>()
+        };
+
+        let mut stored_slots = vec![trusted_slot];
+        for header in generator.period_headers(trusted_slot, 6) {
+            let slot = header.consensus_update.finalized_header.beacon.slot;
+            let msg = UpdateStateMsg {
+                client_message: Binary::from(serde_json::to_vec(&header).unwrap()),
+            };
+            let res = super::update_state(deps.as_mut(), msg).unwrap();
+
+            stored_slots.push(slot);
+            let (expired, kept): (Vec<u64>, Vec<u64>) = stored_slots
+                .into_iter()
+                .partition(|stored_slot| *stored_slot < slot - retention);
+            stored_slots = kept;
+
+            let pruned_event = res
+                .events
+                .iter()
+                .find(|e| e.ty == EVENT_TYPE_CONSENSUS_STATES_PRUNED);
+            if expired.is_empty() {
+                assert!(pruned_event.is_none());
+            } else {
+                let pruned_heights = pruned_event
+                    .expect("pruned event should be emitted")
+                    .attributes
+                    .iter()
+                    .find(|a| a.key == ATTRIBUTE_KEY_PRUNED_HEIGHTS)
+                    .unwrap();
+                assert_eq!(
+                    expired
+                        .iter()
+                        .map(|slot| format!("{}-{slot}", client_state.revision_number))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    pruned_heights.value
+                );
+            }
+            for expired_slot in expired {
+                crate::state::get_eth_consensus_state(
+                    deps.as_ref().storage,
+                    client_state.revision_number,
+                    expired_slot,
+                )
+                .unwrap_err();
+            }
+
+            // The heights are paged from the index, in ascending order
+            let mut heights = query_heights(deps.as_ref(), None);
+            while let Some(last) = heights.last().copied() {
+                let page = query_heights(deps.as_ref(), Some(last));
+                if page.is_empty() {
+                    break;
+                }
+                heights.extend(page);
+            }
+            assert_eq!(stored_slots, heights);
+
+            let status: StatusResult =
+                from_json(crate::query::status(deps.as_ref(), mock_env()).unwrap()).unwrap();
+            assert_eq!(
+                Some(stored_slots[0]),
+                status.earliest_height.map(|height| height.revision_height)
+            );
+        }
+        assert!(stored_slots.len() < 5);
+    }
+
+    #[test]
+    fn test_update_state_after_revision_change() {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let generator = UpdateGenerator::new(16);
+        let trusted_slot = generator.period_start_slot(2) + 9;
+        let (mut client_state, consensus_state) = generator.initial_states(trusted_slot);
+        client_state.revision_number = 1;
+        client_state.consensus_state_retention = Some(generator.period_start_slot(1));
+
+        let msg = crate::msg::InstantiateMsg {
+            client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // The records of the previous revision, at the slots of the current one
+        let previous_slots = [trusted_slot, trusted_slot + 1];
+        let previous_roots = SyncCommitteeRoots {
+            current: Some(B256::repeat_byte(0x01)),
+            next: None,
+        };
+        for slot in previous_slots {
+            crate::state::store_consensus_state(
+                &mut deps.storage,
+                &WasmConsensusState {
+                    data: serde_json::to_vec(&consensus_state).unwrap(),
+                },
+                0,
+                slot,
+            )
+            .unwrap();
+            store_sync_committee_roots(&mut deps.storage, 0, slot, &previous_roots).unwrap();
+            crate::state::store_update_relayer(&mut deps.storage, 0, slot, &creator);
+        }
+
+        // Only the records of the current revision are reported
+        let res = crate::query::consensus_heights(
+            deps.as_ref(),
+            ConsensusHeightsMsg {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![Height {
+                revision_number: 1,
+                revision_height: trusted_slot,
+            }],
+            from_json::<ConsensusHeightsResult>(res).unwrap().heights
+        );
+        let status: StatusResult =
+            from_json(crate::query::status(deps.as_ref(), mock_env()).unwrap()).unwrap();
+        assert_eq!(
+            Some(Height {
+                revision_number: 1,
+                revision_height: trusted_slot,
+            }),
+            status.earliest_height
+        );
+        assert_eq!(
+            SyncCommitteeRoots::default(),
+            get_sync_committee_roots(&deps.storage, 1, trusted_slot).unwrap()
+        );
+        assert_eq!(
+            None,
+            crate::state::get_update_relayer(&deps.storage, 1, trusted_slot)
+        );
+
+        let header = generator.period_headers(trusted_slot, 3).remove(0);
+        let slot = header.consensus_update.finalized_header.beacon.slot;
+        let msg = UpdateStateMsg {
+            client_message: Binary::from(serde_json::to_vec(&header).unwrap()),
+        };
+        let res = super::update_state(deps.as_mut(), msg).unwrap();
+
+        // The records of the previous revision are pruned first
+        let pruned_heights = res
+            .events
+            .iter()
+            .find(|e| e.ty == EVENT_TYPE_CONSENSUS_STATES_PRUNED)
+            .expect("pruned event should be emitted")
+            .attributes
+            .iter()
+            .find(|a| a.key == ATTRIBUTE_KEY_PRUNED_HEIGHTS)
+            .unwrap();
+        assert!(pruned_heights
+            .value
+            .starts_with(&format!("0-{},0-{}", previous_slots[0], previous_slots[1])));
+        for slot in previous_slots {
+            crate::state::get_eth_consensus_state(&deps.storage, 0, slot).unwrap_err();
+            assert_eq!(
+                SyncCommitteeRoots::default(),
+                get_sync_committee_roots(&deps.storage, 0, slot).unwrap()
+            );
+            assert_eq!(
+                None,
+                crate::state::get_update_relayer(&deps.storage, 0, slot)
+            );
+        }
+        assert!(crate::state::get_eth_consensus_state(&deps.storage, 1, slot).is_ok());
+        assert_eq!(
+            vec![slot],
+            crate::state::get_consensus_heights(&deps.storage, 1, Some(trusted_slot), 10)
+        );
+    }
+
+    #[test]
+    fn test_migrate_client_store() {
+        let mut deps = mk_deps();
+        let generator = UpdateGenerator::new(16);
+        let subject_slot = generator.period_start_slot(1);
+        let substitute_slot = generator.period_start_slot(3);
+        let (mut subject_client_state, subject_consensus_state) =
+            generator.initial_states(subject_slot);
+        subject_client_state.is_frozen = true;
+        subject_client_state.min_slot_advance = Some(SLOTS_PER_EPOCH);
+        let (substitute_client_state, substitute_consensus_state) =
+            generator.initial_states(substitute_slot);
+        let substitute_roots = SyncCommitteeRoots {
+            current: Some(B256::repeat_byte(0x01)),
+            next: Some(B256::repeat_byte(0x02)),
+        };
+
+        let instantiate_msg = |client_state: &EthClientState,
+                               consensus_state: &EthConsensusState| {
+            crate::msg::InstantiateMsg {
+                client_state: Binary::from(serde_json::to_vec(client_state).unwrap()),
+                consensus_state: Binary::from(serde_json::to_vec(consensus_state).unwrap()),
+                checksum: b"checksum".into(),
+            }
+        };
+        let mut subject_store = ClientStore::subject(&mut deps.storage);
+        crate::instantiate::client(
+            &mut subject_store,
+            instantiate_msg(&subject_client_state, &subject_consensus_state),
+        )
+        .unwrap();
+        store_frozen_at(&mut subject_store, subject_slot);
+
+        // A substitute of another chain is rejected, and leaves the subject client untouched
+        let mut other_chain_client_state = substitute_client_state.clone();
+        other_chain_client_state.chain_id += 1;
+        crate::instantiate::client(
+            &mut ClientStore::substitute(&mut deps.storage),
+            instantiate_msg(&other_chain_client_state, &substitute_consensus_state),
+        )
+        .unwrap();
+        let err = super::migrate_client_store(deps.as_mut()).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::CheckSubstituteFailed(EthereumIBCError::SubstituteClientMismatch)
+        ));
+        assert_eq!(
+            crate::state::get_eth_client_state(&ClientStore::subject(&mut deps.storage)).unwrap(),
+            subject_client_state
+        );
+
+        let mut substitute_store = ClientStore::substitute(&mut deps.storage);
+        crate::instantiate::client(
+            &mut substitute_store,
+            instantiate_msg(&substitute_client_state, &substitute_consensus_state),
+        )
+        .unwrap();
+        store_sync_committee_roots(
+            &mut substitute_store,
+            substitute_client_state.revision_number,
+            substitute_slot,
+            &substitute_roots,
+        )
+        .unwrap();
+
+        super::migrate_client_store(deps.as_mut()).unwrap();
+
+        let subject_store = ClientStore::subject(&mut deps.storage);
+        let recovered_client_state = crate::state::get_eth_client_state(&subject_store).unwrap();
+        assert!(!recovered_client_state.is_frozen);
+        assert_eq!(substitute_slot, recovered_client_state.latest_slot);
+        // The parameters chosen by the subject client are kept
+        assert_eq!(
+            Some(SLOTS_PER_EPOCH),
+            recovered_client_state.min_slot_advance
+        );
+        let wasm_client_state = crate::state::get_wasm_client_state(&subject_store).unwrap();
+        assert_eq!(b"checksum".to_vec(), wasm_client_state.checksum);
+        assert_eq!(
+            substitute_slot,
+            wasm_client_state.latest_height.unwrap().revision_height
+        );
+        assert_eq!(
+            substitute_consensus_state,
+            crate::state::get_eth_consensus_state(
+                &subject_store,
+                subject_client_state.revision_number,
+                substitute_slot
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            substitute_roots,
+            get_sync_committee_roots(
+                &subject_store,
+                recovered_client_state.revision_number,
+                substitute_slot
+            )
+            .unwrap()
+        );
+        assert_eq!(None, get_frozen_at(&subject_store));
+        // The consensus states of the subject client are kept
+        assert_eq!(
+            subject_consensus_state,
+            crate::state::get_eth_consensus_state(
+                &subject_store,
+                subject_client_state.revision_number,
+                subject_slot
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            vec![subject_slot, substitute_slot],
+            crate::state::get_consensus_heights(
+                &subject_store,
+                recovered_client_state.revision_number,
+                None,
+                10
+            )
+        );
     }
 }
@@ -0,0 +1,134 @@
+//! Tests that the queries of `cw-ics08-wasm-eth-client` are understood by the contract.
+//!
+//! The queries are encoded by the client crate and decoded by the contract, and the results the
+//! other way around, as they would be when another contract queries the light client.
+
+use alloy_primitives::U256;
+use cosmwasm_std::{
+    coins, from_json,
+    testing::{message_info, mock_env},
+    to_json_vec, Binary, Deps,
+};
+use cw_ics08_wasm_eth_client::msg::{
+    self as client_msg, LatestHeightResult, StatusResult, VerifyMembershipAtMsg,
+    VerifyMembershipMsg, VerifyNonMembershipMsg,
+};
+use ethereum_light_client::{
+    membership::evm_ics26_commitment_path,
+    test_utils::{storage_trie::storage_proof_corpus, update_generator::UpdateGenerator},
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    contract::{instantiate, query},
+    custom_query::EthereumCustomQuery,
+    msg::{InstantiateMsg, QueryMsg},
+    test::mk_deps,
+    ContractError,
+};
+
+/// Sends a query of the client crate to the contract, and decodes the result with the client
+/// crate.
+fn query_as<T: DeserializeOwned>(
+    deps: Deps<EthereumCustomQuery>,
+    msg: &client_msg::QueryMsg,
+) -> Result<T, ContractError> {
+    let msg: QueryMsg = from_json(to_json_vec(msg).unwrap()).unwrap();
+    let result = query(deps, mock_env(), msg)?;
+    Ok(from_json(result).unwrap())
+}
+
+#[test]
+fn test_cross_contract_queries() {
+    let mut deps = mk_deps();
+    let creator = deps.api.addr_make("creator");
+    let info = message_info(&creator, &coins(1, "uatom"));
+
+    let generator = UpdateGenerator::new(16);
+    let slot = generator.period_start_slot(1);
+    let path = b"oracle/commitments".to_vec();
+    let case = storage_proof_corpus(evm_ics26_commitment_path(
+        &path,
+        generator.client_state().ibc_commitment_slot,
+    ))
+    .into_iter()
+    .find(|case| case.is_member)
+    .unwrap();
+
+    let (client_state, mut consensus_state) = generator.initial_states(slot);
+    consensus_state.storage_root = case.storage_root;
+    let msg = InstantiateMsg {
+        client_state: Binary::from(serde_json::to_vec(&client_state).unwrap()),
+        consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+        checksum: b"checksum".into(),
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let status: StatusResult = query_as(
+        deps.as_ref(),
+        &client_msg::QueryMsg::Status(client_msg::StatusMsg {}),
+    )
+    .unwrap();
+    assert!(status.is_active());
+    assert_eq!(status.latest_slot, slot);
+
+    let latest_height: LatestHeightResult = query_as(
+        deps.as_ref(),
+        &client_msg::QueryMsg::LatestHeight(client_msg::LatestHeightMsg {}),
+    )
+    .unwrap();
+    let height = client_msg::Height {
+        revision_number: client_state.revision_number,
+        revision_height: slot,
+    };
+    assert_eq!(latest_height.height, height);
+    assert_eq!(
+        latest_height.timestamp,
+        consensus_state.timestamp * 1_000_000_000
+    );
+
+    let proof = Binary::from(serde_json::to_vec(&case.storage_proof).unwrap());
+    let merkle_path = client_msg::MerklePath {
+        key_path: vec![path.into()],
+    };
+    let verify_membership = |value: U256| {
+        query_as::<()>(
+            deps.as_ref(),
+            &client_msg::QueryMsg::VerifyMembership(VerifyMembershipMsg {
+                height,
+                proof: proof.clone(),
+                merkle_path: merkle_path.clone(),
+                value: value.to_be_bytes_vec().into(),
+            }),
+        )
+    };
+    verify_membership(case.storage_proof.value).unwrap();
+    assert!(matches!(
+        verify_membership(case.storage_proof.value + U256::from(1)),
+        Err(ContractError::VerifyMembershipFailed(_))
+    ));
+
+    // The key is stored, so it has no non-membership proof
+    assert!(matches!(
+        query_as::<()>(
+            deps.as_ref(),
+            &client_msg::QueryMsg::VerifyNonMembership(VerifyNonMembershipMsg {
+                height,
+                proof: proof.clone(),
+                merkle_path: merkle_path.clone(),
+            }),
+        ),
+        Err(ContractError::VerifyNonMembershipFailed(_))
+    ));
+
+    query_as::<()>(
+        deps.as_ref(),
+        &client_msg::QueryMsg::VerifyMembershipAt(VerifyMembershipAtMsg {
+            consensus_state: Binary::from(serde_json::to_vec(&consensus_state).unwrap()),
+            proof,
+            merkle_path,
+            value: case.storage_proof.value.to_be_bytes_vec().into(),
+        }),
+    )
+    .unwrap();
+}
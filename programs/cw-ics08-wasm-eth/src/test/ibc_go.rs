@@ -0,0 +1,383 @@
+//! A harness that drives the contract the way the 08-wasm keeper of ibc-go does.
+//!
+//! The messages are the exact JSON that ibc-go marshals with `encoding/json` (snake case keys,
+//! base64 bytes, `omitempty` fields left out), and the results are returned as raw JSON so that
+//! the tests assert on the keys ibc-go unmarshals. A change of the message schema on either side
+//! makes these tests fail, instead of the first `MsgUpdateClient` on a live chain.
+
+use cosmwasm_std::{
+    coins, from_json,
+    testing::{message_info, mock_env, MockApi, MockQuerier, MockStorage},
+    Binary, Env, OwnedDeps,
+};
+use serde_json::Value;
+
+use crate::{
+    contract::{instantiate, query, sudo},
+    custom_query::EthereumCustomQuery,
+    msg::{InstantiateMsg, QueryMsg, SudoMsg},
+    test::mk_deps,
+    ContractError,
+};
+
+/// The status query of ibc-go.
+pub const STATUS_MSG: &str = r#"{"status":{}}"#;
+/// The migrate client store message of ibc-go.
+pub const MIGRATE_CLIENT_STORE_MSG: &str = r#"{"migrate_client_store":{}}"#;
+
+/// Encodes bytes the way `encoding/json` encodes a `[]byte`.
+fn go_bytes(bz: &[u8]) -> String {
+    format!(r#""{}""#, Binary::from(bz).to_base64())
+}
+
+/// Encodes a `clienttypes.Height`, whose fields are both `omitempty`.
+fn go_height(revision_number: u64, revision_height: u64) -> String {
+    let fields = [
+        ("revision_number", revision_number),
+        ("revision_height", revision_height),
+    ]
+    .into_iter()
+    .filter(|(_, value)| *value != 0)
+    .map(|(key, value)| format!(r#""{key}":{value}"#))
+    .collect::<Vec<_>>();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Encodes a `commitmenttypesv2.MerklePath`, whose key path is `omitempty`.
+fn go_merkle_path(key_path: &[&[u8]]) -> String {
+    if key_path.is_empty() {
+        return "{}".to_string();
+    }
+    let key_path = key_path.iter().map(|key| go_bytes(key)).collect::<Vec<_>>();
+    format!(r#"{{"key_path":[{}]}}"#, key_path.join(","))
+}
+
+/// The instantiate message of ibc-go.
+pub fn instantiate_msg(client_state: &[u8], consensus_state: &[u8], checksum: &[u8]) -> String {
+    format!(
+        r#"{{"client_state":{},"consensus_state":{},"checksum":{}}}"#,
+        go_bytes(client_state),
+        go_bytes(consensus_state),
+        go_bytes(checksum)
+    )
+}
+
+/// The update state sudo message of ibc-go.
+pub fn update_state_msg(client_message: &[u8]) -> String {
+    format!(
+        r#"{{"update_state":{{"client_message":{}}}}}"#,
+        go_bytes(client_message)
+    )
+}
+
+/// The update state on misbehaviour sudo message of ibc-go.
+pub fn update_state_on_misbehaviour_msg(client_message: &[u8]) -> String {
+    format!(
+        r#"{{"update_state_on_misbehaviour":{{"client_message":{}}}}}"#,
+        go_bytes(client_message)
+    )
+}
+
+/// The verify membership sudo message of ibc-go.
+pub fn verify_membership_msg(
+    revision_number: u64,
+    revision_height: u64,
+    proof: &[u8],
+    key_path: &[&[u8]],
+    value: &[u8],
+) -> String {
+    format!(
+        r#"{{"verify_membership":{{"height":{},"delay_time_period":0,"delay_block_period":0,"proof":{},"merkle_path":{},"value":{}}}}}"#,
+        go_height(revision_number, revision_height),
+        go_bytes(proof),
+        go_merkle_path(key_path),
+        go_bytes(value)
+    )
+}
+
+/// The verify non-membership sudo message of ibc-go.
+pub fn verify_non_membership_msg(
+    revision_number: u64,
+    revision_height: u64,
+    proof: &[u8],
+    key_path: &[&[u8]],
+) -> String {
+    format!(
+        r#"{{"verify_non_membership":{{"height":{},"delay_time_period":0,"delay_block_period":0,"proof":{},"merkle_path":{}}}}}"#,
+        go_height(revision_number, revision_height),
+        go_bytes(proof),
+        go_merkle_path(key_path)
+    )
+}
+
+/// The verify upgrade and update state sudo message of ibc-go.
+pub fn verify_upgrade_and_update_state_msg(
+    upgrade_client_state: &[u8],
+    upgrade_consensus_state: &[u8],
+    proof_upgrade_client: &[u8],
+    proof_upgrade_consensus_state: &[u8],
+) -> String {
+    format!(
+        r#"{{"verify_upgrade_and_update_state":{{"upgrade_client_state":{},"upgrade_consensus_state":{},"proof_upgrade_client":{},"proof_upgrade_consensus_state":{}}}}}"#,
+        go_bytes(upgrade_client_state),
+        go_bytes(upgrade_consensus_state),
+        go_bytes(proof_upgrade_client),
+        go_bytes(proof_upgrade_consensus_state)
+    )
+}
+
+/// The verify client message query of ibc-go.
+pub fn verify_client_message_msg(client_message: &[u8]) -> String {
+    format!(
+        r#"{{"verify_client_message":{{"client_message":{}}}}}"#,
+        go_bytes(client_message)
+    )
+}
+
+/// The check for misbehaviour query of ibc-go.
+pub fn check_for_misbehaviour_msg(client_message: &[u8]) -> String {
+    format!(
+        r#"{{"check_for_misbehaviour":{{"client_message":{}}}}}"#,
+        go_bytes(client_message)
+    )
+}
+
+/// The timestamp at height query of ibc-go.
+pub fn timestamp_at_height_msg(revision_number: u64, revision_height: u64) -> String {
+    format!(
+        r#"{{"timestamp_at_height":{{"height":{}}}}}"#,
+        go_height(revision_number, revision_height)
+    )
+}
+
+/// The contract behind an 08-wasm client, called with the JSON of ibc-go.
+pub struct IbcGoKeeper {
+    pub deps:
+        OwnedDeps<MockStorage, MockApi, MockQuerier<EthereumCustomQuery>, EthereumCustomQuery>,
+    pub env: Env,
+}
+
+impl IbcGoKeeper {
+    /// Creates the client with the instantiate message of ibc-go.
+    pub fn instantiate(msg: &str) -> Self {
+        let mut deps = mk_deps();
+        let creator = deps.api.addr_make("creator");
+        let info = message_info(&creator, &coins(1, "uatom"));
+
+        let msg: InstantiateMsg =
+            from_json(msg).expect("instantiate message of ibc-go should be deserializable");
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        Self {
+            deps,
+            env: mock_env(),
+        }
+    }
+
+    /// Calls the sudo entry point with a message of ibc-go, and returns the JSON data of the
+    /// response, if any.
+    pub fn sudo(&mut self, msg: &str) -> Result<Option<Value>, ContractError> {
+        let msg: SudoMsg = from_json(msg).expect("sudo message of ibc-go should be deserializable");
+        let res = sudo(self.deps.as_mut(), self.env.clone(), msg)?;
+
+        Ok(res
+            .data
+            .filter(|data| !data.is_empty())
+            .map(|data| serde_json::from_slice(&data).expect("sudo result should be JSON")))
+    }
+
+    /// Calls the query entry point with a message of ibc-go, and returns the JSON result, which is
+    /// `null` for the queries that only return an error or an empty response.
+    pub fn query(&self, msg: &str) -> Result<Value, ContractError> {
+        let msg: QueryMsg =
+            from_json(msg).expect("query message of ibc-go should be deserializable");
+        let res = query(self.deps.as_ref(), self.env.clone(), msg)?;
+
+        if res.is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_slice(&res).expect("query result should be JSON"))
+    }
+}
+
+mod tests {
+    use cosmwasm_std::{from_json, Binary, Timestamp};
+    use ethereum_light_client::{
+        header::Header,
+        test_utils::fixtures::{
+            self, get_packet_proof, InitialState, RelayerMessages, StepsFixture,
+        },
+    };
+    use ibc_proto::ibc::lightclients::wasm::v1::ClientMessage;
+    use prost::Message;
+    use serde_json::json;
+
+    use crate::msg::{
+        Height, MerklePath, QueryMsg, SudoMsg, TimestampAtHeightMsg, UpdateStateMsg,
+        VerifyMembershipMsg, VerifyNonMembershipMsg,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_ibc_go_sudo_msgs() {
+        assert_eq!(
+            SudoMsg::UpdateState(UpdateStateMsg {
+                client_message: Binary::from(b"header"),
+            }),
+            from_json(update_state_msg(b"header")).unwrap()
+        );
+        assert!(matches!(
+            from_json(update_state_on_misbehaviour_msg(b"misbehaviour")).unwrap(),
+            SudoMsg::UpdateStateOnMisbehaviour(msg) if msg.client_message == b"misbehaviour"
+        ));
+        assert!(matches!(
+            from_json(verify_upgrade_and_update_state_msg(b"cs", b"cons", b"p1", b"p2")).unwrap(),
+            SudoMsg::VerifyUpgradeAndUpdateState(msg)
+                if msg.upgrade_client_state == b"cs" && msg.proof_upgrade_consensus_state == b"p2"
+        ));
+        assert!(matches!(
+            from_json(MIGRATE_CLIENT_STORE_MSG).unwrap(),
+            SudoMsg::MigrateClientStore(_)
+        ));
+
+        // ibc-go leaves out the revision number of the ethereum client when it is 0
+        assert_eq!(
+            SudoMsg::VerifyMembership(VerifyMembershipMsg {
+                height: Height {
+                    revision_number: 0,
+                    revision_height: 42,
+                },
+                delay_time_period: 0,
+                delay_block_period: 0,
+                proof: Binary::from(b"proof"),
+                merkle_path: MerklePath {
+                    key_path: vec![Binary::from(b"path")],
+                },
+                value: Binary::from(b"value"),
+            }),
+            from_json(verify_membership_msg(
+                0,
+                42,
+                b"proof",
+                &[b"path".as_slice()],
+                b"value"
+            ))
+            .unwrap()
+        );
+        assert_eq!(
+            SudoMsg::VerifyNonMembership(VerifyNonMembershipMsg {
+                height: Height {
+                    revision_number: 1,
+                    revision_height: 42,
+                },
+                delay_time_period: 0,
+                delay_block_period: 0,
+                proof: Binary::from(b"proof"),
+                merkle_path: MerklePath {
+                    key_path: vec![Binary::from(b"path")],
+                },
+            }),
+            from_json(verify_non_membership_msg(
+                1,
+                42,
+                b"proof",
+                &[b"path".as_slice()]
+            ))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ibc_go_query_msgs() {
+        assert!(matches!(
+            from_json(STATUS_MSG).unwrap(),
+            QueryMsg::Status(_)
+        ));
+        assert_eq!(
+            QueryMsg::TimestampAtHeight(TimestampAtHeightMsg {
+                height: Height {
+                    revision_number: 0,
+                    revision_height: 42,
+                },
+            }),
+            from_json(timestamp_at_height_msg(0, 42)).unwrap()
+        );
+        assert!(matches!(
+            from_json(verify_client_message_msg(b"header")).unwrap(),
+            QueryMsg::VerifyClientMessage(msg) if msg.client_message == b"header"
+        ));
+        assert!(matches!(
+            from_json(check_for_misbehaviour_msg(b"misbehaviour")).unwrap(),
+            QueryMsg::CheckForMisbehaviour(msg) if msg.client_message == b"misbehaviour"
+        ));
+    }
+
+    #[test]
+    // This test runs the 08-wasm keeper calls of the e2e test scenario:
+    // TestICS20TransferERC20TokenfromEthereumToCosmosAndBack_Groth16
+    fn test_ibc_go_keeper_flow() {
+        let fixture: StepsFixture =
+            fixtures::load("TestICS20TransferERC20TokenfromEthereumToCosmosAndBack_Groth16");
+
+        let initial_state: InitialState = fixture.get_data_at_step(0);
+        let client_state_bz = serde_json::to_vec(&initial_state.client_state).unwrap();
+        let consensus_state_bz = serde_json::to_vec(&initial_state.consensus_state).unwrap();
+
+        let mut keeper = IbcGoKeeper::instantiate(&instantiate_msg(
+            &client_state_bz,
+            &consensus_state_bz,
+            b"checksum",
+        ));
+
+        // ibc-go only reads the status
+        assert_eq!(json!("Active"), keeper.query(STATUS_MSG).unwrap()["status"]);
+        assert_eq!(
+            json!({ "timestamp": initial_state.consensus_state.timestamp * 1_000_000_000 }),
+            keeper
+                .query(&timestamp_at_height_msg(
+                    0,
+                    initial_state.consensus_state.slot
+                ))
+                .unwrap()
+        );
+
+        let relayer_messages: RelayerMessages = fixture.get_data_at_step(1);
+        let (update_client_msgs, recv_msgs, _) = relayer_messages.get_sdk_msgs();
+        assert_eq!(1, recv_msgs.len());
+
+        for msg in update_client_msgs {
+            let header_bz = ClientMessage::decode(msg.client_message.unwrap().value.as_slice())
+                .unwrap()
+                .data;
+            let header: Header = serde_json::from_slice(&header_bz).unwrap();
+            keeper.env.block.time = Timestamp::from_seconds(
+                header.consensus_update.attested_header.execution.timestamp + 1000,
+            );
+
+            assert_eq!(
+                Value::Null,
+                keeper
+                    .query(&verify_client_message_msg(&header_bz))
+                    .unwrap()
+            );
+            // ibc-go decodes the heights of the update state result
+            let result = keeper.sudo(&update_state_msg(&header_bz)).unwrap().unwrap();
+            assert_eq!(
+                json!(header.consensus_update.finalized_header.beacon.slot),
+                result["heights"][0]["revision_height"]
+            );
+        }
+
+        let (path, value) = get_packet_proof(recv_msgs[0].packet.clone().unwrap());
+        keeper
+            .sudo(&verify_membership_msg(
+                0,
+                recv_msgs[0].proof_height.unwrap().revision_height,
+                &recv_msgs[0].proof_commitment,
+                &[path.as_slice()],
+                &value,
+            ))
+            .unwrap();
+    }
+}
@@ -1,3 +1,6 @@
+pub mod cross_contract;
+pub mod ibc_go;
+
 use std::marker::PhantomData;
 
 use alloy_primitives::B256;
@@ -3,7 +3,7 @@ use sp1_ics07_tendermint_operator::{
     cli::command::{fixtures, Commands, OperatorCli},
     runners::{
         self,
-        fixtures::{membership, misbehaviour, uc_and_mem, update_client},
+        fixtures::{diff, membership, misbehaviour, regenerate, uc_and_mem, update_client},
     },
 };
 use sp1_sdk::utils::setup_logger;
@@ -29,6 +29,9 @@ async fn main() -> anyhow::Result<()> {
             fixtures::Cmds::Membership(args) => membership::run(args).await,
             fixtures::Cmds::UpdateClientAndMembership(args) => uc_and_mem::run(args).await,
             fixtures::Cmds::Misbehaviour(args) => misbehaviour::run(args).await,
+            fixtures::Cmds::Diff(args) => diff::run(&args),
+            fixtures::Cmds::Regenerate(args) => regenerate::run(&args),
         },
+        Commands::Vkeys(args) => runners::vkeys::run(args).await,
     }
 }
@@ -23,6 +23,8 @@ pub enum Commands {
     Genesis(genesis::Args),
     /// The subcommand to produce the fixtures for testing.
     Fixtures(fixtures::Cmd),
+    /// The subcommand to report the program verifying keys and check them against the contracts.
+    Vkeys(vkeys::Args),
 }
 
 /// The trust options for client operations.
@@ -81,6 +83,33 @@ pub mod genesis {
     }
 }
 
+/// The cli interface for the vkeys command.
+pub mod vkeys {
+    use super::Parser;
+    use alloy::primitives::Address;
+
+    /// The arguments for the `vkeys` executable.
+    #[derive(Parser, Clone)]
+    pub struct Args {
+        /// The addresses of the `SP1ICS07Tendermint` contracts to check the programs against.
+        /// The contracts are read from the RPC of the `RPC_URL` environment variable.
+        #[clap(long, env = "CONTRACT_ADDRESS", value_delimiter = ',')]
+        pub contracts: Vec<Address>,
+
+        /// The expected address of the SP1 verifier of the contracts.
+        #[clap(long)]
+        pub verifier: Option<Address>,
+
+        /// Report path. If not provided, the output will be written to stdout.
+        #[clap(long, short = 'o', value_parser = super::parse_output_path, default_value = "-")]
+        pub output_path: super::OutputPath,
+
+        /// The paths to the ELF files.
+        #[clap(flatten)]
+        pub elf_paths: super::SP1ELFPaths,
+    }
+}
+
 /// The cli interface for the operator.
 pub mod operator {
     use super::Parser;
@@ -130,6 +159,42 @@ pub mod fixtures {
         UpdateClientAndMembership(UpdateClientAndMembershipCmd),
         /// The subcommand to generate the misbehaviour fixtures.
         Misbehaviour(MisbehaviourCmd),
+        /// The subcommand to compare two Ethereum light client fixtures.
+        Diff(DiffCmd),
+        /// The subcommand to regenerate the Ethereum light client fixtures from their raw inputs.
+        Regenerate(RegenerateCmd),
+    }
+
+    /// The arguments for the `Regenerate` fixture executable.
+    #[derive(Parser, Clone)]
+    #[command(about = "Regenerate the Ethereum light client fixtures from their raw inputs")]
+    pub struct RegenerateCmd {
+        /// The directory of the fixtures, whose raw inputs are in its `raw` subdirectory.
+        #[clap(
+            long,
+            default_value = "packages/ethereum/light-client/src/test_utils/fixtures"
+        )]
+        pub fixtures_dir: String,
+
+        /// Record the raw inputs of the fixtures that have none yet from the fixtures themselves,
+        /// which must still be readable with the current light client types.
+        #[clap(long)]
+        pub record: bool,
+
+        /// Only report the fixtures that would change, without writing them.
+        #[clap(long)]
+        pub check: bool,
+    }
+
+    /// The arguments for the `Diff` fixture executable.
+    #[derive(Parser, Clone)]
+    #[command(about = "Compare the steps of two Ethereum light client fixtures")]
+    pub struct DiffCmd {
+        /// The path to the old fixture.
+        pub old: String,
+
+        /// The path to the new fixture.
+        pub new: String,
     }
 
     /// The arguments for the `UpdateClient` fixture executable.
@@ -0,0 +1,32 @@
+//! Runner for comparing Ethereum light client fixtures
+
+use std::path::Path;
+
+use ethereum_light_client::test_utils::{fixture_diff::diff_fixtures, fixtures::StepsFixture};
+
+use crate::cli::command::fixtures::DiffCmd;
+
+/// Reads a fixture and upgrades it to the current schema version.
+fn read_fixture(path: &Path) -> anyhow::Result<StepsFixture> {
+    let fixture: StepsFixture = serde_json::from_slice(&std::fs::read(path)?)?;
+    fixture
+        .upgrade()
+        .map_err(|e| anyhow::anyhow!("incompatible fixture {}: {e}", path.display()))
+}
+
+/// Prints the semantic differences between two fixtures, step by step.
+#[allow(clippy::missing_errors_doc)]
+pub fn run(args: &DiffCmd) -> anyhow::Result<()> {
+    let old = read_fixture(Path::new(&args.old))?;
+    let new = read_fixture(Path::new(&args.new))?;
+
+    let diffs = diff_fixtures(&old, &new).map_err(|e| anyhow::anyhow!(e))?;
+    if diffs.is_empty() {
+        println!("The fixtures are equivalent.");
+    }
+    for diff in diffs {
+        println!("{diff}");
+    }
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
 //! Runners for generating fixtures for testing of the programs.
 
+pub mod diff;
 pub mod membership;
 pub mod misbehaviour;
+pub mod regenerate;
 pub mod uc_and_mem;
 pub mod update_client;
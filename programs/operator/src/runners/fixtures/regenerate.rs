@@ -0,0 +1,97 @@
+//! Runner for regenerating the Ethereum light client fixtures from their raw inputs
+
+use std::path::{Path, PathBuf};
+
+use ethereum_light_client::test_utils::{
+    fixture_diff::diff_fixtures,
+    fixtures::StepsFixture,
+    raw_inputs::{raw_inputs_path, RawFixtureInputs},
+};
+use serde::Serialize;
+
+use crate::cli::command::fixtures::RegenerateCmd;
+
+/// Regenerates every fixture of the fixtures directory from its raw inputs, and prints the
+/// fixtures that changed.
+#[allow(clippy::missing_errors_doc)]
+pub fn run(args: &RegenerateCmd) -> anyhow::Result<()> {
+    let mut fixture_paths = std::fs::read_dir(&args.fixtures_dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<std::io::Result<Vec<PathBuf>>>()?;
+    fixture_paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    fixture_paths.sort();
+
+    let mut changed = 0;
+    let mut missing = Vec::new();
+    for fixture_path in &fixture_paths {
+        let raw_path = raw_inputs_path(fixture_path);
+        let raw_inputs = if raw_path.exists() {
+            serde_json::from_slice::<RawFixtureInputs>(&std::fs::read(&raw_path)?)?
+        } else if args.record {
+            let raw_inputs = RawFixtureInputs::record(&read_fixture(fixture_path)?)
+                .map_err(|e| anyhow::anyhow!("{}: {e}", fixture_path.display()))?;
+            std::fs::create_dir_all(raw_path.parent().unwrap_or_else(|| Path::new(".")))?;
+            std::fs::write(&raw_path, to_json(&raw_inputs)?)?;
+            println!("Recorded the raw inputs of {}", fixture_path.display());
+            raw_inputs
+        } else {
+            missing.push(fixture_path.display().to_string());
+            continue;
+        };
+
+        let regenerated = raw_inputs
+            .regenerate()
+            .map_err(|e| anyhow::anyhow!("{}: {e}", raw_path.display()))?;
+        // A fixture that no longer decodes with the current types is the reason to regenerate it
+        let diffs = match read_fixture(fixture_path) {
+            Ok(committed) if committed == regenerated => continue,
+            Ok(committed) => diff_fixtures(&committed, &regenerated)
+                .unwrap_or_else(|e| vec![format!("the committed fixture does not decode: {e}")]),
+            Err(e) => vec![format!("the committed fixture does not decode: {e}")],
+        };
+
+        changed += 1;
+        println!("{} changed:", fixture_path.display());
+        for diff in diffs {
+            println!("  {diff}");
+        }
+        if !args.check {
+            std::fs::write(fixture_path, to_json(&regenerated)?)?;
+        }
+    }
+
+    if !missing.is_empty() {
+        println!(
+            "No raw inputs for {}, run with --record to record them",
+            missing.join(", ")
+        );
+    }
+    anyhow::ensure!(
+        !args.check || changed == 0,
+        "{changed} fixtures are not up to date with their raw inputs"
+    );
+    println!(
+        "{} fixtures regenerated, {changed} changed.",
+        fixture_paths.len() - missing.len()
+    );
+
+    Ok(())
+}
+
+/// Reads a fixture and upgrades it to the current schema version.
+fn read_fixture(path: &Path) -> anyhow::Result<StepsFixture> {
+    let fixture: StepsFixture = serde_json::from_slice(&std::fs::read(path)?)?;
+    fixture
+        .upgrade()
+        .map_err(|e| anyhow::anyhow!("incompatible fixture {}: {e}", path.display()))
+}
+
+/// Encodes a value as JSON indented like the fixtures written by the e2e tests.
+fn to_json(value: &impl Serialize) -> anyhow::Result<Vec<u8>> {
+    let mut json = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    value.serialize(&mut serde_json::Serializer::with_formatter(
+        &mut json, formatter,
+    ))?;
+    Ok(json)
+}
@@ -3,3 +3,4 @@
 pub mod fixtures;
 pub mod genesis;
 pub mod operator;
+pub mod vkeys;
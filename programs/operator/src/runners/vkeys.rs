@@ -0,0 +1,194 @@
+//! Contains the runner for the vkeys command.
+
+use crate::cli::command::{vkeys::Args, OutputPath};
+use alloy::{
+    primitives::{keccak256, Address, B256},
+    providers::ProviderBuilder,
+};
+use ibc_eureka_solidity_types::sp1_ics07::sp1_ics07_tendermint;
+use sp1_ics07_tendermint_prover::programs::{
+    MembershipProgram, MisbehaviourProgram, SP1Program, UpdateClientAndMembershipProgram,
+    UpdateClientProgram,
+};
+use sp1_sdk::HashableKey;
+use std::{env, path::PathBuf};
+
+/// The fingerprint of an SP1 program.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ProgramFingerprint {
+    /// The verifying key of the program, as committed to by the contract.
+    pub vkey: B256,
+    /// The keccak256 hash of the ELF of the program.
+    pub elf_hash: B256,
+}
+
+/// The fingerprints of the SP1 ICS07 Tendermint programs.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ProgramFingerprints {
+    /// The fingerprint of [`UpdateClientProgram`].
+    pub update_client: ProgramFingerprint,
+    /// The fingerprint of [`MembershipProgram`].
+    pub membership: ProgramFingerprint,
+    /// The fingerprint of [`UpdateClientAndMembershipProgram`].
+    pub uc_and_membership: ProgramFingerprint,
+    /// The fingerprint of [`MisbehaviourProgram`].
+    pub misbehaviour: ProgramFingerprint,
+}
+
+/// The on-chain configuration of an `SP1ICS07Tendermint` contract.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ContractConfig {
+    /// The address of the contract.
+    pub address: Address,
+    /// The address of the SP1 verifier of the contract.
+    pub verifier: Address,
+    /// The verifying key of the update client program.
+    pub update_client_vkey: B256,
+    /// The verifying key of the membership program.
+    pub membership_vkey: B256,
+    /// The verifying key of the update client and membership program.
+    pub uc_and_membership_vkey: B256,
+    /// The verifying key of the misbehaviour program.
+    pub misbehaviour_vkey: B256,
+    /// The mismatches with the local programs and the expected verifier.
+    pub mismatches: Vec<String>,
+}
+
+/// The report of the vkeys command.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct VkeysReport {
+    /// The fingerprints of the local programs.
+    pub programs: ProgramFingerprints,
+    /// The on-chain configuration of the contracts.
+    pub contracts: Vec<ContractConfig>,
+}
+
+impl ProgramFingerprint {
+    /// Computes the fingerprint of a program.
+    /// # Errors
+    /// Returns an error if the verifying key is not a valid hex encoded hash.
+    pub fn of(program: &impl SP1Program) -> anyhow::Result<Self> {
+        Ok(Self {
+            vkey: program.get_vkey().bytes32().parse()?,
+            elf_hash: keccak256(program.elf()),
+        })
+    }
+}
+
+impl ContractConfig {
+    /// Records the mismatches of the contract with the local programs and the expected verifier.
+    pub fn check(&mut self, programs: &ProgramFingerprints, expected_verifier: Option<Address>) {
+        let vkeys = [
+            (
+                "update client",
+                self.update_client_vkey,
+                &programs.update_client,
+            ),
+            ("membership", self.membership_vkey, &programs.membership),
+            (
+                "update client and membership",
+                self.uc_and_membership_vkey,
+                &programs.uc_and_membership,
+            ),
+            (
+                "misbehaviour",
+                self.misbehaviour_vkey,
+                &programs.misbehaviour,
+            ),
+        ];
+        self.mismatches = vkeys
+            .into_iter()
+            .filter(|(_, on_chain, program)| *on_chain != program.vkey)
+            .map(|(name, on_chain, program)| {
+                format!(
+                    "{name} program vkey is {on_chain} on-chain, but {} locally",
+                    program.vkey
+                )
+            })
+            .collect();
+
+        if let Some(expected_verifier) = expected_verifier {
+            if self.verifier != expected_verifier {
+                self.mismatches.push(format!(
+                    "verifier is {} on-chain, but {expected_verifier} is expected",
+                    self.verifier
+                ));
+            }
+        }
+    }
+}
+
+/// Reports the verifying keys and the ELF hashes of the programs, along with the configuration of
+/// the contracts, and fails if a contract does not match the programs or the expected verifier.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run(args: Args) -> anyhow::Result<()> {
+    let programs = ProgramFingerprints {
+        update_client: ProgramFingerprint::of(&UpdateClientProgram::new(std::fs::read(
+            args.elf_paths.update_client_path,
+        )?))?,
+        membership: ProgramFingerprint::of(&MembershipProgram::new(std::fs::read(
+            args.elf_paths.membership_path,
+        )?))?,
+        uc_and_membership: ProgramFingerprint::of(&UpdateClientAndMembershipProgram::new(
+            std::fs::read(args.elf_paths.uc_and_membership_path)?,
+        ))?,
+        misbehaviour: ProgramFingerprint::of(&MisbehaviourProgram::new(std::fs::read(
+            args.elf_paths.misbehaviour_path,
+        )?))?,
+    };
+
+    let mut contracts = Vec::with_capacity(args.contracts.len());
+    if !args.contracts.is_empty() {
+        let rpc_url = env::var("RPC_URL").expect("RPC_URL not set");
+        let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
+
+        for address in args.contracts {
+            let contract = sp1_ics07_tendermint::new(address, provider.clone());
+            let mut config = ContractConfig {
+                address,
+                verifier: contract.VERIFIER().call().await?._0,
+                update_client_vkey: contract.UPDATE_CLIENT_PROGRAM_VKEY().call().await?._0,
+                membership_vkey: contract.MEMBERSHIP_PROGRAM_VKEY().call().await?._0,
+                uc_and_membership_vkey: contract
+                    .UPDATE_CLIENT_AND_MEMBERSHIP_PROGRAM_VKEY()
+                    .call()
+                    .await?
+                    ._0,
+                misbehaviour_vkey: contract.MISBEHAVIOUR_PROGRAM_VKEY().call().await?._0,
+                mismatches: vec![],
+            };
+            config.check(&programs, args.verifier);
+            contracts.push(config);
+        }
+    }
+
+    let report = VkeysReport {
+        programs,
+        contracts,
+    };
+    match args.output_path {
+        OutputPath::File(path) => {
+            std::fs::write(PathBuf::from(path), serde_json::to_string_pretty(&report)?)?;
+        }
+        OutputPath::Stdout => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+
+    let mismatched = report
+        .contracts
+        .iter()
+        .filter(|contract| !contract.mismatches.is_empty())
+        .inspect(|contract| {
+            for mismatch in &contract.mismatches {
+                tracing::error!(contract = %contract.address, "{mismatch}");
+            }
+        })
+        .count();
+    anyhow::ensure!(
+        mismatched == 0,
+        "{mismatched} contract(s) do not match the local programs, proofs would be rejected"
+    );
+
+    Ok(())
+}
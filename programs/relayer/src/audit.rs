@@ -0,0 +1,432 @@
+//! Defines the escrow audit run by `relayer audit-escrow`, the solvency check of an IBC Eureka
+//! route between Ethereum and a Cosmos SDK chain.
+//!
+//! Every token bridged over the route is either escrowed on its native chain and minted as a
+//! voucher on the other one, or burned when it returns. The tokens escrowed on one side must
+//! therefore back all the vouchers of the other side:
+//! - the ERC20 tokens held by the escrow contract of the `transfer` app for the client of the
+//!   Cosmos SDK chain back the `ibc/` vouchers minted on the Cosmos SDK chain,
+//! - the coins held by the escrow account of the `transfer` module for the Ethereum light client
+//!   back the `IBCERC20` vouchers minted on Ethereum.
+//!
+//! An escrow holding less than the supply of its vouchers is a deficit, and means that the route
+//! is insolvent. An escrow holding more is a surplus, usually the tokens sent to the escrow
+//! directly, and is only reported.
+
+use std::{fmt, str::FromStr};
+
+use alloy::{
+    hex,
+    primitives::{Address, U256},
+    providers::RootProvider,
+};
+use anyhow::Result;
+use ibc_eureka_solidity_types::{
+    ics20::{erc20, ics20_transfer},
+    ics26::router::routerInstance,
+};
+use ibc_eureka_utils::rpc::TendermintRpcExt;
+use ibc_proto_eureka::cosmos::{
+    auth::v1beta1::{AddressBytesToStringRequest, AddressBytesToStringResponse},
+    bank::v1beta1::{
+        QueryBalanceRequest, QueryBalanceResponse, QuerySupplyOfRequest, QuerySupplyOfResponse,
+    },
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tendermint_rpc::HttpClient;
+
+use crate::{
+    cli::config::RelayerConfig,
+    core::modules::RelayerModule,
+    doctor::abci_query,
+    modules::{
+        connect_eth_provider,
+        eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+    },
+};
+
+/// The port of the ICS20 transfer applications.
+pub const TRANSFER_PORT: &str = "transfer";
+/// The version prefixed to the pre-image of the escrow addresses of the ICS20 transfer module.
+const ESCROW_ADDRESS_VERSION: &str = "ics20-1";
+
+/// The chain on which a token is native, and escrowed when it is bridged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NativeChain {
+    /// An ERC20 token escrowed on Ethereum, with vouchers on the Cosmos SDK chain.
+    Ethereum,
+    /// A coin escrowed on the Cosmos SDK chain, with vouchers on Ethereum.
+    Cosmos,
+}
+
+/// The balance of the escrow of a denom against the supply of its vouchers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowStatus {
+    /// The escrow holds exactly the supply of the vouchers.
+    Balanced,
+    /// The escrow holds more than the supply of the vouchers.
+    Surplus,
+    /// The escrow holds less than the supply of the vouchers.
+    Deficit,
+}
+
+impl fmt::Display for EscrowStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Balanced => write!(f, "BALANCED"),
+            Self::Surplus => write!(f, "SURPLUS"),
+            Self::Deficit => write!(f, "DEFICIT"),
+        }
+    }
+}
+
+/// The escrow accounting of a denom.
+#[derive(Clone, Debug, Serialize)]
+pub struct DenomAudit {
+    /// The chain on which the denom is native.
+    pub native_chain: NativeChain,
+    /// The native denom: the address of the ERC20 token, or the denom of the coin.
+    pub denom: String,
+    /// The denom of the vouchers: the `ibc/` denom on the Cosmos SDK chain, or the address of the
+    /// `IBCERC20` contract on Ethereum, if any voucher was ever minted.
+    pub voucher_denom: Option<String>,
+    /// The amount held by the escrow.
+    pub escrowed: U256,
+    /// The supply of the vouchers.
+    pub vouchers: U256,
+    /// The status of the escrow.
+    pub status: EscrowStatus,
+}
+
+impl DenomAudit {
+    fn new(
+        native_chain: NativeChain,
+        denom: String,
+        voucher_denom: Option<String>,
+        escrowed: U256,
+        vouchers: U256,
+    ) -> Self {
+        let status = match escrowed.cmp(&vouchers) {
+            std::cmp::Ordering::Equal => EscrowStatus::Balanced,
+            std::cmp::Ordering::Greater => EscrowStatus::Surplus,
+            std::cmp::Ordering::Less => EscrowStatus::Deficit,
+        };
+        Self {
+            native_chain,
+            denom,
+            voucher_denom,
+            escrowed,
+            vouchers,
+            status,
+        }
+    }
+}
+
+/// The escrow audit of a route.
+#[derive(Clone, Debug, Serialize)]
+pub struct EscrowAudit {
+    /// The client of Ethereum on the Cosmos SDK chain.
+    pub cosmos_client_id: String,
+    /// The client of the Cosmos SDK chain on Ethereum.
+    pub eth_client_id: String,
+    /// The escrow contract of the `transfer` app on Ethereum, if any token was ever escrowed.
+    pub eth_escrow: Option<Address>,
+    /// The escrow account of the `transfer` module on the Cosmos SDK chain.
+    pub cosmos_escrow: String,
+    /// The accounting of the audited denoms.
+    pub denoms: Vec<DenomAudit>,
+}
+
+impl EscrowAudit {
+    /// Returns whether every escrow backs the supply of its vouchers.
+    #[must_use]
+    pub fn solvent(&self) -> bool {
+        self.denoms
+            .iter()
+            .all(|denom| denom.status != EscrowStatus::Deficit)
+    }
+
+    /// Renders the audit as a human readable report.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut lines = vec![format!(
+            "Escrow audit of {} (Cosmos) <> {} (Ethereum)",
+            self.cosmos_client_id, self.eth_client_id
+        )];
+        for denom in &self.denoms {
+            let imbalance = if denom.escrowed >= denom.vouchers {
+                format!("+{}", denom.escrowed - denom.vouchers)
+            } else {
+                format!("-{}", denom.vouchers - denom.escrowed)
+            };
+            lines.push(format!(
+                "[{}] {:?} {}: escrowed {}, vouchers {} ({}), imbalance {imbalance}",
+                denom.status,
+                denom.native_chain,
+                denom.denom,
+                denom.escrowed,
+                denom.vouchers,
+                denom.voucher_denom.as_deref().unwrap_or("never minted"),
+            ));
+        }
+        let deficits = self
+            .denoms
+            .iter()
+            .filter(|denom| denom.status == EscrowStatus::Deficit)
+            .count();
+        lines.push(format!(
+            "{} denoms audited, {deficits} in deficit.",
+            self.denoms.len()
+        ));
+        lines.join("\n")
+    }
+}
+
+/// The denoms to audit.
+#[derive(Clone, Debug, Default)]
+pub struct AuditDenoms {
+    /// The ERC20 tokens native to Ethereum.
+    pub erc20s: Vec<Address>,
+    /// The coins native to the Cosmos SDK chain.
+    pub coins: Vec<String>,
+}
+
+/// Audits the escrows of the route of the Ethereum light client `client_id` hosted on `chain`.
+///
+/// The first enabled `eth_to_cosmos` module whose destination chain is `chain` is used to connect
+/// to both chains. The client of the Cosmos SDK chain on Ethereum is the counterparty of
+/// `client_id`.
+/// # Errors
+/// Returns an error if no suitable module is configured or if a balance cannot be fetched.
+pub async fn audit_escrow(
+    config: &RelayerConfig,
+    chain: &str,
+    client_id: &str,
+    denoms: &AuditDenoms,
+) -> Result<EscrowAudit> {
+    let module = config
+        .modules
+        .iter()
+        .find(|module| {
+            module.enabled
+                && module.dst_chain == chain
+                && module.name == EthToCosmosRelayerModule.name()
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!("No enabled `eth_to_cosmos` module with destination chain `{chain}`")
+        })?;
+    let config: EthToCosmosConfig = serde_json::from_value(module.config.clone())?;
+
+    let tm_client = HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?;
+    let provider = connect_eth_provider(
+        &config.eth_rpc_url,
+        &config.eth_rpc_fallback_urls,
+        &config.http_client,
+        config.circuit_breaker,
+    )
+    .await?;
+
+    let eth_client_id = tm_client
+        .counterparty_info(client_id.to_string())
+        .await?
+        .client_id;
+    let ics20_address = routerInstance::new(config.ics26_address, provider.clone())
+        .getIBCApp(TRANSFER_PORT.to_string())
+        .call()
+        .await?
+        ._0;
+    let ics20 = ics20_transfer::new(ics20_address, provider.clone());
+    let eth_escrow = Some(ics20.getEscrow(eth_client_id.clone()).call().await?._0)
+        .filter(|escrow| !escrow.is_zero());
+    let cosmos_escrow = abci_query::<_, AddressBytesToStringResponse>(
+        &tm_client,
+        "/cosmos.auth.v1beta1.Query/AddressBytesToString",
+        &AddressBytesToStringRequest {
+            address_bytes: escrow_address(TRANSFER_PORT, client_id).to_vec(),
+        },
+    )
+    .await?
+    .address_string;
+
+    let mut audits = Vec::with_capacity(denoms.erc20s.len() + denoms.coins.len());
+    for token in &denoms.erc20s {
+        let escrowed = match eth_escrow {
+            Some(escrow) => {
+                erc20::new(*token, provider.clone())
+                    .balanceOf(escrow)
+                    .call()
+                    .await?
+                    ._0
+            }
+            None => U256::ZERO,
+        };
+        let voucher_denom = ibc_denom(&format!(
+            "{TRANSFER_PORT}/{client_id}/{}",
+            token.to_string().to_lowercase()
+        ));
+        let vouchers = supply_of(&tm_client, &voucher_denom).await?;
+        audits.push(DenomAudit::new(
+            NativeChain::Ethereum,
+            token.to_string(),
+            Some(voucher_denom),
+            escrowed,
+            vouchers,
+        ));
+    }
+
+    for coin in &denoms.coins {
+        let escrowed = balance_of(&tm_client, &cosmos_escrow, coin).await?;
+        let voucher = ibc_erc20(&ics20, &format!("{TRANSFER_PORT}/{eth_client_id}/{coin}")).await?;
+        let vouchers = match voucher {
+            Some(voucher) => {
+                erc20::new(voucher, provider.clone())
+                    .totalSupply()
+                    .call()
+                    .await?
+                    ._0
+            }
+            None => U256::ZERO,
+        };
+        audits.push(DenomAudit::new(
+            NativeChain::Cosmos,
+            coin.clone(),
+            voucher.map(|voucher| voucher.to_string()),
+            escrowed,
+            vouchers,
+        ));
+    }
+
+    Ok(EscrowAudit {
+        cosmos_client_id: client_id.to_string(),
+        eth_client_id,
+        eth_escrow,
+        cosmos_escrow,
+        denoms: audits,
+    })
+}
+
+/// Returns the address of the escrow account of the ICS20 transfer module for `port_id` and
+/// `client_id`, as derived by ibc-go.
+#[must_use]
+pub fn escrow_address(port_id: &str, client_id: &str) -> [u8; 20] {
+    let mut hasher = Sha256::new();
+    hasher.update(ESCROW_ADDRESS_VERSION.as_bytes());
+    hasher.update([0]);
+    hasher.update(format!("{port_id}/{client_id}").as_bytes());
+    let mut address = [0; 20];
+    address.copy_from_slice(&hasher.finalize()[..20]);
+    address
+}
+
+/// Returns the `ibc/` denom of the vouchers with the full denom path `path`.
+#[must_use]
+pub fn ibc_denom(path: &str) -> String {
+    format!("ibc/{}", hex::encode_upper(Sha256::digest(path.as_bytes())))
+}
+
+/// Returns the address of the `IBCERC20` contract of the full denom path `path`, if one was
+/// created.
+async fn ibc_erc20(
+    ics20: &ics20_transfer::ics20_transferInstance<(), RootProvider>,
+    path: &str,
+) -> Result<Option<Address>> {
+    match ics20.ibcERC20Contract(path.to_string()).call().await {
+        Ok(contract) => Ok(Some(contract._0)),
+        // The transfer app reverts for the denoms it never minted
+        Err(e) if e.as_revert_data().is_some() => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Returns the supply of `denom` on the Cosmos SDK chain.
+async fn supply_of(tm_client: &HttpClient, denom: &str) -> Result<U256> {
+    let supply = abci_query::<_, QuerySupplyOfResponse>(
+        tm_client,
+        "/cosmos.bank.v1beta1.Query/SupplyOf",
+        &QuerySupplyOfRequest {
+            denom: denom.to_string(),
+        },
+    )
+    .await?;
+    parse_amount(supply.amount.map(|coin| coin.amount))
+}
+
+/// Returns the balance of `denom` of `address` on the Cosmos SDK chain.
+async fn balance_of(tm_client: &HttpClient, address: &str, denom: &str) -> Result<U256> {
+    let balance = abci_query::<_, QueryBalanceResponse>(
+        tm_client,
+        "/cosmos.bank.v1beta1.Query/Balance",
+        &QueryBalanceRequest {
+            address: address.to_string(),
+            denom: denom.to_string(),
+        },
+    )
+    .await?;
+    parse_amount(balance.balance.map(|coin| coin.amount))
+}
+
+/// Parses the amount of a Cosmos SDK coin, which is missing if zero.
+fn parse_amount(amount: Option<String>) -> Result<U256> {
+    amount.map_or(Ok(U256::ZERO), |amount| {
+        U256::from_str(&amount).map_err(|e| anyhow::anyhow!("invalid amount {amount}: {e}"))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escrow_address() {
+        // The first 20 bytes of sha256("ics20-1" || 0x00 || "transfer/channel-0")
+        assert_eq!(
+            hex::encode(escrow_address("transfer", "channel-0")),
+            "ed23c6f4443f49c4b08f856350a5d2c65a203235"
+        );
+    }
+
+    #[test]
+    fn test_ibc_denom() {
+        // The voucher of `uatom` received over `transfer/channel-0`
+        assert_eq!(
+            ibc_denom("transfer/channel-0/uatom"),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+    }
+
+    #[test]
+    fn test_escrow_status() {
+        let audit = |escrowed: u64, vouchers: u64| {
+            DenomAudit::new(
+                NativeChain::Cosmos,
+                "uatom".to_string(),
+                None,
+                U256::from(escrowed),
+                U256::from(vouchers),
+            )
+        };
+        assert_eq!(audit(10, 10).status, EscrowStatus::Balanced);
+        assert_eq!(audit(11, 10).status, EscrowStatus::Surplus);
+        assert_eq!(audit(9, 10).status, EscrowStatus::Deficit);
+
+        let report = EscrowAudit {
+            cosmos_client_id: "08-wasm-0".to_string(),
+            eth_client_id: "client-0".to_string(),
+            eth_escrow: None,
+            cosmos_escrow: "cosmos1escrow".to_string(),
+            denoms: vec![audit(10, 10), audit(11, 10)],
+        };
+        assert!(report.solvent());
+        assert!(report.render().contains("imbalance +1"));
+
+        let report = EscrowAudit {
+            denoms: vec![audit(10, 10), audit(9, 10)],
+            ..report
+        };
+        assert!(!report.solvent());
+        assert!(report.render().contains("imbalance -1"));
+    }
+}
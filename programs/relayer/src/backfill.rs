@@ -0,0 +1,145 @@
+//! Defines the backfill of the historical consensus states of a freshly created Ethereum light
+//! client.
+
+use alloy::hex;
+use ibc_eureka_relayer_lib::{
+    lookback::MaxLookback, slot_timing::SlotTiming, tx_builder::eth_to_cosmos,
+};
+use ibc_eureka_utils::rpc::TendermintRpcExt;
+use ibc_proto_eureka::{cosmos::tx::v1beta1::TxBody, google::protobuf::Any};
+use prost::Message;
+use serde_json::{json, Value};
+use tendermint_rpc::HttpClient;
+
+use crate::{
+    cli::config::RelayerConfig,
+    core::modules::RelayerModule,
+    modules::{
+        connect_eth_provider,
+        eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+    },
+};
+
+/// The default number of headers of a header batch, one per sync committee period.
+pub const DEFAULT_BATCH_SIZE: usize = 8;
+
+/// Produces the transactions that backfill the consensus states of the Ethereum light client
+/// `client_id`, from its latest slot up to the latest finalized slot, as header batches of at most
+/// `batch_size` headers.
+///
+/// The first enabled `eth_to_cosmos` module whose destination chain is `chain` is used to connect
+/// to the chains, and its signer address signs the transactions. A client lagging further behind
+/// than the `max_lookback` of the module is not backfilled, unless `deep_scan` overrides it.
+/// # Errors
+/// Returns an error if no suitable module is configured, if the client lags beyond the max
+/// lookback, or if the updates cannot be built.
+pub async fn backfill_client(
+    config: &RelayerConfig,
+    chain: &str,
+    client_id: &str,
+    batch_size: usize,
+    deep_scan: bool,
+) -> anyhow::Result<Value> {
+    let module = config
+        .modules
+        .iter()
+        .find(|module| {
+            module.enabled
+                && module.dst_chain == chain
+                && module.name == EthToCosmosRelayerModule.name()
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No enabled `{}` module with destination chain `{chain}`",
+                EthToCosmosRelayerModule.name()
+            )
+        })?;
+    let config: EthToCosmosConfig = serde_json::from_value(module.config.clone())?;
+
+    let provider = connect_eth_provider(
+        &config.eth_rpc_url,
+        &config.eth_rpc_fallback_urls,
+        &config.http_client,
+        config.circuit_breaker,
+    )
+    .await?;
+    let beacon_api_client = config.beacon_api_client()?;
+    let slot_timing = SlotTiming::from(&beacon_api_client.spec().await?.data);
+    let finalized_slot = beacon_api_client
+        .finality_update()
+        .await?
+        .data
+        .finalized_header
+        .beacon
+        .slot;
+    let tx_builder = eth_to_cosmos::TxBuilder::new(
+        config.ics26_address,
+        provider,
+        config.eth_beacon_api_url,
+        HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?,
+        config.signer_address,
+        eth_to_cosmos::TxBuilderConfig {
+            compress_client_messages: config.compress_client_messages,
+            slot_timing,
+            ..Default::default()
+        },
+    )
+    .with_beacon_api_client(beacon_api_client);
+
+    let client_state = tx_builder
+        .ethereum_client_state(client_id.to_string())
+        .await?;
+    if !deep_scan {
+        ensure_within_max_lookback(
+            &config.max_lookback,
+            &slot_timing,
+            client_state.latest_slot,
+            finalized_slot,
+        )?;
+    }
+    let update_msgs = tx_builder
+        .build_backfill_msgs(client_id.to_string(), batch_size)
+        .await?;
+
+    let txs = update_msgs
+        .iter()
+        .map(|msg| {
+            anyhow::Ok(hex::encode(
+                TxBody {
+                    messages: vec![Any::from_msg(msg)?],
+                    ..Default::default()
+                }
+                .encode_to_vec(),
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(json!({
+        "client_id": client_id,
+        "from_slot": client_state.latest_slot,
+        "txs": txs,
+    }))
+}
+
+/// Checks that a backfill from `from_slot` up to `finalized_slot` is within the max lookback of
+/// the route, a slot counting as a block. Unlike the packet scans, a backfill cannot be trimmed
+/// to the newest slots, since every header of a batch trusts the state produced by the previous
+/// one.
+fn ensure_within_max_lookback(
+    max_lookback: &MaxLookback,
+    slot_timing: &SlotTiming,
+    from_slot: u64,
+    finalized_slot: u64,
+) -> anyhow::Result<()> {
+    let slot_time_millis = slot_timing.seconds_per_slot.saturating_mul(1_000);
+    let lookback_slots = finalized_slot.saturating_sub(from_slot);
+    if let Some(max_slots) = max_lookback.max_blocks_at(Some(slot_time_millis)) {
+        anyhow::ensure!(
+            lookback_slots <= max_slots,
+            "the client is {lookback_slots} slots behind the latest finalized slot, beyond the \
+             max lookback of {max_slots} slots of the route; pass --deep-scan to backfill it \
+             anyway"
+        );
+    }
+    Ok(())
+}
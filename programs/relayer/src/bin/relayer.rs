@@ -1,17 +1,28 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use clap::Parser;
 use prometheus::{Encoder, TextEncoder};
 use solidity_ibc_eureka_relayer::{
+    audit::{audit_escrow, AuditDenoms},
+    backfill::backfill_client,
+    catch_up::{catch_up_client, CatchUpParams},
+    checksums::{verify_client_checksum, ChecksumRegistry, ChecksumStatus},
     cli::{
-        cmd::{Commands, RelayerCli},
+        cmd::{self, Commands, RelayerCli},
         config::RelayerConfig,
     },
-    core::builder::RelayerBuilder,
+    client_history::export_client_history,
+    core::{
+        approvals, archive, builder::RelayerBuilder, escrow, gas, lock, orphans, scheduler, state,
+        sync_committees,
+    },
+    doctor::run_doctor,
+    inspect::inspect_client,
     modules::{
         cosmos_to_cosmos::CosmosToCosmosRelayerModule, cosmos_to_eth::CosmosToEthRelayerModule,
-        eth_to_cosmos::EthToCosmosRelayerModule,
+        eth_to_cosmos::EthToCosmosRelayerModule, remote::RemoteRelayerModule,
     },
+    recover::{recover_client, RecoverClientParams},
 };
 use warp::Filter;
 
@@ -29,14 +40,33 @@ async fn main() -> anyhow::Result<()> {
                 .with_max_level(config.server.log_level())
                 .init();
 
+            // Lock the persisted state directory, held until the relayer exits.
+            let _state_dir_lock = config
+                .server
+                .packet_archive
+                .as_ref()
+                .map(|packet_archive| {
+                    lock::lock_state_dir(&packet_archive.dir, args.force_takeover)
+                })
+                .transpose()?;
+
             // Build the relayer server.
             let mut relayer_builder = RelayerBuilder::default();
             relayer_builder.add_module(CosmosToEthRelayerModule);
             relayer_builder.add_module(CosmosToCosmosRelayerModule);
             relayer_builder.add_module(EthToCosmosRelayerModule);
+            relayer_builder.add_module(RemoteRelayerModule);
+
+            // Read the escrow operator token before the metrics server serves the approvals.
+            let escrow_operator_token = config
+                .server
+                .escrow_operator_token_path
+                .as_deref()
+                .map(escrow::read_operator_token)
+                .transpose()?;
 
             // Start the metrics server.
-            tokio::spawn(async {
+            tokio::spawn(async move {
                 let metrics_route = warp::path("metrics").map(|| {
                     let encoder = TextEncoder::new();
                     let metric_families = prometheus::gather();
@@ -46,7 +76,27 @@ async fn main() -> anyhow::Result<()> {
                 });
 
                 tracing::info!("Metrics available at http://0.0.0.0:9000/metrics");
-                warp::serve(metrics_route).run(([0, 0, 0, 0], 9000)).await;
+                tracing::info!("Relay approvals available at http://0.0.0.0:9000/approvals");
+                tracing::info!("Sealed relays available at http://0.0.0.0:9000/escrow");
+                tracing::info!("Relayer state available at http://0.0.0.0:9000/state");
+                tracing::info!("Scheduled ack relays available at http://0.0.0.0:9000/scheduled");
+                tracing::info!("Gas spike deferrals available at http://0.0.0.0:9000/gas");
+                tracing::info!("Orphaned packet cleanups accepted at http://0.0.0.0:9000/orphans");
+                tracing::info!(
+                    "Verified sync committees available at http://0.0.0.0:9000/sync-committees"
+                );
+                warp::serve(
+                    metrics_route
+                        .or(approvals::routes())
+                        .or(escrow::routes(escrow_operator_token))
+                        .or(state::routes())
+                        .or(scheduler::routes())
+                        .or(gas::routes())
+                        .or(sync_committees::routes())
+                        .or(orphans::routes()),
+                )
+                .run(([0, 0, 0, 0], 9000))
+                .await;
             });
 
             // Start the relayer server.
@@ -54,5 +104,256 @@ async fn main() -> anyhow::Result<()> {
 
             Ok(())
         }
+        Commands::Doctor(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let report = run_doctor(&config, Duration::from_secs(args.timeout_secs)).await;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", report.render());
+            }
+
+            anyhow::ensure!(report.passed(), "some checks failed");
+
+            Ok(())
+        }
+        Commands::InspectClient(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let client = inspect_client(&config, &args.chain, &args.client).await?;
+            println!("{}", serde_json::to_string_pretty(&client)?);
+
+            Ok(())
+        }
+        Commands::ExportClientHistory(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let history = export_client_history(&config, &args.chain, &args.client).await?;
+            match args.output {
+                Some(output) => {
+                    std::fs::write(&output, serde_json::to_vec_pretty(&history)?)?;
+                    println!("Client history exported to {output}");
+                }
+                None => println!("{}", serde_json::to_string_pretty(&history)?),
+            }
+
+            Ok(())
+        }
+        Commands::AuditEscrow(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let denoms = AuditDenoms {
+                erc20s: args.erc20,
+                coins: args.denom,
+            };
+            loop {
+                match audit_escrow(&config, &args.chain, &args.client, &denoms).await {
+                    Ok(audit) => {
+                        if args.json {
+                            println!("{}", serde_json::to_string(&audit)?);
+                        } else {
+                            println!("{}", audit.render());
+                        }
+                        if args.interval_secs.is_none() {
+                            anyhow::ensure!(audit.solvent(), "some escrows are in deficit");
+                            return Ok(());
+                        }
+                    }
+                    // A failed round of a continuous audit is retried at the next interval
+                    Err(e) if args.interval_secs.is_some() => eprintln!("Escrow audit failed: {e}"),
+                    Err(e) => return Err(e),
+                }
+                tokio::time::sleep(Duration::from_secs(args.interval_secs.unwrap_or_default()))
+                    .await;
+            }
+        }
+        Commands::InspectPacket(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let preimage = archive::inspect_packet(&config, &args.commitment)?;
+            println!("{}", serde_json::to_string_pretty(&preimage)?);
+
+            Ok(())
+        }
+        Commands::CleanupClient(args) => {
+            let cleanup = orphans::OrphanCleanup {
+                src_chain: args.src_chain,
+                dst_chain: args.dst_chain,
+                src_client_id: args.src_client,
+                dst_client_id: args.dst_client,
+                max_packets: args.max_packets,
+            };
+            let report = orphans::request_cleanup(&args.endpoint, &cleanup).await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+
+            Ok(())
+        }
+        Commands::RecoverClient(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let params = RecoverClientParams {
+                client_id: &args.client,
+                checksum_hex: &args.checksum_hex,
+                authority: &args.authority,
+                deposit: &args.deposit,
+            };
+            let recovery = recover_client(&config, &args.chain, &params).await?;
+            println!("{}", serde_json::to_string_pretty(&recovery)?);
+
+            Ok(())
+        }
+        Commands::BackfillClient(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let backfill = backfill_client(
+                &config,
+                &args.chain,
+                &args.client,
+                args.batch_size,
+                args.deep_scan,
+            )
+            .await?;
+            println!("{}", serde_json::to_string_pretty(&backfill)?);
+
+            Ok(())
+        }
+        Commands::CatchUp(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let params = CatchUpParams {
+                client_id: &args.client,
+                max_txs: args.max_txs,
+                fee_budget: args.fee_budget,
+                progress_path: &PathBuf::from(args.progress),
+            };
+            let catch_up = catch_up_client(&config, &args.chain, &params).await?;
+            println!("{}", serde_json::to_string_pretty(&catch_up)?);
+
+            Ok(())
+        }
+        Commands::VerifyChecksum(args) => {
+            let config_bz = std::fs::read(PathBuf::from(args.config))?;
+            let config: RelayerConfig = serde_json::from_slice(&config_bz)?;
+
+            let mut registry = ChecksumRegistry::embedded();
+            if let Some(registry_path) = &args.registry {
+                registry.extend_from_file(registry_path)?;
+            }
+
+            let verification =
+                verify_client_checksum(&config, &args.chain, &args.client, &registry).await?;
+            println!("{}", serde_json::to_string_pretty(&verification)?);
+
+            anyhow::ensure!(
+                verification.status != ChecksumStatus::Unknown,
+                "client {} is backed by the unknown wasm checksum {}",
+                args.client,
+                verification.checksum
+            );
+
+            Ok(())
+        }
+        Commands::State(args) => match args.command {
+            cmd::state::Commands::Export { endpoint, output } => {
+                let snapshot = state::fetch_state(&endpoint).await?;
+                std::fs::write(&output, serde_json::to_vec_pretty(&snapshot)?)?;
+                println!("Relayer state exported to {output}");
+
+                Ok(())
+            }
+            cmd::state::Commands::Import { endpoint, input } => {
+                let snapshot = state::read_snapshot(&input)?;
+                state::upload_state(&endpoint, &snapshot).await?;
+                println!("Relayer state imported from {input}");
+
+                Ok(())
+            }
+        },
+        Commands::SyncCommittees(args) => match args.command {
+            cmd::sync_committees::Commands::Export { endpoint, output } => {
+                let snapshot = sync_committees::fetch_sync_committees(&endpoint).await?;
+                std::fs::write(&output, serde_json::to_vec_pretty(&snapshot)?)?;
+                println!(
+                    "{} sync committees exported to {output}",
+                    snapshot.sync_committees.len()
+                );
+
+                Ok(())
+            }
+            cmd::sync_committees::Commands::Import { endpoint, input } => {
+                let snapshot = sync_committees::read_snapshot(&input)?;
+                let response =
+                    sync_committees::upload_sync_committees(&endpoint, &snapshot).await?;
+                println!("Sync committees from {input}: {response}");
+
+                Ok(())
+            }
+        },
+        Commands::Escrow(args) => match args.command {
+            cmd::escrow::Commands::SubmitShare {
+                endpoint,
+                artifact_id,
+                private_key_path,
+            } => {
+                let artifact = escrow::fetch_artifact(&endpoint, &artifact_id).await?;
+                let secret_key = escrow::read_secret_key(private_key_path.as_ref())?;
+                let submission = escrow::sign_share(
+                    &artifact_id,
+                    escrow::decrypt_share(&artifact, &secret_key)?,
+                    &secret_key,
+                )?;
+                escrow::upload_share(&endpoint, &artifact_id, &submission).await?;
+                println!(
+                    "Share {} of {artifact_id} submitted",
+                    submission.submission.index
+                );
+
+                Ok(())
+            }
+            cmd::escrow::Commands::DecryptShare {
+                artifact,
+                private_key_path,
+                output,
+            } => {
+                let artifact: escrow::SealedArtifact = escrow::read_json(&artifact)?;
+                let secret_key = escrow::read_secret_key(private_key_path.as_ref())?;
+                let submission = escrow::decrypt_share(&artifact, &secret_key)?;
+                std::fs::write(&output, serde_json::to_vec_pretty(&submission)?)?;
+                println!("Share {} written to {output}", submission.index);
+
+                Ok(())
+            }
+            cmd::escrow::Commands::Open {
+                artifact,
+                share,
+                output,
+            } => {
+                let artifact: escrow::SealedArtifact = escrow::read_json(&artifact)?;
+                let key = match artifact.key {
+                    Some(key) => key,
+                    None => {
+                        let shares = share
+                            .iter()
+                            .map(|path| escrow::read_json(path))
+                            .collect::<anyhow::Result<Vec<_>>>()?;
+                        escrow::recover_key(&artifact, &shares)?
+                    }
+                };
+                let relay = escrow::open_artifact(&artifact, &key)?;
+                std::fs::write(&output, serde_json::to_vec_pretty(&relay)?)?;
+                println!("Relay {} written to {output}", artifact.artifact_id);
+
+                Ok(())
+            }
+        },
     }
 }
@@ -0,0 +1,296 @@
+//! Defines the rate-limited and resumable catch-up of a stale Ethereum light client.
+//!
+//! A client lagging months behind cannot be updated with a single relay, since the transaction
+//! carrying a header for every sync committee period would not fit in a block. The catch-up
+//! instead advances the client one period per transaction, a limited number of transactions per
+//! run, and records its progress in a file so that the next run resumes from the transactions
+//! that landed and keeps the fees spent across runs within a budget.
+
+use std::path::Path;
+
+use alloy::hex;
+use ibc_eureka_relayer_lib::{
+    slot_timing::SlotTiming,
+    tx_builder::{eth_to_cosmos, r#trait::TxBuilderService},
+};
+use ibc_eureka_utils::rpc::TendermintRpcExt;
+use ibc_proto_eureka::{cosmos::tx::v1beta1::TxBody, google::protobuf::Any};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::HttpClient;
+
+use crate::{
+    cli::config::RelayerConfig,
+    core::modules::RelayerModule,
+    modules::{
+        connect_eth_provider,
+        eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+    },
+};
+
+/// The default maximum number of transactions produced by a catch-up run.
+pub const DEFAULT_MAX_TXS: usize = 4;
+
+/// The parameters of a catch-up run.
+pub struct CatchUpParams<'a> {
+    /// The client identifier of the Ethereum light client.
+    pub client_id: &'a str,
+    /// The maximum number of transactions produced by the run.
+    pub max_txs: usize,
+    /// The maximum fees of the whole catch-up, in the fee denom of the module, if any.
+    pub fee_budget: Option<u128>,
+    /// The file the progress of the catch-up is persisted to.
+    pub progress_path: &'a Path,
+}
+
+/// The progress of the catch-up of a client, persisted across runs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatchUpProgress {
+    /// The client identifier of the Ethereum light client.
+    pub client_id: String,
+    /// The latest slot of the client when the progress was last recorded.
+    pub latest_slot: u64,
+    /// The fees of the transactions that landed, in the fee denom of the module.
+    pub spent_fee: u128,
+    /// The transactions produced by the last run.
+    pub pending: Vec<CatchUpTx>,
+}
+
+/// A catch-up transaction, advancing the client by a sync committee period.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatchUpTx {
+    /// The finalized slot the client is advanced to once the transaction lands.
+    pub finalized_slot: u64,
+    /// The hex encoded transaction body.
+    pub tx: String,
+    /// The quoted fee of the transaction, in the fee denom of the module.
+    pub fee: Option<u128>,
+}
+
+impl CatchUpProgress {
+    /// Reads the progress of the catch-up of `client_id`, or starts a new one if the file does not
+    /// exist.
+    /// # Errors
+    /// Returns an error if the file cannot be read, or records the catch-up of another client.
+    pub fn load(path: &Path, client_id: &str) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self {
+                client_id: client_id.to_string(),
+                ..Default::default()
+            });
+        }
+
+        let progress: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+        anyhow::ensure!(
+            progress.client_id == client_id,
+            "progress file {} records the catch-up of client {}, not {client_id}",
+            path.display(),
+            progress.client_id
+        );
+        Ok(progress)
+    }
+
+    /// Writes the progress to `path`.
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        Ok(std::fs::write(path, serde_json::to_vec_pretty(self)?)?)
+    }
+
+    /// Settles the pending transactions against the latest slot of the client: the fees of the
+    /// transactions that landed are added to the spent fees, and the other ones are dropped, since
+    /// they are rebuilt from the current client state.
+    /// # Returns
+    /// The number of pending transactions that landed.
+    pub fn settle(&mut self, latest_slot: u64) -> usize {
+        let mut landed = 0;
+        for tx in std::mem::take(&mut self.pending) {
+            if tx.finalized_slot <= latest_slot {
+                self.spent_fee = self.spent_fee.saturating_add(tx.fee.unwrap_or(0));
+                landed += 1;
+            }
+        }
+        self.latest_slot = latest_slot;
+        landed
+    }
+
+    /// Returns the fees left in `budget` for the transactions of this run, once the spent fees
+    /// and the fees of the already pending transactions are deducted.
+    #[must_use]
+    pub fn remaining_budget(&self, budget: u128) -> u128 {
+        self.pending
+            .iter()
+            .fold(budget.saturating_sub(self.spent_fee), |remaining, tx| {
+                remaining.saturating_sub(tx.fee.unwrap_or(0))
+            })
+    }
+}
+
+/// Produces the next transactions of the catch-up of the stale Ethereum light client
+/// `client_id`, one per sync committee period, to sign and submit in order.
+///
+/// The pending transactions of the previous run that advanced the client are settled first, then
+/// at most `max_txs` transactions are built from the current client state, stopping before the
+/// fee budget would be exceeded. The progress is persisted to `progress_path` before returning.
+///
+/// The first enabled `eth_to_cosmos` module whose destination chain is `chain` is used to connect
+/// to the chains, and its signer address signs the transactions. Its fee configuration quotes the
+/// fees counted against the budget.
+/// # Errors
+/// Returns an error if no suitable module is configured, if a fee budget is set without a fee
+/// configuration, or if the updates cannot be built.
+pub async fn catch_up_client(
+    config: &RelayerConfig,
+    chain: &str,
+    params: &CatchUpParams<'_>,
+) -> anyhow::Result<serde_json::Value> {
+    let module = config
+        .modules
+        .iter()
+        .find(|module| {
+            module.enabled
+                && module.dst_chain == chain
+                && module.name == EthToCosmosRelayerModule.name()
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No enabled `{}` module with destination chain `{chain}`",
+                EthToCosmosRelayerModule.name()
+            )
+        })?;
+    let config: EthToCosmosConfig = serde_json::from_value(module.config.clone())?;
+    anyhow::ensure!(
+        params.fee_budget.is_none() || config.fee.is_some(),
+        "a fee budget requires the `fee` configuration of the module to quote the fees"
+    );
+
+    let mut progress = CatchUpProgress::load(params.progress_path, params.client_id)?;
+
+    let provider = connect_eth_provider(
+        &config.eth_rpc_url,
+        &config.eth_rpc_fallback_urls,
+        &config.http_client,
+        config.circuit_breaker,
+    )
+    .await?;
+    let beacon_api_client = config.beacon_api_client()?;
+    let slot_timing = SlotTiming::from(&beacon_api_client.spec().await?.data);
+    let tm_client = HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?;
+    let tx_builder = eth_to_cosmos::TxBuilder::new(
+        config.ics26_address,
+        provider,
+        config.eth_beacon_api_url,
+        tm_client.clone(),
+        config.signer_address,
+        eth_to_cosmos::TxBuilderConfig {
+            compress_client_messages: config.compress_client_messages,
+            slot_timing,
+            ..Default::default()
+        },
+    )
+    .with_beacon_api_client(beacon_api_client);
+
+    let client_state = tx_builder
+        .ethereum_client_state(params.client_id.to_string())
+        .await?;
+    let landed = progress.settle(client_state.latest_slot);
+    tracing::info!(
+        "{landed} catch-up transactions of client {} landed, the client is at slot {}",
+        params.client_id,
+        client_state.latest_slot
+    );
+
+    let updates = tx_builder
+        .build_catch_up_msgs(params.client_id.to_string(), params.max_txs)
+        .await?;
+    let caught_up = updates.is_empty();
+
+    for update in updates {
+        let tx = TxBody {
+            messages: vec![Any::from_msg(&update.msg)?],
+            ..Default::default()
+        }
+        .encode_to_vec();
+
+        let fee = match &config.fee {
+            Some(fee_config) => {
+                let plan = tx_builder.plan_tx(&tx).await?;
+                let quote = fee_config.quote(&tm_client, plan.estimated_gas).await?;
+                Some(quote.fee.amount.parse::<u128>()?)
+            }
+            None => None,
+        };
+        if let (Some(budget), Some(fee)) = (params.fee_budget, fee) {
+            if fee > progress.remaining_budget(budget) {
+                tracing::warn!(
+                    "The fee budget of the catch-up is exhausted before slot {}",
+                    update.finalized_slot
+                );
+                break;
+            }
+        }
+
+        progress.pending.push(CatchUpTx {
+            finalized_slot: update.finalized_slot,
+            tx: hex::encode(tx),
+            fee,
+        });
+    }
+    progress.save(params.progress_path)?;
+
+    Ok(serde_json::json!({
+        "client_id": params.client_id,
+        "from_slot": client_state.latest_slot,
+        "caught_up": caught_up,
+        "spent_fee": progress.spent_fee.to_string(),
+        "remaining_budget": params
+            .fee_budget
+            .map(|budget| progress.remaining_budget(budget).to_string()),
+        "txs": progress.pending,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tx(finalized_slot: u64, fee: u128) -> CatchUpTx {
+        CatchUpTx {
+            finalized_slot,
+            tx: String::new(),
+            fee: Some(fee),
+        }
+    }
+
+    #[test]
+    fn test_settle_progress() {
+        let mut progress = CatchUpProgress {
+            client_id: "08-wasm-0".to_string(),
+            latest_slot: 100,
+            spent_fee: 10,
+            pending: vec![tx(8_292, 5), tx(16_484, 7), tx(24_676, 9)],
+        };
+
+        // Only the first two transactions landed
+        assert_eq!(progress.settle(16_484), 2);
+        assert_eq!(progress.spent_fee, 22);
+        assert_eq!(progress.latest_slot, 16_484);
+        assert!(progress.pending.is_empty());
+
+        // Nothing landed since the last run
+        progress.pending = vec![tx(24_676, 9)];
+        assert_eq!(progress.settle(16_484), 0);
+        assert_eq!(progress.spent_fee, 22);
+    }
+
+    #[test]
+    fn test_remaining_budget() {
+        let progress = CatchUpProgress {
+            spent_fee: 22,
+            pending: vec![tx(24_676, 9)],
+            ..Default::default()
+        };
+        assert_eq!(progress.remaining_budget(100), 69);
+        assert_eq!(progress.remaining_budget(25), 0);
+    }
+}
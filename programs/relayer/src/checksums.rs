@@ -0,0 +1,150 @@
+//! Defines the registry of the released `cw-ics08-wasm-eth` wasm checksums, and the verification
+//! of the wasm code backing an Ethereum light client against it.
+
+use alloy::hex;
+use ibc_eureka_utils::rpc::TendermintRpcExt;
+use ibc_proto_eureka::ibc::lightclients::wasm::v1::ClientState as WasmClientState;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::HttpClient;
+
+use crate::{
+    cli::config::RelayerConfig,
+    core::modules::RelayerModule,
+    modules::eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+};
+
+/// The registry of the released checksums, embedded at compile time.
+/// A release is added to the registry when its optimized wasm binary is published.
+const EMBEDDED_REGISTRY: &str = include_str!("../../cw-ics08-wasm-eth/checksums.json");
+
+/// A released version of the `cw-ics08-wasm-eth` contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleasedChecksum {
+    /// The version of the release.
+    pub version: String,
+    /// The hex encoded sha256 checksum of the optimized wasm binary.
+    pub checksum: String,
+}
+
+/// The registry of the released `cw-ics08-wasm-eth` checksums, ordered from oldest to newest.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumRegistry {
+    /// The released versions.
+    pub releases: Vec<ReleasedChecksum>,
+}
+
+/// How the checksum backing a light client relates to the registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumStatus {
+    /// The checksum is the latest release.
+    Latest,
+    /// The checksum is an older release.
+    Outdated,
+    /// The checksum is not a known release.
+    Unknown,
+}
+
+/// The result of verifying the checksum backing a light client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChecksumVerification {
+    /// The client identifier of the light client.
+    pub client_id: String,
+    /// The hex encoded checksum of the wasm code backing the light client.
+    pub checksum: String,
+    /// How the checksum relates to the registry.
+    pub status: ChecksumStatus,
+    /// The released version of the checksum, if it is known.
+    pub version: Option<String>,
+    /// The latest released version.
+    pub latest_version: Option<String>,
+}
+
+impl ChecksumRegistry {
+    /// Returns the registry embedded in the relayer binary.
+    /// # Panics
+    /// Panics if the embedded registry is malformed.
+    #[must_use]
+    pub fn embedded() -> Self {
+        serde_json::from_str(EMBEDDED_REGISTRY).expect("embedded checksum registry is malformed")
+    }
+
+    /// Adds the releases of a registry file, e.g. of releases newer than the relayer binary.
+    /// # Errors
+    /// Returns an error if the file cannot be read or decoded.
+    pub fn extend_from_file(&mut self, path: &str) -> anyhow::Result<()> {
+        let registry: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+        for release in registry.releases {
+            if !self.releases.contains(&release) {
+                self.releases.push(release);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the release with the given checksum, if any.
+    #[must_use]
+    pub fn find(&self, checksum: &[u8]) -> Option<&ReleasedChecksum> {
+        self.releases.iter().find(|release| {
+            hex::decode(&release.checksum)
+                .is_ok_and(|release_checksum| release_checksum == checksum)
+        })
+    }
+
+    /// Returns how a checksum relates to the registry.
+    #[must_use]
+    pub fn verify(&self, client_id: &str, checksum: &[u8]) -> ChecksumVerification {
+        let release = self.find(checksum);
+        let latest = self.releases.last();
+        let status = match (release, latest) {
+            (Some(release), Some(latest)) if release == latest => ChecksumStatus::Latest,
+            (Some(_), _) => ChecksumStatus::Outdated,
+            (None, _) => ChecksumStatus::Unknown,
+        };
+
+        ChecksumVerification {
+            client_id: client_id.to_string(),
+            checksum: hex::encode(checksum),
+            status,
+            version: release.map(|release| release.version.clone()),
+            latest_version: latest.map(|release| release.version.clone()),
+        }
+    }
+}
+
+/// Fetches the checksum of the wasm code backing the Ethereum light client with `client_id`
+/// hosted on `chain`, and verifies it against the registry.
+///
+/// The first enabled `eth_to_cosmos` module whose destination chain is `chain` is used to connect
+/// to the chain.
+/// # Errors
+/// Returns an error if no suitable module is configured or if the client state cannot be fetched.
+pub async fn verify_client_checksum(
+    config: &RelayerConfig,
+    chain: &str,
+    client_id: &str,
+    registry: &ChecksumRegistry,
+) -> anyhow::Result<ChecksumVerification> {
+    let module = config
+        .modules
+        .iter()
+        .find(|module| {
+            module.enabled
+                && module.dst_chain == chain
+                && module.name == EthToCosmosRelayerModule.name()
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No enabled `{}` module with destination chain `{chain}`",
+                EthToCosmosRelayerModule.name()
+            )
+        })?;
+    let config: EthToCosmosConfig = serde_json::from_value(module.config.clone())?;
+
+    let tm_client = HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?;
+    let client_state_any = tm_client.client_state(client_id.to_string()).await?;
+    let wasm_client_state = WasmClientState::decode(client_state_any.value.as_slice())?;
+
+    Ok(registry.verify(client_id, &wasm_client_state.checksum))
+}
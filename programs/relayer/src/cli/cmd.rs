@@ -16,6 +16,40 @@ pub struct RelayerCli {
 pub enum Commands {
     /// The subcommand to run the relayer.
     Start(start::Args),
+    /// The subcommand to check the configuration, the endpoints and the submitters of the
+    /// configured routes before they are enabled.
+    Doctor(doctor::Args),
+    /// The subcommand to print the decoded state of a light client.
+    InspectClient(inspect_client::Args),
+    /// The subcommand to export the consensus states of an Ethereum light client, with the
+    /// updates that created them, for audits.
+    ExportClientHistory(export_client_history::Args),
+    /// The subcommand to compare the escrowed tokens of a route with the supply of their vouchers
+    /// on the other chain.
+    AuditEscrow(audit_escrow::Args),
+    /// The subcommand to produce the migration and governance proposal that re-activate an
+    /// expired Ethereum light client.
+    RecoverClient(recover_client::Args),
+    /// The subcommand to produce the transactions that backfill the historical consensus states
+    /// of a freshly created Ethereum light client.
+    BackfillClient(backfill_client::Args),
+    /// The subcommand to produce the next transactions of the rate-limited and resumable catch-up
+    /// of a stale Ethereum light client.
+    CatchUp(catch_up::Args),
+    /// The subcommand to export or import the state of a running relayer.
+    State(state::Args),
+    /// The subcommand to verify the wasm code backing an Ethereum light client against the
+    /// registry of released checksums.
+    VerifyChecksum(verify_checksum::Args),
+    /// The subcommand to export or import the sync committees verified by a running relayer.
+    SyncCommittees(sync_committees::Args),
+    /// The subcommand to print the archived pre-image of a packet commitment.
+    InspectPacket(inspect_packet::Args),
+    /// The subcommand to time out the archived packets sent to a frozen or removed counterparty
+    /// client.
+    CleanupClient(cleanup_client::Args),
+    /// The subcommand to reveal the relays of high-value transfers sealed by a relayer.
+    Escrow(escrow::Args),
 }
 
 /// The arguments for the start subcommand.
@@ -28,5 +62,410 @@ pub mod start {
         /// The configuration file for the relayer.
         #[clap(short = 'c', long)]
         pub config: String,
+        /// Take over the lock on the persisted state directory even if it is held by another
+        /// relayer. Only use it if the other relayer is not running anymore.
+        #[clap(long)]
+        pub force_takeover: bool,
+    }
+}
+
+/// The arguments for the doctor subcommand.
+pub mod doctor {
+    use super::Parser;
+
+    /// The arguments for the doctor subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// Print the report as JSON.
+        #[clap(long)]
+        pub json: bool,
+        /// The time after which a check of an endpoint fails, in seconds.
+        #[clap(long, default_value_t = crate::doctor::DEFAULT_CHECK_TIMEOUT_SECS)]
+        pub timeout_secs: u64,
+    }
+}
+
+/// The arguments for the inspect client subcommand.
+pub mod inspect_client {
+    use super::Parser;
+
+    /// The arguments for the inspect client subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The chain identifier hosting the light client.
+        /// The first enabled module with this destination chain is used to connect to it.
+        #[clap(long)]
+        pub chain: String,
+        /// The client identifier of the light client.
+        #[clap(long)]
+        pub client: String,
+    }
+}
+
+/// The arguments for the export client history subcommand.
+pub mod export_client_history {
+    use super::Parser;
+
+    /// The arguments for the export client history subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The chain identifier hosting the Ethereum light client.
+        /// The first enabled `eth_to_cosmos` module with this destination chain is used.
+        #[clap(long)]
+        pub chain: String,
+        /// The client identifier of the Ethereum light client.
+        #[clap(long)]
+        pub client: String,
+        /// The file the history is written to. It is printed if unset.
+        #[clap(long)]
+        pub output: Option<String>,
+    }
+}
+
+/// The arguments for the audit escrow subcommand.
+pub mod audit_escrow {
+    use alloy::primitives::Address;
+
+    use super::Parser;
+
+    /// The arguments for the audit escrow subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The chain identifier of the Cosmos SDK chain.
+        /// The first enabled `eth_to_cosmos` module with this destination chain is used.
+        #[clap(long)]
+        pub chain: String,
+        /// The client identifier of the Ethereum light client on the Cosmos SDK chain.
+        #[clap(long)]
+        pub client: String,
+        /// The address of an ERC20 token native to Ethereum to audit.
+        #[clap(long)]
+        pub erc20: Vec<Address>,
+        /// A denom native to the Cosmos SDK chain to audit.
+        #[clap(long)]
+        pub denom: Vec<String>,
+        /// Print the report as JSON.
+        #[clap(long)]
+        pub json: bool,
+        /// Repeat the audit every interval, in seconds, instead of running it once.
+        #[clap(long)]
+        pub interval_secs: Option<u64>,
+    }
+}
+
+/// The arguments for the inspect packet subcommand.
+pub mod inspect_packet {
+    use super::Parser;
+
+    /// The arguments for the inspect packet subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer, with the `packet_archive` to read.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The hex encoded packet commitment.
+        #[clap(long)]
+        pub commitment: String,
+    }
+}
+
+/// The arguments for the cleanup client subcommand.
+pub mod cleanup_client {
+    use super::Parser;
+
+    /// The arguments for the cleanup client subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The metrics server endpoint of the relayer, which must have a packet archive.
+        #[clap(long, default_value = "http://localhost:9000")]
+        pub endpoint: String,
+        /// The chain of the frozen or removed counterparty client.
+        #[clap(long)]
+        pub src_chain: String,
+        /// The healthy chain the packets were sent from.
+        #[clap(long)]
+        pub dst_chain: String,
+        /// The frozen or removed counterparty client.
+        #[clap(long)]
+        pub src_client: String,
+        /// The client on the healthy chain the packets were sent from.
+        #[clap(long)]
+        pub dst_client: String,
+        /// The maximum number of packets timed out, lowest sequences first.
+        #[clap(long, default_value_t = crate::core::orphans::DEFAULT_MAX_ORPHANED_PACKETS)]
+        pub max_packets: usize,
+    }
+}
+
+/// The arguments for the recover client subcommand.
+pub mod recover_client {
+    use super::Parser;
+
+    /// The arguments for the recover client subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The chain identifier hosting the Ethereum light client.
+        /// The first enabled `eth_to_cosmos` module with this destination chain is used.
+        #[clap(long)]
+        pub chain: String,
+        /// The client identifier of the Ethereum light client.
+        #[clap(long)]
+        pub client: String,
+        /// The hex encoded checksum of the wasm code to migrate the client to.
+        #[clap(long)]
+        pub checksum_hex: String,
+        /// The address of the governance module signing the migration.
+        #[clap(long)]
+        pub authority: String,
+        /// The deposit of the governance proposal.
+        #[clap(long)]
+        pub deposit: String,
+    }
+}
+
+/// The arguments for the backfill client subcommand.
+pub mod backfill_client {
+    use super::Parser;
+
+    /// The arguments for the backfill client subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The chain identifier hosting the Ethereum light client.
+        /// The first enabled `eth_to_cosmos` module with this destination chain is used.
+        #[clap(long)]
+        pub chain: String,
+        /// The client identifier of the Ethereum light client.
+        #[clap(long)]
+        pub client: String,
+        /// The maximum number of headers per transaction.
+        #[clap(long, default_value_t = crate::backfill::DEFAULT_BATCH_SIZE)]
+        pub batch_size: usize,
+        /// Backfill the client even if it lags further behind than the `max_lookback` of the
+        /// module, for an intentional deep recovery.
+        #[clap(long)]
+        pub deep_scan: bool,
+    }
+}
+
+/// The arguments for the catch-up subcommand.
+pub mod catch_up {
+    use super::Parser;
+
+    /// The arguments for the catch-up subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The chain identifier hosting the Ethereum light client.
+        /// The first enabled `eth_to_cosmos` module with this destination chain is used.
+        #[clap(long)]
+        pub chain: String,
+        /// The client identifier of the Ethereum light client.
+        #[clap(long)]
+        pub client: String,
+        /// The maximum number of transactions, one per sync committee period, produced by a run.
+        #[clap(long, default_value_t = crate::catch_up::DEFAULT_MAX_TXS)]
+        pub max_txs: usize,
+        /// The maximum fees of the whole catch-up, in the smallest unit of the fee denom of the
+        /// module. Requires the `fee` configuration of the module.
+        #[clap(long)]
+        pub fee_budget: Option<u128>,
+        /// The file the progress of the catch-up is persisted to, and resumed from.
+        #[clap(long)]
+        pub progress: String,
+    }
+}
+
+/// The arguments for the verify checksum subcommand.
+pub mod verify_checksum {
+    use super::Parser;
+
+    /// The arguments for the verify checksum subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The configuration file for the relayer.
+        #[clap(short = 'c', long)]
+        pub config: String,
+        /// The chain identifier hosting the Ethereum light client.
+        /// The first enabled `eth_to_cosmos` module with this destination chain is used.
+        #[clap(long)]
+        pub chain: String,
+        /// The client identifier of the Ethereum light client.
+        #[clap(long)]
+        pub client: String,
+        /// An optional registry file with releases missing from the embedded registry.
+        #[clap(long)]
+        pub registry: Option<String>,
+    }
+}
+
+/// The arguments for the state subcommand.
+pub mod state {
+    use super::Parser;
+
+    /// The arguments for the verify checksum subcommand.
+    pub mod verify_checksum {
+        use super::Parser;
+
+        /// The arguments for the verify checksum subcommand.
+        #[derive(Clone, Debug, Parser)]
+        pub struct Args {
+            /// The configuration file for the relayer.
+            #[clap(short = 'c', long)]
+            pub config: String,
+            /// The chain identifier hosting the Ethereum light client.
+            /// The first enabled `eth_to_cosmos` module with this destination chain is used.
+            #[clap(long)]
+            pub chain: String,
+            /// The client identifier of the Ethereum light client.
+            #[clap(long)]
+            pub client: String,
+            /// An optional registry file with releases missing from the embedded registry.
+            #[clap(long)]
+            pub registry: Option<String>,
+        }
+    }
+
+    /// The arguments for the state subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The state action to run.
+        #[command(subcommand)]
+        pub command: Commands,
+    }
+
+    /// The state actions.
+    #[derive(Clone, Debug, Parser)]
+    pub enum Commands {
+        /// Writes the state of a running relayer to a snapshot file.
+        Export {
+            /// The metrics server endpoint of the relayer.
+            #[clap(long, default_value = "http://localhost:9000")]
+            endpoint: String,
+            /// The snapshot file to write.
+            #[clap(short = 'o', long)]
+            output: String,
+        },
+        /// Replaces the state of a running relayer with a snapshot file.
+        /// The relayer must be started with the same modules configuration as the exported one.
+        Import {
+            /// The metrics server endpoint of the relayer.
+            #[clap(long, default_value = "http://localhost:9000")]
+            endpoint: String,
+            /// The snapshot file to read.
+            #[clap(short = 'i', long)]
+            input: String,
+        },
+    }
+}
+
+/// The arguments for the sync committees subcommand.
+pub mod sync_committees {
+    use super::Parser;
+
+    /// The arguments for the sync committees subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The sync committees action to run.
+        #[command(subcommand)]
+        pub command: Commands,
+    }
+
+    /// The sync committees actions.
+    #[derive(Clone, Debug, Parser)]
+    pub enum Commands {
+        /// Writes the sync committees verified by a running relayer to a snapshot file.
+        Export {
+            /// The metrics server endpoint of the relayer.
+            #[clap(long, default_value = "http://localhost:9000")]
+            endpoint: String,
+            /// The snapshot file to write.
+            #[clap(short = 'o', long)]
+            output: String,
+        },
+        /// Merges the sync committees of a snapshot file into a running relayer.
+        Import {
+            /// The metrics server endpoint of the relayer.
+            #[clap(long, default_value = "http://localhost:9000")]
+            endpoint: String,
+            /// The snapshot file to read.
+            #[clap(short = 'i', long)]
+            input: String,
+        },
+    }
+}
+
+/// The arguments for the escrow subcommand.
+pub mod escrow {
+    use super::Parser;
+
+    /// The arguments for the escrow subcommand.
+    #[derive(Clone, Debug, Parser)]
+    pub struct Args {
+        /// The escrow action to run.
+        #[command(subcommand)]
+        pub command: Commands,
+    }
+
+    /// The escrow actions.
+    #[derive(Clone, Debug, Parser)]
+    pub enum Commands {
+        /// Decrypts the share of a committee member for a sealed relay of a running relayer, and
+        /// submits it to the relayer.
+        SubmitShare {
+            /// The metrics server endpoint of the relayer.
+            #[clap(long, default_value = "http://localhost:9000")]
+            endpoint: String,
+            /// The identifier of the sealed relay.
+            #[clap(long)]
+            artifact_id: String,
+            /// The file holding the hex encoded secp256k1 private key of the member.
+            #[clap(long)]
+            private_key_path: String,
+        },
+        /// Decrypts the share of a committee member from a sealed artifact file.
+        DecryptShare {
+            /// The sealed artifact file, as served by the escrow API.
+            #[clap(long)]
+            artifact: String,
+            /// The file holding the hex encoded secp256k1 private key of the member.
+            #[clap(long)]
+            private_key_path: String,
+            /// The share file to write.
+            #[clap(short = 'o', long)]
+            output: String,
+        },
+        /// Decrypts a sealed artifact file with its released data key, or with the shares of
+        /// its committee.
+        Open {
+            /// The sealed artifact file, as served by the escrow API.
+            #[clap(long)]
+            artifact: String,
+            /// The share files of the committee members, if the data key is not released.
+            #[clap(long)]
+            share: Vec<String>,
+            /// The relay file to write.
+            #[clap(short = 'o', long)]
+            output: String,
+        },
     }
 }
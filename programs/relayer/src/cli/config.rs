@@ -1,10 +1,12 @@
 //! Defines the top level configuration for the relayer.
 
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 use serde_json::Value;
 use tracing::Level;
 
+use crate::core::{archive::PacketArchiveConfig, artifact::ArtifactSignerConfig};
+
 /// The top level configuration for the relayer.
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 #[allow(clippy::module_name_repetitions)]
@@ -45,6 +47,17 @@ pub struct ServerConfig {
     /// The log level for the server.
     #[serde(default)]
     pub log_level: String,
+    /// The signer of the relay artifacts, for a proving relayer that hands its artifacts off to a
+    /// broadcasting relayer.
+    #[serde(default)]
+    pub artifact_signer: Option<ArtifactSignerConfig>,
+    /// The archive of the pre-images of the relayed packets. Packets are not archived if unset.
+    #[serde(default)]
+    pub packet_archive: Option<PacketArchiveConfig>,
+    /// The path to the file holding the token operators approve the sealed relays of the artifact
+    /// escrow with. The approvals are rejected if unset.
+    #[serde(default)]
+    pub escrow_operator_token_path: Option<PathBuf>,
 }
 
 /// Returns true, used as a default value for boolean fields.
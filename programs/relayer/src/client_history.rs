@@ -0,0 +1,365 @@
+//! Defines the export of the history of an Ethereum light client run by
+//! `relayer export-client-history`, an archive of the client for compliance and security audits.
+//!
+//! The archive holds every consensus state stored by the client, with its slot, roots and
+//! timestamp, and the update that created it. The relayer keeps no record of the updates it
+//! submits, so they are read back from the transactions of the chain hosting the client: ibc-go
+//! emits an `update_client` event for every `MsgUpdateClient`, listing the consensus heights it
+//! created. The updates are only found on nodes indexing their transactions, and the consensus
+//! states whose update was pruned by the node, or that were stored with the client, are exported
+//! without one.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy::hex;
+use anyhow::Result;
+use ibc_eureka_relayer_lib::{authz::unwrap_msg_exec, tx_builder::eth_to_cosmos};
+use ibc_proto_eureka::{
+    cosmos::{base::query::v1beta1::PageRequest, tx::v1beta1::Tx},
+    ibc::{
+        core::client::v1::{
+            MsgUpdateClient, QueryConsensusStateHeightsRequest, QueryConsensusStateHeightsResponse,
+        },
+        lightclients::wasm::v1::ClientMessage,
+    },
+};
+use prost::Message;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tendermint::abci::Event as TmEvent;
+use tendermint_rpc::{query::Query, Client, HttpClient, Order};
+
+use crate::{
+    cli::config::RelayerConfig,
+    core::modules::RelayerModule,
+    doctor::abci_query,
+    modules::{
+        connect_eth_provider,
+        eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+    },
+};
+
+/// The path of the consensus state heights query of ibc-go.
+const CONSENSUS_STATE_HEIGHTS_PATH: &str = "/ibc.core.client.v1.Query/ConsensusStateHeights";
+/// The type URL of `MsgUpdateClient`.
+const MSG_UPDATE_CLIENT_TYPE_URL: &str = "/ibc.core.client.v1.MsgUpdateClient";
+/// The type of the event emitted by ibc-go for every client update.
+const EVENT_TYPE_UPDATE_CLIENT: &str = "update_client";
+/// The attribute of the client identifier of an `update_client` event.
+const ATTRIBUTE_KEY_CLIENT_ID: &str = "client_id";
+/// The attribute of the comma separated consensus heights of an `update_client` event.
+const ATTRIBUTE_KEY_CONSENSUS_HEIGHTS: &str = "consensus_heights";
+/// The number of consensus heights or transactions fetched per query.
+const PAGE_SIZE: u8 = 100;
+
+/// The update of a light client that created some of its consensus states.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ClientUpdate {
+    /// The hash of the transaction of the update.
+    pub tx_hash: String,
+    /// The height of the block the transaction was included in.
+    pub height: u64,
+    /// The signer of the `MsgUpdateClient`.
+    pub signer: String,
+    /// The hex encoded client message of the update, as submitted to the light client.
+    pub client_message: String,
+}
+
+/// Exports the history of the Ethereum light client `client_id` hosted on `chain`: its client
+/// state, and every consensus state it stores with the update that created it.
+///
+/// The first enabled `eth_to_cosmos` module whose destination chain is `chain` is used to connect
+/// to the chain.
+/// # Errors
+/// Returns an error if no suitable module is configured, or if the states or the updates of the
+/// client cannot be fetched.
+pub async fn export_client_history(
+    config: &RelayerConfig,
+    chain: &str,
+    client_id: &str,
+) -> Result<Value> {
+    let module = config
+        .modules
+        .iter()
+        .find(|module| {
+            module.enabled
+                && module.dst_chain == chain
+                && module.name == EthToCosmosRelayerModule.name()
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No enabled `{}` module with destination chain `{chain}`",
+                EthToCosmosRelayerModule.name()
+            )
+        })?;
+    let config: EthToCosmosConfig = serde_json::from_value(module.config.clone())?;
+
+    let provider = connect_eth_provider(
+        &config.eth_rpc_url,
+        &config.eth_rpc_fallback_urls,
+        &config.http_client,
+        config.circuit_breaker,
+    )
+    .await?;
+    let tm_client = HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?;
+    let tx_builder = eth_to_cosmos::TxBuilder::new(
+        config.ics26_address,
+        provider,
+        config.eth_beacon_api_url,
+        tm_client.clone(),
+        config.signer_address,
+        eth_to_cosmos::TxBuilderConfig::default(),
+    );
+
+    let client_state = tx_builder
+        .ethereum_client_state(client_id.to_string())
+        .await?;
+    let slots = consensus_state_slots(&tm_client, client_id).await?;
+    let mut updates = client_updates(&tm_client, client_id).await?;
+
+    let mut consensus_states = Vec::with_capacity(slots.len());
+    for slot in slots {
+        let consensus_state = tx_builder
+            .ethereum_consensus_state(client_id.to_string(), client_state.revision_number, slot)
+            .await?;
+        consensus_states.push(json!({
+            "slot": slot,
+            "consensus_state_hash": consensus_state.canonical_hash()?,
+            "consensus_state": consensus_state,
+            "update": updates.remove(&slot),
+        }));
+    }
+
+    Ok(json!({
+        "client_id": client_id,
+        "chain": chain,
+        "client_type": "ethereum",
+        "client_state_hash": client_state.canonical_hash()?,
+        "client_state": client_state,
+        "consensus_states": consensus_states,
+    }))
+}
+
+/// Returns the slots of the consensus states stored by the client, in ascending order.
+async fn consensus_state_slots(tm_client: &HttpClient, client_id: &str) -> Result<Vec<u64>> {
+    let mut slots = BTreeSet::new();
+    let mut next_key = Vec::new();
+    loop {
+        let response = abci_query::<_, QueryConsensusStateHeightsResponse>(
+            tm_client,
+            CONSENSUS_STATE_HEIGHTS_PATH,
+            &QueryConsensusStateHeightsRequest {
+                client_id: client_id.to_string(),
+                pagination: Some(PageRequest {
+                    key: next_key,
+                    limit: PAGE_SIZE.into(),
+                    ..Default::default()
+                }),
+            },
+        )
+        .await?;
+        slots.extend(
+            response
+                .consensus_state_heights
+                .iter()
+                .map(|height| height.revision_height),
+        );
+
+        match response.pagination {
+            Some(page) if !page.next_key.is_empty() => next_key = page.next_key,
+            _ => return Ok(slots.into_iter().collect()),
+        }
+    }
+}
+
+/// Returns the updates of the client found in the transactions indexed by the chain, by the slot
+/// of the consensus states they created.
+async fn client_updates(
+    tm_client: &HttpClient,
+    client_id: &str,
+) -> Result<BTreeMap<u64, ClientUpdate>> {
+    let query = Query::eq(
+        format!("{EVENT_TYPE_UPDATE_CLIENT}.{ATTRIBUTE_KEY_CLIENT_ID}"),
+        client_id,
+    );
+
+    let mut updates = BTreeMap::new();
+    let mut fetched = 0_u32;
+    for page in 1.. {
+        let response = tm_client
+            .tx_search(query.clone(), false, page, PAGE_SIZE, Order::Ascending)
+            .await?;
+        let page_len = u32::try_from(response.txs.len())?;
+        fetched = fetched.saturating_add(page_len);
+
+        for tx in response.txs {
+            let msgs = update_client_msgs(&tx.tx, client_id)?;
+            let slots_per_msg = created_slots(&tx.tx_result.events, client_id);
+            for (msg, slots) in msgs.into_iter().zip(slots_per_msg) {
+                let client_message = msg
+                    .client_message
+                    .map(|any| ClientMessage::decode(any.value.as_slice()))
+                    .transpose()?
+                    .unwrap_or_default();
+                let update = ClientUpdate {
+                    tx_hash: tx.hash.to_string(),
+                    height: tx.height.value(),
+                    signer: msg.signer,
+                    client_message: hex::encode(client_message.data),
+                };
+                // A consensus state is only created by its first update
+                for slot in slots {
+                    updates.entry(slot).or_insert_with(|| update.clone());
+                }
+            }
+        }
+
+        if page_len == 0 || fetched >= response.total_count {
+            break;
+        }
+    }
+    Ok(updates)
+}
+
+/// Returns the `MsgUpdateClient`s of the client in an encoded transaction, including the ones
+/// executed through authz, in the order they were executed.
+/// # Errors
+/// Returns an error if the transaction or its messages cannot be decoded.
+pub fn update_client_msgs(tx: &[u8], client_id: &str) -> Result<Vec<MsgUpdateClient>> {
+    let msgs = Tx::decode(tx)?.body.unwrap_or_default().messages;
+    let update_client_msgs = unwrap_msg_exec(msgs)?
+        .into_iter()
+        .filter(|msg| msg.type_url == MSG_UPDATE_CLIENT_TYPE_URL)
+        .map(|msg| MsgUpdateClient::decode(msg.value.as_slice()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(update_client_msgs
+        .into_iter()
+        .filter(|msg| msg.client_id == client_id)
+        .collect())
+}
+
+/// Returns the slots of the consensus states created by each update of the client, according to
+/// the `update_client` events of a transaction, in the order of the updates.
+#[must_use]
+pub fn created_slots(events: &[TmEvent], client_id: &str) -> Vec<Vec<u64>> {
+    events
+        .iter()
+        .filter(|event| event.kind == EVENT_TYPE_UPDATE_CLIENT)
+        .filter_map(|event| {
+            let attribute = |key: &str| {
+                event
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key_str().is_ok_and(|k| k == key))
+                    .and_then(|attr| attr.value_str().ok())
+            };
+            if attribute(ATTRIBUTE_KEY_CLIENT_ID)? != client_id {
+                return None;
+            }
+            Some(
+                attribute(ATTRIBUTE_KEY_CONSENSUS_HEIGHTS)
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter_map(|height| height.split_once('-')?.1.parse().ok())
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use ibc_eureka_relayer_lib::authz::wrap_in_msg_exec;
+    use ibc_proto_eureka::{cosmos::tx::v1beta1::TxBody, google::protobuf::Any};
+
+    use super::*;
+
+    fn update_client(client_id: &str, signer: &str) -> Any {
+        Any {
+            type_url: MSG_UPDATE_CLIENT_TYPE_URL.to_string(),
+            value: MsgUpdateClient {
+                client_id: client_id.to_string(),
+                client_message: None,
+                signer: signer.to_string(),
+            }
+            .encode_to_vec(),
+        }
+    }
+
+    fn tx(body: TxBody) -> Vec<u8> {
+        Tx {
+            body: Some(body),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn test_update_client_msgs() {
+        let body = TxBody {
+            messages: vec![
+                update_client("08-wasm-0", "cosmos1a"),
+                update_client("08-wasm-1", "cosmos1b"),
+                Any {
+                    type_url: "/ibc.core.channel.v2.MsgRecvPacket".to_string(),
+                    value: vec![],
+                },
+                update_client("08-wasm-0", "cosmos1c"),
+            ],
+            ..Default::default()
+        };
+        let signers = |tx: &[u8]| {
+            update_client_msgs(tx, "08-wasm-0")
+                .unwrap()
+                .into_iter()
+                .map(|msg| msg.signer)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(signers(&tx(body.clone())), ["cosmos1a", "cosmos1c"]);
+
+        // The updates submitted through authz
+        let wrapped = wrap_in_msg_exec(&body.encode_to_vec(), "cosmos1grantee").unwrap();
+        assert_eq!(
+            signers(&tx(TxBody::decode(wrapped.as_slice()).unwrap())),
+            ["cosmos1a", "cosmos1c"]
+        );
+
+        assert!(update_client_msgs(&[0xff], "08-wasm-0").is_err());
+    }
+
+    #[test]
+    fn test_created_slots() {
+        let events = [
+            TmEvent::new(
+                EVENT_TYPE_UPDATE_CLIENT,
+                [
+                    (ATTRIBUTE_KEY_CLIENT_ID, "08-wasm-0"),
+                    (ATTRIBUTE_KEY_CONSENSUS_HEIGHTS, "0-8192,0-16384"),
+                ],
+            ),
+            TmEvent::new(
+                EVENT_TYPE_UPDATE_CLIENT,
+                [
+                    (ATTRIBUTE_KEY_CLIENT_ID, "08-wasm-1"),
+                    (ATTRIBUTE_KEY_CONSENSUS_HEIGHTS, "0-100"),
+                ],
+            ),
+            TmEvent::new("message", [("action", MSG_UPDATE_CLIENT_TYPE_URL)]),
+            // A duplicate update creates no consensus state
+            TmEvent::new(
+                EVENT_TYPE_UPDATE_CLIENT,
+                [
+                    (ATTRIBUTE_KEY_CLIENT_ID, "08-wasm-0"),
+                    (ATTRIBUTE_KEY_CONSENSUS_HEIGHTS, ""),
+                ],
+            ),
+        ];
+
+        assert_eq!(
+            created_slots(&events, "08-wasm-0"),
+            vec![vec![8192, 16384], vec![]]
+        );
+        assert!(created_slots(&events, "08-wasm-2").is_empty());
+    }
+}
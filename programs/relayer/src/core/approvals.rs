@@ -0,0 +1,157 @@
+//! Defines the approval API for relays held back by the value at risk policies of the modules.
+//!
+//! The pending relays are listed with `GET /approvals` and approved with
+//! `POST /approvals/{relay_id}` on the metrics server. Approved relays are admitted the next time
+//! they are requested.
+//!
+//! This module also applies the per transaction value caps of the modules. The packets that do not
+//! fit within the cap are reported in the `x-relay-deferred-sequences` metadata of the `RelayByTx`
+//! response, so that the submitter relays them in a later transaction.
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+use ibc_eureka_relayer_lib::{
+    events::EurekaEventWithHeight,
+    value_limit::{self, PendingRelay, TxValueCap, ValueRateLimiter},
+};
+use tonic::metadata::{MetadataMap, MetadataValue};
+use warp::{http::StatusCode, Filter};
+
+/// The value rate limiters of all the relayer modules.
+static VALUE_LIMITERS: LazyLock<RwLock<Vec<Arc<ValueRateLimiter>>>> =
+    LazyLock::new(RwLock::default);
+
+/// Registers the value rate limiter of a relayer module with the approval API.
+/// # Panics
+/// Panics if the registry lock is poisoned.
+pub fn register_value_limiter(value_limiter: Arc<ValueRateLimiter>) {
+    VALUE_LIMITERS.write().unwrap().push(value_limiter);
+}
+
+/// Returns the value rate limiters of all the relayer modules, in the order of the configuration.
+/// # Panics
+/// Panics if the registry lock is poisoned.
+pub(crate) fn value_limiters() -> Vec<Arc<ValueRateLimiter>> {
+    VALUE_LIMITERS.read().unwrap().clone()
+}
+
+/// Returns the relays waiting for an approval across all the relayer modules.
+/// # Panics
+/// Panics if the registry lock is poisoned.
+#[must_use]
+pub fn pending_relays() -> Vec<PendingRelay> {
+    VALUE_LIMITERS
+        .read()
+        .unwrap()
+        .iter()
+        .flat_map(|value_limiter| value_limiter.pending())
+        .collect()
+}
+
+/// Approves a pending relay. Returns `false` if no module has a pending relay with the given id.
+/// # Panics
+/// Panics if the registry lock is poisoned.
+#[must_use]
+pub fn approve_relay(relay_id: &str) -> bool {
+    VALUE_LIMITERS
+        .read()
+        .unwrap()
+        .iter()
+        .any(|value_limiter| value_limiter.approve(relay_id))
+}
+
+/// Checks a relay request against the value at risk policy of a module, if it has one.
+/// # Errors
+/// Returns a `FailedPrecondition` status if the relay requires an approval.
+pub fn admit_relay(
+    value_limiter: Option<&ValueRateLimiter>,
+    relay_id: &str,
+    src_client_id: &str,
+    src_events: &[EurekaEventWithHeight],
+) -> Result<(), tonic::Status> {
+    let Some(value_limiter) = value_limiter else {
+        return Ok(());
+    };
+
+    let transfers = value_limit::src_events_to_transfers(src_events, src_client_id)
+        .map_err(|e| tonic::Status::from_error(e.into()))?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| tonic::Status::from_error(e.into()))?
+        .as_secs();
+
+    value_limiter.admit(relay_id, &transfers, now).map_err(|e| {
+        tracing::warn!("Relay held back for approval: {e}");
+        tonic::Status::failed_precondition(e.to_string())
+    })
+}
+
+/// The gRPC metadata key of the packet sequences deferred to a later transaction.
+pub const DEFERRED_SEQUENCES_METADATA_KEY: &str = "x-relay-deferred-sequences";
+
+/// Applies the per transaction value cap of a module, if it has one, to the source events.
+/// Returns the sequences of the packets deferred to a later transaction.
+/// # Errors
+/// Returns an `InvalidArgument` status if an ICS20 payload cannot be decoded.
+pub fn cap_relay_value(
+    tx_value_cap: Option<&TxValueCap>,
+    src_client_id: &str,
+    src_packet_seqs: &[u64],
+    src_events: &mut Vec<EurekaEventWithHeight>,
+) -> Result<Vec<u64>, tonic::Status> {
+    let Some(tx_value_cap) = tx_value_cap else {
+        return Ok(vec![]);
+    };
+
+    let deferred = tx_value_cap
+        .apply(src_events, src_client_id, src_packet_seqs)
+        .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+    if !deferred.is_empty() {
+        tracing::info!(
+            sequences = ?deferred,
+            "Deferring packets exceeding the value cap of the transaction."
+        );
+    }
+    Ok(deferred)
+}
+
+/// Attaches the deferred packet sequences, if any, to the response metadata.
+/// # Panics
+/// Panics if the sequences are not a valid metadata value, which cannot happen.
+pub fn insert_deferred_sequences(deferred: &[u64], metadata: &mut MetadataMap) {
+    if deferred.is_empty() {
+        return;
+    }
+
+    let value = deferred
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    metadata.insert(
+        DEFERRED_SEQUENCES_METADATA_KEY,
+        MetadataValue::try_from(value).unwrap(),
+    );
+}
+
+/// Returns the routes of the approval API.
+#[must_use]
+pub fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let list = warp::path!("approvals")
+        .and(warp::get())
+        .map(|| warp::reply::json(&pending_relays()));
+
+    let approve = warp::path!("approvals" / String)
+        .and(warp::post())
+        .map(|relay_id: String| {
+            let status = if approve_relay(&relay_id) {
+                tracing::info!(%relay_id, "Relay approved.");
+                StatusCode::OK
+            } else {
+                StatusCode::NOT_FOUND
+            };
+            warp::reply::with_status(warp::reply::json(&relay_id), status)
+        });
+
+    list.or(approve)
+}
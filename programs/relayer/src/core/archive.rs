@@ -0,0 +1,156 @@
+//! Defines the archival of the pre-images of the relayed packets.
+//!
+//! A relayer configured with a `packet_archive` keeps the pre-image of every packet of the relay
+//! requests, keyed by its commitment, so that operators can answer what exactly was in a packet
+//! after the chains pruned it. The archived packets are read back with the `inspect-packet`
+//! command. Archival is best effort: a relay never fails because its packets could not be archived.
+//!
+//! The archive is also the only record of the packets orphaned by a frozen or removed
+//! counterparty client, whose timeouts are built from the archived pre-images.
+
+use std::{
+    path::PathBuf,
+    sync::{LazyLock, RwLock},
+};
+
+use alloy::primitives::B256;
+use anyhow::Context;
+use ibc_eureka_relayer_lib::{
+    events::EurekaEventWithHeight,
+    packet_archive::{OutstandingPackets, PacketArchive, PacketPreimage},
+};
+
+use crate::cli::config::RelayerConfig;
+
+/// The configuration of the packet archive.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PacketArchiveConfig {
+    /// The directory the packet pre-images are stored in.
+    pub dir: PathBuf,
+}
+
+/// The packet archive of the relayer, if configured.
+static PACKET_ARCHIVE: LazyLock<RwLock<Option<PacketArchive>>> = LazyLock::new(RwLock::default);
+
+/// Opens the packet archive of the relayer.
+/// # Errors
+/// Returns an error if the archive directory cannot be created.
+/// # Panics
+/// Panics if the lock is poisoned.
+pub fn open_packet_archive(config: &PacketArchiveConfig) -> anyhow::Result<()> {
+    let archive = PacketArchive::open(&config.dir)?;
+    tracing::info!(
+        "Archiving the packet pre-images in {}",
+        archive.dir().display()
+    );
+    *PACKET_ARCHIVE.write().unwrap() = Some(archive);
+    Ok(())
+}
+
+/// Archives the pre-images of the packets of the events emitted on `chain_id`, if the relayer has
+/// a packet archive.
+/// # Panics
+/// Panics if the lock is poisoned.
+pub fn archive_packets(
+    events: &[EurekaEventWithHeight],
+    chain_id: &str,
+    counterparty_chain_id: &str,
+) {
+    let archive = PACKET_ARCHIVE.read().unwrap();
+    let Some(archive) = archive.as_ref() else {
+        return;
+    };
+
+    let archived = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(anyhow::Error::from)
+        .and_then(|now| {
+            archive.archive_events(events, chain_id, counterparty_chain_id, now.as_secs())
+        });
+    match archived {
+        Ok(0) => {}
+        Ok(archived) => tracing::debug!(%chain_id, "Archived {archived} packet pre-images."),
+        Err(e) => tracing::warn!(%chain_id, "Failed to archive the packet pre-images: {e}"),
+    }
+}
+
+/// Returns the archived pre-image of a packet commitment, from the packet archive of the
+/// configuration.
+/// # Errors
+/// Returns an error if the relayer has no packet archive, if the commitment is invalid or not
+/// archived, or if its pre-image cannot be read.
+pub fn inspect_packet(config: &RelayerConfig, commitment: &str) -> anyhow::Result<PacketPreimage> {
+    let packet_archive = config
+        .server
+        .packet_archive
+        .as_ref()
+        .context("the relayer has no packet archive configured")?;
+    let commitment: B256 = commitment
+        .parse()
+        .with_context(|| format!("invalid packet commitment {commitment}"))?;
+
+    PacketArchive::open(&packet_archive.dir)?
+        .get(commitment)?
+        .with_context(|| format!("packet commitment {commitment} is not archived"))
+}
+
+/// Returns the send events of the archived packets of the commitments, for the packets to be
+/// timed out whose transactions are not known.
+/// # Errors
+/// Returns an error if the relayer has no packet archive, or if a commitment is invalid or not
+/// archived.
+/// # Panics
+/// Panics if the lock is poisoned.
+pub fn archived_send_events(commitments: &[Vec<u8>]) -> anyhow::Result<Vec<EurekaEventWithHeight>> {
+    if commitments.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let archive = PACKET_ARCHIVE.read().unwrap();
+    let archive = archive
+        .as_ref()
+        .context("the relayer has no packet archive configured")?;
+    commitments
+        .iter()
+        .map(|commitment| {
+            let commitment = B256::try_from(commitment.as_slice()).with_context(|| {
+                format!(
+                    "invalid packet commitment {}",
+                    alloy::hex::encode(commitment)
+                )
+            })?;
+            let preimage = archive
+                .get(commitment)?
+                .with_context(|| format!("packet commitment {commitment} is not archived"))?;
+            Ok(preimage.send_event())
+        })
+        .collect()
+}
+
+/// Returns the archived packets sent on `chain_id` from `client_id` to `counterparty_client_id`.
+/// At most `max_packets` timed out packets are returned.
+/// # Errors
+/// Returns an error if the relayer has no packet archive, or if the archive cannot be read.
+/// # Panics
+/// Panics if the lock is poisoned.
+pub fn outstanding_packets(
+    chain_id: &str,
+    client_id: &str,
+    counterparty_client_id: &str,
+    max_packets: usize,
+) -> anyhow::Result<OutstandingPackets> {
+    let archive = PACKET_ARCHIVE.read().unwrap();
+    let archive = archive
+        .as_ref()
+        .context("the relayer has no packet archive configured")?;
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+
+    Ok(OutstandingPackets::select(
+        archive.list()?,
+        chain_id,
+        client_id,
+        counterparty_client_id,
+        now.as_secs(),
+        max_packets,
+    ))
+}
@@ -0,0 +1,128 @@
+//! Defines the signed handoff of relay artifacts between a proving and a broadcasting relayer.
+//!
+//! A proving relayer configured with an artifact signer signs the transactions it produces, and
+//! attaches the signature to the gRPC response metadata. A broadcasting relayer forwards the
+//! requests to the proving relayer with the `remote` module, and only returns the transactions
+//! signed by one of its trusted signers. The signature commits to the request, so an artifact
+//! cannot be replayed as the response to another request.
+
+use std::path::PathBuf;
+
+use alloy::{
+    primitives::{keccak256, Address, PrimitiveSignature, B256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol_types::SolValue,
+};
+use tonic::metadata::{MetadataMap, MetadataValue};
+
+/// The gRPC metadata key of the artifact signature.
+pub const ARTIFACT_SIGNATURE_METADATA_KEY: &str = "x-relayer-artifact-signature-bin";
+
+/// The domain separator of the artifact digests.
+const ARTIFACT_DOMAIN: &str = "ibc-eureka-relayer-artifact/v1";
+
+/// The configuration of the artifact signer of a proving relayer.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ArtifactSignerConfig {
+    /// The path to the file holding the hex encoded secp256k1 private key of the signer.
+    pub private_key_path: PathBuf,
+}
+
+/// The request a relay artifact was produced for.
+pub struct ArtifactRequest<'a> {
+    /// The name of the gRPC method.
+    pub method: &'a str,
+    /// The source chain identifier.
+    pub src_chain: &'a str,
+    /// The target chain identifier.
+    pub dst_chain: &'a str,
+    /// The protobuf encoding of the request.
+    pub request: &'a [u8],
+}
+
+impl ArtifactRequest<'_> {
+    /// Returns the digest signed for the artifact produced for this request.
+    #[must_use]
+    pub fn digest(&self, tx: &[u8], address: &str) -> B256 {
+        keccak256(
+            (
+                ARTIFACT_DOMAIN,
+                self.method,
+                self.src_chain,
+                self.dst_chain,
+                keccak256(self.request),
+                tx,
+                address,
+            )
+                .abi_encode(),
+        )
+    }
+}
+
+/// Signs the relay artifacts of a proving relayer.
+pub struct ArtifactSigner {
+    signer: PrivateKeySigner,
+}
+
+impl ArtifactSigner {
+    /// Loads the artifact signer from its configuration.
+    /// # Errors
+    /// Returns an error if the private key cannot be read or decoded.
+    pub fn from_config(config: &ArtifactSignerConfig) -> anyhow::Result<Self> {
+        let private_key = std::fs::read_to_string(&config.private_key_path)?;
+        let signer = private_key.trim().parse::<PrivateKeySigner>()?;
+        tracing::info!(signer = %signer.address(), "Signing relay artifacts.");
+        Ok(Self { signer })
+    }
+
+    /// Signs the artifact produced for a request, and attaches the signature to the response
+    /// metadata.
+    /// # Errors
+    /// Returns an error if the artifact cannot be signed.
+    pub fn sign(
+        &self,
+        request: &ArtifactRequest<'_>,
+        tx: &[u8],
+        address: &str,
+        metadata: &mut MetadataMap,
+    ) -> anyhow::Result<()> {
+        let signature = self.signer.sign_hash_sync(&request.digest(tx, address))?;
+        metadata.insert_bin(
+            ARTIFACT_SIGNATURE_METADATA_KEY,
+            MetadataValue::from_bytes(&signature.as_bytes()),
+        );
+        Ok(())
+    }
+}
+
+/// Verifies the signatures of the relay artifacts received from a proving relayer.
+/// # Errors
+/// Returns a `PermissionDenied` status if the artifact is not signed by one of the trusted signers.
+pub fn verify_artifact(
+    trusted_signers: &[Address],
+    request: &ArtifactRequest<'_>,
+    tx: &[u8],
+    address: &str,
+    metadata: &MetadataMap,
+) -> Result<Address, tonic::Status> {
+    let signature = metadata
+        .get_bin(ARTIFACT_SIGNATURE_METADATA_KEY)
+        .ok_or_else(|| tonic::Status::permission_denied("relay artifact is not signed"))?
+        .to_bytes()
+        .map_err(|e| {
+            tonic::Status::permission_denied(format!("invalid artifact signature: {e}"))
+        })?;
+    let signer = PrimitiveSignature::try_from(signature.as_ref())
+        .and_then(|signature| signature.recover_address_from_prehash(&request.digest(tx, address)))
+        .map_err(|e| {
+            tonic::Status::permission_denied(format!("invalid artifact signature: {e}"))
+        })?;
+
+    if !trusted_signers.contains(&signer) {
+        return Err(tonic::Status::permission_denied(format!(
+            "relay artifact signed by untrusted signer {signer}"
+        )));
+    }
+
+    Ok(signer)
+}
@@ -1,6 +1,6 @@
 //! Defines the `RelayerBuilder` struct that is used to build the relayer server.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     api::{
@@ -9,9 +9,16 @@ use crate::{
     },
     cli::config::RelayerConfig,
 };
-use tonic::{transport::Server, Request, Response};
+use prost::Message;
+use tonic::{metadata::MetadataMap, transport::Server, Request, Response};
 
-use super::modules::RelayerModule;
+use super::{
+    archive,
+    artifact::{ArtifactRequest, ArtifactSigner},
+    escrow,
+    modules::RelayerModule,
+    scheduler,
+};
 
 /// The `RelayerBuilder` struct is used to build the relayer.
 #[derive(Default)]
@@ -26,6 +33,8 @@ pub struct RelayerBuilder {
 struct Relayer {
     /// Mapping of (`src_chain`, `dst_chain`) to the relayer service.
     services: HashMap<(String, String), Box<dyn RelayerService>>,
+    /// The signer of the relay artifacts, if the relayer hands them off to a broadcasting relayer.
+    artifact_signer: Option<ArtifactSigner>,
 }
 
 impl RelayerBuilder {
@@ -59,7 +68,20 @@ impl RelayerBuilder {
             .register_encoded_file_descriptor_set(api::FILE_DESCRIPTOR_SET)
             .build_v1()?; // Build the reflection service
 
-        let mut relayer = Relayer::default();
+        if let Some(packet_archive) = &config.server.packet_archive {
+            archive::open_packet_archive(packet_archive)?;
+            escrow::open_escrow_store(&packet_archive.dir)?;
+        }
+
+        let mut relayer = Relayer {
+            artifact_signer: config
+                .server
+                .artifact_signer
+                .as_ref()
+                .map(ArtifactSigner::from_config)
+                .transpose()?,
+            ..Default::default()
+        };
         // Iterate through all configured modules
         for c in config.modules.into_iter().filter(|c| c.enabled) {
             let module =
@@ -73,10 +95,14 @@ impl RelayerBuilder {
             );
         }
 
+        // Build the scheduled ack relays through the router
+        let relayer = Arc::new(relayer);
+        tokio::spawn(scheduler::run(relayer.clone()));
+
         // Start the gRPC server
         tracing::info!("Started gRPC server on {}", socket_addr);
         Server::builder()
-            .add_service(RelayerServiceServer::new(relayer))
+            .add_service(RelayerServiceServer::from_arc(relayer))
             .add_service(reflection_service)
             .serve(socket_addr)
             .await?;
@@ -101,6 +127,26 @@ impl Relayer {
             })
     }
 
+    /// Signs the artifact of a response, if the relayer has an artifact signer.
+    fn sign_artifact(
+        &self,
+        request: &ArtifactRequest<'_>,
+        tx: &[u8],
+        address: &str,
+        metadata: &mut MetadataMap,
+    ) -> Result<(), tonic::Status> {
+        let Some(artifact_signer) = &self.artifact_signer else {
+            return Ok(());
+        };
+
+        artifact_signer
+            .sign(request, tx, address, metadata)
+            .map_err(|e| {
+                tracing::error!("Failed to sign the relay artifact: {e}");
+                tonic::Status::internal("Failed to sign the relay artifact.")
+            })
+    }
+
     fn add_module(
         &mut self,
         src_chain: String,
@@ -144,15 +190,38 @@ impl RelayerService for Relayer {
         let src_chain = inner_request.src_chain.clone();
         let dst_chain = inner_request.dst_chain.clone();
 
+        let request_bz = inner_request.encode_to_vec();
+        let artifact_request = ArtifactRequest {
+            method: "relay_by_tx",
+            src_chain: &src_chain,
+            dst_chain: &dst_chain,
+            request: &request_bz,
+        };
+
         crate::metrics::track_metrics("relay_by_tx", &src_chain, &dst_chain, || async move {
             let inner_request = request.get_ref();
-            self.get_module(&inner_request.src_chain, &inner_request.dst_chain)?
+            let response = self
+                .get_module(&inner_request.src_chain, &inner_request.dst_chain)?
                 .relay_by_tx(request)
                 .await
                 .map_err(|e| {
                     tracing::error!("Relay by tx request failed: {:?}", e);
+                    // Relays held back by a value at risk policy are reported as is, since the
+                    // caller needs the relay id to request an approval.
+                    if e.code() == tonic::Code::FailedPrecondition {
+                        return e;
+                    }
                     tonic::Status::internal("Failed to relay by tx. See logs for more details.")
-                })
+                })?;
+
+            let (mut metadata, response, extensions) = response.into_parts();
+            self.sign_artifact(
+                &artifact_request,
+                &response.tx,
+                &response.address,
+                &mut metadata,
+            )?;
+            Ok(Response::from_parts(metadata, response, extensions))
         })
         .await
     }
@@ -166,15 +235,33 @@ impl RelayerService for Relayer {
         let src_chain = inner_request.src_chain.clone();
         let dst_chain = inner_request.dst_chain.clone();
 
+        let request_bz = inner_request.encode_to_vec();
+        let artifact_request = ArtifactRequest {
+            method: "create_client",
+            src_chain: &src_chain,
+            dst_chain: &dst_chain,
+            request: &request_bz,
+        };
+
         crate::metrics::track_metrics("create_client", &src_chain, &dst_chain, || async move {
             let inner_request = request.get_ref();
-            self.get_module(&inner_request.src_chain, &inner_request.dst_chain)?
+            let response = self
+                .get_module(&inner_request.src_chain, &inner_request.dst_chain)?
                 .create_client(request)
                 .await
                 .map_err(|e| {
                     tracing::error!("Create client request failed: {:?}", e);
                     tonic::Status::internal("Failed to create client. See logs for more details.")
-                })
+                })?;
+
+            let (mut metadata, response, extensions) = response.into_parts();
+            self.sign_artifact(
+                &artifact_request,
+                &response.tx,
+                &response.address,
+                &mut metadata,
+            )?;
+            Ok(Response::from_parts(metadata, response, extensions))
         })
         .await
     }
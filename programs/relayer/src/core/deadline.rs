@@ -0,0 +1,94 @@
+//! Bounds the relays of the API by the deadline of the requests that triggered them.
+//!
+//! gRPC clients pass the deadline of a request in its `grpc-timeout` header. The relay of a
+//! `RelayByTx` request runs within a [`RelayScope`] bounded by that deadline, so that a relay the
+//! client gave up on is interrupted between its sub-tasks with a retryable `DeadlineExceeded`
+//! error, and a relay whose request is dropped is dropped with all of its sub-tasks. The relays
+//! forwarded to a proving relayer carry the time left to their deadline.
+
+use std::time::Duration;
+
+use ibc_eureka_relayer_lib::relay_scope::RelayScope;
+use tonic::metadata::MetadataMap;
+
+/// The gRPC metadata key of the timeout of a request.
+pub const GRPC_TIMEOUT_METADATA_KEY: &str = "grpc-timeout";
+
+/// Parses the value of a `grpc-timeout` header, an integer of at most 8 digits followed by its
+/// unit, e.g. `30S` or `500m`.
+#[must_use]
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if !value.is_ascii() {
+        return None;
+    }
+    let (amount, unit) = value.split_at(value.len().checked_sub(1)?);
+    if amount.is_empty() || amount.len() > 8 || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount = amount.parse::<u64>().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3_600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Returns the scope of the relay of a request, bounded by its `grpc-timeout` if any.
+#[must_use]
+pub fn relay_scope(metadata: &MetadataMap) -> RelayScope {
+    let Some(value) = metadata.get(GRPC_TIMEOUT_METADATA_KEY) else {
+        return RelayScope::default();
+    };
+    match value.to_str().ok().and_then(parse_grpc_timeout) {
+        Some(timeout) => {
+            tracing::debug!(?timeout, "Bounding the relay by the request deadline.");
+            RelayScope::with_timeout(timeout)
+        }
+        None => {
+            tracing::warn!("Ignoring the malformed {GRPC_TIMEOUT_METADATA_KEY} of the request.");
+            RelayScope::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("30S"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_grpc_timeout("2M"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3_600)));
+        assert_eq!(parse_grpc_timeout("500m"), Some(Duration::from_millis(500)));
+        assert_eq!(
+            parse_grpc_timeout("99999999n"),
+            Some(Duration::from_nanos(99_999_999))
+        );
+
+        assert_eq!(parse_grpc_timeout(""), None);
+        assert_eq!(parse_grpc_timeout("S"), None);
+        assert_eq!(parse_grpc_timeout("30"), None);
+        assert_eq!(parse_grpc_timeout("30s"), None);
+        assert_eq!(parse_grpc_timeout("-1S"), None);
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+    }
+
+    #[test]
+    fn test_relay_scope() {
+        assert_eq!(relay_scope(&MetadataMap::new()).deadline(), None);
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert(GRPC_TIMEOUT_METADATA_KEY, "30S".parse().unwrap());
+        let remaining = relay_scope(&metadata).remaining().unwrap();
+        assert!(remaining > Duration::from_secs(29) && remaining <= Duration::from_secs(30));
+
+        metadata.insert(GRPC_TIMEOUT_METADATA_KEY, "soon".parse().unwrap());
+        assert_eq!(relay_scope(&metadata).deadline(), None);
+    }
+}
@@ -0,0 +1,1344 @@
+//! Defines the escrow of the relays of high-value transfers.
+//!
+//! A module configured with an artifact escrow seals the relays of the transfers at or above its
+//! thresholds instead of returning them. The response of the relay is encrypted with a fresh data
+//! key, and the `RelayByTx` requests for it fail with `FailedPrecondition` until it is revealed.
+//! A revealed relay is returned the next time it is requested, so that the submitter only
+//! broadcasts it once the reveal policy allows it.
+//!
+//! With a `timelock` reveal policy the relayer holds the data key, and reveals the relay once its
+//! delay expired or an operator approved it. With a `committee` reveal policy the data key is
+//! split into Shamir shares, each encrypted to the secp256k1 key of a member of the committee, and
+//! the relayer forgets it. The relay is revealed once `threshold` members submitted their shares.
+//!
+//! The sealed relays are listed with `GET /escrow` and read with `GET /escrow/{artifact_id}` on
+//! the metrics server, which includes the data key once it is released. They are approved with
+//! `POST /escrow/{artifact_id}/approve`, which requires the operator token of the relayer as a
+//! bearer token, and the shares of the committee members are submitted with
+//! `POST /escrow/{artifact_id}/shares`, signed by the key of the member the share is encrypted to.
+//! A relayer without an operator token does not accept approvals.
+//!
+//! A relayer with a packet archive persists the sealed relays in the `escrow` directory of the
+//! archive, along with the data keys it holds and the submitted shares, and reloads them when it
+//! restarts. Without a packet archive, the sealed relays are only kept in memory.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{LazyLock, RwLock},
+};
+
+use alloy::{
+    hex,
+    primitives::{keccak256, Address, Bytes, PrimitiveSignature, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol_types::SolValue,
+};
+use anyhow::{anyhow, ensure, Context};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use ibc_eureka_relayer_lib::{
+    events::EurekaEventWithHeight,
+    value_limit::{self, Transfer},
+};
+use k256::{
+    ecdh::{self, EphemeralSecret},
+    elliptic_curve::sec1::ToEncodedPoint,
+    PublicKey, SecretKey,
+};
+use serde::{Deserialize, Serialize};
+use tonic::{
+    metadata::{Ascii, Binary, KeyAndValueRef, MetadataKey, MetadataMap, MetadataValue},
+    Response,
+};
+use warp::{http::StatusCode, Filter};
+
+use crate::api;
+
+/// The domain separator of the keys encrypting the shares of the committee members.
+const SHARE_KEY_DOMAIN: &str = "ibc-eureka-relayer-escrow-share/v1";
+
+/// The domain separator of the digests signed by the committee members submitting their shares.
+const SHARE_SUBMISSION_DOMAIN: &str = "ibc-eureka-relayer-escrow-share-submission/v1";
+
+/// The size of the data keys and of their shares.
+const KEY_SIZE: usize = 32;
+
+/// The name of the directory of the sealed relays in the persisted state directory.
+pub const ESCROW_DIR_NAME: &str = "escrow";
+
+/// The configuration of the artifact escrow of a relayer module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactEscrowConfig {
+    /// The amount, per denom, at or above which the relay of a transfer is sealed.
+    pub thresholds: HashMap<String, U256>,
+    /// The policy revealing the sealed relays.
+    pub reveal: RevealPolicy,
+}
+
+/// The policy revealing the sealed relays.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum RevealPolicy {
+    /// The relayer holds the data key, and reveals a sealed relay once its delay expired or it
+    /// was approved. Without a delay, a sealed relay is only revealed by an approval.
+    Timelock {
+        /// The delay, in seconds, after which a sealed relay is revealed.
+        #[serde(default)]
+        delay_secs: Option<u64>,
+    },
+    /// The data key is split among the members of a committee, and a sealed relay is revealed
+    /// once `threshold` members submitted their shares.
+    Committee {
+        /// The number of shares revealing a sealed relay.
+        threshold: u8,
+        /// The hex encoded SEC1 secp256k1 public keys of the members.
+        members: Vec<String>,
+    },
+}
+
+impl ArtifactEscrowConfig {
+    /// Validates the reveal policy.
+    /// # Errors
+    /// Returns an error if the threshold of the committee cannot be met, or if the key of a
+    /// member cannot be decoded.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let RevealPolicy::Committee { threshold, members } = &self.reveal {
+            ensure!(
+                *threshold >= 1 && usize::from(*threshold) <= members.len(),
+                "the committee threshold {threshold} is not within 1 and the {} members",
+                members.len()
+            );
+            ensure!(members.len() < 256, "a committee has at most 255 members");
+            for member in members {
+                decode_public_key(member)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sealed relay, as published by the escrow API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedArtifact {
+    /// The identifier of the artifact, which is the identifier of the relay.
+    pub artifact_id: String,
+    /// The source chain identifier.
+    pub src_chain: String,
+    /// The target chain identifier.
+    pub dst_chain: String,
+    /// The transfers at or above the thresholds of the escrow.
+    pub transfers: Vec<Transfer>,
+    /// The unix timestamp at which the relay was sealed.
+    pub sealed_at: u64,
+    /// The unix timestamp at which the relay is revealed, if it has a timelock.
+    pub reveal_at: Option<u64>,
+    /// The nonce of the encryption of the relay.
+    pub nonce: Bytes,
+    /// The encrypted relay, authenticated with the artifact identifier.
+    pub ciphertext: Bytes,
+    /// The number of shares revealing the relay, if the data key is held by a committee.
+    pub threshold: Option<u8>,
+    /// The encrypted shares of the data key, one per committee member.
+    pub key_shares: Vec<KeyShare>,
+    /// The data key, once it is released.
+    pub key: Option<B256>,
+}
+
+/// The share of a data key encrypted to a committee member.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyShare {
+    /// The index of the share, which is its evaluation point.
+    pub index: u8,
+    /// The hex encoded public key of the member.
+    pub member: String,
+    /// The SEC1 public key of the ephemeral key the share is encrypted with.
+    pub ephemeral_key: Bytes,
+    /// The encrypted share.
+    pub ciphertext: Bytes,
+    /// The digest of the share, checked when the share is submitted.
+    pub digest: B256,
+}
+
+/// A share of a data key submitted by a committee member.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareSubmission {
+    /// The index of the share.
+    pub index: u8,
+    /// The decrypted share.
+    pub share: B256,
+}
+
+/// A share submitted to the escrow API, signed by the committee member it is encrypted to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedShareSubmission {
+    /// The submitted share.
+    #[serde(flatten)]
+    pub submission: ShareSubmission,
+    /// The signature of the member over the share and the artifact identifier.
+    pub signature: Bytes,
+}
+
+/// The plaintext of a sealed relay.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedRelay {
+    /// The relay transaction.
+    pub tx: Bytes,
+    /// The address of the target contract, if any.
+    pub address: String,
+    /// The metadata of the relay response, such as the following transactions.
+    pub metadata: Vec<SealedMetadata>,
+}
+
+/// An entry of the metadata of a sealed relay.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum SealedMetadata {
+    /// An ASCII metadata entry.
+    Ascii {
+        /// The key of the entry.
+        key: String,
+        /// The value of the entry.
+        value: String,
+    },
+    /// A binary metadata entry.
+    Binary {
+        /// The key of the entry.
+        key: String,
+        /// The decoded value of the entry.
+        value: Bytes,
+    },
+}
+
+/// A sealed relay held by the escrow.
+#[derive(Debug, Serialize, Deserialize)]
+struct EscrowEntry {
+    artifact: SealedArtifact,
+    /// The data key, if it is held by the relayer or was recovered from the committee shares.
+    key: Option<B256>,
+    /// Whether the relay was approved before its timelock expired.
+    approved: bool,
+    /// The shares submitted by the committee members, by index.
+    shares: BTreeMap<u8, B256>,
+}
+
+impl EscrowEntry {
+    /// Returns the data key if the relay is revealed at `now`.
+    fn released_key(&self, now: u64) -> Option<B256> {
+        let unlocked = self.approved || self.artifact.reveal_at.is_some_and(|at| at <= now);
+        self.key.filter(|_| unlocked)
+    }
+
+    /// Returns the published artifact, with its data key if the relay is revealed at `now`.
+    fn published(&self, now: u64) -> SealedArtifact {
+        SealedArtifact {
+            key: self.released_key(now),
+            ..self.artifact.clone()
+        }
+    }
+}
+
+/// The sealed relays of all the relayer modules, by artifact identifier.
+static SEALED_RELAYS: LazyLock<RwLock<HashMap<String, EscrowEntry>>> =
+    LazyLock::new(RwLock::default);
+
+/// The store the sealed relays are persisted in, if the relayer has a packet archive.
+static ESCROW_STORE: LazyLock<RwLock<Option<EscrowStore>>> = LazyLock::new(RwLock::default);
+
+/// The sealed relays persisted in a directory, one file per relay.
+#[derive(Clone, Debug)]
+struct EscrowStore {
+    dir: PathBuf,
+}
+
+impl EscrowStore {
+    /// Opens the store in `dir`, creating the directory if needed.
+    fn open(dir: PathBuf) -> anyhow::Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create escrow store {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the path of the file of a sealed relay. The artifact identifier is hashed, so that
+    /// it is a valid file name.
+    fn path(&self, artifact_id: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", hex::encode(keccak256(artifact_id))))
+    }
+
+    /// Persists a sealed relay, replacing its previous state.
+    fn store(&self, entry: &EscrowEntry) -> anyhow::Result<()> {
+        let path = self.path(&entry.artifact.artifact_id);
+        // Written to a temporary file first, so that a crash never leaves a truncated relay, and
+        // only readable by the relayer, since it holds the data key
+        let tmp_path = path.with_extension("json.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&tmp_path)?
+            .write_all(&serde_json::to_vec_pretty(entry)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Loads the persisted sealed relays, by artifact identifier.
+    fn load(&self) -> anyhow::Result<HashMap<String, EscrowEntry>> {
+        let mut entries = HashMap::new();
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            // The temporary files of interrupted writes are skipped
+            if !path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                continue;
+            }
+
+            let entry: EscrowEntry = serde_json::from_slice(&fs::read(&path)?)
+                .with_context(|| format!("failed to decode sealed relay {}", path.display()))?;
+            ensure!(
+                path == self.path(&entry.artifact.artifact_id),
+                "sealed relay {} is stored under another artifact identifier",
+                path.display()
+            );
+            entries.insert(entry.artifact.artifact_id.clone(), entry);
+        }
+        Ok(entries)
+    }
+}
+
+/// Opens the store of the sealed relays in the persisted state directory `state_dir`, and
+/// reloads the relays sealed before the relayer restarted.
+/// # Errors
+/// Returns an error if the store cannot be created, or if a sealed relay cannot be read.
+/// # Panics
+/// Panics if the escrow lock is poisoned.
+pub fn open_escrow_store(state_dir: &Path) -> anyhow::Result<()> {
+    let store = EscrowStore::open(state_dir.join(ESCROW_DIR_NAME))?;
+    let entries = store.load()?;
+    tracing::info!(
+        "Persisting the sealed relays in {}, {} reloaded",
+        store.dir.display(),
+        entries.len()
+    );
+    SEALED_RELAYS.write().unwrap().extend(entries);
+    *ESCROW_STORE.write().unwrap() = Some(store);
+    Ok(())
+}
+
+/// Persists a sealed relay, if the relayer has an escrow store.
+/// # Panics
+/// Panics if the escrow lock is poisoned.
+fn persist(entry: &EscrowEntry) -> anyhow::Result<()> {
+    ESCROW_STORE
+        .read()
+        .unwrap()
+        .as_ref()
+        .map_or(Ok(()), |store| store.store(entry))
+}
+
+/// Returns the transfers of the source events at or above the thresholds of the escrow of a
+/// module, if it has one.
+/// # Errors
+/// Returns an `InvalidArgument` status if an ICS20 payload cannot be decoded.
+pub fn sealed_transfers(
+    escrow: Option<&ArtifactEscrowConfig>,
+    src_client_id: &str,
+    src_events: &[EurekaEventWithHeight],
+) -> Result<Vec<Transfer>, tonic::Status> {
+    let Some(escrow) = escrow else {
+        return Ok(vec![]);
+    };
+
+    let transfers = value_limit::src_events_to_transfers(src_events, src_client_id)
+        .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+    Ok(transfers
+        .into_iter()
+        .filter(|transfer| {
+            escrow
+                .thresholds
+                .get(&transfer.denom)
+                .is_some_and(|threshold| transfer.amount >= *threshold)
+        })
+        .collect())
+}
+
+/// Returns the revealed relay of a request, if the relay was sealed by the escrow of a module.
+/// # Errors
+/// Returns a `FailedPrecondition` status if the relay is sealed and not revealed yet.
+/// # Panics
+/// Panics if the escrow lock is poisoned.
+pub fn reveal_sealed_relay(
+    escrow: Option<&ArtifactEscrowConfig>,
+    artifact_id: &str,
+) -> Result<Option<Response<api::RelayByTxResponse>>, tonic::Status> {
+    if escrow.is_none() {
+        return Ok(None);
+    }
+    let now = now_secs()?;
+    let Some((artifact, released_key)) = SEALED_RELAYS
+        .read()
+        .unwrap()
+        .get(artifact_id)
+        .map(|entry| (entry.artifact.clone(), entry.released_key(now)))
+    else {
+        return Ok(None);
+    };
+
+    let Some(key) = released_key else {
+        return Err(sealed_status(&artifact));
+    };
+    let relay = open_artifact(&artifact, &key)
+        .map_err(|e| tonic::Status::internal(format!("failed to open sealed relay: {e}")))?;
+    tracing::info!(%artifact_id, "Returning the revealed relay.");
+    Ok(Some(relay.into_response()))
+}
+
+/// Seals the response of a relay if it has transfers at or above the thresholds of the escrow of
+/// the module, and returns the response otherwise.
+/// # Errors
+/// Returns a `FailedPrecondition` status if the relay is sealed, and an `Internal` status if it
+/// cannot be sealed.
+/// # Panics
+/// Panics if the escrow lock is poisoned.
+pub fn seal_relay(
+    escrow: Option<&ArtifactEscrowConfig>,
+    artifact_id: &str,
+    src_chain: &str,
+    dst_chain: &str,
+    transfers: Vec<Transfer>,
+    response: Response<api::RelayByTxResponse>,
+) -> Result<Response<api::RelayByTxResponse>, tonic::Status> {
+    let Some(escrow) = escrow else {
+        return Ok(response);
+    };
+    if transfers.is_empty() {
+        return Ok(response);
+    }
+
+    let sealed_at = now_secs()?;
+    let relay = SealedRelay::from_response(response)
+        .map_err(|e| tonic::Status::internal(format!("failed to seal relay: {e}")))?;
+    let entry = seal(
+        escrow,
+        artifact_id,
+        src_chain,
+        dst_chain,
+        transfers,
+        sealed_at,
+        &relay,
+    )
+    .map_err(|e| tonic::Status::internal(format!("failed to seal relay: {e}")))?;
+
+    // A relay that would not survive a restart is not sealed, so that it is sealed again
+    persist(&entry)
+        .map_err(|e| tonic::Status::internal(format!("failed to persist sealed relay: {e}")))?;
+    tracing::warn!(
+        %artifact_id,
+        transfers = ?entry.artifact.transfers,
+        "Sealed the relay of high-value transfers."
+    );
+    let status = sealed_status(&entry.artifact);
+    SEALED_RELAYS
+        .write()
+        .unwrap()
+        .insert(artifact_id.to_string(), entry);
+    Err(status)
+}
+
+/// Encrypts a relay and, with a committee reveal policy, splits its data key among the members.
+fn seal(
+    escrow: &ArtifactEscrowConfig,
+    artifact_id: &str,
+    src_chain: &str,
+    dst_chain: &str,
+    transfers: Vec<Transfer>,
+    sealed_at: u64,
+    relay: &SealedRelay,
+) -> anyhow::Result<EscrowEntry> {
+    let mut key = [0; KEY_SIZE];
+    OsRng.fill_bytes(&mut key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &serde_json::to_vec(relay)?,
+                aad: artifact_id.as_bytes(),
+            },
+        )
+        .map_err(|_| anyhow!("failed to encrypt the relay"))?;
+
+    let (reveal_at, threshold, key_shares, held_key) = match &escrow.reveal {
+        RevealPolicy::Timelock { delay_secs } => (
+            delay_secs.map(|delay_secs| sealed_at.saturating_add(delay_secs)),
+            None,
+            vec![],
+            Some(B256::from(key)),
+        ),
+        RevealPolicy::Committee { threshold, members } => {
+            let members_count = u8::try_from(members.len())?;
+            let key_shares = split_secret(&key, *threshold, members_count)?
+                .iter()
+                .zip(members)
+                .zip(1..=members_count)
+                .map(|((share, member), index)| encrypt_share(index, member, share))
+                .collect::<anyhow::Result<_>>()?;
+            (None, Some(*threshold), key_shares, None)
+        }
+    };
+
+    Ok(EscrowEntry {
+        artifact: SealedArtifact {
+            artifact_id: artifact_id.to_string(),
+            src_chain: src_chain.to_string(),
+            dst_chain: dst_chain.to_string(),
+            transfers,
+            sealed_at,
+            reveal_at,
+            nonce: Bytes::copy_from_slice(&nonce),
+            ciphertext: ciphertext.into(),
+            threshold,
+            key_shares,
+            key: None,
+        },
+        key: held_key,
+        approved: false,
+        shares: BTreeMap::new(),
+    })
+}
+
+/// Decrypts a sealed relay with its data key.
+/// # Errors
+/// Returns an error if the data key does not decrypt the relay.
+pub fn open_artifact(artifact: &SealedArtifact, key: &B256) -> anyhow::Result<SealedRelay> {
+    ensure!(
+        artifact.nonce.len() == 12,
+        "invalid nonce of {} bytes",
+        artifact.nonce.len()
+    );
+    let relay = ChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+        .decrypt(
+            Nonce::from_slice(&artifact.nonce),
+            Payload {
+                msg: &artifact.ciphertext,
+                aad: artifact.artifact_id.as_bytes(),
+            },
+        )
+        .map_err(|_| anyhow!("the key does not decrypt artifact {}", artifact.artifact_id))?;
+    Ok(serde_json::from_slice(&relay)?)
+}
+
+/// Recovers the data key of a sealed relay from the shares of its committee.
+/// # Errors
+/// Returns an error if the shares do not match the artifact, or if there are not enough of them.
+pub fn recover_key(artifact: &SealedArtifact, shares: &[ShareSubmission]) -> anyhow::Result<B256> {
+    let threshold = artifact
+        .threshold
+        .context("the data key of the artifact is not held by a committee")?;
+    let mut points = BTreeMap::new();
+    for submission in shares {
+        verify_share(artifact, submission)?;
+        ensure!(
+            points.insert(submission.index, submission.share).is_none(),
+            "duplicate share {}",
+            submission.index
+        );
+    }
+    ensure!(
+        points.len() >= usize::from(threshold),
+        "{} shares given, {threshold} required",
+        points.len()
+    );
+
+    let points = points
+        .into_iter()
+        .take(usize::from(threshold))
+        .map(|(index, share)| (index, share.0))
+        .collect::<Vec<_>>();
+    Ok(B256::from(combine_shares(&points)?))
+}
+
+/// Decrypts the share of a committee member from a sealed artifact.
+/// # Errors
+/// Returns an error if the artifact has no share for the member, or if it cannot be decrypted.
+pub fn decrypt_share(
+    artifact: &SealedArtifact,
+    secret_key: &SecretKey,
+) -> anyhow::Result<ShareSubmission> {
+    let member = secret_key.public_key();
+    let key_share = artifact
+        .key_shares
+        .iter()
+        .find(|key_share| decode_public_key(&key_share.member).is_ok_and(|pk| pk == member))
+        .context("the artifact has no share for this key")?;
+
+    let ephemeral_key = PublicKey::from_sec1_bytes(&key_share.ephemeral_key)?;
+    let shared_secret =
+        ecdh::diffie_hellman(secret_key.to_nonzero_scalar(), ephemeral_key.as_affine());
+    let share_key = share_key(
+        shared_secret.raw_secret_bytes().as_slice(),
+        &key_share.ephemeral_key,
+    );
+    let share = ChaCha20Poly1305::new(Key::from_slice(share_key.as_slice()))
+        .decrypt(&Nonce::default(), key_share.ciphertext.as_ref())
+        .map_err(|_| anyhow!("failed to decrypt share {}", key_share.index))?;
+
+    let submission = ShareSubmission {
+        index: key_share.index,
+        share: B256::try_from(share.as_slice())?,
+    };
+    verify_share(artifact, &submission)?;
+    Ok(submission)
+}
+
+/// Reads a hex encoded secp256k1 private key from a file.
+/// # Errors
+/// Returns an error if the file cannot be read or decoded.
+pub fn read_secret_key(path: &Path) -> anyhow::Result<SecretKey> {
+    let private_key = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(SecretKey::from_slice(&hex::decode(private_key.trim())?)?)
+}
+
+/// Encrypts a share of a data key to a committee member, with a key agreed with an ephemeral key.
+fn encrypt_share(index: u8, member: &str, share: &[u8; KEY_SIZE]) -> anyhow::Result<KeyShare> {
+    let member_key = decode_public_key(member)?;
+    let ephemeral_secret = EphemeralSecret::random(&mut OsRng);
+    let ephemeral_key = Bytes::from(ephemeral_secret.public_key().to_sec1_bytes().into_vec());
+    let shared_secret = ephemeral_secret.diffie_hellman(&member_key);
+    let share_key = share_key(shared_secret.raw_secret_bytes().as_slice(), &ephemeral_key);
+    // The share key is only used once, so the nonce can be fixed
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(share_key.as_slice()))
+        .encrypt(&Nonce::default(), share.as_slice())
+        .map_err(|_| anyhow!("failed to encrypt share {index}"))?;
+
+    Ok(KeyShare {
+        index,
+        member: member.to_string(),
+        ephemeral_key,
+        ciphertext: ciphertext.into(),
+        digest: share_digest(index, share),
+    })
+}
+
+/// Derives the key encrypting a share from the shared secret with the ephemeral key.
+fn share_key(shared_secret: &[u8], ephemeral_key: &Bytes) -> B256 {
+    keccak256(
+        (
+            SHARE_KEY_DOMAIN,
+            B256::from_slice(shared_secret),
+            ephemeral_key.clone(),
+        )
+            .abi_encode(),
+    )
+}
+
+/// Returns the digest of a share, published to check the submitted shares.
+fn share_digest(index: u8, share: &[u8; KEY_SIZE]) -> B256 {
+    keccak256((SHARE_KEY_DOMAIN, U256::from(index), B256::from(*share)).abi_encode())
+}
+
+/// Checks a submitted share against the digest published in the artifact.
+fn verify_share(artifact: &SealedArtifact, submission: &ShareSubmission) -> anyhow::Result<()> {
+    let key_share = artifact
+        .key_shares
+        .iter()
+        .find(|key_share| key_share.index == submission.index)
+        .with_context(|| format!("the artifact has no share {}", submission.index))?;
+    ensure!(
+        key_share.digest == share_digest(submission.index, &submission.share.0),
+        "share {} does not match its digest",
+        submission.index
+    );
+    Ok(())
+}
+
+/// Returns the digest a committee member signs to submit a share of a sealed relay. The digest
+/// commits to the artifact identifier, so that a submission cannot be replayed to another relay.
+fn share_submission_digest(artifact_id: &str, submission: &ShareSubmission) -> B256 {
+    keccak256(
+        (
+            SHARE_SUBMISSION_DOMAIN,
+            artifact_id.to_string(),
+            U256::from(submission.index),
+            submission.share,
+        )
+            .abi_encode(),
+    )
+}
+
+/// Signs the submission of a share of a sealed relay with the key of the committee member.
+/// # Errors
+/// Returns an error if the submission cannot be signed.
+pub fn sign_share(
+    artifact_id: &str,
+    submission: ShareSubmission,
+    secret_key: &SecretKey,
+) -> anyhow::Result<SignedShareSubmission> {
+    let signer = PrivateKeySigner::from_slice(&secret_key.to_bytes())?;
+    let signature = signer.sign_hash_sync(&share_submission_digest(artifact_id, &submission))?;
+    Ok(SignedShareSubmission {
+        submission,
+        signature: Bytes::from(signature.as_bytes().to_vec()),
+    })
+}
+
+/// Checks that a submitted share is signed by the committee member it is encrypted to.
+fn verify_share_signature(
+    artifact: &SealedArtifact,
+    signed: &SignedShareSubmission,
+) -> anyhow::Result<()> {
+    let index = signed.submission.index;
+    let key_share = artifact
+        .key_shares
+        .iter()
+        .find(|key_share| key_share.index == index)
+        .with_context(|| format!("the artifact has no share {index}"))?;
+    let member = decode_public_key(&key_share.member)?;
+    let member = Address::from_raw_public_key(&member.to_encoded_point(false).as_bytes()[1..]);
+
+    let signer = PrimitiveSignature::try_from(signed.signature.as_ref())
+        .and_then(|signature| {
+            signature.recover_address_from_prehash(&share_submission_digest(
+                &artifact.artifact_id,
+                &signed.submission,
+            ))
+        })
+        .map_err(|e| anyhow!("invalid signature of share {index}: {e}"))?;
+    ensure!(
+        signer == member,
+        "share {index} is not signed by its committee member"
+    );
+    Ok(())
+}
+
+/// Reads the operator token that approves sealed relays.
+/// # Errors
+/// Returns an error if the file cannot be read or is empty.
+pub fn read_operator_token(path: &Path) -> anyhow::Result<String> {
+    let token = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let token = token.trim();
+    ensure!(
+        !token.is_empty(),
+        "operator token {} is empty",
+        path.display()
+    );
+    Ok(token.to_string())
+}
+
+/// Returns whether the `Authorization` header of a request carries the operator token. Without an
+/// operator token, no request is authorized.
+fn operator_authorized(operator_token: Option<&str>, authorization: Option<&str>) -> bool {
+    let (Some(operator_token), Some(token)) = (
+        operator_token,
+        authorization.and_then(|authorization| authorization.strip_prefix("Bearer ")),
+    ) else {
+        return false;
+    };
+    // The digests of the tokens are compared without short-circuiting, so that the time taken
+    // leaks neither the length of the operator token nor its matching prefix
+    keccak256(operator_token)
+        .iter()
+        .zip(keccak256(token).iter())
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+/// Decodes the hex encoded SEC1 public key of a committee member.
+fn decode_public_key(member: &str) -> anyhow::Result<PublicKey> {
+    PublicKey::from_sec1_bytes(&hex::decode(member)?)
+        .map_err(|e| anyhow!("invalid committee member key {member}: {e}"))
+}
+
+impl SealedRelay {
+    /// Captures the transaction, the address and the metadata of a relay response.
+    fn from_response(response: Response<api::RelayByTxResponse>) -> anyhow::Result<Self> {
+        let metadata = response
+            .metadata()
+            .iter()
+            .map(|entry| match entry {
+                KeyAndValueRef::Ascii(key, value) => Ok(SealedMetadata::Ascii {
+                    key: key.as_str().to_string(),
+                    value: value.to_str()?.to_string(),
+                }),
+                KeyAndValueRef::Binary(key, value) => Ok(SealedMetadata::Binary {
+                    key: key.as_str().to_string(),
+                    value: value.to_bytes()?.to_vec().into(),
+                }),
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let response = response.into_inner();
+        Ok(Self {
+            tx: response.tx.into(),
+            address: response.address,
+            metadata,
+        })
+    }
+
+    /// Rebuilds the relay response.
+    fn into_response(self) -> Response<api::RelayByTxResponse> {
+        let mut metadata = MetadataMap::new();
+        for entry in self.metadata {
+            match entry {
+                SealedMetadata::Ascii { key, value } => {
+                    match (
+                        MetadataKey::<Ascii>::from_bytes(key.as_bytes()),
+                        MetadataValue::try_from(value),
+                    ) {
+                        (Ok(key), Ok(value)) => {
+                            metadata.append(key, value);
+                        }
+                        _ => tracing::warn!(%key, "Dropping an invalid sealed metadata entry."),
+                    }
+                }
+                SealedMetadata::Binary { key, value } => {
+                    match MetadataKey::<Binary>::from_bytes(key.as_bytes()) {
+                        Ok(key) => {
+                            metadata.append_bin(key, MetadataValue::from_bytes(&value));
+                        }
+                        Err(_) => {
+                            tracing::warn!(%key, "Dropping an invalid sealed metadata entry.")
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut response = Response::new(api::RelayByTxResponse {
+            tx: self.tx.to_vec(),
+            address: self.address,
+        });
+        *response.metadata_mut() = metadata;
+        response
+    }
+}
+
+/// Returns the status of the requests for a sealed relay that is not revealed yet.
+fn sealed_status(artifact: &SealedArtifact) -> tonic::Status {
+    let reveal = match (artifact.threshold, artifact.reveal_at) {
+        (Some(threshold), _) => format!("{threshold} committee shares are submitted"),
+        (None, Some(reveal_at)) => format!("{reveal_at} unless approved earlier"),
+        (None, None) => "it is approved".to_string(),
+    };
+    tonic::Status::failed_precondition(format!(
+        "relay {} of high-value transfers is sealed until {reveal}",
+        artifact.artifact_id
+    ))
+}
+
+/// Returns the current unix timestamp.
+fn now_secs() -> Result<u64, tonic::Status> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| tonic::Status::from_error(e.into()))?
+        .as_secs())
+}
+
+/// Returns the sealed relays across all the relayer modules.
+/// # Panics
+/// Panics if the escrow lock is poisoned.
+#[must_use]
+pub fn sealed_artifacts() -> Vec<SealedArtifact> {
+    let now = now_secs().unwrap_or_default();
+    let mut artifacts = SEALED_RELAYS
+        .read()
+        .unwrap()
+        .values()
+        .map(|entry| entry.published(now))
+        .collect::<Vec<_>>();
+    artifacts.sort_by_key(|artifact| artifact.sealed_at);
+    artifacts
+}
+
+/// Approves a sealed relay, which reveals it before its timelock expires. Returns `false` if
+/// there is no sealed relay with the given id.
+/// # Errors
+/// Returns an error if the approval cannot be persisted.
+/// # Panics
+/// Panics if the escrow lock is poisoned.
+pub fn approve_sealed_relay(artifact_id: &str) -> anyhow::Result<bool> {
+    let mut relays = SEALED_RELAYS.write().unwrap();
+    let Some(entry) = relays.get_mut(artifact_id) else {
+        return Ok(false);
+    };
+    entry.approved = true;
+    persist(entry)?;
+    Ok(true)
+}
+
+/// Submits the share of a committee member for a sealed relay, which is revealed once enough
+/// shares are submitted.
+/// # Errors
+/// Returns an error if there is no sealed relay with the given id, or if the share does not match
+/// it.
+/// # Panics
+/// Panics if the escrow lock is poisoned.
+pub fn submit_share(artifact_id: &str, signed: &SignedShareSubmission) -> anyhow::Result<()> {
+    let submission = signed.submission;
+    let mut relays = SEALED_RELAYS.write().unwrap();
+    let entry = relays
+        .get_mut(artifact_id)
+        .with_context(|| format!("no sealed relay {artifact_id}"))?;
+    verify_share_signature(&entry.artifact, signed)?;
+    verify_share(&entry.artifact, &submission)?;
+    entry.shares.insert(submission.index, submission.share);
+
+    let recoverable = entry
+        .artifact
+        .threshold
+        .is_some_and(|threshold| entry.shares.len() >= usize::from(threshold));
+    if entry.key.is_none() && recoverable {
+        let shares = entry
+            .shares
+            .iter()
+            .map(|(&index, &share)| ShareSubmission { index, share })
+            .collect::<Vec<_>>();
+        let key = recover_key(&entry.artifact, &shares)?;
+        open_artifact(&entry.artifact, &key)?;
+        entry.key = Some(key);
+        entry.approved = true;
+        tracing::info!(%artifact_id, "Recovered the data key of the sealed relay.");
+    }
+    persist(entry)
+}
+
+/// Fetches a sealed relay from the relayer serving the metrics server at `endpoint`.
+/// # Errors
+/// Returns an error if the request fails or the relayer has no such sealed relay.
+pub async fn fetch_artifact(endpoint: &str, artifact_id: &str) -> anyhow::Result<SealedArtifact> {
+    let artifact = reqwest::get(format!(
+        "{}/escrow/{artifact_id}",
+        endpoint.trim_end_matches('/')
+    ))
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+    Ok(artifact)
+}
+
+/// Submits the share of a committee member to the relayer serving the metrics server at
+/// `endpoint`.
+/// # Errors
+/// Returns an error if the request fails or the relayer rejects the share.
+pub async fn upload_share(
+    endpoint: &str,
+    artifact_id: &str,
+    submission: &SignedShareSubmission,
+) -> anyhow::Result<()> {
+    let response = reqwest::Client::new()
+        .post(format!(
+            "{}/escrow/{artifact_id}/shares",
+            endpoint.trim_end_matches('/')
+        ))
+        .json(submission)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let body = response.text().await?;
+    ensure!(
+        status.is_success(),
+        "failed to submit share ({status}): {body}"
+    );
+    Ok(())
+}
+
+/// Reads a JSON file, such as a sealed artifact or a decrypted share.
+/// # Errors
+/// Returns an error if the file cannot be read or decoded.
+pub fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> anyhow::Result<T> {
+    let bz = std::fs::read(path).with_context(|| format!("failed to read {path}"))?;
+    Ok(serde_json::from_slice(&bz)?)
+}
+
+/// Returns the routes of the escrow API. The approvals require `operator_token` as a bearer token,
+/// and are rejected if it is unset.
+#[must_use]
+pub fn routes(
+    operator_token: Option<String>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let list = warp::path!("escrow")
+        .and(warp::get())
+        .map(|| warp::reply::json(&sealed_artifacts()));
+
+    let get = warp::path!("escrow" / String)
+        .and(warp::get())
+        .map(|artifact_id: String| {
+            match sealed_artifacts()
+                .into_iter()
+                .find(|artifact| artifact.artifact_id == artifact_id)
+            {
+                Some(artifact) => {
+                    warp::reply::with_status(warp::reply::json(&artifact), StatusCode::OK)
+                }
+                None => {
+                    warp::reply::with_status(warp::reply::json(&artifact_id), StatusCode::NOT_FOUND)
+                }
+            }
+        });
+
+    let approve = warp::path!("escrow" / String / "approve")
+        .and(warp::post())
+        .and(warp::header::optional::<String>("authorization"))
+        .map(move |artifact_id: String, authorization: Option<String>| {
+            if !operator_authorized(operator_token.as_deref(), authorization.as_deref()) {
+                tracing::warn!(%artifact_id, "Unauthorized approval of a sealed relay.");
+                return warp::reply::with_status(
+                    warp::reply::json(&artifact_id),
+                    StatusCode::UNAUTHORIZED,
+                );
+            }
+
+            let status = match approve_sealed_relay(&artifact_id) {
+                Ok(true) => {
+                    tracing::info!(%artifact_id, "Sealed relay approved.");
+                    StatusCode::OK
+                }
+                Ok(false) => StatusCode::NOT_FOUND,
+                Err(e) => {
+                    tracing::error!(%artifact_id, "Failed to persist the approval: {e}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            };
+            warp::reply::with_status(warp::reply::json(&artifact_id), status)
+        });
+
+    let shares = warp::path!("escrow" / String / "shares")
+        .and(warp::post())
+        .and(warp::body::json())
+        .map(|artifact_id: String, signed: SignedShareSubmission| {
+            match submit_share(&artifact_id, &signed) {
+                Ok(()) => {
+                    tracing::info!(%artifact_id, index = signed.submission.index, "Share submitted.");
+                    warp::reply::with_status(String::new(), StatusCode::OK)
+                }
+                Err(e) => {
+                    tracing::warn!(%artifact_id, "Share rejected: {e}");
+                    warp::reply::with_status(e.to_string(), StatusCode::BAD_REQUEST)
+                }
+            }
+        });
+
+    list.or(get).or(approve).or(shares)
+}
+
+/// Multiplies two elements of GF(2^8) with the AES reduction polynomial.
+///
+/// The multiplication runs in constant time: it always goes through the 8 bits of `b`, and the
+/// additions and reductions are masked rather than branched on, so that its timing does not leak
+/// the shares or the key it operates on.
+const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    let mut i = 0;
+    while i < 8 {
+        // All ones if the low bit of `b` is set, zero otherwise
+        product ^= a & (b & 1).wrapping_neg();
+        // Reduced by the polynomial if the high bit of `a` is shifted out
+        a = (a << 1) ^ (0x1b & (a >> 7).wrapping_neg());
+        b >>= 1;
+        i += 1;
+    }
+    product
+}
+
+/// Inverts a non-zero element of GF(2^8), as its 254th power. The exponent is fixed, so the
+/// inversion runs in constant time.
+const fn gf_inv(a: u8) -> u8 {
+    let mut inverse = 1;
+    let mut base = a;
+    let mut exponent = 254_u8;
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            inverse = gf_mul(inverse, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    inverse
+}
+
+/// Splits a secret into `shares` Shamir shares, any `threshold` of which recover it. The share
+/// at index `i` is the evaluation at `i + 1`.
+fn split_secret(
+    secret: &[u8; KEY_SIZE],
+    threshold: u8,
+    shares: u8,
+) -> anyhow::Result<Vec<[u8; KEY_SIZE]>> {
+    ensure!(
+        (1..=shares).contains(&threshold),
+        "invalid threshold {threshold} for {shares} shares"
+    );
+    let mut coefficients = vec![[0; KEY_SIZE]; usize::from(threshold - 1)];
+    for coefficient in &mut coefficients {
+        OsRng.fill_bytes(coefficient);
+    }
+    Ok(evaluate_shares(secret, &coefficients, shares))
+}
+
+/// Evaluates the polynomial with the secret as its constant term and the given higher degree
+/// coefficients at `1..=shares`.
+fn evaluate_shares(
+    secret: &[u8; KEY_SIZE],
+    coefficients: &[[u8; KEY_SIZE]],
+    shares: u8,
+) -> Vec<[u8; KEY_SIZE]> {
+    (1..=shares)
+        .map(|x| {
+            let mut share = [0; KEY_SIZE];
+            for (i, byte) in share.iter_mut().enumerate() {
+                // Horner's rule, from the highest degree coefficient down to the secret
+                *byte = coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, coefficient| gf_mul(acc, x) ^ coefficient[i]);
+                *byte = gf_mul(*byte, x) ^ secret[i];
+            }
+            share
+        })
+        .collect()
+}
+
+/// Recovers a secret from Shamir shares given as their evaluation point and value.
+/// # Errors
+/// Returns an error if a share is evaluated at zero, which is the secret itself, or if two shares
+/// are evaluated at the same point, which makes the interpolation divide by zero.
+fn combine_shares(shares: &[(u8, [u8; KEY_SIZE])]) -> anyhow::Result<[u8; KEY_SIZE]> {
+    ensure!(!shares.is_empty(), "no shares given");
+    for (i, &(x_i, _)) in shares.iter().enumerate() {
+        ensure!(x_i != 0, "share evaluated at zero");
+        ensure!(
+            shares[..i].iter().all(|&(x_j, _)| x_j != x_i),
+            "duplicate share {x_i}"
+        );
+    }
+
+    let mut secret = [0; KEY_SIZE];
+    for (i, &(x_i, share)) in shares.iter().enumerate() {
+        // The Lagrange basis polynomial of the share evaluated at zero
+        let basis = shares
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold(1, |acc, (_, &(x_j, _))| {
+                gf_mul(acc, gf_mul(x_j, gf_inv(x_j ^ x_i)))
+            });
+        for (byte, share_byte) in secret.iter_mut().zip(share) {
+            *byte ^= gf_mul(basis, share_byte);
+        }
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shamir_roundtrip() {
+        let mut secret = [0; KEY_SIZE];
+        OsRng.fill_bytes(&mut secret);
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        let points = |indices: &[u8]| {
+            indices
+                .iter()
+                .map(|&index| (index, shares[usize::from(index) - 1]))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(combine_shares(&points(&[1, 2, 3])).unwrap(), secret);
+        assert_eq!(combine_shares(&points(&[5, 2, 4])).unwrap(), secret);
+        assert_ne!(combine_shares(&points(&[1, 2])).unwrap(), secret);
+
+        // The secret itself, a repeated share and an empty set are rejected
+        let mut zero = points(&[1, 2]);
+        zero.push((0, secret));
+        assert!(combine_shares(&zero).is_err());
+        assert!(combine_shares(&points(&[1, 2, 2])).is_err());
+        assert!(combine_shares(&[]).is_err());
+
+        assert!(split_secret(&secret, 0, 5).is_err());
+        assert!(split_secret(&secret, 6, 5).is_err());
+    }
+
+    #[test]
+    fn test_gf_known_vectors() {
+        // The multiplication examples of FIPS-197, section 4.2
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf_mul(0x57, 0x13), 0xfe);
+        assert_eq!(gf_mul(0x57, 0x02), 0xae);
+        assert_eq!(gf_mul(0x57, 0x01), 0x57);
+        assert_eq!(gf_mul(0x57, 0x00), 0x00);
+        // The inverse of {53} is {ca}, as in the derivation of the AES S-box
+        assert_eq!(gf_inv(0x53), 0xca);
+        assert_eq!(gf_mul(0x53, gf_inv(0x53)), 0x01);
+        for a in 1..=u8::MAX {
+            assert_eq!(gf_mul(a, gf_inv(a)), 0x01);
+        }
+    }
+
+    #[test]
+    fn test_shamir_known_vectors() {
+        // f(x) = {01} + {57}x, in every byte of the key
+        let secret = [0x01; KEY_SIZE];
+        let shares = evaluate_shares(&secret, &[[0x57; KEY_SIZE]], 3);
+        assert_eq!(
+            shares,
+            vec![[0x56; KEY_SIZE], [0xaf; KEY_SIZE], [0xf8; KEY_SIZE]]
+        );
+
+        for points in [
+            [(1, shares[0]), (2, shares[1])],
+            [(3, shares[2]), (2, shares[1])],
+        ] {
+            assert_eq!(combine_shares(&points).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn test_seal_with_committee() {
+        let member_keys = (0..3)
+            .map(|_| SecretKey::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let escrow = ArtifactEscrowConfig {
+            thresholds: HashMap::new(),
+            reveal: RevealPolicy::Committee {
+                threshold: 2,
+                members: member_keys
+                    .iter()
+                    .map(|key| hex::encode(key.public_key().to_sec1_bytes()))
+                    .collect(),
+            },
+        };
+        escrow.validate().unwrap();
+        let relay = SealedRelay {
+            tx: Bytes::from_static(b"relay tx"),
+            address: "0x01".to_string(),
+            metadata: vec![SealedMetadata::Binary {
+                key: "x-relay-next-tx-bin".to_string(),
+                value: Bytes::from_static(b"next tx"),
+            }],
+        };
+
+        let entry = seal(&escrow, "relay", "src", "dst", vec![], 0, &relay).unwrap();
+        assert_eq!(entry.key, None);
+        let artifact = entry.artifact;
+        let shares = member_keys
+            .iter()
+            .map(|key| decrypt_share(&artifact, key).unwrap())
+            .collect::<Vec<_>>();
+
+        let key = recover_key(&artifact, &shares[1..]).unwrap();
+        assert_eq!(open_artifact(&artifact, &key).unwrap(), relay);
+        assert!(recover_key(&artifact, &shares[..1]).is_err());
+
+        let forged = ShareSubmission {
+            index: 1,
+            share: B256::repeat_byte(1),
+        };
+        assert!(recover_key(&artifact, &[forged, shares[1]]).is_err());
+
+        // A share is only accepted if signed by its member, for this artifact
+        let signed = sign_share(&artifact.artifact_id, shares[0], &member_keys[0]).unwrap();
+        verify_share_signature(&artifact, &signed).unwrap();
+        let other_member = sign_share(&artifact.artifact_id, shares[0], &member_keys[1]).unwrap();
+        assert!(verify_share_signature(&artifact, &other_member).is_err());
+        let other_artifact = sign_share("other", shares[0], &member_keys[0]).unwrap();
+        assert!(verify_share_signature(&artifact, &other_artifact).is_err());
+        let mut unsigned = signed;
+        unsigned.signature = Bytes::new();
+        assert!(verify_share_signature(&artifact, &unsigned).is_err());
+    }
+
+    #[test]
+    fn test_operator_authorized() {
+        assert!(operator_authorized(Some("secret"), Some("Bearer secret")));
+        assert!(!operator_authorized(Some("secret"), Some("Bearer secre")));
+        assert!(!operator_authorized(Some("secret"), Some("secret")));
+        assert!(!operator_authorized(Some("secret"), None));
+        // Approvals are disabled without an operator token
+        assert!(!operator_authorized(None, Some("Bearer ")));
+        assert!(!operator_authorized(None, None));
+    }
+
+    #[test]
+    fn test_escrow_store_reload() {
+        let dir = std::env::temp_dir().join(format!("escrow-store-{}", std::process::id()));
+        let relay = SealedRelay {
+            tx: Bytes::from_static(b"relay tx"),
+            address: "0x01".to_string(),
+            metadata: vec![],
+        };
+
+        let timelock = ArtifactEscrowConfig {
+            thresholds: HashMap::new(),
+            reveal: RevealPolicy::Timelock {
+                delay_secs: Some(3600),
+            },
+        };
+        let mut timelock_entry =
+            seal(&timelock, "relay/1", "src", "dst", vec![], 0, &relay).unwrap();
+        timelock_entry.approved = true;
+
+        let member_keys = (0..3)
+            .map(|_| SecretKey::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let committee = ArtifactEscrowConfig {
+            thresholds: HashMap::new(),
+            reveal: RevealPolicy::Committee {
+                threshold: 2,
+                members: member_keys
+                    .iter()
+                    .map(|key| hex::encode(key.public_key().to_sec1_bytes()))
+                    .collect(),
+            },
+        };
+        let mut committee_entry =
+            seal(&committee, "relay/2", "src", "dst", vec![], 0, &relay).unwrap();
+        let first_share = decrypt_share(&committee_entry.artifact, &member_keys[0]).unwrap();
+        committee_entry
+            .shares
+            .insert(first_share.index, first_share.share);
+
+        let store = EscrowStore::open(dir.clone()).unwrap();
+        store.store(&timelock_entry).unwrap();
+        store.store(&committee_entry).unwrap();
+        // An interrupted write is not reloaded
+        fs::write(dir.join("interrupted.json.tmp"), b"{").unwrap();
+
+        // The relayer restarted
+        let mut entries = EscrowStore::open(dir.clone()).unwrap().load().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let reloaded = entries.remove("relay/1").unwrap();
+        assert_eq!(reloaded.artifact, timelock_entry.artifact);
+        assert!(reloaded.approved);
+        let key = reloaded.released_key(0).unwrap();
+        assert_eq!(Some(key), timelock_entry.key);
+        assert_eq!(open_artifact(&reloaded.artifact, &key).unwrap(), relay);
+
+        let reloaded = entries.remove("relay/2").unwrap();
+        assert_eq!(reloaded.artifact, committee_entry.artifact);
+        assert_eq!(reloaded.key, None);
+        assert_eq!(reloaded.shares, committee_entry.shares);
+        let second_share = decrypt_share(&reloaded.artifact, &member_keys[1]).unwrap();
+        let shares = reloaded
+            .shares
+            .iter()
+            .map(|(&index, &share)| ShareSubmission { index, share })
+            .chain([second_share])
+            .collect::<Vec<_>>();
+        let key = recover_key(&reloaded.artifact, &shares).unwrap();
+        assert_eq!(open_artifact(&reloaded.artifact, &key).unwrap(), relay);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
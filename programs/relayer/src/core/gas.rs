@@ -0,0 +1,214 @@
+//! Defines the gas spike API for the relays toward Ethereum deferred by the modules.
+//!
+//! A module with a `gas_spike` policy samples the base fee of its Ethereum chain, and defers the
+//! non-urgent messages of its relays while the base fee exceeds the ceiling. The base fee, whether
+//! it spikes and the deferred relays of each chain are listed with `GET /gas` on the metrics
+//! server and exported in the metrics. The deferred relays are scheduled once the spike ends.
+//!
+//! The packets deferred from a relay are reported in the `x-relay-gas-deferred` metadata of the
+//! `RelayByTx` response.
+
+use std::{
+    sync::{Arc, LazyLock, RwLock},
+    time::Duration,
+};
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    primitives::Bytes,
+    providers::{Provider, RootProvider},
+};
+use ibc_eureka_relayer_lib::{
+    events::EurekaEventWithHeight,
+    gas_spike::{DeferredRelay, DeferredSequences, GasSpikeGuard, GasSpikeState},
+};
+use serde::Serialize;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use warp::Filter;
+
+use crate::{api, core::scheduler, metrics};
+
+/// The gRPC metadata key of the packets deferred until the gas spike ends.
+pub const GAS_DEFERRED_METADATA_KEY: &str = "x-relay-gas-deferred";
+
+/// The gas spike guards of all the relayer modules, with the chain id of their Ethereum chain.
+static GAS_GUARDS: LazyLock<RwLock<Vec<(String, Arc<GasSpikeGuard>)>>> =
+    LazyLock::new(RwLock::default);
+
+/// The gas spike state of an Ethereum chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ChainGasState {
+    /// The chain id of the Ethereum chain.
+    pub chain_id: String,
+    /// The state of the gas spike guard.
+    #[serde(flatten)]
+    pub state: GasSpikeState,
+}
+
+/// Registers the gas spike guard of a relayer module with the gas spike API, and starts sampling
+/// the base fee of its Ethereum chain.
+/// # Panics
+/// Panics if the registry lock is poisoned.
+pub fn register_gas_guard(chain_id: String, guard: Arc<GasSpikeGuard>, provider: RootProvider) {
+    GAS_GUARDS
+        .write()
+        .unwrap()
+        .push((chain_id.clone(), guard.clone()));
+    tokio::spawn(watch_base_fee(chain_id, guard, provider));
+}
+
+/// Returns the gas spike states of all the relayer modules, in the order of the configuration.
+/// # Panics
+/// Panics if the registry lock is poisoned.
+#[must_use]
+pub fn gas_states() -> Vec<ChainGasState> {
+    GAS_GUARDS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(chain_id, guard)| ChainGasState {
+            chain_id: chain_id.clone(),
+            state: guard.state(),
+        })
+        .collect()
+}
+
+/// Samples the base fee of the chain at the interval of the policy, and schedules the deferred
+/// relays once the spike ends.
+async fn watch_base_fee(chain_id: String, guard: Arc<GasSpikeGuard>, provider: RootProvider) {
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        guard.policy().poll_interval_secs.max(1),
+    ));
+    loop {
+        interval.tick().await;
+
+        let base_fee = match latest_base_fee(&provider).await {
+            Ok(base_fee) => base_fee,
+            Err(e) => {
+                tracing::warn!(%chain_id, "Failed to sample the base fee: {e}");
+                continue;
+            }
+        };
+
+        let was_spiking = guard.state().spiking;
+        let resumed = guard.observe_base_fee(base_fee);
+        let state = guard.state();
+        metrics::observe_gas_spike(
+            &chain_id,
+            state.base_fee,
+            state.spiking,
+            state.deferred.len(),
+        );
+
+        if state.spiking && !was_spiking {
+            tracing::warn!(
+                %chain_id,
+                base_fee,
+                "The base fee exceeds the ceiling, deferring the non-urgent relays."
+            );
+        } else if !state.spiking && was_spiking {
+            tracing::info!(
+                %chain_id,
+                base_fee,
+                "The base fee is below the ceiling, resuming {} deferred relays.",
+                resumed.len()
+            );
+        }
+        resumed
+            .into_iter()
+            .for_each(scheduler::schedule_deferred_relay);
+    }
+}
+
+/// Returns the base fee of the latest block of the chain in wei.
+async fn latest_base_fee(provider: &RootProvider) -> anyhow::Result<u128> {
+    let block = provider
+        .get_block(BlockNumberOrTag::Latest.into())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Latest block not found"))?;
+    let base_fee = block
+        .header
+        .base_fee_per_gas
+        .ok_or_else(|| anyhow::anyhow!("Latest block has no base fee"))?;
+    Ok(base_fee.into())
+}
+
+/// Defers the non-urgent messages of a relay request while the base fee of the target chain
+/// spikes, if the module has a gas spike guard, and queues the relay to resume once it ends.
+/// Returns the sequences of the deferred packets.
+/// # Errors
+/// Returns an `Unavailable` status if every message of the relay is deferred.
+pub fn defer_relay(
+    guard: Option<&GasSpikeGuard>,
+    request: &api::RelayByTxRequest,
+    src_events: &mut Vec<EurekaEventWithHeight>,
+    target_events: &mut Vec<EurekaEventWithHeight>,
+) -> Result<DeferredSequences, tonic::Status> {
+    let Some(guard) = guard else {
+        return Ok(DeferredSequences::default());
+    };
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| tonic::Status::from_error(e.into()))?
+        .as_secs();
+    let deferred = guard.defer_non_urgent(src_events, target_events, now);
+    if deferred.is_empty() {
+        return Ok(deferred);
+    }
+
+    tracing::info!(
+        sequences = ?deferred,
+        "Deferring the non-urgent packets until the gas spike ends."
+    );
+    guard.queue(DeferredRelay {
+        src_chain: request.src_chain.clone(),
+        dst_chain: request.dst_chain.clone(),
+        src_client_id: request.src_client_id.clone(),
+        dst_client_id: request.dst_client_id.clone(),
+        source_tx_ids: request
+            .source_tx_ids
+            .iter()
+            .cloned()
+            .map(Bytes::from)
+            .collect(),
+        timeout_tx_ids: request
+            .timeout_tx_ids
+            .iter()
+            .cloned()
+            .map(Bytes::from)
+            .collect(),
+        sequences: deferred.clone(),
+        deferred_at: now,
+    });
+
+    if src_events.is_empty() && target_events.is_empty() {
+        return Err(tonic::Status::unavailable(format!(
+            "relay deferred until the gas spike ends: {}",
+            serde_json::to_string(&deferred).unwrap_or_default()
+        )));
+    }
+    Ok(deferred)
+}
+
+/// Attaches the packets deferred until the gas spike ends, if any, to the response metadata.
+pub fn insert_gas_deferred(deferred: &DeferredSequences, metadata: &mut MetadataMap) {
+    if deferred.is_empty() {
+        return;
+    }
+
+    match serde_json::to_string(deferred).map(MetadataValue::try_from) {
+        Ok(Ok(value)) => {
+            metadata.insert(GAS_DEFERRED_METADATA_KEY, value);
+        }
+        _ => tracing::warn!("Failed to encode the deferred packets."),
+    }
+}
+
+/// Returns the routes of the gas spike API.
+#[must_use]
+pub fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("gas")
+        .and(warp::get())
+        .map(|| warp::reply::json(&gas_states()))
+}
@@ -0,0 +1,146 @@
+//! Reports the relay hints of the packets of a relay to the submitter of the transaction.
+//!
+//! The hints are attached to the gRPC response metadata of `RelayByTx`, so that the submitter can
+//! bump the fee of express relays without a change to the relayer API. The fee quote of the
+//! transaction, in the fee denom of the module, and the following transactions of a split relay
+//! are reported the same way, as is the retry class of a failed relay.
+
+use ibc_eureka_relayer_lib::{
+    fees::{FeeConfig, FeeQuote},
+    relay_hints::RelayHints,
+    retry,
+    tx_builder::plan::TxPlan,
+};
+use tendermint_rpc::HttpClient;
+use tonic::metadata::{MetadataMap, MetadataValue};
+
+use super::{approvals::DEFERRED_SEQUENCES_METADATA_KEY, gas::GAS_DEFERRED_METADATA_KEY};
+
+/// The gRPC metadata key of the relay priority.
+pub const RELAY_PRIORITY_METADATA_KEY: &str = "x-relay-priority";
+/// The gRPC metadata key of the fee bump, in percent.
+pub const RELAY_FEE_BUMP_METADATA_KEY: &str = "x-relay-fee-bump-percent";
+/// The gRPC metadata key of the quoted fee, as an amount followed by its denom.
+pub const RELAY_FEE_METADATA_KEY: &str = "x-relay-fee";
+/// The gRPC metadata key of the gas limit of the quoted fee.
+pub const RELAY_GAS_LIMIT_METADATA_KEY: &str = "x-relay-gas-limit";
+/// The gRPC binary metadata key of the packet transaction of a split relay, to be submitted once
+/// the update transaction of the response is included.
+pub const RELAY_PACKET_TX_METADATA_KEY: &str = "x-relay-packet-tx-bin";
+/// The gRPC metadata key of the retry class of a failed relay, i.e. `retryable`, `needs_rebuild`
+/// or `fatal`.
+pub const RELAY_RETRY_METADATA_KEY: &str = "x-relay-retry";
+/// The gRPC binary metadata key of the transactions following the one of the response, when the
+/// relay exceeds the size limit of the chain, repeated in the order they are to be submitted.
+pub const RELAY_NEXT_TX_METADATA_KEY: &str = "x-relay-next-tx-bin";
+
+/// Attaches the relay hints to the response metadata.
+pub fn insert_relay_hints(hints: &RelayHints, metadata: &mut MetadataMap) {
+    if !hints.bypassed_sequences.is_empty() {
+        tracing::info!(
+            sequences = ?hints.bypassed_sequences,
+            "Relaying packets that bypassed the request filters."
+        );
+    }
+
+    metadata.insert(
+        RELAY_PRIORITY_METADATA_KEY,
+        MetadataValue::from_static(hints.priority.as_str()),
+    );
+    metadata.insert(RELAY_FEE_BUMP_METADATA_KEY, hints.fee_bump_percent.into());
+}
+
+/// Quotes the fee of a planned relay transaction in the configured fee denom. Failures are only
+/// logged, since the submitter can still pay the fee in the default denom of the chain.
+pub async fn quote_fee(
+    fee: Option<&FeeConfig>,
+    tm_client: &HttpClient,
+    plan: Option<&TxPlan>,
+) -> Option<FeeQuote> {
+    let (fee, plan) = (fee?, plan?);
+    match fee
+        .quote(tm_client, plan.simulated_gas.unwrap_or(plan.estimated_gas))
+        .await
+    {
+        Ok(quote) => {
+            tracing::info!(
+                "Quoted a fee of {}{} for a gas limit of {}.",
+                quote.fee.amount,
+                quote.fee.denom,
+                quote.gas_limit
+            );
+            Some(quote)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to quote the relay fee in {}: {e}", fee.denom);
+            None
+        }
+    }
+}
+
+/// Attaches the fee quote to the response metadata.
+pub fn insert_fee_quote(quote: &FeeQuote, metadata: &mut MetadataMap) {
+    match format!("{}{}", quote.fee.amount, quote.fee.denom).parse() {
+        Ok(fee) => {
+            metadata.insert(RELAY_FEE_METADATA_KEY, fee);
+            metadata.insert(RELAY_GAS_LIMIT_METADATA_KEY, quote.gas_limit.into());
+        }
+        Err(e) => tracing::warn!("Failed to encode the fee quote: {e}"),
+    }
+}
+
+/// Copies the relay hints, the fee quote and the deferred packet sequences, from the response metadata of
+/// another relayer.
+pub fn forward_relay_hints(from: &MetadataMap, to: &mut MetadataMap) {
+    for key in [
+        RELAY_PRIORITY_METADATA_KEY,
+        RELAY_FEE_BUMP_METADATA_KEY,
+        RELAY_FEE_METADATA_KEY,
+        RELAY_GAS_LIMIT_METADATA_KEY,
+        DEFERRED_SEQUENCES_METADATA_KEY,
+        GAS_DEFERRED_METADATA_KEY,
+    ] {
+        if let Some(value) = from.get(key) {
+            to.insert(key, value.clone());
+        }
+    }
+}
+
+/// Attaches the packet transaction of a split relay to the response metadata.
+pub fn insert_packet_tx(packet_tx: &[u8], metadata: &mut MetadataMap) {
+    metadata.insert_bin(
+        RELAY_PACKET_TX_METADATA_KEY,
+        MetadataValue::from_bytes(packet_tx),
+    );
+}
+
+/// Attaches the transactions following the one of the response to the response metadata. The
+/// following transactions of a relay split by size are all attached in order, and otherwise the
+/// packet transaction of a split relay, if any.
+pub fn insert_next_txs(next_txs: &[Vec<u8>], size_split: bool, metadata: &mut MetadataMap) {
+    if !size_split {
+        if let Some(packet_tx) = next_txs.first() {
+            insert_packet_tx(packet_tx, metadata);
+        }
+        return;
+    }
+
+    for tx in next_txs {
+        metadata.append_bin(RELAY_NEXT_TX_METADATA_KEY, MetadataValue::from_bytes(tx));
+    }
+}
+
+/// Converts the error of a relay into a status, with its retry class attached to the metadata.
+pub fn relay_error(err: anyhow::Error) -> tonic::Status {
+    let class = retry::classify_error(&err);
+    let mut status = tonic::Status::from_error(err.into());
+    match MetadataValue::try_from(class.to_string()) {
+        Ok(value) => {
+            status
+                .metadata_mut()
+                .insert(RELAY_RETRY_METADATA_KEY, value);
+        }
+        Err(_) => tracing::warn!("Failed to encode the retry class."),
+    }
+    status
+}
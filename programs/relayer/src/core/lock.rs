@@ -0,0 +1,117 @@
+//! Defines the lock on the persisted state directory of the relayer.
+//!
+//! Two relayers writing to the same directory would interleave their writes, so a relayer takes an
+//! advisory lock on the directory before it starts, and releases it when it exits. A relayer
+//! started against a locked directory refuses to start, and reports which relayer holds the lock.
+//! The lock can be taken over with `--force-takeover`, e.g. when a network filesystem keeps the
+//! lock of a host that is gone.
+//!
+//! The persisted state directory is the `packet_archive` directory, which holds the packet archive
+//! and the sealed relays of the artifact escrow, the only state the relayer keeps on disk.
+
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// The name of the lock file in the persisted state directory.
+pub const LOCK_FILE_NAME: &str = "relayer.lock";
+
+/// The relayer holding the lock, written to the lock file for diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    /// The process identifier of the relayer.
+    pub pid: u32,
+    /// The unix timestamp at which the relayer took the lock.
+    pub locked_at: u64,
+}
+
+/// The lock on a persisted state directory, released when dropped.
+#[derive(Debug)]
+pub struct StateDirLock {
+    path: PathBuf,
+    /// The lock file, whose lock is released when it is closed.
+    _file: File,
+}
+
+impl StateDirLock {
+    /// Returns the path of the lock file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Takes the lock on the persisted state directory `dir`, creating the directory if needed. If
+/// the directory is locked by another relayer, the lock is taken over if `force_takeover` is set.
+/// # Errors
+/// Returns an error if the directory is locked by another relayer and `force_takeover` is not
+/// set, or if the lock file cannot be written.
+pub fn lock_state_dir(dir: &Path, force_takeover: bool) -> Result<StateDirLock> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create state directory {}", dir.display()))?;
+    let path = dir.join(LOCK_FILE_NAME);
+    // Not truncated before it is locked, so that the holder of the lock can be reported
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("failed to open lock file {}", path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let holder = read_holder(&mut file).map_or_else(
+                || "another relayer".to_string(),
+                |holder| {
+                    format!(
+                        "the relayer with pid {} since {}",
+                        holder.pid, holder.locked_at
+                    )
+                },
+            );
+            if !force_takeover {
+                bail!(
+                    "state directory {} is locked by {holder}; running two relayers against the \
+                     same state directory corrupts it. Stop the other relayer, or start with \
+                     --force-takeover if it is not running anymore",
+                    dir.display()
+                );
+            }
+            tracing::warn!(
+                "Taking over the state directory {} locked by {holder}",
+                dir.display()
+            );
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(e).with_context(|| format!("failed to lock {}", path.display()));
+        }
+    }
+
+    let holder = LockHolder {
+        pid: std::process::id(),
+        locked_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs(),
+    };
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all(&serde_json::to_vec(&holder)?)?;
+    file.sync_all()?;
+
+    tracing::info!("Locked the state directory {}", dir.display());
+    Ok(StateDirLock { path, _file: file })
+}
+
+/// Reads the holder of the lock from the lock file, if it can be decoded.
+fn read_holder(file: &mut File) -> Option<LockHolder> {
+    let mut holder_bz = Vec::new();
+    file.read_to_end(&mut holder_bz).ok()?;
+    serde_json::from_slice(&holder_bz).ok()
+}
@@ -1,4 +1,16 @@
 //! This module defines the core functionality of the relayer server.
 
+pub mod approvals;
+pub mod archive;
+pub mod artifact;
 pub mod builder;
+pub mod deadline;
+pub mod escrow;
+pub mod gas;
+pub mod hints;
+pub mod lock;
 pub mod modules;
+pub mod orphans;
+pub mod scheduler;
+pub mod state;
+pub mod sync_committees;
@@ -0,0 +1,149 @@
+//! Defines the cleanup of the packets orphaned by a frozen or removed counterparty client.
+//!
+//! IBC v2 has no channels to close, so the packets sent to a counterparty client that was frozen
+//! or removed stay committed, with their funds escrowed, until they are timed out. Their sending
+//! transactions are usually long forgotten, so the cleanup reads them from the packet archive of
+//! the relayer: `POST /orphans` schedules the timeouts of the archived packets of a route whose
+//! timeout passed, which are then listed with `GET /scheduled`, and reports the packets whose
+//! timeout has not passed yet.
+
+use alloy::primitives::B256;
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use warp::{http::StatusCode, Filter};
+
+use crate::core::{
+    archive,
+    scheduler::{self, AckRelay},
+};
+
+/// The default maximum number of packets timed out by a cleanup.
+pub const DEFAULT_MAX_ORPHANED_PACKETS: usize = 100;
+
+/// A cleanup of the packets orphaned by a counterparty client.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanCleanup {
+    /// The chain of the frozen or removed counterparty client.
+    pub src_chain: String,
+    /// The healthy chain the packets were sent from, where they are timed out.
+    pub dst_chain: String,
+    /// The frozen or removed counterparty client, the destination client of the packets.
+    pub src_client_id: String,
+    /// The client on the healthy chain, the source client of the packets.
+    pub dst_client_id: String,
+    /// The maximum number of packets timed out by the cleanup, lowest sequences first.
+    #[serde(default = "default_max_packets")]
+    pub max_packets: usize,
+}
+
+/// An orphaned packet whose timeout has not passed yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingTimeout {
+    /// The sequence of the packet.
+    pub sequence: u64,
+    /// The timeout of the packet, in seconds.
+    pub timeout_timestamp: u64,
+}
+
+/// The outcome of a cleanup of the orphaned packets.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanCleanupReport {
+    /// The sequences of the packets whose timeouts were scheduled.
+    pub scheduled: Vec<u64>,
+    /// The packets to be timed out by a later cleanup.
+    pub pending: Vec<PendingTimeout>,
+}
+
+const fn default_max_packets() -> usize {
+    DEFAULT_MAX_ORPHANED_PACKETS
+}
+
+/// Schedules the timeouts of the archived packets orphaned by the counterparty client of a
+/// cleanup whose timeout passed.
+/// # Errors
+/// Returns an error if the relayer has no packet archive, or if the archive cannot be read.
+pub fn clean_up_orphans(cleanup: OrphanCleanup) -> Result<OrphanCleanupReport> {
+    let outstanding = archive::outstanding_packets(
+        &cleanup.dst_chain,
+        &cleanup.dst_client_id,
+        &cleanup.src_client_id,
+        cleanup.max_packets,
+    )?;
+    let report = OrphanCleanupReport {
+        scheduled: outstanding
+            .timed_out
+            .iter()
+            .map(|preimage| preimage.sequence)
+            .collect(),
+        pending: outstanding
+            .pending
+            .iter()
+            .map(|preimage| PendingTimeout {
+                sequence: preimage.sequence,
+                timeout_timestamp: preimage.timeout_timestamp,
+            })
+            .collect(),
+    };
+    if report.scheduled.is_empty() {
+        return Ok(report);
+    }
+
+    let commitments = outstanding
+        .timed_out
+        .iter()
+        .map(|preimage| preimage.commitment)
+        .collect::<Vec<B256>>();
+    tracing::info!(
+        ?cleanup,
+        sequences = ?report.scheduled,
+        "Scheduling the timeouts of the orphaned packets."
+    );
+    scheduler::schedule_orphaned_timeouts(
+        AckRelay {
+            src_chain: cleanup.src_chain,
+            dst_chain: cleanup.dst_chain,
+            src_client_id: cleanup.src_client_id,
+            dst_client_id: cleanup.dst_client_id,
+        },
+        &commitments,
+        report.scheduled.clone(),
+    );
+    Ok(report)
+}
+
+/// Requests a cleanup of the orphaned packets from a running relayer.
+/// # Errors
+/// Returns an error if the request fails or is rejected by the relayer.
+pub async fn request_cleanup(
+    endpoint: &str,
+    cleanup: &OrphanCleanup,
+) -> Result<OrphanCleanupReport> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/orphans", endpoint.trim_end_matches('/')))
+        .json(cleanup)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let body = response.text().await?;
+    ensure!(
+        status.is_success(),
+        "failed to clean up the orphaned packets ({status}): {body}"
+    );
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Returns the routes of the orphan cleanup API.
+#[must_use]
+pub fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("orphans")
+        .and(warp::post())
+        .and(warp::body::json())
+        .map(|cleanup: OrphanCleanup| match clean_up_orphans(cleanup) {
+            Ok(report) => warp::reply::with_status(warp::reply::json(&report), StatusCode::OK),
+            Err(e) => {
+                tracing::warn!("Failed to clean up the orphaned packets: {e}");
+                warp::reply::with_status(warp::reply::json(&e.to_string()), StatusCode::BAD_REQUEST)
+            }
+        })
+}
@@ -0,0 +1,415 @@
+//! Defines the scheduler of the ack relays that depend on the recvs relayed by the modules.
+//!
+//! A module with an `ack_schedule` watches its destination chain for the acknowledgements of the
+//! packets it relayed. Once they are written, the ack relay of the opposite route is built through
+//! the relayer router, and the transaction is listed with `GET /scheduled` on the metrics server,
+//! so that round trips no longer wait for the submitter to request the ack relay.
+//!
+//! A module with an `ack_reconciliation` also schedules, once started, the ack relays of the
+//! acknowledgements that were written before, so that a newly started relayer relays back the
+//! historical acks and not only the ones of the packets it relayed itself.
+//!
+//! The relays deferred by the gas spike guard of a module are scheduled the same way once the
+//! spike ends, and so are the timeouts of the packets orphaned by a frozen or removed counterparty
+//! client.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use alloy::primitives::{hex, B256};
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    future, StreamExt,
+};
+use ibc_eureka_relayer_lib::{
+    chain::{Chain, CosmosSdk},
+    gas_spike::DeferredRelay,
+    listener::{cosmos_sdk, ChainListenerService},
+    lookback::{self, MaxLookback},
+    reconciliation::{self, AckReconciliation, ReconciledRoute},
+    scheduler::{self, AckSchedule},
+};
+use ibc_eureka_utils::rpc::TendermintRpcExt;
+use serde::Serialize;
+use tonic::Request;
+use warp::Filter;
+
+use crate::api::{self, relayer_service_server::RelayerService};
+
+/// The maximum number of scheduled relays kept for the submitters.
+pub const MAX_SCHEDULED_RELAYS: usize = 256;
+
+/// The ack relay of a round trip, on the route opposite to the one that relayed the recvs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AckRelay {
+    /// The chain the acknowledgements are written on.
+    pub src_chain: String,
+    /// The chain the acks are relayed to.
+    pub dst_chain: String,
+    /// The client on the source chain.
+    pub src_client_id: String,
+    /// The client on the destination chain, which is the source client of the packets.
+    pub dst_client_id: String,
+}
+
+impl AckRelay {
+    /// Returns the ack relay of the packets relayed along `route`.
+    #[must_use]
+    pub fn reversed(route: &ReconciledRoute) -> Self {
+        Self {
+            src_chain: route.dst_chain.clone(),
+            dst_chain: route.src_chain.clone(),
+            src_client_id: route.dst_client_id.clone(),
+            dst_client_id: route.src_client_id.clone(),
+        }
+    }
+}
+
+/// The reason a relay was scheduled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleReason {
+    /// The acknowledgements of the relayed packets were written.
+    Ack,
+    /// The relay was deferred during a gas spike, which ended.
+    GasSpikeDeferral,
+    /// The packets sent to a frozen or removed counterparty client timed out.
+    OrphanedTimeout,
+}
+
+/// A relay ready to be built, such as an ack relay whose acknowledgements were written on the
+/// source chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ReadyRelay {
+    ack_relay: AckRelay,
+    source_tx_ids: Vec<Vec<u8>>,
+    timeout_tx_ids: Vec<Vec<u8>>,
+    timeout_packet_commitments: Vec<Vec<u8>>,
+    src_packet_sequences: Vec<u64>,
+    dst_packet_sequences: Vec<u64>,
+    reason: ScheduleReason,
+}
+
+/// A relay transaction built by the scheduler.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ScheduledRelay {
+    /// The route of the relay.
+    #[serde(flatten)]
+    pub ack_relay: AckRelay,
+    /// The reason the relay was scheduled.
+    pub reason: ScheduleReason,
+    /// The hex encoded transactions that wrote the acknowledgements, or sent the packets.
+    pub source_tx_ids: Vec<String>,
+    /// The hex encoded transactions of the timed out packets.
+    pub timeout_tx_ids: Vec<String>,
+    /// The hex encoded commitments of the timed out packets read from the packet archive.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timeout_packet_commitments: Vec<String>,
+    /// The hex encoded relay transaction to be submitted.
+    pub tx: String,
+    /// The contract address to submit the transaction to, if applicable.
+    pub address: String,
+}
+
+/// The channel of the relays ready to be built.
+static READY_RELAYS: LazyLock<(
+    UnboundedSender<ReadyRelay>,
+    Mutex<Option<UnboundedReceiver<ReadyRelay>>>,
+)> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::unbounded();
+    (sender, Mutex::new(Some(receiver)))
+});
+
+/// The relay transactions built by the scheduler, oldest first.
+static SCHEDULED_RELAYS: LazyLock<Mutex<VecDeque<ScheduledRelay>>> = LazyLock::new(Mutex::default);
+
+/// Watches the destination chain of a module for the acknowledgements of the packets it relayed,
+/// and schedules the ack relay once they are written.
+///
+/// The packets are identified by the sequences they were sent with from `ack_relay.dst_client_id`,
+/// and the acknowledgements are searched from `start_height` on.
+pub fn schedule_acks<C, L>(
+    schedule: AckSchedule,
+    listener: L,
+    sequences: Vec<u64>,
+    ack_relay: AckRelay,
+    start_height: C::Height,
+) where
+    C: Chain + 'static,
+    C::TxId: AsRef<[u8]> + PartialEq + Send,
+    C::Height: Send,
+    L: ChainListenerService<C> + Send + Sync + 'static,
+{
+    if sequences.is_empty() {
+        return;
+    }
+
+    tracing::info!(
+        ?ack_relay,
+        ?sequences,
+        "Waiting for the acknowledgements of the relayed packets."
+    );
+    tokio::spawn(async move {
+        let tx_ids = match scheduler::wait_for_write_acks(
+            &listener,
+            &schedule,
+            &ack_relay.dst_client_id,
+            sequences,
+            start_height,
+        )
+        .await
+        {
+            Ok(tx_ids) if !tx_ids.is_empty() => tx_ids,
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!("Failed to wait for the acknowledgements: {e}");
+                return;
+            }
+        };
+
+        send_ready_ack_relay(ack_relay, &tx_ids);
+    });
+}
+
+/// Schedules the ack relays of the acknowledgements written on the destination chain of a module
+/// for the packets sent from a Cosmos SDK chain whose commitments are still stored, i.e. that
+/// were never acknowledged on the source chain.
+///
+/// The destination chain is searched below `dst_height`, its latest confirmed height. The scans of
+/// both chains are trimmed to the `max_lookback` of the module, unless the reconciliation is a
+/// deep scan.
+pub async fn reconcile_acks<D, L>(
+    reconciliation: AckReconciliation,
+    max_lookback: MaxLookback,
+    src_listener: cosmos_sdk::ChainListener,
+    dst_listener: L,
+    dst_height: u64,
+) where
+    D: Chain<Height = u64>,
+    D::TxId: AsRef<[u8]> + PartialEq,
+    L: ChainListenerService<D> + Sync,
+{
+    let src_height = match src_listener.confirmed_height().await {
+        Ok(src_height) => src_height,
+        Err(e) => {
+            tracing::warn!("Failed to reconcile the acknowledgements: {e}");
+            return;
+        }
+    };
+    let lookbacks = future::try_join(
+        lookback::trimmed_lookback::<CosmosSdk, _>(
+            &src_listener,
+            &max_lookback,
+            src_height,
+            reconciliation.lookback_blocks,
+            reconciliation.deep_scan,
+        ),
+        lookback::trimmed_lookback::<D, _>(
+            &dst_listener,
+            &max_lookback,
+            dst_height,
+            reconciliation.ack_lookback_blocks,
+            reconciliation.deep_scan,
+        ),
+    )
+    .await;
+    let reconciliation = match lookbacks {
+        Ok((lookback_blocks, ack_lookback_blocks)) => AckReconciliation {
+            lookback_blocks,
+            ack_lookback_blocks,
+            ..reconciliation
+        },
+        Err(e) => {
+            tracing::warn!("Failed to reconcile the acknowledgements: {e}");
+            return;
+        }
+    };
+
+    let tm_client = src_listener.client();
+    for route in &reconciliation.routes {
+        let client_id = route.src_client_id.as_str();
+        let tx_ids = reconciliation::find_unrelayed_acks::<CosmosSdk, D, _, _, _, _>(
+            &src_listener,
+            &dst_listener,
+            &reconciliation,
+            &route.src_client_id,
+            src_height,
+            dst_height,
+            move |sequence| async move {
+                let (value, _) = tm_client
+                    .prove_packet_commitment(client_id, sequence, src_height)
+                    .await?;
+                Ok(!value.is_empty())
+            },
+        )
+        .await;
+
+        match tx_ids {
+            Ok(tx_ids) if tx_ids.is_empty() => {
+                tracing::info!(?route, "No unrelayed acknowledgements to reconcile.");
+            }
+            Ok(tx_ids) => {
+                tracing::info!(
+                    ?route,
+                    "Reconciled {} unrelayed acknowledgements.",
+                    tx_ids.len()
+                );
+                send_ready_ack_relay(AckRelay::reversed(route), &tx_ids);
+            }
+            Err(e) => tracing::warn!(?route, "Failed to reconcile the acknowledgements: {e}"),
+        }
+    }
+}
+
+/// Sends an ack relay whose acknowledgements were written by `tx_ids` to the scheduler.
+fn send_ready_ack_relay<TxId: AsRef<[u8]>>(ack_relay: AckRelay, tx_ids: &[TxId]) {
+    send_ready_relay(ReadyRelay {
+        ack_relay,
+        source_tx_ids: tx_ids.iter().map(|tx_id| tx_id.as_ref().to_vec()).collect(),
+        timeout_tx_ids: vec![],
+        timeout_packet_commitments: vec![],
+        src_packet_sequences: vec![],
+        dst_packet_sequences: vec![],
+        reason: ScheduleReason::Ack,
+    });
+}
+
+/// Schedules a relay deferred during a gas spike, restricted to the packets that were deferred.
+pub fn schedule_deferred_relay(relay: DeferredRelay) {
+    let DeferredRelay {
+        src_chain,
+        dst_chain,
+        src_client_id,
+        dst_client_id,
+        source_tx_ids,
+        timeout_tx_ids,
+        sequences,
+        ..
+    } = relay;
+
+    send_ready_relay(ReadyRelay {
+        ack_relay: AckRelay {
+            src_chain,
+            dst_chain,
+            src_client_id,
+            dst_client_id,
+        },
+        source_tx_ids: source_tx_ids.iter().map(|tx_id| tx_id.to_vec()).collect(),
+        timeout_tx_ids: timeout_tx_ids.iter().map(|tx_id| tx_id.to_vec()).collect(),
+        timeout_packet_commitments: vec![],
+        src_packet_sequences: sequences.recv,
+        dst_packet_sequences: sequences.ack.into_iter().chain(sequences.timeout).collect(),
+        reason: ScheduleReason::GasSpikeDeferral,
+    });
+}
+
+/// Schedules the timeouts of the archived packets sent from `route.dst_client_id` to the frozen
+/// or removed counterparty client `route.src_client_id`, whose pre-images hash to `commitments`.
+pub fn schedule_orphaned_timeouts(route: AckRelay, commitments: &[B256], sequences: Vec<u64>) {
+    send_ready_relay(ReadyRelay {
+        ack_relay: route,
+        source_tx_ids: vec![],
+        timeout_tx_ids: vec![],
+        timeout_packet_commitments: commitments
+            .iter()
+            .map(|commitment| commitment.to_vec())
+            .collect(),
+        src_packet_sequences: vec![],
+        dst_packet_sequences: sequences,
+        reason: ScheduleReason::OrphanedTimeout,
+    });
+}
+
+/// Sends a relay to the scheduler.
+fn send_ready_relay(ready: ReadyRelay) {
+    if READY_RELAYS.0.unbounded_send(ready).is_err() {
+        tracing::warn!("The relay scheduler is not running.");
+    }
+}
+
+/// Builds the scheduled relays once they are ready, through the relayer router.
+/// Only the first call consumes the relays, later calls return immediately.
+/// # Panics
+/// Panics if the channel lock is poisoned.
+pub async fn run<S: RelayerService>(relayer: Arc<S>) {
+    let Some(mut receiver) = READY_RELAYS.1.lock().unwrap().take() else {
+        return;
+    };
+
+    while let Some(ready) = receiver.next().await {
+        let ReadyRelay {
+            ack_relay,
+            source_tx_ids,
+            timeout_tx_ids,
+            timeout_packet_commitments,
+            src_packet_sequences,
+            dst_packet_sequences,
+            reason,
+        } = ready;
+
+        let request = Request::new(api::RelayByTxRequest {
+            src_chain: ack_relay.src_chain.clone(),
+            dst_chain: ack_relay.dst_chain.clone(),
+            source_tx_ids: source_tx_ids.clone(),
+            timeout_tx_ids: timeout_tx_ids.clone(),
+            src_client_id: ack_relay.src_client_id.clone(),
+            dst_client_id: ack_relay.dst_client_id.clone(),
+            src_packet_sequences,
+            dst_packet_sequences,
+            timeout_packet_commitments: timeout_packet_commitments.clone(),
+        });
+
+        match relayer.relay_by_tx(request).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                tracing::info!(?ack_relay, ?reason, "Scheduled relay built.");
+                push_scheduled_relay(ScheduledRelay {
+                    ack_relay,
+                    reason,
+                    source_tx_ids: source_tx_ids.iter().map(hex::encode_prefixed).collect(),
+                    timeout_tx_ids: timeout_tx_ids.iter().map(hex::encode_prefixed).collect(),
+                    timeout_packet_commitments: timeout_packet_commitments
+                        .iter()
+                        .map(hex::encode_prefixed)
+                        .collect(),
+                    tx: hex::encode_prefixed(response.tx),
+                    address: response.address,
+                });
+            }
+            Err(e) => {
+                tracing::warn!(
+                    ?ack_relay,
+                    ?reason,
+                    "Failed to build the scheduled relay: {e}"
+                );
+            }
+        }
+    }
+}
+
+/// Keeps a scheduled relay, dropping the oldest one if too many are kept.
+fn push_scheduled_relay(relay: ScheduledRelay) {
+    let mut relays = SCHEDULED_RELAYS.lock().unwrap();
+    if relays.len() == MAX_SCHEDULED_RELAYS {
+        relays.pop_front();
+    }
+    relays.push_back(relay);
+}
+
+/// Returns the relay transactions built by the scheduler, oldest first.
+/// # Panics
+/// Panics if the lock is poisoned.
+#[must_use]
+pub fn scheduled_relays() -> Vec<ScheduledRelay> {
+    SCHEDULED_RELAYS.lock().unwrap().iter().cloned().collect()
+}
+
+/// Returns the routes of the scheduler API.
+#[must_use]
+pub fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("scheduled")
+        .and(warp::get())
+        .map(|| warp::reply::json(&scheduled_relays()))
+}
@@ -0,0 +1,133 @@
+//! Defines the export and import of the relayer state.
+//!
+//! The state of a running relayer is served with `GET /state` and replaced with `PUT /state` on
+//! the metrics server, so that it can be migrated to another host with the `state export` and
+//! `state import` commands instead of being rebuilt from scratch.
+//!
+//! The relayer currently keeps no scan cursors or packet database: every relay is driven by the
+//! source transactions in the request. The only state is the value at risk windows and the
+//! pending approvals of the modules.
+
+use anyhow::{ensure, Context, Result};
+use ibc_eureka_relayer_lib::value_limit::ValueLimiterSnapshot;
+use serde::{Deserialize, Serialize};
+use warp::{http::StatusCode, Filter};
+
+use super::approvals;
+
+/// The version of the state snapshot format.
+pub const STATE_SNAPSHOT_VERSION: u32 = 1;
+
+/// A portable snapshot of the state of a relayer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayerStateSnapshot {
+    /// The version of the snapshot format.
+    pub version: u32,
+    /// The states of the value rate limiters, in the order of the modules in the configuration.
+    pub value_limiters: Vec<ValueLimiterSnapshot>,
+}
+
+/// Returns a snapshot of the state of the relayer.
+#[must_use]
+pub fn export_state() -> RelayerStateSnapshot {
+    RelayerStateSnapshot {
+        version: STATE_SNAPSHOT_VERSION,
+        value_limiters: approvals::value_limiters()
+            .iter()
+            .map(|value_limiter| value_limiter.snapshot())
+            .collect(),
+    }
+}
+
+/// Replaces the state of the relayer with a snapshot.
+/// The snapshot must be taken from a relayer with the same modules configuration.
+/// # Errors
+/// Returns an error if the snapshot version is not supported or if the snapshot does not match
+/// the configured modules.
+pub fn import_state(snapshot: RelayerStateSnapshot) -> Result<()> {
+    ensure!(
+        snapshot.version == STATE_SNAPSHOT_VERSION,
+        "unsupported state snapshot version {}, expected {STATE_SNAPSHOT_VERSION}",
+        snapshot.version
+    );
+
+    let value_limiters = approvals::value_limiters();
+    ensure!(
+        snapshot.value_limiters.len() == value_limiters.len(),
+        "state snapshot has {} value limiters, but {} are configured",
+        snapshot.value_limiters.len(),
+        value_limiters.len()
+    );
+
+    value_limiters
+        .iter()
+        .zip(snapshot.value_limiters)
+        .for_each(|(value_limiter, snapshot)| value_limiter.restore(snapshot));
+    Ok(())
+}
+
+/// Fetches the state snapshot of the relayer serving the metrics server at `endpoint`.
+/// # Errors
+/// Returns an error if the request fails.
+pub async fn fetch_state(endpoint: &str) -> Result<RelayerStateSnapshot> {
+    let snapshot = reqwest::get(format!("{}/state", endpoint.trim_end_matches('/')))
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(snapshot)
+}
+
+/// Uploads a state snapshot to the relayer serving the metrics server at `endpoint`.
+/// # Errors
+/// Returns an error if the request fails or the relayer rejects the snapshot.
+pub async fn upload_state(endpoint: &str, snapshot: &RelayerStateSnapshot) -> Result<()> {
+    let response = reqwest::Client::new()
+        .put(format!("{}/state", endpoint.trim_end_matches('/')))
+        .json(snapshot)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let body = response.text().await?;
+    ensure!(
+        status.is_success(),
+        "failed to import state ({status}): {body}"
+    );
+    Ok(())
+}
+
+/// Reads a state snapshot from a file.
+/// # Errors
+/// Returns an error if the file cannot be read or decoded.
+pub fn read_snapshot(path: &str) -> Result<RelayerStateSnapshot> {
+    let snapshot_bz =
+        std::fs::read(path).with_context(|| format!("failed to read state snapshot {path}"))?;
+    Ok(serde_json::from_slice(&snapshot_bz)?)
+}
+
+/// Returns the routes of the state API.
+#[must_use]
+pub fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let export = warp::path!("state")
+        .and(warp::get())
+        .map(|| warp::reply::json(&export_state()));
+
+    let import = warp::path!("state")
+        .and(warp::put())
+        .and(warp::body::json())
+        .map(
+            |snapshot: RelayerStateSnapshot| match import_state(snapshot) {
+                Ok(()) => {
+                    tracing::info!("Relayer state imported.");
+                    warp::reply::with_status(String::new(), StatusCode::OK)
+                }
+                Err(e) => {
+                    tracing::warn!("Relayer state import rejected: {e}");
+                    warp::reply::with_status(e.to_string(), StatusCode::BAD_REQUEST)
+                }
+            },
+        );
+
+    export.or(import)
+}
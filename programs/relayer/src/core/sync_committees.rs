@@ -0,0 +1,148 @@
+//! Defines the export and import of the sync committees verified by the relayer.
+//!
+//! All the `eth_to_cosmos` modules share one store of the sync committees they verified. The
+//! committees are served with `GET /sync-committees` and merged with `POST /sync-committees` on
+//! the metrics server, so that a new relayer instance can be seeded with the `sync-committees
+//! export` and `sync-committees import` commands instead of learning every committee from the
+//! Beacon API again.
+//!
+//! The sync committee participation of the recent verified headers of a chain is served with
+//! `GET /sync-committees/participation/{chain_id}`, and recorded in the
+//! `eureka_relayer_sync_committee_*` metrics.
+
+use std::sync::{Arc, LazyLock};
+
+use anyhow::{ensure, Context, Result};
+use ibc_eureka_relayer_lib::sync_committee::{SyncCommitteeSnapshot, SyncCommitteeStore};
+use serde::{Deserialize, Serialize};
+use warp::{http::StatusCode, Filter};
+
+use crate::metrics;
+
+/// The version of the sync committee snapshot format.
+pub const SYNC_COMMITTEES_SNAPSHOT_VERSION: u32 = 1;
+
+/// The sync committee store shared by all the relayer modules.
+static SYNC_COMMITTEES: LazyLock<Arc<SyncCommitteeStore>> = LazyLock::new(|| {
+    Arc::new(
+        SyncCommitteeStore::default()
+            .with_participation_hook(metrics::observe_sync_committee_participation),
+    )
+});
+
+/// A portable snapshot of the sync committees verified by a relayer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCommitteesSnapshot {
+    /// The version of the snapshot format.
+    pub version: u32,
+    /// The sync committees, by beacon chain and period.
+    pub sync_committees: Vec<SyncCommitteeSnapshot>,
+}
+
+/// Returns the sync committee store shared by all the relayer modules.
+#[must_use]
+pub fn sync_committees() -> Arc<SyncCommitteeStore> {
+    SYNC_COMMITTEES.clone()
+}
+
+/// Returns a snapshot of the sync committees of the relayer.
+#[must_use]
+pub fn export_sync_committees() -> SyncCommitteesSnapshot {
+    SyncCommitteesSnapshot {
+        version: SYNC_COMMITTEES_SNAPSHOT_VERSION,
+        sync_committees: SYNC_COMMITTEES.export(),
+    }
+}
+
+/// Merges a snapshot into the sync committees of the relayer, and returns the number of sync
+/// committees that were not known yet.
+/// # Errors
+/// Returns an error if the snapshot version is not supported or if a sync committee is rejected.
+pub fn import_sync_committees(snapshot: SyncCommitteesSnapshot) -> Result<usize> {
+    ensure!(
+        snapshot.version == SYNC_COMMITTEES_SNAPSHOT_VERSION,
+        "unsupported sync committee snapshot version {}, expected {SYNC_COMMITTEES_SNAPSHOT_VERSION}",
+        snapshot.version
+    );
+    SYNC_COMMITTEES.import(snapshot.sync_committees)
+}
+
+/// Fetches the sync committee snapshot of the relayer serving the metrics server at `endpoint`.
+/// # Errors
+/// Returns an error if the request fails.
+pub async fn fetch_sync_committees(endpoint: &str) -> Result<SyncCommitteesSnapshot> {
+    let snapshot = reqwest::get(format!(
+        "{}/sync-committees",
+        endpoint.trim_end_matches('/')
+    ))
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+    Ok(snapshot)
+}
+
+/// Uploads a sync committee snapshot to the relayer serving the metrics server at `endpoint`, and
+/// returns its response.
+/// # Errors
+/// Returns an error if the request fails or the relayer rejects the snapshot.
+pub async fn upload_sync_committees(
+    endpoint: &str,
+    snapshot: &SyncCommitteesSnapshot,
+) -> Result<String> {
+    let response = reqwest::Client::new()
+        .post(format!(
+            "{}/sync-committees",
+            endpoint.trim_end_matches('/')
+        ))
+        .json(snapshot)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let body = response.text().await?;
+    ensure!(
+        status.is_success(),
+        "failed to import sync committees ({status}): {body}"
+    );
+    Ok(body)
+}
+
+/// Reads a sync committee snapshot from a file.
+/// # Errors
+/// Returns an error if the file cannot be read or decoded.
+pub fn read_snapshot(path: &str) -> Result<SyncCommitteesSnapshot> {
+    let snapshot_bz = std::fs::read(path)
+        .with_context(|| format!("failed to read sync committee snapshot {path}"))?;
+    Ok(serde_json::from_slice(&snapshot_bz)?)
+}
+
+/// Returns the routes of the sync committee API.
+#[must_use]
+pub fn routes() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let export = warp::path!("sync-committees")
+        .and(warp::get())
+        .map(|| warp::reply::json(&export_sync_committees()));
+
+    let import = warp::path!("sync-committees")
+        .and(warp::post())
+        .and(warp::body::json())
+        .map(
+            |snapshot: SyncCommitteesSnapshot| match import_sync_committees(snapshot) {
+                Ok(imported) => {
+                    tracing::info!("Imported {imported} new sync committees.");
+                    warp::reply::with_status(format!("imported {imported}"), StatusCode::OK)
+                }
+                Err(e) => {
+                    tracing::warn!("Sync committee import rejected: {e}");
+                    warp::reply::with_status(e.to_string(), StatusCode::BAD_REQUEST)
+                }
+            },
+        );
+
+    let participation = warp::path!("sync-committees" / "participation" / u64)
+        .and(warp::get())
+        .map(|chain_id: u64| warp::reply::json(&SYNC_COMMITTEES.participation(chain_id)));
+
+    export.or(import).or(participation)
+}
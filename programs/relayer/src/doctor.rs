@@ -0,0 +1,908 @@
+//! Defines the self-test suite run by `relayer doctor` before a route is enabled.
+//!
+//! The doctor codifies the checks operators otherwise run by hand against the configuration of a
+//! relayer: that the configuration is consistent, that every endpoint of the enabled modules is
+//! reachable and on the expected network, that the submitters of the transactions exist and can
+//! pay for them, and that the endpoints serve the light client data and the historical proofs the
+//! relays need. Every check is reported, so that a single run lists all the problems of a route.
+
+use std::{collections::BTreeMap, fmt, future::Future, path::Path, str::FromStr, time::Duration};
+
+use alloy::{
+    eips::BlockId,
+    primitives::Address,
+    providers::{Provider, RootProvider},
+};
+use anyhow::Result;
+use ethereum_apis::{circuit_breaker::CircuitBreakerConfig, eth_api::client::EthApiClient};
+use ibc_eureka_relayer_lib::{authz::AuthzConfig, fees::FeeConfig};
+use ibc_eureka_utils::{http::HttpClientConfig, rpc::TendermintRpcExt};
+use ibc_proto_eureka::cosmos::{
+    auth::v1beta1::{BaseAccount, QueryAccountRequest, QueryAccountResponse},
+    bank::v1beta1::{
+        QueryAllBalancesRequest, QueryAllBalancesResponse, QueryBalanceRequest,
+        QueryBalanceResponse,
+    },
+};
+use prost::Message;
+use serde::Serialize;
+use tendermint_rpc::{Client, HttpClient};
+use tracing::Level;
+
+use crate::{
+    cli::config::{ModuleConfig, RelayerConfig},
+    core::modules::RelayerModule,
+    modules::{
+        connect_eth_provider,
+        cosmos_to_cosmos::{CosmosToCosmosConfig, CosmosToCosmosRelayerModule},
+        cosmos_to_eth::{CosmosToEthConfig, CosmosToEthRelayerModule},
+        eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+        remote::{RemoteConfig, RemoteRelayerModule},
+    },
+};
+
+/// The default time after which a check of an endpoint fails, in seconds.
+pub const DEFAULT_CHECK_TIMEOUT_SECS: u64 = 10;
+/// The depth, in blocks, of the state proven to check that an execution RPC endpoint serves
+/// historical proofs. Full nodes only keep the state of the last 128 blocks.
+pub const ARCHIVE_PROOF_DEPTH: u64 = 1024;
+
+/// The name of the file written to check that the packet archive is writable.
+const PROBE_FILE_NAME: &str = ".doctor-probe";
+
+/// The status of a check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// The check passed.
+    Pass,
+    /// The check passed, but the route may not behave as the operator expects.
+    Warn,
+    /// The check failed, and the route must not be enabled.
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass => write!(f, "PASS"),
+            Self::Warn => write!(f, "WARN"),
+            Self::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+/// The category of a check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckCategory {
+    /// The consistency of the configuration, with itself and with the chains.
+    Configuration,
+    /// The reachability of the endpoints.
+    Connectivity,
+    /// The ability of the submitters to sign and pay for the relay transactions.
+    Permissions,
+    /// The data the endpoints serve to the relays, such as light client updates and proofs.
+    Capabilities,
+}
+
+impl fmt::Display for CheckCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Configuration => write!(f, "configuration"),
+            Self::Connectivity => write!(f, "connectivity"),
+            Self::Permissions => write!(f, "permissions"),
+            Self::Capabilities => write!(f, "capabilities"),
+        }
+    }
+}
+
+/// The result of a check.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Check {
+    /// The part of the configuration checked, the server or a module route.
+    pub scope: String,
+    /// The category of the check.
+    pub category: CheckCategory,
+    /// The name of the check.
+    pub name: String,
+    /// The status of the check.
+    pub status: CheckStatus,
+    /// What was observed, or why the check failed.
+    pub detail: String,
+}
+
+/// The report of a doctor run.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DoctorReport {
+    /// The checks, in the order they ran.
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// Returns whether no check failed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Fail)
+    }
+
+    /// Returns the number of checks with `status`.
+    #[must_use]
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    /// Renders the report as one line per check, followed by a summary.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut rendered = String::new();
+        for check in &self.checks {
+            rendered.push_str(&format!(
+                "[{}] {}: {}: {}: {}\n",
+                check.status, check.scope, check.category, check.name, check.detail
+            ));
+        }
+        rendered.push_str(&format!(
+            "{} passed, {} warnings, {} failed",
+            self.count(CheckStatus::Pass),
+            self.count(CheckStatus::Warn),
+            self.count(CheckStatus::Fail)
+        ));
+        rendered
+    }
+
+    fn push(
+        &mut self,
+        scope: &str,
+        category: CheckCategory,
+        name: &str,
+        status: CheckStatus,
+        detail: impl Into<String>,
+    ) {
+        self.checks.push(Check {
+            scope: scope.to_string(),
+            category,
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    /// Records a check that passes with the detail of `result`, or fails with its error.
+    fn record(&mut self, scope: &str, category: CheckCategory, name: &str, result: Result<String>) {
+        match result {
+            Ok(detail) => self.push(scope, category, name, CheckStatus::Pass, detail),
+            Err(e) => self.push(scope, category, name, CheckStatus::Fail, format!("{e:#}")),
+        }
+    }
+
+    fn warn(
+        &mut self,
+        scope: &str,
+        category: CheckCategory,
+        name: &str,
+        detail: impl Into<String>,
+    ) {
+        self.push(scope, category, name, CheckStatus::Warn, detail);
+    }
+}
+
+/// Checks the consistency of the configuration, without connecting to any endpoint.
+#[must_use]
+pub fn check_configuration(config: &RelayerConfig) -> DoctorReport {
+    let mut report = DoctorReport::default();
+    let server = "server";
+
+    if !config.server.log_level.is_empty() && Level::from_str(&config.server.log_level).is_err() {
+        report.warn(
+            server,
+            CheckCategory::Configuration,
+            "log level",
+            format!(
+                "unknown log level `{}`, the relayer logs at info",
+                config.server.log_level
+            ),
+        );
+    }
+    if let Some(packet_archive) = &config.server.packet_archive {
+        report.record(
+            server,
+            CheckCategory::Permissions,
+            "packet archive",
+            check_writable(&packet_archive.dir),
+        );
+    }
+
+    let enabled = config
+        .modules
+        .iter()
+        .filter(|module| module.enabled)
+        .collect::<Vec<_>>();
+    if enabled.is_empty() {
+        report.push(
+            server,
+            CheckCategory::Configuration,
+            "modules",
+            CheckStatus::Fail,
+            "no module is enabled",
+        );
+    }
+
+    // The requests are routed by their source and destination chains, so a route served by two
+    // modules is served by the last one registered
+    let mut routes = BTreeMap::<(&str, &str), Vec<&str>>::new();
+    for module in &enabled {
+        routes
+            .entry((module.src_chain.as_str(), module.dst_chain.as_str()))
+            .or_default()
+            .push(module.name.as_str());
+    }
+    for ((src_chain, dst_chain), modules) in routes.into_iter().filter(|(_, m)| m.len() > 1) {
+        report.push(
+            server,
+            CheckCategory::Configuration,
+            "routes",
+            CheckStatus::Fail,
+            format!(
+                "the route {src_chain}->{dst_chain} is served by several modules: {}",
+                modules.join(", ")
+            ),
+        );
+    }
+
+    for module in enabled {
+        check_module_configuration(&mut report, module);
+    }
+    report
+}
+
+/// Checks the configuration of an enabled module.
+fn check_module_configuration(report: &mut DoctorReport, module: &ModuleConfig) {
+    let scope = module_scope(module);
+    let category = CheckCategory::Configuration;
+    let (network_guard, result) = match module.name.as_str() {
+        name if name == EthToCosmosRelayerModule.name() => {
+            match serde_json::from_value::<EthToCosmosConfig>(module.config.clone()) {
+                Ok(config) => (Some(config.network_guard), Ok(())),
+                Err(e) => (None, Err(e.into())),
+            }
+        }
+        name if name == CosmosToEthRelayerModule.name() => {
+            match serde_json::from_value::<CosmosToEthConfig>(module.config.clone()) {
+                Ok(config) => {
+                    report.record(
+                        &scope,
+                        category,
+                        "SP1 programs",
+                        config
+                            .sp1_programs
+                            .read_programs()
+                            .map(|_| "all the programs are readable".to_string()),
+                    );
+                    (Some(config.network_guard), Ok(()))
+                }
+                Err(e) => (None, Err(e.into())),
+            }
+        }
+        name if name == CosmosToCosmosRelayerModule.name() => {
+            match serde_json::from_value::<CosmosToCosmosConfig>(module.config.clone()) {
+                Ok(config) => (Some(config.network_guard), Ok(())),
+                Err(e) => (None, Err(e.into())),
+            }
+        }
+        name if name == RemoteRelayerModule.name() => {
+            match serde_json::from_value::<RemoteConfig>(module.config.clone()) {
+                Ok(config) if config.trusted_signers.is_empty() => (
+                    None,
+                    Err(anyhow::anyhow!("no trusted artifact signer is configured")),
+                ),
+                Ok(_) => (None, Ok(())),
+                Err(e) => (None, Err(e.into())),
+            }
+        }
+        name => (None, Err(anyhow::anyhow!("unknown module `{name}`"))),
+    };
+    report.record(
+        &scope,
+        category,
+        "module config",
+        result.map(|()| "decoded".to_string()),
+    );
+
+    if let Some(network_guard) = network_guard {
+        if network_guard.source_chain_id.is_none() || network_guard.target_chain_id.is_none() {
+            report.warn(
+                &scope,
+                category,
+                "network guard",
+                "the expected chain IDs are not set, the endpoints are not checked against them",
+            );
+        }
+    }
+}
+
+/// Runs the configuration checks, then connects to the endpoints of the enabled modules to check
+/// their connectivity, the permissions of the submitters and the capabilities of the endpoints.
+/// Every check of an endpoint fails after `timeout`.
+pub async fn run_doctor(config: &RelayerConfig, timeout: Duration) -> DoctorReport {
+    let mut report = check_configuration(config);
+
+    // The modules whose configuration does not decode are already reported
+    for module in config.modules.iter().filter(|module| module.enabled) {
+        let scope = module_scope(module);
+        match module.name.as_str() {
+            name if name == EthToCosmosRelayerModule.name() => {
+                if let Ok(config) = serde_json::from_value(module.config.clone()) {
+                    check_eth_to_cosmos(&mut report, &scope, &config, timeout).await;
+                }
+            }
+            name if name == CosmosToEthRelayerModule.name() => {
+                if let Ok(config) = serde_json::from_value(module.config.clone()) {
+                    check_cosmos_to_eth(&mut report, &scope, &config, timeout).await;
+                }
+            }
+            name if name == CosmosToCosmosRelayerModule.name() => {
+                if let Ok(config) = serde_json::from_value(module.config.clone()) {
+                    check_cosmos_to_cosmos(&mut report, &scope, &config, timeout).await;
+                }
+            }
+            name if name == RemoteRelayerModule.name() => {
+                if let Ok(config) = serde_json::from_value::<RemoteConfig>(module.config.clone()) {
+                    let result = within(timeout, async {
+                        tonic::transport::Channel::from_shared(config.prover_url.clone())?
+                            .connect()
+                            .await?;
+                        Ok(format!("connected to {}", config.prover_url))
+                    })
+                    .await;
+                    report.record(
+                        &scope,
+                        CheckCategory::Connectivity,
+                        "proving relayer",
+                        result,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    report
+}
+
+async fn check_eth_to_cosmos(
+    report: &mut DoctorReport,
+    scope: &str,
+    config: &EthToCosmosConfig,
+    timeout: Duration,
+) {
+    let eth_rpc = EthRpc {
+        rpc_url: &config.eth_rpc_url,
+        fallback_urls: &config.eth_rpc_fallback_urls,
+        http_config: &config.http_client,
+        circuit_breaker: config.circuit_breaker,
+        ics26_address: config.ics26_address,
+        expected_chain_id: config.network_guard.source_chain_id.as_deref(),
+    };
+    if let Some((provider, block_number)) = check_eth_rpc(report, scope, &eth_rpc, timeout).await {
+        // Relays prove the packets at the finalized slot the light client is updated to, which
+        // full nodes only serve for the last 128 blocks
+        let proof_block = block_number.saturating_sub(ARCHIVE_PROOF_DEPTH);
+        let result = within(timeout, async {
+            EthApiClient::new(provider)
+                .get_proof(
+                    &config.ics26_address.to_string(),
+                    vec![],
+                    BlockId::number(proof_block),
+                )
+                .await?;
+            Ok(format!("state proven at block {proof_block}"))
+        })
+        .await;
+        report.record(scope, CheckCategory::Capabilities, "archive proofs", result);
+    }
+
+    match config.beacon_api_client() {
+        Ok(beacon_api_client) => {
+            let result = within(timeout, async {
+                let genesis_validators_root = beacon_api_client
+                    .genesis()
+                    .await?
+                    .data
+                    .genesis_validators_root;
+                if let Some(expected) = config.network_guard.genesis_validators_root {
+                    anyhow::ensure!(
+                        genesis_validators_root == expected,
+                        "genesis validators root {genesis_validators_root}, expected {expected}"
+                    );
+                }
+                Ok(format!("genesis validators root {genesis_validators_root}"))
+            })
+            .await;
+            report.record(scope, CheckCategory::Connectivity, "beacon API", result);
+
+            let result = within(timeout, async {
+                let finality_update = beacon_api_client.finality_update().await?.data;
+                Ok(format!(
+                    "finality update at slot {}",
+                    finality_update.finalized_header.beacon.slot
+                ))
+            })
+            .await;
+            report.record(
+                scope,
+                CheckCategory::Capabilities,
+                "light client data",
+                result,
+            );
+        }
+        Err(e) => report.record(scope, CheckCategory::Connectivity, "beacon API", Err(e)),
+    }
+
+    if let Some(tm_client) = check_tendermint_rpc(
+        report,
+        scope,
+        "target RPC",
+        &config.tm_rpc_url,
+        &config.http_client,
+        config.network_guard.target_chain_id.as_deref(),
+        timeout,
+    )
+    .await
+    {
+        let submitter = Submitter {
+            signer_address: &config.signer_address,
+            authz: config.authz.as_ref(),
+            fee: config.fee.as_ref(),
+        };
+        check_submitter(report, scope, &tm_client, &submitter, timeout).await;
+    }
+}
+
+async fn check_cosmos_to_eth(
+    report: &mut DoctorReport,
+    scope: &str,
+    config: &CosmosToEthConfig,
+    timeout: Duration,
+) {
+    check_tendermint_rpc(
+        report,
+        scope,
+        "source RPC",
+        &config.tm_rpc_url,
+        &config.http_client,
+        config.network_guard.source_chain_id.as_deref(),
+        timeout,
+    )
+    .await;
+
+    // The relay transactions are broadcast by the caller, so there is no submitter to check
+    let eth_rpc = EthRpc {
+        rpc_url: &config.eth_rpc_url,
+        fallback_urls: &config.eth_rpc_fallback_urls,
+        http_config: &config.http_client,
+        circuit_breaker: config.circuit_breaker,
+        ics26_address: config.ics26_address,
+        expected_chain_id: config.network_guard.target_chain_id.as_deref(),
+    };
+    check_eth_rpc(report, scope, &eth_rpc, timeout).await;
+}
+
+async fn check_cosmos_to_cosmos(
+    report: &mut DoctorReport,
+    scope: &str,
+    config: &CosmosToCosmosConfig,
+    timeout: Duration,
+) {
+    check_tendermint_rpc(
+        report,
+        scope,
+        "source RPC",
+        &config.src_rpc_url,
+        &config.http_client,
+        config.network_guard.source_chain_id.as_deref(),
+        timeout,
+    )
+    .await;
+
+    if let Some(tm_client) = check_tendermint_rpc(
+        report,
+        scope,
+        "target RPC",
+        &config.target_rpc_url,
+        &config.http_client,
+        config.network_guard.target_chain_id.as_deref(),
+        timeout,
+    )
+    .await
+    {
+        let submitter = Submitter {
+            signer_address: &config.signer_address,
+            authz: config.authz.as_ref(),
+            fee: config.fee.as_ref(),
+        };
+        check_submitter(report, scope, &tm_client, &submitter, timeout).await;
+    }
+}
+
+/// The execution RPC endpoints of a module.
+struct EthRpc<'a> {
+    rpc_url: &'a str,
+    fallback_urls: &'a [String],
+    http_config: &'a HttpClientConfig,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    ics26_address: Address,
+    expected_chain_id: Option<&'a str>,
+}
+
+/// Checks the execution RPC endpoint and the `ICS26Router` deployed behind it, and returns the
+/// provider with the latest block number if the endpoint is reachable.
+async fn check_eth_rpc(
+    report: &mut DoctorReport,
+    scope: &str,
+    eth_rpc: &EthRpc<'_>,
+    timeout: Duration,
+) -> Option<(RootProvider, u64)> {
+    let connected = within(timeout, async {
+        let provider = connect_eth_provider(
+            eth_rpc.rpc_url,
+            eth_rpc.fallback_urls,
+            eth_rpc.http_config,
+            eth_rpc.circuit_breaker,
+        )
+        .await?;
+        let chain_id = provider.get_chain_id().await?.to_string();
+        let block_number = provider.get_block_number().await?;
+        Ok((provider, chain_id, block_number))
+    })
+    .await;
+    let (provider, chain_id, block_number) = match connected {
+        Ok(connected) => connected,
+        Err(e) => {
+            report.record(scope, CheckCategory::Connectivity, "execution RPC", Err(e));
+            return None;
+        }
+    };
+    report.record(
+        scope,
+        CheckCategory::Connectivity,
+        "execution RPC",
+        expect_chain_id(eth_rpc.expected_chain_id, &chain_id)
+            .map(|()| format!("chain {chain_id} at block {block_number}")),
+    );
+
+    let result = within(timeout, async {
+        let code = provider.get_code_at(eth_rpc.ics26_address).await?;
+        anyhow::ensure!(
+            !code.is_empty(),
+            "no contract is deployed at {}",
+            eth_rpc.ics26_address
+        );
+        Ok(format!("deployed at {}", eth_rpc.ics26_address))
+    })
+    .await;
+    report.record(scope, CheckCategory::Configuration, "ICS26 router", result);
+
+    Some((provider, block_number))
+}
+
+/// Checks a Tendermint RPC endpoint, and returns its client if the endpoint is reachable.
+async fn check_tendermint_rpc(
+    report: &mut DoctorReport,
+    scope: &str,
+    name: &str,
+    rpc_url: &str,
+    http_config: &HttpClientConfig,
+    expected_chain_id: Option<&str>,
+    timeout: Duration,
+) -> Option<HttpClient> {
+    let status = within(timeout, async {
+        let tm_client = HttpClient::from_rpc_url_with_config(rpc_url, http_config)?;
+        let status = tm_client.status().await?;
+        Ok((tm_client, status))
+    })
+    .await;
+    let (tm_client, status) = match status {
+        Ok(status) => status,
+        Err(e) => {
+            report.record(scope, CheckCategory::Connectivity, name, Err(e));
+            return None;
+        }
+    };
+
+    let chain_id = status.node_info.network.to_string();
+    report.record(
+        scope,
+        CheckCategory::Connectivity,
+        name,
+        expect_chain_id(expected_chain_id, &chain_id).map(|()| {
+            format!(
+                "chain {chain_id} at height {}",
+                status.sync_info.latest_block_height
+            )
+        }),
+    );
+    if status.sync_info.catching_up {
+        report.warn(
+            scope,
+            CheckCategory::Capabilities,
+            name,
+            "the node is catching up, its state lags behind the chain",
+        );
+    }
+
+    Some(tm_client)
+}
+
+/// The submitter of the transactions of a module to a Cosmos SDK chain.
+struct Submitter<'a> {
+    signer_address: &'a str,
+    authz: Option<&'a AuthzConfig>,
+    fee: Option<&'a FeeConfig>,
+}
+
+/// Checks that the account submitting the transactions exists and can pay for them, and that it
+/// is granted the relay messages if they are submitted through authz.
+async fn check_submitter(
+    report: &mut DoctorReport,
+    scope: &str,
+    tm_client: &HttpClient,
+    submitter: &Submitter<'_>,
+    timeout: Duration,
+) {
+    // With authz, the grantee signs and pays for the `MsgExec`
+    let address = submitter
+        .authz
+        .map_or(submitter.signer_address, |authz| &authz.grantee_address);
+
+    let result = within(timeout, async {
+        let account = abci_query::<_, QueryAccountResponse>(
+            tm_client,
+            "/cosmos.auth.v1beta1.Query/Account",
+            &QueryAccountRequest {
+                address: address.to_string(),
+            },
+        )
+        .await
+        .map_err(|e| anyhow::anyhow!("account {address} not found, it must be funded: {e}"))?
+        .account
+        .ok_or_else(|| anyhow::anyhow!("account {address} not found, it must be funded"))?;
+        // Vesting and module accounts are not base accounts, but can sign all the same
+        Ok(BaseAccount::decode(account.value.as_slice()).map_or_else(
+            |_| format!("account {address} of type {}", account.type_url),
+            |account| {
+                format!(
+                    "account {address} number {}, sequence {}",
+                    account.account_number, account.sequence
+                )
+            },
+        ))
+    })
+    .await;
+    report.record(
+        scope,
+        CheckCategory::Permissions,
+        "submitter account",
+        result,
+    );
+
+    let result = within(timeout, async {
+        if let Some(fee) = submitter.fee {
+            let balance = abci_query::<_, QueryBalanceResponse>(
+                tm_client,
+                "/cosmos.bank.v1beta1.Query/Balance",
+                &QueryBalanceRequest {
+                    address: address.to_string(),
+                    denom: fee.denom.clone(),
+                },
+            )
+            .await?
+            .balance
+            .map(|coin| coin.amount)
+            .unwrap_or_default();
+            anyhow::ensure!(
+                !balance.is_empty() && balance != "0",
+                "account {address} holds no {} to pay the fees",
+                fee.denom
+            );
+            Ok(format!("{balance}{}", fee.denom))
+        } else {
+            let balances = abci_query::<_, QueryAllBalancesResponse>(
+                tm_client,
+                "/cosmos.bank.v1beta1.Query/AllBalances",
+                &QueryAllBalancesRequest {
+                    address: address.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .balances;
+            anyhow::ensure!(
+                !balances.is_empty(),
+                "account {address} holds no funds to pay the fees"
+            );
+            Ok(balances
+                .iter()
+                .map(|coin| format!("{}{}", coin.amount, coin.denom))
+                .collect::<Vec<_>>()
+                .join(", "))
+        }
+    })
+    .await;
+    report.record(
+        scope,
+        CheckCategory::Permissions,
+        "submitter balance",
+        result,
+    );
+
+    if let Some(authz) = submitter.authz {
+        let result = within(timeout, async {
+            let grants = ibc_eureka_relayer_lib::authz::fetch_grants(
+                tm_client,
+                submitter.signer_address,
+                &authz.grantee_address,
+            )
+            .await?;
+            anyhow::ensure!(
+                !grants.is_empty(),
+                "{} granted nothing to {}",
+                submitter.signer_address,
+                authz.grantee_address
+            );
+            Ok(format!(
+                "{} grants from {}",
+                grants.len(),
+                submitter.signer_address
+            ))
+        })
+        .await;
+        report.record(scope, CheckCategory::Permissions, "authz grants", result);
+    }
+}
+
+/// Sends a gRPC query to a Cosmos SDK chain through ABCI.
+pub(crate) async fn abci_query<Req: Message, Resp: Message + Default>(
+    tm_client: &HttpClient,
+    path: &str,
+    request: &Req,
+) -> Result<Resp> {
+    let abci_resp = tm_client
+        .abci_query(Some(path.to_string()), request.encode_to_vec(), None, false)
+        .await?;
+    anyhow::ensure!(abci_resp.code.is_ok(), "{path} failed: {}", abci_resp.log);
+    Ok(Resp::decode(abci_resp.value.as_slice())?)
+}
+
+/// Checks a chain ID against the one expected by the network guard, if set.
+fn expect_chain_id(expected: Option<&str>, chain_id: &str) -> Result<()> {
+    if let Some(expected) = expected {
+        anyhow::ensure!(
+            chain_id == expected,
+            "chain {chain_id}, expected {expected} by the network guard"
+        );
+    }
+    Ok(())
+}
+
+/// Checks that a directory can be written to, creating it if needed.
+fn check_writable(dir: &Path) -> Result<String> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(PROBE_FILE_NAME);
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)?;
+    Ok(format!("{} is writable", dir.display()))
+}
+
+/// Fails a check after `timeout`.
+async fn within<T>(timeout: Duration, check: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::time::timeout(timeout, check)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", timeout.as_secs()))?
+}
+
+fn module_scope(module: &ModuleConfig) -> String {
+    format!("{} {}->{}", module.name, module.src_chain, module.dst_chain)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::cli::config::ServerConfig;
+
+    fn relayer_config(modules: Vec<ModuleConfig>) -> RelayerConfig {
+        RelayerConfig {
+            modules,
+            server: ServerConfig {
+                address: "127.0.0.1".to_string(),
+                port: 3000,
+                log_level: "verbose".to_string(),
+                artifact_signer: None,
+                packet_archive: None,
+                escrow_operator_token_path: None,
+            },
+        }
+    }
+
+    fn remote_module(src_chain: &str, enabled: bool) -> ModuleConfig {
+        ModuleConfig {
+            name: RemoteRelayerModule.name().to_string(),
+            src_chain: src_chain.to_string(),
+            dst_chain: "cosmoshub-4".to_string(),
+            config: json!({
+                "prover_url": "http://prover:3000",
+                "trusted_signers": ["0x0000000000000000000000000000000000000001"],
+            }),
+            enabled,
+        }
+    }
+
+    fn statuses(report: &DoctorReport, name: &str) -> Vec<CheckStatus> {
+        report
+            .checks
+            .iter()
+            .filter(|check| check.name == name)
+            .map(|check| check.status)
+            .collect()
+    }
+
+    #[test]
+    fn test_check_configuration() {
+        let report = check_configuration(&relayer_config(vec![
+            remote_module("1", true),
+            remote_module("11155111", true),
+        ]));
+        assert!(report.passed());
+        assert_eq!(statuses(&report, "log level"), vec![CheckStatus::Warn]);
+        assert_eq!(
+            statuses(&report, "module config"),
+            vec![CheckStatus::Pass; 2]
+        );
+        assert!(statuses(&report, "routes").is_empty());
+
+        // The disabled modules are not checked, and do not conflict with the enabled ones
+        let report = check_configuration(&relayer_config(vec![
+            remote_module("1", true),
+            remote_module("1", false),
+        ]));
+        assert!(report.passed());
+        assert_eq!(statuses(&report, "module config"), vec![CheckStatus::Pass]);
+
+        let report = check_configuration(&relayer_config(vec![remote_module("1", false)]));
+        assert_eq!(statuses(&report, "modules"), vec![CheckStatus::Fail]);
+    }
+
+    #[test]
+    fn test_check_configuration_failures() {
+        let mut unknown = remote_module("11155111", true);
+        unknown.name = "eth_to_solana".to_string();
+        let mut invalid = remote_module("17000", true);
+        invalid.config = json!({ "prover_url": "http://prover:3000", "trusted_signers": [] });
+
+        let report = check_configuration(&relayer_config(vec![
+            remote_module("1", true),
+            remote_module("1", true),
+            unknown,
+            invalid,
+        ]));
+        assert!(!report.passed());
+        assert_eq!(statuses(&report, "routes"), vec![CheckStatus::Fail]);
+        assert_eq!(
+            statuses(&report, "module config"),
+            vec![
+                CheckStatus::Pass,
+                CheckStatus::Pass,
+                CheckStatus::Fail,
+                CheckStatus::Fail
+            ]
+        );
+        assert!(report.render().ends_with("2 passed, 1 warnings, 3 failed"));
+    }
+}
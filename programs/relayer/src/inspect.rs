@@ -0,0 +1,176 @@
+//! Defines the helpers to inspect the light clients that the relayer modules update.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ibc_eureka_relayer_lib::tx_builder::eth_to_cosmos;
+use ibc_eureka_solidity_types::{
+    ics26::router::routerInstance, msgs::IICS07TendermintMsgs::ClientState,
+    sp1_ics07::sp1_ics07_tendermint,
+};
+use ibc_eureka_utils::rpc::TendermintRpcExt;
+use serde_json::{json, Value};
+use tendermint::block::Height;
+use tendermint_rpc::{Client, HttpClient};
+
+use crate::{
+    cli::config::RelayerConfig,
+    core::modules::RelayerModule,
+    modules::{
+        connect_eth_provider,
+        cosmos_to_eth::{CosmosToEthConfig, CosmosToEthRelayerModule},
+        eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+    },
+};
+
+/// Fetches and decodes the state of the light client with `client_id` hosted on `chain`.
+///
+/// The first enabled module whose destination chain is `chain` is used to connect to the chain.
+/// # Errors
+/// Returns an error if no suitable module is configured or if the state cannot be fetched.
+pub async fn inspect_client(
+    config: &RelayerConfig,
+    chain: &str,
+    client_id: &str,
+) -> anyhow::Result<Value> {
+    let module = config
+        .modules
+        .iter()
+        .find(|module| module.enabled && module.dst_chain == chain)
+        .ok_or_else(|| anyhow::anyhow!("No enabled module with destination chain `{chain}`"))?;
+
+    match module.name.as_str() {
+        name if name == EthToCosmosRelayerModule.name() => {
+            let config = serde_json::from_value(module.config.clone())?;
+            inspect_ethereum_client(config, client_id).await
+        }
+        name if name == CosmosToEthRelayerModule.name() => {
+            let config = serde_json::from_value(module.config.clone())?;
+            inspect_sp1_ics07_client(config, client_id).await
+        }
+        name => anyhow::bail!("Inspecting clients is not supported for module `{name}`"),
+    }
+}
+
+/// Fetches the Ethereum light client state and consensus state from the Cosmos SDK chain.
+async fn inspect_ethereum_client(
+    config: EthToCosmosConfig,
+    client_id: &str,
+) -> anyhow::Result<Value> {
+    let provider = connect_eth_provider(
+        &config.eth_rpc_url,
+        &config.eth_rpc_fallback_urls,
+        &config.http_client,
+        config.circuit_breaker,
+    )
+    .await?;
+    let beacon_api_client = config.beacon_api_client()?;
+    let tx_builder = eth_to_cosmos::TxBuilder::new(
+        config.ics26_address,
+        provider,
+        config.eth_beacon_api_url,
+        HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?,
+        config.signer_address,
+        eth_to_cosmos::TxBuilderConfig::default(),
+    )
+    .with_beacon_api_client(beacon_api_client);
+
+    let client_state = tx_builder
+        .ethereum_client_state(client_id.to_string())
+        .await?;
+    let consensus_state = tx_builder
+        .ethereum_consensus_state(
+            client_id.to_string(),
+            client_state.revision_number,
+            client_state.latest_slot,
+        )
+        .await?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let current_slot = client_state.compute_slot_at_timestamp(now);
+    let current_period =
+        current_slot.map(|slot| client_state.compute_sync_committee_period_at_slot(slot));
+
+    Ok(json!({
+        "client_id": client_id,
+        "client_type": "ethereum",
+        "client_state": client_state,
+        "consensus_state": consensus_state,
+        "computed": {
+            "latest_period": client_state
+                .compute_sync_committee_period_at_slot(client_state.latest_slot),
+            "current_slot": current_slot,
+            "current_period": current_period,
+            "staleness_seconds": now.saturating_sub(consensus_state.timestamp),
+            "frozen": client_state.is_frozen,
+            "client_state_hash": client_state.canonical_hash()?,
+            "consensus_state_hash": consensus_state.canonical_hash()?,
+        },
+    }))
+}
+
+/// Fetches the SP1 ICS07 Tendermint light client state from Ethereum.
+async fn inspect_sp1_ics07_client(
+    config: CosmosToEthConfig,
+    client_id: &str,
+) -> anyhow::Result<Value> {
+    let provider = connect_eth_provider(
+        &config.eth_rpc_url,
+        &config.eth_rpc_fallback_urls,
+        &config.http_client,
+        config.circuit_breaker,
+    )
+    .await?;
+    let ics26_router = routerInstance::new(config.ics26_address, provider.clone());
+
+    let client_address = ics26_router
+        .getClient(client_id.to_string())
+        .call()
+        .await?
+        ._0;
+    let client_state: ClientState = sp1_ics07_tendermint::new(client_address, provider)
+        .clientState()
+        .call()
+        .await?
+        .into();
+
+    let tm_client = HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?;
+    let latest_block_time = tm_client
+        .commit(Height::try_from(client_state.latestHeight.revisionHeight)?)
+        .await?
+        .signed_header
+        .header
+        .time
+        .unix_timestamp();
+
+    let now = i64::try_from(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())?;
+    let staleness = now.saturating_sub(latest_block_time);
+    let trusting_period = i64::from(client_state.trustingPeriod);
+
+    Ok(json!({
+        "client_id": client_id,
+        "client_type": "07-tendermint",
+        "client_address": client_address.to_string(),
+        "client_state": {
+            "chain_id": client_state.chainId,
+            "trust_level": {
+                "numerator": client_state.trustLevel.numerator,
+                "denominator": client_state.trustLevel.denominator,
+            },
+            "latest_height": {
+                "revision_number": client_state.latestHeight.revisionNumber,
+                "revision_height": client_state.latestHeight.revisionHeight,
+            },
+            "trusting_period": client_state.trustingPeriod,
+            "unbonding_period": client_state.unbondingPeriod,
+            "is_frozen": client_state.isFrozen,
+            "zk_algorithm": format!("{:?}", client_state.zkAlgorithm),
+        },
+        "computed": {
+            "latest_block_time": latest_block_time,
+            "staleness_seconds": staleness,
+            "trusting_period_remaining_seconds": trusting_period.saturating_sub(staleness),
+            "expired": staleness >= trusting_period,
+            "frozen": client_state.isFrozen,
+        },
+    }))
+}
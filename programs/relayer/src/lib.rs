@@ -9,7 +9,15 @@ pub mod api {
         tonic::include_file_descriptor_set!("relayer_descriptor");
 }
 
+pub mod audit;
+pub mod backfill;
+pub mod catch_up;
+pub mod checksums;
 pub mod cli;
+pub mod client_history;
 pub mod core;
+pub mod doctor;
+pub mod inspect;
 pub mod metrics;
 pub mod modules;
+pub mod recover;
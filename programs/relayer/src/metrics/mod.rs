@@ -2,9 +2,11 @@
 
 #![allow(missing_docs)]
 
+use ethereum_apis::circuit_breaker::CircuitState;
+use ibc_eureka_relayer_lib::sync_committee::SyncCommitteeParticipation;
 use prometheus::{
     register_counter, register_histogram_vec, register_int_counter_vec, register_int_gauge,
-    Counter, HistogramVec, IntCounterVec, IntGauge,
+    register_int_gauge_vec, Counter, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use std::{sync::LazyLock, time::Instant};
 
@@ -38,6 +40,200 @@ pub static CONNECTED_CLIENTS: LazyLock<IntGauge> = LazyLock::new(|| {
     register_int_gauge!("eureka_relayer_connected_clients", "Connected clients").unwrap()
 });
 
+/// Prometheus metric for the number of slots between the beacon head and the latest finalized
+/// slot, distinguished by `chain`
+pub static BEACON_FINALITY_LAG_SLOTS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_beacon_finality_lag_slots",
+        "Slots between the beacon head and the latest finalized slot",
+        &["chain"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the distribution of the beacon finality lag in slots, distinguished by
+/// `chain`
+pub static BEACON_FINALITY_LAG_HISTOGRAM: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "eureka_relayer_beacon_finality_lag_slots_distribution",
+        "Distribution of the slots between the beacon head and the latest finalized slot",
+        &["chain"],
+        vec![32.0, 64.0, 96.0, 128.0, 192.0, 256.0, 512.0, 1024.0, 4096.0]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the number of finalized execution blocks an Ethereum light client is
+/// behind, distinguished by `chain` and `client_id`
+pub static CLIENT_EXECUTION_LAG_BLOCKS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_client_execution_lag_blocks",
+        "Finalized execution blocks the light client is behind",
+        &["chain", "client_id"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the number of packet commitments that were not deleted after their ack
+/// or timeout landed, distinguished by `chain` and `client_id`
+pub static STALE_COMMITMENTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "eureka_relayer_stale_commitments_total",
+        "Packet commitments not deleted after their ack or timeout landed",
+        &["chain", "client_id"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the state of the circuit breaker of an Ethereum endpoint, 0 if closed,
+/// 1 if half-open and 2 if open, distinguished by `endpoint`
+pub static ENDPOINT_CIRCUIT_STATE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_endpoint_circuit_state",
+        "State of the circuit breaker of the endpoint (0 closed, 1 half-open, 2 open)",
+        &["endpoint"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the number of times the circuit breaker of an Ethereum endpoint opened,
+/// distinguished by `endpoint`
+pub static ENDPOINT_CIRCUIT_OPENED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "eureka_relayer_endpoint_circuit_opened_total",
+        "Times the circuit breaker of the endpoint opened",
+        &["endpoint"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the latest sampled base fee of an Ethereum chain in wei, distinguished by
+/// `chain`
+pub static ETH_BASE_FEE_WEI: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_eth_base_fee_wei",
+        "Latest sampled base fee of the chain in wei",
+        &["chain"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for whether the base fee of an Ethereum chain exceeds the gas spike ceiling,
+/// 1 if it does and 0 otherwise, distinguished by `chain`
+pub static GAS_SPIKE_ACTIVE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_gas_spike_active",
+        "Whether the base fee exceeds the gas spike ceiling (1) or not (0)",
+        &["chain"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the number of relays toward an Ethereum chain deferred until the gas
+/// spike ends, distinguished by `chain`
+pub static GAS_SPIKE_DEFERRED_RELAYS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_gas_spike_deferred_relays",
+        "Relays deferred until the gas spike ends",
+        &["chain"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the number of sync committee members that signed the latest verified
+/// header of an Ethereum chain, distinguished by `chain`
+pub static SYNC_COMMITTEE_PARTICIPANTS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_sync_committee_participants",
+        "Sync committee members that signed the latest verified header",
+        &["chain"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the number of participants of the latest verified header of an Ethereum
+/// chain above the minimum required by the light client, distinguished by `chain`
+pub static SYNC_COMMITTEE_PARTICIPATION_MARGIN: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "eureka_relayer_sync_committee_participation_margin",
+        "Participants of the latest verified header above the minimum of the light client",
+        &["chain"]
+    )
+    .unwrap()
+});
+
+/// Prometheus metric for the distribution of the sync committee participation rate of the
+/// verified headers, distinguished by `chain`
+pub static SYNC_COMMITTEE_PARTICIPATION_RATE: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "eureka_relayer_sync_committee_participation_rate",
+        "Distribution of the share of the sync committee that signed the verified headers",
+        &["chain"],
+        vec![0.5, 0.6, 0.667, 0.7, 0.8, 0.9, 0.95, 0.99, 1.0]
+    )
+    .unwrap()
+});
+
+/// Records a state change of the circuit breaker of an endpoint.
+pub fn observe_circuit_state(endpoint: &str, state: CircuitState) {
+    ENDPOINT_CIRCUIT_STATE
+        .with_label_values(&[endpoint])
+        .set(state.as_metric());
+    if state == CircuitState::Open {
+        ENDPOINT_CIRCUIT_OPENED.with_label_values(&[endpoint]).inc();
+    }
+}
+
+/// Records a sample of the finality lag of an Ethereum chain and of the execution lags of its
+/// light clients.
+#[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+pub fn observe_finality_lag(chain: &str, lag_slots: u64, client_lags: &[(String, u64)]) {
+    BEACON_FINALITY_LAG_SLOTS
+        .with_label_values(&[chain])
+        .set(lag_slots as i64);
+    BEACON_FINALITY_LAG_HISTOGRAM
+        .with_label_values(&[chain])
+        .observe(lag_slots as f64);
+    for (client_id, blocks) in client_lags {
+        CLIENT_EXECUTION_LAG_BLOCKS
+            .with_label_values(&[chain, client_id])
+            .set(*blocks as i64);
+    }
+}
+
+/// Records the sync committee participation of a verified header of an Ethereum chain.
+#[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+pub fn observe_sync_committee_participation(participation: &SyncCommitteeParticipation) {
+    let chain = participation.chain_id.to_string();
+    SYNC_COMMITTEE_PARTICIPANTS
+        .with_label_values(&[&chain])
+        .set(participation.participants as i64);
+    SYNC_COMMITTEE_PARTICIPATION_MARGIN
+        .with_label_values(&[&chain])
+        .set(participation.margin());
+    if participation.committee_size > 0 {
+        SYNC_COMMITTEE_PARTICIPATION_RATE
+            .with_label_values(&[&chain])
+            .observe(participation.participants as f64 / participation.committee_size as f64);
+    }
+}
+
+/// Records the state of the gas spike guard of an Ethereum chain.
+#[allow(clippy::cast_possible_wrap)]
+pub fn observe_gas_spike(chain: &str, base_fee: Option<u128>, spiking: bool, deferred: usize) {
+    if let Some(base_fee) = base_fee {
+        ETH_BASE_FEE_WEI
+            .with_label_values(&[chain])
+            .set(i64::try_from(base_fee).unwrap_or(i64::MAX));
+    }
+    GAS_SPIKE_ACTIVE
+        .with_label_values(&[chain])
+        .set(i64::from(spiking));
+    GAS_SPIKE_DEFERRED_RELAYS
+        .with_label_values(&[chain])
+        .set(deferred as i64);
+}
+
 /// Generic metrics tracking middleware for service calls
 /// # Errors
 /// Returns an error if the function itself returns an error.
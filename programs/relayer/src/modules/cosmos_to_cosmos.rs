@@ -1,19 +1,42 @@
 //! Defines Cosmos to Cosmos relayer module.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use ibc_eureka_relayer_lib::{
+    authz::{self, AuthzConfig},
+    chain::CosmosSdk,
+    confirmation::CosmosConfirmationPolicy,
+    fees::FeeConfig,
+    introspection,
     listener::{cosmos_sdk, ChainListenerService},
-    tx_builder::{cosmos_to_cosmos, TxBuilderService},
+    lookback::MaxLookback,
+    network::{NetworkGuard, NetworkGuardConfig},
+    reconciliation::AckReconciliation,
+    relay_hints::RelayHintPolicy,
+    relayer_fees::RelayerFeeConfig,
+    scheduler::{recv_sequences, AckSchedule},
+    tx_builder::{
+        composition::{self, TxComposition},
+        cosmos_to_cosmos,
+        plan::TxSizeLimit,
+        TxBuilderService,
+    },
+    value_limit::{self, TxValueCap, ValueLimitPolicy, ValueRateLimiter},
 };
-use ibc_eureka_utils::rpc::TendermintRpcExt;
+use ibc_eureka_utils::{http::HttpClientConfig, rpc::TendermintRpcExt};
 use tendermint::Hash;
 use tendermint_rpc::HttpClient;
 use tonic::{Request, Response};
 
 use crate::{
     api::{self, relayer_service_server::RelayerService},
-    core::modules::RelayerModule,
+    core::{
+        approvals, archive, deadline,
+        escrow::{self, ArtifactEscrowConfig},
+        hints,
+        modules::RelayerModule,
+        scheduler::{self, AckRelay},
+    },
 };
 
 /// The `CosmosToCosmosRelayerModule` struct defines the Cosmos to Cosmos relayer module.
@@ -30,6 +53,30 @@ struct CosmosToCosmosRelayerModuleService {
     pub target_listener: cosmos_sdk::ChainListener,
     /// The transaction builder from Cosmos to Cosmos.
     pub tx_builder: cosmos_to_cosmos::TxBuilder,
+    /// The value rate limiter, if the module has a value at risk policy.
+    pub value_limiter: Option<Arc<ValueRateLimiter>>,
+    /// The guard against relaying between networks that do not match the light clients.
+    pub network_guard: NetworkGuard,
+    /// The policy for the relay hints of the packets, if the module honors them.
+    pub relay_hint_policy: Option<RelayHintPolicy>,
+    /// The cap on the value of the packets included in a single relay transaction, if any.
+    pub tx_value_cap: Option<TxValueCap>,
+    /// The schedule of the ack relays that depend on the relayed recvs, if any.
+    pub ack_schedule: Option<AckSchedule>,
+    /// The address of the submitter, which is the granter if the messages are submitted through
+    /// authz.
+    pub signer_address: String,
+    /// The submission through authz, if any.
+    pub authz: Option<AuthzConfig>,
+    /// The fee payment of the relay transactions in another denom than the default of the chain,
+    /// if any.
+    pub fee: Option<FeeConfig>,
+    /// The composition of the client updates and the packet messages into transactions.
+    pub tx_composition: TxComposition,
+    /// The size limit of the relay transactions.
+    pub tx_size_limit: TxSizeLimit,
+    /// The escrow of the relays of high-value transfers, if any.
+    pub artifact_escrow: Option<ArtifactEscrowConfig>,
 }
 
 /// The configuration for the Cosmos to Cosmos relayer module.
@@ -43,22 +90,167 @@ pub struct CosmosToCosmosConfig {
     /// The address of the submitter.
     /// Required since cosmos messages require a signer address.
     pub signer_address: String,
+    /// The confirmation policy for events from the source chain.
+    #[serde(default)]
+    pub confirmation_policy: CosmosConfirmationPolicy,
+    /// The value at risk policy for relays toward the target chain.
+    #[serde(default)]
+    pub value_limit: Option<ValueLimitPolicy>,
+    /// The configuration of the HTTP clients, such as the proxy and the endpoint authentication.
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+    /// The expected networks, and how often the light clients are checked against them.
+    #[serde(default)]
+    pub network_guard: NetworkGuardConfig,
+    /// The policy for the relay hints in the memos of the ICS20 packets. Hints are ignored if unset.
+    #[serde(default)]
+    pub relay_hints: Option<RelayHintPolicy>,
+    /// The cap on the value of the ICS20 packets included in a single relay transaction.
+    /// Packets exceeding the cap are deferred to a later transaction.
+    #[serde(default)]
+    pub tx_value_cap: Option<TxValueCap>,
+    /// The schedule of the ack relays of the opposite route. If set, the acknowledgements of the
+    /// relayed packets are awaited and their ack relay is built as soon as they are written.
+    #[serde(default)]
+    pub ack_schedule: Option<AckSchedule>,
+    /// The reconciliation of the acks written before the relayer started. If set, the
+    /// acknowledgements of the packets sent within its window that were never relayed back are
+    /// scheduled once the module starts.
+    #[serde(default)]
+    pub ack_reconciliation: Option<AckReconciliation>,
+    /// The max lookback of the scans of the history of the route, which bounds the scans of the
+    /// `ack_reconciliation` deeper than it. The scans are not bounded if unset.
+    #[serde(default)]
+    pub max_lookback: MaxLookback,
+    /// The submission of the relay messages through an authz `MsgExec` of a grantee, on behalf of
+    /// the signer address. The messages are submitted directly if unset.
+    #[serde(default)]
+    pub authz: Option<AuthzConfig>,
+    /// The fee payment of the relay transactions in another denom than the default of the chain,
+    /// such as an IBC voucher. The fee is not quoted if unset.
+    #[serde(default)]
+    pub fee: Option<FeeConfig>,
+    /// The claiming of the relayer fees escrowed by the fee middleware of the target chain for
+    /// the acknowledged and the timed out packets, in the same transaction as their relay. The
+    /// fees are not claimed if unset.
+    #[serde(default)]
+    pub relayer_fees: Option<RelayerFeeConfig>,
+    /// Whether the client updates and the packet messages of a relay are submitted in a single
+    /// atomic transaction (default), or split into an update transaction and a packet transaction.
+    #[serde(default)]
+    pub tx_composition: TxComposition,
+    /// The size limit of the relay transactions, by default the `max_tx_bytes` of `CometBFT`.
+    /// Larger relays are split into consecutive transactions within the limit.
+    #[serde(default = "default_tx_size_limit")]
+    pub tx_size_limit: TxSizeLimit,
+    /// The escrow of the relays of high-value transfers, which are sealed until a timelock, an
+    /// approval or a committee reveals them. The relays are returned directly if unset.
+    #[serde(default)]
+    pub artifact_escrow: Option<ArtifactEscrowConfig>,
+}
+
+const fn default_tx_size_limit() -> TxSizeLimit {
+    TxSizeLimit::COSMOS_SDK
 }
 
 impl CosmosToCosmosRelayerModuleService {
-    fn new(config: CosmosToCosmosConfig) -> Self {
-        let src_client = HttpClient::from_rpc_url(&config.src_rpc_url);
-        let src_listener = cosmos_sdk::ChainListener::new(src_client.clone());
-        let target_client = HttpClient::from_rpc_url(&config.target_rpc_url);
+    async fn new(config: CosmosToCosmosConfig) -> Self {
+        let src_client =
+            HttpClient::from_rpc_url_with_config(&config.src_rpc_url, &config.http_client)
+                .unwrap_or_else(|e| panic!("failed to create tendermint client: {e}"));
+        let src_listener = cosmos_sdk::ChainListener::new(src_client.clone())
+            .with_confirmation_policy(config.confirmation_policy);
+        let target_client =
+            HttpClient::from_rpc_url_with_config(&config.target_rpc_url, &config.http_client)
+                .unwrap_or_else(|e| panic!("failed to create tendermint client: {e}"));
         let target_listener = cosmos_sdk::ChainListener::new(target_client.clone());
 
-        let tx_builder =
-            cosmos_to_cosmos::TxBuilder::new(src_client, target_client, config.signer_address);
+        let source_chain_id = src_listener
+            .chain_id()
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch the source chain id: {e}"));
+        let target_chain_id = target_listener
+            .chain_id()
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch the target chain id: {e}"));
+        config
+            .network_guard
+            .ensure_expected_chain_ids(&source_chain_id, &target_chain_id)
+            .unwrap_or_else(|e| panic!("refusing to relay: {e}"));
+        let network_guard = NetworkGuard::new(&config.network_guard);
+
+        let tx_builder = cosmos_to_cosmos::TxBuilder::new(
+            src_client,
+            target_client,
+            config.signer_address.clone(),
+        )
+        .with_confirmation_policy(config.confirmation_policy)
+        .with_relayer_fees(config.relayer_fees);
+
+        if let Some(reconciliation) = config.ack_reconciliation {
+            let max_lookback = config.max_lookback;
+            let src_listener = src_listener.clone();
+            let dst_listener = target_listener.clone();
+            tokio::spawn(async move {
+                match dst_listener.confirmed_height().await {
+                    Ok(dst_height) => {
+                        scheduler::reconcile_acks::<CosmosSdk, _>(
+                            reconciliation,
+                            max_lookback,
+                            src_listener,
+                            dst_listener,
+                            dst_height,
+                        )
+                        .await;
+                    }
+                    Err(e) => tracing::warn!("Failed to reconcile the acknowledgements: {e}"),
+                }
+            });
+        }
+
+        if let Some(artifact_escrow) = &config.artifact_escrow {
+            artifact_escrow
+                .validate()
+                .unwrap_or_else(|e| panic!("invalid artifact escrow: {e}"));
+        }
+
+        let value_limiter = config.value_limit.map(|policy| {
+            let value_limiter = Arc::new(ValueRateLimiter::new(policy));
+            approvals::register_value_limiter(value_limiter.clone());
+            value_limiter
+        });
 
         Self {
             src_listener,
             target_listener,
             tx_builder,
+            value_limiter,
+            network_guard,
+            relay_hint_policy: config.relay_hints,
+            tx_value_cap: config.tx_value_cap,
+            ack_schedule: config.ack_schedule,
+            signer_address: config.signer_address,
+            authz: config.authz,
+            fee: config.fee,
+            tx_composition: config.tx_composition,
+            tx_size_limit: config.tx_size_limit,
+            artifact_escrow: config.artifact_escrow,
+        }
+    }
+
+    /// Wraps the messages of a relay tx in an authz `MsgExec` of the grantee, if the module
+    /// submits through authz.
+    async fn authorize_tx(&self, tx: Vec<u8>) -> Result<Vec<u8>, tonic::Status> {
+        match &self.authz {
+            Some(authz) => authz::authorize_tx(
+                self.target_listener.client(),
+                authz,
+                &self.signer_address,
+                &tx,
+            )
+            .await
+            .map_err(|e| tonic::Status::failed_precondition(e.to_string())),
+            None => Ok(tx),
         }
     }
 }
@@ -101,9 +293,29 @@ impl RelayerService for CosmosToCosmosRelayerModuleService {
     ) -> Result<Response<api::RelayByTxResponse>, tonic::Status> {
         tracing::info!("Handling relay by tx request for Cosmos to Cosmos...");
 
+        let scope = deadline::relay_scope(request.metadata());
         let inner_req = request.into_inner();
         tracing::info!("Got {} source tx IDs", inner_req.source_tx_ids.len());
         tracing::info!("Got {} timeout tx IDs", inner_req.timeout_tx_ids.len());
+        let relay_id = value_limit::relay_id(&inner_req.src_client_id, &inner_req.source_tx_ids);
+
+        self.network_guard
+            .check(&inner_req.dst_client_id, || {
+                self.tx_builder
+                    .ensure_client_network(inner_req.dst_client_id.clone())
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!("Refusing to relay: {e}");
+                tonic::Status::failed_precondition(e.to_string())
+            })?;
+
+        if let Some(response) =
+            escrow::reveal_sealed_relay(self.artifact_escrow.as_ref(), &relay_id)?
+        {
+            return Ok(response);
+        }
+
         let src_txs = inner_req
             .source_tx_ids
             .into_iter()
@@ -118,7 +330,7 @@ impl RelayerService for CosmosToCosmosRelayerModuleService {
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| tonic::Status::from_error(e.into()))?;
 
-        let src_events = self
+        let mut src_events = self
             .src_listener
             .fetch_tx_events(src_txs)
             .await
@@ -130,7 +342,7 @@ impl RelayerService for CosmosToCosmosRelayerModuleService {
             src_events.len()
         );
 
-        let target_events = self
+        let mut target_events = self
             .target_listener
             .fetch_tx_events(target_txs)
             .await
@@ -142,25 +354,164 @@ impl RelayerService for CosmosToCosmosRelayerModuleService {
             target_events.len()
         );
 
+        archive::archive_packets(&src_events, &inner_req.src_chain, &inner_req.dst_chain);
+        archive::archive_packets(&target_events, &inner_req.dst_chain, &inner_req.src_chain);
+
+        target_events.extend(
+            archive::archived_send_events(&inner_req.timeout_packet_commitments)
+                .map_err(|e| tonic::Status::failed_precondition(e.to_string()))?,
+        );
+
+        let mut src_packet_seqs = inner_req.src_packet_sequences;
+        let relay_hints = self.relay_hint_policy.as_ref().map(|policy| {
+            policy.apply(
+                &mut src_events,
+                &inner_req.src_client_id,
+                &mut src_packet_seqs,
+            )
+        });
+
+        let deferred_seqs = approvals::cap_relay_value(
+            self.tx_value_cap.as_ref(),
+            &inner_req.src_client_id,
+            &src_packet_seqs,
+            &mut src_events,
+        )?;
+
+        approvals::admit_relay(
+            self.value_limiter.as_deref(),
+            &relay_id,
+            &inner_req.src_client_id,
+            &src_events,
+        )?;
+
+        let sealed_transfers = escrow::sealed_transfers(
+            self.artifact_escrow.as_ref(),
+            &inner_req.src_client_id,
+            &src_events,
+        )?;
+
+        let ack_relay = AckRelay {
+            src_chain: inner_req.dst_chain.clone(),
+            dst_chain: inner_req.src_chain.clone(),
+            src_client_id: inner_req.dst_client_id.clone(),
+            dst_client_id: inner_req.src_client_id.clone(),
+        };
+
         let tx = self
             .tx_builder
             .relay_events(
+                &scope,
                 src_events,
                 target_events,
                 inner_req.src_client_id,
                 inner_req.dst_client_id,
-                inner_req.src_packet_sequences,
+                src_packet_seqs,
                 inner_req.dst_packet_sequences,
             )
             .await
-            .map_err(|e| tonic::Status::from_error(e.into()))?;
+            .map_err(hints::relay_error)?;
+
+        let plan = match self.tx_builder.plan_tx(&tx).await {
+            Ok(plan) => {
+                tracing::info!(
+                    plan = ?plan,
+                    "Relay tx with {} messages has an estimated gas of {}.",
+                    plan.msgs.len(),
+                    plan.estimated_gas
+                );
+                Some(plan)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to plan the relay tx: {e}");
+                None
+            }
+        };
+
+        if let Some(ack_schedule) = &self.ack_schedule {
+            let scheduled = async {
+                let relayed_packets = introspection::cosmos_relayed_packets(&tx)?;
+                let start_height = self.target_listener.confirmed_height().await?;
+                scheduler::schedule_acks::<CosmosSdk, _>(
+                    ack_schedule.clone(),
+                    self.target_listener.clone(),
+                    recv_sequences(&relayed_packets, &ack_relay.dst_client_id),
+                    ack_relay,
+                    start_height,
+                );
+                anyhow::Ok(())
+            };
+            if let Err(e) = scheduled.await {
+                tracing::warn!("Failed to schedule the ack relay: {e}");
+            }
+        }
+
+        let (tx, packet_tx) = match self.tx_composition {
+            TxComposition::Atomic => (tx, None),
+            TxComposition::Split => match composition::split_cosmos_tx(&tx)
+                .map_err(|e| tonic::Status::from_error(e.into()))?
+            {
+                Some(split) => {
+                    tracing::info!("Split the relay into an update tx and a packet tx.");
+                    (split.update_tx, Some(split.packet_tx))
+                }
+                None => (tx, None),
+            },
+        };
+
+        let composed_txs = 1 + usize::from(packet_tx.is_some());
+        let mut txs = Vec::new();
+        for tx in std::iter::once(tx).chain(packet_tx) {
+            txs.extend(
+                composition::split_cosmos_tx_by_size(&tx, &self.tx_size_limit)
+                    .map_err(|e| tonic::Status::resource_exhausted(e.to_string()))?,
+            );
+        }
+        let size_split = txs.len() > composed_txs;
+        if size_split {
+            tracing::info!(
+                "Split the relay into {} txs within the size limit.",
+                txs.len()
+            );
+        }
+
+        let mut authorized_txs = Vec::with_capacity(txs.len());
+        for tx in txs {
+            authorized_txs.push(self.authorize_tx(tx).await?);
+        }
+        let next_txs = authorized_txs.split_off(1);
+        let tx = authorized_txs.remove(0);
+
+        let fee_quote = hints::quote_fee(
+            self.fee.as_ref(),
+            self.target_listener.client(),
+            plan.as_ref(),
+        )
+        .await;
 
         tracing::info!("Relay by tx request completed.");
 
-        Ok(Response::new(api::RelayByTxResponse {
+        let mut response = Response::new(api::RelayByTxResponse {
             tx,
             address: String::new(),
-        }))
+        });
+        if let Some(relay_hints) = relay_hints {
+            hints::insert_relay_hints(&relay_hints, response.metadata_mut());
+        }
+        if let Some(fee_quote) = &fee_quote {
+            hints::insert_fee_quote(fee_quote, response.metadata_mut());
+        }
+        approvals::insert_deferred_sequences(&deferred_seqs, response.metadata_mut());
+        hints::insert_next_txs(&next_txs, size_split, response.metadata_mut());
+
+        escrow::seal_relay(
+            self.artifact_escrow.as_ref(),
+            &relay_id,
+            &inner_req.src_chain,
+            &inner_req.dst_chain,
+            sealed_transfers,
+            response,
+        )
     }
 
     #[tracing::instrument(skip_all)]
@@ -201,6 +552,8 @@ impl RelayerModule for CosmosToCosmosRelayerModule {
             .map_err(|e| anyhow::anyhow!("failed to parse config: {e}"))?;
 
         tracing::info!("Starting Cosmos to Cosmos relayer server.");
-        Ok(Box::new(CosmosToCosmosRelayerModuleService::new(config)))
+        Ok(Box::new(
+            CosmosToCosmosRelayerModuleService::new(config).await,
+        ))
     }
 }
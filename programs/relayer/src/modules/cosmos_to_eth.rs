@@ -1,14 +1,29 @@
 //! Defines Cosmos to Ethereum relayer module.
 
+use std::sync::Arc;
+
 use alloy::{
     primitives::{Address, TxHash},
     providers::{Provider, RootProvider},
 };
+use ethereum_apis::circuit_breaker::CircuitBreakerConfig;
 use ibc_eureka_relayer_lib::{
+    chain::EthEureka,
+    cleanup::{self, CleanupCheck, CleanupOutcome},
+    confirmation::CosmosConfirmationPolicy,
+    gas_spike::{GasSpikeGuard, GasSpikePolicy},
+    introspection::{self, RelayedPacket},
     listener::{cosmos_sdk, eth_eureka, ChainListenerService},
-    tx_builder::{cosmos_to_eth::TxBuilder, TxBuilderService},
+    lookback::MaxLookback,
+    network::{NetworkGuard, NetworkGuardConfig},
+    reconciliation::AckReconciliation,
+    relay_hints::RelayHintPolicy,
+    scheduler::{recv_sequences, AckSchedule},
+    tx_builder::{composition, cosmos_to_eth::TxBuilder, plan::TxSizeLimit, TxBuilderService},
+    value_limit::{self, TxValueCap, ValueLimitPolicy, ValueRateLimiter},
 };
-use ibc_eureka_utils::rpc::TendermintRpcExt;
+use ibc_eureka_solidity_types::ics26::router::routerInstance;
+use ibc_eureka_utils::{http::HttpClientConfig, rpc::TendermintRpcExt};
 use sp1_ics07_tendermint_prover::programs::{
     MembershipProgram, MisbehaviourProgram, SP1ICS07TendermintPrograms,
     UpdateClientAndMembershipProgram, UpdateClientProgram,
@@ -21,7 +36,15 @@ use tonic::{Request, Response};
 
 use crate::{
     api::{self, relayer_service_server::RelayerService},
-    core::modules::RelayerModule,
+    core::{
+        approvals, archive, deadline,
+        escrow::{self, ArtifactEscrowConfig},
+        gas, hints,
+        modules::RelayerModule,
+        scheduler::{self, AckRelay},
+    },
+    metrics,
+    modules::connect_eth_provider,
 };
 
 /// The `CosmosToEthRelayerModule` struct defines the Cosmos to Ethereum relayer module.
@@ -37,6 +60,24 @@ struct CosmosToEthRelayerModuleService {
     pub eth_listener: eth_eureka::ChainListener<RootProvider>,
     /// The transaction builder for `EthEureka`.
     pub tx_builder: TxBuilder<RootProvider, CpuProverComponents>,
+    /// The value rate limiter, if the module has a value at risk policy.
+    pub value_limiter: Option<Arc<ValueRateLimiter>>,
+    /// The guard against relaying between networks that do not match the light clients.
+    pub network_guard: NetworkGuard,
+    /// The policy for the relay hints of the packets, if the module honors them.
+    pub relay_hint_policy: Option<RelayHintPolicy>,
+    /// The cap on the value of the packets included in a single relay transaction, if any.
+    pub tx_value_cap: Option<TxValueCap>,
+    /// The schedule of the ack relays that depend on the relayed recvs, if any.
+    pub ack_schedule: Option<AckSchedule>,
+    /// The verification of the commitment cleanup of the relayed acks and timeouts, if any.
+    pub commitment_cleanup: Option<CleanupCheck>,
+    /// The guard deferring the non-urgent messages during base fee spikes, if any.
+    pub gas_guard: Option<Arc<GasSpikeGuard>>,
+    /// The size limit of the relay transactions.
+    pub tx_size_limit: TxSizeLimit,
+    /// The escrow of the relays of high-value transfers, if any.
+    pub artifact_escrow: Option<ArtifactEscrowConfig>,
 }
 
 /// The configuration for the Cosmos to Ethereum relayer module.
@@ -49,10 +90,70 @@ pub struct CosmosToEthConfig {
     pub ics26_address: Address,
     /// The EVM RPC URL.
     pub eth_rpc_url: String,
+    /// The fallback EVM RPC URLs. If set, the requests go to the healthiest of all the EVM RPC
+    /// endpoints, and to the next ones when it fails.
+    #[serde(default)]
+    pub eth_rpc_fallback_urls: Vec<String>,
     /// The SP1 prover configuration.
     pub sp1_prover: SP1Config,
     /// The SP1 program paths.
     pub sp1_programs: SP1ProgramPaths,
+    /// The confirmation policy for events from the Cosmos SDK chain.
+    #[serde(default)]
+    pub confirmation_policy: CosmosConfirmationPolicy,
+    /// The value at risk policy for relays toward the target chain.
+    #[serde(default)]
+    pub value_limit: Option<ValueLimitPolicy>,
+    /// The configuration of the HTTP clients, such as the proxy and the endpoint authentication.
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+    /// The circuit breaker of the Ethereum RPC endpoint, which makes the requests fail fast while
+    /// the endpoint keeps failing. The endpoint is not guarded if unset.
+    #[serde(default)]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// The expected networks, and how often the light clients are checked against them.
+    #[serde(default)]
+    pub network_guard: NetworkGuardConfig,
+    /// The policy for the relay hints in the memos of the ICS20 packets. Hints are ignored if unset.
+    #[serde(default)]
+    pub relay_hints: Option<RelayHintPolicy>,
+    /// The cap on the value of the ICS20 packets included in a single relay transaction.
+    /// Packets exceeding the cap are deferred to a later transaction.
+    #[serde(default)]
+    pub tx_value_cap: Option<TxValueCap>,
+    /// The schedule of the ack relays of the opposite route. If set, the acknowledgements of the
+    /// relayed packets are awaited and their ack relay is built as soon as they are written.
+    #[serde(default)]
+    pub ack_schedule: Option<AckSchedule>,
+    /// The reconciliation of the acks written before the relayer started. If set, the
+    /// acknowledgements of the packets sent within its window that were never relayed back are
+    /// scheduled once the module starts.
+    #[serde(default)]
+    pub ack_reconciliation: Option<AckReconciliation>,
+    /// The max lookback of the scans of the history of the route, which bounds the scans of the
+    /// `ack_reconciliation` deeper than it. The scans are not bounded if unset.
+    #[serde(default)]
+    pub max_lookback: MaxLookback,
+    /// The verification that the packet commitments are deleted on Ethereum once the relayed
+    /// acks and timeouts land. Stale commitments are logged and counted in the metrics.
+    #[serde(default)]
+    pub commitment_cleanup: Option<CleanupCheck>,
+    /// The base fee ceiling of Ethereum. While the base fee exceeds it, the acks, the timeouts and
+    /// the recvs of the packets that do not time out soon are deferred until it falls back.
+    #[serde(default)]
+    pub gas_spike: Option<GasSpikePolicy>,
+    /// The calldata budget of the relay transactions, by default the largest transaction accepted
+    /// by the geth transaction pool. Larger relays are split into consecutive multicalls.
+    #[serde(default = "default_tx_size_limit")]
+    pub tx_size_limit: TxSizeLimit,
+    /// The escrow of the relays of high-value transfers, which are sealed until a timelock, an
+    /// approval or a committee reveals them. The relays are returned directly if unset.
+    #[serde(default)]
+    pub artifact_escrow: Option<ArtifactEscrowConfig>,
+}
+
+const fn default_tx_size_limit() -> TxSizeLimit {
+    TxSizeLimit::ETHEREUM
 }
 
 /// The paths to the SP1 programs.
@@ -124,15 +225,37 @@ pub enum SP1Config {
 
 impl CosmosToEthRelayerModuleService {
     async fn new(config: CosmosToEthConfig) -> Self {
-        let tm_client = HttpClient::from_rpc_url(&config.tm_rpc_url);
-        let tm_listener = cosmos_sdk::ChainListener::new(tm_client.clone());
-
-        let provider = RootProvider::builder()
-            .connect(&config.eth_rpc_url)
-            .await
-            .unwrap_or_else(|e| panic!("failed to create provider: {e}"));
+        let tm_client =
+            HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)
+                .unwrap_or_else(|e| panic!("failed to create tendermint client: {e}"));
+        let tm_listener = cosmos_sdk::ChainListener::new(tm_client.clone())
+            .with_confirmation_policy(config.confirmation_policy);
+
+        let provider = connect_eth_provider(
+            &config.eth_rpc_url,
+            &config.eth_rpc_fallback_urls,
+            &config.http_client,
+            config.circuit_breaker,
+        )
+        .await
+        .unwrap_or_else(|e| panic!("failed to create provider: {e}"));
 
         let eth_listener = eth_eureka::ChainListener::new(config.ics26_address, provider.clone());
+        let gas_provider = provider.clone();
+
+        let source_chain_id = tm_listener
+            .chain_id()
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch the source chain id: {e}"));
+        let target_chain_id = eth_listener
+            .chain_id()
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch the target chain id: {e}"));
+        config
+            .network_guard
+            .ensure_expected_chain_ids(&source_chain_id, &target_chain_id)
+            .unwrap_or_else(|e| panic!("refusing to relay: {e}"));
+        let network_guard = NetworkGuard::new(&config.network_guard);
 
         let sp1_programs = config
             .sp1_programs
@@ -216,13 +339,87 @@ impl CosmosToEthRelayerModuleService {
                     )
                 }
             }
-        };
+        }
+        .with_confirmation_policy(config.confirmation_policy);
+
+        if let Some(reconciliation) = config.ack_reconciliation {
+            let max_lookback = config.max_lookback;
+            let src_listener = tm_listener.clone();
+            let dst_listener = eth_listener.clone();
+            tokio::spawn(async move {
+                match dst_listener.confirmed_block_number().await {
+                    Ok(dst_height) => {
+                        scheduler::reconcile_acks::<EthEureka, _>(
+                            reconciliation,
+                            max_lookback,
+                            src_listener,
+                            dst_listener,
+                            dst_height,
+                        )
+                        .await;
+                    }
+                    Err(e) => tracing::warn!("Failed to reconcile the acknowledgements: {e}"),
+                }
+            });
+        }
+
+        let gas_guard = config.gas_spike.map(|policy| {
+            let gas_guard = Arc::new(GasSpikeGuard::new(policy));
+            gas::register_gas_guard(target_chain_id, gas_guard.clone(), gas_provider);
+            gas_guard
+        });
+
+        if let Some(artifact_escrow) = &config.artifact_escrow {
+            artifact_escrow
+                .validate()
+                .unwrap_or_else(|e| panic!("invalid artifact escrow: {e}"));
+        }
+
+        let value_limiter = config.value_limit.map(|policy| {
+            let value_limiter = Arc::new(ValueRateLimiter::new(policy));
+            approvals::register_value_limiter(value_limiter.clone());
+            value_limiter
+        });
 
         Self {
             tm_listener,
             eth_listener,
             tx_builder,
+            value_limiter,
+            network_guard,
+            relay_hint_policy: config.relay_hints,
+            tx_value_cap: config.tx_value_cap,
+            ack_schedule: config.ack_schedule,
+            commitment_cleanup: config.commitment_cleanup,
+            gas_guard,
+            tx_size_limit: config.tx_size_limit,
+            artifact_escrow: config.artifact_escrow,
+        }
+    }
+}
+
+/// Verifies that the commitments of the packets are deleted once their relayed acks and timeouts
+/// land, and counts the stale commitments in the metrics.
+async fn watch_commitment_cleanup(
+    ics26_router: routerInstance<(), RootProvider>,
+    cleanup_check: CleanupCheck,
+    packets: Vec<RelayedPacket>,
+    start_block: u64,
+    chain_id: String,
+) {
+    match cleanup::verify_commitment_cleanup(&ics26_router, &cleanup_check, packets, start_block)
+        .await
+    {
+        Ok(cleanups) => {
+            for cleanup in cleanups {
+                if cleanup.outcome == CleanupOutcome::Stale {
+                    metrics::STALE_COMMITMENTS
+                        .with_label_values(&[&chain_id, &cleanup.packet.source_client])
+                        .inc();
+                }
+            }
         }
+        Err(e) => tracing::warn!("Failed to verify the commitment cleanup: {e}"),
     }
 }
 
@@ -264,25 +461,47 @@ impl RelayerService for CosmosToEthRelayerModuleService {
     ) -> Result<Response<api::RelayByTxResponse>, tonic::Status> {
         tracing::info!("Handling relay by tx request for Cosmos to Eth...");
 
+        let scope = deadline::relay_scope(request.metadata());
         let inner_req = request.into_inner();
         tracing::info!("Got {} source tx IDs", inner_req.source_tx_ids.len());
         tracing::info!("Got {} timeout tx IDs", inner_req.timeout_tx_ids.len());
+        let relay_id = value_limit::relay_id(&inner_req.src_client_id, &inner_req.source_tx_ids);
+
+        self.network_guard
+            .check(&inner_req.dst_client_id, || {
+                self.tx_builder
+                    .ensure_client_network(inner_req.dst_client_id.clone())
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!("Refusing to relay: {e}");
+                tonic::Status::failed_precondition(e.to_string())
+            })?;
+
+        if let Some(response) =
+            escrow::reveal_sealed_relay(self.artifact_escrow.as_ref(), &relay_id)?
+        {
+            return Ok(response);
+        }
+
         let cosmos_txs = inner_req
             .source_tx_ids
-            .into_iter()
+            .iter()
+            .cloned()
             .map(Hash::try_from)
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| tonic::Status::from_error(e.into()))?;
 
         let eth_txs = inner_req
             .timeout_tx_ids
-            .into_iter()
+            .iter()
+            .cloned()
             .map(TryInto::<[u8; 32]>::try_into)
             .map(|tx_hash| tx_hash.map(TxHash::from))
             .collect::<Result<Vec<_>, _>>()
             .map_err(|tx| tonic::Status::from_error(format!("invalid tx hash: {tx:?}").into()))?;
 
-        let cosmos_events = self
+        let mut cosmos_events = self
             .tm_listener
             .fetch_tx_events(cosmos_txs)
             .await
@@ -294,7 +513,7 @@ impl RelayerService for CosmosToEthRelayerModuleService {
             cosmos_events.len()
         );
 
-        let eth_events = self
+        let mut eth_events = self
             .eth_listener
             .fetch_tx_events(eth_txs)
             .await
@@ -303,25 +522,152 @@ impl RelayerService for CosmosToEthRelayerModuleService {
         tracing::debug!(eth_events = ?eth_events, "Fetched EVM events.");
         tracing::info!("Fetched {} eureka events from EVM.", eth_events.len());
 
+        archive::archive_packets(&cosmos_events, &inner_req.src_chain, &inner_req.dst_chain);
+        archive::archive_packets(&eth_events, &inner_req.dst_chain, &inner_req.src_chain);
+
+        eth_events.extend(
+            archive::archived_send_events(&inner_req.timeout_packet_commitments)
+                .map_err(|e| tonic::Status::failed_precondition(e.to_string()))?,
+        );
+
+        let gas_deferred = gas::defer_relay(
+            self.gas_guard.as_deref(),
+            &inner_req,
+            &mut cosmos_events,
+            &mut eth_events,
+        )?;
+
+        let mut src_packet_seqs = inner_req.src_packet_sequences;
+        let relay_hints = self.relay_hint_policy.as_ref().map(|policy| {
+            policy.apply(
+                &mut cosmos_events,
+                &inner_req.src_client_id,
+                &mut src_packet_seqs,
+            )
+        });
+
+        let deferred_seqs = approvals::cap_relay_value(
+            self.tx_value_cap.as_ref(),
+            &inner_req.src_client_id,
+            &src_packet_seqs,
+            &mut cosmos_events,
+        )?;
+
+        approvals::admit_relay(
+            self.value_limiter.as_deref(),
+            &relay_id,
+            &inner_req.src_client_id,
+            &cosmos_events,
+        )?;
+
+        let sealed_transfers = escrow::sealed_transfers(
+            self.artifact_escrow.as_ref(),
+            &inner_req.src_client_id,
+            &cosmos_events,
+        )?;
+
+        let ack_relay = AckRelay {
+            src_chain: inner_req.dst_chain.clone(),
+            dst_chain: inner_req.src_chain.clone(),
+            src_client_id: inner_req.dst_client_id.clone(),
+            dst_client_id: inner_req.src_client_id.clone(),
+        };
+
         let multicall_tx = self
             .tx_builder
             .relay_events(
+                &scope,
                 cosmos_events,
                 eth_events,
                 inner_req.src_client_id,
                 inner_req.dst_client_id,
-                inner_req.src_packet_sequences,
+                src_packet_seqs,
                 inner_req.dst_packet_sequences,
             )
             .await
-            .map_err(|e| tonic::Status::from_error(e.into()))?;
+            .map_err(hints::relay_error)?;
+
+        match self.tx_builder.plan_tx(&multicall_tx).await {
+            Ok(plan) => tracing::info!(
+                plan = ?plan,
+                "Relay tx with {} messages has an estimated gas of {}.",
+                plan.msgs.len(),
+                plan.estimated_gas
+            ),
+            Err(e) => tracing::warn!("Failed to plan the relay tx: {e}"),
+        }
+
+        if let Some(ack_schedule) = &self.ack_schedule {
+            let scheduled = async {
+                let relayed_packets = introspection::eth_relayed_packets(&multicall_tx)?;
+                let start_height = self.eth_listener.confirmed_block_number().await?;
+                scheduler::schedule_acks::<EthEureka, _>(
+                    ack_schedule.clone(),
+                    self.eth_listener.clone(),
+                    recv_sequences(&relayed_packets, &ack_relay.dst_client_id),
+                    ack_relay,
+                    start_height,
+                );
+                anyhow::Ok(())
+            };
+            if let Err(e) = scheduled.await {
+                tracing::warn!("Failed to schedule the ack relay: {e}");
+            }
+        }
+
+        if let Some(cleanup_check) = &self.commitment_cleanup {
+            let watched = async {
+                let packets =
+                    cleanup::cleanup_packets(introspection::eth_relayed_packets(&multicall_tx)?);
+                if packets.is_empty() {
+                    return anyhow::Ok(());
+                }
+                let start_block = self.eth_listener.confirmed_block_number().await?;
+                tokio::spawn(watch_commitment_cleanup(
+                    self.tx_builder.ics26_router.clone(),
+                    cleanup_check.clone(),
+                    packets,
+                    start_block,
+                    inner_req.dst_chain.clone(),
+                ));
+                anyhow::Ok(())
+            };
+            if let Err(e) = watched.await {
+                tracing::warn!("Failed to watch the commitment cleanup: {e}");
+            }
+        }
+
+        let mut txs = composition::split_multicall_tx_by_size(&multicall_tx, &self.tx_size_limit)
+            .map_err(|e| tonic::Status::resource_exhausted(e.to_string()))?;
+        let next_txs = txs.split_off(1);
+        if !next_txs.is_empty() {
+            tracing::info!(
+                "Split the relay into {} txs within the size limit.",
+                next_txs.len() + 1
+            );
+        }
 
         tracing::info!("Relay by tx request completed.");
 
-        Ok(Response::new(api::RelayByTxResponse {
-            tx: multicall_tx,
+        let mut response = Response::new(api::RelayByTxResponse {
+            tx: txs.remove(0),
             address: self.tx_builder.ics26_router.address().to_string(),
-        }))
+        });
+        if let Some(relay_hints) = relay_hints {
+            hints::insert_relay_hints(&relay_hints, response.metadata_mut());
+        }
+        approvals::insert_deferred_sequences(&deferred_seqs, response.metadata_mut());
+        gas::insert_gas_deferred(&gas_deferred, response.metadata_mut());
+        hints::insert_next_txs(&next_txs, true, response.metadata_mut());
+
+        escrow::seal_relay(
+            self.artifact_escrow.as_ref(),
+            &relay_id,
+            &inner_req.src_chain,
+            &inner_req.dst_chain,
+            sealed_transfers,
+            response,
+        )
     }
 
     #[tracing::instrument(skip_all)]
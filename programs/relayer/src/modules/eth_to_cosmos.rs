@@ -1,24 +1,54 @@
 //! Defines Ethereum to Cosmos relayer module.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use alloy::{
-    primitives::{Address, TxHash},
+    primitives::{Address, TxHash, U256},
     providers::{Provider, RootProvider},
 };
+use ethereum_apis::{beacon_api::client::BeaconApiClient, circuit_breaker::CircuitBreakerConfig};
 use ibc_eureka_relayer_lib::{
+    authz::{self, AuthzConfig},
+    chain::CosmosSdk,
+    confirmation::EthConfirmationPolicy,
     events::EurekaEventWithHeight,
+    fees::FeeConfig,
+    finality::{FinalityLag, FinalityLagMonitor},
+    introspection,
     listener::{cosmos_sdk, eth_eureka, ChainListenerService},
-    tx_builder::{eth_to_cosmos, TxBuilderService},
+    lookback::MaxLookback,
+    network::{NetworkGuard, NetworkGuardConfig},
+    relay_hints::RelayHintPolicy,
+    relay_scope::RelayScope,
+    relayer_fees::RelayerFeeConfig,
+    scheduler::{recv_sequences, AckSchedule},
+    slot_timing::SlotTiming,
+    tx_builder::{
+        composition::{self, TxComposition},
+        eth_to_cosmos::{self, ProofHeightStrategy, TxBuilderConfig},
+        plan::{TxPlan, TxSizeLimit},
+        TxBuilderService,
+    },
+    value_limit::{self, TxValueCap, ValueLimitPolicy, ValueRateLimiter},
 };
-use ibc_eureka_utils::rpc::TendermintRpcExt;
+use ibc_eureka_solidity_types::ics26::ICS26_IBC_STORAGE_SLOT;
+use ibc_eureka_utils::{http::HttpClientConfig, rpc::TendermintRpcExt};
 use tendermint::Hash;
 use tendermint_rpc::HttpClient;
 use tonic::{Request, Response};
 
 use crate::{
     api::{self, relayer_service_server::RelayerService},
-    core::modules::RelayerModule,
+    core::{
+        approvals, archive, deadline,
+        escrow::{self, ArtifactEscrowConfig},
+        hints,
+        modules::RelayerModule,
+        scheduler::{self, AckRelay},
+        sync_committees,
+    },
+    metrics,
+    modules::connect_eth_provider,
 };
 
 /// The `CosmosToCosmosRelayerModule` struct defines the Cosmos to Cosmos relayer module.
@@ -34,10 +64,34 @@ struct EthToCosmosRelayerModuleService {
     pub tm_listener: cosmos_sdk::ChainListener,
     /// The transaction builder for Ethereum to Cosmos.
     pub tx_builder: EthToCosmosTxBuilder,
+    /// The value rate limiter, if the module has a value at risk policy.
+    pub value_limiter: Option<Arc<ValueRateLimiter>>,
+    /// The guard against relaying between networks that do not match the light clients.
+    pub network_guard: NetworkGuard,
+    /// The policy for the relay hints of the packets, if the module honors them.
+    pub relay_hint_policy: Option<RelayHintPolicy>,
+    /// The cap on the value of the packets included in a single relay transaction, if any.
+    pub tx_value_cap: Option<TxValueCap>,
+    /// The schedule of the ack relays that depend on the relayed recvs, if any.
+    pub ack_schedule: Option<AckSchedule>,
+    /// The address of the submitter, which is the granter if the messages are submitted through
+    /// authz.
+    pub signer_address: String,
+    /// The submission through authz, if any.
+    pub authz: Option<AuthzConfig>,
+    /// The fee payment of the relay transactions in another denom than the default of the chain,
+    /// if any.
+    pub fee: Option<FeeConfig>,
+    /// The composition of the client updates and the packet messages into transactions.
+    pub tx_composition: TxComposition,
+    /// The size limit of the relay transactions.
+    pub tx_size_limit: TxSizeLimit,
+    /// The escrow of the relays of high-value transfers, if any.
+    pub artifact_escrow: Option<ArtifactEscrowConfig>,
 }
 
 enum EthToCosmosTxBuilder {
-    Real(eth_to_cosmos::TxBuilder<RootProvider>),
+    Real(Arc<eth_to_cosmos::TxBuilder<RootProvider>>),
     Mock(eth_to_cosmos::MockTxBuilder<RootProvider>),
 }
 
@@ -51,47 +105,337 @@ pub struct EthToCosmosConfig {
     pub tm_rpc_url: String,
     /// The EVM RPC URL.
     pub eth_rpc_url: String,
+    /// The fallback EVM RPC URLs. If set, the requests go to the healthiest of all the EVM RPC
+    /// endpoints, and to the next ones when it fails.
+    #[serde(default)]
+    pub eth_rpc_fallback_urls: Vec<String>,
     /// The Ethereum Beacon API URL
     pub eth_beacon_api_url: String,
+    /// The fallback Ethereum Beacon API URLs, requested in order when the previous ones fail.
+    #[serde(default)]
+    pub eth_beacon_api_fallback_urls: Vec<String>,
     /// The address of the submitter.
     /// Required since cosmos messages require a signer address.
     pub signer_address: String,
     /// Whether to run in mock mode.
     #[serde(default)]
     pub mock: bool,
+    /// The confirmation policy for events from Ethereum.
+    #[serde(default)]
+    pub confirmation_policy: EthConfirmationPolicy,
+    /// The value at risk policy for relays toward the target chain.
+    #[serde(default)]
+    pub value_limit: Option<ValueLimitPolicy>,
+    /// The configuration of the HTTP clients, such as the proxy and the endpoint authentication.
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+    /// The circuit breaker of the Ethereum endpoints, which takes an endpoint that keeps failing
+    /// out of rotation for a while. The endpoints are not guarded if unset.
+    #[serde(default)]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// The expected networks, and how often the light clients are checked against them.
+    #[serde(default)]
+    pub network_guard: NetworkGuardConfig,
+    /// The policy for the relay hints in the memos of the ICS20 packets. Hints are ignored if unset.
+    #[serde(default)]
+    pub relay_hints: Option<RelayHintPolicy>,
+    /// The cap on the value of the ICS20 packets included in a single relay transaction.
+    /// Packets exceeding the cap are deferred to a later transaction.
+    #[serde(default)]
+    pub tx_value_cap: Option<TxValueCap>,
+    /// The schedule of the ack relays of the opposite route. If set, the acknowledgements of the
+    /// relayed packets are awaited and their ack relay is built as soon as they are written.
+    #[serde(default)]
+    pub ack_schedule: Option<AckSchedule>,
+    /// The max lookback of the scans of the history of the route. The backfill of an Ethereum
+    /// light client lagging further behind is refused. The scans are not bounded if unset.
+    #[serde(default)]
+    pub max_lookback: MaxLookback,
+    /// Whether to submit the intermediate light client updates within a sync committee period,
+    /// which keeps the consensus state timestamps of the client continuous.
+    #[serde(default)]
+    pub intermediate_updates: bool,
+    /// The strategy selecting the height the membership proofs are anchored at when the light
+    /// client is updated: the latest finalized slot (default) or the minimal slot covering the
+    /// packets.
+    #[serde(default)]
+    pub proof_height_strategy: ProofHeightStrategy,
+    /// Whether to zlib compress the client messages for the light clients created with
+    /// `compressed_client_messages`, which shrinks the update transactions.
+    #[serde(default)]
+    pub compress_client_messages: bool,
+    /// The number of times a relay is rebuilt against the latest height of the light client when
+    /// a consensus state its proofs are verified against is pruned while it is built.
+    #[serde(default = "default_stale_proof_retries")]
+    pub stale_proof_retries: u32,
+    /// Whether the commitment storage slot is queried from the `ICS26Router` instead of derived
+    /// from its storage layout. If set, the new light clients prove the commitments at the
+    /// queried slot, and the light clients are checked against it by the network guard.
+    #[serde(default)]
+    pub query_commitment_slot: bool,
+    /// The number of epochs the light client headers selected for a relay are reused for by the
+    /// next relays to the same client, as long as the client is not updated in between. The
+    /// headers are selected for every relay if zero.
+    #[serde(default = "default_update_plan_epochs")]
+    pub update_plan_epochs: u64,
+    /// Whether the light client updates are attributed to the signer address in their client
+    /// messages, which the light client records with the consensus states. Only supported by the
+    /// light clients released with the attribution.
+    #[serde(default)]
+    pub attribute_updates: bool,
+    /// The monitoring of the beacon finality lag and of the execution lag of the light clients,
+    /// exposed as metrics. The lags are not monitored if unset.
+    #[serde(default)]
+    pub finality_lag: Option<FinalityLagMonitor>,
+    /// The submission of the relay messages through an authz `MsgExec` of a grantee, on behalf of
+    /// the signer address. The messages are submitted directly if unset.
+    #[serde(default)]
+    pub authz: Option<AuthzConfig>,
+    /// The fee payment of the relay transactions in another denom than the default of the chain,
+    /// such as an IBC voucher. The fee is not quoted if unset.
+    #[serde(default)]
+    pub fee: Option<FeeConfig>,
+    /// The claiming of the relayer fees escrowed by the fee middleware of the target chain for
+    /// the acknowledged and the timed out packets, in the same transaction as their relay. The
+    /// fees are not claimed if unset.
+    #[serde(default)]
+    pub relayer_fees: Option<RelayerFeeConfig>,
+    /// Whether the client updates and the packet messages of a relay are submitted in a single
+    /// atomic transaction (default), or split into an update transaction and a packet transaction.
+    #[serde(default)]
+    pub tx_composition: TxComposition,
+    /// The size limit of the relay transactions, by default the `max_tx_bytes` of `CometBFT`.
+    /// Larger relays are split into consecutive transactions within the limit.
+    #[serde(default = "default_tx_size_limit")]
+    pub tx_size_limit: TxSizeLimit,
+    /// The escrow of the relays of high-value transfers, which are sealed until a timelock, an
+    /// approval or a committee reveals them. The relays are returned directly if unset.
+    #[serde(default)]
+    pub artifact_escrow: Option<ArtifactEscrowConfig>,
+}
+
+const fn default_tx_size_limit() -> TxSizeLimit {
+    TxSizeLimit::COSMOS_SDK
+}
+
+const fn default_stale_proof_retries() -> u32 {
+    eth_to_cosmos::DEFAULT_STALE_PROOF_RETRIES
+}
+
+const fn default_update_plan_epochs() -> u64 {
+    eth_to_cosmos::DEFAULT_UPDATE_PLAN_EPOCHS
+}
+
+impl EthToCosmosConfig {
+    /// Creates the Beacon API client with the configured HTTP client.
+    pub(crate) fn beacon_api_client(&self) -> anyhow::Result<BeaconApiClient> {
+        let mut beacon_api_client = BeaconApiClient::new(self.eth_beacon_api_url.clone())
+            .with_client(self.http_client.build_client(&self.eth_beacon_api_url)?);
+        if let Some(circuit_breaker) = self.circuit_breaker {
+            beacon_api_client = beacon_api_client
+                .with_circuit_breaker(circuit_breaker, Some(metrics::observe_circuit_state));
+        }
+        for url in &self.eth_beacon_api_fallback_urls {
+            beacon_api_client =
+                beacon_api_client.with_fallback(url.clone(), self.http_client.build_client(url)?);
+        }
+        Ok(beacon_api_client)
+    }
+
+    /// Returns the options of the relays built by the tx builder, with the given slot timing of
+    /// the beacon chain.
+    pub(crate) fn tx_builder_config(&self, slot_timing: SlotTiming) -> TxBuilderConfig {
+        TxBuilderConfig {
+            confirmation_policy: self.confirmation_policy,
+            intermediate_updates: self.intermediate_updates,
+            slot_timing,
+            proof_height_strategy: self.proof_height_strategy,
+            compress_client_messages: self.compress_client_messages,
+            stale_proof_retries: self.stale_proof_retries,
+            query_commitment_slot: self.query_commitment_slot,
+            update_plan_epochs: self.update_plan_epochs,
+            update_relayer: self.attribute_updates.then(|| self.signer_address.clone()),
+            relayer_fees: self.relayer_fees.clone(),
+            ..TxBuilderConfig::default()
+        }
+    }
 }
 
 impl EthToCosmosRelayerModuleService {
     async fn new(config: EthToCosmosConfig) -> Self {
-        let provider = RootProvider::builder()
-            .connect(&config.eth_rpc_url)
-            .await
-            .unwrap_or_else(|e| panic!("failed to create provider: {e}"));
-        let eth_listener = eth_eureka::ChainListener::new(config.ics26_address, provider.clone());
-
-        let tm_client = HttpClient::from_rpc_url(&config.tm_rpc_url);
+        let provider = connect_eth_provider(
+            &config.eth_rpc_url,
+            &config.eth_rpc_fallback_urls,
+            &config.http_client,
+            config.circuit_breaker,
+        )
+        .await
+        .unwrap_or_else(|e| panic!("failed to create provider: {e}"));
+        let eth_listener = eth_eureka::ChainListener::new(config.ics26_address, provider.clone())
+            .with_confirmation_policy(config.confirmation_policy);
+
+        let tm_client =
+            HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)
+                .unwrap_or_else(|e| panic!("failed to create tendermint client: {e}"));
         let tm_listener = cosmos_sdk::ChainListener::new(tm_client.clone());
 
+        let beacon_api_client = config
+            .beacon_api_client()
+            .unwrap_or_else(|e| panic!("failed to create beacon api client: {e}"));
+
+        let source_chain_id = eth_listener
+            .chain_id()
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch the source chain id: {e}"));
+        let target_chain_id = tm_listener
+            .chain_id()
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch the target chain id: {e}"));
+        config
+            .network_guard
+            .ensure_expected_chain_ids(&source_chain_id, &target_chain_id)
+            .unwrap_or_else(|e| panic!("refusing to relay: {e}"));
+        let genesis_validators_root = beacon_api_client
+            .genesis()
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch the beacon genesis: {e}"))
+            .data
+            .genesis_validators_root;
+        config
+            .network_guard
+            .ensure_expected_genesis_validators_root(genesis_validators_root)
+            .unwrap_or_else(|e| panic!("refusing to relay: {e}"));
+        let network_guard = NetworkGuard::new(&config.network_guard);
+        let slot_timing = SlotTiming::from(
+            &beacon_api_client
+                .spec()
+                .await
+                .unwrap_or_else(|e| panic!("failed to fetch the beacon spec: {e}"))
+                .data,
+        );
+        tracing::info!(?slot_timing, "Using the slot timing of the beacon chain.");
+
         let tx_builder = if config.mock {
             EthToCosmosTxBuilder::Mock(eth_to_cosmos::MockTxBuilder::new(
                 config.ics26_address,
                 provider,
-                config.signer_address,
+                config.signer_address.clone(),
             ))
         } else {
-            EthToCosmosTxBuilder::Real(eth_to_cosmos::TxBuilder::new(
-                config.ics26_address,
-                provider,
-                config.eth_beacon_api_url,
-                tm_client,
-                config.signer_address,
-            ))
+            let tx_builder_config = config.tx_builder_config(slot_timing);
+            let tx_builder = Arc::new(
+                eth_to_cosmos::TxBuilder::new(
+                    config.ics26_address,
+                    provider,
+                    config.eth_beacon_api_url,
+                    tm_client,
+                    config.signer_address.clone(),
+                    tx_builder_config,
+                )
+                .with_sync_committees(sync_committees::sync_committees())
+                .with_beacon_api_client(beacon_api_client),
+            );
+            if config.query_commitment_slot {
+                let commitment_slot = tx_builder
+                    .ibc_commitment_slot()
+                    .await
+                    .unwrap_or_else(|e| panic!("failed to query the commitment slot: {e}"));
+                let derived_slot = U256::from_be_slice(&ICS26_IBC_STORAGE_SLOT);
+                if commitment_slot == derived_slot {
+                    tracing::info!("The router declares the derived commitment slot.");
+                } else {
+                    tracing::warn!(
+                        "The router declares the commitment slot {commitment_slot:#x} instead of \
+                         the derived {derived_slot:#x}, new clients prove the declared one."
+                    );
+                }
+            }
+            if let Some(monitor) = config.finality_lag {
+                tokio::spawn(monitor_finality_lag(
+                    source_chain_id,
+                    monitor,
+                    tx_builder.clone(),
+                ));
+            }
+            EthToCosmosTxBuilder::Real(tx_builder)
         };
 
+        if let Some(artifact_escrow) = &config.artifact_escrow {
+            artifact_escrow
+                .validate()
+                .unwrap_or_else(|e| panic!("invalid artifact escrow: {e}"));
+        }
+
+        let value_limiter = config.value_limit.map(|policy| {
+            let value_limiter = Arc::new(ValueRateLimiter::new(policy));
+            approvals::register_value_limiter(value_limiter.clone());
+            value_limiter
+        });
+
         Self {
             eth_listener,
             tm_listener,
             tx_builder,
+            value_limiter,
+            network_guard,
+            relay_hint_policy: config.relay_hints,
+            tx_value_cap: config.tx_value_cap,
+            ack_schedule: config.ack_schedule,
+            signer_address: config.signer_address,
+            authz: config.authz,
+            fee: config.fee,
+            tx_composition: config.tx_composition,
+            tx_size_limit: config.tx_size_limit,
+            artifact_escrow: config.artifact_escrow,
+        }
+    }
+
+    /// Wraps the messages of a relay tx in an authz `MsgExec` of the grantee, if the module
+    /// submits through authz.
+    async fn authorize_tx(&self, tx: Vec<u8>) -> Result<Vec<u8>, tonic::Status> {
+        match &self.authz {
+            Some(authz) => {
+                authz::authorize_tx(self.tm_listener.client(), authz, &self.signer_address, &tx)
+                    .await
+                    .map_err(|e| tonic::Status::failed_precondition(e.to_string()))
+            }
+            None => Ok(tx),
+        }
+    }
+}
+
+/// Samples the finality lag of the source chain and the execution lag of the monitored light
+/// clients, records them as metrics and logs the alerts they raise.
+async fn monitor_finality_lag(
+    chain_id: String,
+    monitor: FinalityLagMonitor,
+    tx_builder: Arc<eth_to_cosmos::TxBuilder<RootProvider>>,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(monitor.interval_secs));
+    loop {
+        interval.tick().await;
+
+        let lag = match FinalityLag::fetch(&tx_builder.beacon_api_client).await {
+            Ok(lag) => lag,
+            Err(e) => {
+                tracing::warn!("Failed to sample the finality lag: {e}");
+                continue;
+            }
+        };
+
+        let mut client_lags = Vec::with_capacity(monitor.client_ids.len());
+        for client_id in &monitor.client_ids {
+            match tx_builder.ethereum_client_state(client_id.clone()).await {
+                Ok(client_state) => {
+                    client_lags.push((client_id.clone(), lag.client_lag_blocks(&client_state)));
+                }
+                Err(e) => tracing::warn!(client_id, "Failed to fetch the client state: {e}"),
+            }
+        }
+
+        metrics::observe_finality_lag(&chain_id, lag.slots(), &client_lags);
+        for alert in monitor.alerts(&lag, &client_lags) {
+            tracing::warn!(chain_id, "Finality lag alert: {alert}");
         }
     }
 }
@@ -134,9 +478,29 @@ impl RelayerService for EthToCosmosRelayerModuleService {
     ) -> Result<Response<api::RelayByTxResponse>, tonic::Status> {
         tracing::info!("Handling relay by tx request for Eth to Cosmos...");
 
+        let scope = deadline::relay_scope(request.metadata());
         let inner_req = request.into_inner();
         tracing::info!("Got {} source tx IDs", inner_req.source_tx_ids.len());
         tracing::info!("Got {} timeout tx IDs", inner_req.timeout_tx_ids.len());
+        let relay_id = value_limit::relay_id(&inner_req.src_client_id, &inner_req.source_tx_ids);
+
+        self.network_guard
+            .check(&inner_req.dst_client_id, || {
+                self.tx_builder
+                    .ensure_client_network(inner_req.dst_client_id.clone())
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!("Refusing to relay: {e}");
+                tonic::Status::failed_precondition(e.to_string())
+            })?;
+
+        if let Some(response) =
+            escrow::reveal_sealed_relay(self.artifact_escrow.as_ref(), &relay_id)?
+        {
+            return Ok(response);
+        }
+
         let eth_txs = inner_req
             .source_tx_ids
             .into_iter()
@@ -152,7 +516,7 @@ impl RelayerService for EthToCosmosRelayerModuleService {
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| tonic::Status::from_error(e.into()))?;
 
-        let eth_events = self
+        let mut eth_events = self
             .eth_listener
             .fetch_tx_events(eth_txs)
             .await
@@ -161,7 +525,7 @@ impl RelayerService for EthToCosmosRelayerModuleService {
         tracing::debug!(eth_events = ?eth_events, "Fetched EVM events.");
         tracing::info!("Fetched {} eureka events from EVM.", eth_events.len());
 
-        let cosmos_events = self
+        let mut cosmos_events = self
             .tm_listener
             .fetch_tx_events(cosmos_txs)
             .await
@@ -173,25 +537,160 @@ impl RelayerService for EthToCosmosRelayerModuleService {
             cosmos_events.len()
         );
 
+        archive::archive_packets(&eth_events, &inner_req.src_chain, &inner_req.dst_chain);
+        archive::archive_packets(&cosmos_events, &inner_req.dst_chain, &inner_req.src_chain);
+
+        cosmos_events.extend(
+            archive::archived_send_events(&inner_req.timeout_packet_commitments)
+                .map_err(|e| tonic::Status::failed_precondition(e.to_string()))?,
+        );
+
+        let mut src_packet_seqs = inner_req.src_packet_sequences;
+        let relay_hints = self.relay_hint_policy.as_ref().map(|policy| {
+            policy.apply(
+                &mut eth_events,
+                &inner_req.src_client_id,
+                &mut src_packet_seqs,
+            )
+        });
+
+        let deferred_seqs = approvals::cap_relay_value(
+            self.tx_value_cap.as_ref(),
+            &inner_req.src_client_id,
+            &src_packet_seqs,
+            &mut eth_events,
+        )?;
+
+        approvals::admit_relay(
+            self.value_limiter.as_deref(),
+            &relay_id,
+            &inner_req.src_client_id,
+            &eth_events,
+        )?;
+
+        let sealed_transfers = escrow::sealed_transfers(
+            self.artifact_escrow.as_ref(),
+            &inner_req.src_client_id,
+            &eth_events,
+        )?;
+
+        let ack_relay = AckRelay {
+            src_chain: inner_req.dst_chain.clone(),
+            dst_chain: inner_req.src_chain.clone(),
+            src_client_id: inner_req.dst_client_id.clone(),
+            dst_client_id: inner_req.src_client_id.clone(),
+        };
+
         let tx = self
             .tx_builder
             .relay_events(
+                &scope,
                 eth_events,
                 cosmos_events,
                 inner_req.src_client_id,
                 inner_req.dst_client_id,
-                inner_req.src_packet_sequences,
+                src_packet_seqs,
                 inner_req.dst_packet_sequences,
             )
             .await
-            .map_err(|e| tonic::Status::from_error(e.into()))?;
+            .map_err(hints::relay_error)?;
+
+        let plan = match self.tx_builder.plan_tx(&tx).await {
+            Ok(plan) => {
+                tracing::info!(
+                    plan = ?plan,
+                    "Relay tx with {} messages has an estimated gas of {}.",
+                    plan.msgs.len(),
+                    plan.estimated_gas
+                );
+                Some(plan)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to plan the relay tx: {e}");
+                None
+            }
+        };
+
+        if let Some(ack_schedule) = &self.ack_schedule {
+            let scheduled = async {
+                let relayed_packets = introspection::cosmos_relayed_packets(&tx)?;
+                let start_height = self.tm_listener.confirmed_height().await?;
+                scheduler::schedule_acks::<CosmosSdk, _>(
+                    ack_schedule.clone(),
+                    self.tm_listener.clone(),
+                    recv_sequences(&relayed_packets, &ack_relay.dst_client_id),
+                    ack_relay,
+                    start_height,
+                );
+                anyhow::Ok(())
+            };
+            if let Err(e) = scheduled.await {
+                tracing::warn!("Failed to schedule the ack relay: {e}");
+            }
+        }
+
+        let (tx, packet_tx) = match self.tx_composition {
+            TxComposition::Atomic => (tx, None),
+            TxComposition::Split => match composition::split_cosmos_tx(&tx)
+                .map_err(|e| tonic::Status::from_error(e.into()))?
+            {
+                Some(split) => {
+                    tracing::info!("Split the relay into an update tx and a packet tx.");
+                    (split.update_tx, Some(split.packet_tx))
+                }
+                None => (tx, None),
+            },
+        };
+
+        let composed_txs = 1 + usize::from(packet_tx.is_some());
+        let mut txs = Vec::new();
+        for tx in std::iter::once(tx).chain(packet_tx) {
+            txs.extend(
+                composition::split_cosmos_tx_by_size(&tx, &self.tx_size_limit)
+                    .map_err(|e| tonic::Status::resource_exhausted(e.to_string()))?,
+            );
+        }
+        let size_split = txs.len() > composed_txs;
+        if size_split {
+            tracing::info!(
+                "Split the relay into {} txs within the size limit.",
+                txs.len()
+            );
+        }
+
+        let mut authorized_txs = Vec::with_capacity(txs.len());
+        for tx in txs {
+            authorized_txs.push(self.authorize_tx(tx).await?);
+        }
+        let next_txs = authorized_txs.split_off(1);
+        let tx = authorized_txs.remove(0);
+
+        let fee_quote =
+            hints::quote_fee(self.fee.as_ref(), self.tm_listener.client(), plan.as_ref()).await;
 
         tracing::info!("Relay by tx request completed.");
 
-        Ok(Response::new(api::RelayByTxResponse {
+        let mut response = Response::new(api::RelayByTxResponse {
             tx,
             address: String::new(),
-        }))
+        });
+        if let Some(relay_hints) = relay_hints {
+            hints::insert_relay_hints(&relay_hints, response.metadata_mut());
+        }
+        if let Some(fee_quote) = &fee_quote {
+            hints::insert_fee_quote(fee_quote, response.metadata_mut());
+        }
+        approvals::insert_deferred_sequences(&deferred_seqs, response.metadata_mut());
+        hints::insert_next_txs(&next_txs, size_split, response.metadata_mut());
+
+        escrow::seal_relay(
+            self.artifact_escrow.as_ref(),
+            &relay_id,
+            &inner_req.src_chain,
+            &inner_req.dst_chain,
+            sealed_transfers,
+            response,
+        )
     }
 
     #[tracing::instrument(skip_all)]
@@ -237,8 +736,10 @@ impl RelayerModule for EthToCosmosRelayerModule {
 }
 
 impl EthToCosmosTxBuilder {
+    #[allow(clippy::too_many_arguments)]
     async fn relay_events(
         &self,
+        scope: &RelayScope,
         src_events: Vec<EurekaEventWithHeight>,
         target_events: Vec<EurekaEventWithHeight>,
         src_client_id: String,
@@ -249,6 +750,7 @@ impl EthToCosmosTxBuilder {
         match self {
             Self::Real(tb) => {
                 tb.relay_events(
+                    scope,
                     src_events,
                     target_events,
                     src_client_id,
@@ -260,6 +762,7 @@ impl EthToCosmosTxBuilder {
             }
             Self::Mock(tb) => {
                 tb.relay_events(
+                    scope,
                     src_events,
                     target_events,
                     src_client_id,
@@ -279,6 +782,21 @@ impl EthToCosmosTxBuilder {
         }
     }
 
+    async fn plan_tx(&self, tx: &[u8]) -> anyhow::Result<TxPlan> {
+        match self {
+            Self::Real(tb) => tb.plan_tx(tx).await,
+            Self::Mock(tb) => tb.plan_tx(tx).await,
+        }
+    }
+
+    async fn ensure_client_network(&self, client_id: String) -> anyhow::Result<()> {
+        match self {
+            Self::Real(tb) => tb.ensure_client_network(client_id).await,
+            // The mock light clients do not track a network
+            Self::Mock(_) => Ok(()),
+        }
+    }
+
     const fn ics26_router_address(&self) -> &Address {
         match self {
             Self::Real(tb) => tb.ics26_router.address(),
@@ -3,3 +3,85 @@
 pub mod cosmos_to_cosmos;
 pub mod cosmos_to_eth;
 pub mod eth_to_cosmos;
+pub mod remote;
+
+use alloy::{
+    providers::RootProvider,
+    rpc::client::{ClientBuilder, RpcClient},
+    transports::{http::Http, BoxTransport},
+};
+use anyhow::ensure;
+use ethereum_apis::{
+    circuit_breaker::{endpoint_label, CircuitBreaker, CircuitBreakerConfig, CircuitBreakerLayer},
+    eth_api::pool::EndpointPool,
+};
+use ibc_eureka_utils::http::HttpClientConfig;
+use tower::Layer;
+
+use crate::metrics;
+
+/// Connects to an Ethereum RPC endpoint. HTTP endpoints use a client built from `http_config`,
+/// guarded by a circuit breaker if `circuit_breaker` is set. With fallback endpoints, the requests
+/// are routed through an [`EndpointPool`] of all the endpoints, which must be HTTP endpoints.
+pub(crate) async fn connect_eth_provider(
+    rpc_url: &str,
+    fallback_rpc_urls: &[String],
+    http_config: &HttpClientConfig,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+) -> anyhow::Result<RootProvider> {
+    if !fallback_rpc_urls.is_empty() {
+        let mut pool = EndpointPool::new(
+            endpoint_label(rpc_url),
+            eth_http_transport(rpc_url, http_config, circuit_breaker)?,
+        );
+        for url in fallback_rpc_urls {
+            pool = pool.with_endpoint(
+                endpoint_label(url),
+                eth_http_transport(url, http_config, circuit_breaker)?,
+            );
+        }
+        return Ok(pool.into_provider());
+    }
+
+    if !rpc_url.starts_with("http") {
+        return Ok(RootProvider::builder().connect(rpc_url).await?);
+    }
+
+    let transport = Http::with_client(http_config.build_client(rpc_url)?, rpc_url.parse()?);
+    let Some(circuit_breaker) = circuit_breaker else {
+        return Ok(RootProvider::new(RpcClient::new(transport, false)));
+    };
+
+    let breaker = CircuitBreaker::new(endpoint_label(rpc_url), circuit_breaker)
+        .with_transition_hook(metrics::observe_circuit_state);
+    Ok(RootProvider::new(
+        ClientBuilder::default()
+            .layer(CircuitBreakerLayer::new(breaker))
+            .transport(transport, false),
+    ))
+}
+
+/// Returns the transport of an HTTP endpoint of an [`EndpointPool`], guarded by a circuit breaker
+/// if `circuit_breaker` is set, so that the pool fails over without waiting for the timeout.
+fn eth_http_transport(
+    rpc_url: &str,
+    http_config: &HttpClientConfig,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+) -> anyhow::Result<BoxTransport> {
+    ensure!(
+        rpc_url.starts_with("http"),
+        "only HTTP endpoints can be pooled with fallback endpoints, got {}",
+        endpoint_label(rpc_url)
+    );
+
+    let transport = Http::with_client(http_config.build_client(rpc_url)?, rpc_url.parse()?);
+    let Some(circuit_breaker) = circuit_breaker else {
+        return Ok(BoxTransport::new(transport));
+    };
+
+    let breaker = CircuitBreaker::new(endpoint_label(rpc_url), circuit_breaker)
+        .with_transition_hook(metrics::observe_circuit_state);
+    Ok(BoxTransport::new(
+        CircuitBreakerLayer::new(breaker).layer(transport),
+    ))
+}
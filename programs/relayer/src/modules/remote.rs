@@ -0,0 +1,171 @@
+//! Defines the remote relayer module, which forwards requests to a proving relayer.
+//!
+//! The module lets a broadcasting relayer, which needs no prover, hand off the proving to a
+//! relayer on another machine. Only the relay artifacts signed by one of the trusted signers of
+//! the module are returned to the caller.
+
+use alloy::primitives::Address;
+use prost::Message;
+use tonic::{transport::Channel, Request, Response};
+
+use crate::{
+    api::{
+        self, relayer_service_client::RelayerServiceClient, relayer_service_server::RelayerService,
+    },
+    core::{
+        artifact::{verify_artifact, ArtifactRequest},
+        deadline, hints,
+        modules::RelayerModule,
+    },
+};
+
+/// The `RemoteRelayerModule` struct defines the remote relayer module.
+#[derive(Clone, Copy, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RemoteRelayerModule;
+
+/// The `RemoteRelayerModuleService` forwards the requests to a proving relayer.
+struct RemoteRelayerModuleService {
+    /// The gRPC client of the proving relayer.
+    pub client: RelayerServiceClient<Channel>,
+    /// The signers trusted to sign the relay artifacts.
+    pub trusted_signers: Vec<Address>,
+}
+
+/// The configuration for the remote relayer module.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RemoteConfig {
+    /// The gRPC URL of the proving relayer, e.g. `http://prover:3000`.
+    pub prover_url: String,
+    /// The addresses of the artifact signers of the proving relayer.
+    pub trusted_signers: Vec<Address>,
+}
+
+impl RemoteRelayerModuleService {
+    fn new(config: RemoteConfig) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            !config.trusted_signers.is_empty(),
+            "at least one trusted artifact signer is required"
+        );
+
+        let channel = Channel::from_shared(config.prover_url)?.connect_lazy();
+        Ok(Self {
+            client: RelayerServiceClient::new(channel),
+            trusted_signers: config.trusted_signers,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl RelayerService for RemoteRelayerModuleService {
+    #[tracing::instrument(skip_all)]
+    async fn info(
+        &self,
+        request: Request<api::InfoRequest>,
+    ) -> Result<Response<api::InfoResponse>, tonic::Status> {
+        tracing::info!("Forwarding info request to the proving relayer...");
+        let response = self.client.clone().info(request.into_inner()).await?;
+        Ok(Response::new(response.into_inner()))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn relay_by_tx(
+        &self,
+        request: Request<api::RelayByTxRequest>,
+    ) -> Result<Response<api::RelayByTxResponse>, tonic::Status> {
+        tracing::info!("Forwarding relay by tx request to the proving relayer...");
+
+        let scope = deadline::relay_scope(request.metadata());
+        let inner_req = request.into_inner();
+        let request_bz = inner_req.encode_to_vec();
+        let artifact_request = ArtifactRequest {
+            method: "relay_by_tx",
+            src_chain: &inner_req.src_chain,
+            dst_chain: &inner_req.dst_chain,
+            request: &request_bz,
+        };
+
+        let mut forwarded_req = Request::new(inner_req.clone());
+        if let Some(remaining) = scope.remaining() {
+            forwarded_req.set_timeout(remaining);
+        }
+        let response = self.client.clone().relay_by_tx(forwarded_req).await?;
+        let signer = verify_artifact(
+            &self.trusted_signers,
+            &artifact_request,
+            &response.get_ref().tx,
+            &response.get_ref().address,
+            response.metadata(),
+        )?;
+
+        // The following txs of a split relay are not covered by the artifact signature
+        if [
+            hints::RELAY_PACKET_TX_METADATA_KEY,
+            hints::RELAY_NEXT_TX_METADATA_KEY,
+        ]
+        .into_iter()
+        .any(|key| response.metadata().get_bin(key).is_some())
+        {
+            return Err(tonic::Status::failed_precondition(
+                "the proving relayer split the relay, which cannot be forwarded as a signed artifact",
+            ));
+        }
+
+        tracing::info!(%signer, "Relay by tx request completed by the proving relayer.");
+
+        let (metadata, inner, _) = response.into_parts();
+        let mut response = Response::new(inner);
+        hints::forward_relay_hints(&metadata, response.metadata_mut());
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn create_client(
+        &self,
+        request: Request<api::CreateClientRequest>,
+    ) -> Result<Response<api::CreateClientResponse>, tonic::Status> {
+        tracing::info!("Forwarding create client request to the proving relayer...");
+
+        let inner_req = request.into_inner();
+        let request_bz = inner_req.encode_to_vec();
+        let artifact_request = ArtifactRequest {
+            method: "create_client",
+            src_chain: &inner_req.src_chain,
+            dst_chain: &inner_req.dst_chain,
+            request: &request_bz,
+        };
+
+        let response = self.client.clone().create_client(inner_req.clone()).await?;
+        let signer = verify_artifact(
+            &self.trusted_signers,
+            &artifact_request,
+            &response.get_ref().tx,
+            &response.get_ref().address,
+            response.metadata(),
+        )?;
+
+        tracing::info!(%signer, "Create client request completed by the proving relayer.");
+
+        Ok(Response::new(response.into_inner()))
+    }
+}
+
+#[tonic::async_trait]
+impl RelayerModule for RemoteRelayerModule {
+    fn name(&self) -> &'static str {
+        "remote"
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn create_service(
+        &self,
+        config: serde_json::Value,
+    ) -> anyhow::Result<Box<dyn RelayerService>> {
+        let config = serde_json::from_value::<RemoteConfig>(config)
+            .map_err(|e| anyhow::anyhow!("failed to parse config: {e}"))?;
+
+        tracing::info!(prover_url = %config.prover_url, "Starting remote relayer module.");
+        Ok(Box::new(RemoteRelayerModuleService::new(config)?))
+    }
+}
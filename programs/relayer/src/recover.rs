@@ -0,0 +1,131 @@
+//! Defines the helpers to re-activate an expired or frozen Ethereum light client through governance.
+
+use alloy::hex;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ibc_eureka_relayer_lib::tx_builder::eth_to_cosmos;
+use ibc_eureka_utils::rpc::TendermintRpcExt;
+use serde_json::{json, Value};
+use tendermint_rpc::HttpClient;
+
+use crate::{
+    cli::config::RelayerConfig,
+    core::modules::RelayerModule,
+    modules::{
+        connect_eth_provider,
+        eth_to_cosmos::{EthToCosmosConfig, EthToCosmosRelayerModule},
+    },
+};
+
+/// The type URL of the 08-wasm `MsgMigrateContract` message.
+const MSG_MIGRATE_CONTRACT_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.MsgMigrateContract";
+
+/// The parameters of the governance proposal that re-activates the client.
+pub struct RecoverClientParams<'a> {
+    /// The client identifier of the Ethereum light client.
+    pub client_id: &'a str,
+    /// The hex encoded checksum of the wasm code to migrate the client to.
+    pub checksum_hex: &'a str,
+    /// The governance module address that signs the migration.
+    pub authority: &'a str,
+    /// The deposit of the governance proposal, e.g. `10000000stake`.
+    pub deposit: &'a str,
+}
+
+/// Produces the 08-wasm `MigrateMsg`, re-initializing the Ethereum light client with fresh state
+/// derived from the latest light client bootstrap, and the governance proposal that submits it
+/// with `MsgMigrateContract`.
+///
+/// The first enabled `eth_to_cosmos` module whose destination chain is `chain` is used to connect
+/// to the chains.
+/// # Errors
+/// Returns an error if no suitable module is configured or if the fresh state cannot be derived.
+pub async fn recover_client(
+    config: &RelayerConfig,
+    chain: &str,
+    params: &RecoverClientParams<'_>,
+) -> anyhow::Result<Value> {
+    let module = config
+        .modules
+        .iter()
+        .find(|module| {
+            module.enabled
+                && module.dst_chain == chain
+                && module.name == EthToCosmosRelayerModule.name()
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No enabled `{}` module with destination chain `{chain}`",
+                EthToCosmosRelayerModule.name()
+            )
+        })?;
+    let config: EthToCosmosConfig = serde_json::from_value(module.config.clone())?;
+
+    let provider = connect_eth_provider(
+        &config.eth_rpc_url,
+        &config.eth_rpc_fallback_urls,
+        &config.http_client,
+        config.circuit_breaker,
+    )
+    .await?;
+    let beacon_api_client = config.beacon_api_client()?;
+    let tx_builder = eth_to_cosmos::TxBuilder::new(
+        config.ics26_address,
+        provider,
+        config.eth_beacon_api_url,
+        HttpClient::from_rpc_url_with_config(&config.tm_rpc_url, &config.http_client)?,
+        params.authority.to_string(),
+        eth_to_cosmos::TxBuilderConfig::default(),
+    )
+    .with_beacon_api_client(beacon_api_client);
+
+    let current_client_state = tx_builder
+        .ethereum_client_state(params.client_id.to_string())
+        .await?;
+    let (client_state, consensus_state) = tx_builder
+        .bootstrap_ethereum_states(current_client_state.revision_number)
+        .await?;
+    anyhow::ensure!(
+        client_state.chain_id == current_client_state.chain_id
+            && client_state.ibc_contract_address == current_client_state.ibc_contract_address,
+        "The configured Ethereum chain does not match the one tracked by client `{}`",
+        params.client_id
+    );
+
+    let checksum = BASE64.encode(hex::decode(params.checksum_hex)?);
+    let migrate_msg = json!({
+        "instantiate_msg": {
+            "client_state": BASE64.encode(serde_json::to_vec(&client_state)?),
+            "consensus_state": BASE64.encode(serde_json::to_vec(&consensus_state)?),
+            "checksum": checksum,
+        },
+    });
+
+    let proposal = json!({
+        "messages": [{
+            "@type": MSG_MIGRATE_CONTRACT_TYPE_URL,
+            "signer": params.authority,
+            "client_id": params.client_id,
+            "checksum": checksum,
+            "msg": BASE64.encode(serde_json::to_vec(&migrate_msg)?),
+        }],
+        "metadata": "",
+        "deposit": params.deposit,
+        "title": format!("Re-activate Ethereum light client {}", params.client_id),
+        "summary": format!(
+            "Migrates the Ethereum light client {} to checksum {} and re-initializes it at slot {} (previously at slot {})",
+            params.client_id,
+            params.checksum_hex,
+            client_state.latest_slot,
+            current_client_state.latest_slot,
+        ),
+        "expedited": false,
+    });
+
+    Ok(json!({
+        "client_id": params.client_id,
+        "previous_latest_slot": current_client_state.latest_slot,
+        "latest_slot": client_state.latest_slot,
+        "migrate_msg": migrate_msg,
+        "proposal": proposal,
+    }))
+}
@@ -12,6 +12,8 @@ import { ILightClientMsgs } from "../../contracts/msgs/ILightClientMsgs.sol";
 import { IIBCAppCallbacks } from "../../contracts/msgs/IIBCAppCallbacks.sol";
 
 import { IICS26RouterErrors } from "../../contracts/errors/IICS26RouterErrors.sol";
+import { IICS24HostErrors } from "../../contracts/errors/IICS24HostErrors.sol";
+import { IIBCUUPSUpgradeableErrors } from "../../contracts/errors/IIBCUUPSUpgradeableErrors.sol";
 import { IICS26Router } from "../../contracts/interfaces/IICS26Router.sol";
 
 import { ICS26Router } from "../../contracts/ICS26Router.sol";
@@ -44,6 +46,46 @@ contract ICS26RouterTest is Test {
         ics26Router.grantRole(ics26Router.CLIENT_ID_CUSTOMIZER_ROLE(), address(this));
     }
 
+    function test_getCommitmentStorageSlot() public view {
+        bytes32 expectedSlot =
+            keccak256(abi.encode(uint256(keccak256("ibc.storage.IBCStore")) - 1)) & ~bytes32(uint256(0xff));
+        assertEq(ics26Router.getCommitmentStorageSlot(), expectedSlot);
+    }
+
+    function test_success_commitUpgradedClient() public {
+        bytes32 clientStateHash = sha256("upgradedClient");
+        bytes32 consensusStateHash = sha256("upgradedConsState");
+
+        vm.expectEmit();
+        emit IICS26Router.UpgradedClientCommitted(42, clientStateHash, consensusStateHash);
+        ics26Router.commitUpgradedClient(42, clientStateHash, consensusStateHash);
+
+        // The light clients prove the commitments under the upgrade paths of the slot
+        assertEq(ics26Router.getCommitment(keccak256("upgradedIBCState/42/upgradedClient")), clientStateHash);
+        assertEq(ics26Router.getCommitment(keccak256("upgradedIBCState/42/upgradedConsState")), consensusStateHash);
+    }
+
+    function test_failure_commitUpgradedClient() public {
+        bytes32 clientStateHash = sha256("upgradedClient");
+        bytes32 consensusStateHash = sha256("upgradedConsState");
+
+        address unauthorized = makeAddr("unauthorized");
+        vm.expectRevert(abi.encodeWithSelector(IIBCUUPSUpgradeableErrors.Unauthorized.selector));
+        vm.prank(unauthorized);
+        ics26Router.commitUpgradedClient(42, clientStateHash, consensusStateHash);
+
+        vm.expectRevert(abi.encodeWithSelector(IICS24HostErrors.IBCEmptyUpgradedStateCommitment.selector));
+        ics26Router.commitUpgradedClient(42, clientStateHash, bytes32(0));
+
+        ics26Router.commitUpgradedClient(42, clientStateHash, consensusStateHash);
+        vm.expectRevert(
+            abi.encodeWithSelector(
+                IICS24HostErrors.IBCUpgradedStateAlreadyCommitted.selector, bytes("upgradedIBCState/42/upgradedClient")
+            )
+        );
+        ics26Router.commitUpgradedClient(42, sha256("otherClient"), sha256("otherConsState"));
+    }
+
     function test_success_addIBCAppUsingAddress() public {
         address mockApp = makeAddr("mockApp");
         string memory mockAppStr = Strings.toHexString(mockApp);